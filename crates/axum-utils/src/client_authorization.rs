@@ -191,7 +191,12 @@ impl Credentials {
     }
 }
 
-async fn fetch_jwks(
+/// Resolve the JWKS of a client, fetching it from its `jwks_uri` if needed
+///
+/// # Errors
+///
+/// Returns an error if the JWKS could not be fetched or parsed
+pub async fn fetch_jwks(
     http_client: &reqwest::Client,
     jwks: &JwksOrJwksUri,
 ) -> Result<PublicJsonWebKeySet, BoxError> {
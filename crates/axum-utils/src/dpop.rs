@@ -0,0 +1,782 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Verification of [DPoP] proofs, used to bind access and refresh tokens to a
+//! key held by the client.
+//!
+//! [DPoP]: https://www.rfc-editor.org/rfc/rfc9449.html
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    response::{IntoResponse, Response},
+};
+use base64ct::{Base64UrlUnpadded, Encoding};
+use chrono::{DateTime, Duration, Utc};
+use headers::{Header, HeaderName, HeaderValue};
+use http::{Method, StatusCode, header::AUTHORIZATION, request::Parts};
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, Claim, ClaimError, TimeNotBefore, TimeOptions},
+    jwa::{AsymmetricKeyFromJwkError, AsymmetricVerifyingKey},
+    jwk::Thumbprint,
+    jwt::{Jwt, JwtDecodeError, JwtVerificationError},
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use url::Url;
+
+/// The algorithms we accept for signing DPoP proofs
+pub const DPOP_SIGNING_ALGS: &[JsonWebSignatureAlg] = &[
+    JsonWebSignatureAlg::Rs256,
+    JsonWebSignatureAlg::Rs384,
+    JsonWebSignatureAlg::Rs512,
+    JsonWebSignatureAlg::Ps256,
+    JsonWebSignatureAlg::Ps384,
+    JsonWebSignatureAlg::Ps512,
+    JsonWebSignatureAlg::Es256,
+    JsonWebSignatureAlg::Es384,
+    JsonWebSignatureAlg::Es256K,
+];
+
+/// The `typ` header of DPoP proofs
+const DPOP_TYP: &str = "dpop+jwt";
+
+/// How old a DPoP proof can be. The `jti` of proofs we've seen are kept in
+/// memory for this long, so this should stay short.
+const MAX_PROOF_AGE: Duration = Duration::minutes(5);
+
+/// How far in the future the `iat` of a proof can be, to account for clock
+/// skew
+const FUTURE_LEEWAY: Duration = Duration::minutes(1);
+
+const HTM: Claim<String> = Claim::new("htm");
+const HTU: Claim<String> = Claim::new("htu");
+const ATH: Claim<String> = Claim::new("ath");
+
+static DPOP: HeaderName = HeaderName::from_static("dpop");
+
+#[derive(Debug, Error)]
+pub enum DPoPError {
+    #[error("invalid DPoP proof header")]
+    InvalidHeader,
+
+    #[error("invalid type {0:?} for DPoP proof")]
+    InvalidType(Option<String>),
+
+    #[error("algorithm {0} is not supported for DPoP proofs")]
+    UnsupportedAlgorithm(JsonWebSignatureAlg),
+
+    #[error("DPoP proof has no key")]
+    MissingKey,
+
+    #[error("invalid key in DPoP proof")]
+    InvalidKey(#[from] AsymmetricKeyFromJwkError),
+
+    #[error("invalid DPoP proof signature")]
+    InvalidSignature(#[from] JwtVerificationError),
+
+    #[error(transparent)]
+    InvalidClaim(#[from] ClaimError),
+
+    #[error("DPoP proof is too old")]
+    Expired,
+
+    #[error("DPoP proof was issued for another method")]
+    MethodMismatch,
+
+    #[error("DPoP proof was issued for another URL")]
+    UrlMismatch,
+
+    #[error("DPoP proof was issued for another access token")]
+    AccessTokenMismatch,
+
+    #[error("DPoP proof was already used")]
+    Replayed,
+
+    #[error("a DPoP proof is required")]
+    MissingProof,
+
+    #[error("the token is bound to another key")]
+    KeyMismatch,
+
+    #[error("the token must be used with the DPoP authorization scheme")]
+    SchemeMismatch,
+}
+
+/// Keeps track of the DPoP proofs seen recently, so that they can't be
+/// replayed
+///
+/// Proofs are only remembered until they expire. The cache lives in memory,
+/// so replays across multiple instances of the service are not detected.
+#[derive(Debug, Clone, Default)]
+pub struct DPoPReplayCache {
+    inner: Arc<Mutex<ReplayCacheInner>>,
+}
+
+#[derive(Debug, Default)]
+struct ReplayCacheInner {
+    /// Hashes of the `jti` of the proofs seen, with when they expire
+    seen: HashMap<[u8; 32], DateTime<Utc>>,
+
+    /// When expired entries were last cleaned up
+    last_cleanup: Option<DateTime<Utc>>,
+}
+
+impl DPoPReplayCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a proof, returning `false` if it was already seen
+    ///
+    /// The `jti` is scoped to the key which signed the proof, so that clients
+    /// can't interfere with each other.
+    fn insert(&self, jkt: &str, jti: &str, expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        let key: [u8; 32] = Sha256::new()
+            .chain_update(jkt.as_bytes())
+            .chain_update(b":")
+            .chain_update(jti.as_bytes())
+            .finalize()
+            .into();
+
+        // A poisoned lock only means another thread panicked while holding it,
+        // the map itself is still usable
+        let mut inner = self
+            .inner
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        // Clean up expired entries once in a while, so that the map doesn't
+        // grow forever
+        if inner
+            .last_cleanup
+            .is_none_or(|last_cleanup| last_cleanup + Duration::minutes(1) < now)
+        {
+            inner.seen.retain(|_, expires_at| *expires_at >= now);
+            inner.last_cleanup = Some(now);
+        }
+
+        match inner.seen.get(&key) {
+            Some(seen_until) if *seen_until >= now => false,
+            _ => {
+                inner.seen.insert(key, expires_at);
+                true
+            }
+        }
+    }
+}
+
+/// A DPoP proof, as sent in the `DPoP` header
+#[derive(Debug, Clone)]
+pub struct DPoPProof(Jwt<'static, HashMap<String, Value>>);
+
+impl DPoPProof {
+    /// Verify the proof for a request, and return the JWK thumbprint of the key
+    /// it was signed with
+    ///
+    /// # Parameters
+    ///
+    /// * `method`: The HTTP method of the request
+    /// * `url`: The URL of the request
+    /// * `access_token`: The access token sent with the request, if any
+    /// * `now`: The current time
+    /// * `replay_cache`: The cache of the proofs seen recently
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the proof is invalid, if it was issued for another
+    /// request, or if it was already used
+    pub fn verify(
+        &self,
+        method: &Method,
+        url: &Url,
+        access_token: Option<&str>,
+        now: DateTime<Utc>,
+        replay_cache: &DPoPReplayCache,
+    ) -> Result<String, DPoPError> {
+        let header = self.0.header();
+
+        if header.typ() != Some(DPOP_TYP) {
+            return Err(DPoPError::InvalidType(header.typ().map(ToOwned::to_owned)));
+        }
+
+        let alg = header.alg();
+        if !DPOP_SIGNING_ALGS.contains(alg) {
+            return Err(DPoPError::UnsupportedAlgorithm(alg.clone()));
+        }
+
+        let jwk = header.jwk().ok_or(DPoPError::MissingKey)?;
+        let key = AsymmetricVerifyingKey::from_jwk_and_alg(jwk.params(), alg)?;
+        self.0.verify(&key)?;
+
+        let mut claims = self.0.payload().clone();
+
+        let jti = claims::JTI.extract_required(&mut claims)?;
+
+        let options = TimeOptions::new(now).leeway(FUTURE_LEEWAY);
+        let iat = claims::IAT
+            .extract_required_with_options(&mut claims, TimeNotBefore::from(&options))?;
+        if *iat + MAX_PROOF_AGE < now {
+            return Err(DPoPError::Expired);
+        }
+
+        let htm = HTM.extract_required(&mut claims)?;
+        if htm != method.as_str() {
+            return Err(DPoPError::MethodMismatch);
+        }
+
+        // The URL is compared without the query and fragment parts
+        let htu = HTU.extract_required(&mut claims)?;
+        let mut htu = Url::parse(&htu).map_err(|_| ClaimError::InvalidClaim("htu"))?;
+        htu.set_query(None);
+        htu.set_fragment(None);
+        let mut url = url.clone();
+        url.set_query(None);
+        url.set_fragment(None);
+        if htu != url {
+            return Err(DPoPError::UrlMismatch);
+        }
+
+        if let Some(access_token) = access_token {
+            let ath = ATH.extract_required(&mut claims)?;
+            if ath != access_token_hash(access_token) {
+                return Err(DPoPError::AccessTokenMismatch);
+            }
+        }
+
+        // Only remember proofs which are otherwise valid
+        let jkt = jwk.thumbprint_sha256_base64();
+        if !replay_cache.insert(&jkt, &jti, *iat + MAX_PROOF_AGE, now) {
+            return Err(DPoPError::Replayed);
+        }
+
+        Ok(jkt)
+    }
+}
+
+/// Compute the `ath` claim value for an access token
+fn access_token_hash(access_token: &str) -> String {
+    Base64UrlUnpadded::encode_string(&Sha256::digest(access_token.as_bytes()))
+}
+
+impl Header for DPoPProof {
+    fn name() -> &'static HeaderName {
+        &DPOP
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        Self: Sized,
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        // Only one proof can be sent with a request
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        if values.next().is_some() {
+            return Err(headers::Error::invalid());
+        }
+
+        let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+        let jwt: Result<_, JwtDecodeError> = Jwt::try_from(value.to_owned());
+        jwt.map(Self).map_err(|_| headers::Error::invalid())
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value =
+            HeaderValue::from_str(self.0.as_str()).expect("a JWT is always a valid header value");
+        values.extend(std::iter::once(value));
+    }
+}
+
+impl IntoResponse for DPoPError {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+impl<S> OptionalFromRequestParts<S> for DPoPProof
+where
+    S: Send + Sync,
+{
+    type Rejection = DPoPError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        proof_from_parts(parts)
+    }
+}
+
+/// An access token sent in the `Authorization` header, with either the
+/// `Bearer` or the `DPoP` scheme, along with the DPoP proof sent with the
+/// request if any
+#[derive(Debug)]
+pub struct AccessTokenAuthorization {
+    token: String,
+    dpop_scheme: bool,
+    proof: Option<DPoPProof>,
+    method: Method,
+}
+
+impl AccessTokenAuthorization {
+    /// The access token sent in the request
+    #[must_use]
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Check that the token is presented according to its DPoP binding
+    ///
+    /// Tokens bound to a key must be sent with the `DPoP` scheme along with a
+    /// valid proof signed by this key, and unbound tokens must be sent with
+    /// the `Bearer` scheme.
+    ///
+    /// # Parameters
+    ///
+    /// * `bound_jkt`: The JWK thumbprint the token is bound to, if any
+    /// * `url`: The URL of the protected resource
+    /// * `now`: The current time
+    /// * `replay_cache`: The cache of the DPoP proofs seen recently
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is not presented according to its binding
+    pub fn verify_binding(
+        &self,
+        bound_jkt: Option<&str>,
+        url: &Url,
+        now: DateTime<Utc>,
+        replay_cache: &DPoPReplayCache,
+    ) -> Result<(), DPoPError> {
+        verify_binding(
+            bound_jkt,
+            self.dpop_scheme,
+            self.proof.as_ref(),
+            &self.method,
+            url,
+            &self.token,
+            now,
+            replay_cache,
+        )
+    }
+}
+
+/// Check that an access token is presented according to its DPoP binding
+///
+/// # Errors
+///
+/// Returns an error if the token is not presented according to its binding
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_binding(
+    bound_jkt: Option<&str>,
+    dpop_scheme: bool,
+    proof: Option<&DPoPProof>,
+    method: &Method,
+    url: &Url,
+    access_token: &str,
+    now: DateTime<Utc>,
+    replay_cache: &DPoPReplayCache,
+) -> Result<(), DPoPError> {
+    match (bound_jkt, dpop_scheme) {
+        (Some(bound_jkt), true) => {
+            let proof = proof.ok_or(DPoPError::MissingProof)?;
+            let jkt = proof.verify(method, url, Some(access_token), now, replay_cache)?;
+            if jkt != bound_jkt {
+                return Err(DPoPError::KeyMismatch);
+            }
+            Ok(())
+        }
+        (None, false) => Ok(()),
+        (Some(_), false) | (None, true) => Err(DPoPError::SchemeMismatch),
+    }
+}
+
+/// Parse the `Authorization` header, returning the token and whether it was
+/// sent with the `DPoP` scheme
+pub(crate) fn parse_authorization(value: &HeaderValue) -> Option<(String, bool)> {
+    let value = value.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim_start();
+    if token.is_empty() {
+        return None;
+    }
+
+    if scheme.eq_ignore_ascii_case("Bearer") {
+        Some((token.to_owned(), false))
+    } else if scheme.eq_ignore_ascii_case("DPoP") {
+        Some((token.to_owned(), true))
+    } else {
+        None
+    }
+}
+
+/// Extract the DPoP proof from the request headers
+pub(crate) fn proof_from_parts(parts: &Parts) -> Result<Option<DPoPProof>, DPoPError> {
+    let mut values = parts.headers.get_all(&DPOP).iter().peekable();
+    if values.peek().is_none() {
+        return Ok(None);
+    }
+
+    DPoPProof::decode(&mut values)
+        .map(Some)
+        .map_err(|_| DPoPError::InvalidHeader)
+}
+
+#[derive(Debug, Error)]
+pub enum AccessTokenAuthorizationRejection {
+    #[error("missing authorization header")]
+    Missing,
+
+    #[error("invalid authorization header")]
+    InvalidHeader,
+
+    #[error(transparent)]
+    InvalidProof(#[from] DPoPError),
+}
+
+impl IntoResponse for AccessTokenAuthorizationRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+    }
+}
+
+impl<S> OptionalFromRequestParts<S> for AccessTokenAuthorization
+where
+    S: Send + Sync,
+{
+    type Rejection = AccessTokenAuthorizationRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        let Some(value) = parts.headers.get(AUTHORIZATION) else {
+            return Ok(None);
+        };
+
+        let (token, dpop_scheme) =
+            parse_authorization(value).ok_or(AccessTokenAuthorizationRejection::InvalidHeader)?;
+        let proof = proof_from_parts(parts)?;
+
+        Ok(Some(Self {
+            token,
+            dpop_scheme,
+            proof,
+            method: parts.method.clone(),
+        }))
+    }
+}
+
+impl<S> FromRequestParts<S> for AccessTokenAuthorization
+where
+    S: Send + Sync,
+{
+    type Rejection = AccessTokenAuthorizationRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        <Self as OptionalFromRequestParts<S>>::from_request_parts(parts, state)
+            .await?
+            .ok_or(AccessTokenAuthorizationRejection::Missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::disallowed_methods)]
+
+    use mas_jose::{jwk::PublicJsonWebKey, jwt::JsonWebSignatureHeader};
+    use mas_keystore::PrivateKey;
+    use rand::thread_rng;
+    use serde_json::json;
+
+    use super::*;
+
+    fn proof(key: &PrivateKey, claims: Value) -> DPoPProof {
+        let alg = JsonWebSignatureAlg::Es256;
+        let jwk = PublicJsonWebKey::new(key.into());
+        let header = JsonWebSignatureHeader::new(alg.clone())
+            .with_typ(DPOP_TYP.to_owned())
+            .with_jwk(jwk);
+        let signer = key.signing_key_for_alg(&alg).unwrap();
+        let claims: HashMap<String, Value> = serde_json::from_value(claims).unwrap();
+        let jwt = Jwt::sign_with_rng(&mut thread_rng(), header, claims, &signer).unwrap();
+        DPoPProof(jwt)
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let key = PrivateKey::generate_ec_p256(thread_rng());
+        let jkt = key.thumbprint_sha256_base64();
+        let now = Utc::now();
+        let url = Url::parse("https://example.com/oauth2/token").unwrap();
+        let cache = DPoPReplayCache::new();
+
+        let valid = proof(
+            &key,
+            json!({
+                "jti": "abcdef",
+                "htm": "POST",
+                "htu": "https://example.com/oauth2/token",
+                "iat": now.timestamp(),
+            }),
+        );
+        assert_eq!(
+            valid
+                .verify(&Method::POST, &url, None, now, &cache)
+                .unwrap(),
+            jkt
+        );
+
+        // Wrong method or URL
+        assert!(matches!(
+            valid.verify(&Method::GET, &url, None, now, &cache),
+            Err(DPoPError::MethodMismatch)
+        ));
+        let other = Url::parse("https://example.com/oauth2/userinfo").unwrap();
+        assert!(matches!(
+            valid.verify(&Method::POST, &other, None, now, &cache),
+            Err(DPoPError::UrlMismatch)
+        ));
+
+        // Too old
+        assert!(matches!(
+            valid.verify(
+                &Method::POST,
+                &url,
+                None,
+                now + Duration::minutes(10),
+                &cache
+            ),
+            Err(DPoPError::Expired)
+        ));
+
+        // Missing the access token hash
+        assert!(matches!(
+            valid.verify(&Method::POST, &url, Some("token"), now, &cache),
+            Err(DPoPError::InvalidClaim(_))
+        ));
+
+        let with_ath = proof(
+            &key,
+            json!({
+                "jti": "ghijkl",
+                "htm": "POST",
+                "htu": "https://example.com/oauth2/token",
+                "iat": now.timestamp(),
+                "ath": access_token_hash("token"),
+            }),
+        );
+        assert_eq!(
+            with_ath
+                .verify(&Method::POST, &url, Some("token"), now, &cache)
+                .unwrap(),
+            jkt
+        );
+        assert!(matches!(
+            with_ath.verify(&Method::POST, &url, Some("other"), now, &cache),
+            Err(DPoPError::AccessTokenMismatch)
+        ));
+
+        // Check the binding rules. The proof was already used above, so start
+        // with a fresh cache.
+        let cache = DPoPReplayCache::new();
+        assert!(
+            verify_binding(
+                Some(&jkt),
+                true,
+                Some(&with_ath),
+                &Method::POST,
+                &url,
+                "token",
+                now,
+                &cache
+            )
+            .is_ok()
+        );
+        assert!(matches!(
+            verify_binding(
+                Some(&jkt),
+                false,
+                None,
+                &Method::POST,
+                &url,
+                "token",
+                now,
+                &cache
+            ),
+            Err(DPoPError::SchemeMismatch)
+        ));
+        assert!(matches!(
+            verify_binding(
+                Some(&jkt),
+                true,
+                None,
+                &Method::POST,
+                &url,
+                "token",
+                now,
+                &cache
+            ),
+            Err(DPoPError::MissingProof)
+        ));
+        let cache = DPoPReplayCache::new();
+        assert!(matches!(
+            verify_binding(
+                Some("other"),
+                true,
+                Some(&with_ath),
+                &Method::POST,
+                &url,
+                "token",
+                now,
+                &cache
+            ),
+            Err(DPoPError::KeyMismatch)
+        ));
+        assert!(
+            verify_binding(None, false, None, &Method::POST, &url, "token", now, &cache).is_ok()
+        );
+        assert!(matches!(
+            verify_binding(
+                None,
+                true,
+                Some(&with_ath),
+                &Method::POST,
+                &url,
+                "token",
+                now,
+                &cache
+            ),
+            Err(DPoPError::SchemeMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_replay() {
+        let key = PrivateKey::generate_ec_p256(thread_rng());
+        let now = Utc::now();
+        let url = Url::parse("https://example.com/oauth2/token").unwrap();
+        let cache = DPoPReplayCache::new();
+
+        let claims = json!({
+            "jti": "abcdef",
+            "htm": "POST",
+            "htu": "https://example.com/oauth2/token",
+            "iat": now.timestamp(),
+        });
+        let first = proof(&key, claims.clone());
+        assert!(first.verify(&Method::POST, &url, None, now, &cache).is_ok());
+
+        // The same proof can't be used twice
+        assert!(matches!(
+            first.verify(&Method::POST, &url, None, now, &cache),
+            Err(DPoPError::Replayed)
+        ));
+
+        // Neither can another proof with the same jti and key
+        let second = proof(&key, claims.clone());
+        assert!(matches!(
+            second.verify(
+                &Method::POST,
+                &url,
+                None,
+                now + Duration::minutes(1),
+                &cache
+            ),
+            Err(DPoPError::Replayed)
+        ));
+
+        // Another key can use the same jti
+        let other_key = PrivateKey::generate_ec_p256(thread_rng());
+        let other = proof(&other_key, claims);
+        assert!(other.verify(&Method::POST, &url, None, now, &cache).is_ok());
+
+        // So can a new proof with another jti
+        let fresh = proof(
+            &key,
+            json!({
+                "jti": "ghijkl",
+                "htm": "POST",
+                "htu": "https://example.com/oauth2/token",
+                "iat": now.timestamp(),
+            }),
+        );
+        assert!(fresh.verify(&Method::POST, &url, None, now, &cache).is_ok());
+
+        // A proof rejected for another reason doesn't burn its jti
+        let unused = proof(
+            &key,
+            json!({
+                "jti": "mnopqr",
+                "htm": "POST",
+                "htu": "https://example.com/oauth2/token",
+                "iat": now.timestamp(),
+            }),
+        );
+        assert!(matches!(
+            unused.verify(&Method::GET, &url, None, now, &cache),
+            Err(DPoPError::MethodMismatch)
+        ));
+        assert!(
+            unused
+                .verify(&Method::POST, &url, None, now, &cache)
+                .is_ok()
+        );
+
+        // Replays through the binding check are rejected as well
+        let with_ath = proof(
+            &key,
+            json!({
+                "jti": "stuvwx",
+                "htm": "GET",
+                "htu": "https://example.com/oauth2/token",
+                "iat": now.timestamp(),
+                "ath": access_token_hash("token"),
+            }),
+        );
+        let jkt = key.thumbprint_sha256_base64();
+        let binding = |now| {
+            verify_binding(
+                Some(&jkt),
+                true,
+                Some(&with_ath),
+                &Method::GET,
+                &url,
+                "token",
+                now,
+                &cache,
+            )
+        };
+        assert!(binding(now).is_ok());
+        assert!(matches!(binding(now), Err(DPoPError::Replayed)));
+    }
+
+    #[test]
+    fn test_parse_authorization() {
+        assert_eq!(
+            parse_authorization(&HeaderValue::from_static("Bearer abc")),
+            Some(("abc".to_owned(), false))
+        );
+        assert_eq!(
+            parse_authorization(&HeaderValue::from_static("DPoP abc")),
+            Some(("abc".to_owned(), true))
+        );
+        assert_eq!(
+            parse_authorization(&HeaderValue::from_static("Basic abc")),
+            None
+        );
+        assert_eq!(
+            parse_authorization(&HeaderValue::from_static("DPoP ")),
+            None
+        );
+    }
+}
@@ -8,14 +8,16 @@ use std::{collections::HashMap, error::Error};
 
 use axum::{
     extract::{
-        Form, FromRequest, FromRequestParts,
+        Form, FromRequest,
         rejection::{FailedToDeserializeForm, FormRejection},
     },
     response::{IntoResponse, Response},
 };
-use axum_extra::typed_header::{TypedHeader, TypedHeaderRejectionReason};
-use headers::{Authorization, Header, HeaderMapExt, HeaderName, authorization::Bearer};
-use http::{HeaderMap, HeaderValue, Request, StatusCode, header::WWW_AUTHENTICATE};
+use headers::{Header, HeaderMapExt, HeaderName};
+use http::{
+    HeaderMap, HeaderValue, Method, Request, StatusCode,
+    header::{AUTHORIZATION, WWW_AUTHENTICATE},
+};
 use mas_data_model::{Clock, Session};
 use mas_storage::{
     RepositoryAccess,
@@ -23,6 +25,9 @@ use mas_storage::{
 };
 use serde::{Deserialize, de::DeserializeOwned};
 use thiserror::Error;
+use url::Url;
+
+use crate::dpop::{self, DPoPError, DPoPProof, DPoPReplayCache};
 
 #[derive(Debug, Deserialize)]
 struct AuthorizedForm<F> {
@@ -36,23 +41,43 @@ struct AuthorizedForm<F> {
 #[derive(Debug)]
 enum AccessToken {
     Form(String),
-    Header(String),
+    Header {
+        token: String,
+        /// Whether the token was sent with the `DPoP` authorization scheme
+        dpop_scheme: bool,
+    },
     None,
 }
 
 impl AccessToken {
+    fn token(&self) -> Option<&str> {
+        match self {
+            AccessToken::Form(t) | AccessToken::Header { token: t, .. } => Some(t),
+            AccessToken::None => None,
+        }
+    }
+
+    fn is_dpop_scheme(&self) -> bool {
+        matches!(
+            self,
+            AccessToken::Header {
+                dpop_scheme: true,
+                ..
+            }
+        )
+    }
+
     async fn fetch<E>(
         &self,
         repo: &mut impl RepositoryAccess<Error = E>,
     ) -> Result<(mas_data_model::AccessToken, Session), AuthorizationVerificationError<E>> {
-        let token = match self {
-            AccessToken::Form(t) | AccessToken::Header(t) => t,
-            AccessToken::None => return Err(AuthorizationVerificationError::MissingToken),
-        };
+        let token = self
+            .token()
+            .ok_or(AuthorizationVerificationError::MissingToken)?;
 
         let token = repo
             .oauth2_access_token()
-            .find_by_token(token.as_str())
+            .find_by_token(token)
             .await?
             .ok_or(AuthorizationVerificationError::InvalidToken)?;
 
@@ -69,33 +94,64 @@ impl AccessToken {
 #[derive(Debug)]
 pub struct UserAuthorization<F = ()> {
     access_token: AccessToken,
+    dpop_proof: Option<DPoPProof>,
+    method: Method,
     form: Option<F>,
 }
 
 impl<F: Send> UserAuthorization<F> {
+    /// Check that the access token is presented according to its DPoP binding
+    fn verify_binding<E>(
+        &self,
+        token: &mas_data_model::AccessToken,
+        url: &Url,
+        clock: &impl Clock,
+        replay_cache: &DPoPReplayCache,
+    ) -> Result<(), AuthorizationVerificationError<E>> {
+        let Some(access_token) = self.access_token.token() else {
+            return Err(AuthorizationVerificationError::MissingToken);
+        };
+
+        dpop::verify_binding(
+            token.dpop_jkt.as_deref(),
+            self.access_token.is_dpop_scheme(),
+            self.dpop_proof.as_ref(),
+            &self.method,
+            url,
+            access_token,
+            clock.now(),
+            replay_cache,
+        )
+        .map_err(AuthorizationVerificationError::InvalidDPoPProof)
+    }
+
     // TODO: take scopes to validate as parameter
     /// Verify a user authorization and return the session and the protected
     /// form value
     ///
     /// # Errors
     ///
-    /// Returns an error if the token is invalid, if the user session ended or
-    /// if the form is missing
+    /// Returns an error if the token is invalid, if the user session ended, if
+    /// the DPoP proof is invalid or if the form is missing
     pub async fn protected_form<E>(
         self,
         repo: &mut impl RepositoryAccess<Error = E>,
         clock: &impl Clock,
+        url: &Url,
+        replay_cache: &DPoPReplayCache,
     ) -> Result<(Session, F), AuthorizationVerificationError<E>> {
-        let Some(form) = self.form else {
-            return Err(AuthorizationVerificationError::MissingForm);
-        };
-
         let (token, session) = self.access_token.fetch(repo).await?;
 
         if !token.is_valid(clock.now()) || !session.is_valid() {
             return Err(AuthorizationVerificationError::InvalidToken);
         }
 
+        self.verify_binding(&token, url, clock, replay_cache)?;
+
+        let Some(form) = self.form else {
+            return Err(AuthorizationVerificationError::MissingForm);
+        };
+
         Ok((session, form))
     }
 
@@ -104,11 +160,14 @@ impl<F: Send> UserAuthorization<F> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the token is invalid or if the user session ended
+    /// Returns an error if the token is invalid, if the user session ended or
+    /// if the DPoP proof is invalid
     pub async fn protected<E>(
         self,
         repo: &mut impl RepositoryAccess<Error = E>,
         clock: &impl Clock,
+        url: &Url,
+        replay_cache: &DPoPReplayCache,
     ) -> Result<Session, AuthorizationVerificationError<E>> {
         let (token, session) = self.access_token.fetch(repo).await?;
 
@@ -116,6 +175,8 @@ impl<F: Send> UserAuthorization<F> {
             return Err(AuthorizationVerificationError::InvalidToken);
         }
 
+        self.verify_binding(&token, url, clock, replay_cache)?;
+
         if !token.is_used() {
             // Mark the token as used
             repo.oauth2_access_token().mark_used(clock, token).await?;
@@ -127,6 +188,7 @@ impl<F: Send> UserAuthorization<F> {
 
 pub enum UserAuthorizationError {
     InvalidHeader,
+    InvalidDPoPProof,
     TokenInFormAndHeader,
     BadForm(FailedToDeserializeForm),
     Internal(Box<dyn Error>),
@@ -143,6 +205,9 @@ pub enum AuthorizationVerificationError<E> {
     #[error("missing form")]
     MissingForm,
 
+    #[error("invalid DPoP proof")]
+    InvalidDPoPProof(#[source] DPoPError),
+
     #[error(transparent)]
     Internal(#[from] E),
 }
@@ -237,7 +302,10 @@ impl Header for WwwAuthenticate {
 impl IntoResponse for UserAuthorizationError {
     fn into_response(self) -> Response {
         match self {
-            Self::BadForm(_) | Self::InvalidHeader | Self::TokenInFormAndHeader => {
+            Self::BadForm(_)
+            | Self::InvalidHeader
+            | Self::InvalidDPoPProof
+            | Self::TokenInFormAndHeader => {
                 let mut headers = HeaderMap::new();
 
                 headers.typed_insert(WwwAuthenticate::Bearer {
@@ -268,7 +336,7 @@ where
                 });
                 (StatusCode::BAD_REQUEST, headers).into_response()
             }
-            Self::InvalidToken => {
+            Self::InvalidToken | Self::InvalidDPoPProof(_) => {
                 let mut headers = HeaderMap::new();
 
                 headers.typed_insert(WwwAuthenticate::Bearer {
@@ -294,21 +362,22 @@ where
         req: Request<axum::body::Body>,
         state: &S,
     ) -> Result<Self, Self::Rejection> {
-        let (mut parts, body) = req.into_parts();
-        let header =
-            TypedHeader::<Authorization<Bearer>>::from_request_parts(&mut parts, state).await;
-
-        // Take the Authorization header
-        let token_from_header = match header {
-            Ok(header) => Some(header.token().to_owned()),
-            Err(err) => match err.reason() {
-                // If it's missing it is fine
-                TypedHeaderRejectionReason::Missing => None,
-                // If the header could not be parsed, return the error
-                _ => return Err(UserAuthorizationError::InvalidHeader),
-            },
+        let (parts, body) = req.into_parts();
+
+        // Take the Authorization header, which can use either the `Bearer` or the
+        // `DPoP` scheme
+        let token_from_header = match parts.headers.get(AUTHORIZATION) {
+            Some(value) => Some(
+                dpop::parse_authorization(value).ok_or(UserAuthorizationError::InvalidHeader)?,
+            ),
+            // If it's missing it is fine
+            None => None,
         };
 
+        let dpop_proof =
+            dpop::proof_from_parts(&parts).map_err(|_| UserAuthorizationError::InvalidDPoPProof)?;
+        let method = parts.method.clone();
+
         let req = Request::from_parts(parts, body);
 
         // Take the form value
@@ -328,11 +397,16 @@ where
         let access_token = match (token_from_header, token_from_form) {
             // Ensure the token should not be in both the form and the access token
             (Some(_), Some(_)) => return Err(UserAuthorizationError::TokenInFormAndHeader),
-            (Some(t), None) => AccessToken::Header(t),
+            (Some((token, dpop_scheme)), None) => AccessToken::Header { token, dpop_scheme },
             (None, Some(t)) => AccessToken::Form(t),
             (None, None) => AccessToken::None,
         };
 
-        Ok(UserAuthorization { access_token, form })
+        Ok(UserAuthorization {
+            access_token,
+            dpop_proof,
+            method,
+            form,
+        })
     }
 }
@@ -11,8 +11,8 @@ use ipnetwork::IpNetwork;
 use mas_context::LogContext;
 use mas_data_model::{AppVersion, BoxClock, BoxRng, SiteConfig, SystemClock};
 use mas_handlers::{
-    ActivityTracker, BoundActivityTracker, CookieManager, ErrorWrapper, GraphQLSchema, Limiter,
-    MetadataCache, RequesterFingerprint, passwords::PasswordManager,
+    ActivityTracker, BoundActivityTracker, CookieManager, DPoPReplayCache, ErrorWrapper,
+    GraphQLSchema, Limiter, MetadataCache, RequesterFingerprint, passwords::PasswordManager,
 };
 use mas_i18n::Translator;
 use mas_keystore::{Encrypter, Keystore};
@@ -47,6 +47,7 @@ pub struct AppState {
     pub activity_tracker: ActivityTracker,
     pub trusted_proxies: Vec<IpNetwork>,
     pub limiter: Limiter,
+    pub dpop_replay_cache: DPoPReplayCache,
 }
 
 impl AppState {
@@ -202,6 +203,12 @@ impl FromRef<AppState> for Limiter {
     }
 }
 
+impl FromRef<AppState> for DPoPReplayCache {
+    fn from_ref(input: &AppState) -> Self {
+        input.dpop_replay_cache.clone()
+    }
+}
+
 impl FromRef<AppState> for Arc<PolicyFactory> {
     fn from_ref(input: &AppState) -> Self {
         input.policy_factory.clone()
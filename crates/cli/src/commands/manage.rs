@@ -7,6 +7,7 @@
 use std::{collections::BTreeMap, process::ExitCode};
 
 use anyhow::Context;
+use camino::Utf8PathBuf;
 use chrono::Duration;
 use clap::{ArgAction, CommandFactory, Parser};
 use console::{Alignment, Style, Term, pad_str, style};
@@ -23,12 +24,12 @@ use mas_storage::{
     compat::{CompatAccessTokenRepository, CompatSessionFilter, CompatSessionRepository},
     oauth2::OAuth2SessionFilter,
     queue::{
-        DeactivateUserJob, ProvisionUserJob, QueueJobRepositoryExt as _, ReactivateUserJob,
-        SyncDevicesJob,
+        DeactivateUserJob, DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        ReactivateUserJob, SyncDevicesJob,
     },
     user::{
         BrowserSessionFilter, UserEmailRepository, UserFilter, UserPasswordRepository,
-        UserRepository,
+        UserRepository, export_user,
     },
 };
 use mas_storage_pg::{DatabaseError, PgRepository};
@@ -37,6 +38,7 @@ use rand::{
     distributions::{Alphanumeric, DistString as _},
 };
 use sqlx::{Acquire, types::Uuid};
+use tokio::io::AsyncWriteExt as _;
 use tracing::{error, info, info_span, warn};
 use zeroize::Zeroizing;
 
@@ -147,6 +149,18 @@ enum Subcommand {
         dry_run: bool,
     },
 
+    /// Export everything stored about a user as a JSON archive
+    ExportUser {
+        /// User to export
+        username: String,
+
+        /// The path to the file to write the archive to
+        ///
+        /// If not specified, the archive will be written to stdout
+        #[clap(short, long)]
+        output: Option<Utf8PathBuf>,
+    },
+
     /// Lock a user
     LockUser {
         /// User to lock
@@ -614,6 +628,36 @@ impl Options {
                 Ok(ExitCode::SUCCESS)
             }
 
+            SC::ExportUser { username, output } => {
+                let _span =
+                    info_span!("cli.manage.export_user", user.username = username).entered();
+                let database_config = DatabaseConfig::extract_or_default(figment)
+                    .map_err(anyhow::Error::from_boxed)?;
+                let mut conn = database_connection_from_config(&database_config).await?;
+                let txn = conn.begin().await?;
+                let mut repo = PgRepository::from_conn(txn);
+
+                let user = repo
+                    .user()
+                    .find_by_username(&username)
+                    .await?
+                    .context("User not found")?;
+
+                let archive = export_user(&mut repo, &clock, &user).await?;
+                let archive = serde_json::to_string_pretty(&archive)?;
+
+                if let Some(output) = output {
+                    info!("Writing archive to {output:?}");
+                    let mut file = tokio::fs::File::create(output).await?;
+                    file.write_all(archive.as_bytes()).await?;
+                } else {
+                    info!("Writing archive to standard output");
+                    tokio::io::stdout().write_all(archive.as_bytes()).await?;
+                }
+
+                Ok(ExitCode::SUCCESS)
+            }
+
             SC::LockUser {
                 username,
                 deactivate,
@@ -1150,6 +1194,10 @@ impl UserCreationRequest<'_> {
             .schedule_job(rng, clock, provision_job)
             .await?;
 
+        repo.queue_job()
+            .schedule_job(rng, clock, DispatchWebhookEventJob::user_registered(&user))
+            .await?;
+
         Ok(user)
     }
 }
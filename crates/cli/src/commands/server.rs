@@ -11,11 +11,12 @@ use clap::Parser;
 use figment::Figment;
 use itertools::Itertools;
 use mas_config::{
-    AppConfig, ClientsConfig, ConfigurationSection, ConfigurationSectionExt, UpstreamOAuth2Config,
+    AppConfig, ClientsConfig, ConfigurationSection, ConfigurationSectionExt, RateLimitingBackend,
+    UpstreamOAuth2Config,
 };
 use mas_context::LogContext;
 use mas_data_model::SystemClock;
-use mas_handlers::{ActivityTracker, CookieManager, Limiter, MetadataCache};
+use mas_handlers::{ActivityTracker, CookieManager, DPoPReplayCache, Limiter, MetadataCache};
 use mas_listener::server::Server;
 use mas_router::UrlBuilder;
 use mas_storage_pg::{MIGRATOR, PgRepositoryFactory};
@@ -157,6 +158,7 @@ impl Options {
             &config.passwords,
             &config.account,
             &config.captcha,
+            &config.webhooks,
         )?;
 
         // Load and compile the templates
@@ -187,6 +189,8 @@ impl Options {
                 homeserver_connection.clone(),
                 url_builder.clone(),
                 &site_config,
+                key_store.clone(),
+                http_client.clone(),
                 shutdown.soft_shutdown_token(),
                 shutdown.task_tracker(),
             )
@@ -216,8 +220,14 @@ impl Options {
         // Build a rate limiter.
         // This should not raise an error here as the config should already have been
         // validated.
-        let limiter = Limiter::new(&config.rate_limiting)
-            .context("rate-limiting configuration is not valid")?;
+        let limiter = match config.rate_limiting.backend {
+            RateLimitingBackend::Memory => Limiter::new(&config.rate_limiting),
+            RateLimitingBackend::Postgres => Limiter::new_shared(
+                &config.rate_limiting,
+                PgRepositoryFactory::new(pool.clone()).boxed(),
+            ),
+        }
+        .context("rate-limiting configuration is not valid")?;
 
         // Explicitly the config to properly zeroize secret keys
         drop(config);
@@ -232,6 +242,7 @@ impl Options {
             password_manager.clone(),
             url_builder.clone(),
             limiter.clone(),
+            encrypter.clone(),
         );
 
         let state = {
@@ -252,6 +263,7 @@ impl Options {
                 activity_tracker,
                 trusted_proxies,
                 limiter,
+                dpop_replay_cache: DPoPReplayCache::new(),
             };
             s.init_metrics();
             s.init_metadata_cache();
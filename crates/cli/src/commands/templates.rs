@@ -12,7 +12,7 @@ use clap::Parser;
 use figment::Figment;
 use mas_config::{
     AccountConfig, BrandingConfig, CaptchaConfig, ConfigurationSection, ConfigurationSectionExt,
-    ExperimentalConfig, MatrixConfig, PasswordsConfig, TemplatesConfig,
+    ExperimentalConfig, MatrixConfig, PasswordsConfig, TemplatesConfig, WebhooksConfig,
 };
 use mas_data_model::{Clock, SystemClock};
 use rand::SeedableRng;
@@ -58,6 +58,8 @@ impl Options {
                     .map_err(anyhow::Error::from_boxed)?;
                 let captcha_config = CaptchaConfig::extract_or_default(figment)
                     .map_err(anyhow::Error::from_boxed)?;
+                let webhooks_config = WebhooksConfig::extract_or_default(figment)
+                    .map_err(anyhow::Error::from_boxed)?;
 
                 let clock = SystemClock::default();
                 // XXX: we should disallow SeedableRng::from_entropy
@@ -71,6 +73,7 @@ impl Options {
                     &password_config,
                     &account_config,
                     &captcha_config,
+                    &webhooks_config,
                 )?;
                 let templates = templates_from_config(
                     &template_config,
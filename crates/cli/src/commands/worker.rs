@@ -6,6 +6,7 @@
 
 use std::{process::ExitCode, time::Duration};
 
+use anyhow::Context;
 use clap::Parser;
 use figment::Figment;
 use mas_config::{AppConfig, ConfigurationSection};
@@ -49,6 +50,7 @@ impl Options {
             &config.passwords,
             &config.account,
             &config.captcha,
+            &config.webhooks,
         )?;
 
         // Load and compile the templates
@@ -64,8 +66,15 @@ impl Options {
         let mailer = mailer_from_config(&config.email, &templates)?;
         test_mailer_in_background(&mailer, Duration::from_secs(30));
 
+        // Initialize the key store, used to sign the back-channel logout tokens
+        let key_store = config
+            .secrets
+            .key_store()
+            .await
+            .context("could not import keys from config")?;
+
         let http_client = mas_http::reqwest_client();
-        let conn = homeserver_connection_from_config(&config.matrix, http_client).await?;
+        let conn = homeserver_connection_from_config(&config.matrix, http_client.clone()).await?;
 
         drop(config);
 
@@ -77,6 +86,8 @@ impl Options {
             conn,
             url_builder,
             &site_config,
+            key_store,
+            http_client,
             shutdown.soft_shutdown_token(),
             shutdown.task_tracker(),
         )
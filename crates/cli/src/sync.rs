@@ -10,73 +10,13 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use mas_config::{ClientsConfig, UpstreamOAuth2Config};
 use mas_data_model::Clock;
+use mas_handlers::upstream_oauth2::config::provider_params_from_config;
 use mas_keystore::Encrypter;
-use mas_storage::{
-    Pagination, RepositoryAccess,
-    upstream_oauth2::{UpstreamOAuthProviderFilter, UpstreamOAuthProviderParams},
-};
+use mas_storage::{Pagination, RepositoryAccess, upstream_oauth2::UpstreamOAuthProviderFilter};
 use mas_storage_pg::PgRepository;
 use sqlx::{Connection, PgConnection, postgres::PgAdvisoryLock};
 use tracing::{error, info, info_span, warn};
 
-fn map_import_action(
-    config: mas_config::UpstreamOAuth2ImportAction,
-) -> mas_data_model::UpstreamOAuthProviderImportAction {
-    match config {
-        mas_config::UpstreamOAuth2ImportAction::Ignore => {
-            mas_data_model::UpstreamOAuthProviderImportAction::Ignore
-        }
-        mas_config::UpstreamOAuth2ImportAction::Suggest => {
-            mas_data_model::UpstreamOAuthProviderImportAction::Suggest
-        }
-        mas_config::UpstreamOAuth2ImportAction::Force => {
-            mas_data_model::UpstreamOAuthProviderImportAction::Force
-        }
-        mas_config::UpstreamOAuth2ImportAction::Require => {
-            mas_data_model::UpstreamOAuthProviderImportAction::Require
-        }
-    }
-}
-
-fn map_import_on_conflict(
-    config: mas_config::UpstreamOAuth2OnConflict,
-) -> mas_data_model::UpstreamOAuthProviderOnConflict {
-    match config {
-        mas_config::UpstreamOAuth2OnConflict::Add => {
-            mas_data_model::UpstreamOAuthProviderOnConflict::Add
-        }
-        mas_config::UpstreamOAuth2OnConflict::Fail => {
-            mas_data_model::UpstreamOAuthProviderOnConflict::Fail
-        }
-    }
-}
-
-fn map_claims_imports(
-    config: &mas_config::UpstreamOAuth2ClaimsImports,
-) -> mas_data_model::UpstreamOAuthProviderClaimsImports {
-    mas_data_model::UpstreamOAuthProviderClaimsImports {
-        subject: mas_data_model::UpstreamOAuthProviderSubjectPreference {
-            template: config.subject.template.clone(),
-        },
-        localpart: mas_data_model::UpstreamOAuthProviderLocalpartPreference {
-            action: map_import_action(config.localpart.action),
-            template: config.localpart.template.clone(),
-            on_conflict: map_import_on_conflict(config.localpart.on_conflict),
-        },
-        displayname: mas_data_model::UpstreamOAuthProviderImportPreference {
-            action: map_import_action(config.displayname.action),
-            template: config.displayname.template.clone(),
-        },
-        email: mas_data_model::UpstreamOAuthProviderImportPreference {
-            action: map_import_action(config.email.action),
-            template: config.email.template.clone(),
-        },
-        account_name: mas_data_model::UpstreamOAuthProviderSubjectPreference {
-            template: config.account_name.template.clone(),
-        },
-    }
-}
-
 #[tracing::instrument(name = "config.sync", skip_all)]
 pub async fn config_sync(
     upstream_oauth2_config: UpstreamOAuth2Config,
@@ -113,11 +53,12 @@ pub async fn config_sync(
             .map(|p| p.id)
             .collect::<BTreeSet<_>>();
 
-        // Let's assume we have less than 1000 providers
+        // Let's assume we have less than 1000 providers. Providers created through
+        // the admin API are not managed by the configuration file.
         let page = repo
             .upstream_oauth_provider()
             .list(
-                UpstreamOAuthProviderFilter::default(),
+                UpstreamOAuthProviderFilter::new().static_only(),
                 Pagination::first(1000),
             )
             .await?;
@@ -222,121 +163,25 @@ pub async fn config_sync(
                     None
                 };
 
-            let discovery_mode = match provider.discovery_mode {
-                mas_config::UpstreamOAuth2DiscoveryMode::Oidc => {
-                    mas_data_model::UpstreamOAuthProviderDiscoveryMode::Oidc
-                }
-                mas_config::UpstreamOAuth2DiscoveryMode::Insecure => {
-                    mas_data_model::UpstreamOAuthProviderDiscoveryMode::Insecure
-                }
-                mas_config::UpstreamOAuth2DiscoveryMode::Disabled => {
-                    mas_data_model::UpstreamOAuthProviderDiscoveryMode::Disabled
-                }
-            };
+            let provider_id = provider.id;
+            let params = provider_params_from_config(provider, encrypted_client_secret, ui_order)?;
 
-            let token_endpoint_auth_method = match provider.token_endpoint_auth_method {
-                mas_config::UpstreamOAuth2TokenAuthMethod::None => {
-                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::None
-                }
-                mas_config::UpstreamOAuth2TokenAuthMethod::ClientSecretBasic => {
-                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::ClientSecretBasic
-                }
-                mas_config::UpstreamOAuth2TokenAuthMethod::ClientSecretPost => {
-                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::ClientSecretPost
-                }
-                mas_config::UpstreamOAuth2TokenAuthMethod::ClientSecretJwt => {
-                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::ClientSecretJwt
-                }
-                mas_config::UpstreamOAuth2TokenAuthMethod::PrivateKeyJwt => {
-                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::PrivateKeyJwt
-                }
-                mas_config::UpstreamOAuth2TokenAuthMethod::SignInWithApple => {
-                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::SignInWithApple
-                }
-            };
-
-            let response_mode = provider
-                .response_mode
-                .map(|response_mode| match response_mode {
-                    mas_config::UpstreamOAuth2ResponseMode::Query => {
-                        mas_data_model::UpstreamOAuthProviderResponseMode::Query
-                    }
-                    mas_config::UpstreamOAuth2ResponseMode::FormPost => {
-                        mas_data_model::UpstreamOAuthProviderResponseMode::FormPost
-                    }
-                });
-
-            if discovery_mode.is_disabled() {
-                if provider.authorization_endpoint.is_none() {
-                    error!(provider.id = %provider.id, "Provider has discovery disabled but no authorization endpoint set");
+            if params.discovery_mode.is_disabled() {
+                if params.authorization_endpoint_override.is_none() {
+                    error!(provider.id = %provider_id, "Provider has discovery disabled but no authorization endpoint set");
                 }
 
-                if provider.token_endpoint.is_none() {
-                    error!(provider.id = %provider.id, "Provider has discovery disabled but no token endpoint set");
+                if params.token_endpoint_override.is_none() {
+                    error!(provider.id = %provider_id, "Provider has discovery disabled but no token endpoint set");
                 }
 
-                if provider.jwks_uri.is_none() {
-                    warn!(provider.id = %provider.id, "Provider has discovery disabled but no JWKS URI set");
+                if params.jwks_uri_override.is_none() {
+                    warn!(provider.id = %provider_id, "Provider has discovery disabled but no JWKS URI set");
                 }
             }
 
-            let pkce_mode = match provider.pkce_method {
-                mas_config::UpstreamOAuth2PkceMethod::Auto => {
-                    mas_data_model::UpstreamOAuthProviderPkceMode::Auto
-                }
-                mas_config::UpstreamOAuth2PkceMethod::Always => {
-                    mas_data_model::UpstreamOAuthProviderPkceMode::S256
-                }
-                mas_config::UpstreamOAuth2PkceMethod::Never => {
-                    mas_data_model::UpstreamOAuthProviderPkceMode::Disabled
-                }
-            };
-
-            let on_backchannel_logout = match provider.on_backchannel_logout {
-                mas_config::UpstreamOAuth2OnBackchannelLogout::DoNothing => {
-                    mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing
-                }
-                mas_config::UpstreamOAuth2OnBackchannelLogout::LogoutBrowserOnly => {
-                    mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::LogoutBrowserOnly
-                }
-                mas_config::UpstreamOAuth2OnBackchannelLogout::LogoutAll => {
-                    mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::LogoutAll
-                }
-            };
-
             repo.upstream_oauth_provider()
-                .upsert(
-                    clock,
-                    provider.id,
-                    UpstreamOAuthProviderParams {
-                        issuer: provider.issuer,
-                        human_name: provider.human_name,
-                        brand_name: provider.brand_name,
-                        scope: provider.scope.parse()?,
-                        token_endpoint_auth_method,
-                        token_endpoint_signing_alg: provider.token_endpoint_auth_signing_alg,
-                        id_token_signed_response_alg: provider.id_token_signed_response_alg,
-                        client_id: provider.client_id,
-                        encrypted_client_secret,
-                        claims_imports: map_claims_imports(&provider.claims_imports),
-                        token_endpoint_override: provider.token_endpoint,
-                        userinfo_endpoint_override: provider.userinfo_endpoint,
-                        authorization_endpoint_override: provider.authorization_endpoint,
-                        jwks_uri_override: provider.jwks_uri,
-                        discovery_mode,
-                        pkce_mode,
-                        fetch_userinfo: provider.fetch_userinfo,
-                        userinfo_signed_response_alg: provider.userinfo_signed_response_alg,
-                        response_mode,
-                        additional_authorization_parameters: provider
-                            .additional_authorization_parameters
-                            .into_iter()
-                            .collect(),
-                        forward_login_hint: provider.forward_login_hint,
-                        ui_order,
-                        on_backchannel_logout,
-                    },
-                )
+                .upsert(clock, provider_id, params)
                 .await?;
         }
     }
@@ -406,6 +251,12 @@ pub async fn config_sync(
                     jwks.cloned(),
                     jwks_uri.cloned(),
                     client.redirect_uris,
+                    client.post_logout_redirect_uris,
+                    client.backchannel_logout_uri,
+                    client.backchannel_logout_session_required,
+                    client.require_pushed_authorization_requests,
+                    client.require_signed_request_object,
+                    client.access_token_signed_response_alg,
                 )
                 .await?;
         }
@@ -9,13 +9,13 @@ use std::{sync::Arc, time::Duration};
 use anyhow::Context;
 use mas_config::{
     AccountConfig, BrandingConfig, CaptchaConfig, DatabaseConfig, EmailConfig, EmailSmtpMode,
-    EmailTransportKind, ExperimentalConfig, HomeserverKind, MatrixConfig, PasswordsConfig,
-    PolicyConfig, TemplatesConfig,
+    EmailTransportKind, ExperimentalConfig, HomeserverKind, MatrixConfig, PasswordBackend,
+    PasswordsConfig, PolicyConfig, TemplatesConfig, WebhookEventKind, WebhooksConfig,
 };
 use mas_context::LogContext;
-use mas_data_model::{SessionExpirationConfig, SiteConfig};
+use mas_data_model::{SessionExpirationConfig, SiteConfig, WebhookEndpoint, WebhookEvent};
 use mas_email::{MailTransport, Mailer};
-use mas_handlers::passwords::PasswordManager;
+use mas_handlers::passwords::{PasswordManager, breached::BreachedPasswords, ldap::Ldap};
 use mas_matrix::{HomeserverConnection, ReadOnlyHomeserverConnection};
 use mas_matrix_synapse::{LegacySynapseConnection, SynapseConnection};
 use mas_policy::PolicyFactory;
@@ -55,7 +55,30 @@ pub async fn password_manager_from_config(
         },
     );
 
-    PasswordManager::new(config.minimum_complexity(), schemes)
+    let mut password_manager = PasswordManager::new(config.minimum_complexity(), schemes)?
+        .with_history(config.history.into());
+
+    if let Some(max_age) = config.max_age {
+        password_manager = password_manager.with_max_age(max_age);
+    }
+
+    if let Some(breached_passwords) = &config.breached_passwords {
+        let breached_passwords = BreachedPasswords::open(&breached_passwords.path)?;
+        password_manager = password_manager.with_breached_passwords(breached_passwords);
+    }
+
+    if config.backend() == PasswordBackend::Ldap {
+        // This should have been checked when validating the configuration
+        let ldap_config = config
+            .ldap
+            .as_ref()
+            .context("invalid password configuration: missing 'ldap' section")?;
+        let bind_password = ldap_config.bind_password().await?;
+        let ldap = Ldap::new(ldap_config, bind_password)?;
+        password_manager = password_manager.with_ldap(ldap);
+    }
+
+    Ok(password_manager)
 }
 
 pub fn mailer_from_config(
@@ -183,6 +206,27 @@ pub fn captcha_config_from_config(
     }))
 }
 
+fn webhooks_from_config(webhooks_config: &WebhooksConfig) -> Vec<WebhookEndpoint> {
+    webhooks_config
+        .endpoints
+        .iter()
+        .map(|endpoint| WebhookEndpoint {
+            url: endpoint.url.clone(),
+            secret: endpoint.secret.clone(),
+            events: endpoint
+                .events
+                .iter()
+                .map(|event| match event {
+                    WebhookEventKind::UserRegistered => WebhookEvent::UserRegistered,
+                    WebhookEventKind::UserDeactivated => WebhookEvent::UserDeactivated,
+                    WebhookEventKind::UserEmailVerified => WebhookEvent::UserEmailVerified,
+                    WebhookEventKind::SessionCreated => WebhookEvent::SessionCreated,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
 pub fn site_config_from_config(
     branding_config: &BrandingConfig,
     matrix_config: &MatrixConfig,
@@ -190,8 +234,12 @@ pub fn site_config_from_config(
     password_config: &PasswordsConfig,
     account_config: &AccountConfig,
     captcha_config: &CaptchaConfig,
+    webhooks_config: &WebhooksConfig,
 ) -> Result<SiteConfig, anyhow::Error> {
     let captcha = captcha_config_from_config(captcha_config)?;
+    // Passwords of directory users are managed by the directory
+    let local_passwords =
+        password_config.enabled() && password_config.backend() == PasswordBackend::Local;
     let session_expiration = experimental_config
         .inactive_session_expiration
         .as_ref()
@@ -209,22 +257,24 @@ pub fn site_config_from_config(
         tos_uri: branding_config.tos_uri.clone(),
         imprint: branding_config.imprint.clone(),
         password_login_enabled: password_config.enabled(),
-        password_registration_enabled: password_config.enabled()
+        password_registration_enabled: local_passwords
             && account_config.password_registration_enabled,
         password_registration_email_required: account_config.password_registration_email_required,
         registration_token_required: account_config.registration_token_required,
         email_change_allowed: account_config.email_change_allowed,
         displayname_change_allowed: account_config.displayname_change_allowed,
-        password_change_allowed: password_config.enabled()
-            && account_config.password_change_allowed,
-        account_recovery_allowed: password_config.enabled()
-            && account_config.password_recovery_enabled,
+        password_change_allowed: local_passwords && account_config.password_change_allowed,
+        account_recovery_allowed: local_passwords && account_config.password_recovery_enabled,
         account_deactivation_allowed: account_config.account_deactivation_allowed,
         captcha,
         minimum_password_complexity: password_config.minimum_complexity(),
         session_expiration,
         login_with_email_allowed: account_config.login_with_email_allowed,
         plan_management_iframe_uri: experimental_config.plan_management_iframe_uri.clone(),
+        passkeys_enabled: account_config.passkeys_enabled,
+        totp_enabled: account_config.totp_enabled,
+        deactivated_user_retention: account_config.deactivated_user_retention,
+        webhooks: webhooks_from_config(webhooks_config),
     })
 }
 
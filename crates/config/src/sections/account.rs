@@ -4,8 +4,10 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use chrono::Duration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 
 use crate::ConfigurationSection;
 
@@ -29,6 +31,7 @@ const fn is_default_false(value: &bool) -> bool {
 
 /// Configuration section to configure features related to account management
 #[allow(clippy::struct_excessive_bools)]
+#[serde_as]
 #[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
 pub struct AccountConfig {
     /// Whether users are allowed to change their email addresses. Defaults to
@@ -88,6 +91,31 @@ pub struct AccountConfig {
     /// is disabled.
     #[serde(default = "default_false", skip_serializing_if = "is_default_false")]
     pub registration_token_required: bool,
+
+    /// Whether users can register passkeys and use them to log in. Defaults
+    /// to `false`.
+    #[serde(default = "default_false", skip_serializing_if = "is_default_false")]
+    pub passkeys_enabled: bool,
+
+    /// Whether users can enrol a TOTP authenticator app as a second factor.
+    /// Defaults to `false`.
+    ///
+    /// Users who already enrolled one still have to provide a code when
+    /// logging in with their password, even if this is later disabled.
+    #[serde(default = "default_false", skip_serializing_if = "is_default_false")]
+    pub totp_enabled: bool,
+
+    /// Time in seconds after which the personal data of deactivated users is
+    /// erased. Disabled by default.
+    ///
+    /// Once erased, the email addresses, credentials, upstream links, IP
+    /// addresses and user agents of the user are removed, and the user can't
+    /// be reactivated anymore. The username stays reserved, so that it can
+    /// never be registered again.
+    #[schemars(with = "Option<u64>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub deactivated_user_retention: Option<Duration>,
 }
 
 impl Default for AccountConfig {
@@ -102,6 +130,9 @@ impl Default for AccountConfig {
             account_deactivation_allowed: default_true(),
             login_with_email_allowed: default_false(),
             registration_token_required: default_false(),
+            passkeys_enabled: default_false(),
+            totp_enabled: default_false(),
+            deactivated_user_retention: None,
         }
     }
 }
@@ -117,6 +148,9 @@ impl AccountConfig {
             && is_default_true(&self.account_deactivation_allowed)
             && is_default_false(&self.login_with_email_allowed)
             && is_default_false(&self.registration_token_required)
+            && is_default_false(&self.passkeys_enabled)
+            && is_default_false(&self.totp_enabled)
+            && self.deactivated_user_retention.is_none()
     }
 }
 
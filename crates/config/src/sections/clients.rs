@@ -8,7 +8,7 @@ use std::ops::Deref;
 
 use anyhow::bail;
 use camino::Utf8PathBuf;
-use mas_iana::oauth::OAuthClientAuthenticationMethod;
+use mas_iana::{jose::JsonWebSignatureAlg, oauth::OAuthClientAuthenticationMethod};
 use mas_jose::jwk::PublicJsonWebKeySet;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize, de::Error};
@@ -166,6 +166,39 @@ pub struct ClientConfig {
     /// List of allowed redirect URIs
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub redirect_uris: Vec<Url>,
+
+    /// List of URIs to which the user can be redirected after logging out
+    /// through the `end_session_endpoint`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_logout_redirect_uris: Vec<Url>,
+
+    /// URI to which logout tokens are sent when a session of this client ends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// Whether the logout tokens sent to the `backchannel_logout_uri` must
+    /// include the `sid` claim
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub backchannel_logout_session_required: bool,
+
+    /// Whether the client must use pushed authorization requests to start an
+    /// authorization flow
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_pushed_authorization_requests: bool,
+
+    /// Whether the client must send its authorization requests as request
+    /// objects signed with one of the keys from its `jwks` or `jwks_uri`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_signed_request_object: bool,
+
+    /// The algorithm used to sign the access tokens issued to this client.
+    ///
+    /// If set, access tokens are JWTs as described in RFC 9068, signed with
+    /// one of the keys from the `secrets` section. Their audience is the
+    /// homeserver, identified by its server name. If not set, the client gets
+    /// opaque access tokens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
 }
 
 impl ClientConfig {
@@ -243,6 +276,22 @@ impl ClientConfig {
             }
         }
 
+        if self.require_signed_request_object && self.jwks.is_none() && self.jwks_uri.is_none() {
+            let error = figment::error::Error::custom(
+                "jwks or jwks_uri is required for require_signed_request_object",
+            );
+            return Err(Box::new(error.with_path("require_signed_request_object")));
+        }
+
+        if self.access_token_signed_response_alg == Some(JsonWebSignatureAlg::None) {
+            let error = figment::error::Error::custom(
+                "access_token_signed_response_alg must not be 'none'",
+            );
+            return Err(Box::new(
+                error.with_path("access_token_signed_response_alg"),
+            ));
+        }
+
         Ok(())
     }
 
@@ -355,6 +404,11 @@ mod tests {
                           client_auth_method: none
                           redirect_uris:
                             - https://exemple.fr/callback
+                          post_logout_redirect_uris:
+                            - https://exemple.fr/logged-out
+                          backchannel_logout_uri: https://exemple.fr/backchannel-logout
+                          backchannel_logout_session_required: true
+                          require_pushed_authorization_requests: true
 
                         - client_id: 01GFWR32NCQ12B8Z0J8CPXRRB6
                           client_auth_method: client_secret_basic
@@ -370,6 +424,8 @@ mod tests {
 
                         - client_id: 01GFWR4BNFDCC4QDG6AMSP1VRR
                           client_auth_method: private_key_jwt
+                          require_signed_request_object: true
+                          access_token_signed_response_alg: RS256
                           jwks:
                             keys:
                             - kid: "03e84aed4ef4431014e8617567864c4efaaaede9"
@@ -409,6 +465,27 @@ mod tests {
                     Ulid::from_str("01GFWR32NCQ12B8Z0J8CPXRRB6").unwrap()
                 );
                 assert_eq!(config.0[1].redirect_uris, Vec::new());
+                assert_eq!(
+                    config.0[0].post_logout_redirect_uris,
+                    vec!["https://exemple.fr/logged-out".parse().unwrap()]
+                );
+                assert_eq!(config.0[1].post_logout_redirect_uris, Vec::new());
+                assert_eq!(
+                    config.0[0].backchannel_logout_uri,
+                    Some("https://exemple.fr/backchannel-logout".parse().unwrap())
+                );
+                assert!(config.0[0].backchannel_logout_session_required);
+                assert_eq!(config.0[1].backchannel_logout_uri, None);
+                assert!(!config.0[1].backchannel_logout_session_required);
+                assert!(config.0[0].require_pushed_authorization_requests);
+                assert!(!config.0[1].require_pushed_authorization_requests);
+                assert!(!config.0[0].require_signed_request_object);
+                assert!(config.0[4].require_signed_request_object);
+                assert_eq!(config.0[0].access_token_signed_response_alg, None);
+                assert_eq!(
+                    config.0[4].access_token_signed_response_alg,
+                    Some(JsonWebSignatureAlg::Rs256)
+                );
 
                 assert!(config.0[0].client_secret.is_none());
                 assert!(matches!(config.0[1].client_secret, Some(ClientSecret::File(ref p)) if p == "secret"));
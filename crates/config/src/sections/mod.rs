@@ -24,6 +24,7 @@ mod secrets;
 mod telemetry;
 mod templates;
 mod upstream_oauth2;
+mod webhooks;
 
 pub use self::{
     account::AccountConfig,
@@ -39,10 +40,12 @@ pub use self::{
     },
     matrix::{HomeserverKind, MatrixConfig},
     passwords::{
-        Algorithm as PasswordAlgorithm, HashingScheme as PasswordHashingScheme, PasswordsConfig,
+        Algorithm as PasswordAlgorithm, BreachedPasswordsConfig,
+        HashingScheme as PasswordHashingScheme, LdapAttributeMapping, LdapConfig, PasswordBackend,
+        PasswordsConfig,
     },
     policy::PolicyConfig,
-    rate_limiting::RateLimitingConfig,
+    rate_limiting::{RateLimitingBackend, RateLimitingConfig},
     secrets::SecretsConfig,
     telemetry::{
         MetricsConfig, MetricsExporterKind, Propagator, TelemetryConfig, TracingConfig,
@@ -56,8 +59,10 @@ pub use self::{
         OnBackchannelLogout as UpstreamOAuth2OnBackchannelLogout,
         OnConflict as UpstreamOAuth2OnConflict, PkceMethod as UpstreamOAuth2PkceMethod,
         Provider as UpstreamOAuth2Provider, ResponseMode as UpstreamOAuth2ResponseMode,
+        Saml as UpstreamOAuth2Saml, SignInWithApple as UpstreamOAuth2SignInWithApple,
         TokenAuthMethod as UpstreamOAuth2TokenAuthMethod, UpstreamOAuth2Config,
     },
+    webhooks::{WebhookEndpointConfig, WebhookEventKind, WebhooksConfig},
 };
 use crate::util::ConfigurationSection;
 
@@ -124,6 +129,10 @@ pub struct RootConfig {
     #[serde(default, skip_serializing_if = "AccountConfig::is_default")]
     pub account: AccountConfig,
 
+    /// Configuration section to deliver lifecycle events to external systems
+    #[serde(default, skip_serializing_if = "WebhooksConfig::is_default")]
+    pub webhooks: WebhooksConfig,
+
     /// Experimental configuration options
     #[serde(default, skip_serializing_if = "ExperimentalConfig::is_default")]
     pub experimental: ExperimentalConfig,
@@ -149,6 +158,7 @@ impl ConfigurationSection for RootConfig {
         self.branding.validate(figment)?;
         self.captcha.validate(figment)?;
         self.account.validate(figment)?;
+        self.webhooks.validate(figment)?;
         self.experimental.validate(figment)?;
 
         Ok(())
@@ -181,6 +191,7 @@ impl RootConfig {
             branding: BrandingConfig::default(),
             captcha: CaptchaConfig::default(),
             account: AccountConfig::default(),
+            webhooks: WebhooksConfig::default(),
             experimental: ExperimentalConfig::default(),
         })
     }
@@ -204,6 +215,7 @@ impl RootConfig {
             branding: BrandingConfig::default(),
             captcha: CaptchaConfig::default(),
             account: AccountConfig::default(),
+            webhooks: WebhooksConfig::default(),
             experimental: ExperimentalConfig::default(),
         }
     }
@@ -247,6 +259,9 @@ pub struct AppConfig {
     #[serde(default)]
     pub account: AccountConfig,
 
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+
     #[serde(default)]
     pub experimental: ExperimentalConfig,
 }
@@ -268,6 +283,7 @@ impl ConfigurationSection for AppConfig {
         self.branding.validate(figment)?;
         self.captcha.validate(figment)?;
         self.account.validate(figment)?;
+        self.webhooks.validate(figment)?;
         self.experimental.validate(figment)?;
 
         Ok(())
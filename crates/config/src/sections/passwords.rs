@@ -8,8 +8,11 @@ use std::cmp::Reverse;
 
 use anyhow::bail;
 use camino::Utf8PathBuf;
+use chrono::Duration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use url::Url;
 
 use crate::ConfigurationSection;
 
@@ -32,7 +35,34 @@ fn default_minimum_complexity() -> u8 {
     3
 }
 
+fn default_user_filter() -> String {
+    "(uid={username})".to_owned()
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+const fn is_zero(value: &u16) -> bool {
+    *value == 0
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_default_backend(value: &PasswordBackend) -> bool {
+    *value == PasswordBackend::default()
+}
+
+/// Which backend checks the passwords of users
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordBackend {
+    /// Check passwords against the hashes stored in the database
+    #[default]
+    Local,
+
+    /// Check passwords by binding to an LDAP directory, like Active Directory
+    Ldap,
+}
+
 /// User password hashing config
+#[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PasswordsConfig {
     /// Whether password-based authentication is enabled
@@ -57,6 +87,36 @@ pub struct PasswordsConfig {
     /// - 4: any more than that
     #[serde(default = "default_minimum_complexity")]
     minimum_complexity: u8,
+
+    /// Number of previous passwords of a user which can't be reused when
+    /// setting a new password. Defaults to 0, which allows reusing any
+    /// previous password.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub history: u16,
+
+    /// Maximum age of a password in seconds. Users who log in with an older
+    /// password have to change it before continuing. Passwords never expire
+    /// if not set.
+    #[schemars(with = "Option<u64>", range(min = 86400))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde_as(as = "Option<serde_with::DurationSeconds<i64>>")]
+    pub max_age: Option<Duration>,
+
+    /// Which backend checks the passwords of users. Defaults to `local`.
+    ///
+    /// With the `ldap` backend, users can't register, change or recover their
+    /// password, and are provisioned on their first login.
+    #[serde(default, skip_serializing_if = "is_default_backend")]
+    pub backend: PasswordBackend,
+
+    /// Settings of the LDAP directory, required by the `ldap` backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ldap: Option<LdapConfig>,
+
+    /// Reject new passwords which appear in a corpus of breached passwords.
+    /// Disabled if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breached_passwords: Option<BreachedPasswordsConfig>,
 }
 
 impl Default for PasswordsConfig {
@@ -65,6 +125,11 @@ impl Default for PasswordsConfig {
             enabled: default_enabled(),
             schemes: default_schemes(),
             minimum_complexity: default_minimum_complexity(),
+            history: 0,
+            max_age: None,
+            backend: PasswordBackend::default(),
+            ldap: None,
+            breached_passwords: None,
         }
     }
 }
@@ -104,6 +169,31 @@ impl ConfigurationSection for PasswordsConfig {
             }
         }
 
+        if self
+            .max_age
+            .is_some_and(|max_age| max_age < Duration::days(1))
+        {
+            return Err(annotate(figment::Error::from(
+                "`max_age` must be at least a day".to_owned(),
+            ))
+            .into());
+        }
+
+        if self.backend == PasswordBackend::Ldap {
+            let Some(ldap) = &self.ldap else {
+                return Err(annotate(figment::Error::from(
+                    "The `ldap` backend requires the `ldap` section".to_owned(),
+                ))
+                .into());
+            };
+
+            ldap.validate().map_err(|message| {
+                let mut error = annotate(figment::Error::from(message.to_owned()));
+                error.path.push("ldap".to_owned());
+                error
+            })?;
+        }
+
         Ok(())
     }
 }
@@ -115,6 +205,12 @@ impl PasswordsConfig {
         self.enabled
     }
 
+    /// Which backend checks the passwords of users
+    #[must_use]
+    pub fn backend(&self) -> PasswordBackend {
+        self.backend
+    }
+
     /// Minimum complexity of passwords, from 0 to 4, according to the zxcvbn
     /// scorer.
     #[must_use]
@@ -175,6 +271,153 @@ const fn is_default_false(value: &bool) -> bool {
     !*value
 }
 
+/// A local mirror of a corpus of breached passwords, in the format of the
+/// Have I Been Pwned "Pwned Passwords" dataset
+///
+/// Passwords are looked up by their uppercase hex-encoded SHA-1 hash, so the
+/// corpus never has to leave the server.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BreachedPasswordsConfig {
+    /// Path to the corpus. It can either be:
+    ///
+    /// - a directory of range files, named after the first 5 characters of
+    ///   the hashes they contain (like `21BD1.txt`), each line being the 35
+    ///   remaining characters of a hash followed by `:` and a count
+    /// - a single file, each line being a full hash followed by `:` and a
+    ///   count, sorted by hash
+    #[schemars(with = "String")]
+    pub path: Utf8PathBuf,
+}
+
+/// Settings of the LDAP directory used to check passwords
+///
+/// Users are first searched in the directory, using the `bind_dn` credentials
+/// if set, then their password is checked by binding as the entry found.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct LdapConfig {
+    /// URL of the directory server, with the `ldap` or `ldaps` scheme
+    #[schemars(url)]
+    pub url: Url,
+
+    /// Whether to upgrade the connection to TLS with `StartTLS`. Only valid
+    /// with the `ldap` scheme.
+    #[serde(default, skip_serializing_if = "is_default_false")]
+    pub starttls: bool,
+
+    /// The DN to bind as when searching users. Users are searched anonymously
+    /// if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_dn: Option<String>,
+
+    /// The password to bind with when searching users
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_password: Option<String>,
+
+    /// Same as `bind_password`, but read from a file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub bind_password_file: Option<Utf8PathBuf>,
+
+    /// The DN under which users are searched
+    pub base_dn: String,
+
+    /// The filter used to search users. `{username}` is replaced by the
+    /// escaped username the user entered.
+    ///
+    /// Defaults to `(uid={username})`. With Active Directory, this is usually
+    /// `(sAMAccountName={username})`.
+    #[serde(default = "default_user_filter")]
+    pub user_filter: String,
+
+    /// Whether a user which already exists locally can be linked to the
+    /// directory entry with the same localpart on its first login. Defaults
+    /// to `false`, which rejects the login.
+    #[serde(default, skip_serializing_if = "is_default_false")]
+    pub link_existing_users: bool,
+
+    /// How the attributes of the directory entry are mapped to the user
+    #[serde(default)]
+    pub attributes: LdapAttributeMapping,
+}
+
+impl LdapConfig {
+    fn validate(&self) -> Result<(), &'static str> {
+        match self.url.scheme() {
+            "ldap" => {}
+            "ldaps" => {
+                if self.starttls {
+                    return Err("`starttls` can only be used with the `ldap` scheme");
+                }
+            }
+            _ => return Err("The URL must have the `ldap` or `ldaps` scheme"),
+        }
+
+        if self.bind_password.is_some() && self.bind_password_file.is_some() {
+            return Err("Cannot specify both `bind_password` and `bind_password_file`");
+        }
+
+        let has_password = self.bind_password.is_some() || self.bind_password_file.is_some();
+        if self.bind_dn.is_some() != has_password {
+            return Err("`bind_dn` and `bind_password` must be set together");
+        }
+
+        if !self.user_filter.contains("{username}") {
+            return Err("The `user_filter` must contain the `{username}` placeholder");
+        }
+
+        Ok(())
+    }
+
+    /// Load the password to bind with when searching users
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the password file could not be read
+    pub async fn bind_password(&self) -> Result<Option<String>, anyhow::Error> {
+        match (&self.bind_password, &self.bind_password_file) {
+            (Some(password), None) => Ok(Some(password.clone())),
+            (None, Some(path)) => {
+                let raw = tokio::fs::read_to_string(path).await?;
+                // Strip the trailing newline most editors add
+                Ok(Some(raw.trim_end_matches(['\r', '\n']).to_owned()))
+            }
+            (Some(_), Some(_)) => {
+                bail!("Cannot specify both `bind_password` and `bind_password_file`")
+            }
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+/// How the attributes of a directory entry are mapped to the user
+///
+/// Each of those is a Jinja2 template, in which the attributes of the entry
+/// are available in the `user` variable, along with its DN as `user.dn`.
+/// Attributes with a single value are strings, and attributes with multiple
+/// values are lists.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct LdapAttributeMapping {
+    /// The template for a stable identifier of the entry, used to link it to
+    /// the user. Defaults to `{{ user.dn }}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+
+    /// The template for the localpart of users provisioned on their first
+    /// login. Defaults to `{{ user.uid }}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub localpart: Option<String>,
+
+    /// The template for the display name of users provisioned on their first
+    /// login. No display name is set if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub displayname: Option<String>,
+
+    /// The template for the email address of users provisioned on their first
+    /// login. No email address is added if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
 /// Parameters for a password hashing scheme
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct HashingScheme {
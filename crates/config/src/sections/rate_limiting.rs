@@ -15,6 +15,15 @@ use crate::ConfigurationSection;
 /// Configuration related to sending emails
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct RateLimitingConfig {
+    /// Where the state of the rate limiters is kept. Defaults to `memory`.
+    ///
+    /// When running multiple replicas of the service, the in-memory state
+    /// isn't shared between them, so the effective limits are multiplied by
+    /// the number of replicas. Use `postgres` to share the state between
+    /// replicas through the database.
+    #[serde(default)]
+    pub backend: RateLimitingBackend,
+
     /// Account Recovery-specific rate limits
     #[serde(default)]
     pub account_recovery: AccountRecoveryRateLimitingConfig,
@@ -33,6 +42,18 @@ pub struct RateLimitingConfig {
     pub email_authentication: EmailauthenticationRateLimitingConfig,
 }
 
+/// Where the state of the rate limiters is kept
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitingBackend {
+    /// Keep the state in the memory of each replica
+    #[default]
+    Memory,
+
+    /// Keep the state in the database, shared by all replicas
+    Postgres,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct LoginRateLimitingConfig {
     /// Controls how many login attempts are permitted
@@ -260,6 +281,7 @@ fn default_email_authentication_attempt_per_session() -> RateLimiterConfiguratio
 impl Default for RateLimitingConfig {
     fn default() -> Self {
         RateLimitingConfig {
+            backend: RateLimitingBackend::default(),
             login: LoginRateLimitingConfig::default(),
             registration: default_registration(),
             account_recovery: AccountRecoveryRateLimitingConfig::default(),
@@ -51,84 +51,7 @@ impl ConfigurationSection for UpstreamOAuth2Config {
                 error
             };
 
-            if !matches!(provider.discovery_mode, DiscoveryMode::Disabled)
-                && provider.issuer.is_none()
-            {
-                return Err(annotate(figment::Error::custom(
-                    "The `issuer` field is required when discovery is enabled",
-                ))
-                .into());
-            }
-
-            match provider.token_endpoint_auth_method {
-                TokenAuthMethod::None
-                | TokenAuthMethod::PrivateKeyJwt
-                | TokenAuthMethod::SignInWithApple => {
-                    if provider.client_secret.is_some() {
-                        return Err(annotate(figment::Error::custom(
-                            "Unexpected field `client_secret` for the selected authentication method",
-                        )).into());
-                    }
-                }
-                TokenAuthMethod::ClientSecretBasic
-                | TokenAuthMethod::ClientSecretPost
-                | TokenAuthMethod::ClientSecretJwt => {
-                    if provider.client_secret.is_none() {
-                        return Err(annotate(figment::Error::missing_field("client_secret")).into());
-                    }
-                }
-            }
-
-            match provider.token_endpoint_auth_method {
-                TokenAuthMethod::None
-                | TokenAuthMethod::ClientSecretBasic
-                | TokenAuthMethod::ClientSecretPost
-                | TokenAuthMethod::SignInWithApple => {
-                    if provider.token_endpoint_auth_signing_alg.is_some() {
-                        return Err(annotate(figment::Error::custom(
-                            "Unexpected field `token_endpoint_auth_signing_alg` for the selected authentication method",
-                        )).into());
-                    }
-                }
-                TokenAuthMethod::ClientSecretJwt | TokenAuthMethod::PrivateKeyJwt => {
-                    if provider.token_endpoint_auth_signing_alg.is_none() {
-                        return Err(annotate(figment::Error::missing_field(
-                            "token_endpoint_auth_signing_alg",
-                        ))
-                        .into());
-                    }
-                }
-            }
-
-            match provider.token_endpoint_auth_method {
-                TokenAuthMethod::SignInWithApple => {
-                    if provider.sign_in_with_apple.is_none() {
-                        return Err(
-                            annotate(figment::Error::missing_field("sign_in_with_apple")).into(),
-                        );
-                    }
-                }
-
-                _ => {
-                    if provider.sign_in_with_apple.is_some() {
-                        return Err(annotate(figment::Error::custom(
-                            "Unexpected field `sign_in_with_apple` for the selected authentication method",
-                        )).into());
-                    }
-                }
-            }
-
-            if matches!(
-                provider.claims_imports.localpart.on_conflict,
-                OnConflict::Add
-            ) && !matches!(
-                provider.claims_imports.localpart.action,
-                ImportAction::Force | ImportAction::Require
-            ) {
-                return Err(annotate(figment::Error::custom(
-                    "The field `action` must be either `force` or `require` when `on_conflict` is set to `add`",
-                )).into());
-            }
+            provider.validate().map_err(|error| annotate(*error))?;
         }
 
         Ok(())
@@ -425,6 +348,7 @@ fn signed_response_alg_default() -> JsonWebSignatureAlg {
     JsonWebSignatureAlg::Rs256
 }
 
+/// Additional parameters for the `sign_in_with_apple` authentication method
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SignInWithApple {
     /// The private key file used to sign the `id_token`
@@ -443,6 +367,28 @@ pub struct SignInWithApple {
     pub key_id: String,
 }
 
+/// Settings for providers which speak SAML 2.0 instead of OAuth 2.0
+///
+/// For those providers, `issuer` is the entity ID of the identity provider,
+/// and `client_id` is the entity ID used by the service to identify itself.
+/// The service provider metadata is served at
+/// `/upstream/saml/metadata/{provider_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Saml {
+    /// The URL of the single sign-on service of the identity provider, using
+    /// the HTTP-POST binding
+    pub sso_url: Url,
+
+    /// PEM-encoded X.509 certificates the identity provider signs its
+    /// responses with
+    pub certificates: Vec<String>,
+
+    /// The format of the name identifier to request, e.g.
+    /// `urn:oasis:names:tc:SAML:2.0:nameid-format:persistent`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_id_format: Option<String>,
+}
+
 fn default_scope() -> String {
     "openid".to_owned()
 }
@@ -655,4 +601,135 @@ pub struct Provider {
     /// Defaults to `do_nothing`.
     #[serde(default, skip_serializing_if = "OnBackchannelLogout::is_default")]
     pub on_backchannel_logout: OnBackchannelLogout,
+
+    /// Settings for providers which speak SAML 2.0 instead of OAuth 2.0
+    ///
+    /// Requires `discovery_mode` to be `disabled` and
+    /// `token_endpoint_auth_method` to be `none`. Attributes of the assertion
+    /// are available to the `claims_imports` templates as `user.<attribute>`,
+    /// and the name identifier as `user.sub`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saml: Option<Saml>,
+}
+
+impl Provider {
+    /// Check that the fields of this provider are consistent with each other
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first inconsistency found
+    pub fn validate(&self) -> Result<(), Box<figment::Error>> {
+        if !matches!(self.discovery_mode, DiscoveryMode::Disabled) && self.issuer.is_none() {
+            return Err(Box::new(figment::Error::custom(
+                "The `issuer` field is required when discovery is enabled",
+            )));
+        }
+
+        match self.token_endpoint_auth_method {
+            TokenAuthMethod::None
+            | TokenAuthMethod::PrivateKeyJwt
+            | TokenAuthMethod::SignInWithApple => {
+                if self.client_secret.is_some() {
+                    return Err(Box::new(figment::Error::custom(
+                        "Unexpected field `client_secret` for the selected authentication method",
+                    )));
+                }
+            }
+            TokenAuthMethod::ClientSecretBasic
+            | TokenAuthMethod::ClientSecretPost
+            | TokenAuthMethod::ClientSecretJwt => {
+                if self.client_secret.is_none() {
+                    return Err(Box::new(figment::Error::missing_field("client_secret")));
+                }
+            }
+        }
+
+        match self.token_endpoint_auth_method {
+            TokenAuthMethod::None
+            | TokenAuthMethod::ClientSecretBasic
+            | TokenAuthMethod::ClientSecretPost
+            | TokenAuthMethod::SignInWithApple => {
+                if self.token_endpoint_auth_signing_alg.is_some() {
+                    return Err(Box::new(figment::Error::custom(
+                        "Unexpected field `token_endpoint_auth_signing_alg` for the selected authentication method",
+                    )));
+                }
+            }
+            TokenAuthMethod::ClientSecretJwt | TokenAuthMethod::PrivateKeyJwt => {
+                if self.token_endpoint_auth_signing_alg.is_none() {
+                    return Err(Box::new(figment::Error::missing_field(
+                        "token_endpoint_auth_signing_alg",
+                    )));
+                }
+            }
+        }
+
+        match self.token_endpoint_auth_method {
+            TokenAuthMethod::SignInWithApple => {
+                if self.sign_in_with_apple.is_none() {
+                    return Err(Box::new(figment::Error::missing_field(
+                        "sign_in_with_apple",
+                    )));
+                }
+            }
+
+            _ => {
+                if self.sign_in_with_apple.is_some() {
+                    return Err(Box::new(figment::Error::custom(
+                        "Unexpected field `sign_in_with_apple` for the selected authentication method",
+                    )));
+                }
+            }
+        }
+
+        if matches!(self.claims_imports.localpart.on_conflict, OnConflict::Add)
+            && !matches!(
+                self.claims_imports.localpart.action,
+                ImportAction::Force | ImportAction::Require
+            )
+        {
+            return Err(Box::new(figment::Error::custom(
+                "The field `action` must be either `force` or `require` when `on_conflict` is set to `add`",
+            )));
+        }
+
+        if let Some(saml) = &self.saml {
+            if self.issuer.is_none() {
+                return Err(Box::new(figment::Error::custom(
+                    "The `issuer` field is required for SAML 2.0 providers",
+                )));
+            }
+
+            if !matches!(self.discovery_mode, DiscoveryMode::Disabled) {
+                return Err(Box::new(figment::Error::custom(
+                    "The `discovery_mode` field must be set to `disabled` for SAML 2.0 providers",
+                )));
+            }
+
+            if !matches!(self.token_endpoint_auth_method, TokenAuthMethod::None) {
+                return Err(Box::new(figment::Error::custom(
+                    "The `token_endpoint_auth_method` field must be set to `none` for SAML 2.0 providers",
+                )));
+            }
+
+            if saml.certificates.is_empty() {
+                return Err(Box::new(figment::Error::custom(
+                    "At least one certificate is required for SAML 2.0 providers",
+                )));
+            }
+
+            for certificate in &saml.certificates {
+                match pem_rfc7468::decode_vec(certificate.as_bytes()) {
+                    Ok(("CERTIFICATE", _)) => {}
+                    _ => {
+                        return Err(Box::new(figment::Error::custom(
+                            "Invalid SAML 2.0 certificate, expected a PEM-encoded X.509 certificate",
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,120 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize, de::Error};
+use url::Url;
+
+use crate::ConfigurationSection;
+
+/// A lifecycle event which can be delivered to a webhook endpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, JsonSchema, Serialize)]
+pub enum WebhookEventKind {
+    /// A user registered
+    #[serde(rename = "user.registered")]
+    UserRegistered,
+
+    /// A user was deactivated
+    #[serde(rename = "user.deactivated")]
+    UserDeactivated,
+
+    /// A user verified an email address
+    #[serde(rename = "user_email.verified")]
+    UserEmailVerified,
+
+    /// A user started a new session
+    #[serde(rename = "session.created")]
+    SessionCreated,
+}
+
+/// An endpoint to which lifecycle events are delivered
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize)]
+pub struct WebhookEndpointConfig {
+    /// The URL to which the events are sent
+    pub url: Url,
+
+    /// The secret used to sign the payloads.
+    ///
+    /// Each request carries a `X-MAS-Webhook-Signature` header, containing
+    /// the hex-encoded HMAC-SHA256 of the request body with this secret.
+    pub secret: String,
+
+    /// The events to deliver to this endpoint. All events are delivered if
+    /// this is empty or omitted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<WebhookEventKind>,
+}
+
+/// Configuration section to deliver lifecycle events to external systems
+#[derive(Clone, Debug, Deserialize, JsonSchema, Serialize, Default)]
+pub struct WebhooksConfig {
+    /// The endpoints to which lifecycle events are delivered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub endpoints: Vec<WebhookEndpointConfig>,
+}
+
+impl WebhooksConfig {
+    /// Returns true if the configuration is the default one
+    pub(crate) fn is_default(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+}
+
+impl ConfigurationSection for WebhooksConfig {
+    const PATH: Option<&'static str> = Some("webhooks");
+
+    fn validate(
+        &self,
+        figment: &figment::Figment,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let metadata = figment.find_metadata(Self::PATH.unwrap());
+
+        let error_on_endpoint = |mut error: figment::error::Error, index: usize, field: &str| {
+            error.metadata = metadata.cloned();
+            error.profile = Some(figment::Profile::Default);
+            error.path = vec![
+                Self::PATH.unwrap().to_owned(),
+                "endpoints".to_owned(),
+                index.to_string(),
+                field.to_owned(),
+            ];
+            error
+        };
+
+        let mut urls = BTreeSet::new();
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            if !matches!(endpoint.url.scheme(), "http" | "https") {
+                return Err(error_on_endpoint(
+                    figment::error::Error::custom("webhook URLs must use http or https"),
+                    index,
+                    "url",
+                )
+                .into());
+            }
+
+            if !urls.insert(endpoint.url.as_str()) {
+                return Err(error_on_endpoint(
+                    figment::error::Error::custom("duplicate webhook URL"),
+                    index,
+                    "url",
+                )
+                .into());
+            }
+
+            if endpoint.secret.is_empty() {
+                return Err(error_on_endpoint(
+                    figment::error::Error::custom("the webhook secret must not be empty"),
+                    index,
+                    "secret",
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
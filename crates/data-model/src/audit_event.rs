@@ -0,0 +1,319 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid::Ulid;
+
+/// A security-relevant action recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditAction {
+    /// A user was created
+    #[serde(rename = "user.add")]
+    UserAdd,
+
+    /// A user was locked
+    #[serde(rename = "user.lock")]
+    UserLock,
+
+    /// A user was unlocked
+    #[serde(rename = "user.unlock")]
+    UserUnlock,
+
+    /// A user was deactivated
+    #[serde(rename = "user.deactivate")]
+    UserDeactivate,
+
+    /// A user was reactivated
+    #[serde(rename = "user.reactivate")]
+    UserReactivate,
+
+    /// The admin privileges of a user were changed
+    #[serde(rename = "user.set_admin")]
+    UserSetAdmin,
+
+    /// The password of a user was set
+    #[serde(rename = "user.set_password")]
+    UserSetPassword,
+
+    /// A user was allowed to reset their cross-signing keys
+    #[serde(rename = "user.allow_cross_signing_reset")]
+    UserAllowCrossSigningReset,
+
+    /// A session impersonating a user was started by an admin
+    #[serde(rename = "user.impersonate")]
+    UserImpersonate,
+
+    /// An export of everything stored about a user was requested
+    #[serde(rename = "user.export")]
+    UserExport,
+
+    /// The personal data of a deactivated user was erased
+    #[serde(rename = "user.erase")]
+    UserErase,
+
+    /// An email address was added to a user
+    #[serde(rename = "user_email.add")]
+    UserEmailAdd,
+
+    /// An email address was removed from a user
+    #[serde(rename = "user_email.remove")]
+    UserEmailRemove,
+
+    /// A passkey was registered for a user
+    #[serde(rename = "user_passkey.add")]
+    UserPasskeyAdd,
+
+    /// A passkey of a user was removed
+    #[serde(rename = "user_passkey.remove")]
+    UserPasskeyRemove,
+
+    /// A user enrolled a TOTP authenticator
+    #[serde(rename = "user_totp.add")]
+    UserTotpAdd,
+
+    /// The TOTP authenticator of a user was removed
+    #[serde(rename = "user_totp.remove")]
+    UserTotpRemove,
+
+    /// A browser session was finished
+    #[serde(rename = "browser_session.finish")]
+    BrowserSessionFinish,
+
+    /// A compatibility session was finished
+    #[serde(rename = "compat_session.finish")]
+    CompatSessionFinish,
+
+    /// An OAuth 2.0 session was created on behalf of a user by an admin
+    #[serde(rename = "oauth2_session.add")]
+    OAuth2SessionAdd,
+
+    /// An OAuth 2.0 session was finished
+    #[serde(rename = "oauth2_session.finish")]
+    OAuth2SessionFinish,
+
+    /// A personal session was created
+    #[serde(rename = "personal_session.add")]
+    PersonalSessionAdd,
+
+    /// The access token of a personal session was regenerated
+    #[serde(rename = "personal_session.regenerate")]
+    PersonalSessionRegenerate,
+
+    /// A personal session was revoked
+    #[serde(rename = "personal_session.revoke")]
+    PersonalSessionRevoke,
+
+    /// An upstream OAuth 2.0 link was added to a user
+    #[serde(rename = "upstream_oauth_link.add")]
+    UpstreamOAuthLinkAdd,
+
+    /// An upstream OAuth 2.0 link was removed
+    #[serde(rename = "upstream_oauth_link.remove")]
+    UpstreamOAuthLinkRemove,
+
+    /// An OAuth 2.0 client was created
+    #[serde(rename = "oauth2_client.add")]
+    OAuth2ClientAdd,
+
+    /// An OAuth 2.0 client was deleted
+    #[serde(rename = "oauth2_client.delete")]
+    OAuth2ClientDelete,
+
+    /// The secret of an OAuth 2.0 client was regenerated
+    #[serde(rename = "oauth2_client.regenerate_secret")]
+    OAuth2ClientRegenerateSecret,
+
+    /// The redirect URIs of an OAuth 2.0 client were changed
+    #[serde(rename = "oauth2_client.set_redirect_uris")]
+    OAuth2ClientSetRedirectUris,
+
+    /// An upstream OAuth 2.0 provider was created
+    #[serde(rename = "upstream_oauth_provider.add")]
+    UpstreamOAuthProviderAdd,
+
+    /// An upstream OAuth 2.0 provider was updated
+    #[serde(rename = "upstream_oauth_provider.update")]
+    UpstreamOAuthProviderUpdate,
+
+    /// An upstream OAuth 2.0 provider was disabled
+    #[serde(rename = "upstream_oauth_provider.disable")]
+    UpstreamOAuthProviderDisable,
+
+    /// An upstream OAuth 2.0 provider was deleted
+    #[serde(rename = "upstream_oauth_provider.delete")]
+    UpstreamOAuthProviderDelete,
+
+    /// A user registration token was created
+    #[serde(rename = "user_registration_token.add")]
+    UserRegistrationTokenAdd,
+
+    /// A user registration token was updated
+    #[serde(rename = "user_registration_token.update")]
+    UserRegistrationTokenUpdate,
+
+    /// A user registration token was revoked
+    #[serde(rename = "user_registration_token.revoke")]
+    UserRegistrationTokenRevoke,
+
+    /// A user registration token was unrevoked
+    #[serde(rename = "user_registration_token.unrevoke")]
+    UserRegistrationTokenUnrevoke,
+
+    /// The policy data was set
+    #[serde(rename = "policy_data.set")]
+    PolicyDataSet,
+}
+
+impl AuditAction {
+    /// All the known actions
+    pub const ALL: &[Self] = &[
+        Self::UserAdd,
+        Self::UserLock,
+        Self::UserUnlock,
+        Self::UserDeactivate,
+        Self::UserReactivate,
+        Self::UserSetAdmin,
+        Self::UserSetPassword,
+        Self::UserAllowCrossSigningReset,
+        Self::UserImpersonate,
+        Self::UserExport,
+        Self::UserErase,
+        Self::UserEmailAdd,
+        Self::UserEmailRemove,
+        Self::UserPasskeyAdd,
+        Self::UserPasskeyRemove,
+        Self::UserTotpAdd,
+        Self::UserTotpRemove,
+        Self::BrowserSessionFinish,
+        Self::CompatSessionFinish,
+        Self::OAuth2SessionAdd,
+        Self::OAuth2SessionFinish,
+        Self::PersonalSessionAdd,
+        Self::PersonalSessionRegenerate,
+        Self::PersonalSessionRevoke,
+        Self::UpstreamOAuthLinkAdd,
+        Self::UpstreamOAuthLinkRemove,
+        Self::OAuth2ClientAdd,
+        Self::OAuth2ClientDelete,
+        Self::OAuth2ClientRegenerateSecret,
+        Self::OAuth2ClientSetRedirectUris,
+        Self::UpstreamOAuthProviderAdd,
+        Self::UpstreamOAuthProviderUpdate,
+        Self::UpstreamOAuthProviderDisable,
+        Self::UpstreamOAuthProviderDelete,
+        Self::UserRegistrationTokenAdd,
+        Self::UserRegistrationTokenUpdate,
+        Self::UserRegistrationTokenRevoke,
+        Self::UserRegistrationTokenUnrevoke,
+        Self::PolicyDataSet,
+    ];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UserAdd => "user.add",
+            Self::UserLock => "user.lock",
+            Self::UserUnlock => "user.unlock",
+            Self::UserDeactivate => "user.deactivate",
+            Self::UserReactivate => "user.reactivate",
+            Self::UserSetAdmin => "user.set_admin",
+            Self::UserSetPassword => "user.set_password",
+            Self::UserAllowCrossSigningReset => "user.allow_cross_signing_reset",
+            Self::UserImpersonate => "user.impersonate",
+            Self::UserExport => "user.export",
+            Self::UserErase => "user.erase",
+            Self::UserEmailAdd => "user_email.add",
+            Self::UserEmailRemove => "user_email.remove",
+            Self::UserPasskeyAdd => "user_passkey.add",
+            Self::UserPasskeyRemove => "user_passkey.remove",
+            Self::UserTotpAdd => "user_totp.add",
+            Self::UserTotpRemove => "user_totp.remove",
+            Self::BrowserSessionFinish => "browser_session.finish",
+            Self::CompatSessionFinish => "compat_session.finish",
+            Self::OAuth2SessionAdd => "oauth2_session.add",
+            Self::OAuth2SessionFinish => "oauth2_session.finish",
+            Self::PersonalSessionAdd => "personal_session.add",
+            Self::PersonalSessionRegenerate => "personal_session.regenerate",
+            Self::PersonalSessionRevoke => "personal_session.revoke",
+            Self::UpstreamOAuthLinkAdd => "upstream_oauth_link.add",
+            Self::UpstreamOAuthLinkRemove => "upstream_oauth_link.remove",
+            Self::OAuth2ClientAdd => "oauth2_client.add",
+            Self::OAuth2ClientDelete => "oauth2_client.delete",
+            Self::OAuth2ClientRegenerateSecret => "oauth2_client.regenerate_secret",
+            Self::OAuth2ClientSetRedirectUris => "oauth2_client.set_redirect_uris",
+            Self::UpstreamOAuthProviderAdd => "upstream_oauth_provider.add",
+            Self::UpstreamOAuthProviderUpdate => "upstream_oauth_provider.update",
+            Self::UpstreamOAuthProviderDisable => "upstream_oauth_provider.disable",
+            Self::UpstreamOAuthProviderDelete => "upstream_oauth_provider.delete",
+            Self::UserRegistrationTokenAdd => "user_registration_token.add",
+            Self::UserRegistrationTokenUpdate => "user_registration_token.update",
+            Self::UserRegistrationTokenRevoke => "user_registration_token.revoke",
+            Self::UserRegistrationTokenUnrevoke => "user_registration_token.unrevoke",
+            Self::PolicyDataSet => "policy_data.set",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid audit action {0:?}")]
+pub struct InvalidAuditActionError(String);
+
+impl std::str::FromStr for AuditAction {
+    type Err = InvalidAuditActionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| InvalidAuditActionError(s.to_owned()))
+    }
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Who performed an audited action, and from where
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AuditActor {
+    /// The user who performed the action, if any. This is not set for actions
+    /// performed by OAuth 2.0 clients on their own behalf, or by the system.
+    pub user_id: Option<Ulid>,
+
+    /// The browser, OAuth 2.0 or personal session through which the action
+    /// was performed, if any
+    pub session_id: Option<Ulid>,
+
+    /// The IP address of the requester
+    pub ip_address: Option<IpAddr>,
+
+    /// The user agent of the requester
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEvent {
+    pub id: Ulid,
+    pub created_at: DateTime<Utc>,
+    pub action: AuditAction,
+    pub actor: AuditActor,
+
+    /// The user affected by the action, if any
+    pub target_user_id: Option<Ulid>,
+
+    /// The object affected by the action, if any. Depending on the action,
+    /// this is the ID of a user, a session, an email address, a client, etc.
+    pub target_id: Option<Ulid>,
+
+    /// Additional, action-specific details
+    pub details: serde_json::Value,
+}
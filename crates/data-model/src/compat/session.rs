@@ -11,7 +11,7 @@ use serde::Serialize;
 use ulid::Ulid;
 
 use super::Device;
-use crate::InvalidTransitionError;
+use crate::{Impersonation, InvalidTransitionError};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 pub enum CompatSessionState {
@@ -79,6 +79,9 @@ pub struct CompatSession {
     pub user_agent: Option<String>,
     pub last_active_at: Option<DateTime<Utc>>,
     pub last_active_ip: Option<IpAddr>,
+
+    /// Set if the session was created by an admin to impersonate the user
+    pub impersonation: Option<Impersonation>,
 }
 
 impl std::ops::Deref for CompatSession {
@@ -0,0 +1,33 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ulid::Ulid;
+
+/// Marks a session as created by an admin to impersonate its user
+///
+/// Impersonation sessions are time-boxed: once they expire, their tokens are
+/// no longer valid.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Impersonation {
+    /// The user who created the session, if the admin acted as a user
+    pub impersonator_user_id: Option<Ulid>,
+
+    /// The OAuth 2.0 client which created the session, if the admin acted as
+    /// a client
+    pub impersonator_client_id: Option<Ulid>,
+
+    /// When the impersonation ends
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Impersonation {
+    /// Returns `true` if the impersonation ended
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+}
@@ -8,8 +8,10 @@
 
 use thiserror::Error;
 
+pub(crate) mod audit_event;
 pub mod clock;
 pub(crate) mod compat;
+mod impersonation;
 pub mod oauth2;
 pub mod personal;
 pub(crate) mod policy_data;
@@ -20,6 +22,7 @@ pub(crate) mod user_agent;
 pub(crate) mod users;
 mod utils;
 mod version;
+pub(crate) mod webhook;
 
 /// Error when an invalid state transition is attempted.
 #[derive(Debug, Error)]
@@ -29,14 +32,17 @@ pub struct InvalidTransitionError;
 pub use ulid::Ulid;
 
 pub use self::{
+    audit_event::{AuditAction, AuditActor, AuditEvent, InvalidAuditActionError},
     clock::{Clock, SystemClock},
     compat::{
         CompatAccessToken, CompatRefreshToken, CompatRefreshTokenState, CompatSession,
         CompatSessionState, CompatSsoLogin, CompatSsoLoginState, Device, ToScopeTokenError,
     },
+    impersonation::Impersonation,
     oauth2::{
         AuthorizationCode, AuthorizationGrant, AuthorizationGrantStage, Client, DeviceCodeGrant,
-        DeviceCodeGrantState, InvalidRedirectUriError, JwksOrJwksUri, Pkce, Session, SessionState,
+        DeviceCodeGrantState, InvalidRedirectUriError, JwksOrJwksUri, Pkce,
+        PushedAuthorizationRequest, Session, SessionState,
     },
     policy_data::PolicyData,
     site_config::{CaptchaConfig, CaptchaService, SessionExpirationConfig, SiteConfig},
@@ -50,14 +56,21 @@ pub use self::{
         UpstreamOAuthProviderImportPreference, UpstreamOAuthProviderLocalpartPreference,
         UpstreamOAuthProviderOnBackchannelLogout, UpstreamOAuthProviderOnConflict,
         UpstreamOAuthProviderPkceMode, UpstreamOAuthProviderResponseMode,
-        UpstreamOAuthProviderSubjectPreference, UpstreamOAuthProviderTokenAuthMethod,
+        UpstreamOAuthProviderSamlSettings, UpstreamOAuthProviderSubjectPreference,
+        UpstreamOAuthProviderTokenAuthMethod,
     },
     user_agent::{DeviceType, UserAgent},
     users::{
-        Authentication, AuthenticationMethod, BrowserSession, Password, User, UserEmail,
-        UserEmailAuthentication, UserEmailAuthenticationCode, UserRecoverySession,
+        Authentication, AuthenticationMethod, BrowserSession, Password, SecurityNotification, User,
+        UserEmail, UserEmailAuthentication, UserEmailAuthenticationCode, UserExport, UserLdapLink,
+        UserPasskey, UserPasskeyChallenge, UserRecoveryCode, UserRecoverySession,
         UserRecoveryTicket, UserRegistration, UserRegistrationPassword, UserRegistrationToken,
+        UserTerms, UserTotp,
     },
     utils::{BoxClock, BoxRng},
     version::AppVersion,
+    webhook::{
+        InvalidWebhookEventError, WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryState,
+        WebhookEndpoint, WebhookEvent,
+    },
 };
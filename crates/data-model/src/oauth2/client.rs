@@ -28,6 +28,7 @@ pub enum JwksOrJwksUri {
     JwksUri(Url),
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Client {
     pub id: Ulid,
@@ -35,6 +36,10 @@ pub struct Client {
     /// Client identifier
     pub client_id: String,
 
+    /// Whether the client is defined in the configuration, rather than
+    /// registered at runtime
+    pub is_static: bool,
+
     /// Hash of the client metadata
     pub metadata_digest: Option<String>,
 
@@ -86,6 +91,29 @@ pub struct Client {
     /// URI using the https scheme that a third party can use to initiate a
     /// login by the RP
     pub initiate_login_uri: Option<Url>,
+
+    /// Array of URLs supplied by the RP to which it MAY request that the
+    /// End-User's User Agent be redirected after a logout has been performed
+    pub post_logout_redirect_uris: Vec<Url>,
+
+    /// URL that will cause the client to log itself out when sent a logout
+    /// token
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// Whether the client requires a `sid` claim in the logout token
+    pub backchannel_logout_session_required: bool,
+
+    /// Whether the client must send its authorization requests through the
+    /// pushed authorization request endpoint
+    pub require_pushed_authorization_requests: bool,
+
+    /// Whether the client must send its authorization requests as signed
+    /// request objects
+    pub require_signed_request_object: bool,
+
+    /// JWS alg algorithm used to sign the access tokens issued to this client.
+    /// If set, access tokens are JWTs instead of opaque strings.
+    pub access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
 }
 
 #[derive(Debug, Error)]
@@ -123,6 +151,13 @@ impl Client {
         }
     }
 
+    /// Whether the given URI is one of the registered post-logout redirect
+    /// URIs of this client.
+    #[must_use]
+    pub fn has_post_logout_redirect_uri(&self, uri: &Url) -> bool {
+        uri_matches_one_of(uri, &self.post_logout_redirect_uris)
+    }
+
     /// Create a client metadata object for this client
     #[must_use]
     pub fn into_metadata(self) -> ClientMetadata {
@@ -164,12 +199,24 @@ impl Client {
             require_auth_time: None,
             default_acr_values: None,
             request_uris: None,
-            require_signed_request_object: None,
-            require_pushed_authorization_requests: None,
+            require_signed_request_object: self.require_signed_request_object.then_some(true),
+            require_pushed_authorization_requests: self
+                .require_pushed_authorization_requests
+                .then_some(true),
             introspection_signed_response_alg: None,
             introspection_encrypted_response_alg: None,
             introspection_encrypted_response_enc: None,
-            post_logout_redirect_uris: None,
+            post_logout_redirect_uris: if self.post_logout_redirect_uris.is_empty() {
+                None
+            } else {
+                Some(self.post_logout_redirect_uris)
+            },
+            backchannel_logout_session_required: self
+                .backchannel_logout_uri
+                .is_some()
+                .then_some(self.backchannel_logout_session_required),
+            backchannel_logout_uri: self.backchannel_logout_uri,
+            access_token_signed_response_alg: self.access_token_signed_response_alg,
         }
     }
 
@@ -180,6 +227,7 @@ impl Client {
             Self {
                 id: Ulid::from_datetime_with_source(now.into(), rng),
                 client_id: "client1".to_owned(),
+                is_static: false,
                 metadata_digest: None,
                 encrypted_client_secret: None,
                 application_type: Some(ApplicationType::Web),
@@ -201,11 +249,22 @@ impl Client {
                 id_token_signed_response_alg: None,
                 userinfo_signed_response_alg: None,
                 jwks: None,
+                post_logout_redirect_uris: vec![
+                    Url::parse("https://client1.example.com/logged-out").unwrap(),
+                ],
+                backchannel_logout_uri: Some(
+                    Url::parse("https://client1.example.com/backchannel-logout").unwrap(),
+                ),
+                backchannel_logout_session_required: true,
+                require_pushed_authorization_requests: false,
+                require_signed_request_object: false,
+                access_token_signed_response_alg: None,
             },
             // Another client without any URIs set
             Self {
                 id: Ulid::from_datetime_with_source(now.into(), rng),
                 client_id: "client2".to_owned(),
+                is_static: false,
                 metadata_digest: None,
                 encrypted_client_secret: None,
                 application_type: Some(ApplicationType::Native),
@@ -222,6 +281,12 @@ impl Client {
                 id_token_signed_response_alg: None,
                 userinfo_signed_response_alg: None,
                 jwks: None,
+                post_logout_redirect_uris: Vec::new(),
+                backchannel_logout_uri: None,
+                backchannel_logout_session_required: false,
+                require_pushed_authorization_requests: true,
+                require_signed_request_object: false,
+                access_token_signed_response_alg: None,
             },
         ]
     }
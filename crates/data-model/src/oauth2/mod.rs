@@ -7,6 +7,7 @@
 mod authorization_grant;
 mod client;
 mod device_code_grant;
+mod pushed_authorization_request;
 mod session;
 
 pub use self::{
@@ -15,5 +16,6 @@ pub use self::{
     },
     client::{Client, InvalidRedirectUriError, JwksOrJwksUri},
     device_code_grant::{DeviceCodeGrant, DeviceCodeGrantState},
+    pushed_authorization_request::PushedAuthorizationRequest,
     session::{Session, SessionState},
 };
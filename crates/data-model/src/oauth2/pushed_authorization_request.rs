@@ -0,0 +1,50 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use ulid::Ulid;
+
+/// An authorization request which was pushed by a client to the [pushed
+/// authorization request endpoint], and which can be referenced by its
+/// `request_uri` in an authorization request.
+///
+/// [pushed authorization request endpoint]: https://www.rfc-editor.org/rfc/rfc9126.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushedAuthorizationRequest {
+    pub id: Ulid,
+    pub client_id: Ulid,
+
+    /// The `request_uri` handed out to the client
+    pub request_uri: String,
+
+    /// The parameters of the authorization request, as they were sent by the
+    /// client
+    pub parameters: BTreeMap<String, String>,
+
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+
+    /// When the request was used in an authorization request. A pushed
+    /// request can only be used once.
+    pub consumed_at: Option<DateTime<Utc>>,
+}
+
+impl PushedAuthorizationRequest {
+    /// The prefix of the `request_uri`s handed out to clients
+    pub const REQUEST_URI_PREFIX: &'static str = "urn:ietf:params:oauth:request_uri:";
+
+    /// How long a pushed authorization request can be used after being
+    /// created
+    pub const VALIDITY: chrono::Duration = chrono::Duration::seconds(60);
+
+    /// Returns `true` if the request can still be used
+    #[must_use]
+    pub fn is_valid(&self, now: DateTime<Utc>) -> bool {
+        self.consumed_at.is_none() && now < self.expires_at
+    }
+}
@@ -11,7 +11,7 @@ use oauth2_types::scope::Scope;
 use serde::Serialize;
 use ulid::Ulid;
 
-use crate::InvalidTransitionError;
+use crate::{Impersonation, InvalidTransitionError};
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 pub enum SessionState {
@@ -84,6 +84,12 @@ pub struct Session {
     pub last_active_at: Option<DateTime<Utc>>,
     pub last_active_ip: Option<IpAddr>,
     pub human_name: Option<String>,
+
+    /// The session this one was derived from through a token exchange, if any
+    pub parent_session_id: Option<Ulid>,
+
+    /// Set if the session was created by an admin to impersonate the user
+    pub impersonation: Option<Impersonation>,
 }
 
 impl std::ops::Deref for Session {
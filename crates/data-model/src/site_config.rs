@@ -7,6 +7,8 @@
 use chrono::Duration;
 use url::Url;
 
+use crate::WebhookEndpoint;
+
 /// Which Captcha service is being used
 #[derive(Debug, Clone, Copy)]
 pub enum CaptchaService {
@@ -99,4 +101,16 @@ pub struct SiteConfig {
 
     /// The iframe URL to show in the plan tab of the UI
     pub plan_management_iframe_uri: Option<String>,
+
+    /// Whether users can register passkeys and use them to log in.
+    pub passkeys_enabled: bool,
+
+    /// Whether users can enrol a TOTP authenticator app as a second factor.
+    pub totp_enabled: bool,
+
+    /// Time after which the personal data of deactivated users is erased.
+    pub deactivated_user_retention: Option<Duration>,
+
+    /// The endpoints to which lifecycle events are delivered.
+    pub webhooks: Vec<WebhookEndpoint>,
 }
@@ -19,6 +19,7 @@ pub use self::{
         OnBackchannelLogout as UpstreamOAuthProviderOnBackchannelLogout,
         OnConflict as UpstreamOAuthProviderOnConflict, PkceMode as UpstreamOAuthProviderPkceMode,
         ResponseMode as UpstreamOAuthProviderResponseMode,
+        SamlSettings as UpstreamOAuthProviderSamlSettings,
         SubjectPreference as UpstreamOAuthProviderSubjectPreference,
         TokenAuthMethod as UpstreamOAuthProviderTokenAuthMethod, UpstreamOAuthProvider,
     },
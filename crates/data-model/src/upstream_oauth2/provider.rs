@@ -261,6 +261,11 @@ pub struct InvalidUpstreamOAuth2OnBackchannelLogout(String);
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct UpstreamOAuthProvider {
     pub id: Ulid,
+
+    /// Whether the provider is defined in the configuration, rather than
+    /// created through the admin API
+    pub is_static: bool,
+
     pub issuer: Option<String>,
     pub human_name: Option<String>,
     pub brand_name: Option<String>,
@@ -285,6 +290,9 @@ pub struct UpstreamOAuthProvider {
     pub additional_authorization_parameters: Vec<(String, String)>,
     pub forward_login_hint: bool,
     pub on_backchannel_logout: OnBackchannelLogout,
+
+    /// If set, this provider speaks SAML 2.0 instead of OAuth 2.0/OIDC
+    pub saml: Option<SamlSettings>,
 }
 
 impl PartialOrd for UpstreamOAuthProvider {
@@ -305,6 +313,31 @@ impl UpstreamOAuthProvider {
     pub const fn enabled(&self) -> bool {
         self.disabled_at.is_none()
     }
+
+    /// Returns `true` if the provider is a SAML 2.0 identity provider
+    #[must_use]
+    pub const fn is_saml(&self) -> bool {
+        self.saml.is_some()
+    }
+}
+
+/// Settings for upstream providers speaking SAML 2.0
+///
+/// For those providers, the `issuer` is the entity ID of the identity
+/// provider, and the `client_id` is the entity ID we use as a service provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SamlSettings {
+    /// The URL of the single sign-on service of the identity provider, using
+    /// the HTTP-POST binding
+    pub sso_url: Url,
+
+    /// PEM-encoded X.509 certificates used by the identity provider to sign
+    /// its responses
+    pub certificates: Vec<String>,
+
+    /// The format of the name identifier to request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_id_format: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
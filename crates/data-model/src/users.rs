@@ -8,10 +8,12 @@ use std::net::IpAddr;
 
 use chrono::{DateTime, Utc};
 use rand::Rng;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use ulid::Ulid;
 use url::Url;
 
+use crate::{CompatSession, UpstreamOAuthProvider};
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct User {
     pub id: Ulid,
@@ -20,6 +22,11 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub locked_at: Option<DateTime<Utc>>,
     pub deactivated_at: Option<DateTime<Utc>>,
+
+    /// When the personal data of the user was erased. Only the username is
+    /// kept, so that it can't be registered again.
+    pub erased_at: Option<DateTime<Utc>>,
+
     pub can_request_admin: bool,
     pub is_guest: bool,
 }
@@ -57,6 +64,7 @@ impl User {
             created_at: now,
             locked_at: None,
             deactivated_at: None,
+            erased_at: None,
             can_request_admin: false,
             is_guest: false,
         }]
@@ -83,9 +91,133 @@ pub struct Authentication {
 pub enum AuthenticationMethod {
     Password { user_password_id: Ulid },
     UpstreamOAuth2 { upstream_oauth2_session_id: Ulid },
+    Passkey { user_passkey_id: Ulid },
+    Totp { user_totp_id: Ulid },
+    RecoveryCode { user_recovery_code_id: Ulid },
+    Ldap { user_ldap_link_id: Ulid },
     Unknown,
 }
 
+impl AuthenticationMethod {
+    /// The Authentication Method Reference values, as defined in RFC 8176,
+    /// describing this authentication method
+    #[must_use]
+    pub fn amr(&self) -> &'static [&'static str] {
+        match self {
+            // LDAP authentication is a bind with the user's password
+            Self::Password { .. } | Self::Ldap { .. } => &["pwd"],
+            // Proof-of-possession of the passkey, with a user presence test
+            Self::Passkey { .. } => &["pop", "user"],
+            // The second factor is always checked after the password
+            Self::Totp { .. } => &["pwd", "otp", "mfa"],
+            Self::RecoveryCode { .. } => &["pwd", "mfa"],
+            Self::UpstreamOAuth2 { .. } | Self::Unknown => &[],
+        }
+    }
+
+    /// Returns `true` if this authentication involved a second factor
+    #[must_use]
+    pub fn is_multi_factor(&self) -> bool {
+        matches!(self, Self::Totp { .. } | Self::RecoveryCode { .. })
+    }
+}
+
+/// A link between a user and an entry of the LDAP directory used to
+/// authenticate passwords
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserLdapLink {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    /// The stable identifier of the directory entry, rendered from its
+    /// attributes
+    pub subject: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A `WebAuthn` credential (passkey) registered by a user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserPasskey {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    /// The credential ID, as returned by the authenticator, base64url-encoded
+    pub credential_id: String,
+    pub name: String,
+    /// The transports hinted by the authenticator when registering
+    pub transports: Vec<String>,
+    /// The COSE-encoded public key of the credential
+    pub public_key: Vec<u8>,
+    /// The AAGUID of the authenticator which created the credential
+    pub aaguid: Option<String>,
+    pub sign_count: u32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A `WebAuthn` challenge, used either to register a new passkey or to
+/// authenticate with an existing one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserPasskeyChallenge {
+    pub id: Ulid,
+    /// The browser session which started the challenge, if any. This is set
+    /// when registering a new passkey
+    pub user_session_id: Option<Ulid>,
+    pub challenge: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl UserPasskeyChallenge {
+    /// How long a challenge is valid after being created
+    pub const VALIDITY: chrono::Duration = chrono::Duration::minutes(10);
+
+    /// Returns `true` if the challenge can still be used
+    #[must_use]
+    pub fn is_valid(&self, now: DateTime<Utc>) -> bool {
+        self.completed_at.is_none() && now < self.created_at + Self::VALIDITY
+    }
+}
+
+/// A TOTP secret, used by a user as a second authentication factor
+///
+/// The secret is unconfirmed until the user proves they can generate valid
+/// codes with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserTotp {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    /// The shared secret, encrypted with the site encryption key
+    #[serde(skip)]
+    pub encrypted_secret: String,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// The time step of the last code used. Codes from this step or earlier
+    /// ones are rejected, so that they can't be replayed
+    pub last_used_step: Option<i64>,
+}
+
+impl UserTotp {
+    /// Returns `true` if the enrolment was confirmed, and the secret should be
+    /// checked when logging in
+    #[must_use]
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmed_at.is_some()
+    }
+}
+
+/// A single-use code, which can be used in place of a TOTP code if the user
+/// lost access to their authenticator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserRecoveryCode {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    /// The code, encrypted with the site encryption key
+    #[serde(skip)]
+    pub encrypted_code: String,
+    pub created_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
 /// A session to recover a user if they have lost their credentials
 ///
 /// For each session intiated, there may be multiple [`UserRecoveryTicket`]s
@@ -211,6 +343,125 @@ impl UserEmail {
     }
 }
 
+/// The acceptance of a version of the terms of service by a user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserTerms {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    pub terms_url: Url,
+
+    /// When the user accepted the terms of service
+    pub created_at: DateTime<Utc>,
+}
+
+/// An export of everything stored about a user, for subject access requests
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UserExport {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    pub created_at: DateTime<Utc>,
+
+    /// When the archive was built, if it was
+    pub completed_at: Option<DateTime<Utc>>,
+
+    /// The archive, once built
+    pub archive: Option<serde_json::Value>,
+}
+
+impl UserExport {
+    /// Returns `true` if the archive was built
+    #[must_use]
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+}
+
+/// A security-relevant event on a user account, which the user is notified
+/// about by email
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SecurityNotification {
+    /// The password of the user was changed
+    PasswordChanged,
+
+    /// An email address was added to the account
+    EmailAdded { email: String },
+
+    /// An email address was removed from the account
+    EmailRemoved { email: String },
+
+    /// A new session was started, possibly from an unfamiliar device
+    NewSession {
+        session_id: Ulid,
+        user_agent: Option<String>,
+    },
+
+    /// The account was locked
+    AccountLocked,
+
+    /// The account was deactivated
+    AccountDeactivated,
+
+    /// An account from an upstream identity provider was linked
+    UpstreamAccountLinked { provider: String },
+}
+
+impl SecurityNotification {
+    /// A new browser session was started
+    #[must_use]
+    pub fn new_browser_session(session: &BrowserSession) -> Self {
+        Self::NewSession {
+            session_id: session.id,
+            user_agent: session.user_agent.clone(),
+        }
+    }
+
+    /// An upstream account from the given provider was linked
+    #[must_use]
+    pub fn upstream_account_linked(provider: &UpstreamOAuthProvider) -> Self {
+        let provider = provider
+            .human_name
+            .clone()
+            .or_else(|| provider.issuer.clone())
+            .unwrap_or_else(|| provider.id.to_string());
+        Self::UpstreamAccountLinked { provider }
+    }
+
+    /// A new compatibility session was started
+    #[must_use]
+    pub fn new_compat_session(session: &CompatSession) -> Self {
+        Self::NewSession {
+            session_id: session.id,
+            user_agent: session.user_agent.clone(),
+        }
+    }
+
+    #[must_use]
+    pub fn samples(now: chrono::DateTime<Utc>, rng: &mut impl Rng) -> Vec<Self> {
+        vec![
+            Self::PasswordChanged,
+            Self::EmailAdded {
+                email: "alice@example.com".to_owned(),
+            },
+            Self::EmailRemoved {
+                email: "alice@example.com".to_owned(),
+            },
+            Self::NewSession {
+                session_id: Ulid::from_datetime_with_source(now.into(), rng),
+                user_agent: Some(
+                    "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0"
+                        .to_owned(),
+                ),
+            },
+            Self::AccountLocked,
+            Self::AccountDeactivated,
+            Self::UpstreamAccountLinked {
+                provider: "Example".to_owned(),
+            },
+        ]
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct UserRegistrationPassword {
     pub hashed_password: String,
@@ -0,0 +1,187 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid::Ulid;
+use url::Url;
+
+/// A lifecycle event which can be delivered to external systems through
+/// webhooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    /// A user registered
+    #[serde(rename = "user.registered")]
+    UserRegistered,
+
+    /// A user was deactivated
+    #[serde(rename = "user.deactivated")]
+    UserDeactivated,
+
+    /// A user verified an email address
+    #[serde(rename = "user_email.verified")]
+    UserEmailVerified,
+
+    /// A user started a new session
+    #[serde(rename = "session.created")]
+    SessionCreated,
+}
+
+impl WebhookEvent {
+    /// All the known events
+    pub const ALL: &[Self] = &[
+        Self::UserRegistered,
+        Self::UserDeactivated,
+        Self::UserEmailVerified,
+        Self::SessionCreated,
+    ];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UserRegistered => "user.registered",
+            Self::UserDeactivated => "user.deactivated",
+            Self::UserEmailVerified => "user_email.verified",
+            Self::SessionCreated => "session.created",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid webhook event {0:?}")]
+pub struct InvalidWebhookEventError(String);
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = InvalidWebhookEventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|event| event.as_str() == s)
+            .ok_or_else(|| InvalidWebhookEventError(s.to_owned()))
+    }
+}
+
+impl std::fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An endpoint to which lifecycle events are delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookEndpoint {
+    /// The URL to which the events are sent
+    pub url: Url,
+
+    /// The secret used to sign the payloads
+    pub secret: String,
+
+    /// The events delivered to this endpoint. All events are delivered if
+    /// this is empty.
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhookEndpoint {
+    /// Whether the given event should be delivered to this endpoint
+    #[must_use]
+    pub fn is_subscribed_to(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryState {
+    /// The delivery is still being attempted
+    Pending,
+
+    /// The endpoint acknowledged the event
+    Delivered { delivered_at: DateTime<Utc> },
+
+    /// The delivery was abandoned
+    Failed { failed_at: DateTime<Utc> },
+}
+
+impl WebhookDeliveryState {
+    /// Returns `true` if the delivery is still being attempted
+    #[must_use]
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending)
+    }
+
+    /// Mark the delivery as delivered
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delivery is not pending
+    pub fn deliver(
+        self,
+        delivered_at: DateTime<Utc>,
+    ) -> Result<Self, crate::InvalidTransitionError> {
+        match self {
+            Self::Pending => Ok(Self::Delivered { delivered_at }),
+            Self::Delivered { .. } | Self::Failed { .. } => Err(crate::InvalidTransitionError),
+        }
+    }
+
+    /// Mark the delivery as failed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the delivery is not pending
+    pub fn fail(self, failed_at: DateTime<Utc>) -> Result<Self, crate::InvalidTransitionError> {
+        match self {
+            Self::Pending => Ok(Self::Failed { failed_at }),
+            Self::Delivered { .. } | Self::Failed { .. } => Err(crate::InvalidTransitionError),
+        }
+    }
+}
+
+/// The delivery of an event to a webhook endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookDelivery {
+    pub id: Ulid,
+    pub created_at: DateTime<Utc>,
+    pub state: WebhookDeliveryState,
+
+    /// The event being delivered
+    pub event: WebhookEvent,
+
+    /// The URL of the endpoint the event is delivered to
+    pub url: Url,
+
+    /// The JSON payload sent to the endpoint
+    pub payload: serde_json::Value,
+
+    /// The attempts made so far, oldest first
+    pub attempts: Vec<WebhookDeliveryAttempt>,
+}
+
+/// A single attempt at delivering an event to a webhook endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookDeliveryAttempt {
+    pub id: Ulid,
+    pub created_at: DateTime<Utc>,
+
+    /// The HTTP status code returned by the endpoint, if it responded
+    pub status_code: Option<u16>,
+
+    /// A description of why the attempt failed, if it did
+    pub error: Option<String>,
+}
+
+impl WebhookDeliveryAttempt {
+    /// Whether the endpoint acknowledged the event in this attempt
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+            && self
+                .status_code
+                .is_some_and(|code| (200..300).contains(&code))
+    }
+}
@@ -10,7 +10,10 @@ use lettre::{
     AsyncTransport, Message,
     message::{Mailbox, MessageBuilder, MultiPart},
 };
-use mas_templates::{EmailRecoveryContext, EmailVerificationContext, Templates, WithLanguage};
+use mas_templates::{
+    EmailRecoveryContext, EmailSecurityNotificationContext, EmailVerificationContext, Templates,
+    WithLanguage,
+};
 use thiserror::Error;
 
 use crate::MailTransport;
@@ -99,6 +102,34 @@ impl Mailer {
         Ok(message)
     }
 
+    fn prepare_security_notification_email(
+        &self,
+        to: Mailbox,
+        context: &WithLanguage<EmailSecurityNotificationContext>,
+    ) -> Result<Message, Error> {
+        let plain = self
+            .templates
+            .render_email_security_notification_txt(context)?;
+
+        let html = self
+            .templates
+            .render_email_security_notification_html(context)?;
+
+        let multipart = MultiPart::alternative_plain_html(plain, html);
+
+        let subject = self
+            .templates
+            .render_email_security_notification_subject(context)?;
+
+        let message = self
+            .base_message()
+            .subject(subject.trim())
+            .to(to)
+            .multipart(multipart)?;
+
+        Ok(message)
+    }
+
     /// Send the verification email to a user
     ///
     /// # Errors
@@ -147,6 +178,30 @@ impl Mailer {
         Ok(())
     }
 
+    /// Send a security notification email to a user
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the email failed rendering or failed sending
+    #[tracing::instrument(
+        name = "email.security_notification.send",
+        skip_all,
+        fields(
+            email.to = %to,
+            email.language = %context.language(),
+            user.id = %context.user().id,
+        ),
+    )]
+    pub async fn send_security_notification_email(
+        &self,
+        to: Mailbox,
+        context: &WithLanguage<EmailSecurityNotificationContext>,
+    ) -> Result<(), Error> {
+        let message = self.prepare_security_notification_email(to, context)?;
+        self.transport.send(message).await?;
+        Ok(())
+    }
+
     /// Test the connetion to the mail server
     ///
     /// # Errors
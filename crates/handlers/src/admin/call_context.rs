@@ -9,22 +9,26 @@ use std::convert::Infallible;
 use aide::OperationIo;
 use axum::{
     Json,
-    extract::FromRequestParts,
+    extract::{FromRef, FromRequestParts, MatchedPath, OriginalUri},
     response::{IntoResponse, Response},
 };
-use axum_extra::TypedHeader;
-use headers::{Authorization, authorization::Bearer};
-use hyper::StatusCode;
-use mas_axum_utils::record_error;
+use hyper::{StatusCode, header::USER_AGENT};
+use mas_axum_utils::{
+    dpop::{
+        AccessTokenAuthorization, AccessTokenAuthorizationRejection, DPoPError, DPoPReplayCache,
+    },
+    record_error,
+};
 use mas_data_model::{
-    BoxClock, Session, TokenFormatError, TokenType, User,
+    AuditActor, BoxClock, Session, TokenFormatError, TokenType, User,
     personal::session::{PersonalSession, PersonalSessionOwner},
 };
+use mas_router::UrlBuilder;
 use mas_storage::{BoxRepository, RepositoryError};
 use oauth2_types::scope::Scope;
 use ulid::Ulid;
 
-use super::response::ErrorResponse;
+use super::{response::ErrorResponse, scope};
 use crate::BoundActivityTracker;
 
 #[derive(Debug, thiserror::Error)]
@@ -37,6 +41,10 @@ pub enum Rejection {
     #[error("Invalid authorization header")]
     InvalidAuthorizationHeader,
 
+    /// The DPoP proof is missing or invalid for the access token
+    #[error("Invalid DPoP proof")]
+    InvalidDPoPProof(#[source] DPoPError),
+
     /// Couldn't load the database repository
     #[error("Couldn't load the database repository")]
     RepositorySetup(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
@@ -73,9 +81,14 @@ pub enum Rejection {
     #[error("Failed to load user {0}")]
     LoadUser(Ulid),
 
-    /// The session does not have the `urn:mas:admin` scope
+    /// The session does not have any of the admin scopes
     #[error("Missing urn:mas:admin scope")]
     MissingScope,
+
+    /// The session has admin scopes, but none of them grant access to this
+    /// endpoint
+    #[error("Insufficient scope for this endpoint")]
+    InsufficientScope,
 }
 
 impl IntoResponse for Rejection {
@@ -99,8 +112,11 @@ impl IntoResponse for Rejection {
             | Rejection::SessionRevoked
             | Rejection::UserLocked
             | Rejection::MissingScope
+            | Rejection::InvalidDPoPProof(_)
             | Rejection::InvalidAccessTokenType(_) => StatusCode::UNAUTHORIZED,
 
+            Rejection::InsufficientScope => StatusCode::FORBIDDEN,
+
             Rejection::RepositorySetup(_)
             | Rejection::Repository(_)
             | Rejection::LoadSession(_)
@@ -123,6 +139,9 @@ pub struct CallContext {
     pub clock: BoxClock,
     pub user: Option<User>,
     pub session: CallerSession,
+
+    /// The caller, as recorded in the audit log
+    pub actor: AuditActor,
 }
 
 impl<S> FromRequestParts<S> for CallContext
@@ -131,6 +150,8 @@ where
     BoundActivityTracker: FromRequestParts<S, Rejection = Infallible>,
     BoxRepository: FromRequestParts<S>,
     BoxClock: FromRequestParts<S, Rejection = Infallible>,
+    UrlBuilder: FromRef<S>,
+    DPoPReplayCache: FromRef<S>,
     <BoxRepository as FromRequestParts<S>>::Rejection:
         Into<Box<dyn std::error::Error + Send + Sync + 'static>>,
 {
@@ -150,19 +171,30 @@ where
             .map_err(Rejection::RepositorySetup)?;
 
         // Extract the access token from the authorization header
-        let token = TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+        let authorization = AccessTokenAuthorization::from_request_parts(parts, state)
             .await
-            .map_err(|e| {
-                // We map to two differentsson of errors depending on whether the header is
+            .map_err(|e| match e {
+                // We map to different kind of errors depending on whether the header is
                 // missing or invalid
-                if e.is_missing() {
-                    Rejection::MissingAuthorizationHeader
-                } else {
+                AccessTokenAuthorizationRejection::Missing => Rejection::MissingAuthorizationHeader,
+                AccessTokenAuthorizationRejection::InvalidHeader => {
                     Rejection::InvalidAuthorizationHeader
                 }
+                AccessTokenAuthorizationRejection::InvalidProof(e) => {
+                    Rejection::InvalidDPoPProof(e)
+                }
             })?;
 
-        let token = token.token();
+        // DPoP proofs are issued for the URL as seen by the client
+        let uri = parts
+            .extensions
+            .get::<OriginalUri>()
+            .map_or(&parts.uri, |OriginalUri(uri)| uri);
+        let mut url = UrlBuilder::from_ref(state).http_base();
+        url.set_path(uri.path());
+        let dpop_replay_cache = DPoPReplayCache::from_ref(state);
+
+        let token = authorization.token();
         let token_type = TokenType::check(token)?;
 
         let session = match token_type {
@@ -189,6 +221,16 @@ where
                     return Err(Rejection::TokenExpired);
                 }
 
+                // Check that the token is presented according to its DPoP binding
+                authorization
+                    .verify_binding(
+                        token.dpop_jkt.as_deref(),
+                        &url,
+                        clock.now(),
+                        &dpop_replay_cache,
+                    )
+                    .map_err(Rejection::InvalidDPoPProof)?;
+
                 // Record the activity on the session
                 activity_tracker
                     .record_oauth2_session(&clock, &session)
@@ -219,6 +261,11 @@ where
                     return Err(Rejection::TokenExpired);
                 }
 
+                // Personal access tokens are never bound to a DPoP key
+                authorization
+                    .verify_binding(None, &url, clock.now(), &dpop_replay_cache)
+                    .map_err(Rejection::InvalidDPoPProof)?;
+
                 // Check the validity of the owner of the personal session
                 match session.owner {
                     PersonalSessionOwner::User(owner_user_id) => {
@@ -280,17 +327,37 @@ where
             None
         };
 
-        // For now, we only check that the session has the admin scope
-        // Later we might want to check other route-specific scopes
-        if !session.scope().contains("urn:mas:admin") {
+        if !scope::has_any_admin_scope(session.scope()) {
             return Err(Rejection::MissingScope);
         }
 
+        // Check that one of the scopes grants access to this specific route. If
+        // for some reason we don't know the route, only full access is enough.
+        let route = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map_or("", MatchedPath::as_str);
+        if !scope::grants_access(session.scope(), &parts.method, route) {
+            return Err(Rejection::InsufficientScope);
+        }
+
+        let actor = AuditActor {
+            user_id: session.user_id(),
+            session_id: Some(session.id()),
+            ip_address: activity_tracker.ip(),
+            user_agent: parts
+                .headers
+                .get(USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned),
+        };
+
         Ok(Self {
             repo,
             clock,
             user,
             session,
+            actor,
         })
     }
 }
@@ -310,6 +377,13 @@ impl CallerSession {
         }
     }
 
+    pub fn id(&self) -> Ulid {
+        match self {
+            CallerSession::OAuth2Session(session) => session.id,
+            CallerSession::PersonalSession(session) => session.id,
+        }
+    }
+
     pub fn user_id(&self) -> Option<Ulid> {
         match self {
             CallerSession::OAuth2Session(session) => session.user_id,
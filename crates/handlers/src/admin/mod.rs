@@ -22,6 +22,7 @@ use indexmap::IndexMap;
 use mas_axum_utils::InternalError;
 use mas_data_model::{AppVersion, BoxRng, SiteConfig};
 use mas_http::CorsLayerExt;
+use mas_keystore::Encrypter;
 use mas_matrix::HomeserverConnection;
 use mas_policy::PolicyFactory;
 use mas_router::{
@@ -37,10 +38,12 @@ mod model;
 mod params;
 mod response;
 mod schema;
+mod scim;
+mod scope;
 mod v1;
 
 use self::call_context::CallContext;
-use crate::passwords::PasswordManager;
+use crate::{passwords::PasswordManager, upstream_oauth2::cache::MetadataCache};
 
 fn finish(t: TransformOpenApi) -> TransformOpenApi {
     t.title("Matrix Authentication Service admin API")
@@ -59,6 +62,11 @@ fn finish(t: TransformOpenApi) -> TransformOpenApi {
             description: Some("Manage the dynamic policy data".to_owned()),
             ..Tag::default()
         })
+        .tag(Tag {
+            name: "oauth2-client".to_owned(),
+            description: Some("Manage OAuth 2.0 clients".to_owned()),
+            ..Tag::default()
+        })
         .tag(Tag {
             name: "oauth2-session".to_owned(),
             description: Some("Manage OAuth2 sessions".to_owned()),
@@ -74,6 +82,11 @@ fn finish(t: TransformOpenApi) -> TransformOpenApi {
             description: Some("Manage emails associated with users".to_owned()),
             ..Tag::default()
         })
+        .tag(Tag {
+            name: "user-export".to_owned(),
+            description: Some("Export everything stored about users".to_owned()),
+            ..Tag::default()
+        })
         .tag(Tag {
             name: "user-session".to_owned(),
             description: Some("Manage browser sessions of users".to_owned()),
@@ -97,6 +110,16 @@ fn finish(t: TransformOpenApi) -> TransformOpenApi {
             description: Some("Manage upstream OAuth 2.0 providers".to_owned()),
             ..Tag::default()
         })
+        .tag(Tag {
+            name: "audit-event".to_owned(),
+            description: Some("Query the audit log of security-relevant actions".to_owned()),
+            ..Tag::default()
+        })
+        .tag(Tag {
+            name: "webhook-delivery".to_owned(),
+            description: Some("Inspect the deliveries of lifecycle events to webhooks".to_owned()),
+            ..Tag::default()
+        })
         .security_scheme("oauth2", oauth_security_scheme(None))
         .security_scheme(
             "token",
@@ -108,7 +131,7 @@ fn finish(t: TransformOpenApi) -> TransformOpenApi {
             },
         )
         .security_requirement_scopes("oauth2", ["urn:mas:admin"])
-        .security_requirement_scopes("bearer", ["urn:mas:admin"])
+        .security_requirement_scopes("token", ["urn:mas:admin"])
 }
 
 fn oauth_security_scheme(url_builder: Option<&UrlBuilder>) -> SecurityScheme {
@@ -128,10 +151,7 @@ fn oauth_security_scheme(url_builder: Option<&UrlBuilder>) -> SecurityScheme {
         )
     };
 
-    let scopes = IndexMap::from([(
-        "urn:mas:admin".to_owned(),
-        "Grant access to the admin API".to_owned(),
-    )]);
+    let scopes = self::scope::scheme_scopes();
 
     SecurityScheme::OAuth2 {
         flows: OAuth2Flows {
@@ -166,6 +186,8 @@ where
     Arc<PolicyFactory>: FromRef<S>,
     SiteConfig: FromRef<S>,
     AppVersion: FromRef<S>,
+    Encrypter: FromRef<S>,
+    MetadataCache: FromRef<S>,
 {
     // We *always* want to explicitly set the possible responses, beacuse the
     // infered ones are not necessarily correct
@@ -189,7 +211,12 @@ where
         .nest("/api/admin/v1", self::v1::router())
         .finish_api_with(&mut api, finish);
 
+    // Each operation documents the scopes which grant access to it
+    self::scope::document(&mut api);
+
     let router = router
+        // SCIM comes with its own schemas, so it isn't part of the OpenAPI spec
+        .nest(self::scim::BASE_PATH, self::scim::router())
         // Serve the OpenAPI spec as JSON
         .route(
             "/api/spec.json",
@@ -226,6 +253,8 @@ where
                     CONTENT_TYPE,
                     // Swagger will send this header, so we have to allow it to avoid CORS errors
                     HeaderName::from_static("x-requested-with"),
+                    // Clients send DPoP proofs in this header
+                    HeaderName::from_static("dpop"),
                 ]),
         );
 
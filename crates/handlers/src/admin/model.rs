@@ -8,14 +8,15 @@ use std::net::IpAddr;
 
 use chrono::{DateTime, Utc};
 use mas_data_model::{
-    Device,
+    AuditAction, Device, JwksOrJwksUri, WebhookDeliveryState, WebhookEvent,
     personal::{
         PersonalAccessToken as DataModelPersonalAccessToken,
         session::{PersonalSession as DataModelPersonalSession, PersonalSessionOwner},
     },
 };
+use mas_iana::oauth::OAuthClientAuthenticationMethod;
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ulid::Ulid;
 use url::Url;
@@ -57,6 +58,10 @@ pub struct User {
     /// When the user was deactivated. If null, the user is not deactivated.
     deactivated_at: Option<DateTime<Utc>>,
 
+    /// When the personal data of the user was erased. If null, the user was
+    /// not erased.
+    erased_at: Option<DateTime<Utc>>,
+
     /// Whether the user can request admin privileges.
     admin: bool,
 
@@ -74,6 +79,7 @@ impl User {
                 created_at: DateTime::default(),
                 locked_at: None,
                 deactivated_at: None,
+                erased_at: None,
                 admin: false,
                 legacy_guest: false,
             },
@@ -83,6 +89,7 @@ impl User {
                 created_at: DateTime::default(),
                 locked_at: None,
                 deactivated_at: None,
+                erased_at: None,
                 admin: true,
                 legacy_guest: false,
             },
@@ -92,6 +99,7 @@ impl User {
                 created_at: DateTime::default(),
                 locked_at: Some(DateTime::default()),
                 deactivated_at: None,
+                erased_at: None,
                 admin: false,
                 legacy_guest: true,
             },
@@ -107,6 +115,7 @@ impl From<mas_data_model::User> for User {
             created_at: user.created_at,
             locked_at: user.locked_at,
             deactivated_at: user.deactivated_at,
+            erased_at: user.erased_at,
             admin: user.can_request_admin,
             legacy_guest: user.is_guest,
         }
@@ -723,6 +732,11 @@ pub struct UpstreamOAuthProvider {
 
     /// When the provider was disabled. If null, the provider is enabled.
     disabled_at: Option<DateTime<Utc>>,
+
+    /// Whether the provider is defined in the configuration file. Those can't
+    /// be modified through the API.
+    #[serde(rename = "static")]
+    is_static: bool,
 }
 
 impl From<mas_data_model::UpstreamOAuthProvider> for UpstreamOAuthProvider {
@@ -734,6 +748,7 @@ impl From<mas_data_model::UpstreamOAuthProvider> for UpstreamOAuthProvider {
             brand_name: provider.brand_name,
             created_at: provider.created_at,
             disabled_at: provider.disabled_at,
+            is_static: provider.is_static,
         }
     }
 }
@@ -758,6 +773,7 @@ impl UpstreamOAuthProvider {
                 brand_name: Some("google".to_owned()),
                 created_at: DateTime::default(),
                 disabled_at: None,
+                is_static: true,
             },
             Self {
                 id: Ulid::from_bytes([0x02; 16]),
@@ -766,6 +782,7 @@ impl UpstreamOAuthProvider {
                 brand_name: Some("apple".to_owned()),
                 created_at: DateTime::default(),
                 disabled_at: Some(DateTime::default()),
+                is_static: true,
             },
             Self {
                 id: Ulid::from_bytes([0x03; 16]),
@@ -774,11 +791,143 @@ impl UpstreamOAuthProvider {
                 brand_name: None,
                 created_at: DateTime::default(),
                 disabled_at: None,
+                is_static: false,
             },
         ]
     }
 }
 
+/// An OAuth 2.0 client
+#[derive(Serialize, JsonSchema)]
+pub struct OAuth2Client {
+    #[serde(skip)]
+    id: Ulid,
+
+    /// The client ID used in OAuth 2.0 requests
+    client_id: String,
+
+    /// Whether the client is defined in the configuration file. Those can't be
+    /// modified through the API, and clients created through the API are never
+    /// static.
+    #[serde(rename = "static")]
+    is_static: bool,
+
+    /// When the client was created
+    created_at: DateTime<Utc>,
+
+    /// A human-readable name for the client
+    client_name: Option<String>,
+
+    /// The URL of the client's website
+    client_uri: Option<Url>,
+
+    /// The method the client uses to authenticate to the token endpoint. If
+    /// null, the client is a public client.
+    token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
+
+    /// The grant types the client is allowed to use
+    grant_types: Vec<String>,
+
+    /// The redirect URIs registered for the client
+    redirect_uris: Vec<Url>,
+
+    /// The URL of the client's JSON Web Key Set
+    jwks_uri: Option<Url>,
+
+    /// The client secret (only returned on creation and when regenerated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret: Option<String>,
+}
+
+impl From<mas_data_model::Client> for OAuth2Client {
+    fn from(client: mas_data_model::Client) -> Self {
+        let jwks_uri = match client.jwks {
+            Some(JwksOrJwksUri::JwksUri(uri)) => Some(uri),
+            _ => None,
+        };
+
+        Self {
+            id: client.id,
+            client_id: client.client_id,
+            is_static: client.is_static,
+            created_at: client.id.datetime().into(),
+            client_name: client.client_name,
+            client_uri: client.client_uri,
+            token_endpoint_auth_method: client.token_endpoint_auth_method,
+            grant_types: client.grant_types.iter().map(ToString::to_string).collect(),
+            redirect_uris: client.redirect_uris,
+            jwks_uri,
+            // If relevant, the caller will populate using `with_client_secret`
+            // afterwards.
+            client_secret: None,
+        }
+    }
+}
+
+impl Resource for OAuth2Client {
+    const KIND: &'static str = "oauth2-client";
+    const PATH: &'static str = "/api/admin/v1/oauth2-clients";
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+impl OAuth2Client {
+    /// Samples of OAuth 2.0 clients
+    pub fn samples() -> [Self; 3] {
+        [
+            Self {
+                id: Ulid::from_bytes([0x01; 16]),
+                client_id: Ulid::from_bytes([0x01; 16]).to_string(),
+                is_static: true,
+                created_at: DateTime::default(),
+                client_name: Some("Synapse".to_owned()),
+                client_uri: None,
+                token_endpoint_auth_method: Some(
+                    OAuthClientAuthenticationMethod::ClientSecretBasic,
+                ),
+                grant_types: vec!["client_credentials".to_owned()],
+                redirect_uris: Vec::new(),
+                jwks_uri: None,
+                client_secret: None,
+            },
+            Self {
+                id: Ulid::from_bytes([0x02; 16]),
+                client_id: Ulid::from_bytes([0x02; 16]).to_string(),
+                is_static: false,
+                created_at: DateTime::default(),
+                client_name: Some("Element".to_owned()),
+                client_uri: Some(Url::parse("https://element.example.com/").unwrap()),
+                token_endpoint_auth_method: Some(OAuthClientAuthenticationMethod::None),
+                grant_types: vec!["authorization_code".to_owned(), "refresh_token".to_owned()],
+                redirect_uris: vec![Url::parse("https://element.example.com/callback").unwrap()],
+                jwks_uri: None,
+                client_secret: None,
+            },
+            Self {
+                id: Ulid::from_bytes([0x03; 16]),
+                client_id: Ulid::from_bytes([0x03; 16]).to_string(),
+                is_static: false,
+                created_at: DateTime::default(),
+                client_name: Some("Backend service".to_owned()),
+                client_uri: None,
+                token_endpoint_auth_method: Some(OAuthClientAuthenticationMethod::PrivateKeyJwt),
+                grant_types: vec!["client_credentials".to_owned()],
+                redirect_uris: Vec::new(),
+                jwks_uri: Some(Url::parse("https://service.example.com/jwks.json").unwrap()),
+                client_secret: None,
+            },
+        ]
+    }
+
+    /// Add the client secret (for use in creation responses)
+    pub fn with_client_secret(mut self, client_secret: String) -> Self {
+        self.client_secret = Some(client_secret);
+        self
+    }
+}
+
 /// An error that shouldn't happen in practice, but suggests database
 /// inconsistency.
 #[derive(Debug, Error)]
@@ -954,3 +1103,451 @@ impl PersonalSession {
         self
     }
 }
+
+/// The kind of session an admin can start to impersonate a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImpersonationKind {
+    /// An OAuth 2.0 session
+    OAuth2,
+
+    /// A compatibility session, for legacy clients
+    Compat,
+}
+
+/// A session started by an admin to impersonate a user
+#[derive(Serialize, JsonSchema)]
+pub struct ImpersonationSession {
+    #[serde(skip)]
+    id: Ulid,
+
+    /// The kind of session which was started
+    kind: ImpersonationKind,
+
+    /// The ID of the impersonated user
+    #[schemars(with = "super::schema::Ulid")]
+    user_id: Ulid,
+
+    /// The ID of the admin user who started the session, if any
+    #[schemars(with = "Option<super::schema::Ulid>")]
+    impersonator_user_id: Option<Ulid>,
+
+    /// The ID of the `OAuth2` client which started the session, if any
+    #[schemars(with = "Option<super::schema::Ulid>")]
+    impersonator_client_id: Option<Ulid>,
+
+    /// The `OAuth2` scopes of the session, for OAuth 2.0 sessions
+    scope: Option<String>,
+
+    /// The Matrix device ID of the session, for compatibility sessions
+    #[schemars(with = "super::schema::Device")]
+    device_id: Option<Device>,
+
+    /// When the session was created
+    created_at: DateTime<Utc>,
+
+    /// When the impersonation ends. The access token stops working after
+    /// this time.
+    expires_at: DateTime<Utc>,
+
+    /// The access token of the session
+    access_token: String,
+}
+
+impl ImpersonationSession {
+    /// Build the resource from an OAuth 2.0 session and its access token
+    pub fn from_oauth2_session(
+        session: &mas_data_model::Session,
+        impersonation: &mas_data_model::Impersonation,
+        user_id: Ulid,
+        access_token: String,
+    ) -> Self {
+        Self {
+            id: session.id,
+            kind: ImpersonationKind::OAuth2,
+            user_id,
+            impersonator_user_id: impersonation.impersonator_user_id,
+            impersonator_client_id: impersonation.impersonator_client_id,
+            scope: Some(session.scope.to_string()),
+            device_id: None,
+            created_at: session.created_at,
+            expires_at: impersonation.expires_at,
+            access_token,
+        }
+    }
+
+    /// Build the resource from a compatibility session and its access token
+    pub fn from_compat_session(
+        session: mas_data_model::CompatSession,
+        impersonation: &mas_data_model::Impersonation,
+        access_token: String,
+    ) -> Self {
+        Self {
+            id: session.id,
+            kind: ImpersonationKind::Compat,
+            user_id: session.user_id,
+            impersonator_user_id: impersonation.impersonator_user_id,
+            impersonator_client_id: impersonation.impersonator_client_id,
+            scope: None,
+            device_id: session.device,
+            created_at: session.created_at,
+            expires_at: impersonation.expires_at,
+            access_token,
+        }
+    }
+
+    /// Sample impersonation session for documentation/testing
+    pub fn sample() -> Self {
+        Self {
+            id: Ulid::from_bytes([0x01; 16]),
+            kind: ImpersonationKind::Compat,
+            user_id: Ulid::from_bytes([0x01; 16]),
+            impersonator_user_id: Some(Ulid::from_bytes([0x02; 16])),
+            impersonator_client_id: Some(Ulid::from_bytes([0x03; 16])),
+            scope: None,
+            device_id: Some("AABBCCDDEE".to_owned().into()),
+            created_at: DateTime::default(),
+            expires_at: DateTime::default() + chrono::Duration::hours(1),
+            access_token: "mct_EXAMPLEdH6BsV1yXAiJ5nKLQOXeC7m_7fG1p0".to_owned(),
+        }
+    }
+}
+
+impl Resource for ImpersonationSession {
+    const KIND: &'static str = "impersonation-session";
+    // Impersonation sessions are regular sessions, so they link to the
+    // endpoints of the kind of session they are
+    const PATH: &'static str = "/api/admin/v1/oauth2-sessions";
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+
+    fn path(&self) -> String {
+        match self.kind {
+            ImpersonationKind::OAuth2 => format!("{}/{}", OAuth2Session::PATH, self.id),
+            ImpersonationKind::Compat => format!("{}/{}", CompatSession::PATH, self.id),
+        }
+    }
+}
+
+/// An event recorded in the audit log
+#[derive(Serialize, JsonSchema)]
+pub struct AuditEvent {
+    #[serde(skip)]
+    id: Ulid,
+
+    /// When the action was performed
+    created_at: DateTime<Utc>,
+
+    /// The action which was performed
+    #[schemars(with = "super::schema::AuditAction")]
+    action: AuditAction,
+
+    /// The ID of the user who performed the action, if any
+    #[schemars(with = "Option<super::schema::Ulid>")]
+    actor_user_id: Option<Ulid>,
+
+    /// The ID of the session through which the action was performed, if any
+    #[schemars(with = "Option<super::schema::Ulid>")]
+    actor_session_id: Option<Ulid>,
+
+    /// The IP address of the requester, if known
+    actor_ip_address: Option<IpAddr>,
+
+    /// The user agent of the requester, if known
+    actor_user_agent: Option<String>,
+
+    /// The ID of the user affected by the action, if any
+    #[schemars(with = "Option<super::schema::Ulid>")]
+    target_user_id: Option<Ulid>,
+
+    /// The ID of the object affected by the action, if any
+    #[schemars(with = "Option<super::schema::Ulid>")]
+    target_id: Option<Ulid>,
+
+    /// Additional, action-specific details
+    details: serde_json::Value,
+}
+
+impl Resource for AuditEvent {
+    const KIND: &'static str = "audit-event";
+    const PATH: &'static str = "/api/admin/v1/audit-events";
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+impl From<mas_data_model::AuditEvent> for AuditEvent {
+    fn from(event: mas_data_model::AuditEvent) -> Self {
+        Self {
+            id: event.id,
+            created_at: event.created_at,
+            action: event.action,
+            actor_user_id: event.actor.user_id,
+            actor_session_id: event.actor.session_id,
+            actor_ip_address: event.actor.ip_address,
+            actor_user_agent: event.actor.user_agent,
+            target_user_id: event.target_user_id,
+            target_id: event.target_id,
+            details: event.details,
+        }
+    }
+}
+
+impl AuditEvent {
+    pub fn samples() -> [Self; 2] {
+        [
+            Self {
+                id: Ulid::from_bytes([0x01; 16]),
+                created_at: DateTime::default(),
+                action: AuditAction::UserLock,
+                actor_user_id: Some(Ulid::from_bytes([0x02; 16])),
+                actor_session_id: Some(Ulid::from_bytes([0x03; 16])),
+                actor_ip_address: Some("127.0.0.1".parse().unwrap()),
+                actor_user_agent: Some("curl/8.5.0".to_owned()),
+                target_user_id: Some(Ulid::from_bytes([0x04; 16])),
+                target_id: Some(Ulid::from_bytes([0x04; 16])),
+                details: serde_json::json!({}),
+            },
+            Self {
+                id: Ulid::from_bytes([0x05; 16]),
+                created_at: DateTime::default(),
+                action: AuditAction::UserEmailAdd,
+                actor_user_id: Some(Ulid::from_bytes([0x04; 16])),
+                actor_session_id: Some(Ulid::from_bytes([0x06; 16])),
+                actor_ip_address: None,
+                actor_user_agent: None,
+                target_user_id: Some(Ulid::from_bytes([0x04; 16])),
+                target_id: Some(Ulid::from_bytes([0x07; 16])),
+                details: serde_json::json!({ "email": "alice@example.com" }),
+            },
+        ]
+    }
+}
+
+/// The outcome of a single attempt at delivering a webhook event
+#[derive(Serialize, JsonSchema)]
+pub struct WebhookDeliveryAttempt {
+    /// When the attempt was made
+    created_at: DateTime<Utc>,
+
+    /// The HTTP status code returned by the endpoint, if it responded
+    status_code: Option<u16>,
+
+    /// Why the attempt failed, if it did
+    error: Option<String>,
+}
+
+impl From<mas_data_model::WebhookDeliveryAttempt> for WebhookDeliveryAttempt {
+    fn from(attempt: mas_data_model::WebhookDeliveryAttempt) -> Self {
+        Self {
+            created_at: attempt.created_at,
+            status_code: attempt.status_code,
+            error: attempt.error,
+        }
+    }
+}
+
+/// The delivery of a lifecycle event to a webhook endpoint
+#[derive(Serialize, JsonSchema)]
+pub struct WebhookDelivery {
+    #[serde(skip)]
+    id: Ulid,
+
+    /// When the event was recorded for delivery
+    created_at: DateTime<Utc>,
+
+    /// The event being delivered
+    #[schemars(with = "super::schema::WebhookEvent")]
+    event: WebhookEvent,
+
+    /// The URL of the endpoint the event is delivered to
+    url: Url,
+
+    /// The JSON payload sent to the endpoint
+    payload: serde_json::Value,
+
+    /// When the endpoint acknowledged the event, if it did
+    delivered_at: Option<DateTime<Utc>>,
+
+    /// When the delivery was abandoned, if it was
+    failed_at: Option<DateTime<Utc>>,
+
+    /// The attempts made so far, oldest first
+    attempts: Vec<WebhookDeliveryAttempt>,
+}
+
+impl Resource for WebhookDelivery {
+    const KIND: &'static str = "webhook-delivery";
+    const PATH: &'static str = "/api/admin/v1/webhook-deliveries";
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+impl From<mas_data_model::WebhookDelivery> for WebhookDelivery {
+    fn from(delivery: mas_data_model::WebhookDelivery) -> Self {
+        let (delivered_at, failed_at) = match delivery.state {
+            WebhookDeliveryState::Pending => (None, None),
+            WebhookDeliveryState::Delivered { delivered_at } => (Some(delivered_at), None),
+            WebhookDeliveryState::Failed { failed_at } => (None, Some(failed_at)),
+        };
+
+        Self {
+            id: delivery.id,
+            created_at: delivery.created_at,
+            event: delivery.event,
+            url: delivery.url,
+            payload: delivery.payload,
+            delivered_at,
+            failed_at,
+            attempts: delivery.attempts.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl WebhookDelivery {
+    pub fn samples() -> [Self; 2] {
+        [
+            Self {
+                id: Ulid::from_bytes([0x01; 16]),
+                created_at: DateTime::default(),
+                event: WebhookEvent::UserRegistered,
+                url: "https://hooks.example.com/mas".parse().unwrap(),
+                payload: serde_json::json!({
+                    "id": Ulid::from_bytes([0x02; 16]),
+                    "type": "user.registered",
+                    "created_at": DateTime::<Utc>::default(),
+                    "data": {
+                        "user_id": Ulid::from_bytes([0x03; 16]),
+                        "username": "alice",
+                    },
+                }),
+                delivered_at: Some(DateTime::default()),
+                failed_at: None,
+                attempts: vec![WebhookDeliveryAttempt {
+                    created_at: DateTime::default(),
+                    status_code: Some(204),
+                    error: None,
+                }],
+            },
+            Self {
+                id: Ulid::from_bytes([0x04; 16]),
+                created_at: DateTime::default(),
+                event: WebhookEvent::UserDeactivated,
+                url: "https://hooks.example.com/mas".parse().unwrap(),
+                payload: serde_json::json!({
+                    "id": Ulid::from_bytes([0x05; 16]),
+                    "type": "user.deactivated",
+                    "created_at": DateTime::<Utc>::default(),
+                    "data": {
+                        "user_id": Ulid::from_bytes([0x03; 16]),
+                        "username": "alice",
+                    },
+                }),
+                delivered_at: None,
+                failed_at: None,
+                attempts: vec![WebhookDeliveryAttempt {
+                    created_at: DateTime::default(),
+                    status_code: Some(503),
+                    error: Some("Endpoint responded with 503 Service Unavailable".to_owned()),
+                }],
+            },
+        ]
+    }
+}
+
+/// An export of everything stored about a user
+#[derive(Serialize, JsonSchema)]
+pub struct UserExport {
+    #[serde(skip)]
+    id: Ulid,
+
+    /// The ID of the exported user
+    #[schemars(with = "super::schema::Ulid")]
+    user_id: Ulid,
+
+    /// When the export was requested
+    created_at: DateTime<Utc>,
+
+    /// When the archive was built. If null, the export is still pending.
+    completed_at: Option<DateTime<Utc>>,
+
+    /// The archive of everything stored about the user, once the export is
+    /// completed. See the admin API documentation for its format.
+    archive: Option<serde_json::Value>,
+}
+
+impl Resource for UserExport {
+    const KIND: &'static str = "user-export";
+    const PATH: &'static str = "/api/admin/v1/user-exports";
+
+    fn id(&self) -> Ulid {
+        self.id
+    }
+}
+
+impl From<mas_data_model::UserExport> for UserExport {
+    fn from(export: mas_data_model::UserExport) -> Self {
+        Self {
+            id: export.id,
+            user_id: export.user_id,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            archive: export.archive,
+        }
+    }
+}
+
+impl UserExport {
+    pub fn samples() -> [Self; 2] {
+        [
+            Self {
+                id: Ulid::from_bytes([0x01; 16]),
+                user_id: Ulid::from_bytes([0x02; 16]),
+                created_at: DateTime::default(),
+                completed_at: Some(DateTime::default()),
+                archive: Some(serde_json::json!({
+                    "version": 1,
+                    "exported_at": DateTime::<Utc>::default(),
+                    "user": {
+                        "id": Ulid::from_bytes([0x02; 16]),
+                        "username": "alice",
+                        "created_at": DateTime::<Utc>::default(),
+                        "locked_at": null,
+                        "deactivated_at": null,
+                        "can_request_admin": false,
+                        "is_guest": false,
+                    },
+                    "emails": [{
+                        "id": Ulid::from_bytes([0x03; 16]),
+                        "email": "alice@example.com",
+                        "created_at": DateTime::<Utc>::default(),
+                    }],
+                    "passwords": [],
+                    "passkeys": [],
+                    "totp": null,
+                    "ldap_link": null,
+                    "upstream_oauth_links": [],
+                    "terms": [],
+                    "registration": null,
+                    "browser_sessions": [],
+                    "oauth2_sessions": [],
+                    "compat_sessions": [],
+                    "personal_sessions": [],
+                    "audit_events": [],
+                })),
+            },
+            Self {
+                id: Ulid::from_bytes([0x04; 16]),
+                user_id: Ulid::from_bytes([0x02; 16]),
+                created_at: DateTime::default(),
+                completed_at: None,
+                archive: None,
+            },
+        ]
+    }
+}
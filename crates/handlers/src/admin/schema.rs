@@ -57,3 +57,53 @@ impl JsonSchema for Device {
         })
     }
 }
+
+/// A type to use for schema definitions of audit log actions
+///
+/// Use with `#[schemars(with = "crate::admin::schema::AuditAction")]`
+pub struct AuditAction;
+
+impl JsonSchema for AuditAction {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("AuditAction")
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let actions: Vec<&str> = mas_data_model::AuditAction::ALL
+            .iter()
+            .map(|action| action.as_str())
+            .collect();
+
+        json_schema!({
+            "type": "string",
+            "title": "Audit action",
+            "description": "The kind of action recorded in the audit log",
+            "enum": actions,
+        })
+    }
+}
+
+/// A type to use for schema definitions of webhook events
+///
+/// Use with `#[schemars(with = "crate::admin::schema::WebhookEvent")]`
+pub struct WebhookEvent;
+
+impl JsonSchema for WebhookEvent {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("WebhookEvent")
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        let events: Vec<&str> = mas_data_model::WebhookEvent::ALL
+            .iter()
+            .map(|event| event.as_str())
+            .collect();
+
+        json_schema!({
+            "type": "string",
+            "title": "Webhook event",
+            "description": "The kind of lifecycle event delivered to webhook endpoints",
+            "enum": events,
+        })
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A parser for the subset of SCIM filters identity providers use to look up
+//! users before provisioning them
+
+/// A filter on the `/Users` collection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserFilter {
+    /// `userName eq "alice"`
+    UserName(String),
+
+    /// `emails eq "alice@example.com"` or `emails.value eq "alice@example.com"`
+    Email(String),
+}
+
+/// Parse a SCIM filter expression
+///
+/// Only equality on `userName` and `emails` is supported, which is what
+/// identity providers use to find out whether a user already exists. Returns
+/// [`None`] for anything else.
+pub fn parse(filter: &str) -> Option<UserFilter> {
+    let filter = filter.trim();
+
+    let (attribute, rest) = filter.split_once(char::is_whitespace)?;
+    let (operator, value) = rest.trim_start().split_once(char::is_whitespace)?;
+
+    if !operator.eq_ignore_ascii_case("eq") {
+        return None;
+    }
+
+    let value = parse_string(value.trim())?;
+
+    // Attribute names are case insensitive, and may be prefixed by the schema URN
+    let attribute = attribute
+        .strip_prefix("urn:ietf:params:scim:schemas:core:2.0:User:")
+        .unwrap_or(attribute);
+
+    if attribute.eq_ignore_ascii_case("userName") {
+        Some(UserFilter::UserName(value))
+    } else if attribute.eq_ignore_ascii_case("emails")
+        || attribute.eq_ignore_ascii_case("emails.value")
+    {
+        Some(UserFilter::Email(value))
+    } else {
+        None
+    }
+}
+
+/// Parse a JSON string literal, as used for values in SCIM filters
+fn parse_string(value: &str) -> Option<String> {
+    if !value.starts_with('"') {
+        return None;
+    }
+
+    serde_json::from_str(value).ok()
+}
+
+/// Parse a `emails[value eq "alice@example.com"]` PATCH path, returning the
+/// email address
+pub fn parse_email_value_path(path: &str) -> Option<String> {
+    let (attribute, rest) = path.split_once('[')?;
+    if !attribute.trim().eq_ignore_ascii_case("emails") {
+        return None;
+    }
+
+    let inner = rest.strip_suffix(']')?;
+    let (attribute, rest) = inner.trim().split_once(char::is_whitespace)?;
+    if !attribute.eq_ignore_ascii_case("value") {
+        return None;
+    }
+
+    let (operator, value) = rest.trim_start().split_once(char::is_whitespace)?;
+    if !operator.eq_ignore_ascii_case("eq") {
+        return None;
+    }
+
+    parse_string(value.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse(r#"userName eq "alice""#),
+            Some(UserFilter::UserName("alice".to_owned()))
+        );
+        assert_eq!(
+            parse(r#"  username EQ "alice"  "#),
+            Some(UserFilter::UserName("alice".to_owned()))
+        );
+        assert_eq!(
+            parse(r#"urn:ietf:params:scim:schemas:core:2.0:User:userName eq "alice""#),
+            Some(UserFilter::UserName("alice".to_owned()))
+        );
+        assert_eq!(
+            parse(r#"emails eq "alice@example.com""#),
+            Some(UserFilter::Email("alice@example.com".to_owned()))
+        );
+        assert_eq!(
+            parse(r#"emails.value eq "alice@example.com""#),
+            Some(UserFilter::Email("alice@example.com".to_owned()))
+        );
+        assert_eq!(
+            parse(r#"userName eq "with \"quotes\"""#),
+            Some(UserFilter::UserName(r#"with "quotes""#.to_owned()))
+        );
+
+        assert_eq!(parse(r#"userName sw "al""#), None);
+        assert_eq!(parse(r#"displayName eq "Alice""#), None);
+        assert_eq!(parse("userName eq alice"), None);
+        assert_eq!(parse(r#"userName eq "alice" and active eq true"#), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_parse_email_value_path() {
+        assert_eq!(
+            parse_email_value_path(r#"emails[value eq "alice@example.com"]"#),
+            Some("alice@example.com".to_owned())
+        );
+        assert_eq!(parse_email_value_path(r#"emails[type eq "work"]"#), None);
+        assert_eq!(parse_email_value_path("emails"), None);
+    }
+}
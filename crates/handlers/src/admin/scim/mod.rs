@@ -0,0 +1,275 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A [SCIM 2.0] provisioning API for users, for identity providers which
+//! drive accounts from an HR system
+//!
+//! It is authenticated like the rest of the admin API, but isn't part of its
+//! `OpenAPI` description, as SCIM comes with its own schemas.
+//!
+//! [SCIM 2.0]: https://datatracker.ietf.org/doc/html/rfc7644
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{FromRef, FromRequestParts, rejection::JsonRejection},
+    http::HeaderValue,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use axum_extra::extract::QueryRejection;
+use chrono::{DateTime, Utc};
+use hyper::{StatusCode, header::CONTENT_TYPE};
+use mas_axum_utils::record_error;
+use mas_data_model::{BoxRng, User, UserEmail};
+use mas_matrix::HomeserverConnection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use ulid::Ulid;
+
+use super::call_context::CallContext;
+use crate::impl_from_error_for_route;
+
+mod filter;
+mod users;
+
+const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+const SERVICE_PROVIDER_CONFIG_SCHEMA: &str =
+    "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+
+/// The base path of the SCIM API
+pub const BASE_PATH: &str = "/api/admin/scim/v2";
+
+/// The maximum number of resources returned in a single list response
+const MAX_RESULTS: usize = 100;
+
+pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+    Arc<dyn HomeserverConnection>: FromRef<S>,
+    BoxRng: FromRequestParts<S>,
+    CallContext: FromRequestParts<S>,
+{
+    Router::new()
+        .route("/ServiceProviderConfig", get(service_provider_config))
+        .route("/Users", get(users::list).post(users::create))
+        .route(
+            "/Users/{id}",
+            get(users::get)
+                .put(users::replace)
+                .patch(users::patch)
+                .delete(users::delete),
+        )
+}
+
+/// A JSON response with the `application/scim+json` content type
+struct ScimJson<T>(T);
+
+impl<T: Serialize> IntoResponse for ScimJson<T> {
+    fn into_response(self) -> Response {
+        let mut response = Json(self.0).into_response();
+        response.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/scim+json"),
+        );
+        response
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error(transparent)]
+    Homeserver(anyhow::Error),
+
+    #[error("Resource {0} not found")]
+    NotFound(String),
+
+    #[error("User {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
+
+    #[error("Invalid request body")]
+    InvalidBody(#[from] JsonRejection),
+
+    #[error("Invalid query parameters")]
+    InvalidQuery(#[from] QueryRejection),
+
+    #[error("Username is not valid")]
+    UsernameNotValid,
+
+    #[error("User already exists")]
+    UserAlreadyExists,
+
+    #[error("Username is reserved by the homeserver")]
+    UsernameReserved,
+
+    #[error("The userName attribute can't be changed")]
+    UsernameImmutable,
+
+    #[error("Email {0:?} is not valid")]
+    EmailNotValid(String),
+
+    #[error("Email {0:?} is already in use")]
+    EmailAlreadyInUse(String),
+
+    #[error("Unsupported filter {0:?}")]
+    InvalidFilter(String),
+
+    #[error("Unsupported path {0:?}")]
+    InvalidPath(String),
+
+    #[error("Invalid value for {0:?}")]
+    InvalidValue(String),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl RouteError {
+    /// The `scimType` of the error, as defined in RFC 7644, section 3.12
+    fn scim_type(&self) -> Option<&'static str> {
+        match self {
+            Self::Internal(_) | Self::Homeserver(_) | Self::NotFound(_) | Self::AdminUser(_) => {
+                None
+            }
+            Self::InvalidBody(_) => Some("invalidSyntax"),
+            Self::InvalidQuery(_)
+            | Self::UsernameNotValid
+            | Self::EmailNotValid(_)
+            | Self::InvalidValue(_) => Some("invalidValue"),
+            Self::UserAlreadyExists | Self::UsernameReserved | Self::EmailAlreadyInUse(_) => {
+                Some("uniqueness")
+            }
+            Self::UsernameImmutable => Some("mutability"),
+            Self::InvalidFilter(_) => Some("invalidFilter"),
+            Self::InvalidPath(_) => Some("invalidPath"),
+        }
+    }
+}
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> Response {
+        let sentry_event_id = record_error!(self, Self::Internal(_) | Self::Homeserver(_));
+        let status = match self {
+            Self::Internal(_) | Self::Homeserver(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
+            Self::UserAlreadyExists | Self::UsernameReserved | Self::EmailAlreadyInUse(_) => {
+                StatusCode::CONFLICT
+            }
+            Self::InvalidBody(_)
+            | Self::InvalidQuery(_)
+            | Self::UsernameNotValid
+            | Self::UsernameImmutable
+            | Self::EmailNotValid(_)
+            | Self::InvalidFilter(_)
+            | Self::InvalidPath(_)
+            | Self::InvalidValue(_) => StatusCode::BAD_REQUEST,
+        };
+
+        let mut body = json!({
+            "schemas": [ERROR_SCHEMA],
+            "status": status.as_str(),
+            "detail": self.to_string(),
+        });
+        if let Some(scim_type) = self.scim_type() {
+            body["scimType"] = scim_type.into();
+        }
+
+        (status, sentry_event_id, ScimJson(body)).into_response()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    resource_type: &'static str,
+    created: DateTime<Utc>,
+    location: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Email {
+    value: String,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    primary: bool,
+}
+
+/// A user, as represented in SCIM
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScimUser {
+    schemas: [&'static str; 1],
+    id: String,
+    user_name: String,
+    active: bool,
+    emails: Vec<Email>,
+    meta: Meta,
+}
+
+impl ScimUser {
+    fn new(user: &User, emails: Vec<UserEmail>) -> Self {
+        let emails = emails
+            .into_iter()
+            .enumerate()
+            .map(|(index, email)| Email {
+                value: email.email,
+                primary: index == 0,
+            })
+            .collect();
+
+        Self {
+            schemas: [USER_SCHEMA],
+            id: user.id.to_string(),
+            user_name: user.username.clone(),
+            active: user.is_valid(),
+            emails,
+            meta: Meta {
+                resource_type: "User",
+                created: user.created_at,
+                location: format!("{BASE_PATH}/Users/{}", user.id),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListResponse<T> {
+    schemas: [&'static str; 1],
+    total_results: usize,
+    start_index: usize,
+    items_per_page: usize,
+    #[serde(rename = "Resources")]
+    resources: Vec<T>,
+}
+
+/// Advertise the features supported by this SCIM implementation
+async fn service_provider_config() -> impl IntoResponse {
+    ScimJson(json!({
+        "schemas": [SERVICE_PROVIDER_CONFIG_SCHEMA],
+        "patch": { "supported": true },
+        "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+        "filter": { "supported": true, "maxResults": MAX_RESULTS },
+        "changePassword": { "supported": false },
+        "sort": { "supported": false },
+        "etag": { "supported": false },
+        "authenticationSchemes": [{
+            "type": "oauthbearertoken",
+            "name": "OAuth Bearer Token",
+            "description": "An access token with the urn:mas:admin scope",
+        }],
+        "meta": {
+            "resourceType": "ServiceProviderConfig",
+            "location": format!("{BASE_PATH}/ServiceProviderConfig"),
+        },
+    }))
+}
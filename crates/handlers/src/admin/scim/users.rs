@@ -0,0 +1,941 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    Json,
+    extract::{Path, State, rejection::JsonRejection},
+    response::IntoResponse,
+};
+use axum_extra::extract::{Query, QueryRejection};
+use hyper::{StatusCode, header::LOCATION};
+use mas_data_model::{
+    AuditAction, AuditActor, BoxClock, BoxRng, SecurityNotification, User, UserEmail,
+};
+use mas_matrix::HomeserverConnection;
+use mas_storage::{
+    BoxRepository, Pagination,
+    queue::{
+        DeactivateUserJob, DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        SendSecurityNotificationJob,
+    },
+    user::{UserEmailFilter, UserFilter},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::{info, warn};
+use ulid::Ulid;
+
+use super::{
+    Email, LIST_RESPONSE_SCHEMA, ListResponse, MAX_RESULTS, PATCH_OP_SCHEMA, RouteError, ScimJson,
+    ScimUser,
+    filter::{self, UserFilter as ScimUserFilter},
+};
+use crate::admin::{
+    call_context::{CallContext, CallerSession},
+    scope,
+    v1::users::username_valid,
+};
+
+#[derive(Deserialize)]
+struct Name {
+    formatted: Option<String>,
+}
+
+/// The body of `POST /Users` and `PUT /Users/{id}` requests
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserRequest {
+    user_name: String,
+
+    #[serde(default)]
+    active: Option<bool>,
+
+    #[serde(default)]
+    display_name: Option<String>,
+
+    #[serde(default)]
+    name: Option<Name>,
+
+    #[serde(default)]
+    emails: Vec<Email>,
+}
+
+impl UserRequest {
+    /// The display name of the user, from either `displayName` or
+    /// `name.formatted`
+    fn display_name(&self) -> Option<String> {
+        self.display_name
+            .clone()
+            .or_else(|| self.name.as_ref().and_then(|name| name.formatted.clone()))
+    }
+
+    fn emails(&self) -> Vec<String> {
+        self.emails
+            .iter()
+            .map(|email| email.value.clone())
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct PatchOperation {
+    op: String,
+
+    #[serde(default)]
+    path: Option<String>,
+
+    #[serde(default)]
+    value: Option<Value>,
+}
+
+/// The body of `PATCH /Users/{id}` requests
+#[derive(Deserialize)]
+pub struct PatchRequest {
+    #[serde(default)]
+    schemas: Vec<String>,
+
+    #[serde(rename = "Operations")]
+    operations: Vec<PatchOperation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListParams {
+    filter: Option<String>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+}
+
+/// Parse a boolean, which some identity providers send as a string
+fn parse_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(b) => Some(*b),
+        Value::String(s) if s.eq_ignore_ascii_case("true") => Some(true),
+        Value::String(s) if s.eq_ignore_ascii_case("false") => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a list of emails, or a single email object
+fn parse_emails(value: &Value) -> Option<Vec<String>> {
+    let emails: Vec<Email> = match value {
+        Value::Array(_) => serde_json::from_value(value.clone()).ok()?,
+        Value::Object(_) => vec![serde_json::from_value(value.clone()).ok()?],
+        _ => return None,
+    };
+
+    Some(emails.into_iter().map(|email| email.value).collect())
+}
+
+async fn lookup_user(repo: &mut BoxRepository, id: &str) -> Result<User, RouteError> {
+    let not_found = || RouteError::NotFound(id.to_owned());
+    let user_id = Ulid::from_str(id).map_err(|_| not_found())?;
+    repo.user().lookup(user_id).await?.ok_or_else(not_found)
+}
+
+/// Look up a user to modify, making sure the caller is allowed to manage them
+async fn lookup_managed_user(
+    repo: &mut BoxRepository,
+    session: &CallerSession,
+    id: &str,
+) -> Result<User, RouteError> {
+    let user = lookup_user(repo, id).await?;
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(user.id));
+    }
+    Ok(user)
+}
+
+async fn load_emails(repo: &mut BoxRepository, user: &User) -> Result<Vec<UserEmail>, RouteError> {
+    let mut emails = Vec::new();
+    let mut cursor = Pagination::first(100);
+    loop {
+        let page = repo
+            .user_email()
+            .list(UserEmailFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            emails.push(edge.node);
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    Ok(emails)
+}
+
+/// Lock or unlock a user so that it matches the `active` attribute
+async fn set_active(
+    repo: &mut BoxRepository,
+    rng: &mut BoxRng,
+    clock: &BoxClock,
+    actor: &AuditActor,
+    homeserver: &dyn HomeserverConnection,
+    mut user: User,
+    active: bool,
+) -> Result<User, RouteError> {
+    if active == user.is_valid() {
+        return Ok(user);
+    }
+
+    if !active {
+        user = repo.user().lock(clock, user).await?;
+
+        repo.audit_event()
+            .add(
+                rng,
+                clock,
+                actor.clone(),
+                AuditAction::UserLock,
+                Some(user.id),
+                Some(user.id),
+                json!({}),
+            )
+            .await?;
+
+        repo.queue_job()
+            .schedule_job(
+                rng,
+                clock,
+                SendSecurityNotificationJob::new(&user, SecurityNotification::AccountLocked),
+            )
+            .await?;
+
+        return Ok(user);
+    }
+
+    if user.deactivated_at.is_some() {
+        homeserver
+            .reactivate_user(&user.username)
+            .await
+            .map_err(RouteError::Homeserver)?;
+
+        user = repo.user().reactivate(user).await?;
+
+        repo.audit_event()
+            .add(
+                rng,
+                clock,
+                actor.clone(),
+                AuditAction::UserReactivate,
+                Some(user.id),
+                Some(user.id),
+                json!({}),
+            )
+            .await?;
+    }
+
+    if user.locked_at.is_some() {
+        user = repo.user().unlock(user).await?;
+
+        repo.audit_event()
+            .add(
+                rng,
+                clock,
+                actor.clone(),
+                AuditAction::UserUnlock,
+                Some(user.id),
+                Some(user.id),
+                json!({}),
+            )
+            .await?;
+    }
+
+    Ok(user)
+}
+
+/// Add and remove email addresses so that the user has exactly the given
+/// ones
+async fn set_emails(
+    repo: &mut BoxRepository,
+    rng: &mut BoxRng,
+    clock: &BoxClock,
+    actor: &AuditActor,
+    user: &User,
+    emails: &[String],
+) -> Result<(), RouteError> {
+    let current = load_emails(repo, user).await?;
+
+    for user_email in &current {
+        if emails
+            .iter()
+            .any(|email| email.eq_ignore_ascii_case(&user_email.email))
+        {
+            continue;
+        }
+
+        repo.audit_event()
+            .add(
+                rng,
+                clock,
+                actor.clone(),
+                AuditAction::UserEmailRemove,
+                Some(user.id),
+                Some(user_email.id),
+                json!({ "email": user_email.email }),
+            )
+            .await?;
+
+        repo.queue_job()
+            .schedule_job(
+                rng,
+                clock,
+                SendSecurityNotificationJob::new(
+                    user,
+                    SecurityNotification::EmailRemoved {
+                        email: user_email.email.clone(),
+                    },
+                ),
+            )
+            .await?;
+
+        repo.user_email().remove(user_email.clone()).await?;
+    }
+
+    for email in emails {
+        if current
+            .iter()
+            .any(|user_email| user_email.email.eq_ignore_ascii_case(email))
+        {
+            continue;
+        }
+
+        if lettre::Address::from_str(email).is_err() {
+            return Err(RouteError::EmailNotValid(email.clone()));
+        }
+
+        let count = repo
+            .user_email()
+            .count(UserEmailFilter::new().for_email(email))
+            .await?;
+        if count > 0 {
+            return Err(RouteError::EmailAlreadyInUse(email.clone()));
+        }
+
+        let user_email = repo
+            .user_email()
+            .add(rng, clock, user, email.clone())
+            .await?;
+
+        repo.audit_event()
+            .add(
+                rng,
+                clock,
+                actor.clone(),
+                AuditAction::UserEmailAdd,
+                Some(user.id),
+                Some(user_email.id),
+                json!({ "email": user_email.email }),
+            )
+            .await?;
+
+        repo.queue_job()
+            .schedule_job(
+                rng,
+                clock,
+                SendSecurityNotificationJob::new(
+                    user,
+                    SecurityNotification::EmailAdded {
+                        email: user_email.email,
+                    },
+                ),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Schedule a job to sync the user and its emails with the homeserver
+async fn provision(
+    repo: &mut BoxRepository,
+    rng: &mut BoxRng,
+    clock: &BoxClock,
+    user: &User,
+    display_name: Option<String>,
+) -> Result<(), RouteError> {
+    let mut job = ProvisionUserJob::new(user);
+    if let Some(display_name) = display_name {
+        job = job.set_display_name(display_name);
+    }
+    repo.queue_job().schedule_job(rng, clock, job).await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "handler.admin.scim.users.list", skip_all)]
+pub async fn list(
+    CallContext { mut repo, .. }: CallContext,
+    query: Result<Query<ListParams>, QueryRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    let Query(params) = query?;
+    let start_index = params.start_index.unwrap_or(1).max(1);
+    let count = params.count.unwrap_or(MAX_RESULTS).min(MAX_RESULTS);
+
+    let (total_results, users) = if let Some(filter) = params.filter {
+        let filter =
+            filter::parse(&filter).ok_or_else(|| RouteError::InvalidFilter(filter.clone()))?;
+
+        let mut users = Vec::new();
+        match filter {
+            ScimUserFilter::UserName(username) => {
+                if let Some(user) = repo.user().find_by_username(&username).await? {
+                    users.push(user);
+                }
+            }
+            ScimUserFilter::Email(email) => {
+                let page = repo
+                    .user_email()
+                    .list(
+                        UserEmailFilter::new().for_email(&email),
+                        Pagination::first(MAX_RESULTS),
+                    )
+                    .await?;
+                for edge in page.edges {
+                    if let Some(user) = repo.user().lookup(edge.node.user_id).await? {
+                        users.push(user);
+                    }
+                }
+            }
+        }
+
+        let total_results = users.len();
+        let users = users
+            .into_iter()
+            .skip(start_index - 1)
+            .take(count)
+            .collect();
+        (total_results, users)
+    } else {
+        let filter = UserFilter::new();
+        let total_results = repo.user().count(filter).await?;
+
+        // SCIM paginates with offsets, so walk through the pages until we reach
+        // the requested index
+        let mut users = Vec::with_capacity(count);
+        let mut to_skip = start_index - 1;
+        let mut cursor = Pagination::first(MAX_RESULTS);
+        'pages: while users.len() < count {
+            let page = repo.user().list(filter, cursor).await?;
+
+            for edge in page.edges {
+                cursor = cursor.after(edge.cursor);
+                if to_skip > 0 {
+                    to_skip -= 1;
+                } else if users.len() < count {
+                    users.push(edge.node);
+                } else {
+                    break 'pages;
+                }
+            }
+
+            if !page.has_next_page {
+                break;
+            }
+        }
+
+        (total_results, users)
+    };
+
+    let mut resources = Vec::with_capacity(users.len());
+    for user in users {
+        let emails = load_emails(&mut repo, &user).await?;
+        resources.push(ScimUser::new(&user, emails));
+    }
+
+    Ok(ScimJson(ListResponse {
+        schemas: [LIST_RESPONSE_SCHEMA],
+        total_results,
+        start_index,
+        items_per_page: resources.len(),
+        resources,
+    }))
+}
+
+#[tracing::instrument(name = "handler.admin.scim.users.get", skip_all, fields(user.id = %id))]
+pub async fn get(
+    CallContext { mut repo, .. }: CallContext,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, RouteError> {
+    let user = lookup_user(&mut repo, &id).await?;
+    let emails = load_emails(&mut repo, &user).await?;
+
+    Ok(ScimJson(ScimUser::new(&user, emails)))
+}
+
+#[tracing::instrument(name = "handler.admin.scim.users.create", skip_all)]
+pub async fn create(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    mut rng: BoxRng,
+    State(homeserver): State<Arc<dyn HomeserverConnection>>,
+    body: Result<Json<UserRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    let Json(params) = body?;
+
+    if repo.user().exists(&params.user_name).await? {
+        return Err(RouteError::UserAlreadyExists);
+    }
+
+    if !username_valid(&params.user_name) {
+        return Err(RouteError::UsernameNotValid);
+    }
+
+    let available = homeserver
+        .is_localpart_available(&params.user_name)
+        .await
+        .map_err(RouteError::Homeserver)?;
+    if !available {
+        return Err(RouteError::UsernameReserved);
+    }
+
+    let display_name = params.display_name();
+    let emails = params.emails();
+
+    let user = repo.user().add(&mut rng, &clock, params.user_name).await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor.clone(),
+            AuditAction::UserAdd,
+            Some(user.id),
+            Some(user.id),
+            json!({ "scim": true }),
+        )
+        .await?;
+
+    set_emails(&mut repo, &mut rng, &clock, &actor, &user, &emails).await?;
+
+    let user = match params.active {
+        Some(active) => {
+            set_active(
+                &mut repo,
+                &mut rng,
+                &clock,
+                &actor,
+                &*homeserver,
+                user,
+                active,
+            )
+            .await?
+        }
+        None => user,
+    };
+
+    provision(&mut repo, &mut rng, &clock, &user, display_name).await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::user_registered(&user),
+        )
+        .await?;
+
+    let emails = load_emails(&mut repo, &user).await?;
+
+    repo.save().await?;
+
+    info!(%user.id, "Provisioned user through SCIM");
+
+    let resource = ScimUser::new(&user, emails);
+    let location = resource.meta.location.clone();
+    Ok((
+        StatusCode::CREATED,
+        [(LOCATION, location)],
+        ScimJson(resource),
+    ))
+}
+
+#[tracing::instrument(name = "handler.admin.scim.users.replace", skip_all, fields(user.id = %id))]
+pub async fn replace(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
+    }: CallContext,
+    mut rng: BoxRng,
+    State(homeserver): State<Arc<dyn HomeserverConnection>>,
+    Path(id): Path<String>,
+    body: Result<Json<UserRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    let Json(params) = body?;
+    let user = lookup_managed_user(&mut repo, &session, &id).await?;
+
+    if params.user_name != user.username {
+        return Err(RouteError::UsernameImmutable);
+    }
+
+    let emails = params.emails();
+    set_emails(&mut repo, &mut rng, &clock, &actor, &user, &emails).await?;
+
+    let user = match params.active {
+        Some(active) => {
+            set_active(
+                &mut repo,
+                &mut rng,
+                &clock,
+                &actor,
+                &*homeserver,
+                user,
+                active,
+            )
+            .await?
+        }
+        None => user,
+    };
+
+    provision(&mut repo, &mut rng, &clock, &user, params.display_name()).await?;
+
+    let emails = load_emails(&mut repo, &user).await?;
+
+    repo.save().await?;
+
+    Ok(ScimJson(ScimUser::new(&user, emails)))
+}
+
+#[tracing::instrument(name = "handler.admin.scim.users.patch", skip_all, fields(user.id = %id))]
+pub async fn patch(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
+    }: CallContext,
+    mut rng: BoxRng,
+    State(homeserver): State<Arc<dyn HomeserverConnection>>,
+    Path(id): Path<String>,
+    body: Result<Json<PatchRequest>, JsonRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    let Json(params) = body?;
+    if !params.schemas.is_empty() && !params.schemas.iter().any(|s| s == PATCH_OP_SCHEMA) {
+        return Err(RouteError::InvalidValue("schemas".to_owned()));
+    }
+
+    let user = lookup_managed_user(&mut repo, &session, &id).await?;
+
+    // Accumulate the changes, and apply them at the end
+    let mut emails: Vec<String> = load_emails(&mut repo, &user)
+        .await?
+        .into_iter()
+        .map(|user_email| user_email.email)
+        .collect();
+    let mut active = None;
+    let mut display_name = None;
+
+    for operation in params.operations {
+        let op = operation.op.to_ascii_lowercase();
+        let value = operation.value.unwrap_or(Value::Null);
+        let explicit_path = operation.path.is_some();
+
+        // Without a path, the value is an object with the attributes to change
+        let attributes: Vec<(String, Value)> = match operation.path {
+            Some(path) => vec![(path, value)],
+            None => match value {
+                Value::Object(map) => map.into_iter().collect(),
+                _ => return Err(RouteError::InvalidValue("value".to_owned())),
+            },
+        };
+
+        for (path, value) in attributes {
+            match (op.as_str(), path.as_str()) {
+                ("add" | "replace", "active") => {
+                    active = Some(
+                        parse_bool(&value).ok_or_else(|| RouteError::InvalidValue(path.clone()))?,
+                    );
+                }
+
+                ("add" | "replace", "displayName" | "name.formatted") => {
+                    let Value::String(value) = value else {
+                        return Err(RouteError::InvalidValue(path));
+                    };
+                    display_name = Some(value);
+                }
+
+                ("add" | "replace", "userName") => {
+                    if value.as_str() != Some(user.username.as_str()) {
+                        return Err(RouteError::UsernameImmutable);
+                    }
+                }
+
+                ("add", "emails") => {
+                    let new = parse_emails(&value).ok_or(RouteError::InvalidValue(path))?;
+                    for email in new {
+                        if !emails.iter().any(|e| e.eq_ignore_ascii_case(&email)) {
+                            emails.push(email);
+                        }
+                    }
+                }
+
+                ("replace", "emails") => {
+                    emails = parse_emails(&value).ok_or(RouteError::InvalidValue(path))?;
+                }
+
+                ("remove", "emails") => emails.clear(),
+
+                ("remove", path) if path.starts_with("emails[") => {
+                    let email = filter::parse_email_value_path(path)
+                        .ok_or_else(|| RouteError::InvalidPath(path.to_owned()))?;
+                    emails.retain(|e| !e.eq_ignore_ascii_case(&email));
+                }
+
+                ("add" | "replace" | "remove", _) => {
+                    // Identity providers send attributes we don't store, like
+                    // `externalId`, as part of full updates
+                    if explicit_path {
+                        return Err(RouteError::InvalidPath(path));
+                    }
+                    warn!(%path, "Ignoring unsupported SCIM attribute");
+                }
+
+                _ => return Err(RouteError::InvalidValue("op".to_owned())),
+            }
+        }
+    }
+
+    set_emails(&mut repo, &mut rng, &clock, &actor, &user, &emails).await?;
+
+    let user = match active {
+        Some(active) => {
+            set_active(
+                &mut repo,
+                &mut rng,
+                &clock,
+                &actor,
+                &*homeserver,
+                user,
+                active,
+            )
+            .await?
+        }
+        None => user,
+    };
+
+    provision(&mut repo, &mut rng, &clock, &user, display_name).await?;
+
+    let emails = load_emails(&mut repo, &user).await?;
+
+    repo.save().await?;
+
+    Ok(ScimJson(ScimUser::new(&user, emails)))
+}
+
+#[tracing::instrument(name = "handler.admin.scim.users.delete", skip_all, fields(user.id = %id))]
+pub async fn delete(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
+    }: CallContext,
+    mut rng: BoxRng,
+    Path(id): Path<String>,
+) -> Result<StatusCode, RouteError> {
+    let user = lookup_managed_user(&mut repo, &session, &id).await?;
+
+    // Deleting a user through SCIM deactivates it, like the admin API does
+    if user.deactivated_at.is_none() {
+        let user = repo.user().deactivate(&clock, user).await?;
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                actor,
+                AuditAction::UserDeactivate,
+                Some(user.id),
+                Some(user.id),
+                json!({ "erase": true, "scim": true }),
+            )
+            .await?;
+
+        info!(%user.id, "Scheduling deactivation of user");
+        repo.queue_job()
+            .schedule_job(&mut rng, &clock, DeactivateUserJob::new(&user, true))
+            .await?;
+
+        repo.save().await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_storage::{RepositoryAccess, user::UserEmailFilter};
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_provisioning_lifecycle(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        // Create a user
+        let request = Request::post("/api/admin/scim/v2/Users")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "schemas": ["urn:ietf:params:scim:schemas:core:2.0:User"],
+                "userName": "alice",
+                "displayName": "Alice",
+                "emails": [{ "value": "alice@example.com", "primary": true }],
+                "active": true,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["id"].as_str().unwrap().to_owned();
+        assert_eq!(body["userName"], "alice");
+        assert_eq!(body["active"], true);
+        assert_eq!(body["emails"][0]["value"], "alice@example.com");
+        assert_eq!(
+            body["meta"]["location"],
+            format!("/api/admin/scim/v2/Users/{id}")
+        );
+
+        // Creating it again conflicts
+        let request = Request::post("/api/admin/scim/v2/Users")
+            .bearer(&token)
+            .json(serde_json::json!({ "userName": "alice" }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CONFLICT);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["scimType"], "uniqueness");
+
+        // Look it up by userName
+        let request = Request::get("/api/admin/scim/v2/Users?filter=userName%20eq%20%22alice%22")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["totalResults"], 1);
+        assert_eq!(body["Resources"][0]["id"], id);
+
+        // Swap its email and disable it
+        let request = Request::patch(format!("/api/admin/scim/v2/Users/{id}"))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    { "op": "Remove", "path": "emails[value eq \"alice@example.com\"]" },
+                    { "op": "Add", "path": "emails", "value": [{ "value": "alice@example.org" }] },
+                    { "op": "Replace", "value": { "active": "False", "externalId": "42" } },
+                ],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["active"], false);
+        assert_eq!(body["emails"].as_array().unwrap().len(), 1);
+        assert_eq!(body["emails"][0]["value"], "alice@example.org");
+
+        // The username can't be changed
+        let request = Request::put(format!("/api/admin/scim/v2/Users/{id}"))
+            .bearer(&token)
+            .json(serde_json::json!({ "userName": "bob" }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["scimType"], "mutability");
+
+        // Delete it
+        let request = Request::delete(format!("/api/admin/scim/v2/Users/{id}"))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NO_CONTENT);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .lookup(id.parse().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.deactivated_at.is_some());
+        let emails = repo
+            .user_email()
+            .count(UserEmailFilter::new().for_user(&user))
+            .await
+            .unwrap();
+        assert_eq!(emails, 1);
+
+        // Unknown users are not found
+        let request = Request::get("/api/admin/scim/v2/Users/not-a-ulid")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let user = repo.user().set_can_request_admin(user, true).await.unwrap();
+        repo.save().await.unwrap();
+
+        // Admins can be read, but not changed by a delegated admin
+        let request = Request::get(format!("/api/admin/scim/v2/Users/{}", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let request = Request::patch(format!("/api/admin/scim/v2/Users/{}", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "schemas": ["urn:ietf:params:scim:api:messages:2.0:PatchOp"],
+                "Operations": [
+                    { "op": "Add", "path": "emails", "value": [{ "value": "mallory@example.com" }] },
+                ],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let request = Request::delete(format!("/api/admin/scim/v2/Users/{}", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo.user().lookup(user.id).await.unwrap().unwrap();
+        assert!(user.deactivated_at.is_none());
+        let emails = repo
+            .user_email()
+            .count(UserEmailFilter::new().for_user(&user))
+            .await
+            .unwrap();
+        assert_eq!(emails, 0);
+    }
+}
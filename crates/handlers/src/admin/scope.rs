@@ -0,0 +1,339 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Fine-grained scopes for the admin API
+//!
+//! The `urn:mas:admin` scope grants access to the whole API. The other scopes
+//! grant access to a subset of it, so that access can be delegated, e.g. to
+//! helpdesk staff who need to unlock users without being able to promote
+//! admins.
+
+use aide::openapi::{OpenApi, ReferenceOr, SecurityRequirement};
+use axum::http::Method;
+use indexmap::IndexMap;
+use mas_data_model::User;
+use oauth2_types::scope::Scope;
+
+/// A scope granting access to some or all of the admin API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdminScope {
+    /// Full access to the admin API
+    Full,
+
+    /// Read-only access to the whole admin API
+    Read,
+
+    /// Manage users, their emails and their upstream links, and export their
+    /// data
+    Users,
+
+    /// List and end sessions
+    Sessions,
+
+    /// Manage user registration tokens
+    RegistrationTokens,
+
+    /// Manage the policy data
+    PolicyData,
+}
+
+impl AdminScope {
+    pub const ALL: [Self; 6] = [
+        Self::Full,
+        Self::Read,
+        Self::Users,
+        Self::Sessions,
+        Self::RegistrationTokens,
+        Self::PolicyData,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Full => "urn:mas:admin",
+            Self::Read => "urn:mas:admin:read",
+            Self::Users => "urn:mas:admin:users",
+            Self::Sessions => "urn:mas:admin:sessions",
+            Self::RegistrationTokens => "urn:mas:admin:registration-tokens",
+            Self::PolicyData => "urn:mas:admin:policy-data",
+        }
+    }
+
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Full => "Grant access to the admin API",
+            Self::Read => "Grant read-only access to the admin API",
+            Self::Users => "Manage users, their emails and their upstream links",
+            Self::Sessions => "List and end sessions",
+            Self::RegistrationTokens => "Manage user registration tokens",
+            Self::PolicyData => "Manage the policy data",
+        }
+    }
+
+    /// Whether the given scope includes this admin scope
+    fn granted_by(self, scope: &Scope) -> bool {
+        scope.contains(self.as_str())
+    }
+}
+
+/// Whether `path` is `prefix` or a sub-path of it
+fn is_under(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The resource scope which grants access to a route, if any
+fn resource_scope(method: &Method, path: &str) -> Option<AdminScope> {
+    if let Some(path) = path.strip_prefix(super::scim::BASE_PATH) {
+        return is_under(path, "/Users").then_some(AdminScope::Users);
+    }
+
+    let path = path.strip_prefix("/api/admin/v1")?;
+
+    // Those let the caller escalate their privileges, so they need full access:
+    // promoting admins, or issuing arbitrary tokens on behalf of users
+    if path == "/users/{id}/set-admin"
+        || path == "/users/{id}/impersonate"
+        || (path == "/personal-sessions" && method == Method::POST)
+        || path == "/personal-sessions/{id}/regenerate"
+    {
+        return None;
+    }
+
+    if is_under(path, "/users")
+        || is_under(path, "/user-emails")
+        || is_under(path, "/user-exports")
+        || is_under(path, "/upstream-oauth-links")
+    {
+        Some(AdminScope::Users)
+    } else if is_under(path, "/compat-sessions")
+        || is_under(path, "/oauth2-sessions")
+        || is_under(path, "/user-sessions")
+        || is_under(path, "/personal-sessions")
+    {
+        Some(AdminScope::Sessions)
+    } else if is_under(path, "/user-registration-tokens") {
+        Some(AdminScope::RegistrationTokens)
+    } else if is_under(path, "/policy-data") {
+        Some(AdminScope::PolicyData)
+    } else {
+        None
+    }
+}
+
+/// The scopes which grant access to a route, given its method and its path
+/// template. Any of them is sufficient.
+#[must_use]
+pub fn scopes_for_route(method: &Method, path: &str) -> Vec<AdminScope> {
+    let mut scopes = vec![AdminScope::Full];
+
+    if method == Method::GET || method == Method::HEAD {
+        scopes.push(AdminScope::Read);
+    }
+
+    if let Some(scope) = resource_scope(method, path) {
+        scopes.push(scope);
+    }
+
+    scopes
+}
+
+/// Whether the given scope includes any of the admin scopes
+#[must_use]
+pub fn has_any_admin_scope(scope: &Scope) -> bool {
+    AdminScope::ALL.iter().any(|admin| admin.granted_by(scope))
+}
+
+/// Whether the given scope grants access to a route
+#[must_use]
+pub fn grants_access(scope: &Scope, method: &Method, path: &str) -> bool {
+    scopes_for_route(method, path)
+        .into_iter()
+        .any(|admin| admin.granted_by(scope))
+}
+
+/// Whether the given scope allows managing the given user
+///
+/// Users who can request admin access can only be managed with full access,
+/// else a delegated admin could take over their account and escalate their
+/// own privileges.
+#[must_use]
+pub fn can_manage_user(scope: &Scope, user: &User) -> bool {
+    !user.can_request_admin || AdminScope::Full.granted_by(scope)
+}
+
+/// The scopes to advertise in the OAuth 2.0 security scheme
+pub fn scheme_scopes() -> IndexMap<String, String> {
+    AdminScope::ALL
+        .iter()
+        .map(|scope| (scope.as_str().to_owned(), scope.description().to_owned()))
+        .collect()
+}
+
+/// Set the security requirements of each operation in the API spec, so
+/// that they reflect the scopes which grant access to it
+pub fn document(api: &mut OpenApi) {
+    let Some(paths) = api.paths.as_mut() else {
+        return;
+    };
+
+    for (path, item) in &mut paths.paths {
+        let ReferenceOr::Item(item) = item else {
+            continue;
+        };
+
+        let operations = [
+            (Method::GET, &mut item.get),
+            (Method::POST, &mut item.post),
+            (Method::PUT, &mut item.put),
+            (Method::PATCH, &mut item.patch),
+            (Method::DELETE, &mut item.delete),
+        ];
+
+        for (method, operation) in operations {
+            let Some(operation) = operation else {
+                continue;
+            };
+
+            operation.security = scopes_for_route(&method, path)
+                .into_iter()
+                .flat_map(|scope| {
+                    ["oauth2", "token"].map(|scheme| {
+                        SecurityRequirement::from([(
+                            scheme.to_owned(),
+                            vec![scope.as_str().to_owned()],
+                        )])
+                    })
+                })
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_for_route() {
+        let scope: Scope = "urn:mas:admin:users urn:mas:admin:sessions"
+            .parse()
+            .unwrap();
+
+        // Users can be managed, and sessions ended
+        assert!(grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/users/{id}/unlock"
+        ));
+        assert!(grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/user-sessions/{id}/finish"
+        ));
+        assert!(grants_access(
+            &scope,
+            &Method::PATCH,
+            "/api/admin/scim/v2/Users/{id}"
+        ));
+        assert!(grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/user-exports"
+        ));
+
+        // But admins can't be promoted, nor policy data changed
+        assert!(!grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/users/{id}/set-admin"
+        ));
+        assert!(!grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/personal-sessions"
+        ));
+        assert!(!grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/users/{id}/impersonate"
+        ));
+        assert!(!grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/policy-data"
+        ));
+        assert!(!grants_access(
+            &scope,
+            &Method::GET,
+            "/api/admin/v1/audit-events"
+        ));
+
+        // The read scope grants access to everything, but read-only
+        let scope: Scope = "urn:mas:admin:read".parse().unwrap();
+        assert!(grants_access(
+            &scope,
+            &Method::GET,
+            "/api/admin/v1/audit-events"
+        ));
+        assert!(grants_access(
+            &scope,
+            &Method::GET,
+            "/api/admin/v1/policy-data/latest"
+        ));
+        assert!(!grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/users/{id}/lock"
+        ));
+
+        // The full scope grants access to everything
+        let scope: Scope = "urn:mas:admin".parse().unwrap();
+        assert!(grants_access(
+            &scope,
+            &Method::POST,
+            "/api/admin/v1/users/{id}/set-admin"
+        ));
+
+        // Unrelated scopes don't
+        let scope: Scope = "openid urn:mas:admin:unknown".parse().unwrap();
+        assert!(!has_any_admin_scope(&scope));
+        assert!(!grants_access(&scope, &Method::GET, "/api/admin/v1/users"));
+    }
+
+    #[test]
+    fn test_can_manage_user() {
+        let mut user = User {
+            id: ulid::Ulid::nil(),
+            username: "alice".to_owned(),
+            sub: "123-456".to_owned(),
+            created_at: chrono::DateTime::UNIX_EPOCH,
+            locked_at: None,
+            deactivated_at: None,
+            erased_at: None,
+            can_request_admin: false,
+            is_guest: false,
+        };
+
+        let users: Scope = "urn:mas:admin:users".parse().unwrap();
+        let full: Scope = "urn:mas:admin".parse().unwrap();
+        assert!(can_manage_user(&users, &user));
+        assert!(can_manage_user(&full, &user));
+
+        // Admins can only be managed with full access
+        user.can_request_admin = true;
+        assert!(!can_manage_user(&users, &user));
+        assert!(can_manage_user(&full, &user));
+    }
+
+    #[test]
+    fn test_is_under() {
+        assert!(is_under("/users", "/users"));
+        assert!(is_under("/users/{id}", "/users"));
+        assert!(!is_under("/user-sessions", "/user"));
+        assert!(!is_under("/users", "/users/{id}"));
+    }
+}
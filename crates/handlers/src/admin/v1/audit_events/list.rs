@@ -0,0 +1,364 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{OperationIo, transform::TransformOperation};
+use axum::{Json, response::IntoResponse};
+use axum_extra::extract::{Query, QueryRejection};
+use axum_macros::FromRequestParts;
+use chrono::{DateTime, Utc};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::AuditAction;
+use mas_storage::{Page, audit_event::AuditEventFilter};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use ulid::Ulid;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::{AuditEvent, Resource},
+        params::{IncludeCount, Pagination},
+        response::{ErrorResponse, PaginatedResponse},
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(FromRequestParts, Deserialize, JsonSchema, OperationIo)]
+#[serde(rename = "AuditEventFilter")]
+#[aide(input_with = "Query<FilterParams>")]
+#[from_request(via(Query), rejection(RouteError))]
+pub struct FilterParams {
+    /// Retrieve the events performed by the given user
+    #[serde(rename = "filter[actor_user]")]
+    #[schemars(with = "Option<crate::admin::schema::Ulid>")]
+    actor_user: Option<Ulid>,
+
+    /// Retrieve the events affecting the given user
+    #[serde(rename = "filter[target_user]")]
+    #[schemars(with = "Option<crate::admin::schema::Ulid>")]
+    target_user: Option<Ulid>,
+
+    /// Retrieve the events of the given action
+    #[serde(rename = "filter[action]")]
+    #[schemars(with = "Option<crate::admin::schema::AuditAction>")]
+    action: Option<AuditAction>,
+
+    /// Retrieve the events which happened before the given time
+    #[serde(rename = "filter[created_before]")]
+    created_before: Option<DateTime<Utc>>,
+
+    /// Retrieve the events which happened after the given time
+    #[serde(rename = "filter[created_after]")]
+    created_after: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Display for FilterParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut sep = '?';
+
+        if let Some(actor_user) = self.actor_user {
+            write!(f, "{sep}filter[actor_user]={actor_user}")?;
+            sep = '&';
+        }
+        if let Some(target_user) = self.target_user {
+            write!(f, "{sep}filter[target_user]={target_user}")?;
+            sep = '&';
+        }
+        if let Some(action) = self.action {
+            write!(f, "{sep}filter[action]={action}")?;
+            sep = '&';
+        }
+        if let Some(created_before) = self.created_before {
+            write!(
+                f,
+                "{sep}filter[created_before]={}",
+                created_before.format("%Y-%m-%dT%H:%M:%SZ")
+            )?;
+            sep = '&';
+        }
+        if let Some(created_after) = self.created_after {
+            write!(
+                f,
+                "{sep}filter[created_after]={}",
+                created_after.format("%Y-%m-%dT%H:%M:%SZ")
+            )?;
+            sep = '&';
+        }
+
+        let _ = sep;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("User ID {0} not found")]
+    UserNotFound(Ulid),
+
+    #[error("Invalid filter parameters")]
+    InvalidFilter(#[from] QueryRejection),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::UserNotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidFilter(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("listAuditEvents")
+        .summary("List audit events")
+        .description(
+            "Retrieve a list of events from the audit log, oldest first.
+Use the `page[last]` parameter to retrieve the most recent events.",
+        )
+        .tag("audit-event")
+        .response_with::<200, Json<PaginatedResponse<AuditEvent>>, _>(|t| {
+            let events = AuditEvent::samples();
+            let pagination = mas_storage::Pagination::first(events.len());
+            let page = Page {
+                edges: events
+                    .into_iter()
+                    .map(|node| mas_storage::pagination::Edge {
+                        cursor: node.id(),
+                        node,
+                    })
+                    .collect(),
+                has_next_page: true,
+                has_previous_page: false,
+            };
+
+            t.description("Paginated response of audit events").example(
+                PaginatedResponse::for_page(page, pagination, Some(42), AuditEvent::PATH),
+            )
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::UserNotFound(Ulid::nil()));
+            t.description("User was not found").example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.audit_events.list", skip_all)]
+pub async fn handler(
+    CallContext { mut repo, .. }: CallContext,
+    Pagination(pagination, include_count): Pagination,
+    params: FilterParams,
+) -> Result<Json<PaginatedResponse<AuditEvent>>, RouteError> {
+    let base = format!("{path}{params}", path = AuditEvent::PATH);
+    let base = include_count.add_to_base(&base);
+    let filter = AuditEventFilter::new();
+
+    let actor_user = if let Some(user_id) = params.actor_user {
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await?
+            .ok_or(RouteError::UserNotFound(user_id))?;
+
+        Some(user)
+    } else {
+        None
+    };
+
+    let target_user = if let Some(user_id) = params.target_user {
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await?
+            .ok_or(RouteError::UserNotFound(user_id))?;
+
+        Some(user)
+    } else {
+        None
+    };
+
+    let filter = match &actor_user {
+        Some(user) => filter.for_actor_user(user),
+        None => filter,
+    };
+
+    let filter = match &target_user {
+        Some(user) => filter.for_target_user(user),
+        None => filter,
+    };
+
+    let filter = match params.action {
+        Some(action) => filter.with_action(action),
+        None => filter,
+    };
+
+    let filter = match params.created_before {
+        Some(created_before) => filter.with_created_before(created_before),
+        None => filter,
+    };
+
+    let filter = match params.created_after {
+        Some(created_after) => filter.with_created_after(created_after),
+        None => filter,
+    };
+
+    let response = match include_count {
+        IncludeCount::True => {
+            let page = repo
+                .audit_event()
+                .list(filter, pagination)
+                .await?
+                .map(AuditEvent::from);
+            let count = repo.audit_event().count(filter).await?;
+            PaginatedResponse::for_page(page, pagination, Some(count), &base)
+        }
+        IncludeCount::False => {
+            let page = repo
+                .audit_event()
+                .list(filter, pagination)
+                .await?
+                .map(AuditEvent::from);
+            PaginatedResponse::for_page(page, pagination, None, &base)
+        }
+        IncludeCount::Only => {
+            let count = repo.audit_event().count(filter).await?;
+            PaginatedResponse::for_count_only(count, &base)
+        }
+    };
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_data_model::Clock;
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_list(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+        let mut rng = state.rng();
+
+        // Provision two users
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let bob = repo
+            .user()
+            .add(&mut rng, &state.clock, "bob".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Lock both users, and unlock alice through the admin API, which records
+        // audit events
+        for user in [&alice, &bob] {
+            let request = Request::post(format!("/api/admin/v1/users/{}/lock", user.id))
+                .bearer(&token)
+                .empty();
+            let response = state.request(request).await;
+            response.assert_status(StatusCode::OK);
+        }
+
+        state.clock.advance(chrono::Duration::minutes(1));
+
+        let request = Request::post(format!("/api/admin/v1/users/{}/unlock", alice.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let request = Request::get("/api/admin/v1/audit-events")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 3);
+        assert_eq!(body["data"][0]["type"], "audit-event");
+        assert_eq!(body["data"][0]["attributes"]["action"], "user.lock");
+        assert_eq!(
+            body["data"][0]["attributes"]["target_user_id"],
+            alice.id.to_string()
+        );
+        assert_eq!(body["data"][2]["attributes"]["action"], "user.unlock");
+
+        // Filter by target user
+        let request = Request::get(format!(
+            "/api/admin/v1/audit-events?filter[target_user]={}",
+            bob.id
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 1);
+        assert_eq!(
+            body["data"][0]["attributes"]["target_user_id"],
+            bob.id.to_string()
+        );
+
+        // Filter by action
+        let request = Request::get("/api/admin/v1/audit-events?filter[action]=user.unlock")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 1);
+        assert_eq!(
+            body["links"]["self"],
+            "/api/admin/v1/audit-events?filter[action]=user.unlock&page[first]=10"
+        );
+
+        // Filter by creation date
+        let request = Request::get(format!(
+            "/api/admin/v1/audit-events?filter[created_before]={}",
+            state.clock.now().format("%Y-%m-%dT%H:%M:%SZ")
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 2);
+
+        // Unknown action
+        let request = Request::get("/api/admin/v1/audit-events?filter[action]=user.explode")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Unknown user
+        let request = Request::get(format!(
+            "/api/admin/v1/audit-events?filter[actor_user]={}",
+            ulid::Ulid::nil()
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
@@ -7,8 +7,9 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use mas_storage::queue::{QueueJobRepositoryExt as _, SyncDevicesJob};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -82,7 +83,10 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.compat_sessions.finish", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
@@ -112,6 +116,18 @@ pub async fn handler(
     // Finish the session
     let session = repo.compat_session().finish(&clock, session).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::CompatSessionFinish,
+            Some(session.user_id),
+            Some(session.id),
+            json!({}),
+        )
+        .await?;
+
     // Get the SSO login info for the response
     let sso_login = repo.compat_sso_login().find_for_session(&session).await?;
 
@@ -12,13 +12,16 @@ use aide::axum::{
 };
 use axum::extract::{FromRef, FromRequestParts};
 use mas_data_model::{AppVersion, BoxRng, SiteConfig};
+use mas_keystore::Encrypter;
 use mas_matrix::HomeserverConnection;
 use mas_policy::PolicyFactory;
 
 use super::call_context::CallContext;
-use crate::passwords::PasswordManager;
+use crate::{passwords::PasswordManager, upstream_oauth2::cache::MetadataCache};
 
+mod audit_events;
 mod compat_sessions;
+mod oauth2_clients;
 mod oauth2_sessions;
 mod personal_sessions;
 mod policy_data;
@@ -26,10 +29,12 @@ mod site_config;
 mod upstream_oauth_links;
 mod upstream_oauth_providers;
 mod user_emails;
+mod user_exports;
 mod user_registration_tokens;
 mod user_sessions;
-mod users;
+pub(super) mod users;
 mod version;
+mod webhook_deliveries;
 
 pub fn router<S>() -> ApiRouter<S>
 where
@@ -39,6 +44,8 @@ where
     SiteConfig: FromRef<S>,
     AppVersion: FromRef<S>,
     Arc<PolicyFactory>: FromRef<S>,
+    Encrypter: FromRef<S>,
+    MetadataCache: FromRef<S>,
     BoxRng: FromRequestParts<S>,
     CallContext: FromRequestParts<S>,
 {
@@ -51,6 +58,14 @@ where
             "/version",
             get_with(self::version::handler, self::version::doc),
         )
+        .api_route(
+            "/audit-events",
+            get_with(self::audit_events::list, self::audit_events::list_doc),
+        )
+        .api_route(
+            "/audit-events/{id}",
+            get_with(self::audit_events::get, self::audit_events::get_doc),
+        )
         .api_route(
             "/compat-sessions",
             get_with(self::compat_sessions::list, self::compat_sessions::list_doc),
@@ -66,6 +81,32 @@ where
                 self::compat_sessions::finish_doc,
             ),
         )
+        .api_route(
+            "/oauth2-clients",
+            get_with(self::oauth2_clients::list, self::oauth2_clients::list_doc)
+                .post_with(self::oauth2_clients::add, self::oauth2_clients::add_doc),
+        )
+        .api_route(
+            "/oauth2-clients/{id}",
+            get_with(self::oauth2_clients::get, self::oauth2_clients::get_doc).delete_with(
+                self::oauth2_clients::delete,
+                self::oauth2_clients::delete_doc,
+            ),
+        )
+        .api_route(
+            "/oauth2-clients/{id}/regenerate-secret",
+            post_with(
+                self::oauth2_clients::regenerate_secret,
+                self::oauth2_clients::regenerate_secret_doc,
+            ),
+        )
+        .api_route(
+            "/oauth2-clients/{id}/set-redirect-uris",
+            post_with(
+                self::oauth2_clients::set_redirect_uris,
+                self::oauth2_clients::set_redirect_uris_doc,
+            ),
+        )
         .api_route(
             "/oauth2-sessions",
             get_with(self::oauth2_sessions::list, self::oauth2_sessions::list_doc),
@@ -149,6 +190,10 @@ where
             "/users/{id}/set-admin",
             post_with(self::users::set_admin, self::users::set_admin_doc),
         )
+        .api_route(
+            "/users/{id}/impersonate",
+            post_with(self::users::impersonate, self::users::impersonate_doc),
+        )
         .api_route(
             "/users/{id}/deactivate",
             post_with(self::users::deactivate, self::users::deactivate_doc),
@@ -157,6 +202,10 @@ where
             "/users/{id}/reactivate",
             post_with(self::users::reactivate, self::users::reactivate_doc),
         )
+        .api_route(
+            "/users/{id}/erase",
+            post_with(self::users::erase, self::users::erase_doc),
+        )
         .api_route(
             "/users/{id}/lock",
             post_with(self::users::lock, self::users::lock_doc),
@@ -175,6 +224,14 @@ where
             get_with(self::user_emails::get, self::user_emails::get_doc)
                 .delete_with(self::user_emails::delete, self::user_emails::delete_doc),
         )
+        .api_route(
+            "/user-exports",
+            post_with(self::user_exports::add, self::user_exports::add_doc),
+        )
+        .api_route(
+            "/user-exports/{id}",
+            get_with(self::user_exports::get, self::user_exports::get_doc),
+        )
         .api_route(
             "/user-sessions",
             get_with(self::user_sessions::list, self::user_sessions::list_doc),
@@ -250,6 +307,10 @@ where
             get_with(
                 self::upstream_oauth_providers::list,
                 self::upstream_oauth_providers::list_doc,
+            )
+            .post_with(
+                self::upstream_oauth_providers::add,
+                self::upstream_oauth_providers::add_doc,
             ),
         )
         .api_route(
@@ -257,6 +318,35 @@ where
             get_with(
                 self::upstream_oauth_providers::get,
                 self::upstream_oauth_providers::get_doc,
+            )
+            .put_with(
+                self::upstream_oauth_providers::update,
+                self::upstream_oauth_providers::update_doc,
+            )
+            .delete_with(
+                self::upstream_oauth_providers::delete,
+                self::upstream_oauth_providers::delete_doc,
+            ),
+        )
+        .api_route(
+            "/upstream-oauth-providers/{id}/disable",
+            post_with(
+                self::upstream_oauth_providers::disable,
+                self::upstream_oauth_providers::disable_doc,
+            ),
+        )
+        .api_route(
+            "/webhook-deliveries",
+            get_with(
+                self::webhook_deliveries::list,
+                self::webhook_deliveries::list_doc,
+            ),
+        )
+        .api_route(
+            "/webhook-deliveries/{id}",
+            get_with(
+                self::webhook_deliveries::get,
+                self::webhook_deliveries::get_doc,
             ),
         )
 }
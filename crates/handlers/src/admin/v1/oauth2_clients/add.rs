@@ -0,0 +1,322 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, extract::State, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use mas_iana::oauth::OAuthClientAuthenticationMethod;
+use mas_keystore::Encrypter;
+use oauth2_types::requests::GrantType;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+use super::{generate_client_secret, uses_client_secret};
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::OAuth2Client,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Authentication method {0} is not supported")]
+    UnsupportedAuthMethod(OAuthClientAuthenticationMethod),
+
+    #[error("Clients using the private_key_jwt authentication method must have a jwks_uri")]
+    MissingJwksUri,
+
+    #[error("Clients using the authorization_code grant type must have redirect URIs")]
+    MissingRedirectUris,
+
+    #[error("Redirect URI {0} must not contain a fragment")]
+    RedirectUriWithFragment(Url),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(mas_keystore::aead::Error);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::UnsupportedAuthMethod(_)
+            | Self::MissingJwksUri
+            | Self::MissingRedirectUris
+            | Self::RedirectUriWithFragment(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+fn default_auth_method() -> OAuthClientAuthenticationMethod {
+    OAuthClientAuthenticationMethod::ClientSecretBasic
+}
+
+/// # JSON payload for the `POST /api/admin/v1/oauth2-clients` endpoint
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename = "AddOAuth2ClientRequest")]
+pub struct Request {
+    /// A human-readable name for the client
+    client_name: Option<String>,
+
+    /// The URL of the client's website
+    client_uri: Option<Url>,
+
+    /// The method the client uses to authenticate to the token endpoint.
+    ///
+    /// Use `none` for public clients. For `client_secret_basic`,
+    /// `client_secret_post` and `client_secret_jwt`, a client secret is
+    /// generated and returned in the response. `private_key_jwt` requires a
+    /// `jwks_uri`.
+    #[serde(default = "default_auth_method")]
+    token_endpoint_auth_method: OAuthClientAuthenticationMethod,
+
+    /// The grant types the client is allowed to use
+    #[schemars(with = "Vec<String>")]
+    grant_types: Vec<GrantType>,
+
+    /// The redirect URIs of the client. Required if the client uses the
+    /// `authorization_code` grant type.
+    #[serde(default)]
+    redirect_uris: Vec<Url>,
+
+    /// The URL of the client's JSON Web Key Set
+    jwks_uri: Option<Url>,
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("addOAuth2Client")
+        .summary("Create a new OAuth 2.0 client")
+        .description(
+            "The client secret, if any, is only returned in this response. \
+Clients created through this API are never `static`: they are not defined in the configuration file, so they can be modified and deleted through this API, and won't be removed by `config sync --prune`. \
+To manage a client from the configuration file instead, add it to the `clients` section and run `config sync`.",
+        )
+        .tag("oauth2-client")
+        .response_with::<201, Json<SingleResponse<OAuth2Client>>, _>(|t| {
+            let [sample, ..] = OAuth2Client::samples();
+            let sample = sample.with_client_secret("SxYyJc8zkTwFMKAZC3AHfr1wcWxHmC6X".to_owned());
+            let response = SingleResponse::new_canonical(sample);
+            t.description("OAuth 2.0 client was created")
+                .example(response)
+        })
+        .response_with::<400, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::MissingRedirectUris);
+            t.description("Client parameters are invalid")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.oauth2_clients.add", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(encrypter)): NoApi<State<Encrypter>>,
+    Json(params): Json<Request>,
+) -> Result<(StatusCode, Json<SingleResponse<OAuth2Client>>), RouteError> {
+    let method = params.token_endpoint_auth_method;
+    match method {
+        OAuthClientAuthenticationMethod::None
+        | OAuthClientAuthenticationMethod::ClientSecretBasic
+        | OAuthClientAuthenticationMethod::ClientSecretPost
+        | OAuthClientAuthenticationMethod::ClientSecretJwt => {}
+        OAuthClientAuthenticationMethod::PrivateKeyJwt => {
+            if params.jwks_uri.is_none() {
+                return Err(RouteError::MissingJwksUri);
+            }
+        }
+        method => return Err(RouteError::UnsupportedAuthMethod(method)),
+    }
+
+    if params.grant_types.contains(&GrantType::AuthorizationCode) && params.redirect_uris.is_empty()
+    {
+        return Err(RouteError::MissingRedirectUris);
+    }
+
+    if let Some(uri) = params
+        .redirect_uris
+        .iter()
+        .find(|uri| uri.fragment().is_some())
+    {
+        return Err(RouteError::RedirectUriWithFragment(uri.clone()));
+    }
+
+    let (client_secret, encrypted_client_secret) = if uses_client_secret(Some(&method)) {
+        let client_secret = generate_client_secret(&mut rng);
+        let encrypted_client_secret = encrypter.encrypt_to_string(client_secret.as_bytes())?;
+        (Some(client_secret), Some(encrypted_client_secret))
+    } else {
+        (None, None)
+    };
+
+    let client = repo
+        .oauth2_client()
+        .add(
+            &mut rng,
+            &clock,
+            params.redirect_uris,
+            None,
+            encrypted_client_secret,
+            None,
+            params.grant_types,
+            params.client_name,
+            None,
+            params.client_uri,
+            None,
+            None,
+            params.jwks_uri,
+            None,
+            None,
+            None,
+            Some(method),
+            None,
+            None,
+            Vec::new(),
+            None,
+            false,
+            false,
+            false,
+            None,
+        )
+        .await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::OAuth2ClientAdd,
+            None,
+            Some(client.id),
+            json!({ "client_id": client.client_id, "client_name": client.client_name }),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    let mut client = OAuth2Client::from(client);
+    if let Some(client_secret) = client_secret {
+        client = client.with_client_secret(client_secret);
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SingleResponse::new_canonical(client)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_router::SimpleRoute;
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_add_confidential_client(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "client_name": "Backend",
+                "grant_types": ["client_credentials"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let attributes = &body["data"]["attributes"];
+        assert_eq!(attributes["client_name"], "Backend");
+        assert_eq!(attributes["static"], false);
+        assert_eq!(
+            attributes["token_endpoint_auth_method"],
+            "client_secret_basic"
+        );
+        let client_id = attributes["client_id"].as_str().unwrap();
+        let client_secret = attributes["client_secret"].as_str().unwrap();
+
+        // The client can use its credentials
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_add_public_client(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "token_endpoint_auth_method": "none",
+                "grant_types": ["authorization_code", "refresh_token"],
+                "redirect_uris": ["https://example.com/callback"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert!(body["data"]["attributes"].get("client_secret").is_none());
+        assert_eq!(
+            body["data"]["attributes"]["redirect_uris"],
+            serde_json::json!(["https://example.com/callback"])
+        );
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_add_invalid(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        // Missing redirect URIs
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "token_endpoint_auth_method": "none",
+                "grant_types": ["authorization_code"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Missing JWKS URI
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "token_endpoint_auth_method": "private_key_jwt",
+                "grant_types": ["client_credentials"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{OperationIo, transform::TransformOperation};
+use axum::{Json, response::IntoResponse};
+use axum_extra::extract::{Query, QueryRejection};
+use axum_macros::FromRequestParts;
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_iana::oauth::OAuthClientAuthenticationMethod;
+use mas_storage::{Page, oauth2::OAuth2ClientFilter};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::{OAuth2Client, Resource},
+        params::{IncludeCount, Pagination},
+        response::{ErrorResponse, PaginatedResponse},
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(FromRequestParts, Deserialize, JsonSchema, OperationIo)]
+#[serde(rename = "OAuth2ClientFilter")]
+#[aide(input_with = "Query<FilterParams>")]
+#[from_request(via(Query), rejection(RouteError))]
+pub struct FilterParams {
+    /// Retrieve the clients which are (or are not) defined in the
+    /// configuration file
+    #[serde(rename = "filter[static]")]
+    is_static: Option<bool>,
+
+    /// Retrieve the clients using the given token endpoint authentication
+    /// method
+    #[serde(rename = "filter[token-endpoint-auth-method]")]
+    token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
+}
+
+impl std::fmt::Display for FilterParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut sep = '?';
+
+        if let Some(is_static) = self.is_static {
+            write!(f, "{sep}filter[static]={is_static}")?;
+            sep = '&';
+        }
+
+        if let Some(method) = &self.token_endpoint_auth_method {
+            write!(f, "{sep}filter[token-endpoint-auth-method]={method}")?;
+            sep = '&';
+        }
+
+        let _ = sep;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Invalid filter parameters")]
+    InvalidFilter(#[from] QueryRejection),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidFilter(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("listOAuth2Clients")
+        .summary("List OAuth 2.0 clients")
+        .tag("oauth2-client")
+        .response_with::<200, Json<PaginatedResponse<OAuth2Client>>, _>(|t| {
+            let clients = OAuth2Client::samples();
+            let pagination = mas_storage::Pagination::first(clients.len());
+            let page = Page {
+                edges: clients
+                    .into_iter()
+                    .map(|node| mas_storage::pagination::Edge {
+                        cursor: node.id(),
+                        node,
+                    })
+                    .collect(),
+                has_next_page: true,
+                has_previous_page: false,
+            };
+
+            t.description("Paginated response of OAuth 2.0 clients")
+                .example(PaginatedResponse::for_page(
+                    page,
+                    pagination,
+                    Some(42),
+                    OAuth2Client::PATH,
+                ))
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.oauth2_clients.list", skip_all)]
+pub async fn handler(
+    CallContext { mut repo, .. }: CallContext,
+    Pagination(pagination, include_count): Pagination,
+    params: FilterParams,
+) -> Result<Json<PaginatedResponse<OAuth2Client>>, RouteError> {
+    let base = format!("{path}{params}", path = OAuth2Client::PATH);
+    let base = include_count.add_to_base(&base);
+    let filter = OAuth2ClientFilter::new();
+
+    let filter = match params.is_static {
+        Some(true) => filter.static_only(),
+        Some(false) => filter.dynamic_only(),
+        None => filter,
+    };
+
+    let filter = match &params.token_endpoint_auth_method {
+        Some(method) => filter.with_token_endpoint_auth_method(method),
+        None => filter,
+    };
+
+    let response = match include_count {
+        IncludeCount::True => {
+            let page = repo
+                .oauth2_client()
+                .list(filter, pagination)
+                .await?
+                .map(OAuth2Client::from);
+            let count = repo.oauth2_client().count(filter).await?;
+            PaginatedResponse::for_page(page, pagination, Some(count), &base)
+        }
+        IncludeCount::False => {
+            let page = repo
+                .oauth2_client()
+                .list(filter, pagination)
+                .await?
+                .map(OAuth2Client::from);
+            PaginatedResponse::for_page(page, pagination, None, &base)
+        }
+        IncludeCount::Only => {
+            let count = repo.oauth2_client().count(filter).await?;
+            PaginatedResponse::for_count_only(count, &base)
+        }
+    };
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_list(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        // Create a public client
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "client_name": "Public client",
+                "token_endpoint_auth_method": "none",
+                "grant_types": ["authorization_code"],
+                "redirect_uris": ["https://example.com/callback"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        // We have two clients: the one used for the admin token, and the public one
+        let request = Request::get("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 2);
+        assert_eq!(body["data"].as_array().unwrap().len(), 2);
+
+        // Filter by authentication method
+        let request =
+            Request::get("/api/admin/v1/oauth2-clients?filter[token-endpoint-auth-method]=none")
+                .bearer(&token)
+                .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 1);
+        assert_eq!(
+            body["data"][0]["attributes"]["client_name"],
+            "Public client"
+        );
+
+        // None of them are static
+        let request = Request::get("/api/admin/v1/oauth2-clients?filter[static]=true&count=only")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 0);
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use mas_iana::oauth::OAuthClientAuthenticationMethod;
+use rand::{
+    RngCore,
+    distributions::{Alphanumeric, DistString},
+};
+
+mod add;
+mod delete;
+mod get;
+mod list;
+mod regenerate_secret;
+mod set_redirect_uris;
+
+pub use self::{
+    add::{doc as add_doc, handler as add},
+    delete::{doc as delete_doc, handler as delete},
+    get::{doc as get_doc, handler as get},
+    list::{doc as list_doc, handler as list},
+    regenerate_secret::{doc as regenerate_secret_doc, handler as regenerate_secret},
+    set_redirect_uris::{doc as set_redirect_uris_doc, handler as set_redirect_uris},
+};
+
+/// Whether the given authentication method relies on a client secret
+fn uses_client_secret(method: Option<&OAuthClientAuthenticationMethod>) -> bool {
+    matches!(
+        method,
+        Some(
+            OAuthClientAuthenticationMethod::ClientSecretBasic
+                | OAuthClientAuthenticationMethod::ClientSecretPost
+                | OAuthClientAuthenticationMethod::ClientSecretJwt
+        )
+    )
+}
+
+/// Generate a new random client secret
+fn generate_client_secret(rng: &mut (impl RngCore + ?Sized)) -> String {
+    Alphanumeric.sample_string(rng, 32)
+}
@@ -0,0 +1,251 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, extract::State, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use mas_keystore::Encrypter;
+use serde_json::json;
+use ulid::Ulid;
+
+use super::{generate_client_secret, uses_client_secret};
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::OAuth2Client,
+        params::UlidPathParam,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("OAuth 2.0 client ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("OAuth 2.0 client ID {0} is defined in the configuration file")]
+    StaticClient(Ulid),
+
+    #[error("OAuth 2.0 client ID {0} does not use a client secret")]
+    NoClientSecret(Ulid),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(mas_keystore::aead::Error);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::StaticClient(_) => StatusCode::CONFLICT,
+            Self::NoClientSecret(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("regenerateOAuth2ClientSecret")
+        .summary("Regenerate the secret of an OAuth 2.0 client")
+        .description(
+            "The previous secret stops working immediately. The new secret is only returned in this response.",
+        )
+        .tag("oauth2-client")
+        .response_with::<200, Json<SingleResponse<OAuth2Client>>, _>(|t| {
+            let [sample, ..] = OAuth2Client::samples();
+            let sample = sample.with_client_secret("SxYyJc8zkTwFMKAZC3AHfr1wcWxHmC6X".to_owned());
+            let response = SingleResponse::new_canonical(sample);
+            t.description("The client secret was regenerated")
+                .example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("OAuth 2.0 client was not found")
+                .example(response)
+        })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::StaticClient(Ulid::nil()));
+            t.description("OAuth 2.0 client is defined in the configuration file")
+                .example(response)
+        })
+        .response_with::<422, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NoClientSecret(Ulid::nil()));
+            t.description("OAuth 2.0 client does not use a client secret")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.oauth2_clients.regenerate_secret", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(encrypter)): NoApi<State<Encrypter>>,
+    id: UlidPathParam,
+) -> Result<Json<SingleResponse<OAuth2Client>>, RouteError> {
+    let id = *id;
+    let client = repo
+        .oauth2_client()
+        .lookup(id)
+        .await?
+        .ok_or(RouteError::NotFound(id))?;
+
+    if client.is_static {
+        return Err(RouteError::StaticClient(id));
+    }
+
+    if !uses_client_secret(client.token_endpoint_auth_method.as_ref()) {
+        return Err(RouteError::NoClientSecret(id));
+    }
+
+    let client_secret = generate_client_secret(&mut rng);
+    let encrypted_client_secret = encrypter.encrypt_to_string(client_secret.as_bytes())?;
+
+    let client = repo
+        .oauth2_client()
+        .set_client_secret(client, encrypted_client_secret)
+        .await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::OAuth2ClientRegenerateSecret,
+            None,
+            Some(client.id),
+            json!({}),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    Ok(Json(SingleResponse::new_canonical(
+        OAuth2Client::from(client).with_client_secret(client_secret),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_router::SimpleRoute;
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_regenerate_secret(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["client_credentials"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["data"]["id"].as_str().unwrap().to_owned();
+        let old_secret = body["data"]["attributes"]["client_secret"]
+            .as_str()
+            .unwrap()
+            .to_owned();
+
+        let request = Request::post(format!(
+            "/api/admin/v1/oauth2-clients/{id}/regenerate-secret"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        let new_secret = body["data"]["attributes"]["client_secret"]
+            .as_str()
+            .unwrap()
+            .to_owned();
+        assert_ne!(old_secret, new_secret);
+
+        // The old secret doesn't work anymore, but the new one does
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": id,
+                "client_secret": old_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": id,
+                "client_secret": new_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_public_client(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "token_endpoint_auth_method": "none",
+                "grant_types": ["authorization_code"],
+                "redirect_uris": ["https://example.com/callback"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["data"]["id"].as_str().unwrap();
+
+        let request = Request::post(format!(
+            "/api/admin/v1/oauth2-clients/{id}/regenerate-secret"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_not_found(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let client_id = Ulid::nil();
+        let request = Request::post(format!(
+            "/api/admin/v1/oauth2-clients/{client_id}/regenerate-secret"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
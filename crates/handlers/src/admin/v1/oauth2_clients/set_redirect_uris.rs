@@ -0,0 +1,226 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use oauth2_types::requests::GrantType;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use ulid::Ulid;
+use url::Url;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::{OAuth2Client, Resource},
+        params::UlidPathParam,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("OAuth 2.0 client ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("OAuth 2.0 client ID {0} is defined in the configuration file")]
+    StaticClient(Ulid),
+
+    #[error("Clients using the authorization_code grant type must have redirect URIs")]
+    MissingRedirectUris,
+
+    #[error("Redirect URI {0} must not contain a fragment")]
+    RedirectUriWithFragment(Url),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::StaticClient(_) => StatusCode::CONFLICT,
+            Self::MissingRedirectUris | Self::RedirectUriWithFragment(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+/// # JSON payload for the `POST /api/admin/v1/oauth2-clients/:id/set-redirect-uris` endpoint
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename = "SetOAuth2ClientRedirectUrisRequest")]
+pub struct Request {
+    /// The new list of redirect URIs of the client
+    redirect_uris: Vec<Url>,
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("setOAuth2ClientRedirectUris")
+        .summary("Replace the redirect URIs of an OAuth 2.0 client")
+        .tag("oauth2-client")
+        .response_with::<200, Json<SingleResponse<OAuth2Client>>, _>(|t| {
+            let [_, sample, ..] = OAuth2Client::samples();
+            let id = sample.id();
+            let response = SingleResponse::new(
+                sample,
+                format!("/api/admin/v1/oauth2-clients/{id}/set-redirect-uris"),
+            );
+            t.description("The redirect URIs were updated")
+                .example(response)
+        })
+        .response_with::<400, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::MissingRedirectUris);
+            t.description("The redirect URIs are invalid")
+                .example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("OAuth 2.0 client was not found")
+                .example(response)
+        })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::StaticClient(Ulid::nil()));
+            t.description("OAuth 2.0 client is defined in the configuration file")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.oauth2_clients.set_redirect_uris", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    id: UlidPathParam,
+    Json(params): Json<Request>,
+) -> Result<Json<SingleResponse<OAuth2Client>>, RouteError> {
+    let id = *id;
+    let client = repo
+        .oauth2_client()
+        .lookup(id)
+        .await?
+        .ok_or(RouteError::NotFound(id))?;
+
+    if client.is_static {
+        return Err(RouteError::StaticClient(id));
+    }
+
+    if client.grant_types.contains(&GrantType::AuthorizationCode) && params.redirect_uris.is_empty()
+    {
+        return Err(RouteError::MissingRedirectUris);
+    }
+
+    if let Some(uri) = params
+        .redirect_uris
+        .iter()
+        .find(|uri| uri.fragment().is_some())
+    {
+        return Err(RouteError::RedirectUriWithFragment(uri.clone()));
+    }
+
+    let client = repo
+        .oauth2_client()
+        .set_redirect_uris(client, params.redirect_uris)
+        .await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::OAuth2ClientSetRedirectUris,
+            None,
+            Some(client.id),
+            json!({ "redirect_uris": client.redirect_uris }),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    Ok(Json(SingleResponse::new(
+        OAuth2Client::from(client),
+        format!("/api/admin/v1/oauth2-clients/{id}/set-redirect-uris"),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_set_redirect_uris(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/oauth2-clients")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "token_endpoint_auth_method": "none",
+                "grant_types": ["authorization_code"],
+                "redirect_uris": ["https://example.com/callback"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["data"]["id"].as_str().unwrap().to_owned();
+
+        let request = Request::post(format!(
+            "/api/admin/v1/oauth2-clients/{id}/set-redirect-uris"
+        ))
+        .bearer(&token)
+        .json(serde_json::json!({
+            "redirect_uris": ["https://example.com/a", "https://example.com/b"],
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(
+            body["data"]["attributes"]["redirect_uris"],
+            serde_json::json!(["https://example.com/a", "https://example.com/b"])
+        );
+
+        // The client uses the authorization code grant, so it needs redirect URIs
+        let request = Request::post(format!(
+            "/api/admin/v1/oauth2-clients/{id}/set-redirect-uris"
+        ))
+        .bearer(&token)
+        .json(serde_json::json!({
+            "redirect_uris": [],
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Fragments are not allowed
+        let request = Request::post(format!(
+            "/api/admin/v1/oauth2-clients/{id}/set-redirect-uris"
+        ))
+        .bearer(&token)
+        .json(serde_json::json!({
+            "redirect_uris": ["https://example.com/callback#fragment"],
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
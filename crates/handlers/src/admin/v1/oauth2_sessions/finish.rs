@@ -7,8 +7,9 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
-use mas_storage::queue::{QueueJobRepositoryExt as _, SyncDevicesJob};
+use mas_data_model::{AuditAction, BoxRng};
+use mas_storage::queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _, SyncDevicesJob};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -82,7 +83,10 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.oauth2_sessions.finish", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
@@ -109,6 +113,27 @@ pub async fn handler(
     // Finish the session
     let session = repo.oauth2_session().finish(&clock, session).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::OAuth2SessionFinish,
+            session.user_id,
+            Some(session.id),
+            json!({}),
+        )
+        .await?;
+
+    // Notify the client that the session ended
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            BackchannelLogoutJob::for_oauth2_session(&session),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -11,11 +11,12 @@ use axum::{Json, extract::State, response::IntoResponse};
 use chrono::Duration;
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::{BoxRng, Device, TokenType};
+use mas_data_model::{AuditAction, BoxRng, Device, TokenType};
 use mas_matrix::HomeserverConnection;
 use oauth2_types::scope::Scope;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -104,6 +105,7 @@ pub async fn handler(
         mut repo,
         clock,
         session,
+        actor,
         ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
@@ -137,6 +139,18 @@ pub async fn handler(
         )
         .await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::PersonalSessionAdd,
+            Some(actor_user.id),
+            Some(session.id),
+            json!({ "human_name": session.human_name, "scope": session.scope }),
+        )
+        .await?;
+
     // Create the initial token for the session
     let access_token_string = TokenType::PersonalAccessToken.generate(&mut rng);
     let access_token = repo
@@ -8,9 +8,10 @@ use axum::{Json, response::IntoResponse};
 use chrono::Duration;
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::{BoxRng, TokenType};
+use mas_data_model::{AuditAction, BoxRng, TokenType};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use tracing::error;
 
 use crate::{
@@ -91,6 +92,7 @@ pub async fn handler(
         mut repo,
         clock,
         session: caller_session,
+        actor,
         ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
@@ -147,6 +149,18 @@ pub async fn handler(
         )
         .await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::PersonalSessionRegenerate,
+            Some(session.actor_user_id),
+            Some(session.id),
+            json!({}),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok((
@@ -7,8 +7,9 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use mas_storage::queue::{QueueJobRepositoryExt as _, SyncDevicesJob};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -80,7 +81,10 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 )]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     session_id: UlidPathParam,
@@ -98,6 +102,18 @@ pub async fn handler(
 
     let session = repo.personal_session().revoke(&clock, session).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::PersonalSessionRevoke,
+            Some(session.actor_user_id),
+            Some(session.id),
+            json!({}),
+        )
+        .await?;
+
     if session.has_device() {
         // If the session has a device, then we are now
         // deleting a device and should schedule a device sync to clean up.
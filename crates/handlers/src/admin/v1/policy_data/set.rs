@@ -9,10 +9,11 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, extract::State, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use mas_policy::PolicyFactory;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 
 use crate::{
     admin::{
@@ -85,7 +86,10 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.policy_data.set", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     State(policy_factory): State<Arc<PolicyFactory>>,
@@ -99,6 +103,18 @@ pub async fn handler(
     // Swap the policy data. This will fail if the policy data is invalid
     policy_factory.set_dynamic_data(policy_data.clone()).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::PolicyDataSet,
+            None,
+            Some(policy_data.id),
+            json!({}),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok((
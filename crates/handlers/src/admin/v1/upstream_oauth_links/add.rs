@@ -7,9 +7,10 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -17,6 +18,7 @@ use crate::{
         call_context::CallContext,
         model::{Resource, UpstreamOAuthLink},
         response::{ErrorResponse, SingleResponse},
+        scope,
     },
     impl_from_error_for_route,
 };
@@ -35,6 +37,9 @@ pub enum RouteError {
 
     #[error("Upstream OAuth 2.0 Provider ID {0} not found")]
     ProviderNotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -47,6 +52,7 @@ impl IntoResponse for RouteError {
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::LinkAlreadyExists(_, _) => StatusCode::CONFLICT,
             Self::UserNotFound(_) | Self::ProviderNotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -97,6 +103,11 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
             t.description("The subject from the provider is already linked to another user")
                 .example(response)
         })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The user is an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::UserNotFound(Ulid::nil()));
             t.description("User or provider was not found")
@@ -107,7 +118,11 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.upstream_oauth_links.post", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     Json(params): Json<Request>,
@@ -119,6 +134,10 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::UserNotFound(params.user_id))?;
 
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(params.user_id));
+    }
+
     // Find the provider
     let provider = repo
         .upstream_oauth_provider()
@@ -143,6 +162,18 @@ pub async fn handler(
             .await?;
         link.user_id = Some(user.id);
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                actor.clone(),
+                AuditAction::UpstreamOAuthLinkAdd,
+                Some(user.id),
+                Some(link.id),
+                json!({ "provider_id": provider.id, "subject": link.subject }),
+            )
+            .await?;
+
         repo.save().await?;
 
         return Ok((
@@ -167,6 +198,18 @@ pub async fn handler(
         .await?;
     link.user_id = Some(user.id);
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UpstreamOAuthLinkAdd,
+            Some(user.id),
+            Some(link.id),
+            json!({ "provider_id": provider.id, "subject": link.subject }),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok((
@@ -462,4 +505,48 @@ mod tests {
         }
         "###);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .set_can_request_admin(alice, true)
+            .await
+            .unwrap();
+
+        let provider = repo
+            .upstream_oauth_provider()
+            .add(
+                &mut rng,
+                &state.clock,
+                test_utils::oidc_provider_params("provider1"),
+            )
+            .await
+            .unwrap();
+
+        repo.save().await.unwrap();
+
+        // A delegated admin can't link an admin to an upstream account they
+        // control
+        let request = Request::post("/api/admin/v1/upstream-oauth-links")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "user_id": alice.id,
+                "provider_id": provider.id,
+                "subject": "subject1"
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+    }
 }
@@ -3,14 +3,16 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
-    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse},
+    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse, scope},
     impl_from_error_for_route,
 };
 
@@ -22,6 +24,9 @@ pub enum RouteError {
 
     #[error("Upstream OAuth 2.0 Link ID {0} not found")]
     NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -33,6 +38,7 @@ impl IntoResponse for RouteError {
         let status = match self {
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -44,6 +50,11 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
         .summary("Delete an upstream OAuth 2.0 link")
         .tag("upstream-oauth-link")
         .response_with::<204, (), _>(|t| t.description("Upstream OAuth 2.0 link was deleted"))
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The link belongs to an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
             t.description("Upstream OAuth 2.0 link was not found")
@@ -54,8 +65,13 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.upstream_oauth_links.delete", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
 ) -> Result<StatusCode, RouteError> {
     let link = repo
@@ -64,6 +80,28 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::NotFound(*id))?;
 
+    if let Some(user_id) = link.user_id {
+        let user = repo.user().lookup(user_id).await?;
+        if user
+            .as_ref()
+            .is_some_and(|user| !scope::can_manage_user(session.scope(), user))
+        {
+            return Err(RouteError::AdminUser(user_id));
+        }
+    }
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UpstreamOAuthLinkRemove,
+            link.user_id,
+            Some(link.id),
+            json!({ "provider_id": link.provider_id, "subject": link.subject }),
+        )
+        .await?;
+
     repo.upstream_oauth_link().remove(&clock, link).await?;
 
     repo.save().await?;
@@ -177,4 +215,70 @@ mod tests {
         let response = state.request(request).await;
         response.assert_status(StatusCode::NOT_FOUND);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_delete_admin_link(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.unwrap();
+
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .set_can_request_admin(alice, true)
+            .await
+            .unwrap();
+
+        let provider = repo
+            .upstream_oauth_provider()
+            .add(
+                &mut rng,
+                &state.clock,
+                test_utils::oidc_provider_params("provider1"),
+            )
+            .await
+            .unwrap();
+
+        let link = repo
+            .upstream_oauth_link()
+            .add(
+                &mut rng,
+                &state.clock,
+                &provider,
+                String::from("subject1"),
+                None,
+            )
+            .await
+            .unwrap();
+
+        repo.upstream_oauth_link()
+            .associate_to_user(&link, &alice)
+            .await
+            .unwrap();
+
+        repo.save().await.unwrap();
+
+        // Links of admins can't be removed by a delegated admin
+        let request = Request::delete(format!("/api/admin/v1/upstream-oauth-links/{}", link.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        assert!(
+            repo.upstream_oauth_link()
+                .lookup(link.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        repo.save().await.unwrap();
+    }
 }
@@ -51,6 +51,7 @@ mod test_utils {
             forward_login_hint: false,
             ui_order: 0,
             on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
         }
     }
 }
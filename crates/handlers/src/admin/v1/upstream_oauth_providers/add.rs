@@ -0,0 +1,219 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, extract::State, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use mas_keystore::Encrypter;
+use oauth2_types::scope::InvalidScope;
+use serde_json::json;
+use ulid::Ulid;
+
+use super::{ProviderRequest, encrypt_client_secret};
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::UpstreamOAuthProvider,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+    upstream_oauth2::config::provider_params_from_config,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Invalid provider: {0}")]
+    InvalidProvider(String),
+
+    #[error("Invalid scope")]
+    InvalidScope(#[from] InvalidScope),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidProvider(_) | Self::InvalidScope(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("addUpstreamOAuthProvider")
+        .summary("Add an upstream OAuth 2.0 provider")
+        .description(
+            "The provider is validated the same way as providers defined in the configuration file. \
+Providers added through this API are not managed by `config sync`, and won't be disabled by it.",
+        )
+        .tag("upstream-oauth-provider")
+        .response_with::<201, Json<SingleResponse<UpstreamOAuthProvider>>, _>(|t| {
+            let [_, _, sample] = UpstreamOAuthProvider::samples();
+            t.description("The upstream OAuth provider was created")
+                .example(SingleResponse::new_canonical(sample))
+        })
+        .response_with::<400, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::InvalidProvider(
+                "The `issuer` field is required when discovery is enabled".to_owned(),
+            ));
+            t.description("The provider parameters are invalid")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.upstream_oauth_providers.add", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(encrypter)): NoApi<State<Encrypter>>,
+    Json(params): Json<ProviderRequest>,
+) -> Result<(StatusCode, Json<SingleResponse<UpstreamOAuthProvider>>), RouteError> {
+    // The repository generates the ID, this one is only used for validation
+    let (provider, ui_order) = params.into_config(Ulid::nil());
+    provider
+        .validate()
+        .map_err(|e| RouteError::InvalidProvider(e.to_string()))?;
+
+    let encrypted_client_secret =
+        encrypt_client_secret(&encrypter, &provider).map_err(RouteError::Internal)?;
+    let params = provider_params_from_config(provider, encrypted_client_secret, ui_order)?;
+
+    let provider = repo
+        .upstream_oauth_provider()
+        .add(&mut rng, &clock, params)
+        .await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UpstreamOAuthProviderAdd,
+            None,
+            Some(provider.id),
+            json!({ "issuer": provider.issuer, "human_name": provider.human_name }),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SingleResponse::new_canonical(UpstreamOAuthProvider::from(
+            provider,
+        ))),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_storage::RepositoryAccess;
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_add_provider(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "human_name": "Tenant",
+                "client_id": "mas",
+                "client_secret": "hunter2",
+                "token_endpoint_auth_method": "client_secret_post",
+                "scope": "openid profile",
+                "claims_imports": {
+                    "localpart": {
+                        "action": "require",
+                        "template": "{{ user.preferred_username }}",
+                    },
+                },
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let attributes = &body["data"]["attributes"];
+        assert_eq!(attributes["issuer"], "https://tenant.example.com/");
+        assert_eq!(attributes["human_name"], "Tenant");
+        assert_eq!(attributes["static"], false);
+        assert_eq!(attributes["disabled_at"], serde_json::Value::Null);
+
+        // The client secret should be stored encrypted
+        let id = body["data"]["id"].as_str().unwrap().parse().unwrap();
+        let mut repo = state.repository().await.unwrap();
+        let provider = repo
+            .upstream_oauth_provider()
+            .lookup(id)
+            .await
+            .unwrap()
+            .unwrap();
+        let encrypted = provider.encrypted_client_secret.unwrap();
+        assert_ne!(encrypted, "hunter2");
+        let decrypted = state.encrypter.decrypt_string(&encrypted).unwrap();
+        assert_eq!(decrypted, b"hunter2");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_add_invalid(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        // Missing client secret
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "client_secret_basic",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Missing issuer with discovery enabled
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "client_id": "mas",
+                "token_endpoint_auth_method": "none",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Invalid scope
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "none",
+                "scope": "openid \"profile\"",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
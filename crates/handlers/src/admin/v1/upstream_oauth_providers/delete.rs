@@ -0,0 +1,233 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, extract::State, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::{
+    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse},
+    impl_from_error_for_route,
+    upstream_oauth2::cache::MetadataCache,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Upstream OAuth provider ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("Upstream OAuth provider ID {0} is defined in the configuration file")]
+    StaticProvider(Ulid),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::StaticProvider(_) => StatusCode::CONFLICT,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("deleteUpstreamOAuthProvider")
+        .summary("Delete an upstream OAuth 2.0 provider")
+        .description(
+            "This also deletes all the links between users and this provider. Consider disabling the provider instead.",
+        )
+        .tag("upstream-oauth-provider")
+        .response_with::<204, (), _>(|t| t.description("Provider was deleted"))
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("Provider was not found").example(response)
+        })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::StaticProvider(Ulid::nil()));
+            t.description("Provider is defined in the configuration file")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.upstream_oauth_providers.delete", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(metadata_cache)): NoApi<State<MetadataCache>>,
+    id: UlidPathParam,
+) -> Result<StatusCode, RouteError> {
+    let id = *id;
+    let provider = repo
+        .upstream_oauth_provider()
+        .lookup(id)
+        .await?
+        .ok_or(RouteError::NotFound(id))?;
+
+    if provider.is_static {
+        return Err(RouteError::StaticProvider(id));
+    }
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UpstreamOAuthProviderDelete,
+            None,
+            Some(provider.id),
+            json!({ "issuer": provider.issuer, "human_name": provider.human_name }),
+        )
+        .await?;
+
+    let issuer = provider.issuer.clone();
+    repo.upstream_oauth_provider().delete(provider).await?;
+
+    repo.save().await?;
+
+    if let Some(issuer) = issuer {
+        metadata_cache.invalidate(&issuer).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_data_model::{
+        UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderDiscoveryMode,
+        UpstreamOAuthProviderOnBackchannelLogout, UpstreamOAuthProviderPkceMode,
+        UpstreamOAuthProviderTokenAuthMethod,
+    };
+    use mas_iana::jose::JsonWebSignatureAlg;
+    use mas_storage::{
+        RepositoryAccess,
+        upstream_oauth2::{UpstreamOAuthProviderParams, UpstreamOAuthProviderRepository},
+    };
+    use oauth2_types::scope::{OPENID, Scope};
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_delete(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "none",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["data"]["id"].as_str().unwrap().to_owned();
+
+        let request = Request::delete(format!("/api/admin/v1/upstream-oauth-providers/{id}"))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NO_CONTENT);
+
+        // Verify that the provider was deleted
+        let request = Request::get(format!("/api/admin/v1/upstream-oauth-providers/{id}"))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_static_provider(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        // Providers upserted by `config sync` are static
+        let mut repo = state.repository().await.unwrap();
+        let provider = repo
+            .upstream_oauth_provider()
+            .upsert(
+                &state.clock,
+                Ulid::from(1u128),
+                UpstreamOAuthProviderParams {
+                    issuer: Some("https://accounts.google.com".to_owned()),
+                    human_name: None,
+                    brand_name: None,
+                    discovery_mode: UpstreamOAuthProviderDiscoveryMode::Oidc,
+                    pkce_mode: UpstreamOAuthProviderPkceMode::Auto,
+                    jwks_uri_override: None,
+                    authorization_endpoint_override: None,
+                    token_endpoint_override: None,
+                    userinfo_endpoint_override: None,
+                    fetch_userinfo: false,
+                    userinfo_signed_response_alg: None,
+                    client_id: "client-id".to_owned(),
+                    encrypted_client_secret: None,
+                    token_endpoint_signing_alg: None,
+                    token_endpoint_auth_method: UpstreamOAuthProviderTokenAuthMethod::None,
+                    id_token_signed_response_alg: JsonWebSignatureAlg::Rs256,
+                    response_mode: None,
+                    scope: Scope::from_iter([OPENID]),
+                    claims_imports: UpstreamOAuthProviderClaimsImports::default(),
+                    additional_authorization_parameters: vec![],
+                    forward_login_hint: false,
+                    on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
+                    ui_order: 0,
+                },
+            )
+            .await
+            .unwrap();
+        Box::new(repo).save().await.unwrap();
+
+        let id = provider.id;
+        let request = Request::delete(format!("/api/admin/v1/upstream-oauth-providers/{id}"))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CONFLICT);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_not_found(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let provider_id = Ulid::nil();
+        let request = Request::delete(format!(
+            "/api/admin/v1/upstream-oauth-providers/{provider_id}"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
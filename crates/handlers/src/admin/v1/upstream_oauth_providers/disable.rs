@@ -0,0 +1,200 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, extract::State, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::{Resource, UpstreamOAuthProvider},
+        params::UlidPathParam,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+    upstream_oauth2::cache::MetadataCache,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Upstream OAuth provider ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("Upstream OAuth provider ID {0} is defined in the configuration file")]
+    StaticProvider(Ulid),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::StaticProvider(_) => StatusCode::CONFLICT,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("disableUpstreamOAuthProvider")
+        .summary("Disable an upstream OAuth 2.0 provider")
+        .description(
+            "Users can no longer log in with a disabled provider, but their links to it are kept. \
+Disabling an already disabled provider does nothing.",
+        )
+        .tag("upstream-oauth-provider")
+        .response_with::<200, Json<SingleResponse<UpstreamOAuthProvider>>, _>(|t| {
+            let [_, sample, _] = UpstreamOAuthProvider::samples();
+            let id = sample.id();
+            let response = SingleResponse::new(
+                sample,
+                format!("/api/admin/v1/upstream-oauth-providers/{id}/disable"),
+            );
+            t.description("The provider was disabled").example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("Provider was not found").example(response)
+        })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::StaticProvider(Ulid::nil()));
+            t.description("Provider is defined in the configuration file")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.upstream_oauth_providers.disable", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(metadata_cache)): NoApi<State<MetadataCache>>,
+    id: UlidPathParam,
+) -> Result<Json<SingleResponse<UpstreamOAuthProvider>>, RouteError> {
+    let id = *id;
+    let mut provider = repo
+        .upstream_oauth_provider()
+        .lookup(id)
+        .await?
+        .ok_or(RouteError::NotFound(id))?;
+
+    if provider.is_static {
+        return Err(RouteError::StaticProvider(id));
+    }
+
+    if provider.enabled() {
+        provider = repo
+            .upstream_oauth_provider()
+            .disable(&clock, provider)
+            .await?;
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                actor,
+                AuditAction::UpstreamOAuthProviderDisable,
+                None,
+                Some(provider.id),
+                json!({ "issuer": provider.issuer, "human_name": provider.human_name }),
+            )
+            .await?;
+
+        repo.save().await?;
+
+        if let Some(issuer) = &provider.issuer {
+            metadata_cache.invalidate(issuer).await;
+        }
+    }
+
+    Ok(Json(SingleResponse::new(
+        UpstreamOAuthProvider::from(provider),
+        format!("/api/admin/v1/upstream-oauth-providers/{id}/disable"),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_disable_provider(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "none",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["data"]["id"].as_str().unwrap().to_owned();
+
+        let request = Request::post(format!(
+            "/api/admin/v1/upstream-oauth-providers/{id}/disable"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        let disabled_at = body["data"]["attributes"]["disabled_at"].clone();
+        assert!(disabled_at.is_string());
+
+        // Disabling it again is a no-op
+        let request = Request::post(format!(
+            "/api/admin/v1/upstream-oauth-providers/{id}/disable"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["attributes"]["disabled_at"], disabled_at);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_not_found(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let provider_id = Ulid::nil();
+        let request = Request::post(format!(
+            "/api/admin/v1/upstream-oauth-providers/{provider_id}/disable"
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
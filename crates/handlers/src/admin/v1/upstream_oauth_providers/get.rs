@@ -118,6 +118,7 @@ mod tests {
             additional_authorization_parameters: vec![],
             forward_login_hint: false,
             on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
             ui_order: 0,
         };
 
@@ -164,7 +165,8 @@ mod tests {
               "human_name": "Google",
               "brand_name": "google",
               "created_at": "2022-01-16T14:40:00Z",
-              "disabled_at": null
+              "disabled_at": null,
+              "static": false
             },
             "links": {
               "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG0MZAA6S4AF7CTV32E"
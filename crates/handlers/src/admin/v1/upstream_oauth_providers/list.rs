@@ -31,6 +31,11 @@ pub struct FilterParams {
     /// Retrieve providers that are (or are not) enabled
     #[serde(rename = "filter[enabled]")]
     enabled: Option<bool>,
+
+    /// Retrieve providers that are (or are not) defined in the configuration
+    /// file
+    #[serde(rename = "filter[static]")]
+    is_static: Option<bool>,
 }
 
 impl std::fmt::Display for FilterParams {
@@ -42,6 +47,11 @@ impl std::fmt::Display for FilterParams {
             sep = '&';
         }
 
+        if let Some(is_static) = self.is_static {
+            write!(f, "{sep}filter[static]={is_static}")?;
+            sep = '&';
+        }
+
         let _ = sep;
         Ok(())
     }
@@ -118,6 +128,12 @@ pub async fn handler(
         None => filter,
     };
 
+    let filter = match params.is_static {
+        Some(true) => filter.static_only(),
+        Some(false) => filter.dynamic_only(),
+        None => filter,
+    };
+
     let response = match include_count {
         IncludeCount::True => {
             let page = repo
@@ -190,6 +206,7 @@ mod tests {
             additional_authorization_parameters: vec![],
             forward_login_hint: false,
             on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
             ui_order: 0,
         };
 
@@ -222,6 +239,7 @@ mod tests {
             additional_authorization_parameters: vec![],
             forward_login_hint: false,
             on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
             ui_order: 1,
         };
 
@@ -261,6 +279,7 @@ mod tests {
             additional_authorization_parameters: vec![],
             forward_login_hint: false,
             on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
             ui_order: 2,
         };
 
@@ -304,7 +323,8 @@ mod tests {
                 "human_name": "Apple ID",
                 "brand_name": "apple",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": "2022-01-16T14:40:00Z"
+                "disabled_at": "2022-01-16T14:40:00Z",
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG07HNEZXNQM2KNBNF6"
@@ -323,7 +343,8 @@ mod tests {
                 "human_name": "Microsoft",
                 "brand_name": "microsoft",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG09AVTNSQFMSR34AJC"
@@ -342,7 +363,8 @@ mod tests {
                 "human_name": "Google",
                 "brand_name": "google",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG0MZAA6S4AF7CTV32E"
@@ -392,7 +414,8 @@ mod tests {
                 "human_name": "Microsoft",
                 "brand_name": "microsoft",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG09AVTNSQFMSR34AJC"
@@ -411,7 +434,8 @@ mod tests {
                 "human_name": "Google",
                 "brand_name": "google",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG0MZAA6S4AF7CTV32E"
@@ -461,7 +485,8 @@ mod tests {
                 "human_name": "Apple ID",
                 "brand_name": "apple",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": "2022-01-16T14:40:00Z"
+                "disabled_at": "2022-01-16T14:40:00Z",
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG07HNEZXNQM2KNBNF6"
@@ -512,7 +537,8 @@ mod tests {
                 "human_name": "Apple ID",
                 "brand_name": "apple",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": "2022-01-16T14:40:00Z"
+                "disabled_at": "2022-01-16T14:40:00Z",
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG07HNEZXNQM2KNBNF6"
@@ -531,7 +557,8 @@ mod tests {
                 "human_name": "Microsoft",
                 "brand_name": "microsoft",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG09AVTNSQFMSR34AJC"
@@ -578,7 +605,8 @@ mod tests {
                 "human_name": "Google",
                 "brand_name": "google",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG0MZAA6S4AF7CTV32E"
@@ -640,7 +668,8 @@ mod tests {
                 "human_name": "Apple ID",
                 "brand_name": "apple",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": "2022-01-16T14:40:00Z"
+                "disabled_at": "2022-01-16T14:40:00Z",
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG07HNEZXNQM2KNBNF6"
@@ -659,7 +688,8 @@ mod tests {
                 "human_name": "Microsoft",
                 "brand_name": "microsoft",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG09AVTNSQFMSR34AJC"
@@ -678,7 +708,8 @@ mod tests {
                 "human_name": "Google",
                 "brand_name": "google",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG0MZAA6S4AF7CTV32E"
@@ -737,7 +768,8 @@ mod tests {
                 "human_name": "Microsoft",
                 "brand_name": "microsoft",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG09AVTNSQFMSR34AJC"
@@ -756,7 +788,8 @@ mod tests {
                 "human_name": "Google",
                 "brand_name": "google",
                 "created_at": "2022-01-16T14:40:00Z",
-                "disabled_at": null
+                "disabled_at": null,
+                "static": false
               },
               "links": {
                 "self": "/api/admin/v1/upstream-oauth-providers/01FSHN9AG0MZAA6S4AF7CTV32E"
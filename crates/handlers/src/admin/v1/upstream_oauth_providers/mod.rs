@@ -3,10 +3,223 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::collections::BTreeMap;
+
+use mas_config::{
+    UpstreamOAuth2ClaimsImports, UpstreamOAuth2DiscoveryMode, UpstreamOAuth2OnBackchannelLogout,
+    UpstreamOAuth2PkceMethod, UpstreamOAuth2Provider, UpstreamOAuth2ResponseMode,
+    UpstreamOAuth2Saml, UpstreamOAuth2SignInWithApple, UpstreamOAuth2TokenAuthMethod,
+};
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_keystore::Encrypter;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use ulid::Ulid;
+use url::Url;
+
+mod add;
+mod delete;
+mod disable;
 mod get;
 mod list;
+mod update;
 
 pub use self::{
+    add::{doc as add_doc, handler as add},
+    delete::{doc as delete_doc, handler as delete},
+    disable::{doc as disable_doc, handler as disable},
     get::{doc as get_doc, handler as get},
     list::{doc as list_doc, handler as list},
+    update::{doc as update_doc, handler as update},
 };
+
+fn default_scope() -> String {
+    "openid".to_owned()
+}
+
+fn default_signed_response_alg() -> JsonWebSignatureAlg {
+    JsonWebSignatureAlg::Rs256
+}
+
+/// Additional parameters for the `sign_in_with_apple` authentication method
+#[derive(Deserialize, JsonSchema)]
+pub struct SignInWithApple {
+    /// The PEM-encoded private key used to sign the client assertion
+    private_key: String,
+
+    /// The Team ID of the Apple Developer Portal
+    team_id: String,
+
+    /// The key ID of the Apple Developer Portal
+    key_id: String,
+}
+
+/// # JSON payload for creating or updating an upstream OAuth 2.0 provider
+///
+/// This has the same fields as a provider in the `upstream_oauth2` section of
+/// the configuration file, and is validated the same way.
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename = "UpstreamOAuthProviderRequest")]
+pub struct ProviderRequest {
+    /// The OIDC issuer URL. Required if OIDC discovery is enabled.
+    issuer: Option<String>,
+
+    /// A human-readable name for the provider, that will be shown to users
+    human_name: Option<String>,
+
+    /// A brand identifier used to customise the UI, e.g. `apple`, `google`
+    brand_name: Option<String>,
+
+    /// The client ID to use when authenticating with the provider
+    client_id: String,
+
+    /// The client secret to use when authenticating with the provider. Required
+    /// by the `client_secret_basic`, `client_secret_post` and
+    /// `client_secret_jwt` methods.
+    client_secret: Option<String>,
+
+    /// The method to authenticate the client with the provider
+    token_endpoint_auth_method: UpstreamOAuth2TokenAuthMethod,
+
+    /// Additional parameters for the `sign_in_with_apple` method
+    sign_in_with_apple: Option<SignInWithApple>,
+
+    /// The JWS algorithm to use when authenticating the client with the
+    /// provider. Required by the `client_secret_jwt` and `private_key_jwt`
+    /// methods.
+    token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
+
+    /// Expected signature for the JWT payload returned by the token
+    /// authentication endpoint. Defaults to `RS256`.
+    #[serde(default = "default_signed_response_alg")]
+    id_token_signed_response_alg: JsonWebSignatureAlg,
+
+    /// The scopes to request from the provider. Defaults to `openid`.
+    #[serde(default = "default_scope")]
+    scope: String,
+
+    /// How to discover the provider's configuration. Defaults to `oidc`.
+    #[serde(default)]
+    discovery_mode: UpstreamOAuth2DiscoveryMode,
+
+    /// Whether to use proof key for code exchange (PKCE). Defaults to `auto`.
+    #[serde(default)]
+    pkce_method: UpstreamOAuth2PkceMethod,
+
+    /// Whether to fetch the user profile from the userinfo endpoint
+    #[serde(default)]
+    fetch_userinfo: bool,
+
+    /// Expected signature for the JWT payload returned by the userinfo
+    /// endpoint. If not specified, the response is expected to be an unsigned
+    /// JSON payload.
+    userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
+
+    /// The URL to use for the provider's authorization endpoint, instead of
+    /// the discovered one
+    authorization_endpoint: Option<Url>,
+
+    /// The URL to use for the provider's userinfo endpoint, instead of the
+    /// discovered one
+    userinfo_endpoint: Option<Url>,
+
+    /// The URL to use for the provider's token endpoint, instead of the
+    /// discovered one
+    token_endpoint: Option<Url>,
+
+    /// The URL to use for getting the provider's public keys, instead of the
+    /// discovered one
+    jwks_uri: Option<Url>,
+
+    /// The response mode we ask the provider to use for the callback
+    response_mode: Option<UpstreamOAuth2ResponseMode>,
+
+    /// How claims should be imported from the provider
+    #[serde(default)]
+    claims_imports: UpstreamOAuth2ClaimsImports,
+
+    /// Additional parameters to include in the authorization request
+    #[serde(default)]
+    additional_authorization_parameters: BTreeMap<String, String>,
+
+    /// Whether the `login_hint` should be forwarded to the provider
+    #[serde(default)]
+    forward_login_hint: bool,
+
+    /// What to do when receiving an OIDC Backchannel logout request. Defaults
+    /// to `do_nothing`.
+    #[serde(default)]
+    on_backchannel_logout: UpstreamOAuth2OnBackchannelLogout,
+
+    /// Settings for providers which speak SAML 2.0 instead of OAuth 2.0. The
+    /// `issuer` is then the entity ID of the identity provider, and the
+    /// `client_id` the entity ID of this service.
+    saml: Option<UpstreamOAuth2Saml>,
+
+    /// The position of the provider in the list shown to users. Defaults to 0.
+    #[serde(default)]
+    ui_order: i32,
+}
+
+impl ProviderRequest {
+    /// Convert the request into a provider, as it would be defined in the
+    /// configuration file, and its position in the UI
+    fn into_config(self, id: Ulid) -> (UpstreamOAuth2Provider, i32) {
+        let sign_in_with_apple =
+            self.sign_in_with_apple
+                .map(|siwa| UpstreamOAuth2SignInWithApple {
+                    private_key_file: None,
+                    private_key: Some(siwa.private_key),
+                    team_id: siwa.team_id,
+                    key_id: siwa.key_id,
+                });
+
+        let provider = UpstreamOAuth2Provider {
+            enabled: true,
+            id,
+            synapse_idp_id: None,
+            issuer: self.issuer,
+            human_name: self.human_name,
+            brand_name: self.brand_name,
+            client_id: self.client_id,
+            client_secret: self.client_secret,
+            token_endpoint_auth_method: self.token_endpoint_auth_method,
+            sign_in_with_apple,
+            token_endpoint_auth_signing_alg: self.token_endpoint_auth_signing_alg,
+            id_token_signed_response_alg: self.id_token_signed_response_alg,
+            scope: self.scope,
+            discovery_mode: self.discovery_mode,
+            pkce_method: self.pkce_method,
+            fetch_userinfo: self.fetch_userinfo,
+            userinfo_signed_response_alg: self.userinfo_signed_response_alg,
+            authorization_endpoint: self.authorization_endpoint,
+            userinfo_endpoint: self.userinfo_endpoint,
+            token_endpoint: self.token_endpoint,
+            jwks_uri: self.jwks_uri,
+            response_mode: self.response_mode,
+            claims_imports: self.claims_imports,
+            additional_authorization_parameters: self.additional_authorization_parameters,
+            forward_login_hint: self.forward_login_hint,
+            on_backchannel_logout: self.on_backchannel_logout,
+            saml: self.saml,
+        };
+
+        (provider, self.ui_order)
+    }
+}
+
+/// Encrypt the client secret of the provider, or the Sign in with Apple
+/// parameters, the same way `config sync` does
+fn encrypt_client_secret(
+    encrypter: &Encrypter,
+    provider: &UpstreamOAuth2Provider,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    if let Some(client_secret) = provider.client_secret.as_deref() {
+        Ok(Some(encrypter.encrypt_to_string(client_secret.as_bytes())?))
+    } else if let Some(siwa) = &provider.sign_in_with_apple {
+        let encoded = serde_json::to_vec(siwa)?;
+        Ok(Some(encrypter.encrypt_to_string(&encoded)?))
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, extract::State, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use mas_keystore::Encrypter;
+use oauth2_types::scope::InvalidScope;
+use serde_json::json;
+use ulid::Ulid;
+
+use super::{ProviderRequest, encrypt_client_secret};
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::UpstreamOAuthProvider,
+        params::UlidPathParam,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+    upstream_oauth2::{cache::MetadataCache, config::provider_params_from_config},
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Upstream OAuth provider ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("Upstream OAuth provider ID {0} is defined in the configuration file")]
+    StaticProvider(Ulid),
+
+    #[error("Invalid provider: {0}")]
+    InvalidProvider(String),
+
+    #[error("Invalid scope")]
+    InvalidScope(#[from] InvalidScope),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::StaticProvider(_) => StatusCode::CONFLICT,
+            Self::InvalidProvider(_) | Self::InvalidScope(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("updateUpstreamOAuthProvider")
+        .summary("Update an upstream OAuth 2.0 provider")
+        .description(
+            "This replaces the whole provider definition, including the client secret, which must be sent again if the provider uses one. \
+Disabled providers stay disabled.",
+        )
+        .tag("upstream-oauth-provider")
+        .response_with::<200, Json<SingleResponse<UpstreamOAuthProvider>>, _>(|t| {
+            let [_, _, sample] = UpstreamOAuthProvider::samples();
+            t.description("The upstream OAuth provider was updated")
+                .example(SingleResponse::new_canonical(sample))
+        })
+        .response_with::<400, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::InvalidProvider(
+                "The `issuer` field is required when discovery is enabled".to_owned(),
+            ));
+            t.description("The provider parameters are invalid")
+                .example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("Provider was not found").example(response)
+        })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::StaticProvider(Ulid::nil()));
+            t.description("Provider is defined in the configuration file")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.upstream_oauth_providers.update", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(encrypter)): NoApi<State<Encrypter>>,
+    NoApi(State(metadata_cache)): NoApi<State<MetadataCache>>,
+    id: UlidPathParam,
+    Json(params): Json<ProviderRequest>,
+) -> Result<Json<SingleResponse<UpstreamOAuthProvider>>, RouteError> {
+    let id = *id;
+    let provider = repo
+        .upstream_oauth_provider()
+        .lookup(id)
+        .await?
+        .ok_or(RouteError::NotFound(id))?;
+
+    if provider.is_static {
+        return Err(RouteError::StaticProvider(id));
+    }
+
+    let (config, ui_order) = params.into_config(id);
+    config
+        .validate()
+        .map_err(|e| RouteError::InvalidProvider(e.to_string()))?;
+
+    let encrypted_client_secret =
+        encrypt_client_secret(&encrypter, &config).map_err(RouteError::Internal)?;
+    let params = provider_params_from_config(config, encrypted_client_secret, ui_order)?;
+
+    let previous_issuer = provider.issuer.clone();
+    let provider = repo
+        .upstream_oauth_provider()
+        .update(provider, params)
+        .await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UpstreamOAuthProviderUpdate,
+            None,
+            Some(provider.id),
+            json!({ "issuer": provider.issuer, "human_name": provider.human_name }),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    // The discovery mode or the issuer may have changed, so make sure we don't
+    // use stale metadata
+    for issuer in previous_issuer.iter().chain(provider.issuer.iter()) {
+        metadata_cache.invalidate(issuer).await;
+    }
+
+    Ok(Json(SingleResponse::new_canonical(
+        UpstreamOAuthProvider::from(provider),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_update_provider(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/upstream-oauth-providers")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "human_name": "Tenant",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "none",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        let id = body["data"]["id"].as_str().unwrap().to_owned();
+
+        let request = Request::put(format!("/api/admin/v1/upstream-oauth-providers/{id}"))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "human_name": "Tenant SSO",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "none",
+                "brand_name": "tenant",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["id"], id);
+        assert_eq!(body["data"]["attributes"]["human_name"], "Tenant SSO");
+        assert_eq!(body["data"]["attributes"]["brand_name"], "tenant");
+
+        // The update is validated like the creation
+        let request = Request::put(format!("/api/admin/v1/upstream-oauth-providers/{id}"))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "issuer": "https://tenant.example.com/",
+                "client_id": "mas",
+                "token_endpoint_auth_method": "client_secret_post",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_not_found(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let provider_id = Ulid::nil();
+        let request = Request::put(format!(
+            "/api/admin/v1/upstream-oauth-providers/{provider_id}"
+        ))
+        .bearer(&token)
+        .json(serde_json::json!({
+            "issuer": "https://tenant.example.com/",
+            "client_id": "mas",
+            "token_endpoint_auth_method": "none",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
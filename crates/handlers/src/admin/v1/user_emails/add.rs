@@ -9,13 +9,14 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng, SecurityNotification};
 use mas_storage::{
-    queue::{ProvisionUserJob, QueueJobRepositoryExt as _},
+    queue::{ProvisionUserJob, QueueJobRepositoryExt as _, SendSecurityNotificationJob},
     user::UserEmailFilter,
 };
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -23,6 +24,7 @@ use crate::{
         call_context::CallContext,
         model::UserEmail,
         response::{ErrorResponse, SingleResponse},
+        scope,
     },
     impl_from_error_for_route,
 };
@@ -46,6 +48,9 @@ pub enum RouteError {
 
     #[error("User ID {0} not found")]
     UserNotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -59,6 +64,7 @@ impl IntoResponse for RouteError {
             Self::EmailAlreadyInUse(_) => StatusCode::CONFLICT,
             Self::EmailNotValid { .. } => StatusCode::BAD_REQUEST,
             Self::UserNotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -102,6 +108,11 @@ Note that this endpoint ignores any policy which would normally prevent the emai
             });
             t.description("Email is not valid").example(response)
         })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The user is an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::UserNotFound(Ulid::nil()));
             t.description("User was not found").example(response)
@@ -111,7 +122,11 @@ Note that this endpoint ignores any policy which would normally prevent the emai
 #[tracing::instrument(name = "handler.admin.v1.user_emails.add", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     Json(params): Json<Request>,
@@ -123,6 +138,10 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::UserNotFound(params.user_id))?;
 
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(params.user_id));
+    }
+
     // Validate the email
     if let Err(source) = lettre::Address::from_str(&params.email) {
         return Err(RouteError::EmailNotValid {
@@ -147,11 +166,37 @@ pub async fn handler(
         .add(&mut rng, &clock, &user, params.email)
         .await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserEmailAdd,
+            Some(user.id),
+            Some(user_email.id),
+            json!({ "email": user_email.email }),
+        )
+        .await?;
+
     // Schedule a job to update the user
     repo.queue_job()
         .schedule_job(&mut rng, &clock, ProvisionUserJob::new_for_id(user.id))
         .await?;
 
+    // Let the user know that an email address was added
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(
+                &user,
+                SecurityNotification::EmailAdded {
+                    email: user_email.email.clone(),
+                },
+            ),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok((
@@ -321,4 +366,44 @@ mod tests {
         }
         "###);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .set_can_request_admin(alice, true)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // A delegated admin can't add an email to an admin, which they could
+        // then use to recover their account
+        let request = Request::post("/api/admin/v1/user-emails")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "email": "alice@example.com",
+                "user_id": alice.id,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+        let body: serde_json::Value = response.json();
+        assert_eq!(
+            body["errors"][0]["title"],
+            format!(
+                "User ID {} is an admin, which requires the urn:mas:admin scope",
+                alice.id
+            )
+        );
+    }
 }
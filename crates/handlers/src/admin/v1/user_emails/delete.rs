@@ -7,12 +7,15 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
-use mas_storage::queue::{ProvisionUserJob, QueueJobRepositoryExt as _};
+use mas_data_model::{AuditAction, BoxRng, SecurityNotification};
+use mas_storage::queue::{
+    ProvisionUserJob, QueueJobRepositoryExt as _, SendSecurityNotificationJob,
+};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
-    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse},
+    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse, scope},
     impl_from_error_for_route,
 };
 
@@ -24,6 +27,9 @@ pub enum RouteError {
 
     #[error("User email ID {0} not found")]
     NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -35,6 +41,7 @@ impl IntoResponse for RouteError {
         let status = match self {
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -46,6 +53,11 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
         .summary("Delete a user email")
         .tag("user-email")
         .response_with::<204, (), _>(|t| t.description("User email was found"))
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The email belongs to an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
             t.description("User email was not found").example(response)
@@ -55,7 +67,11 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.user_emails.delete", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
@@ -67,11 +83,46 @@ pub async fn handler(
         .ok_or(RouteError::NotFound(*id))?;
 
     let job = ProvisionUserJob::new_for_id(email.user_id);
+    let user = repo.user().lookup(email.user_id).await?;
+    if user
+        .as_ref()
+        .is_some_and(|user| !scope::can_manage_user(session.scope(), user))
+    {
+        return Err(RouteError::AdminUser(email.user_id));
+    }
+
+    let notification = SecurityNotification::EmailRemoved {
+        email: email.email.clone(),
+    };
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserEmailRemove,
+            Some(email.user_id),
+            Some(email.id),
+            json!({ "email": email.email }),
+        )
+        .await?;
+
     repo.user_email().remove(email).await?;
 
     // Schedule a job to update the user
     repo.queue_job().schedule_job(&mut rng, &clock, job).await?;
 
+    // Let the user know that the email address was removed
+    if let Some(user) = user {
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                SendSecurityNotificationJob::new(&user, notification),
+            )
+            .await?;
+    }
+
     repo.save().await?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -138,4 +189,46 @@ mod tests {
         let response = state.request(request).await;
         response.assert_status(StatusCode::NOT_FOUND);
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_delete_admin_email(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .set_can_request_admin(alice, true)
+            .await
+            .unwrap();
+        let email = repo
+            .user_email()
+            .add(
+                &mut rng,
+                &state.clock,
+                &alice,
+                "alice@example.com".to_owned(),
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Emails of admins can't be removed by a delegated admin
+        let request = Request::delete(format!("/api/admin/v1/user-emails/{}", email.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        assert!(repo.user_email().lookup(email.id).await.unwrap().is_some());
+        repo.save().await.unwrap();
+    }
 }
@@ -0,0 +1,332 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use axum::{Json, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use mas_storage::{
+    queue::{ExportUserJob, QueueJobRepositoryExt as _},
+    user::export_user,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::info;
+use ulid::Ulid;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::UserExport,
+        response::{ErrorResponse, SingleResponse},
+        scope,
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("User ID {0} not found")]
+    UserNotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(serde_json::Error);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::UserNotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+/// # JSON payload for the `POST /api/admin/v1/user-exports` endpoint
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename = "AddUserExportRequest")]
+pub struct Request {
+    /// The ID of the user to export
+    #[schemars(with = "crate::admin::schema::Ulid")]
+    user_id: Ulid,
+
+    /// Whether to build the archive in the background, for users with a lot
+    /// of data. The export can then be polled until it is completed.
+    #[serde(default)]
+    background: bool,
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("addUserExport")
+        .summary("Export everything stored about a user")
+        .description(
+            "Build an archive of everything stored about a user, to answer a subject access request.
+The archive never contains secrets like password hashes or tokens.
+
+By default the archive is built right away.
+If `background` is set, a job is scheduled to build it, and the export has to be polled until it is completed.",
+        )
+        .tag("user-export")
+        .response_with::<201, Json<SingleResponse<UserExport>>, _>(|t| {
+            let [sample, ..] = UserExport::samples();
+            let response = SingleResponse::new_canonical(sample);
+            t.description("User export was completed").example(response)
+        })
+        .response_with::<202, Json<SingleResponse<UserExport>>, _>(|t| {
+            let [_, sample, ..] = UserExport::samples();
+            let response = SingleResponse::new_canonical(sample);
+            t.description("User export was scheduled").example(response)
+        })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The user is an admin, and the caller doesn't have full access")
+                .example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::UserNotFound(Ulid::nil()));
+            t.description("User was not found").example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.user_exports.add", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    Json(params): Json<Request>,
+) -> Result<(StatusCode, Json<SingleResponse<UserExport>>), RouteError> {
+    let user = repo
+        .user()
+        .lookup(params.user_id)
+        .await?
+        .ok_or(RouteError::UserNotFound(params.user_id))?;
+
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(params.user_id));
+    }
+
+    let user_export = repo.user_export().add(&mut rng, &clock, &user).await?;
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserExport,
+            Some(user.id),
+            Some(user_export.id),
+            json!({ "background": params.background }),
+        )
+        .await?;
+
+    let (status, user_export) = if params.background {
+        info!(%user.id, %user_export.id, "Scheduling export of user");
+        repo.queue_job()
+            .schedule_job(&mut rng, &clock, ExportUserJob::new(&user_export))
+            .await?;
+
+        (StatusCode::ACCEPTED, user_export)
+    } else {
+        let archive = export_user(&mut repo, &clock, &user).await?;
+        let archive = serde_json::to_value(&archive)?;
+        let user_export = repo
+            .user_export()
+            .complete(&clock, user_export, archive)
+            .await?;
+
+        (StatusCode::CREATED, user_export)
+    };
+
+    repo.save().await?;
+
+    Ok((
+        status,
+        Json(SingleResponse::new_canonical(user_export.into())),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use insta::assert_json_snapshot;
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_export(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.user_email()
+            .add(
+                &mut rng,
+                &state.clock,
+                &alice,
+                "alice@example.com".to_owned(),
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::post("/api/admin/v1/user-exports")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "user_id": alice.id,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["type"], "user-export");
+        let attributes = &body["data"]["attributes"];
+        assert_eq!(attributes["user_id"], alice.id.to_string());
+        assert_eq!(attributes["completed_at"], "2022-01-16T14:40:00Z");
+
+        let archive = &attributes["archive"];
+        assert_eq!(archive["version"], 1);
+        assert_eq!(archive["user"]["username"], "alice");
+        assert_eq!(archive["emails"][0]["email"], "alice@example.com");
+        // The export itself is recorded in the audit log
+        assert_eq!(archive["audit_events"][0]["action"], "user.export");
+        assert_eq!(archive["audit_events"][0]["by_user"], false);
+
+        // The export can be fetched afterwards
+        let id = body["data"]["id"].as_str().unwrap();
+        let request = Request::get(format!("/api/admin/v1/user-exports/{id}"))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let fetched: serde_json::Value = response.json();
+        assert_eq!(fetched["data"], body["data"]);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_export_background(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::post("/api/admin/v1/user-exports")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "user_id": alice.id,
+                "background": true,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::ACCEPTED);
+        let body: serde_json::Value = response.json();
+        assert!(body["data"]["attributes"]["completed_at"].is_null());
+        assert!(body["data"]["attributes"]["archive"].is_null());
+
+        // Once the job ran, the archive is available
+        state.run_jobs_in_queue().await;
+
+        let id = body["data"]["id"].as_str().unwrap();
+        let request = Request::get(format!("/api/admin/v1/user-exports/{id}"))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert!(body["data"]["attributes"]["completed_at"].is_string());
+        assert_eq!(
+            body["data"]["attributes"]["archive"]["user"]["username"],
+            "alice"
+        );
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_user_not_found(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let request = Request::post("/api/admin/v1/user-exports")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "user_id": Ulid::nil(),
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+        let body: serde_json::Value = response.json();
+        assert_json_snapshot!(body, @r###"
+        {
+          "errors": [
+            {
+              "title": "User ID 00000000000000000000000000 not found"
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_export_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .set_can_request_admin(alice, true)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Admins can't be exported by a delegated admin
+        let request = Request::post("/api/admin/v1/user-exports")
+            .bearer(&token)
+            .json(serde_json::json!({
+                "user_id": alice.id,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{OperationIo, transform::TransformOperation};
+use axum::{Json, response::IntoResponse};
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use ulid::Ulid;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::UserExport,
+        params::UlidPathParam,
+        response::{ErrorResponse, SingleResponse},
+        scope,
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("User export ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("getUserExport")
+        .summary("Get a user export")
+        .description("The archive is only present once the export is completed.")
+        .tag("user-export")
+        .response_with::<200, Json<SingleResponse<UserExport>>, _>(|t| {
+            let [sample, ..] = UserExport::samples();
+            let response = SingleResponse::new_canonical(sample);
+            t.description("User export was found").example(response)
+        })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The export is of an admin, and the caller doesn't have full access")
+                .example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("User export was not found").example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.user_exports.get", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo, session, ..
+    }: CallContext,
+    id: UlidPathParam,
+) -> Result<Json<SingleResponse<UserExport>>, RouteError> {
+    let user_export = repo
+        .user_export()
+        .lookup(*id)
+        .await?
+        .ok_or(RouteError::NotFound(*id))?;
+
+    let user = repo.user().lookup(user_export.user_id).await?;
+    if user
+        .as_ref()
+        .is_some_and(|user| !scope::can_manage_user(session.scope(), user))
+    {
+        return Err(RouteError::AdminUser(user_export.user_id));
+    }
+
+    Ok(Json(SingleResponse::new_canonical(UserExport::from(
+        user_export,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_get_admin_user_export(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .set_can_request_admin(alice, true)
+            .await
+            .unwrap();
+        let user_export = repo
+            .user_export()
+            .add(&mut rng, &state.clock, &alice)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Exports of admins can't be read by a delegated admin
+        let request = Request::get(format!("/api/admin/v1/user-exports/{}", user_export.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+    }
+}
@@ -9,10 +9,11 @@ use axum::{Json, response::IntoResponse};
 use chrono::{DateTime, Utc};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use rand::distributions::{Alphanumeric, DistString};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 
 use crate::{
     admin::{
@@ -78,7 +79,10 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.user_registration_tokens.post", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     Json(params): Json<Request>,
@@ -105,6 +109,18 @@ pub async fn handler(
         )
         .await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserRegistrationTokenAdd,
+            None,
+            Some(registration_token.id),
+            json!({ "usage_limit": registration_token.usage_limit, "expires_at": registration_token.expires_at }),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok((
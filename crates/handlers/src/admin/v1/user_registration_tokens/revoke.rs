@@ -4,10 +4,12 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -74,8 +76,12 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.user_registration_tokens.revoke", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
 ) -> Result<Json<SingleResponse<UserRegistrationToken>>, RouteError> {
     let id = *id;
@@ -93,6 +99,18 @@ pub async fn handler(
     // Revoke the token
     let token = repo.user_registration_token().revoke(&clock, token).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserRegistrationTokenRevoke,
+            None,
+            Some(token.id),
+            json!({}),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -4,13 +4,15 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use chrono::{DateTime, Utc};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
 use schemars::JsonSchema;
 use serde::{Deserialize, Deserializer};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -101,8 +103,12 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.user_registration_tokens.update", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
     Json(request): Json<Request>,
 ) -> Result<Json<SingleResponse<UserRegistrationToken>>, RouteError> {
@@ -131,6 +137,18 @@ pub async fn handler(
             .await?;
     }
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserRegistrationTokenUpdate,
+            None,
+            Some(token.id),
+            json!({ "usage_limit": token.usage_limit, "expires_at": token.expires_at }),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -3,10 +3,13 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
+use mas_storage::queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -80,8 +83,12 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.user_sessions.finish", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
 ) -> Result<Json<SingleResponse<UserSession>>, RouteError> {
     let id = *id;
@@ -99,6 +106,27 @@ pub async fn handler(
     // Finish the session
     let session = repo.browser_session().finish(&clock, session).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::BrowserSessionFinish,
+            Some(session.user.id),
+            Some(session.id),
+            json!({}),
+        )
+        .await?;
+
+    // Notify the clients which got a session through this browser session
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            BackchannelLogoutJob::for_browser_session(&session),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -10,10 +10,12 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, extract::State, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use mas_matrix::{HomeserverConnection, ProvisionRequest};
+use mas_storage::queue::{DispatchWebhookEventJob, QueueJobRepositoryExt as _};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use tracing::warn;
 
 use crate::{
@@ -37,7 +39,7 @@ fn valid_username_character(c: char) -> bool {
 }
 
 // XXX: this should be shared with the graphql handler
-fn username_valid(username: &str) -> bool {
+pub(in crate::admin) fn username_valid(username: &str) -> bool {
     if username.is_empty() || username.len() > 255 {
         return false;
     }
@@ -133,7 +135,10 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.users.add", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     State(homeserver): State<Arc<dyn HomeserverConnection>>,
@@ -165,6 +170,26 @@ pub async fn handler(
 
     let user = repo.user().add(&mut rng, &clock, params.username).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserAdd,
+            Some(user.id),
+            Some(user.id),
+            json!({}),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::user_registered(&user),
+        )
+        .await?;
+
     homeserver
         .provision_user(&ProvisionRequest::new(&user.username, &user.sub))
         .await
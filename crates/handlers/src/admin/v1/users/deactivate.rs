@@ -8,10 +8,11 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng};
 use mas_storage::queue::{DeactivateUserJob, QueueJobRepositoryExt as _};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use tracing::info;
 use ulid::Ulid;
 
@@ -21,6 +22,7 @@ use crate::{
         model::{Resource, User},
         params::UlidPathParam,
         response::{ErrorResponse, SingleResponse},
+        scope,
     },
     impl_from_error_for_route,
 };
@@ -33,6 +35,9 @@ pub enum RouteError {
 
     #[error("User ID {0} not found")]
     NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -44,6 +49,7 @@ impl IntoResponse for RouteError {
         let status = match self {
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -85,6 +91,11 @@ This invalidates any existing session, and will ask the homeserver to make them
                 SingleResponse::new(charlie, format!("/api/admin/v1/users/{id}/deactivate"));
             t.description("User was deactivated").example(response)
         })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The user is an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
             t.description("User ID not found").example(response)
@@ -94,7 +105,11 @@ This invalidates any existing session, and will ask the homeserver to make them
 #[tracing::instrument(name = "handler.admin.v1.users.deactivate", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
@@ -108,8 +123,24 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::NotFound(id))?;
 
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(id));
+    }
+
     let user = repo.user().deactivate(&clock, user).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserDeactivate,
+            Some(user.id),
+            Some(user.id),
+            json!({ "erase": !params.skip_erase }),
+        )
+        .await?;
+
     info!(%user.id, "Scheduling deactivation of user");
     repo.queue_job()
         .schedule_job(
@@ -209,6 +240,7 @@ mod tests {
               "created_at": "2022-01-16T14:40:00Z",
               "locked_at": null,
               "deactivated_at": "2022-01-16T14:40:00Z",
+              "erased_at": null,
               "admin": false,
               "legacy_guest": false
             },
@@ -290,6 +322,7 @@ mod tests {
               "created_at": "2022-01-16T14:40:00Z",
               "locked_at": "2022-01-16T14:40:00Z",
               "deactivated_at": "2022-01-16T14:41:00Z",
+              "erased_at": null,
               "admin": false,
               "legacy_guest": false
             },
@@ -321,4 +354,33 @@ mod tests {
             "User ID 01040G2081040G2081040G2081 not found"
         );
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_deactivate_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let user = repo.user().set_can_request_admin(user, true).await.unwrap();
+        repo.save().await.unwrap();
+
+        // Admins can't be deactivated by a delegated admin
+        let request = Request::post(format!("/api/admin/v1/users/{}/deactivate", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo.user().lookup(user.id).await.unwrap().unwrap();
+        assert!(user.deactivated_at.is_none());
+        repo.save().await.unwrap();
+    }
 }
@@ -0,0 +1,418 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::sync::Arc;
+
+use aide::{NoApi, OperationIo, transform::TransformOperation};
+use anyhow::Context;
+use axum::{Json, extract::State, response::IntoResponse};
+use chrono::Duration;
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::{
+    AuditAction, BoxRng, Device, Impersonation, TokenType, personal::session::PersonalSessionOwner,
+};
+use mas_matrix::HomeserverConnection;
+use mas_storage::queue::{EndImpersonationJob, QueueJobRepositoryExt as _};
+use oauth2_types::scope::Scope;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::{
+    admin::{
+        call_context::{CallContext, CallerSession},
+        model::{ImpersonationKind, ImpersonationSession, Resource},
+        params::UlidPathParam,
+        response::{ErrorResponse, SingleResponse},
+    },
+    impl_from_error_for_route,
+};
+
+/// How long an impersonation lasts if not specified, in seconds
+const DEFAULT_EXPIRES_IN: u32 = 60 * 60;
+
+/// The longest an impersonation can last, in seconds
+const MAX_EXPIRES_IN: u32 = 24 * 60 * 60;
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("User ID {0} not found")]
+    NotFound(Ulid),
+
+    #[error("User ID {0} is locked or deactivated")]
+    UserNotActive(Ulid),
+
+    #[error("OAuth 2.0 client ID {0} not found")]
+    ClientNotFound(Ulid),
+
+    #[error("OAuth 2.0 sessions need a client ID and a scope")]
+    MissingClientOrScope,
+
+    #[error("Invalid scope")]
+    InvalidScope,
+
+    #[error("Impersonation sessions can't last longer than {MAX_EXPIRES_IN} seconds")]
+    ExpiresInTooLong,
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NotFound(_) | Self::ClientNotFound(_) => StatusCode::NOT_FOUND,
+            Self::UserNotActive(_) => StatusCode::CONFLICT,
+            Self::MissingClientOrScope | Self::InvalidScope | Self::ExpiresInTooLong => {
+                StatusCode::BAD_REQUEST
+            }
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+/// # JSON payload for the `POST /api/admin/v1/users/:id/impersonate` endpoint
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename = "UserImpersonateRequest")]
+pub struct Request {
+    /// The kind of session to start
+    kind: ImpersonationKind,
+
+    /// The ID of the `OAuth2` client the session is for. Required for
+    /// OAuth 2.0 sessions.
+    #[schemars(with = "Option<crate::admin::schema::Ulid>")]
+    client_id: Option<Ulid>,
+
+    /// The `OAuth2` scopes of the session. Required for OAuth 2.0 sessions.
+    scope: Option<String>,
+
+    /// How long the impersonation lasts, in seconds. Defaults to an hour, and
+    /// can't be longer than a day.
+    expires_in: Option<u32>,
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("impersonateUser")
+        .summary("Start a session impersonating a user")
+        .description("Start a time-boxed OAuth 2.0 or compatibility session on behalf of the user, and get an access token for it.
+The session is visible to the user, and introspecting its token reports the admin who started it in the `act` claim.
+No refresh token is issued: once the impersonation ends, the session is ended and its device removed from the homeserver.")
+        .tag("user")
+        .response_with::<201, Json<SingleResponse<ImpersonationSession>>, _>(|t| {
+            let response = SingleResponse::new_canonical(ImpersonationSession::sample());
+            t.description("The impersonation session was started")
+                .example(response)
+        })
+        .response_with::<400, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::MissingClientOrScope);
+            t.description("The request is invalid").example(response)
+        })
+        .response_with::<404, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
+            t.description("User or client was not found")
+                .example(response)
+        })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::UserNotActive(Ulid::nil()));
+            t.description("User is locked or deactivated")
+                .example(response)
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.users.impersonate", skip_all)]
+pub async fn handler(
+    CallContext {
+        mut repo,
+        clock,
+        user: caller_user,
+        session: caller_session,
+        actor,
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
+    NoApi(State(homeserver)): NoApi<State<Arc<dyn HomeserverConnection>>>,
+    id: UlidPathParam,
+    Json(params): Json<Request>,
+) -> Result<(StatusCode, Json<SingleResponse<ImpersonationSession>>), RouteError> {
+    let id = *id;
+    let user = repo
+        .user()
+        .lookup(id)
+        .await?
+        .ok_or(RouteError::NotFound(id))?;
+
+    if !user.is_valid() {
+        return Err(RouteError::UserNotActive(id));
+    }
+
+    let expires_in = params.expires_in.unwrap_or(DEFAULT_EXPIRES_IN);
+    if expires_in > MAX_EXPIRES_IN {
+        return Err(RouteError::ExpiresInTooLong);
+    }
+    let expires_in = Duration::seconds(i64::from(expires_in));
+
+    // Record who is impersonating the user: the admin user, and the client
+    // they used to call the API
+    let impersonator_client_id = match &caller_session {
+        CallerSession::OAuth2Session(session) => Some(session.client_id),
+        CallerSession::PersonalSession(session) => match session.owner {
+            PersonalSessionOwner::OAuth2Client(client_id) => Some(client_id),
+            PersonalSessionOwner::User(_) => None,
+        },
+    };
+    let impersonation = Impersonation {
+        impersonator_user_id: caller_user.map(|caller| caller.id),
+        impersonator_client_id,
+        expires_at: clock.now() + expires_in,
+    };
+
+    let (resource, device, end_job) = match params.kind {
+        ImpersonationKind::OAuth2 => {
+            let (Some(client_id), Some(scope)) = (params.client_id, params.scope) else {
+                return Err(RouteError::MissingClientOrScope);
+            };
+            let scope: Scope = scope.parse().map_err(|_| RouteError::InvalidScope)?;
+
+            let client = repo
+                .oauth2_client()
+                .lookup(client_id)
+                .await?
+                .ok_or(RouteError::ClientNotFound(client_id))?;
+
+            let session = repo
+                .oauth2_session()
+                .add_impersonation(
+                    &mut rng,
+                    &clock,
+                    &client,
+                    &user,
+                    scope,
+                    impersonation.clone(),
+                )
+                .await?;
+
+            let access_token_string = TokenType::AccessToken.generate(&mut rng);
+            repo.oauth2_access_token()
+                .add(
+                    &mut rng,
+                    &clock,
+                    &session,
+                    access_token_string.clone(),
+                    Some(expires_in),
+                    None,
+                )
+                .await?;
+
+            let device = session.scope.iter().find_map(Device::from_scope_token);
+            let resource = ImpersonationSession::from_oauth2_session(
+                &session,
+                &impersonation,
+                user.id,
+                access_token_string,
+            );
+            let end_job = EndImpersonationJob::for_oauth2_session(&session);
+            (resource, device, end_job)
+        }
+
+        ImpersonationKind::Compat => {
+            let device = Device::generate(&mut rng);
+            let session = repo
+                .compat_session()
+                .add_impersonation(
+                    &mut rng,
+                    &clock,
+                    &user,
+                    device.clone(),
+                    impersonation.clone(),
+                )
+                .await?;
+
+            let access_token_string = TokenType::CompatAccessToken.generate(&mut rng);
+            repo.compat_access_token()
+                .add(
+                    &mut rng,
+                    &clock,
+                    &session,
+                    access_token_string.clone(),
+                    Some(expires_in),
+                )
+                .await?;
+
+            let end_job = EndImpersonationJob::for_compat_session(&session);
+            let resource = ImpersonationSession::from_compat_session(
+                session,
+                &impersonation,
+                access_token_string,
+            );
+            (resource, Some(device), end_job)
+        }
+    };
+
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserImpersonate,
+            Some(user.id),
+            Some(resource.id()),
+            json!({ "kind": params.kind, "expires_in": expires_in.num_seconds() }),
+        )
+        .await?;
+
+    // End the session and remove its device once the impersonation expires
+    repo.queue_job()
+        .schedule_job_later(&mut rng, &clock, end_job, impersonation.expires_at)
+        .await?;
+
+    if let Some(device) = device {
+        // Lock the user sync to make sure we don't get into a race condition
+        repo.user().acquire_lock_for_sync(&user).await?;
+
+        // NOTE: We haven't relinquished the repo at this point, so we are
+        // holding a transaction across the homeserver operation, like when
+        // creating personal sessions.
+        homeserver
+            .upsert_device(&user.username, device.as_str(), None)
+            .await
+            .context("Failed to provision device")
+            .map_err(|e| RouteError::Internal(e.into()))?;
+    }
+
+    repo.save().await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SingleResponse::new_canonical(resource)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_storage::{RepositoryAccess, compat::CompatSessionRepository, user::UserRepository};
+    use sqlx::PgPool;
+    use ulid::Ulid;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_impersonate(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::post(format!("/api/admin/v1/users/{}/impersonate", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "kind": "compat",
+                "expires_in": 600,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["type"], "impersonation-session");
+        assert_eq!(body["data"]["attributes"]["kind"], "compat");
+        assert_eq!(
+            body["data"]["attributes"]["expires_at"],
+            "2022-01-16T14:50:00Z"
+        );
+        assert!(body["data"]["attributes"]["impersonator_client_id"].is_string());
+
+        // The session is marked as impersonated
+        let id: Ulid = body["data"]["id"].as_str().unwrap().parse().unwrap();
+        let mut repo = state.repository().await.unwrap();
+        let session = repo.compat_session().lookup(id).await.unwrap().unwrap();
+        let impersonation = session.impersonation.expect("session to be impersonated");
+        assert_eq!(
+            impersonation.expires_at.to_rfc3339(),
+            "2022-01-16T14:50:00+00:00"
+        );
+        repo.save().await.unwrap();
+
+        // Start an OAuth 2.0 session for the client which called the API
+        let client_id = body["data"]["attributes"]["impersonator_client_id"].clone();
+        let request = Request::post(format!("/api/admin/v1/users/{}/impersonate", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "kind": "oauth2",
+                "client_id": client_id,
+                "scope": "openid",
+                "expires_in": 600,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["attributes"]["kind"], "oauth2");
+        assert_eq!(body["data"]["attributes"]["scope"], "openid");
+
+        // The token works, until the impersonation ends
+        let access_token = body["data"]["attributes"]["access_token"].as_str().unwrap();
+        assert!(state.is_access_token_valid(access_token).await);
+        state.clock.advance(chrono::Duration::minutes(11));
+        assert!(!state.is_access_token_valid(access_token).await);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_impersonate_invalid(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // OAuth 2.0 sessions need a client
+        let request = Request::post(format!("/api/admin/v1/users/{}/impersonate", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "kind": "oauth2",
+                "scope": "openid",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Impersonation is time-boxed
+        let request = Request::post(format!("/api/admin/v1/users/{}/impersonate", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "kind": "compat",
+                "expires_in": 7 * 24 * 60 * 60,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Delegated admins can't impersonate users
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let request = Request::post(format!("/api/admin/v1/users/{}/impersonate", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "kind": "compat",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+    }
+}
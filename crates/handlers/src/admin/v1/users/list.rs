@@ -253,6 +253,7 @@ mod tests {
                 "created_at": "2022-01-16T14:40:00Z",
                 "locked_at": null,
                 "deactivated_at": null,
+                "erased_at": null,
                 "admin": false,
                 "legacy_guest": false
               },
@@ -273,6 +274,7 @@ mod tests {
                 "created_at": "2022-01-16T14:40:00Z",
                 "locked_at": null,
                 "deactivated_at": null,
+                "erased_at": null,
                 "admin": false,
                 "legacy_guest": false
               },
@@ -312,6 +314,7 @@ mod tests {
                 "created_at": "2022-01-16T14:40:00Z",
                 "locked_at": null,
                 "deactivated_at": null,
+                "erased_at": null,
                 "admin": false,
                 "legacy_guest": false
               },
@@ -332,6 +335,7 @@ mod tests {
                 "created_at": "2022-01-16T14:40:00Z",
                 "locked_at": null,
                 "deactivated_at": null,
+                "erased_at": null,
                 "admin": false,
                 "legacy_guest": false
               },
@@ -389,6 +393,7 @@ mod tests {
                 "created_at": "2022-01-16T14:40:00Z",
                 "locked_at": null,
                 "deactivated_at": null,
+                "erased_at": null,
                 "admin": false,
                 "legacy_guest": false
               },
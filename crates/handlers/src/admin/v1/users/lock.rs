@@ -4,10 +4,13 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng, SecurityNotification};
+use mas_storage::queue::{QueueJobRepositoryExt as _, SendSecurityNotificationJob};
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -16,6 +19,7 @@ use crate::{
         model::{Resource, User},
         params::UlidPathParam,
         response::{ErrorResponse, SingleResponse},
+        scope,
     },
     impl_from_error_for_route,
 };
@@ -28,6 +32,9 @@ pub enum RouteError {
 
     #[error("User ID {0} not found")]
     NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -39,6 +46,7 @@ impl IntoResponse for RouteError {
         let status = match self {
             Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -58,6 +66,11 @@ This DOES NOT invalidate any existing session, meaning that all their existing s
             let response = SingleResponse::new(charlie, format!("/api/admin/v1/users/{id}/lock"));
             t.description("User was locked").example(response)
         })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The user is an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
             t.description("User ID not found").example(response)
@@ -67,8 +80,13 @@ This DOES NOT invalidate any existing session, meaning that all their existing s
 #[tracing::instrument(name = "handler.admin.v1.users.lock", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
 ) -> Result<Json<SingleResponse<User>>, RouteError> {
     let id = *id;
@@ -78,8 +96,35 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::NotFound(id))?;
 
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(id));
+    }
+
+    let was_locked = user.locked_at.is_some();
     let user = repo.user().lock(&clock, user).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserLock,
+            Some(user.id),
+            Some(user.id),
+            json!({}),
+        )
+        .await?;
+
+    if !was_locked {
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                SendSecurityNotificationJob::new(&user, SecurityNotification::AccountLocked),
+            )
+            .await?;
+    }
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -179,4 +224,33 @@ mod tests {
             "User ID 01040G2081040G2081040G2081 not found"
         );
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_lock_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let user = repo.user().set_can_request_admin(user, true).await.unwrap();
+        repo.save().await.unwrap();
+
+        // Admins can't be locked by a delegated admin
+        let request = Request::post(format!("/api/admin/v1/users/{}/lock", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo.user().lookup(user.id).await.unwrap().unwrap();
+        assert!(user.locked_at.is_none());
+        repo.save().await.unwrap();
+    }
 }
@@ -7,7 +7,9 @@
 mod add;
 mod by_username;
 mod deactivate;
+mod erase;
 mod get;
+mod impersonate;
 mod list;
 mod lock;
 mod reactivate;
@@ -15,11 +17,14 @@ mod set_admin;
 mod set_password;
 mod unlock;
 
+pub(in crate::admin) use self::add::username_valid;
 pub use self::{
     add::{doc as add_doc, handler as add},
     by_username::{doc as by_username_doc, handler as by_username},
     deactivate::{doc as deactivate_doc, handler as deactivate},
+    erase::{doc as erase_doc, handler as erase},
     get::{doc as get_doc, handler as get},
+    impersonate::{doc as impersonate_doc, handler as impersonate},
     list::{doc as list_doc, handler as list},
     lock::{doc as lock_doc, handler as lock},
     reactivate::{doc as reactivate_doc, handler as reactivate},
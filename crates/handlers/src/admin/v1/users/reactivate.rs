@@ -5,11 +5,13 @@
 
 use std::sync::Arc;
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, extract::State, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
 use mas_matrix::HomeserverConnection;
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -18,6 +20,7 @@ use crate::{
         model::{Resource, User},
         params::UlidPathParam,
         response::{ErrorResponse, SingleResponse},
+        scope,
     },
     impl_from_error_for_route,
 };
@@ -33,6 +36,12 @@ pub enum RouteError {
 
     #[error("User ID {0} not found")]
     NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
+
+    #[error("User ID {0} was erased and can't be reactivated")]
+    Erased(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -44,6 +53,8 @@ impl IntoResponse for RouteError {
         let status = match self {
             Self::Internal(_) | Self::Homeserver(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::AdminUser(_) => StatusCode::FORBIDDEN,
+            Self::Erased(_) => StatusCode::CONFLICT,
         };
         (status, sentry_event_id, Json(error)).into_response()
     }
@@ -63,15 +74,32 @@ This DOES NOT unlock a locked user, which is still prevented from doing any acti
             let response = SingleResponse::new(sample, format!("/api/admin/v1/users/{id}/reactivate"));
             t.description("User was reactivated").example(response)
         })
+        .response_with::<403, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::AdminUser(Ulid::nil()));
+            t.description("The user is an admin, and the caller doesn't have full access")
+                .example(response)
+        })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
             t.description("User ID not found").example(response)
         })
+        .response_with::<409, RouteError, _>(|t| {
+            let response = ErrorResponse::from_error(&RouteError::Erased(Ulid::nil()));
+            t.description("The personal data of the user was erased")
+                .example(response)
+        })
 }
 
 #[tracing::instrument(name = "handler.admin.v1.users.reactivate", skip_all)]
 pub async fn handler(
-    CallContext { mut repo, .. }: CallContext,
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     State(homeserver): State<Arc<dyn HomeserverConnection>>,
     id: UlidPathParam,
 ) -> Result<Json<SingleResponse<User>>, RouteError> {
@@ -82,6 +110,14 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::NotFound(id))?;
 
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(id));
+    }
+
+    if user.erased_at.is_some() {
+        return Err(RouteError::Erased(id));
+    }
+
     // Call the homeserver synchronously to reactivate the user
     homeserver
         .reactivate_user(&user.username)
@@ -91,6 +127,18 @@ pub async fn handler(
     // Now reactivate the user in our database
     let user = repo.user().reactivate(user).await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserReactivate,
+            Some(user.id),
+            Some(user.id),
+            json!({}),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -219,4 +267,33 @@ mod tests {
             "User ID 01040G2081040G2081040G2081 not found"
         );
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_reactivate_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let user = repo.user().set_can_request_admin(user, true).await.unwrap();
+        let user = repo.user().deactivate(&state.clock, user).await.unwrap();
+        repo.save().await.unwrap();
+
+        // Admins can't be reactivated by a delegated admin
+        let request = Request::post(format!("/api/admin/v1/users/{}/reactivate", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo.user().lookup(user.id).await.unwrap().unwrap();
+        assert!(user.deactivated_at.is_some());
+        repo.save().await.unwrap();
+    }
 }
@@ -4,12 +4,14 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use aide::{OperationIo, transform::TransformOperation};
+use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
+use mas_data_model::{AuditAction, BoxRng};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use ulid::Ulid;
 
 use crate::{
@@ -75,7 +77,13 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 
 #[tracing::instrument(name = "handler.admin.v1.users.set_admin", skip_all)]
 pub async fn handler(
-    CallContext { mut repo, .. }: CallContext,
+    CallContext {
+        mut repo,
+        clock,
+        actor,
+        ..
+    }: CallContext,
+    NoApi(mut rng): NoApi<BoxRng>,
     id: UlidPathParam,
     Json(params): Json<Request>,
 ) -> Result<Json<SingleResponse<User>>, RouteError> {
@@ -91,6 +99,18 @@ pub async fn handler(
         .set_can_request_admin(user, params.admin)
         .await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserSetAdmin,
+            Some(user.id),
+            Some(user.id),
+            json!({ "admin": params.admin }),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(Json(SingleResponse::new(
@@ -158,4 +178,78 @@ mod tests {
         assert!(!user.can_request_admin);
         repo.save().await.unwrap();
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_delegated_admin_cant_promote(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state
+            .token_with_scope("urn:mas:admin:read urn:mas:admin:users")
+            .await;
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // The token can manage users...
+        let request = Request::post(format!("/api/admin/v1/users/{}/lock", user.id))
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        // ...but not promote them to admins
+        let request = Request::post(format!("/api/admin/v1/users/{}/set-admin", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "admin": true,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo.user().lookup(user.id).await.unwrap().unwrap();
+        assert!(!user.can_request_admin);
+        repo.save().await.unwrap();
+
+        // Tokens without any admin scope are rejected altogether
+        let token = state.token_with_scope("openid").await;
+        let request = Request::get("/api/admin/v1/users").bearer(&token).empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_delegated_admin_cant_demote(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let user = repo.user().set_can_request_admin(user, true).await.unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::post(format!("/api/admin/v1/users/{}/set-admin", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "admin": false,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo.user().lookup(user.id).await.unwrap().unwrap();
+        assert!(user.can_request_admin);
+        repo.save().await.unwrap();
+    }
 }
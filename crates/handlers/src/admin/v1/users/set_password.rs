@@ -8,14 +8,16 @@ use aide::{NoApi, OperationIo, transform::TransformOperation};
 use axum::{Json, extract::State, response::IntoResponse};
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
-use mas_data_model::BoxRng;
+use mas_data_model::{AuditAction, BoxRng, SecurityNotification};
+use mas_storage::queue::{QueueJobRepositoryExt as _, SendSecurityNotificationJob};
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde_json::json;
 use ulid::Ulid;
 use zeroize::Zeroizing;
 
 use crate::{
-    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse},
+    admin::{call_context::CallContext, params::UlidPathParam, response::ErrorResponse, scope},
     impl_from_error_for_route,
     passwords::PasswordManager,
 };
@@ -29,6 +31,9 @@ pub enum RouteError {
     #[error("Password is too weak")]
     PasswordTooWeak,
 
+    #[error("Password was recently used")]
+    PasswordReused,
+
     #[error("Password auth is disabled")]
     PasswordAuthDisabled,
 
@@ -37,6 +42,9 @@ pub enum RouteError {
 
     #[error("User ID {0} not found")]
     NotFound(Ulid),
+
+    #[error("User ID {0} is an admin, which requires the urn:mas:admin scope")]
+    AdminUser(Ulid),
 }
 
 impl_from_error_for_route!(mas_storage::RepositoryError);
@@ -47,8 +55,8 @@ impl IntoResponse for RouteError {
         let sentry_event_id = record_error!(self, Self::Internal(_) | Self::Password(_));
         let status = match self {
             Self::Internal(_) | Self::Password(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            Self::PasswordAuthDisabled => StatusCode::FORBIDDEN,
-            Self::PasswordTooWeak => StatusCode::BAD_REQUEST,
+            Self::PasswordAuthDisabled | Self::AdminUser(_) => StatusCode::FORBIDDEN,
+            Self::PasswordTooWeak | Self::PasswordReused => StatusCode::BAD_REQUEST,
             Self::NotFound(_) => StatusCode::NOT_FOUND,
         };
         (status, sentry_event_id, Json(error)).into_response()
@@ -63,7 +71,7 @@ pub struct Request {
     #[schemars(example = &"hunter2")]
     password: String,
 
-    /// Skip the password complexity check
+    /// Skip the password complexity and history checks
     skip_password_check: Option<bool>,
 }
 
@@ -75,12 +83,15 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
         .response_with::<204, (), _>(|t| t.description("Password was set"))
         .response_with::<400, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::PasswordTooWeak);
-            t.description("Password is too weak").example(response)
+            t.description("Password is too weak or was recently used")
+                .example(response)
         })
         .response_with::<403, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::PasswordAuthDisabled);
-            t.description("Password auth is disabled in the server configuration")
-                .example(response)
+            t.description(
+                "Password auth is disabled in the server configuration, or the user is an admin and the caller doesn't have full access",
+            )
+            .example(response)
         })
         .response_with::<404, RouteError, _>(|t| {
             let response = ErrorResponse::from_error(&RouteError::NotFound(Ulid::nil()));
@@ -91,7 +102,11 @@ pub fn doc(operation: TransformOperation) -> TransformOperation {
 #[tracing::instrument(name = "handler.admin.v1.users.set_password", skip_all)]
 pub async fn handler(
     CallContext {
-        mut repo, clock, ..
+        mut repo,
+        clock,
+        actor,
+        session,
+        ..
     }: CallContext,
     NoApi(mut rng): NoApi<BoxRng>,
     State(password_manager): State<PasswordManager>,
@@ -108,6 +123,10 @@ pub async fn handler(
         .await?
         .ok_or(RouteError::NotFound(*id))?;
 
+    if !scope::can_manage_user(session.scope(), &user) {
+        return Err(RouteError::AdminUser(*id));
+    }
+
     let skip_password_check = params.skip_password_check.unwrap_or(false);
     tracing::info!(skip_password_check, "skip_password_check");
     if !skip_password_check
@@ -118,6 +137,15 @@ pub async fn handler(
         return Err(RouteError::PasswordTooWeak);
     }
 
+    if !skip_password_check
+        && password_manager
+            .is_password_reused(&mut repo, &user, &params.password)
+            .await
+            .map_err(RouteError::Password)?
+    {
+        return Err(RouteError::PasswordReused);
+    }
+
     let password = Zeroizing::new(params.password);
     let (version, hashed_password) = password_manager
         .hash(&mut rng, password)
@@ -128,6 +156,26 @@ pub async fn handler(
         .add(&mut rng, &clock, &user, version, hashed_password, None)
         .await?;
 
+    repo.audit_event()
+        .add(
+            &mut rng,
+            &clock,
+            actor,
+            AuditAction::UserSetPassword,
+            Some(user.id),
+            Some(user.id),
+            json!({ "skip_password_check": skip_password_check }),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(&user, SecurityNotification::PasswordChanged),
+        )
+        .await?;
+
     repo.save().await?;
 
     Ok(StatusCode::NO_CONTENT)
@@ -253,6 +301,63 @@ mod tests {
         assert_eq!(res, PasswordVerificationResult::Success(()));
     }
 
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_reused_password(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        state.password_manager = state.password_manager.clone().with_history(2);
+        let token = state.token_with_scope("urn:mas:admin").await;
+
+        // Create a user
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let user_id = user.id;
+
+        for password in [
+            "this is a good enough password",
+            "this is another good enough password",
+        ] {
+            let request = Request::post(format!("/api/admin/v1/users/{user_id}/set-password"))
+                .bearer(&token)
+                .json(serde_json::json!({
+                    "password": password,
+                }));
+
+            let response = state.request(request).await;
+            response.assert_status(StatusCode::NO_CONTENT);
+        }
+
+        // Setting back the first password is refused
+        let request = Request::post(format!("/api/admin/v1/users/{user_id}/set-password"))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "password": "this is a good enough password",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["errors"][0]["title"], "Password was recently used");
+
+        // Unless the checks are skipped
+        let request = Request::post(format!("/api/admin/v1/users/{user_id}/set-password"))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "password": "this is a good enough password",
+                "skip_password_check": true,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NO_CONTENT);
+    }
+
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
     async fn test_unknown_user(pool: PgPool) {
         setup();
@@ -295,4 +400,45 @@ mod tests {
         let body: serde_json::Value = response.json();
         assert_eq!(body["errors"][0]["title"], "Password auth is disabled");
     }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_set_password_admin_user(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin:users").await;
+        let mut rng = state.rng();
+
+        let mut repo = state.repository().await.unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let user = repo.user().set_can_request_admin(user, true).await.unwrap();
+        repo.save().await.unwrap();
+
+        // A delegated admin can't take over an admin by setting their password
+        let request = Request::post(format!("/api/admin/v1/users/{}/set-password", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "password": "this is a good enough password",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        let mut repo = state.repository().await.unwrap();
+        let password = repo.user_password().active(&user).await.unwrap();
+        assert!(password.is_none());
+        repo.save().await.unwrap();
+
+        // With full access, it works
+        let token = state.token_with_scope("urn:mas:admin").await;
+        let request = Request::post(format!("/api/admin/v1/users/{}/set-password", user.id))
+            .bearer(&token)
+            .json(serde_json::json!({
+                "password": "this is a good enough password",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NO_CONTENT);
+    }
 }
@@ -0,0 +1,322 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use aide::{OperationIo, transform::TransformOperation};
+use axum::{Json, response::IntoResponse};
+use axum_extra::extract::{Query, QueryRejection};
+use axum_macros::FromRequestParts;
+use hyper::StatusCode;
+use mas_axum_utils::record_error;
+use mas_data_model::WebhookEvent;
+use mas_storage::{Page, webhook::WebhookDeliveryFilter};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    admin::{
+        call_context::CallContext,
+        model::{Resource, WebhookDelivery},
+        params::{IncludeCount, Pagination},
+        response::{ErrorResponse, PaginatedResponse},
+    },
+    impl_from_error_for_route,
+};
+
+#[derive(Deserialize, JsonSchema, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl std::fmt::Display for WebhookDeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Delivered => write!(f, "delivered"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+#[derive(FromRequestParts, Deserialize, JsonSchema, OperationIo)]
+#[serde(rename = "WebhookDeliveryFilter")]
+#[aide(input_with = "Query<FilterParams>")]
+#[from_request(via(Query), rejection(RouteError))]
+pub struct FilterParams {
+    /// Retrieve the deliveries of the given event
+    #[serde(rename = "filter[event]")]
+    #[schemars(with = "Option<crate::admin::schema::WebhookEvent>")]
+    event: Option<WebhookEvent>,
+
+    /// Retrieve the deliveries with the given status
+    ///
+    /// Defaults to retrieve all deliveries, including pending, delivered and
+    /// failed ones.
+    ///
+    /// * `pending`: Only retrieve deliveries which are still being attempted
+    ///
+    /// * `delivered`: Only retrieve deliveries which the endpoint acknowledged
+    ///
+    /// * `failed`: Only retrieve deliveries which were abandoned
+    #[serde(rename = "filter[status]")]
+    status: Option<WebhookDeliveryStatus>,
+}
+
+impl std::fmt::Display for FilterParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut sep = '?';
+
+        if let Some(event) = self.event {
+            write!(f, "{sep}filter[event]={event}")?;
+            sep = '&';
+        }
+        if let Some(status) = self.status {
+            write!(f, "{sep}filter[status]={status}")?;
+            sep = '&';
+        }
+
+        let _ = sep;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error, OperationIo)]
+#[aide(output_with = "Json<ErrorResponse>")]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("Invalid filter parameters")]
+    InvalidFilter(#[from] QueryRejection),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let error = ErrorResponse::from_error(&self);
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+        let status = match self {
+            Self::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidFilter(_) => StatusCode::BAD_REQUEST,
+        };
+        (status, sentry_event_id, Json(error)).into_response()
+    }
+}
+
+pub fn doc(operation: TransformOperation) -> TransformOperation {
+    operation
+        .id("listWebhookDeliveries")
+        .summary("List webhook deliveries")
+        .description(
+            "Retrieve a list of deliveries of lifecycle events to webhook endpoints, along with their attempts, oldest first.
+Use the `page[last]` parameter to retrieve the most recent deliveries.",
+        )
+        .tag("webhook-delivery")
+        .response_with::<200, Json<PaginatedResponse<WebhookDelivery>>, _>(|t| {
+            let deliveries = WebhookDelivery::samples();
+            let pagination = mas_storage::Pagination::first(deliveries.len());
+            let page = Page {
+                edges: deliveries
+                    .into_iter()
+                    .map(|node| mas_storage::pagination::Edge {
+                        cursor: node.id(),
+                        node,
+                    })
+                    .collect(),
+                has_next_page: true,
+                has_previous_page: false,
+            };
+
+            t.description("Paginated response of webhook deliveries")
+                .example(PaginatedResponse::for_page(
+                    page,
+                    pagination,
+                    Some(42),
+                    WebhookDelivery::PATH,
+                ))
+        })
+}
+
+#[tracing::instrument(name = "handler.admin.v1.webhook_deliveries.list", skip_all)]
+pub async fn handler(
+    CallContext { mut repo, .. }: CallContext,
+    Pagination(pagination, include_count): Pagination,
+    params: FilterParams,
+) -> Result<Json<PaginatedResponse<WebhookDelivery>>, RouteError> {
+    let base = format!("{path}{params}", path = WebhookDelivery::PATH);
+    let base = include_count.add_to_base(&base);
+    let filter = WebhookDeliveryFilter::new();
+
+    let filter = match params.event {
+        Some(event) => filter.with_event(event),
+        None => filter,
+    };
+
+    let filter = match params.status {
+        Some(WebhookDeliveryStatus::Pending) => filter.pending_only(),
+        Some(WebhookDeliveryStatus::Delivered) => filter.delivered_only(),
+        Some(WebhookDeliveryStatus::Failed) => filter.failed_only(),
+        None => filter,
+    };
+
+    let response = match include_count {
+        IncludeCount::True => {
+            let page = repo
+                .webhook_delivery()
+                .list(filter, pagination)
+                .await?
+                .map(WebhookDelivery::from);
+            let count = repo.webhook_delivery().count(filter).await?;
+            PaginatedResponse::for_page(page, pagination, Some(count), &base)
+        }
+        IncludeCount::False => {
+            let page = repo
+                .webhook_delivery()
+                .list(filter, pagination)
+                .await?
+                .map(WebhookDelivery::from);
+            PaginatedResponse::for_page(page, pagination, None, &base)
+        }
+        IncludeCount::Only => {
+            let count = repo.webhook_delivery().count(filter).await?;
+            PaginatedResponse::for_count_only(count, &base)
+        }
+    };
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_data_model::WebhookEvent;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_list(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        let token = state.token_with_scope("urn:mas:admin").await;
+        let mut rng = state.rng();
+
+        // Record two deliveries, one of which failed once then succeeded
+        let url: url::Url = "https://hooks.example.com/mas".parse().unwrap();
+        let mut repo = state.repository().await.unwrap();
+        let registered = repo
+            .webhook_delivery()
+            .add(
+                &mut rng,
+                &state.clock,
+                WebhookEvent::UserRegistered,
+                url.clone(),
+                json!({ "type": "user.registered" }),
+            )
+            .await
+            .unwrap();
+        let registered = repo
+            .webhook_delivery()
+            .add_attempt(
+                &mut rng,
+                &state.clock,
+                registered,
+                Some(503),
+                Some("Endpoint responded with 503 Service Unavailable".to_owned()),
+            )
+            .await
+            .unwrap();
+        let registered = repo
+            .webhook_delivery()
+            .add_attempt(&mut rng, &state.clock, registered, Some(204), None)
+            .await
+            .unwrap();
+        let registered = repo
+            .webhook_delivery()
+            .mark_as_delivered(&state.clock, registered)
+            .await
+            .unwrap();
+        repo.webhook_delivery()
+            .add(
+                &mut rng,
+                &state.clock,
+                WebhookEvent::UserDeactivated,
+                url,
+                json!({ "type": "user.deactivated" }),
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::get("/api/admin/v1/webhook-deliveries")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 2);
+        assert_eq!(body["data"][0]["type"], "webhook-delivery");
+        assert_eq!(body["data"][0]["attributes"]["event"], "user.registered");
+        assert_eq!(
+            body["data"][0]["attributes"]["attempts"][0]["status_code"],
+            503
+        );
+        assert_eq!(
+            body["data"][0]["attributes"]["attempts"][1]["status_code"],
+            204
+        );
+
+        // Filter by event
+        let request =
+            Request::get("/api/admin/v1/webhook-deliveries?filter[event]=user.deactivated")
+                .bearer(&token)
+                .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 1);
+        assert_eq!(body["data"][0]["attributes"]["event"], "user.deactivated");
+
+        // Filter by status
+        let request = Request::get("/api/admin/v1/webhook-deliveries?filter[status]=delivered")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["meta"]["count"], 1);
+        assert_eq!(body["data"][0]["id"], registered.id.to_string());
+        assert_eq!(
+            body["links"]["self"],
+            "/api/admin/v1/webhook-deliveries?filter[status]=delivered&page[first]=10"
+        );
+
+        // Lookup a single delivery
+        let request = Request::get(format!(
+            "/api/admin/v1/webhook-deliveries/{}",
+            registered.id
+        ))
+        .bearer(&token)
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(
+            body["data"]["attributes"]["attempts"][1]["error"],
+            json!(null)
+        );
+
+        // Unknown event
+        let request = Request::get("/api/admin/v1/webhook-deliveries?filter[event]=user.exploded")
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
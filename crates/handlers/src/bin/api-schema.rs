@@ -57,10 +57,13 @@ impl_from_ref!(mas_router::UrlBuilder);
 impl_from_ref!(mas_templates::Templates);
 impl_from_ref!(Arc<dyn mas_matrix::HomeserverConnection>);
 impl_from_ref!(mas_keystore::Keystore);
+impl_from_ref!(mas_keystore::Encrypter);
 impl_from_ref!(mas_handlers::passwords::PasswordManager);
+impl_from_ref!(mas_handlers::MetadataCache);
 impl_from_ref!(Arc<mas_policy::PolicyFactory>);
 impl_from_ref!(mas_data_model::SiteConfig);
 impl_from_ref!(mas_data_model::AppVersion);
+impl_from_ref!(mas_handlers::DPoPReplayCache);
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (mut api, _) = mas_handlers::admin_api_router::<DummyState>();
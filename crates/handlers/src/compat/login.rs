@@ -12,8 +12,8 @@ use chrono::Duration;
 use hyper::StatusCode;
 use mas_axum_utils::record_error;
 use mas_data_model::{
-    BoxClock, BoxRng, Clock, CompatSession, CompatSsoLoginState, Device, SiteConfig, TokenType,
-    User,
+    BoxClock, BoxRng, Clock, CompatSession, CompatSsoLoginState, Device, SecurityNotification,
+    SiteConfig, TokenType, User,
 };
 use mas_matrix::HomeserverConnection;
 use mas_storage::{
@@ -22,7 +22,10 @@ use mas_storage::{
         CompatAccessTokenRepository, CompatRefreshTokenRepository, CompatSessionRepository,
         CompatSsoLoginRepository,
     },
-    queue::{QueueJobRepositoryExt as _, SyncDevicesJob},
+    queue::{
+        DispatchWebhookEventJob, QueueJobRepositoryExt as _, SendSecurityNotificationJob,
+        SyncDevicesJob,
+    },
     user::{UserPasswordRepository, UserRepository},
 };
 use opentelemetry::{Key, KeyValue, metrics::Counter};
@@ -35,7 +38,10 @@ use zeroize::Zeroizing;
 use super::{MatrixError, MatrixJsonBody};
 use crate::{
     BoundActivityTracker, Limiter, METER, RequesterFingerprint, impl_from_error_for_route,
-    passwords::{PasswordManager, PasswordVerificationResult},
+    passwords::{
+        PasswordManager, PasswordVerificationResult,
+        ldap::{self, LdapLoginError},
+    },
     rate_limit::PasswordCheckLimitedError,
 };
 
@@ -211,6 +217,9 @@ pub enum RouteError {
     #[error("user is locked")]
     UserLocked,
 
+    #[error("user has a second factor")]
+    SecondFactorRequired,
+
     #[error("failed to provision device")]
     ProvisionDeviceFailed(#[source] anyhow::Error),
 }
@@ -274,6 +283,11 @@ impl IntoResponse for RouteError {
                 error: "User account has been locked",
                 status: StatusCode::UNAUTHORIZED,
             },
+            Self::SecondFactorRequired => MatrixError {
+                errcode: "M_FORBIDDEN",
+                error: "Password login is not available for accounts with two-factor authentication",
+                status: StatusCode::FORBIDDEN,
+            },
         };
 
         (sentry_event_id, response).into_response()
@@ -329,6 +343,7 @@ pub(crate) async fn post(
                 &limiter,
                 requester,
                 &mut repo,
+                &*homeserver,
                 username,
                 password,
                 input.device_id, // TODO check for validity
@@ -361,6 +376,25 @@ pub(crate) async fn post(
             .await?;
     }
 
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(
+                &user,
+                SecurityNotification::new_compat_session(&session),
+            ),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::compat_session_created(&session),
+        )
+        .await?;
+
     let user_id = homeserver.mxid(&user.username);
 
     // If the client asked for a refreshable token, make it expire
@@ -578,11 +612,100 @@ async fn user_password_login(
     limiter: &Limiter,
     requester: RequesterFingerprint,
     repo: &mut BoxRepository,
+    homeserver: &dyn HomeserverConnection,
     username: &str,
     password: String,
     requested_device_id: Option<String>,
     initial_device_display_name: Option<String>,
 ) -> Result<(CompatSession, User), RouteError> {
+    let password = Zeroizing::new(password);
+
+    let user = if let Some(ldap) = password_manager.ldap() {
+        let (user, _user_ldap_link) = ldap::login(
+            &mut rng, clock, repo, homeserver, limiter, requester, ldap, username, &password,
+        )
+        .await
+        .map_err(|e| match e {
+            LdapLoginError::UserNotFound | LdapLoginError::UsernameNotAvailable { .. } => {
+                RouteError::UserNotFound
+            }
+            LdapLoginError::PasswordMismatch => RouteError::PasswordMismatch,
+            LdapLoginError::RateLimited(e) => RouteError::RateLimited(e),
+            e => RouteError::Internal(Box::new(e)),
+        })?;
+
+        if user.deactivated_at.is_some() {
+            return Err(RouteError::UserNotFound);
+        }
+
+        if user.locked_at.is_some() {
+            return Err(RouteError::UserLocked);
+        }
+
+        user
+    } else {
+        local_password_login(
+            &mut rng,
+            clock,
+            password_manager,
+            limiter,
+            requester,
+            repo,
+            username,
+            password,
+        )
+        .await?
+    };
+
+    // The password alone isn't enough for users who set up a second factor, and
+    // there is no way to ask for it here: they have to log in through SSO
+    let user_totp = repo.user_totp().find_for_user(&user).await?;
+    if user_totp.is_some_and(|user_totp| user_totp.is_confirmed()) {
+        return Err(RouteError::SecondFactorRequired);
+    }
+
+    // We're about to create a device, let's explicitly acquire a lock, so that
+    // any concurrent sync will read after we've committed
+    repo.user().acquire_lock_for_sync(&user).await?;
+
+    // Now that the user credentials have been verified, start a new compat session
+    let device = if let Some(requested_device_id) = requested_device_id {
+        Device::from(requested_device_id)
+    } else {
+        Device::generate(&mut rng)
+    };
+
+    repo.app_session()
+        .finish_sessions_to_replace_device(clock, &user, &device)
+        .await?;
+
+    let session = repo
+        .compat_session()
+        .add(
+            &mut rng,
+            clock,
+            &user,
+            device,
+            None,
+            false,
+            initial_device_display_name,
+        )
+        .await?;
+
+    Ok((session, user))
+}
+
+/// Find a user and check its password against the local password hash
+async fn local_password_login(
+    mut rng: &mut (impl RngCore + CryptoRng + Send),
+    clock: &impl Clock,
+    password_manager: &PasswordManager,
+    limiter: &Limiter,
+    requester: RequesterFingerprint,
+    repo: &mut BoxRepository,
+    username: &str,
+    password: Zeroizing<String>,
+) -> Result<User, RouteError> {
     // Find the user
     let user = repo
         .user()
@@ -596,7 +719,7 @@ async fn user_password_login(
     }
 
     // Check the rate limit
-    limiter.check_password(requester, &user)?;
+    limiter.check_password(requester, &user).await?;
 
     // Lookup its password
     let user_password = repo
@@ -606,8 +729,6 @@ async fn user_password_login(
         .ok_or(RouteError::NoPassword)?;
 
     // Verify the password
-    let password = Zeroizing::new(password);
-
     match password_manager
         .verify_and_upgrade(
             &mut rng,
@@ -636,35 +757,7 @@ async fn user_password_login(
         }
     }
 
-    // We're about to create a device, let's explicitly acquire a lock, so that
-    // any concurrent sync will read after we've committed
-    repo.user().acquire_lock_for_sync(&user).await?;
-
-    // Now that the user credentials have been verified, start a new compat session
-    let device = if let Some(requested_device_id) = requested_device_id {
-        Device::from(requested_device_id)
-    } else {
-        Device::generate(&mut rng)
-    };
-
-    repo.app_session()
-        .finish_sessions_to_replace_device(clock, &user, &device)
-        .await?;
-
-    let session = repo
-        .compat_session()
-        .add(
-            &mut rng,
-            clock,
-            &user,
-            device,
-            None,
-            false,
-            initial_device_display_name,
-        )
-        .await?;
-
-    Ok((session, user))
+    Ok(user)
 }
 
 #[cfg(test)]
@@ -1177,6 +1270,55 @@ mod tests {
         "###);
     }
 
+    /// Test that users with a second factor can't log in with only their
+    /// password.
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_password_login_with_totp(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let user = user_with_password(&state, "alice", "password", false).await;
+
+        let mut repo = state.repository().await.unwrap();
+        let user_totp = repo
+            .user_totp()
+            .add(&mut state.rng(), &state.clock, &user, "secret".to_owned())
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        let request = Request::post("/_matrix/client/v3/login").json(serde_json::json!({
+            "type": "m.login.password",
+            "identifier": {
+                "type": "m.id.user",
+                "user": "alice",
+            },
+            "password": "password",
+        }));
+
+        // An unconfirmed TOTP credential doesn't prevent logging in
+        let response = state.request(request.clone()).await;
+        response.assert_status(StatusCode::OK);
+
+        let mut repo = state.repository().await.unwrap();
+        repo.user_totp()
+            .confirm(&state.clock, user_totp, 0)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Once confirmed, the password alone isn't enough
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+        let body: serde_json::Value = response.json();
+        insta::assert_json_snapshot!(body, @r###"
+        {
+          "errcode": "M_FORBIDDEN",
+          "error": "Password login is not available for accounts with two-factor authentication"
+        }
+        "###);
+    }
+
     /// Test the response of an unsupported password identifier.
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
     async fn test_unsupported_login_identifier(pool: PgPool) {
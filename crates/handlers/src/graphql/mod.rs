@@ -23,14 +23,18 @@ use axum::{
 use axum_extra::typed_header::TypedHeader;
 use chrono::{DateTime, Utc};
 use futures_util::TryStreamExt;
-use headers::{Authorization, ContentType, HeaderValue, authorization::Bearer};
+use headers::{ContentType, HeaderValue};
 use hyper::header::CACHE_CONTROL;
 use mas_axum_utils::{
-    InternalError, SessionInfo, SessionInfoExt, cookies::CookieJar, sentry::SentryEventID,
+    InternalError, SessionInfo, SessionInfoExt,
+    cookies::CookieJar,
+    dpop::{AccessTokenAuthorization, DPoPReplayCache},
+    sentry::SentryEventID,
 };
 use mas_data_model::{
-    BoxClock, BoxRng, BrowserSession, Clock, Session, SiteConfig, SystemClock, User,
+    AuditActor, BoxClock, BoxRng, BrowserSession, Clock, Session, SiteConfig, SystemClock, User,
 };
+use mas_keystore::Encrypter;
 use mas_matrix::HomeserverConnection;
 use mas_policy::{InstantiateError, Policy, PolicyFactory};
 use mas_router::UrlBuilder;
@@ -41,6 +45,7 @@ use rand_chacha::ChaChaRng;
 use state::has_session_ended;
 use tracing::{Instrument, info_span};
 use ulid::Ulid;
+use url::Url;
 
 mod model;
 mod mutations;
@@ -76,6 +81,7 @@ struct GraphQLState {
     password_manager: PasswordManager,
     url_builder: UrlBuilder,
     limiter: Limiter,
+    encrypter: Encrypter,
 }
 
 #[async_trait::async_trait]
@@ -108,6 +114,10 @@ impl state::State for GraphQLState {
         &self.limiter
     }
 
+    fn encrypter(&self) -> &Encrypter {
+        &self.encrypter
+    }
+
     fn clock(&self) -> BoxClock {
         let clock = SystemClock::default();
         Box::new(clock)
@@ -131,6 +141,7 @@ pub fn schema(
     password_manager: PasswordManager,
     url_builder: UrlBuilder,
     limiter: Limiter,
+    encrypter: Encrypter,
 ) -> Schema {
     let state = GraphQLState {
         repository_factory,
@@ -140,6 +151,7 @@ pub fn schema(
         password_manager,
         url_builder,
         limiter,
+        encrypter,
     };
     let state: BoxState = Box::new(state);
 
@@ -229,6 +241,7 @@ impl IntoResponse for RouteError {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn get_requester(
     undocumented_oauth2_access: bool,
     clock: &impl Clock,
@@ -236,9 +249,11 @@ async fn get_requester(
     mut repo: BoxRepository,
     session_info: &SessionInfo,
     user_agent: Option<String>,
-    token: Option<&str>,
+    authorization: Option<&AccessTokenAuthorization>,
+    url: &Url,
+    dpop_replay_cache: &DPoPReplayCache,
 ) -> Result<Requester, RouteError> {
-    let entity = if let Some(token) = token {
+    let entity = if let Some(authorization) = authorization {
         // If we haven't enabled undocumented_oauth2_access on the listener, we bail out
         if !undocumented_oauth2_access {
             return Err(RouteError::InvalidToken);
@@ -246,7 +261,7 @@ async fn get_requester(
 
         let token = repo
             .oauth2_access_token()
-            .find_by_token(token)
+            .find_by_token(authorization.token())
             .await?
             .ok_or(RouteError::InvalidToken)?;
 
@@ -279,6 +294,19 @@ async fn get_requester(
             return Err(RouteError::InvalidToken);
         }
 
+        // Check that the token is presented according to its DPoP binding
+        if authorization
+            .verify_binding(
+                token.dpop_jkt.as_deref(),
+                url,
+                clock.now(),
+                dpop_replay_cache,
+            )
+            .is_err()
+        {
+            return Err(RouteError::InvalidToken);
+        }
+
         if !session.scope.contains("urn:mas:graphql:*") {
             return Err(RouteError::MissingScope);
         }
@@ -308,6 +336,8 @@ async fn get_requester(
 
 pub async fn post(
     AxumState(schema): AxumState<Schema>,
+    AxumState(url_builder): AxumState<UrlBuilder>,
+    AxumState(dpop_replay_cache): AxumState<DPoPReplayCache>,
     Extension(ExtraRouterParameters {
         undocumented_oauth2_access,
     }): Extension<ExtraRouterParameters>,
@@ -316,14 +346,11 @@ pub async fn post(
     activity_tracker: BoundActivityTracker,
     cookie_jar: CookieJar,
     content_type: Option<TypedHeader<ContentType>>,
-    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    authorization: Option<AccessTokenAuthorization>,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
     body: Body,
 ) -> Result<impl IntoResponse, RouteError> {
     let body = body.into_data_stream();
-    let token = authorization
-        .as_ref()
-        .map(|TypedHeader(Authorization(bearer))| bearer.token());
     let user_agent = user_agent.map(|TypedHeader(h)| h.to_string());
     let (session_info, mut cookie_jar) = cookie_jar.session_info();
     let requester = get_requester(
@@ -333,7 +360,9 @@ pub async fn post(
         repo,
         &session_info,
         user_agent,
-        token,
+        authorization.as_ref(),
+        &url_builder.graphql_endpoint(),
+        &dpop_replay_cache,
     )
     .await?;
 
@@ -368,6 +397,8 @@ pub async fn post(
 
 pub async fn get(
     AxumState(schema): AxumState<Schema>,
+    AxumState(url_builder): AxumState<UrlBuilder>,
+    AxumState(dpop_replay_cache): AxumState<DPoPReplayCache>,
     Extension(ExtraRouterParameters {
         undocumented_oauth2_access,
     }): Extension<ExtraRouterParameters>,
@@ -375,13 +406,10 @@ pub async fn get(
     repo: BoxRepository,
     activity_tracker: BoundActivityTracker,
     cookie_jar: CookieJar,
-    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    authorization: Option<AccessTokenAuthorization>,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
     RawQuery(query): RawQuery,
 ) -> Result<impl IntoResponse, InternalError> {
-    let token = authorization
-        .as_ref()
-        .map(|TypedHeader(Authorization(bearer))| bearer.token());
     let user_agent = user_agent.map(|TypedHeader(h)| h.to_string());
     let (session_info, mut cookie_jar) = cookie_jar.session_info();
     let requester = get_requester(
@@ -391,7 +419,9 @@ pub async fn get(
         repo,
         &session_info,
         user_agent,
-        token,
+        authorization.as_ref(),
+        &url_builder.graphql_endpoint(),
+        &dpop_replay_cache,
     )
     .await?;
 
@@ -454,6 +484,22 @@ impl Requester {
             user_agent: self.user_agent.clone(),
         }
     }
+
+    /// The requester, as recorded in the audit log
+    pub fn audit_actor(&self) -> AuditActor {
+        let session_id = match &self.entity {
+            RequestingEntity::BrowserSession(session) => Some(session.id),
+            RequestingEntity::OAuth2Session(tuple) => Some(tuple.0.id),
+            RequestingEntity::Anonymous => None,
+        };
+
+        AuditActor {
+            user_id: self.entity.user().map(|user| user.id),
+            session_id,
+            ip_address: self.ip_address,
+            user_agent: self.user_agent.clone(),
+        }
+    }
 }
 
 impl Deref for Requester {
@@ -500,6 +546,12 @@ impl OwnerId for mas_data_model::UserEmail {
     }
 }
 
+impl OwnerId for mas_data_model::UserPasskey {
+    fn owner_id(&self) -> Option<Ulid> {
+        Some(self.user_id)
+    }
+}
+
 impl OwnerId for Session {
     fn owner_id(&self) -> Option<Ulid> {
         self.user_id
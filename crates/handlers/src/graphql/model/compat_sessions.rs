@@ -11,7 +11,7 @@ use mas_data_model::Device;
 use mas_storage::{compat::CompatSessionRepository, user::UserRepository};
 use url::Url;
 
-use super::{BrowserSession, NodeType, SessionState, User, UserAgent};
+use super::{BrowserSession, Impersonation, NodeType, SessionState, User, UserAgent};
 use crate::graphql::state::ContextExt;
 
 /// Lazy-loaded reverse reference.
@@ -170,6 +170,11 @@ impl CompatSession {
     pub async fn human_name(&self) -> Option<&str> {
         self.session.human_name.as_deref()
     }
+
+    /// Set if the session was started by an admin to impersonate the user.
+    pub async fn impersonation(&self) -> Option<Impersonation> {
+        self.session.impersonation.clone().map(Impersonation)
+    }
 }
 
 /// A compat SSO login represents a login done through the legacy Matrix login
@@ -4,8 +4,11 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use async_graphql::{Enum, Interface, Object, SimpleObject};
+use async_graphql::{Context, Description, Enum, Interface, Object, SimpleObject};
 use chrono::{DateTime, Utc};
+use mas_storage::{oauth2::OAuth2ClientRepository, user::UserRepository};
+
+use crate::graphql::state::ContextExt;
 
 mod browser_sessions;
 mod compat_sessions;
@@ -26,7 +29,9 @@ pub use self::{
     oauth::{OAuth2Client, OAuth2Session},
     site_config::{SITE_CONFIG_ID, SiteConfig},
     upstream_oauth::{UpstreamOAuth2Link, UpstreamOAuth2Provider},
-    users::{AppSession, User, UserEmail, UserEmailAuthentication, UserRecoveryTicket},
+    users::{
+        AppSession, User, UserEmail, UserEmailAuthentication, UserPasskey, UserRecoveryTicket,
+    },
     viewer::{Anonymous, Viewer, ViewerSession},
 };
 
@@ -43,6 +48,7 @@ pub enum CreationEvent {
     BrowserSession(Box<BrowserSession>),
     UserEmail(Box<UserEmail>),
     UserEmailAuthentication(Box<UserEmailAuthentication>),
+    UserPasskey(Box<UserPasskey>),
     UserRecoveryTicket(Box<UserRecoveryTicket>),
     UpstreamOAuth2Provider(Box<UpstreamOAuth2Provider>),
     UpstreamOAuth2Link(Box<UpstreamOAuth2Link>),
@@ -135,3 +141,50 @@ impl From<mas_data_model::UserAgent> for UserAgent {
         }
     }
 }
+
+/// Details about a session which was started by an admin to impersonate the
+/// user.
+#[derive(Description)]
+pub struct Impersonation(pub mas_data_model::Impersonation);
+
+#[Object(use_type_description)]
+impl Impersonation {
+    /// When the impersonation ends. The session can't be used after this.
+    async fn expires_at(&self) -> DateTime<Utc> {
+        self.0.expires_at
+    }
+
+    /// The username of the admin who started the session, if known.
+    async fn impersonator_username(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<String>, async_graphql::Error> {
+        let Some(user_id) = self.0.impersonator_user_id else {
+            return Ok(None);
+        };
+
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+        let user = repo.user().lookup(user_id).await?;
+        repo.cancel().await?;
+
+        Ok(user.map(|user| user.username))
+    }
+
+    /// The OAuth 2.0 client used to start the session, if known.
+    async fn impersonator_client(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<OAuth2Client>, async_graphql::Error> {
+        let Some(client_id) = self.0.impersonator_client_id else {
+            return Ok(None);
+        };
+
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+        let client = repo.oauth2_client().lookup(client_id).await?;
+        repo.cancel().await?;
+
+        Ok(client.map(OAuth2Client))
+    }
+}
@@ -11,7 +11,7 @@ use mas_storage::{oauth2::OAuth2ClientRepository, user::BrowserSessionRepository
 use oauth2_types::oidc::ApplicationType;
 use url::Url;
 
-use super::{BrowserSession, NodeType, SessionState, User, UserAgent};
+use super::{BrowserSession, Impersonation, NodeType, SessionState, User, UserAgent};
 use crate::graphql::{UserId, state::ContextExt};
 
 /// An OAuth 2.0 session represents a client session which used the OAuth APIs
@@ -132,6 +132,11 @@ impl OAuth2Session {
     pub async fn human_name(&self) -> Option<&str> {
         self.0.human_name.as_deref()
     }
+
+    /// Set if the session was started by an admin to impersonate the user.
+    pub async fn impersonation(&self) -> Option<Impersonation> {
+        self.0.impersonation.clone().map(Impersonation)
+    }
 }
 
 /// The application type advertised by the client.
@@ -59,6 +59,12 @@ pub struct SiteConfig {
 
     /// Experimental plan management iframe URI.
     plan_management_iframe_uri: Option<String>,
+
+    /// Whether users can register passkeys and use them to log in.
+    passkeys_enabled: bool,
+
+    /// Whether users can enrol a TOTP authenticator app as a second factor.
+    totp_enabled: bool,
 }
 
 #[derive(SimpleObject)]
@@ -106,6 +112,8 @@ impl SiteConfig {
             minimum_password_complexity: data_model.minimum_password_complexity,
             login_with_email_allowed: data_model.login_with_email_allowed,
             plan_management_iframe_uri: data_model.plan_management_iframe_uri.clone(),
+            passkeys_enabled: data_model.passkeys_enabled,
+            totp_enabled: data_model.totp_enabled,
         }
     }
 }
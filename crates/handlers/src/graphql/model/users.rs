@@ -17,7 +17,10 @@ use mas_storage::{
     compat::{CompatSessionFilter, CompatSsoLoginFilter, CompatSsoLoginRepository},
     oauth2::{OAuth2SessionFilter, OAuth2SessionRepository},
     upstream_oauth2::{UpstreamOAuthLinkFilter, UpstreamOAuthLinkRepository},
-    user::{BrowserSessionFilter, BrowserSessionRepository, UserEmailFilter, UserEmailRepository},
+    user::{
+        BrowserSessionFilter, BrowserSessionRepository, UserEmailFilter, UserEmailRepository,
+        UserPasskeyFilter, UserPasskeyRepository, UserTotpRepository,
+    },
 };
 
 use super::{
@@ -385,6 +388,66 @@ impl User {
         .await
     }
 
+    /// Get the list of passkeys, chronologically sorted
+    async fn passkeys(
+        &self,
+        ctx: &Context<'_>,
+
+        #[graphql(desc = "Returns the elements in the list that come after the cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the elements in the list that come before the cursor.")]
+        before: Option<String>,
+        #[graphql(desc = "Returns the first *n* elements from the list.")] first: Option<i32>,
+        #[graphql(desc = "Returns the last *n* elements from the list.")] last: Option<i32>,
+    ) -> Result<Connection<Cursor, UserPasskey, PreloadedTotalCount>, async_graphql::Error> {
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            async |after, before, first, last| {
+                let after_id = after
+                    .map(|x: OpaqueCursor<NodeCursor>| x.extract_for_type(NodeType::UserPasskey))
+                    .transpose()?;
+                let before_id = before
+                    .map(|x: OpaqueCursor<NodeCursor>| x.extract_for_type(NodeType::UserPasskey))
+                    .transpose()?;
+                let pagination = Pagination::try_new(before_id, after_id, first, last)?;
+
+                let filter = UserPasskeyFilter::new().for_user(&self.0);
+
+                let page = repo.user_passkey().list(filter, pagination).await?;
+
+                // Preload the total count if requested
+                let count = if ctx.look_ahead().field("totalCount").exists() {
+                    Some(repo.user_passkey().count(filter).await?)
+                } else {
+                    None
+                };
+
+                repo.cancel().await?;
+
+                let mut connection = Connection::with_additional_fields(
+                    page.has_previous_page,
+                    page.has_next_page,
+                    PreloadedTotalCount(count),
+                );
+                connection.edges.extend(page.edges.into_iter().map(|edge| {
+                    Edge::new(
+                        OpaqueCursor(NodeCursor(NodeType::UserPasskey, edge.cursor)),
+                        UserPasskey(edge.node),
+                    )
+                }));
+
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
     /// Get the list of OAuth 2.0 sessions, chronologically sorted
     #[allow(clippy::too_many_arguments)]
     async fn oauth2_sessions(
@@ -714,6 +777,29 @@ impl User {
 
         Ok(password.is_some())
     }
+
+    /// Check if the user enrolled a TOTP authenticator app as a second factor.
+    async fn has_totp(&self, ctx: &Context<'_>) -> Result<bool, async_graphql::Error> {
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+
+        let user_totp = repo.user_totp().find_for_user(&self.0).await?;
+
+        Ok(user_totp.is_some_and(|user_totp| user_totp.is_confirmed()))
+    }
+
+    /// Get the number of recovery codes the user has not used yet.
+    async fn recovery_codes_remaining(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<usize, async_graphql::Error> {
+        let state = ctx.state();
+        let mut repo = state.repository().await?;
+
+        let codes = repo.user_totp().unused_recovery_codes(&self.0).await?;
+
+        Ok(codes.len())
+    }
 }
 
 /// A session in an application, either a compatibility or an OAuth 2.0 one
@@ -752,6 +838,34 @@ impl UserEmail {
     }
 }
 
+/// A passkey registered by a user
+#[derive(Description)]
+pub struct UserPasskey(pub mas_data_model::UserPasskey);
+
+#[Object(use_type_description)]
+impl UserPasskey {
+    /// ID of the object.
+    pub async fn id(&self) -> ID {
+        NodeType::UserPasskey.id(self.0.id)
+    }
+
+    /// Human-readable name of the passkey
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// When the object was created.
+    pub async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// When the passkey was last used to authenticate. Is `null` if the passkey
+    /// was never used.
+    async fn last_used_at(&self) -> Option<DateTime<Utc>> {
+        self.0.last_used_at
+    }
+}
+
 /// The state of a compatibility session.
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
 pub enum UserEmailState {
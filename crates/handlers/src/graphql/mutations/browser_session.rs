@@ -5,7 +5,12 @@
 // Please see LICENSE files in the repository root for full details.
 
 use async_graphql::{Context, Enum, ID, InputObject, Object};
-use mas_storage::RepositoryAccess;
+use mas_data_model::AuditAction;
+use mas_storage::{
+    RepositoryAccess,
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _},
+};
+use serde_json::json;
 
 use crate::graphql::{
     model::{BrowserSession, NodeType},
@@ -73,6 +78,7 @@ impl BrowserSessionMutations {
 
         let mut repo = state.repository().await?;
         let clock = state.clock();
+        let mut rng = state.rng();
 
         let session = repo.browser_session().lookup(browser_session_id).await?;
 
@@ -86,6 +92,27 @@ impl BrowserSessionMutations {
 
         let session = repo.browser_session().finish(&clock, session).await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::BrowserSessionFinish,
+                Some(session.user.id),
+                Some(session.id),
+                json!({}),
+            )
+            .await?;
+
+        // Notify the clients which got a session through this browser session
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                BackchannelLogoutJob::for_browser_session(&session),
+            )
+            .await?;
+
         repo.save().await?;
 
         // If we are ending the *current* session, we need to clear the session cookie
@@ -6,11 +6,13 @@
 
 use anyhow::Context as _;
 use async_graphql::{Context, Enum, ID, InputObject, Object};
+use mas_data_model::AuditAction;
 use mas_storage::{
     RepositoryAccess,
     compat::CompatSessionRepository,
     queue::{QueueJobRepositoryExt as _, SyncDevicesJob},
 };
+use serde_json::json;
 
 use crate::graphql::{
     model::{CompatSession, NodeType},
@@ -80,7 +82,7 @@ pub enum SetCompatSessionNamePayload {
     NotFound,
 
     /// The session was updated.
-    Updated(mas_data_model::CompatSession),
+    Updated(Box<mas_data_model::CompatSession>),
 }
 
 /// The status of the `setCompatSessionName` mutation.
@@ -106,7 +108,7 @@ impl SetCompatSessionNamePayload {
     /// The session that was updated.
     async fn oauth2_session(&self) -> Option<CompatSession> {
         match self {
-            Self::Updated(session) => Some(CompatSession::new(session.clone())),
+            Self::Updated(session) => Some(CompatSession::new(*session.clone())),
             Self::NotFound => None,
         }
     }
@@ -149,6 +151,18 @@ impl CompatSessionMutations {
 
         let session = repo.compat_session().finish(&clock, session).await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::CompatSessionFinish,
+                Some(session.user_id),
+                Some(session.id),
+                json!({}),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(EndCompatSessionPayload::Ended(Box::new(session)))
@@ -196,6 +210,6 @@ impl CompatSessionMutations {
 
         repo.save().await?;
 
-        Ok(SetCompatSessionNamePayload::Updated(session))
+        Ok(SetCompatSessionNamePayload::Updated(Box::new(session)))
     }
 }
@@ -10,6 +10,8 @@ mod matrix;
 mod oauth2_session;
 mod user;
 mod user_email;
+mod user_passkey;
+mod user_totp;
 
 use anyhow::Context as _;
 use async_graphql::MergedObject;
@@ -24,6 +26,8 @@ use crate::passwords::PasswordManager;
 #[derive(Default, MergedObject)]
 pub struct Mutation(
     user_email::UserEmailMutations,
+    user_passkey::UserPasskeyMutations,
+    user_totp::UserTotpMutations,
     user::UserMutations,
     oauth2_session::OAuth2SessionMutations,
     compat_session::CompatSessionMutations,
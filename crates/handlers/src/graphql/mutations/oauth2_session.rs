@@ -7,17 +7,18 @@
 use anyhow::Context as _;
 use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
 use chrono::Duration;
-use mas_data_model::{Device, TokenType};
+use mas_data_model::{AuditAction, Device, TokenType};
 use mas_storage::{
     RepositoryAccess,
     oauth2::{
         OAuth2AccessTokenRepository, OAuth2ClientRepository, OAuth2RefreshTokenRepository,
         OAuth2SessionRepository,
     },
-    queue::{QueueJobRepositoryExt as _, SyncDevicesJob},
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _, SyncDevicesJob},
     user::UserRepository,
 };
 use oauth2_types::scope::Scope;
+use serde_json::json;
 
 use crate::graphql::{
     model::{NodeType, OAuth2Session},
@@ -228,7 +229,7 @@ impl OAuth2SessionMutations {
         };
         let access_token = repo
             .oauth2_access_token()
-            .add(&mut rng, &clock, &session, access_token, ttl)
+            .add(&mut rng, &clock, &session, access_token, ttl, None)
             .await?;
 
         let refresh_token = if permanent {
@@ -244,6 +245,21 @@ impl OAuth2SessionMutations {
             Some(refresh_token)
         };
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::OAuth2SessionAdd,
+                Some(user.id),
+                Some(session.id),
+                json!({
+                    "scope": session.scope.to_string(),
+                    "expires_at": access_token.expires_at,
+                }),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(CreateOAuth2SessionPayload {
@@ -290,6 +306,27 @@ impl OAuth2SessionMutations {
 
         let session = repo.oauth2_session().finish(&clock, session).await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::OAuth2SessionFinish,
+                session.user_id,
+                Some(session.id),
+                json!({}),
+            )
+            .await?;
+
+        // Notify the client that the session ended
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                BackchannelLogoutJob::for_oauth2_session(&session),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(EndOAuth2SessionPayload::Ended(Box::new(session)))
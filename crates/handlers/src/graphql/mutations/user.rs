@@ -6,13 +6,15 @@
 
 use anyhow::Context as _;
 use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
+use mas_data_model::{AuditAction, SecurityNotification};
 use mas_storage::{
     queue::{
-        DeactivateUserJob, ProvisionUserJob, QueueJobRepositoryExt as _,
-        SendAccountRecoveryEmailsJob,
+        DeactivateUserJob, DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        SendAccountRecoveryEmailsJob, SendSecurityNotificationJob,
     },
     user::UserRepository,
 };
+use serde_json::json;
 use tracing::{info, warn};
 use ulid::Ulid;
 use url::Url;
@@ -297,6 +299,12 @@ enum SetPasswordStatus {
     /// security requirements.
     InvalidNewPassword,
 
+    /// The new password appears in a corpus of breached passwords.
+    BreachedNewPassword,
+
+    /// The new password is one of the last passwords of the user.
+    ReusedNewPassword,
+
     /// You aren't allowed to set the password for that user.
     /// This happens if you aren't setting your own password and you aren't a
     /// server administrator.
@@ -518,6 +526,26 @@ impl UserMutations {
             .schedule_job(&mut rng, &clock, ProvisionUserJob::new(&user))
             .await?;
 
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                DispatchWebhookEventJob::user_registered(&user),
+            )
+            .await?;
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserAdd,
+                Some(user.id),
+                Some(user.id),
+                json!({ "username": user.username }),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(AddUserPayload::Added(user))
@@ -548,6 +576,7 @@ impl UserMutations {
         };
 
         let deactivate = input.deactivate.unwrap_or(false);
+        let was_locked = user.locked_at.is_some();
 
         let user = repo.user().lock(&state.clock(), user).await?;
 
@@ -556,8 +585,29 @@ impl UserMutations {
             repo.queue_job()
                 .schedule_job(&mut rng, &clock, DeactivateUserJob::new(&user, deactivate))
                 .await?;
+        } else if !was_locked {
+            // The deactivation job sends its own notification
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    SendSecurityNotificationJob::new(&user, SecurityNotification::AccountLocked),
+                )
+                .await?;
         }
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserLock,
+                Some(user.id),
+                Some(user.id),
+                json!({ "deactivate": deactivate }),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(LockUserPayload::Locked(user))
@@ -571,6 +621,8 @@ impl UserMutations {
     ) -> Result<UnlockUserPayload, async_graphql::Error> {
         let state = ctx.state();
         let requester = ctx.requester();
+        let clock = state.clock();
+        let mut rng = state.rng();
         let matrix = state.homeserver_connection();
 
         if !requester.is_admin() {
@@ -592,6 +644,18 @@ impl UserMutations {
         let user = repo.user().reactivate(user).await?;
         let user = repo.user().unlock(user).await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserUnlock,
+                Some(user.id),
+                Some(user.id),
+                json!({}),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(UnlockUserPayload::Unlocked(user))
@@ -606,6 +670,8 @@ impl UserMutations {
     ) -> Result<SetCanRequestAdminPayload, async_graphql::Error> {
         let state = ctx.state();
         let requester = ctx.requester();
+        let clock = state.clock();
+        let mut rng = state.rng();
 
         if !requester.is_admin() {
             return Err(async_graphql::Error::new("Unauthorized"));
@@ -625,6 +691,18 @@ impl UserMutations {
             .set_can_request_admin(user, input.can_request_admin)
             .await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserSetAdmin,
+                Some(user.id),
+                Some(user.id),
+                json!({ "admin": user.can_request_admin }),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(SetCanRequestAdminPayload::Updated(user))
@@ -645,10 +723,8 @@ impl UserMutations {
         }
 
         let mut repo = state.repository().await?;
-        let user = repo.user().lookup(user_id).await?;
-        repo.cancel().await?;
-
-        let Some(user) = user else {
+        let Some(user) = repo.user().lookup(user_id).await? else {
+            repo.cancel().await?;
             return Ok(AllowUserCrossSigningResetPayload::NotFound);
         };
 
@@ -657,6 +733,20 @@ impl UserMutations {
             .await
             .context("Failed to allow cross-signing reset")?;
 
+        repo.audit_event()
+            .add(
+                &mut state.rng(),
+                &state.clock(),
+                requester.audit_actor(),
+                AuditAction::UserAllowCrossSigningReset,
+                Some(user.id),
+                Some(user.id),
+                json!({}),
+            )
+            .await?;
+
+        repo.save().await?;
+
         Ok(AllowUserCrossSigningResetPayload::Allowed(user))
     }
 
@@ -703,6 +793,15 @@ impl UserMutations {
             });
         }
 
+        if password_manager
+            .is_password_breached(&input.new_password)
+            .await?
+        {
+            return Ok(SetPasswordPayload {
+                status: SetPasswordStatus::BreachedNewPassword,
+            });
+        }
+
         let mut repo = state.repository().await?;
         let Some(user) = repo.user().lookup(user_id).await? else {
             return Ok(SetPasswordPayload {
@@ -752,6 +851,15 @@ impl UserMutations {
             }
         }
 
+        if password_manager
+            .is_password_reused(&mut repo, &user, &input.new_password)
+            .await?
+        {
+            return Ok(SetPasswordPayload {
+                status: SetPasswordStatus::ReusedNewPassword,
+            });
+        }
+
         let (new_password_version, new_password_hash) = password_manager
             .hash(state.rng(), Zeroizing::new(input.new_password))
             .await?;
@@ -767,6 +875,26 @@ impl UserMutations {
             )
             .await?;
 
+        repo.queue_job()
+            .schedule_job(
+                &mut state.rng(),
+                &state.clock(),
+                SendSecurityNotificationJob::new(&user, SecurityNotification::PasswordChanged),
+            )
+            .await?;
+
+        repo.audit_event()
+            .add(
+                &mut state.rng(),
+                &state.clock(),
+                requester.audit_actor(),
+                AuditAction::UserSetPassword,
+                Some(user.id),
+                Some(user.id),
+                json!({}),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(SetPasswordPayload {
@@ -803,6 +931,15 @@ impl UserMutations {
             });
         }
 
+        if password_manager
+            .is_password_breached(&input.new_password)
+            .await?
+        {
+            return Ok(SetPasswordPayload {
+                status: SetPasswordStatus::BreachedNewPassword,
+            });
+        }
+
         let mut repo = state.repository().await?;
 
         let Some(ticket) = repo.user_recovery().find_ticket(&input.ticket).await? else {
@@ -847,6 +984,15 @@ impl UserMutations {
             });
         }
 
+        if password_manager
+            .is_password_reused(&mut repo, &user, &input.new_password)
+            .await?
+        {
+            return Ok(SetPasswordPayload {
+                status: SetPasswordStatus::ReusedNewPassword,
+            });
+        }
+
         let (new_password_version, new_password_hash) = password_manager
             .hash(state.rng(), Zeroizing::new(input.new_password))
             .await?;
@@ -862,6 +1008,26 @@ impl UserMutations {
             )
             .await?;
 
+        repo.queue_job()
+            .schedule_job(
+                &mut state.rng(),
+                &state.clock(),
+                SendSecurityNotificationJob::new(&user, SecurityNotification::PasswordChanged),
+            )
+            .await?;
+
+        repo.audit_event()
+            .add(
+                &mut state.rng(),
+                &state.clock(),
+                requester.audit_actor(),
+                AuditAction::UserSetPassword,
+                Some(user.id),
+                Some(user.id),
+                json!({ "recovery": true }),
+            )
+            .await?;
+
         // Mark the session as consumed
         repo.user_recovery()
             .consume_ticket(&clock, ticket, session)
@@ -901,8 +1067,9 @@ impl UserMutations {
             .await?
             .context("Could not load recovery session")?;
 
-        if let Err(e) =
-            limiter.check_account_recovery(requester.fingerprint(), &recovery_session.email)
+        if let Err(e) = limiter
+            .check_account_recovery(requester.fingerprint(), &recovery_session.email)
+            .await
         {
             tracing::warn!(error = &e as &dyn std::error::Error);
             return Ok(ResendRecoveryEmailPayload::RateLimited);
@@ -979,6 +1146,18 @@ impl UserMutations {
             )
             .await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserDeactivate,
+                Some(user.id),
+                Some(user.id),
+                json!({ "erase": input.hs_erase }),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(DeactivateUserPayload::Deactivated(user))
@@ -6,12 +6,17 @@
 
 use anyhow::Context as _;
 use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
+use mas_data_model::{AuditAction, SecurityNotification};
 use mas_i18n::DataLocale;
 use mas_storage::{
     RepositoryAccess,
-    queue::{ProvisionUserJob, QueueJobRepositoryExt as _, SendEmailAuthenticationCodeJob},
+    queue::{
+        DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        SendEmailAuthenticationCodeJob, SendSecurityNotificationJob,
+    },
     user::{UserEmailFilter, UserEmailRepository, UserRepository},
 };
+use serde_json::json;
 
 use super::verify_password_if_needed;
 use crate::graphql::{
@@ -470,6 +475,31 @@ impl UserEmailMutations {
                 .add(&mut rng, &clock, &user, input.email)
                 .await?;
 
+            repo.audit_event()
+                .add(
+                    &mut rng,
+                    &clock,
+                    requester.audit_actor(),
+                    AuditAction::UserEmailAdd,
+                    Some(user.id),
+                    Some(user_email.id),
+                    json!({ "email": user_email.email }),
+                )
+                .await?;
+
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    SendSecurityNotificationJob::new(
+                        &user,
+                        SecurityNotification::EmailAdded {
+                            email: user_email.email.clone(),
+                        },
+                    ),
+                )
+                .await?;
+
             (true, user_email)
         };
 
@@ -533,6 +563,18 @@ impl UserEmailMutations {
 
         // TODO: don't allow removing the last email address
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserEmailRemove,
+                Some(user.id),
+                Some(user_email.id),
+                json!({ "email": user_email.email }),
+            )
+            .await?;
+
         repo.user_email().remove(user_email.clone()).await?;
 
         // Schedule a job to update the user
@@ -540,6 +582,19 @@ impl UserEmailMutations {
             .schedule_job(&mut rng, &clock, ProvisionUserJob::new(&user))
             .await?;
 
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                SendSecurityNotificationJob::new(
+                    &user,
+                    SecurityNotification::EmailRemoved {
+                        email: user_email.email.clone(),
+                    },
+                ),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(RemoveEmailPayload::Removed(user_email))
@@ -625,8 +680,9 @@ impl UserEmailMutations {
             return Ok(StartEmailAuthenticationPayload::InvalidEmailAddress);
         }
 
-        if let Err(e) =
-            limiter.check_email_authentication_email(requester.fingerprint(), &input.email)
+        if let Err(e) = limiter
+            .check_email_authentication_email(requester.fingerprint(), &input.email)
+            .await
         {
             tracing::warn!(error = &e as &dyn std::error::Error);
             return Ok(StartEmailAuthenticationPayload::RateLimited);
@@ -742,8 +798,9 @@ impl UserEmailMutations {
             return Ok(ResendEmailAuthenticationCodePayload::Completed);
         }
 
-        if let Err(e) =
-            limiter.check_email_authentication_send_code(requester.fingerprint(), &authentication)
+        if let Err(e) = limiter
+            .check_email_authentication_send_code(requester.fingerprint(), &authentication)
+            .await
         {
             tracing::warn!(error = &e as &dyn std::error::Error);
             return Ok(ResendEmailAuthenticationCodePayload::RateLimited);
@@ -798,7 +855,10 @@ impl UserEmailMutations {
             return Ok(CompleteEmailAuthenticationPayload::InvalidCode);
         }
 
-        if let Err(e) = limiter.check_email_authentication_attempt(&authentication) {
+        if let Err(e) = limiter
+            .check_email_authentication_attempt(&authentication)
+            .await
+        {
             tracing::warn!(error = &e as &dyn std::error::Error);
             return Ok(CompleteEmailAuthenticationPayload::RateLimited);
         }
@@ -833,7 +893,8 @@ impl UserEmailMutations {
             return Ok(CompleteEmailAuthenticationPayload::InUse);
         }
 
-        repo.user_email()
+        let user_email = repo
+            .user_email()
             .add(
                 &mut rng,
                 &clock,
@@ -842,6 +903,39 @@ impl UserEmailMutations {
             )
             .await?;
 
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                ctx.requester().audit_actor(),
+                AuditAction::UserEmailAdd,
+                Some(browser_session.user.id),
+                Some(user_email.id),
+                json!({ "email": user_email.email }),
+            )
+            .await?;
+
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                DispatchWebhookEventJob::user_email_verified(&user_email),
+            )
+            .await?;
+
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                SendSecurityNotificationJob::new(
+                    &browser_session.user,
+                    SecurityNotification::EmailAdded {
+                        email: user_email.email,
+                    },
+                ),
+            )
+            .await?;
+
         repo.save().await?;
 
         Ok(CompleteEmailAuthenticationPayload::Completed)
@@ -0,0 +1,469 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use anyhow::Context as _;
+use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
+use mas_data_model::AuditAction;
+use mas_storage::{
+    RepositoryAccess,
+    user::{UserPasskeyRepository, UserRepository},
+};
+use serde_json::json;
+
+use super::verify_password_if_needed;
+use crate::{
+    graphql::{
+        model::{NodeType, UserPasskey},
+        state::ContextExt,
+    },
+    webauthn::Webauthn,
+};
+
+/// The maximum length of a passkey name
+const MAX_NAME_LENGTH: usize = 256;
+
+#[derive(Default)]
+pub struct UserPasskeyMutations {
+    _private: (),
+}
+
+/// The payload of the `startRegisterPasskey` mutation
+#[derive(Description)]
+struct StartRegisterPasskeyPayload {
+    id: ulid::Ulid,
+    options: String,
+}
+
+#[Object(use_type_description)]
+impl StartRegisterPasskeyPayload {
+    /// The ID of the registration challenge, to pass to
+    /// `completeRegisterPasskey`
+    async fn id(&self) -> ID {
+        ID(self.id.to_string())
+    }
+
+    /// The options to pass to `navigator.credentials.create()`, as a JSON
+    /// string with binary values encoded as base64url
+    async fn options(&self) -> &str {
+        &self.options
+    }
+}
+
+/// The input for the `completeRegisterPasskey` mutation
+#[derive(InputObject)]
+struct CompleteRegisterPasskeyInput {
+    /// The ID of the registration challenge
+    id: ID,
+
+    /// A human-readable name for the passkey
+    name: String,
+
+    /// The response of `navigator.credentials.create()`, as a JSON string with
+    /// binary values encoded as base64url
+    response: String,
+}
+
+/// The status of the `completeRegisterPasskey` mutation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum CompleteRegisterPasskeyStatus {
+    /// The passkey was added
+    Added,
+
+    /// The registration challenge is invalid or has expired
+    InvalidChallenge,
+
+    /// The response from the authenticator is invalid
+    InvalidResponse,
+
+    /// The name is invalid
+    InvalidName,
+
+    /// The passkey is already registered
+    Exists,
+}
+
+/// The payload of the `completeRegisterPasskey` mutation
+#[derive(Description)]
+enum CompleteRegisterPasskeyPayload {
+    Added(mas_data_model::UserPasskey),
+    InvalidChallenge,
+    InvalidResponse,
+    InvalidName,
+    Exists,
+}
+
+#[Object(use_type_description)]
+impl CompleteRegisterPasskeyPayload {
+    /// Status of the operation
+    async fn status(&self) -> CompleteRegisterPasskeyStatus {
+        match self {
+            Self::Added(_) => CompleteRegisterPasskeyStatus::Added,
+            Self::InvalidChallenge => CompleteRegisterPasskeyStatus::InvalidChallenge,
+            Self::InvalidResponse => CompleteRegisterPasskeyStatus::InvalidResponse,
+            Self::InvalidName => CompleteRegisterPasskeyStatus::InvalidName,
+            Self::Exists => CompleteRegisterPasskeyStatus::Exists,
+        }
+    }
+
+    /// The passkey that was added
+    async fn passkey(&self) -> Option<UserPasskey> {
+        match self {
+            Self::Added(passkey) => Some(UserPasskey(passkey.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// The input for the `renamePasskey` mutation
+#[derive(InputObject)]
+struct RenamePasskeyInput {
+    /// The ID of the passkey to rename
+    id: ID,
+
+    /// The new name of the passkey
+    name: String,
+}
+
+/// The status of the `renamePasskey` mutation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum RenamePasskeyStatus {
+    /// The passkey was renamed
+    Renamed,
+
+    /// The passkey was not found
+    NotFound,
+
+    /// The name is invalid
+    InvalidName,
+}
+
+/// The payload of the `renamePasskey` mutation
+#[derive(Description)]
+enum RenamePasskeyPayload {
+    Renamed(mas_data_model::UserPasskey),
+    NotFound,
+    InvalidName,
+}
+
+#[Object(use_type_description)]
+impl RenamePasskeyPayload {
+    /// Status of the operation
+    async fn status(&self) -> RenamePasskeyStatus {
+        match self {
+            Self::Renamed(_) => RenamePasskeyStatus::Renamed,
+            Self::NotFound => RenamePasskeyStatus::NotFound,
+            Self::InvalidName => RenamePasskeyStatus::InvalidName,
+        }
+    }
+
+    /// The passkey that was renamed
+    async fn passkey(&self) -> Option<UserPasskey> {
+        match self {
+            Self::Renamed(passkey) => Some(UserPasskey(passkey.clone())),
+            Self::NotFound | Self::InvalidName => None,
+        }
+    }
+}
+
+/// The input for the `removePasskey` mutation
+#[derive(InputObject)]
+struct RemovePasskeyInput {
+    /// The ID of the passkey to remove
+    id: ID,
+
+    /// The user's current password. This is required if the user is not an
+    /// admin and it has a password on its account.
+    password: Option<String>,
+}
+
+/// The status of the `removePasskey` mutation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum RemovePasskeyStatus {
+    /// The passkey was removed
+    Removed,
+
+    /// The passkey was not found
+    NotFound,
+
+    /// The password provided is incorrect
+    IncorrectPassword,
+}
+
+/// The payload of the `removePasskey` mutation
+#[derive(Description)]
+enum RemovePasskeyPayload {
+    Removed(mas_data_model::UserPasskey),
+    NotFound,
+    IncorrectPassword,
+}
+
+#[Object(use_type_description)]
+impl RemovePasskeyPayload {
+    /// Status of the operation
+    async fn status(&self) -> RemovePasskeyStatus {
+        match self {
+            Self::Removed(_) => RemovePasskeyStatus::Removed,
+            Self::NotFound => RemovePasskeyStatus::NotFound,
+            Self::IncorrectPassword => RemovePasskeyStatus::IncorrectPassword,
+        }
+    }
+
+    /// The passkey that was removed
+    async fn passkey(&self) -> Option<UserPasskey> {
+        match self {
+            Self::Removed(passkey) => Some(UserPasskey(passkey.clone())),
+            Self::NotFound | Self::IncorrectPassword => None,
+        }
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.trim().is_empty() && name.len() <= MAX_NAME_LENGTH
+}
+
+#[Object]
+impl UserPasskeyMutations {
+    /// Start registering a new passkey for the current user
+    async fn start_register_passkey(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<StartRegisterPasskeyPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let mut rng = state.rng();
+        let clock = state.clock();
+        let requester = ctx.requester();
+
+        // Only allow calling this if the requester is a browser session
+        let Some(browser_session) = requester.browser_session() else {
+            return Err(async_graphql::Error::new("Unauthorized"));
+        };
+
+        if !state.site_config().passkeys_enabled {
+            return Err(async_graphql::Error::new(
+                "Passkeys are not enabled on this server",
+            ));
+        }
+
+        let webauthn = Webauthn::new(state.url_builder());
+        let mut repo = state.repository().await?;
+
+        let existing = repo.user_passkey().all(&browser_session.user).await?;
+
+        let challenge = Webauthn::generate_challenge(&mut rng);
+        let challenge = repo
+            .user_passkey()
+            .add_challenge_for_session(&mut rng, &clock, challenge, browser_session)
+            .await?;
+
+        let options = webauthn.creation_options(&challenge, &browser_session.user, &existing);
+
+        repo.save().await?;
+
+        Ok(StartRegisterPasskeyPayload {
+            id: challenge.id,
+            options: options.to_string(),
+        })
+    }
+
+    /// Complete the registration of a passkey
+    async fn complete_register_passkey(
+        &self,
+        ctx: &Context<'_>,
+        input: CompleteRegisterPasskeyInput,
+    ) -> Result<CompleteRegisterPasskeyPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let mut rng = state.rng();
+        let clock = state.clock();
+        let requester = ctx.requester();
+
+        // Only allow calling this if the requester is a browser session
+        let Some(browser_session) = requester.browser_session() else {
+            return Err(async_graphql::Error::new("Unauthorized"));
+        };
+
+        if !state.site_config().passkeys_enabled {
+            return Err(async_graphql::Error::new(
+                "Passkeys are not enabled on this server",
+            ));
+        }
+
+        let Ok(id) = input.id.parse() else {
+            return Ok(CompleteRegisterPasskeyPayload::InvalidChallenge);
+        };
+
+        if !valid_name(&input.name) {
+            return Ok(CompleteRegisterPasskeyPayload::InvalidName);
+        }
+
+        let webauthn = Webauthn::new(state.url_builder());
+        let mut repo = state.repository().await?;
+
+        let Some(challenge) = repo.user_passkey().lookup_challenge(id).await? else {
+            return Ok(CompleteRegisterPasskeyPayload::InvalidChallenge);
+        };
+
+        // The challenge must have been issued for this browser session
+        if challenge.user_session_id != Some(browser_session.id) || !challenge.is_valid(clock.now())
+        {
+            return Ok(CompleteRegisterPasskeyPayload::InvalidChallenge);
+        }
+
+        let challenge = repo
+            .user_passkey()
+            .complete_challenge(&clock, challenge)
+            .await?;
+
+        let credential = match Webauthn::parse_registration(&input.response)
+            .and_then(|response| webauthn.verify_registration(&challenge, &response))
+        {
+            Ok(credential) => credential,
+            Err(e) => {
+                tracing::warn!(
+                    error = &e as &dyn std::error::Error,
+                    "Invalid passkey registration response"
+                );
+                // Save the completion of the challenge, so that it can't be reused
+                repo.save().await?;
+                return Ok(CompleteRegisterPasskeyPayload::InvalidResponse);
+            }
+        };
+
+        if repo
+            .user_passkey()
+            .find_by_credential_id(&credential.credential_id)
+            .await?
+            .is_some()
+        {
+            repo.save().await?;
+            return Ok(CompleteRegisterPasskeyPayload::Exists);
+        }
+
+        let passkey = repo
+            .user_passkey()
+            .add(
+                &mut rng,
+                &clock,
+                &browser_session.user,
+                input.name.trim().to_owned(),
+                credential.credential_id,
+                credential.transports,
+                credential.public_key,
+                credential.aaguid,
+                credential.sign_count,
+            )
+            .await?;
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserPasskeyAdd,
+                Some(browser_session.user.id),
+                Some(passkey.id),
+                json!({ "name": passkey.name }),
+            )
+            .await?;
+
+        repo.save().await?;
+
+        Ok(CompleteRegisterPasskeyPayload::Added(passkey))
+    }
+
+    /// Rename a passkey
+    async fn rename_passkey(
+        &self,
+        ctx: &Context<'_>,
+        input: RenamePasskeyInput,
+    ) -> Result<RenamePasskeyPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let id = NodeType::UserPasskey.extract_ulid(&input.id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+
+        let Some(passkey) = repo.user_passkey().lookup(id).await? else {
+            return Ok(RenamePasskeyPayload::NotFound);
+        };
+
+        if !requester.is_owner_or_admin(&passkey) {
+            return Ok(RenamePasskeyPayload::NotFound);
+        }
+
+        if !valid_name(&input.name) {
+            return Ok(RenamePasskeyPayload::InvalidName);
+        }
+
+        let passkey = repo
+            .user_passkey()
+            .rename(passkey, input.name.trim().to_owned())
+            .await?;
+
+        repo.save().await?;
+
+        Ok(RenamePasskeyPayload::Renamed(passkey))
+    }
+
+    /// Remove a passkey
+    async fn remove_passkey(
+        &self,
+        ctx: &Context<'_>,
+        input: RemovePasskeyInput,
+    ) -> Result<RemovePasskeyPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let mut rng = state.rng();
+        let clock = state.clock();
+        let id = NodeType::UserPasskey.extract_ulid(&input.id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+
+        let Some(passkey) = repo.user_passkey().lookup(id).await? else {
+            return Ok(RemovePasskeyPayload::NotFound);
+        };
+
+        if !requester.is_owner_or_admin(&passkey) {
+            return Ok(RemovePasskeyPayload::NotFound);
+        }
+
+        let user = repo
+            .user()
+            .lookup(passkey.user_id)
+            .await?
+            .context("Failed to load user")?;
+
+        // Validate the password input if needed
+        if !verify_password_if_needed(
+            requester,
+            state.site_config(),
+            &state.password_manager(),
+            input.password,
+            &user,
+            &mut repo,
+        )
+        .await?
+        {
+            return Ok(RemovePasskeyPayload::IncorrectPassword);
+        }
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserPasskeyRemove,
+                Some(user.id),
+                Some(passkey.id),
+                json!({ "name": passkey.name }),
+            )
+            .await?;
+
+        repo.user_passkey().remove(passkey.clone()).await?;
+
+        repo.save().await?;
+
+        Ok(RemovePasskeyPayload::Removed(passkey))
+    }
+}
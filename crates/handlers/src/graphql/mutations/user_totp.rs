@@ -0,0 +1,370 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use anyhow::Context as _;
+use async_graphql::{Context, Description, Enum, ID, InputObject, Object};
+use mas_data_model::AuditAction;
+use mas_storage::{
+    RepositoryAccess,
+    user::{UserRepository, UserTotpRepository},
+};
+use serde_json::json;
+
+use super::verify_password_if_needed;
+use crate::{
+    graphql::{model::NodeType, state::ContextExt},
+    totp::{self, Totp},
+};
+
+#[derive(Default)]
+pub struct UserTotpMutations {
+    _private: (),
+}
+
+/// The status of the `startEnrollTotp` mutation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum StartEnrollTotpStatus {
+    /// The enrolment was started, and must be completed with
+    /// `completeEnrollTotp`
+    Started,
+
+    /// The user already has a TOTP authenticator
+    AlreadyEnrolled,
+}
+
+/// The payload of the `startEnrollTotp` mutation
+#[derive(Description)]
+enum StartEnrollTotpPayload {
+    Started {
+        secret: String,
+        uri: String,
+        qr_code: String,
+    },
+    AlreadyEnrolled,
+}
+
+#[Object(use_type_description)]
+impl StartEnrollTotpPayload {
+    /// Status of the operation
+    async fn status(&self) -> StartEnrollTotpStatus {
+        match self {
+            Self::Started { .. } => StartEnrollTotpStatus::Started,
+            Self::AlreadyEnrolled => StartEnrollTotpStatus::AlreadyEnrolled,
+        }
+    }
+
+    /// The shared secret, encoded in base32, for users who can't scan the QR
+    /// code
+    async fn secret(&self) -> Option<&str> {
+        match self {
+            Self::Started { secret, .. } => Some(secret),
+            Self::AlreadyEnrolled => None,
+        }
+    }
+
+    /// The `otpauth://` URI to give to the authenticator app
+    async fn uri(&self) -> Option<&str> {
+        match self {
+            Self::Started { uri, .. } => Some(uri),
+            Self::AlreadyEnrolled => None,
+        }
+    }
+
+    /// The `otpauth://` URI rendered as a QR code, as an SVG `data:` URI
+    async fn qr_code(&self) -> Option<&str> {
+        match self {
+            Self::Started { qr_code, .. } => Some(qr_code),
+            Self::AlreadyEnrolled => None,
+        }
+    }
+}
+
+/// The input for the `completeEnrollTotp` mutation
+#[derive(InputObject)]
+struct CompleteEnrollTotpInput {
+    /// A code generated by the authenticator app
+    code: String,
+}
+
+/// The status of the `completeEnrollTotp` mutation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum CompleteEnrollTotpStatus {
+    /// The TOTP authenticator was enrolled
+    Enrolled,
+
+    /// The code is invalid
+    InvalidCode,
+
+    /// There is no enrolment in progress
+    NotStarted,
+}
+
+/// The payload of the `completeEnrollTotp` mutation
+#[derive(Description)]
+enum CompleteEnrollTotpPayload {
+    Enrolled(Vec<String>),
+    InvalidCode,
+    NotStarted,
+}
+
+#[Object(use_type_description)]
+impl CompleteEnrollTotpPayload {
+    /// Status of the operation
+    async fn status(&self) -> CompleteEnrollTotpStatus {
+        match self {
+            Self::Enrolled(_) => CompleteEnrollTotpStatus::Enrolled,
+            Self::InvalidCode => CompleteEnrollTotpStatus::InvalidCode,
+            Self::NotStarted => CompleteEnrollTotpStatus::NotStarted,
+        }
+    }
+
+    /// The recovery codes, which can be used once each in place of a TOTP
+    /// code. They are only shown once.
+    async fn recovery_codes(&self) -> Option<&[String]> {
+        match self {
+            Self::Enrolled(codes) => Some(codes),
+            Self::InvalidCode | Self::NotStarted => None,
+        }
+    }
+}
+
+/// The input for the `removeTotp` mutation
+#[derive(InputObject)]
+struct RemoveTotpInput {
+    /// The ID of the user to remove the TOTP authenticator from
+    user_id: ID,
+
+    /// The user's current password. This is required if the user is not an
+    /// admin and it has a password on its account.
+    password: Option<String>,
+}
+
+/// The status of the `removeTotp` mutation
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+enum RemoveTotpStatus {
+    /// The TOTP authenticator and the recovery codes were removed
+    Removed,
+
+    /// The user does not have a TOTP authenticator
+    NotFound,
+
+    /// The password provided is incorrect
+    IncorrectPassword,
+}
+
+/// The payload of the `removeTotp` mutation
+#[derive(Description)]
+enum RemoveTotpPayload {
+    Removed,
+    NotFound,
+    IncorrectPassword,
+}
+
+#[Object(use_type_description)]
+impl RemoveTotpPayload {
+    /// Status of the operation
+    async fn status(&self) -> RemoveTotpStatus {
+        match self {
+            Self::Removed => RemoveTotpStatus::Removed,
+            Self::NotFound => RemoveTotpStatus::NotFound,
+            Self::IncorrectPassword => RemoveTotpStatus::IncorrectPassword,
+        }
+    }
+}
+
+#[Object]
+impl UserTotpMutations {
+    /// Start enrolling a TOTP authenticator app for the current user
+    async fn start_enroll_totp(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<StartEnrollTotpPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let mut rng = state.rng();
+        let clock = state.clock();
+        let requester = ctx.requester();
+
+        // Only allow calling this if the requester is a browser session
+        let Some(browser_session) = requester.browser_session() else {
+            return Err(async_graphql::Error::new("Unauthorized"));
+        };
+
+        if !state.site_config().totp_enabled {
+            return Err(async_graphql::Error::new(
+                "TOTP is not enabled on this server",
+            ));
+        }
+
+        let mut repo = state.repository().await?;
+
+        // Replace any enrolment which was started but never completed
+        let existing = repo
+            .user_totp()
+            .find_for_user(&browser_session.user)
+            .await?;
+        if let Some(existing) = existing {
+            if existing.is_confirmed() {
+                return Ok(StartEnrollTotpPayload::AlreadyEnrolled);
+            }
+
+            repo.user_totp().remove(existing).await?;
+        }
+
+        let totp = Totp::generate(&mut rng);
+        let encrypted_secret = state
+            .encrypter()
+            .encrypt_to_string(totp.secret())
+            .context("Failed to encrypt the TOTP secret")?;
+
+        repo.user_totp()
+            .add(&mut rng, &clock, &browser_session.user, encrypted_secret)
+            .await?;
+
+        let issuer = &state.site_config().server_name;
+        let account = &browser_session.user.username;
+        let qr_code = totp
+            .qr_code(issuer, account)
+            .context("Failed to render the QR code")?;
+
+        repo.save().await?;
+
+        Ok(StartEnrollTotpPayload::Started {
+            secret: totp.secret_base32(),
+            uri: totp.uri(issuer, account).to_string(),
+            qr_code,
+        })
+    }
+
+    /// Complete the enrolment of a TOTP authenticator app, by checking a code
+    /// it generated
+    async fn complete_enroll_totp(
+        &self,
+        ctx: &Context<'_>,
+        input: CompleteEnrollTotpInput,
+    ) -> Result<CompleteEnrollTotpPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let mut rng = state.rng();
+        let clock = state.clock();
+        let requester = ctx.requester();
+
+        // Only allow calling this if the requester is a browser session
+        let Some(browser_session) = requester.browser_session() else {
+            return Err(async_graphql::Error::new("Unauthorized"));
+        };
+
+        if !state.site_config().totp_enabled {
+            return Err(async_graphql::Error::new(
+                "TOTP is not enabled on this server",
+            ));
+        }
+
+        let mut repo = state.repository().await?;
+
+        let Some(user_totp) = repo
+            .user_totp()
+            .find_for_user(&browser_session.user)
+            .await?
+            .filter(|user_totp| !user_totp.is_confirmed())
+        else {
+            return Ok(CompleteEnrollTotpPayload::NotStarted);
+        };
+
+        let secret = state
+            .encrypter()
+            .decrypt_string(&user_totp.encrypted_secret)
+            .context("Failed to decrypt the TOTP secret")?;
+        let Some(step) = Totp::from_secret(secret).verify(&input.code, clock.now(), None) else {
+            return Ok(CompleteEnrollTotpPayload::InvalidCode);
+        };
+
+        let user_totp = repo.user_totp().confirm(&clock, user_totp, step).await?;
+
+        let recovery_codes = totp::generate_recovery_codes(&mut rng);
+        let encrypted_codes = recovery_codes
+            .iter()
+            .map(|code| state.encrypter().encrypt_to_string(code.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to encrypt the recovery codes")?;
+        repo.user_totp()
+            .replace_recovery_codes(&mut rng, &clock, &browser_session.user, encrypted_codes)
+            .await?;
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserTotpAdd,
+                Some(browser_session.user.id),
+                Some(user_totp.id),
+                json!({}),
+            )
+            .await?;
+
+        repo.save().await?;
+
+        Ok(CompleteEnrollTotpPayload::Enrolled(recovery_codes))
+    }
+
+    /// Remove the TOTP authenticator app and the recovery codes of a user
+    async fn remove_totp(
+        &self,
+        ctx: &Context<'_>,
+        input: RemoveTotpInput,
+    ) -> Result<RemoveTotpPayload, async_graphql::Error> {
+        let state = ctx.state();
+        let mut rng = state.rng();
+        let clock = state.clock();
+        let id = NodeType::User.extract_ulid(&input.user_id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+
+        let Some(user) = repo.user().lookup(id).await? else {
+            return Ok(RemoveTotpPayload::NotFound);
+        };
+
+        if !requester.is_owner_or_admin(&user) {
+            return Ok(RemoveTotpPayload::NotFound);
+        }
+
+        let Some(user_totp) = repo.user_totp().find_for_user(&user).await? else {
+            return Ok(RemoveTotpPayload::NotFound);
+        };
+
+        // Validate the password input if needed
+        if !verify_password_if_needed(
+            requester,
+            state.site_config(),
+            &state.password_manager(),
+            input.password,
+            &user,
+            &mut repo,
+        )
+        .await?
+        {
+            return Ok(RemoveTotpPayload::IncorrectPassword);
+        }
+
+        repo.audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                requester.audit_actor(),
+                AuditAction::UserTotpRemove,
+                Some(user.id),
+                Some(user_totp.id),
+                json!({}),
+            )
+            .await?;
+
+        repo.user_totp().remove(user_totp).await?;
+        repo.user_totp().remove_recovery_codes(&user).await?;
+
+        repo.save().await?;
+
+        Ok(RemoveTotpPayload::Removed)
+    }
+}
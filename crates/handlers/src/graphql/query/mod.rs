@@ -9,7 +9,7 @@ use async_graphql::{Context, ID, MergedObject, Object};
 use crate::graphql::{
     model::{
         Anonymous, BrowserSession, CompatSession, Node, NodeType, OAuth2Client, OAuth2Session,
-        SiteConfig, User, UserEmail, UserRecoveryTicket,
+        SiteConfig, User, UserEmail, UserPasskey, UserRecoveryTicket,
     },
     state::ContextExt,
 };
@@ -183,6 +183,31 @@ impl BaseQuery {
         Ok(Some(UserEmail(user_email)))
     }
 
+    /// Fetch a user passkey by its ID.
+    async fn user_passkey(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> Result<Option<UserPasskey>, async_graphql::Error> {
+        let state = ctx.state();
+        let id = NodeType::UserPasskey.extract_ulid(&id)?;
+        let requester = ctx.requester();
+
+        let mut repo = state.repository().await?;
+        let user_passkey = repo.user_passkey().lookup(id).await?;
+        repo.cancel().await?;
+
+        let Some(user_passkey) = user_passkey else {
+            return Ok(None);
+        };
+
+        if !requester.is_owner_or_admin(&user_passkey) {
+            return Ok(None);
+        }
+
+        Ok(Some(UserPasskey(user_passkey)))
+    }
+
     /// Fetch a user recovery ticket.
     async fn user_recovery_ticket(
         &self,
@@ -269,6 +294,11 @@ impl BaseQuery {
                 .await?
                 .map(|e| Node::UserEmailAuthentication(Box::new(e))),
 
+            NodeType::UserPasskey => self
+                .user_passkey(ctx, id)
+                .await?
+                .map(|p| Node::UserPasskey(Box::new(p))),
+
             NodeType::CompatSession => self
                 .compat_session(ctx, id)
                 .await?
@@ -6,6 +6,7 @@
 
 use async_graphql::{Response, ServerError};
 use mas_data_model::{BoxClock, BoxRng, SiteConfig};
+use mas_keystore::Encrypter;
 use mas_matrix::HomeserverConnection;
 use mas_policy::Policy;
 use mas_router::UrlBuilder;
@@ -26,6 +27,7 @@ pub trait State {
     fn site_config(&self) -> &SiteConfig;
     fn url_builder(&self) -> &UrlBuilder;
     fn limiter(&self) -> &Limiter;
+    fn encrypter(&self) -> &Encrypter;
 }
 
 pub type BoxState = Box<dyn State + Send + Sync + 'static>;
@@ -7,11 +7,12 @@
 use axum::http::Request;
 use hyper::StatusCode;
 use mas_axum_utils::SessionInfoExt;
-use mas_data_model::{AccessToken, Client, TokenType, User};
+use mas_data_model::{AccessToken, AuditAction, Client, TokenType, User};
 use mas_matrix::{HomeserverConnection, ProvisionRequest};
 use mas_router::SimpleRoute;
 use mas_storage::{
-    RepositoryAccess,
+    Pagination, RepositoryAccess,
+    audit_event::AuditEventFilter,
     oauth2::{OAuth2AccessTokenRepository, OAuth2ClientRepository},
 };
 use oauth2_types::{
@@ -50,6 +51,12 @@ async fn create_test_client(state: &TestState) -> Client {
             None,
             None,
             None,
+            vec![],
+            None,
+            false,
+            false,
+            false,
+            None,
         )
         .await
         .unwrap();
@@ -100,7 +107,14 @@ async fn start_oauth_session(
 
     let access_token = repo
         .oauth2_access_token()
-        .add(&mut rng, &state.clock, &session, access_token_str, None)
+        .add(
+            &mut rng,
+            &state.clock,
+            &session,
+            access_token_str,
+            None,
+            None,
+        )
         .await
         .unwrap();
 
@@ -568,8 +582,30 @@ async fn test_oauth2_client_credentials(pool: PgPool) {
         .oauth2_access_token()
         .find_by_token(token)
         .await
+        .unwrap()
+        .unwrap();
+
+    // The creation of the session should be audited
+    let events = repo
+        .audit_event()
+        .list(
+            AuditEventFilter::new().with_action(AuditAction::OAuth2SessionAdd),
+            Pagination::first(10),
+        )
+        .await
         .unwrap();
-    assert!(token.is_some());
+    assert_eq!(events.edges.len(), 1);
+    let event = &events.edges[0].node;
+    assert_eq!(event.target_user_id, Some(user_id.parse().unwrap()));
+    assert_eq!(event.target_id, Some(token.session_id));
+    assert!(event.actor.session_id.is_some());
+    assert_eq!(
+        event.details,
+        serde_json::json!({
+            "scope": "urn:matrix:org.matrix.msc2967.client:api:* urn:matrix:org.matrix.msc2967.client:device:AABBCCDDEE urn:synapse:admin:*",
+            "expires_at": null,
+        })
+    );
 }
 
 /// Test the addUser mutation
@@ -67,6 +67,8 @@ mod rate_limit;
 mod session;
 #[cfg(test)]
 mod test_utils;
+mod totp;
+mod webauthn;
 
 static METER: LazyLock<Meter> = LazyLock::new(|| {
     let scope = opentelemetry::InstrumentationScope::builder(env!("CARGO_PKG_NAME"))
@@ -93,7 +95,7 @@ macro_rules! impl_from_error_for_route {
     };
 }
 
-pub use mas_axum_utils::{ErrorWrapper, cookies::CookieManager};
+pub use mas_axum_utils::{ErrorWrapper, cookies::CookieManager, dpop::DPoPReplayCache};
 use mas_data_model::{BoxClock, BoxRng};
 
 pub use self::{
@@ -119,12 +121,14 @@ pub fn graphql_router<S>(playground: bool, undocumented_oauth2_access: bool) ->
 where
     S: Clone + Send + Sync + 'static,
     graphql::Schema: FromRef<S>,
+    UrlBuilder: FromRef<S>,
     BoundActivityTracker: FromRequestParts<S>,
     BoxRepository: FromRequestParts<S>,
     BoxClock: FromRequestParts<S>,
     Encrypter: FromRef<S>,
     CookieJar: FromRequestParts<S>,
     Limiter: FromRef<S>,
+    DPoPReplayCache: FromRef<S>,
     RequesterFingerprint: FromRequestParts<S>,
 {
     let mut router = Router::new()
@@ -147,6 +151,7 @@ where
                     ACCEPT_LANGUAGE,
                     CONTENT_LANGUAGE,
                     CONTENT_TYPE,
+                    HeaderName::from_static("dpop"),
                 ]),
         );
 
@@ -197,6 +202,7 @@ pub fn api_router<S>() -> Router<S>
 where
     S: Clone + Send + Sync + 'static,
     Keystore: FromRef<S>,
+    DPoPReplayCache: FromRef<S>,
     UrlBuilder: FromRef<S>,
     BoxRepository: FromRequestParts<S>,
     ActivityTracker: FromRequestParts<S>,
@@ -236,10 +242,20 @@ where
             mas_router::OAuth2RegistrationEndpoint::route(),
             post(self::oauth2::registration::post),
         )
+        .route(
+            mas_router::OAuth2ClientConfigurationEndpoint::route(),
+            get(self::oauth2::client_configuration::get)
+                .put(self::oauth2::client_configuration::put)
+                .delete(self::oauth2::client_configuration::delete),
+        )
         .route(
             mas_router::OAuth2DeviceAuthorizationEndpoint::route(),
             post(self::oauth2::device::authorize::post),
         )
+        .route(
+            mas_router::OAuth2PushedAuthorizationRequestEndpoint::route(),
+            post(self::oauth2::par::post),
+        )
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
@@ -252,6 +268,8 @@ where
                     CONTENT_TYPE,
                     // Swagger will send this header, so we have to allow it to avoid CORS errors
                     HeaderName::from_static("x-requested-with"),
+                    // Clients send DPoP proofs in this header
+                    HeaderName::from_static("dpop"),
                 ])
                 .max_age(Duration::from_secs(60 * 60)),
         )
@@ -389,6 +407,23 @@ where
             mas_router::Login::route(),
             get(self::views::login::get).post(self::views::login::post),
         )
+        .route(
+            mas_router::PasskeyLogin::route(),
+            post(self::views::login::post_passkey),
+        )
+        .route(
+            mas_router::PasskeyLoginChallenge::route(),
+            post(self::views::login::post_passkey_challenge),
+        )
+        .route(
+            mas_router::LoginMfa::route(),
+            get(self::views::login_mfa::get).post(self::views::login_mfa::post),
+        )
+        .route(
+            mas_router::LoginPasswordExpired::route(),
+            get(self::views::login_password_expired::get)
+                .post(self::views::login_password_expired::post),
+        )
         .route(mas_router::Logout::route(), post(self::views::logout::post))
         .route(
             mas_router::Register::route(),
@@ -429,6 +464,14 @@ where
             mas_router::OAuth2AuthorizationEndpoint::route(),
             get(self::oauth2::authorization::get),
         )
+        .route(
+            mas_router::OAuth2EndSessionEndpoint::route(),
+            get(self::oauth2::end_session::get).post(self::oauth2::end_session::post),
+        )
+        .route(
+            mas_router::OAuth2EndSessionConfirm::route(),
+            post(self::oauth2::end_session::confirm),
+        )
         .route(
             mas_router::Consent::route(),
             get(self::oauth2::authorization::consent::get)
@@ -455,6 +498,14 @@ where
             mas_router::UpstreamOAuth2BackchannelLogout::route(),
             post(self::upstream_oauth2::backchannel_logout::post),
         )
+        .route(
+            mas_router::UpstreamSamlMetadata::route(),
+            get(self::upstream_oauth2::saml::metadata::get),
+        )
+        .route(
+            mas_router::UpstreamSamlAssertionConsumerService::route(),
+            post(self::upstream_oauth2::saml::acs::post),
+        )
         .route(
             mas_router::DeviceCodeLink::route(),
             get(self::oauth2::device::link::get),
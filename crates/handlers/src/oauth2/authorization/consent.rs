@@ -31,7 +31,7 @@ use ulid::Ulid;
 use super::callback::CallbackDestination;
 use crate::{
     BoundActivityTracker, PreferredLanguage, impl_from_error_for_route,
-    oauth2::generate_id_token,
+    oauth2::{generate_id_token, is_mfa_authenticated},
     session::{SessionOrFallback, load_session_or_fallback},
 };
 
@@ -136,12 +136,15 @@ pub(crate) async fn get(
 
     let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
 
+    let mfa_authenticated = is_mfa_authenticated(&mut repo, &session).await?;
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             user: Some(&session.user),
+            mfa_authenticated,
             client: &client,
             scope: &grant.scope,
             grant_type: mas_policy::GrantType::AuthorizationCode,
+            subject: None,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -235,12 +238,15 @@ pub(crate) async fn post(
         return Err(RouteError::GrantNotPending(grant.id));
     }
 
+    let mfa_authenticated = is_mfa_authenticated(&mut repo, &browser_session).await?;
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             user: Some(&browser_session.user),
+            mfa_authenticated,
             client: &client,
             scope: &grant.scope,
             grant_type: mas_policy::GrantType::AuthorizationCode,
+            subject: None,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
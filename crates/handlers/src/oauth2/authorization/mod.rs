@@ -4,17 +4,22 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
+use std::collections::{BTreeMap, btree_map::Entry};
+
 use axum::{
     extract::{Form, State},
     response::{IntoResponse, Response},
 };
 use hyper::StatusCode;
 use mas_axum_utils::{GenericError, InternalError, SessionInfoExt, cookies::CookieJar};
-use mas_data_model::{AuthorizationCode, BoxClock, BoxRng, Pkce};
+use mas_data_model::{AuthorizationCode, BoxClock, BoxRng, Clock, Pkce};
 use mas_router::{PostAuthAction, UrlBuilder};
 use mas_storage::{
     BoxRepository,
-    oauth2::{OAuth2AuthorizationGrantRepository, OAuth2ClientRepository},
+    oauth2::{
+        OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
+        OAuth2PushedAuthorizationRequestRepository,
+    },
 };
 use mas_templates::Templates;
 use oauth2_types::{
@@ -32,6 +37,7 @@ use crate::{BoundActivityTracker, PreferredLanguage, impl_from_error_for_route};
 
 mod callback;
 pub(crate) mod consent;
+pub(crate) mod request_object;
 
 #[derive(Debug, Error)]
 pub enum RouteError {
@@ -41,6 +47,15 @@ pub enum RouteError {
     #[error("could not find client")]
     ClientNotFound,
 
+    #[error("invalid parameters")]
+    InvalidParameters(#[source] serde_urlencoded::de::Error),
+
+    #[error("invalid or expired request_uri")]
+    InvalidRequestUri,
+
+    #[error("invalid request object")]
+    InvalidRequestObject(#[from] self::request_object::RequestObjectError),
+
     #[error("invalid response mode")]
     InvalidResponseMode,
 
@@ -56,6 +71,9 @@ impl IntoResponse for RouteError {
         match self {
             Self::Internal(e) => InternalError::new(e).into_response(),
             e @ (Self::ClientNotFound
+            | Self::InvalidParameters(_)
+            | Self::InvalidRequestUri
+            | Self::InvalidRequestObject(_)
             | Self::InvalidResponseMode
             | Self::IntoCallbackDestination(_)
             | Self::UnknownRedirectUri(_)) => {
@@ -74,12 +92,130 @@ impl_from_error_for_route!(mas_policy::EvaluationError);
 #[derive(Deserialize)]
 pub(crate) struct Params {
     #[serde(flatten)]
-    auth: AuthorizationRequest,
+    pub(crate) auth: AuthorizationRequest,
 
     #[serde(flatten)]
     pkce: Option<pkce::AuthorizationRequest>,
 }
 
+impl Params {
+    /// Parse the parameters of an authorization request from the raw
+    /// key-value pairs, the same way they would be parsed from a query string
+    pub(crate) fn from_parameters(
+        parameters: &BTreeMap<String, String>,
+    ) -> Result<Self, serde_urlencoded::de::Error> {
+        let encoded = serde_urlencoded::to_string(parameters).map_err(serde::de::Error::custom)?;
+        serde_urlencoded::from_str(&encoded)
+    }
+}
+
+/// The parameters of an authorization request, once resolved from a pushed
+/// authorization request and a signed request object
+struct LoadedParams {
+    params: Params,
+
+    /// Whether the parameters came from a pushed authorization request
+    pushed: bool,
+
+    /// Whether the parameters came from a signed request object
+    signed: bool,
+}
+
+/// Load the parameters of an authorization request, either from the request
+/// itself, or from a pushed authorization request if it references one with
+/// the `request_uri` parameter.
+///
+/// If the parameters include a `request` object, it is verified against the
+/// client's keys and merged with the other parameters.
+async fn load_params(
+    repo: &mut BoxRepository,
+    http_client: &reqwest::Client,
+    clock: &dyn Clock,
+    url_builder: &UrlBuilder,
+    form: BTreeMap<String, String>,
+) -> Result<LoadedParams, RouteError> {
+    let (parameters, pushed) = if let Some(request_uri) = form.get("request_uri") {
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .find_by_request_uri(request_uri)
+            .await?
+            .filter(|request| request.is_valid(clock.now()))
+            .ok_or(RouteError::InvalidRequestUri)?;
+
+        let client = repo
+            .oauth2_client()
+            .lookup(request.client_id)
+            .await?
+            .ok_or(RouteError::ClientNotFound)?;
+
+        // The client_id must match the one of the client which pushed the request
+        if form.get("client_id") != Some(&client.client_id) {
+            return Err(RouteError::InvalidRequestUri);
+        }
+
+        // Pushed requests are single-use
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .consume(clock, request)
+            .await?;
+
+        (request.parameters, true)
+    } else {
+        (form, false)
+    };
+
+    let signed = parameters.contains_key("request");
+    let parameters = if signed {
+        let client_id = parameters
+            .get("client_id")
+            .ok_or(RouteError::ClientNotFound)?;
+
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(client_id)
+            .await?
+            .ok_or(RouteError::ClientNotFound)?;
+
+        self::request_object::merge(
+            http_client,
+            clock,
+            url_builder.oidc_issuer().as_str(),
+            &client,
+            parameters,
+        )
+        .await?
+    } else {
+        parameters
+    };
+
+    let params = Params::from_parameters(&parameters).map_err(RouteError::InvalidParameters)?;
+    Ok(LoadedParams {
+        params,
+        pushed,
+        signed,
+    })
+}
+
+/// Collect the raw parameters of an authorization request, keeping the first
+/// value of each parameter.
+///
+/// Parameters must not be included more than once, so this also returns
+/// whether any of them was repeated.
+fn collect_parameters(pairs: Vec<(String, String)>) -> (BTreeMap<String, String>, bool) {
+    let mut parameters = BTreeMap::new();
+    let mut duplicated = false;
+    for (key, value) in pairs {
+        match parameters.entry(key) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            Entry::Occupied(_) => duplicated = true,
+        }
+    }
+
+    (parameters, duplicated)
+}
+
 /// Given a list of response types and an optional user-defined response mode,
 /// figure out what response mode must be used, and emit an error if the
 /// suggested response mode isn't allowed for the given response types.
@@ -106,7 +242,10 @@ fn resolve_response_mode(
 
 #[tracing::instrument(
     name = "handlers.oauth2.authorization.get",
-    fields(client.id = %params.auth.client_id),
+    fields(client.id = form
+        .iter()
+        .find(|(key, _)| key == "client_id")
+        .map(|(_, value)| value.as_str())),
     skip_all,
 )]
 pub(crate) async fn get(
@@ -115,11 +254,19 @@ pub(crate) async fn get(
     PreferredLanguage(locale): PreferredLanguage,
     State(templates): State<Templates>,
     State(url_builder): State<UrlBuilder>,
+    State(http_client): State<reqwest::Client>,
     activity_tracker: BoundActivityTracker,
     mut repo: BoxRepository,
     cookie_jar: CookieJar,
-    Form(params): Form<Params>,
+    Form(form): Form<Vec<(String, String)>>,
 ) -> Result<Response, RouteError> {
+    let (form, duplicated) = collect_parameters(form);
+    let LoadedParams {
+        params,
+        pushed,
+        signed,
+    } = load_params(&mut repo, &http_client, &clock, &url_builder, form).await?;
+
     // First, figure out what client it is
     let client = repo
         .oauth2_client()
@@ -153,16 +300,17 @@ pub(crate) async fn get(
             let maybe_session = session_info.load_active_session(&mut repo).await?;
             let prompt = params.auth.prompt.as_deref().unwrap_or_default();
 
-            // Check if the request/request_uri/registration params are used. If so, reply
-            // with the right error since we don't support them.
-            if params.auth.request.is_some() {
+            // Repeated parameters are ambiguous, so reject the whole request
+            if duplicated {
                 return Ok(callback_destination.go(
                     &templates,
                     &locale,
-                    ClientError::from(ClientErrorCode::RequestNotSupported),
+                    ClientError::from(ClientErrorCode::InvalidRequest),
                 )?);
             }
 
+            // Pushed requests and request objects can't reference another request. Any
+            // other request_uri was already resolved when loading the parameters.
             if params.auth.request_uri.is_some() {
                 return Ok(callback_destination.go(
                     &templates,
@@ -171,6 +319,24 @@ pub(crate) async fn get(
                 )?);
             }
 
+            // Some clients must go through the pushed authorization request endpoint
+            if client.require_pushed_authorization_requests && !pushed {
+                return Ok(callback_destination.go(
+                    &templates,
+                    &locale,
+                    ClientError::from(ClientErrorCode::InvalidRequest),
+                )?);
+            }
+
+            // Some clients must send their parameters in a signed request object
+            if client.require_signed_request_object && !signed {
+                return Ok(callback_destination.go(
+                    &templates,
+                    &locale,
+                    ClientError::from(ClientErrorCode::InvalidRequest),
+                )?);
+            }
+
             // Check if the client asked for a `token` response type, and bail out if it's
             // the case, since we don't support them
             if response_type.has_token() {
@@ -315,3 +481,28 @@ pub(crate) async fn get(
 
     Ok((cookie_jar, response).into_response())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_parameters() {
+        let pair = |key: &str, value: &str| (key.to_owned(), value.to_owned());
+
+        let (parameters, duplicated) =
+            collect_parameters(vec![pair("client_id", "client"), pair("scope", "openid")]);
+        assert!(!duplicated);
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters["scope"], "openid");
+
+        let (parameters, duplicated) = collect_parameters(vec![
+            pair("client_id", "client"),
+            pair("scope", "openid"),
+            pair("scope", "openid email"),
+        ]);
+        assert!(duplicated);
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters["scope"], "openid");
+    }
+}
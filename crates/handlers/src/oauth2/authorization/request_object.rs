@@ -0,0 +1,320 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for [JWT-Secured Authorization Requests], where the parameters of
+//! an authorization request are passed in a `request` JWT signed by the
+//! client.
+//!
+//! [JWT-Secured Authorization Requests]: https://www.rfc-editor.org/rfc/rfc9101.html
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::BoxError;
+use mas_axum_utils::client_authorization::fetch_jwks;
+use mas_data_model::{Client, Clock};
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, Claim, ClaimError, Equality, TimeOptions},
+    jwt::{Jwt, JwtDecodeError, NoKeyWorked},
+};
+use serde_json::Value;
+use thiserror::Error;
+
+/// The algorithms we accept for signing request objects. Only asymmetric
+/// algorithms are accepted, as the signature is verified using the client's
+/// registered public keys.
+pub const REQUEST_OBJECT_SIGNING_ALGS: &[JsonWebSignatureAlg] = &[
+    JsonWebSignatureAlg::Rs256,
+    JsonWebSignatureAlg::Rs384,
+    JsonWebSignatureAlg::Rs512,
+    JsonWebSignatureAlg::Ps256,
+    JsonWebSignatureAlg::Ps384,
+    JsonWebSignatureAlg::Ps512,
+    JsonWebSignatureAlg::Es256,
+    JsonWebSignatureAlg::Es384,
+    JsonWebSignatureAlg::Es256K,
+];
+
+const CLIENT_ID: Claim<String, Equality<str>> = Claim::new("client_id");
+const REQUEST: Claim<Value> = Claim::new("request");
+const REQUEST_URI: Claim<Value> = Claim::new("request_uri");
+
+#[derive(Debug, Error)]
+pub enum RequestObjectError {
+    #[error("could not decode the request object")]
+    Decode(#[from] JwtDecodeError),
+
+    #[error("request object signed with unsupported algorithm {0}")]
+    UnsupportedAlgorithm(JsonWebSignatureAlg),
+
+    #[error("client {0} has no JWKS registered")]
+    NoJwks(String),
+
+    #[error("could not fetch the JWKS of client {client_id}")]
+    JwksFetch {
+        client_id: String,
+        #[source]
+        source: BoxError,
+    },
+
+    #[error("invalid request object signature")]
+    InvalidSignature(#[from] NoKeyWorked),
+
+    #[error(transparent)]
+    InvalidClaim(#[from] ClaimError),
+}
+
+/// Verify the `request` parameter of an authorization request, and merge the
+/// parameters it contains with the other ones.
+///
+/// The parameters from the request object take precedence over the ones
+/// passed alongside it. If the client requires signed request objects, the
+/// parameters passed alongside it are ignored altogether, so that they can't
+/// be tampered with.
+///
+/// # Errors
+///
+/// Returns an error if the request object is not correctly signed by the
+/// client, or if its claims are invalid.
+pub async fn merge(
+    http_client: &reqwest::Client,
+    clock: &dyn Clock,
+    issuer: &str,
+    client: &Client,
+    mut parameters: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, RequestObjectError> {
+    let Some(request) = parameters.remove("request") else {
+        return Ok(parameters);
+    };
+
+    let jwt: Jwt<'static, HashMap<String, Value>> = Jwt::try_from(request)?;
+
+    let alg = jwt.header().alg();
+    if !REQUEST_OBJECT_SIGNING_ALGS.contains(alg) {
+        return Err(RequestObjectError::UnsupportedAlgorithm(alg.clone()));
+    }
+
+    let jwks = client
+        .jwks
+        .as_ref()
+        .ok_or_else(|| RequestObjectError::NoJwks(client.client_id.clone()))?;
+
+    let jwks =
+        fetch_jwks(http_client, jwks)
+            .await
+            .map_err(|source| RequestObjectError::JwksFetch {
+                client_id: client.client_id.clone(),
+                source,
+            })?;
+
+    jwt.verify_with_jwks(&jwks)?;
+
+    let (_header, mut claims) = jwt.into_parts();
+
+    // The request object is issued by the client, for us
+    claims::ISS.extract_optional_with_options(&mut claims, client.client_id.as_str())?;
+    let issuer = issuer.to_owned();
+    claims::AUD.extract_optional_with_options(&mut claims, &issuer)?;
+
+    let time_options = TimeOptions::new(clock.now());
+    claims::EXP.extract_optional_with_options(&mut claims, &time_options)?;
+    claims::NBF.extract_optional_with_options(&mut claims, &time_options)?;
+    claims::IAT.extract_optional_with_options(&mut claims, &time_options)?;
+    claims::JTI.extract_optional(&mut claims)?;
+
+    // It must be for the same client, and can't reference another request
+    CLIENT_ID.extract_optional_with_options(&mut claims, client.client_id.as_str())?;
+    REQUEST.assert_absent(&claims)?;
+    REQUEST_URI.assert_absent(&claims)?;
+
+    if client.require_signed_request_object {
+        parameters.clear();
+    }
+
+    parameters.insert("client_id".to_owned(), client.client_id.clone());
+
+    for (key, value) in claims {
+        let value = match value {
+            Value::Null => continue,
+            Value::String(value) => value,
+            // Non-string values are passed as their JSON representation, which
+            // matches how numbers would be passed in a query string
+            value => value.to_string(),
+        };
+
+        parameters.insert(key, value);
+    }
+
+    Ok(parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use mas_data_model::{JwksOrJwksUri, clock::MockClock};
+    use mas_jose::jwt::JsonWebSignatureHeader;
+    use mas_keystore::{JsonWebKey, JsonWebKeySet, Keystore, PrivateKey};
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use serde_json::json;
+
+    use super::*;
+
+    const ISSUER: &str = "https://example.com/";
+
+    fn keystore() -> Keystore {
+        let rsa = PrivateKey::load_pem(include_str!(
+            "../../../../keystore/tests/keys/rsa.pkcs1.pem"
+        ))
+        .unwrap();
+        let rsa = JsonWebKey::new(rsa).with_kid("client-rsa");
+        Keystore::new(JsonWebKeySet::new(vec![rsa]))
+    }
+
+    fn sign(keystore: &Keystore, alg: JsonWebSignatureAlg, claims: Value) -> String {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let key = keystore.signing_key_for_algorithm(&alg).unwrap();
+        let signer = key.params().signing_key_for_alg(&alg).unwrap();
+        let header = JsonWebSignatureHeader::new(alg);
+        Jwt::sign_with_rng(&mut rng, header, claims, &signer)
+            .unwrap()
+            .into_string()
+    }
+
+    fn client(keystore: &Keystore, clock: &MockClock) -> Client {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let mut client = Client::samples(clock.now(), &mut rng).remove(0);
+        client.jwks = Some(JwksOrJwksUri::Jwks(keystore.public_jwks()));
+        client
+    }
+
+    #[tokio::test]
+    async fn test_merge_request_object() {
+        let http_client = mas_http::reqwest_client();
+        let clock = MockClock::default();
+        let keystore = keystore();
+        let mut client = client(&keystore, &clock);
+        let exp = (clock.now() + Duration::try_minutes(5).unwrap()).timestamp();
+
+        let request = sign(
+            &keystore,
+            JsonWebSignatureAlg::Rs256,
+            json!({
+                "iss": client.client_id,
+                "aud": ISSUER,
+                "exp": exp,
+                "client_id": client.client_id,
+                "response_type": "code",
+                "scope": "openid",
+                "max_age": 60,
+            }),
+        );
+
+        let parameters = BTreeMap::from([
+            ("client_id".to_owned(), client.client_id.clone()),
+            ("request".to_owned(), request.clone()),
+            ("scope".to_owned(), "openid email".to_owned()),
+            ("state".to_owned(), "abcdef".to_owned()),
+        ]);
+
+        // The parameters in the request object take precedence
+        let merged = merge(&http_client, &clock, ISSUER, &client, parameters.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            merged,
+            BTreeMap::from([
+                ("client_id".to_owned(), client.client_id.clone()),
+                ("response_type".to_owned(), "code".to_owned()),
+                ("scope".to_owned(), "openid".to_owned()),
+                ("max_age".to_owned(), "60".to_owned()),
+                ("state".to_owned(), "abcdef".to_owned()),
+            ])
+        );
+
+        // Clients requiring signed request objects only get the parameters from
+        // the request object
+        client.require_signed_request_object = true;
+        let merged = merge(&http_client, &clock, ISSUER, &client, parameters.clone())
+            .await
+            .unwrap();
+        assert!(!merged.contains_key("state"));
+        client.require_signed_request_object = false;
+
+        // The request object is only valid for us
+        let res = merge(
+            &http_client,
+            &clock,
+            "https://other.example.com/",
+            &client,
+            parameters.clone(),
+        )
+        .await;
+        assert!(matches!(res, Err(RequestObjectError::InvalidClaim(_))));
+
+        // The request object expires
+        clock.advance(Duration::try_minutes(15).unwrap());
+        let res = merge(&http_client, &clock, ISSUER, &client, parameters).await;
+        assert!(matches!(res, Err(RequestObjectError::InvalidClaim(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_request_object() {
+        let http_client = mas_http::reqwest_client();
+        let clock = MockClock::default();
+        let keystore = keystore();
+        let client = client(&keystore, &clock);
+
+        let parameters = |request: String| {
+            BTreeMap::from([
+                ("client_id".to_owned(), client.client_id.clone()),
+                ("request".to_owned(), request),
+            ])
+        };
+
+        // Signed with a key the client doesn't have
+        let other_client = Keystore::new(JsonWebKeySet::new(vec![
+            JsonWebKey::new(PrivateKey::generate_ec_p256(ChaChaRng::seed_from_u64(42)))
+                .with_kid("client-rsa"),
+        ]));
+        let request = sign(
+            &other_client,
+            JsonWebSignatureAlg::Es256,
+            json!({ "response_type": "code" }),
+        );
+        let res = merge(&http_client, &clock, ISSUER, &client, parameters(request)).await;
+        assert!(matches!(res, Err(RequestObjectError::InvalidSignature(_))));
+
+        // Issued for another client
+        let request = sign(
+            &keystore,
+            JsonWebSignatureAlg::Rs256,
+            json!({ "client_id": "other-client" }),
+        );
+        let res = merge(&http_client, &clock, ISSUER, &client, parameters(request)).await;
+        assert!(matches!(res, Err(RequestObjectError::InvalidClaim(_))));
+
+        // Referencing another request
+        let request = sign(
+            &keystore,
+            JsonWebSignatureAlg::Rs256,
+            json!({ "request_uri": "https://example.com/request.jwt" }),
+        );
+        let res = merge(&http_client, &clock, ISSUER, &client, parameters(request)).await;
+        assert!(matches!(res, Err(RequestObjectError::InvalidClaim(_))));
+
+        // Unsigned request objects are rejected
+        let request = format!(
+            "{}.{}.",
+            "eyJhbGciOiJub25lIn0",      // {"alg":"none"}
+            "eyJzY29wZSI6Im9wZW5pZCJ9"  // {"scope":"openid"}
+        );
+        let res = merge(&http_client, &clock, ISSUER, &client, parameters(request)).await;
+        assert!(matches!(
+            res,
+            Err(RequestObjectError::UnsupportedAlgorithm(_) | RequestObjectError::Decode(_))
+        ));
+    }
+}
@@ -0,0 +1,594 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Client configuration endpoint, as described in [RFC 7592]. It lets
+//! dynamically registered clients read, update and delete their own
+//! registration, using the registration access token they got when
+//! registering.
+//!
+//! [RFC 7592]: https://www.rfc-editor.org/rfc/rfc7592.html
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use axum_extra::TypedHeader;
+use headers::{Authorization, authorization::Bearer};
+use hyper::{HeaderMap, StatusCode, header::WWW_AUTHENTICATE};
+use mas_axum_utils::record_error;
+use mas_data_model::Client;
+use mas_keystore::Encrypter;
+use mas_policy::{EvaluationResult, Policy};
+use mas_router::UrlBuilder;
+use mas_storage::{BoxRepository, oauth2::OAuth2ClientRepository};
+use oauth2_types::{
+    errors::{ClientError, ClientErrorCode},
+    registration::{
+        ClientMetadata, ClientMetadataVerificationError, ClientRegistrationResponse, Localized,
+        VerifiedClientMetadata,
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+use tracing::info;
+
+use crate::{
+    BoundActivityTracker, impl_from_error_for_route, oauth2::registration::public_suffix_field,
+};
+
+/// Fields which are set by the server, and which clients must not send when
+/// updating their registration
+const READ_ONLY_FIELDS: [&str; 4] = [
+    "registration_access_token",
+    "registration_client_uri",
+    "client_secret_expires_at",
+    "client_id_issued_at",
+];
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("missing registration access token")]
+    MissingToken,
+
+    #[error("invalid registration access token")]
+    InvalidToken,
+
+    #[error(transparent)]
+    JsonExtract(#[from] axum::extract::rejection::JsonRejection),
+
+    #[error("the request body is not a JSON object")]
+    NotAnObject,
+
+    #[error("the client_id does not match the registered client")]
+    ClientIdMismatch,
+
+    #[error("the client_secret does not match the registered client secret")]
+    ClientSecretMismatch,
+
+    #[error("{0} can't be set by the client")]
+    ReadOnlyField(&'static str),
+
+    #[error("the token_endpoint_auth_method of a client can't be changed")]
+    AuthMethodChanged,
+
+    #[error("could not parse the client metadata")]
+    InvalidJson(#[source] serde_json::Error),
+
+    #[error("invalid client metadata")]
+    InvalidClientMetadata(#[from] ClientMetadataVerificationError),
+
+    #[error("{0} is a public suffix, not a valid domain")]
+    UrlIsPublicSuffix(&'static str),
+
+    #[error("client update denied by the policy: {0}")]
+    PolicyDenied(EvaluationResult),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(mas_policy::LoadError);
+impl_from_error_for_route!(mas_policy::EvaluationError);
+impl_from_error_for_route!(mas_keystore::DecryptError);
+impl_from_error_for_route!(std::string::FromUtf8Error);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+
+        let response = match self {
+            Self::Internal(_) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ClientError::from(ClientErrorCode::ServerError)),
+            )
+                .into_response(),
+
+            // As per RFC 6750, errors related to the bearer token are signalled in the
+            // `WWW-Authenticate` header
+            Self::MissingToken => {
+                let mut headers = HeaderMap::new();
+                headers.insert(WWW_AUTHENTICATE, "Bearer".parse().unwrap());
+                (StatusCode::UNAUTHORIZED, headers).into_response()
+            }
+
+            e @ Self::InvalidToken => {
+                let mut headers = HeaderMap::new();
+                headers.insert(
+                    WWW_AUTHENTICATE,
+                    r#"Bearer error="invalid_token""#.parse().unwrap(),
+                );
+                (
+                    StatusCode::UNAUTHORIZED,
+                    headers,
+                    Json(
+                        ClientError::from(ClientErrorCode::AccessDenied)
+                            .with_description(e.to_string()),
+                    ),
+                )
+                    .into_response()
+            }
+
+            Self::JsonExtract(axum::extract::rejection::JsonRejection::JsonDataError(e)) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidClientMetadata)
+                        .with_description(e.to_string()),
+                ),
+            )
+                .into_response(),
+
+            Self::InvalidJson(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidClientMetadata)
+                        .with_description(e.to_string()),
+                ),
+            )
+                .into_response(),
+
+            Self::JsonExtract(_) | Self::NotAnObject => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidRequest)),
+            )
+                .into_response(),
+
+            e @ (Self::ClientIdMismatch
+            | Self::ClientSecretMismatch
+            | Self::ReadOnlyField(_)
+            | Self::AuthMethodChanged) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidClientMetadata)
+                        .with_description(e.to_string()),
+                ),
+            )
+                .into_response(),
+
+            Self::InvalidClientMetadata(
+                ClientMetadataVerificationError::MissingRedirectUris
+                | ClientMetadataVerificationError::RedirectUriWithFragment(_),
+            ) => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidRedirectUri)),
+            )
+                .into_response(),
+
+            Self::InvalidClientMetadata(e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidClientMetadata)
+                        .with_description(e.to_string()),
+                ),
+            )
+                .into_response(),
+
+            Self::UrlIsPublicSuffix("redirect_uri") => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRedirectUri)
+                        .with_description("redirect_uri is not using a valid domain".to_owned()),
+                ),
+            )
+                .into_response(),
+
+            Self::UrlIsPublicSuffix(field) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidClientMetadata)
+                        .with_description(format!("{field} is not using a valid domain")),
+                ),
+            )
+                .into_response(),
+
+            Self::PolicyDenied(evaluation) => {
+                let code = if evaluation
+                    .violations
+                    .iter()
+                    .any(|v| v.msg.contains("redirect_uri"))
+                {
+                    ClientErrorCode::InvalidRedirectUri
+                } else {
+                    ClientErrorCode::InvalidClientMetadata
+                };
+
+                let collected = &evaluation
+                    .violations
+                    .iter()
+                    .map(|v| v.msg.clone())
+                    .collect::<Vec<String>>();
+                let joined = collected.join("; ");
+
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ClientError::from(code).with_description(joined)),
+                )
+                    .into_response()
+            }
+        };
+
+        (sentry_event_id, response).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct RouteResponse {
+    #[serde(flatten)]
+    response: ClientRegistrationResponse,
+    #[serde(flatten)]
+    metadata: VerifiedClientMetadata,
+}
+
+/// Find the client authenticated by the registration access token, and check
+/// that it matches the client ID in the path
+async fn authenticate(
+    repo: &mut BoxRepository,
+    client_id: &str,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<(Client, String), RouteError> {
+    let TypedHeader(authorization) = authorization.ok_or(RouteError::MissingToken)?;
+    let token = authorization.token();
+
+    let client = repo
+        .oauth2_client()
+        .find_by_registration_access_token(token)
+        .await?
+        .filter(|client| client.client_id == client_id)
+        .ok_or(RouteError::InvalidToken)?;
+
+    Ok((client, token.to_owned()))
+}
+
+/// Build the response describing the current registration of the client
+fn render(
+    client: Client,
+    registration_access_token: String,
+    encrypter: &Encrypter,
+    url_builder: &UrlBuilder,
+) -> Result<RouteResponse, RouteError> {
+    let client_secret = client
+        .encrypted_client_secret
+        .as_deref()
+        .map(|encrypted| encrypter.decrypt_string(encrypted))
+        .transpose()?
+        .map(String::from_utf8)
+        .transpose()?;
+
+    let response = ClientRegistrationResponse {
+        client_id: client.client_id.clone(),
+        client_secret,
+        client_id_issued_at: Some(client.id.datetime().into()),
+        client_secret_expires_at: None,
+        registration_access_token: Some(registration_access_token),
+        registration_client_uri: Some(url_builder.oauth_client_configuration_endpoint(client.id)),
+    };
+
+    // We round-trip back to the metadata to output it in the response
+    // This should never fail, as the client is valid
+    let metadata = client.into_metadata().validate()?;
+
+    Ok(RouteResponse { response, metadata })
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.client_configuration.get",
+    fields(client.id = client_id),
+    skip_all,
+)]
+pub(crate) async fn get(
+    mut repo: BoxRepository,
+    State(encrypter): State<Encrypter>,
+    State(url_builder): State<UrlBuilder>,
+    Path(client_id): Path<String>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<impl IntoResponse, RouteError> {
+    let (client, token) = authenticate(&mut repo, &client_id, authorization).await?;
+
+    let response = render(client, token, &encrypter, &url_builder)?;
+
+    Ok(Json(response))
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.client_configuration.put",
+    fields(client.id = client_id),
+    skip_all,
+)]
+pub(crate) async fn put(
+    mut repo: BoxRepository,
+    mut policy: Policy,
+    activity_tracker: BoundActivityTracker,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    State(encrypter): State<Encrypter>,
+    State(url_builder): State<UrlBuilder>,
+    Path(client_id): Path<String>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+    body: Result<Json<Value>, axum::extract::rejection::JsonRejection>,
+) -> Result<impl IntoResponse, RouteError> {
+    let (client, token) = authenticate(&mut repo, &client_id, authorization).await?;
+
+    let Json(body) = body?;
+    let Value::Object(mut body) = body else {
+        return Err(RouteError::NotAnObject);
+    };
+
+    // The request must be about the same client, and may only repeat its current
+    // secret
+    if body.remove("client_id") != Some(Value::String(client.client_id.clone())) {
+        return Err(RouteError::ClientIdMismatch);
+    }
+
+    if let Some(client_secret) = body.remove("client_secret") {
+        let current = client
+            .encrypted_client_secret
+            .as_deref()
+            .map(|encrypted| encrypter.decrypt_string(encrypted))
+            .transpose()?
+            .map(String::from_utf8)
+            .transpose()?;
+
+        if client_secret.as_str() != current.as_deref() {
+            return Err(RouteError::ClientSecretMismatch);
+        }
+    }
+
+    if let Some(field) = READ_ONLY_FIELDS
+        .into_iter()
+        .find(|field| body.contains_key(*field))
+    {
+        return Err(RouteError::ReadOnlyField(field));
+    }
+
+    let body: ClientMetadata =
+        serde_json::from_value(Value::Object(body)).map_err(RouteError::InvalidJson)?;
+
+    info!(?body, "Client configuration update");
+
+    let metadata = body.sorted().validate()?;
+
+    if let Some(field) = public_suffix_field(&metadata) {
+        return Err(RouteError::UrlIsPublicSuffix(field));
+    }
+
+    let res = policy
+        .evaluate_client_registration(mas_policy::ClientRegistrationInput {
+            client_metadata: &metadata,
+            requester: mas_policy::Requester {
+                ip_address: activity_tracker.ip(),
+                user_agent: user_agent.map(|ua| ua.to_string()),
+            },
+        })
+        .await?;
+    if !res.valid() {
+        return Err(RouteError::PolicyDenied(res));
+    }
+
+    // Changing the authentication method would require issuing or revoking a
+    // client secret, which we don't support
+    if metadata.token_endpoint_auth_method != client.token_endpoint_auth_method {
+        return Err(RouteError::AuthMethodChanged);
+    }
+
+    let client = repo
+        .oauth2_client()
+        .update_metadata(
+            client,
+            metadata.redirect_uris().to_vec(),
+            metadata.application_type.clone(),
+            metadata.grant_types().to_vec(),
+            metadata
+                .client_name
+                .clone()
+                .map(Localized::to_non_localized),
+            metadata.logo_uri.clone().map(Localized::to_non_localized),
+            metadata.client_uri.clone().map(Localized::to_non_localized),
+            metadata.policy_uri.clone().map(Localized::to_non_localized),
+            metadata.tos_uri.clone().map(Localized::to_non_localized),
+            metadata.jwks_uri.clone(),
+            metadata.jwks.clone(),
+            metadata.id_token_signed_response_alg.clone(),
+            metadata.userinfo_signed_response_alg.clone(),
+            metadata.token_endpoint_auth_signing_alg.clone(),
+            metadata.initiate_login_uri.clone(),
+            metadata
+                .post_logout_redirect_uris
+                .clone()
+                .unwrap_or_default(),
+            metadata.backchannel_logout_uri.clone(),
+            metadata.backchannel_logout_session_required(),
+            metadata.require_pushed_authorization_requests(),
+            metadata.require_signed_request_object(),
+            metadata.access_token_signed_response_alg.clone(),
+        )
+        .await?;
+
+    info!(%client.id, "Updated client registration");
+
+    let response = render(client, token, &encrypter, &url_builder)?;
+
+    repo.save().await?;
+
+    Ok(Json(response))
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.client_configuration.delete",
+    fields(client.id = client_id),
+    skip_all,
+)]
+pub(crate) async fn delete(
+    mut repo: BoxRepository,
+    Path(client_id): Path<String>,
+    authorization: Option<TypedHeader<Authorization<Bearer>>>,
+) -> Result<impl IntoResponse, RouteError> {
+    let (client, _token) = authenticate(&mut repo, &client_id, authorization).await?;
+
+    let id = client.id;
+    repo.oauth2_client().delete(client).await?;
+    repo.save().await?;
+
+    info!(client.id = %id, "Deleted client registration");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_router::SimpleRoute;
+    use oauth2_types::{
+        errors::{ClientError, ClientErrorCode},
+        registration::ClientRegistrationResponse,
+    };
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    async fn register(state: &TestState) -> ClientRegistrationResponse {
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        response.json()
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_client_configuration(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let registration = register(&state).await;
+        let token = registration.registration_access_token.unwrap();
+        let uri = registration.registration_client_uri.unwrap();
+        let client_secret = registration.client_secret.unwrap();
+
+        // Read the registration back
+        let request = Request::get(uri.path()).bearer(&token).empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["client_id"], registration.client_id);
+        assert_eq!(body["client_secret"], client_secret);
+        assert_eq!(
+            body["redirect_uris"],
+            serde_json::json!(["https://example.com/"])
+        );
+
+        // Update the redirect URIs
+        let request = Request::put(uri.path())
+            .bearer(&token)
+            .json(serde_json::json!({
+                "client_id": registration.client_id,
+                "client_secret": client_secret,
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let body: serde_json::Value = response.json();
+        assert_eq!(
+            body["redirect_uris"],
+            serde_json::json!(["https://example.com/callback"])
+        );
+
+        // Delete the registration
+        let request = Request::delete(uri.path()).bearer(&token).empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NO_CONTENT);
+
+        // The token is not valid anymore
+        let request = Request::get(uri.path()).bearer(&token).empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_client_configuration_errors(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let registration = register(&state).await;
+        let token = registration.registration_access_token.unwrap();
+        let uri = registration.registration_client_uri.unwrap();
+
+        // Missing token
+        let request = Request::get(uri.path()).empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // Invalid token
+        let request = Request::get(uri.path()).bearer("not-a-valid-token").empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // Token used for another client
+        let other = register(&state).await;
+        let request = Request::get(other.registration_client_uri.unwrap().path())
+            .bearer(&token)
+            .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+
+        // Read-only fields can't be sent
+        let request = Request::put(uri.path())
+            .bearer(&token)
+            .json(serde_json::json!({
+                "client_id": registration.client_id,
+                "registration_access_token": token,
+                "redirect_uris": ["https://example.com/"],
+                "token_endpoint_auth_method": "client_secret_basic",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+
+        // The authentication method can't be changed
+        let request = Request::put(uri.path())
+            .bearer(&token)
+            .json(serde_json::json!({
+                "client_id": registration.client_id,
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/"],
+                "token_endpoint_auth_method": "none",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidClientMetadata);
+    }
+}
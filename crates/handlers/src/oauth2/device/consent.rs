@@ -27,6 +27,7 @@ use ulid::Ulid;
 
 use crate::{
     BoundActivityTracker, PreferredLanguage,
+    oauth2::is_mfa_authenticated,
     session::{SessionOrFallback, load_session_or_fallback},
 };
 
@@ -104,12 +105,15 @@ pub(crate) async fn get(
         .map_err(InternalError::from_anyhow)?;
 
     // Evaluate the policy
+    let mfa_authenticated = is_mfa_authenticated(&mut repo, &session).await?;
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             grant_type: mas_policy::GrantType::DeviceCode,
+            subject: None,
             client: &client,
             scope: &grant.scope,
             user: Some(&session.user),
+            mfa_authenticated,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -206,12 +210,15 @@ pub(crate) async fn post(
         .map_err(InternalError::from_anyhow)?;
 
     // Evaluate the policy
+    let mfa_authenticated = is_mfa_authenticated(&mut repo, &session).await?;
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             grant_type: mas_policy::GrantType::DeviceCode,
+            subject: None,
             client: &client,
             scope: &grant.scope,
             user: Some(&session.user),
+            mfa_authenticated,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent,
@@ -5,6 +5,7 @@
 // Please see LICENSE files in the repository root for full details.
 
 use axum::{Json, extract::State, response::IntoResponse};
+use mas_axum_utils::dpop::DPOP_SIGNING_ALGS;
 use mas_iana::oauth::{
     OAuthAuthorizationEndpointResponseType, OAuthClientAuthenticationMethod,
     PkceCodeChallengeMethod,
@@ -19,6 +20,7 @@ use oauth2_types::{
 };
 use serde::Serialize;
 
+use super::authorization::request_object::REQUEST_OBJECT_SIGNING_ALGS;
 use crate::SiteConfig;
 
 #[derive(Debug, Serialize)]
@@ -60,11 +62,21 @@ pub(crate) async fn get(
     let authorization_endpoint = Some(url_builder.oauth_authorization_endpoint());
     let token_endpoint = Some(url_builder.oauth_token_endpoint());
     let device_authorization_endpoint = Some(url_builder.oauth_device_authorization_endpoint());
+    let pushed_authorization_request_endpoint =
+        Some(url_builder.oauth_pushed_authorization_request_endpoint());
     let jwks_uri = Some(url_builder.jwks_uri());
     let introspection_endpoint = Some(url_builder.oauth_introspection_endpoint());
     let revocation_endpoint = Some(url_builder.oauth_revocation_endpoint());
     let userinfo_endpoint = Some(url_builder.oidc_userinfo_endpoint());
     let registration_endpoint = Some(url_builder.oauth_registration_endpoint());
+    let end_session_endpoint = Some(url_builder.oidc_end_session_endpoint());
+
+    let backchannel_logout_supported = Some(true);
+    let backchannel_logout_session_supported = Some(true);
+
+    let dpop_signing_alg_values_supported = Some(DPOP_SIGNING_ALGS.to_vec());
+
+    let request_object_signing_alg_values_supported = Some(REQUEST_OBJECT_SIGNING_ALGS.to_vec());
 
     let scopes_supported = Some(vec![scope::OPENID.to_string(), scope::EMAIL.to_string()]);
 
@@ -85,6 +97,7 @@ pub(crate) async fn get(
         GrantType::RefreshToken,
         GrantType::ClientCredentials,
         GrantType::DeviceCode,
+        GrantType::TokenExchange,
     ]);
 
     let token_endpoint_auth_methods_supported = client_auth_methods_supported.clone();
@@ -127,7 +140,7 @@ pub(crate) async fn get(
     ]);
 
     let claims_parameter_supported = Some(false);
-    let request_parameter_supported = Some(false);
+    let request_parameter_supported = Some(true);
     let request_uri_parameter_supported = Some(false);
 
     let prompt_values_supported = Some({
@@ -170,8 +183,14 @@ pub(crate) async fn get(
         claims_parameter_supported,
         request_parameter_supported,
         request_uri_parameter_supported,
+        request_object_signing_alg_values_supported,
         prompt_values_supported,
         device_authorization_endpoint,
+        end_session_endpoint,
+        backchannel_logout_supported,
+        backchannel_logout_session_supported,
+        pushed_authorization_request_endpoint,
+        dpop_signing_alg_values_supported,
         ..ProviderMetadata::default()
     };
 
@@ -194,6 +213,7 @@ pub(crate) async fn get(
 #[cfg(test)]
 mod tests {
     use hyper::{Request, StatusCode};
+    use mas_iana::jose::JsonWebSignatureAlg;
     use oauth2_types::oidc::ProviderMetadata;
     use sqlx::PgPool;
 
@@ -209,6 +229,36 @@ mod tests {
         response.assert_status(StatusCode::OK);
 
         let metadata: ProviderMetadata = response.json();
+        assert_eq!(
+            metadata.end_session_endpoint,
+            Some(state.url_builder.oidc_end_session_endpoint())
+        );
+        assert_eq!(metadata.backchannel_logout_supported, Some(true));
+        assert_eq!(metadata.backchannel_logout_session_supported, Some(true));
+        assert_eq!(
+            metadata.pushed_authorization_request_endpoint,
+            Some(
+                state
+                    .url_builder
+                    .oauth_pushed_authorization_request_endpoint()
+            )
+        );
+
+        assert_eq!(metadata.request_parameter_supported, Some(true));
+        assert!(
+            metadata
+                .request_object_signing_alg_values_supported
+                .as_ref()
+                .is_some_and(|algs| !algs.contains(&JsonWebSignatureAlg::None))
+        );
+
+        assert!(
+            metadata
+                .dpop_signing_alg_values_supported
+                .as_ref()
+                .is_some_and(|algs| algs.contains(&JsonWebSignatureAlg::Es256))
+        );
+
         metadata
             .validate(state.url_builder.oidc_issuer().as_str())
             .expect("Invalid metadata");
@@ -0,0 +1,608 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! OpenID Connect RP-Initiated Logout 1.0
+//!
+//! See <https://openid.net/specs/openid-connect-rpinitiated-1_0.html>
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Form, State},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::Query;
+use hyper::StatusCode;
+use mas_axum_utils::{
+    GenericError, InternalError, SessionInfoExt,
+    cookies::CookieJar,
+    csrf::{CsrfExt, ProtectedForm},
+};
+use mas_data_model::{BoxClock, BoxRng, Client};
+use mas_jose::{
+    claims::{self, Claim, OneOrMany},
+    jwt::Jwt,
+};
+use mas_keystore::Keystore;
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxRepository,
+    oauth2::{OAuth2ClientRepository, OAuth2SessionFilter},
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _, SyncDevicesJob},
+    user::BrowserSessionRepository,
+};
+use mas_templates::{EndSessionContext, TemplateContext, Templates};
+use oauth2_types::oidc::RpInitiatedLogoutRequest;
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    BoundActivityTracker, PreferredLanguage, impl_from_error_for_route,
+    session::{SessionOrFallback, load_session_or_fallback},
+};
+
+#[derive(Debug, Error)]
+pub enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error(transparent)]
+    Csrf(#[from] mas_axum_utils::csrf::CsrfError),
+
+    #[error("The id_token_hint is invalid")]
+    InvalidIdTokenHint,
+
+    #[error("The client_id does not match the audience of the id_token_hint")]
+    ClientIdMismatch,
+
+    #[error("Client not found")]
+    ClientNotFound,
+
+    #[error("A post_logout_redirect_uri was given without identifying the client")]
+    MissingClient,
+
+    #[error("The post_logout_redirect_uri is not registered for this client")]
+    InvalidPostLogoutRedirectUri,
+}
+
+/// The `aud` claim, without checking it against a known client ID, since we
+/// use it to find the client
+const AUD: Claim<OneOrMany<String>> = Claim::new("aud");
+
+impl_from_error_for_route!(mas_templates::TemplateError);
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(crate::session::SessionLoadError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Internal(e) => InternalError::new(e).into_response(),
+            e @ Self::ClientNotFound => GenericError::new(StatusCode::NOT_FOUND, e).into_response(),
+            // The spec asks us to not redirect to an unverified URI, so we
+            // display an error instead
+            e @ (Self::Csrf(_)
+            | Self::InvalidIdTokenHint
+            | Self::ClientIdMismatch
+            | Self::MissingClient
+            | Self::InvalidPostLogoutRedirectUri) => {
+                GenericError::new(StatusCode::BAD_REQUEST, e).into_response()
+            }
+        }
+    }
+}
+
+/// A logout request which was checked against the client metadata
+struct ValidatedLogoutRequest {
+    /// The client which initiated the logout, if it could be identified
+    client: Option<Client>,
+
+    /// The `sub` claim of the `id_token_hint`, if one was given
+    sub: Option<String>,
+
+    /// Where to send the user after the logout, with the `state` appended
+    redirect_uri: Option<Url>,
+}
+
+impl ValidatedLogoutRequest {
+    /// Redirect to the client if it asked for it, or else to the login page
+    fn go_next(self, url_builder: &UrlBuilder) -> Response {
+        match self.redirect_uri {
+            Some(uri) => Redirect::to(uri.as_str()).into_response(),
+            None => url_builder
+                .redirect(&mas_router::Login::default())
+                .into_response(),
+        }
+    }
+}
+
+/// Check the parameters of the logout request
+async fn validate(
+    request: &RpInitiatedLogoutRequest,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
+    repo: &mut BoxRepository,
+) -> Result<ValidatedLogoutRequest, RouteError> {
+    let mut client_id = request.client_id.clone();
+    let mut sub = None;
+
+    if let Some(id_token_hint) = &request.id_token_hint {
+        // The ID token was issued by us, so we can check it against our own
+        // keys. It is only a hint, so it may have expired already.
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(id_token_hint.as_str()).map_err(|_| RouteError::InvalidIdTokenHint)?;
+        jwt.verify_with_jwks(&key_store.public_jwks())
+            .map_err(|_| RouteError::InvalidIdTokenHint)?;
+
+        let (_header, mut claims) = jwt.into_parts();
+        let issuer = url_builder.oidc_issuer();
+        claims::ISS
+            .extract_required_with_options(&mut claims, issuer.as_str())
+            .map_err(|_| RouteError::InvalidIdTokenHint)?;
+        let audience = AUD
+            .extract_required(&mut claims)
+            .map_err(|_| RouteError::InvalidIdTokenHint)?;
+        sub = Some(
+            claims::SUB
+                .extract_required(&mut claims)
+                .map_err(|_| RouteError::InvalidIdTokenHint)?,
+        );
+
+        // We only ever issue ID tokens with a single audience
+        let [audience] = &audience[..] else {
+            return Err(RouteError::InvalidIdTokenHint);
+        };
+
+        if client_id.as_ref().is_some_and(|id| id != audience) {
+            return Err(RouteError::ClientIdMismatch);
+        }
+        client_id = Some(audience.clone());
+    }
+
+    let client = if let Some(client_id) = client_id {
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await?
+            .ok_or(RouteError::ClientNotFound)?;
+        Some(client)
+    } else {
+        None
+    };
+
+    let redirect_uri = match (&request.post_logout_redirect_uri, &client) {
+        (None, _) => None,
+        (Some(_), None) => return Err(RouteError::MissingClient),
+        (Some(uri), Some(client)) => {
+            if !client.has_post_logout_redirect_uri(uri) {
+                return Err(RouteError::InvalidPostLogoutRedirectUri);
+            }
+
+            let mut uri = uri.clone();
+            if let Some(state) = &request.state {
+                uri.query_pairs_mut().append_pair("state", state);
+            }
+            Some(uri)
+        }
+    };
+
+    Ok(ValidatedLogoutRequest {
+        client,
+        sub,
+        redirect_uri,
+    })
+}
+
+#[tracing::instrument(name = "handlers.oauth2.end_session.get", skip_all)]
+pub(crate) async fn get(
+    rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    State(key_store): State<Keystore>,
+    repo: BoxRepository,
+    cookie_jar: CookieJar,
+    Query(request): Query<RpInitiatedLogoutRequest>,
+) -> Result<Response, RouteError> {
+    show(
+        rng,
+        clock,
+        locale,
+        templates,
+        url_builder,
+        key_store,
+        repo,
+        cookie_jar,
+        request,
+    )
+    .await
+}
+
+#[tracing::instrument(name = "handlers.oauth2.end_session.post", skip_all)]
+pub(crate) async fn post(
+    rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    State(key_store): State<Keystore>,
+    repo: BoxRepository,
+    cookie_jar: CookieJar,
+    Form(request): Form<RpInitiatedLogoutRequest>,
+) -> Result<Response, RouteError> {
+    show(
+        rng,
+        clock,
+        locale,
+        templates,
+        url_builder,
+        key_store,
+        repo,
+        cookie_jar,
+        request,
+    )
+    .await
+}
+
+/// Validate the logout request and ask the user to confirm it. The spec
+/// allows both GET and POST requests, which only differ in how the parameters
+/// are passed.
+#[allow(clippy::too_many_arguments)]
+async fn show(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    locale: mas_i18n::DataLocale,
+    templates: Templates,
+    url_builder: UrlBuilder,
+    key_store: Keystore,
+    mut repo: BoxRepository,
+    cookie_jar: CookieJar,
+    request: RpInitiatedLogoutRequest,
+) -> Result<Response, RouteError> {
+    let validated = validate(&request, &key_store, &url_builder, &mut repo).await?;
+
+    let (cookie_jar, maybe_session) = match load_session_or_fallback(
+        cookie_jar, &clock, &mut rng, &templates, &locale, &mut repo,
+    )
+    .await?
+    {
+        SessionOrFallback::MaybeSession {
+            cookie_jar,
+            maybe_session,
+            ..
+        } => (cookie_jar, maybe_session),
+        SessionOrFallback::Fallback { response } => return Ok(response),
+    };
+
+    // If there is nothing to log out from, send the user back right away
+    let Some(session) = maybe_session else {
+        return Ok((cookie_jar, validated.go_next(&url_builder)).into_response());
+    };
+
+    if validated
+        .sub
+        .as_ref()
+        .is_some_and(|sub| *sub != session.user.sub)
+    {
+        // The user will still be asked to confirm the logout, so this isn't
+        // critical
+        tracing::warn!(
+            user.id = %session.user.id,
+            "The id_token_hint was issued for another user than the one currently logged in"
+        );
+    }
+
+    let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
+    let ctx = EndSessionContext::new(validated.client, request)
+        .with_session(session)
+        .with_csrf(csrf_token.form_value())
+        .with_language(locale);
+
+    let content = templates.render_end_session(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
+
+#[derive(Deserialize)]
+pub(crate) struct EndSessionForm {
+    #[serde(flatten)]
+    request: RpInitiatedLogoutRequest,
+
+    /// Set if the user also wants to end the OAuth 2.0 sessions started from
+    /// this browser session
+    #[serde(default)]
+    end_oauth2_sessions: Option<String>,
+}
+
+#[tracing::instrument(name = "handlers.oauth2.end_session.confirm", skip_all)]
+pub(crate) async fn confirm(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    State(url_builder): State<UrlBuilder>,
+    State(key_store): State<Keystore>,
+    mut repo: BoxRepository,
+    activity_tracker: BoundActivityTracker,
+    cookie_jar: CookieJar,
+    Form(form): Form<ProtectedForm<EndSessionForm>>,
+) -> Result<Response, RouteError> {
+    let form = cookie_jar.verify_form(&clock, form)?;
+
+    // The parameters went through the user agent again, so check them again
+    let validated = validate(&form.request, &key_store, &url_builder, &mut repo).await?;
+
+    let (session_info, cookie_jar) = cookie_jar.session_info();
+
+    if let Some(session_id) = session_info.current_session_id() {
+        let maybe_session = repo.browser_session().lookup(session_id).await?;
+        if let Some(session) = maybe_session
+            && session.finished_at.is_none()
+        {
+            activity_tracker
+                .record_browser_session(&clock, &session)
+                .await;
+
+            if form.end_oauth2_sessions.is_some() {
+                let filter = OAuth2SessionFilter::new()
+                    .for_browser_session(&session)
+                    .active_only();
+                let affected = repo.oauth2_session().finish_bulk(&clock, filter).await?;
+                tracing::info!(
+                    user.id = %session.user.id,
+                    "Finished {affected} OAuth 2.0 sessions"
+                );
+
+                if affected > 0 {
+                    repo.queue_job()
+                        .schedule_job(&mut rng, &clock, SyncDevicesJob::new(&session.user))
+                        .await?;
+                }
+            }
+
+            let session = repo.browser_session().finish(&clock, session).await?;
+
+            // Notify the clients which got a session through this browser
+            // session, including the ones we may just have ended
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    BackchannelLogoutJob::for_browser_session(&session),
+                )
+                .await?;
+        }
+    }
+
+    repo.save().await?;
+
+    // We always want to clear out the session cookie, even if the session was
+    // invalid
+    let cookie_jar = cookie_jar.update_session_info(&session_info.mark_session_ended());
+
+    Ok((cookie_jar, validated.go_next(&url_builder)).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode, header::LOCATION};
+    use mas_axum_utils::SessionInfoExt as _;
+    use mas_data_model::{BrowserSession, Client};
+    use mas_router::SimpleRoute;
+    use oauth2_types::registration::ClientRegistrationResponse;
+    use sqlx::PgPool;
+
+    use crate::test_utils::{CookieHelper, RequestBuilderExt, ResponseExt, TestState, setup};
+
+    /// Register a client with a `post_logout_redirect_uri`, and start a browser
+    /// session for a user
+    async fn provision(state: &TestState) -> (Client, BrowserSession) {
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "post_logout_redirect_uris": ["https://example.com/logged-out"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: ClientRegistrationResponse = response.json();
+
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.unwrap();
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&response.client_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let user = repo
+            .user()
+            .add(&mut rng, &state.clock, "john".to_owned())
+            .await
+            .unwrap();
+        let browser_session = repo
+            .browser_session()
+            .add(&mut rng, &state.clock, &user, None)
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        (client, browser_session)
+    }
+
+    fn logout_request(params: &serde_json::Value) -> Request<String> {
+        let query = serde_urlencoded::to_string(params).unwrap();
+        Request::get(format!(
+            "{}?{query}",
+            mas_router::OAuth2EndSessionEndpoint::PATH
+        ))
+        .empty()
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_id_token_hint(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let (client, browser_session) = provision(&state).await;
+
+        let id_token = crate::oauth2::generate_id_token(
+            &mut state.rng(),
+            &state.clock,
+            &state.url_builder,
+            &state.key_store,
+            &client,
+            None,
+            &browser_session,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // A hint which isn't a JWT is rejected
+        let request = logout_request(&serde_json::json!({
+            "id_token_hint": "not-a-jwt",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // So is a hint with a broken signature
+        let (rest, _signature) = id_token.rsplit_once('.').unwrap();
+        let request = logout_request(&serde_json::json!({
+            "id_token_hint": format!("{rest}.c2lnbmF0dXJl"),
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // The client_id must match the audience of the hint
+        let request = logout_request(&serde_json::json!({
+            "id_token_hint": id_token,
+            "client_id": "some-other-client",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // A valid hint identifies the client, so the redirect URI can be checked
+        // without a client_id. Without a session, the user is sent back right
+        // away.
+        let request = logout_request(&serde_json::json!({
+            "id_token_hint": id_token,
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "https://example.com/logged-out");
+
+        // An expired hint is still accepted
+        state.clock.advance(chrono::Duration::days(1));
+        let request = logout_request(&serde_json::json!({
+            "id_token_hint": id_token,
+            "client_id": client.client_id,
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "/login");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_post_logout_redirect_uri(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let (client, _browser_session) = provision(&state).await;
+
+        // A redirect URI which wasn't registered by the client is rejected
+        let request = logout_request(&serde_json::json!({
+            "client_id": client.client_id,
+            "post_logout_redirect_uri": "https://example.com/callback",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // So is a redirect URI without a way to identify the client
+        let request = logout_request(&serde_json::json!({
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // An unknown client is rejected
+        let request = logout_request(&serde_json::json!({
+            "client_id": "some-other-client",
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::NOT_FOUND);
+
+        // The registered redirect URI is accepted
+        let request = logout_request(&serde_json::json!({
+            "client_id": client.client_id,
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "https://example.com/logged-out");
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_state_round_trip(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+        let (client, browser_session) = provision(&state).await;
+
+        let cookies = CookieHelper::new();
+        cookies.import(state.cookie_jar().set_session(&browser_session));
+
+        // The user is asked to confirm the logout, and the request parameters are
+        // carried over in the form
+        let request = logout_request(&serde_json::json!({
+            "client_id": client.client_id,
+            "post_logout_redirect_uri": "https://example.com/logged-out",
+            "state": "some-state",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        assert!(
+            response
+                .body()
+                .contains(r#"name="state" value="some-state""#)
+        );
+
+        let csrf = response
+            .body()
+            .split("name=\"csrf\" value=\"")
+            .nth(1)
+            .unwrap()
+            .split('\"')
+            .next()
+            .unwrap()
+            .to_owned();
+
+        // Confirming ends the browser session and redirects back to the client,
+        // with the state
+        let request =
+            Request::post(mas_router::OAuth2EndSessionConfirm::PATH).form(serde_json::json!({
+                "csrf": csrf,
+                "client_id": client.client_id,
+                "post_logout_redirect_uri": "https://example.com/logged-out",
+                "state": "some-state",
+            }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "https://example.com/logged-out?state=some-state");
+
+        let mut repo = state.repository().await.unwrap();
+        let browser_session = repo
+            .browser_session()
+            .lookup(browser_session.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(browser_session.finished_at.is_some());
+    }
+}
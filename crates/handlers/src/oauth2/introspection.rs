@@ -5,7 +5,7 @@
 // Please see LICENSE files in the repository root for full details.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeSet, HashMap},
     sync::{Arc, LazyLock},
 };
 
@@ -16,9 +16,11 @@ use mas_axum_utils::{
     record_error,
 };
 use mas_data_model::{
-    BoxClock, Clock, Device, TokenFormatError, TokenType, personal::session::PersonalSessionOwner,
+    BoxClock, Clock, Device, Impersonation, TokenFormatError, TokenType,
+    personal::session::PersonalSessionOwner,
 };
 use mas_iana::oauth::{OAuthClientAuthenticationMethod, OAuthTokenTypeHint};
+use mas_jose::{claims, jwt::Jwt};
 use mas_keystore::Encrypter;
 use mas_matrix::HomeserverConnection;
 use mas_storage::{
@@ -29,7 +31,7 @@ use mas_storage::{
 };
 use oauth2_types::{
     errors::{ClientError, ClientErrorCode},
-    requests::{IntrospectionRequest, IntrospectionResponse},
+    requests::{Actor, Confirmation, IntrospectionRequest, IntrospectionResponse},
     scope::{Scope, ScopeToken},
 };
 use opentelemetry::{Key, KeyValue, metrics::Counter};
@@ -224,6 +226,8 @@ const INACTIVE: IntrospectionResponse = IntrospectionResponse {
     iss: None,
     jti: None,
     device_id: None,
+    cnf: None,
+    act: None,
 };
 
 const UNSTABLE_API_SCOPE: ScopeToken =
@@ -253,6 +257,41 @@ fn normalize_scope(mut scope: Scope) -> Scope {
     scope
 }
 
+/// Get the `jti` claim of a JWT access token.
+///
+/// The signature is not checked, as the token was already found in the
+/// database. Returns `None` for opaque access tokens.
+fn jwt_access_token_id(token: &str) -> Option<String> {
+    let jwt: Jwt<'_, HashMap<String, serde_json::Value>> = Jwt::try_from(token).ok()?;
+    let (_, mut claims) = jwt.into_parts();
+    claims::JTI.extract_optional(&mut claims).ok().flatten()
+}
+
+/// Build the `act` claim of a session created by an admin to impersonate its
+/// user, identifying the admin who created it.
+async fn impersonation_actor(
+    repo: &mut BoxRepository,
+    impersonation: Option<&Impersonation>,
+) -> Result<Option<Actor>, RouteError> {
+    let Some(impersonation) = impersonation else {
+        return Ok(None);
+    };
+
+    // The impersonator might have been deleted since
+    let sub = if let Some(user_id) = impersonation.impersonator_user_id {
+        repo.user().lookup(user_id).await?.map(|user| user.sub)
+    } else {
+        None
+    };
+
+    Ok(Some(Actor {
+        sub,
+        client_id: impersonation
+            .impersonator_client_id
+            .map(|client_id| client_id.to_string()),
+    }))
+}
+
 #[tracing::instrument(
     name = "handlers.oauth2.introspection.post",
     fields(client.id = credentials.client_id()),
@@ -341,10 +380,17 @@ pub(crate) async fn post(
                 .await?
                 .ok_or(RouteError::CantLoadOAuthSession(access_token.session_id))?;
 
-            if !session.is_valid() {
+            if !session.is_valid()
+                || session
+                    .impersonation
+                    .as_ref()
+                    .is_some_and(|impersonation| impersonation.is_expired(clock.now()))
+            {
                 return Err(RouteError::InvalidOAuthSession(session.id));
             }
 
+            let act = impersonation_actor(&mut repo, session.impersonation.as_ref()).await?;
+
             // If this is the first time we're using this token, mark it as used
             if !access_token.is_used() {
                 access_token = repo
@@ -400,8 +446,14 @@ pub(crate) async fn post(
                 sub,
                 aud: None,
                 iss: None,
-                jti: Some(access_token.jti()),
+                // JWT access tokens carry their own ID, which differs from the one
+                // of the database row
+                jti: Some(jwt_access_token_id(token).unwrap_or_else(|| access_token.jti())),
                 device_id: None,
+                cnf: access_token
+                    .dpop_jkt
+                    .map(|jkt| Confirmation { jkt: Some(jkt) }),
+                act,
             }
         }
 
@@ -422,10 +474,17 @@ pub(crate) async fn post(
                 .await?
                 .ok_or(RouteError::CantLoadOAuthSession(refresh_token.session_id))?;
 
-            if !session.is_valid() {
+            if !session.is_valid()
+                || session
+                    .impersonation
+                    .as_ref()
+                    .is_some_and(|impersonation| impersonation.is_expired(clock.now()))
+            {
                 return Err(RouteError::InvalidOAuthSession(session.id));
             }
 
+            let act = impersonation_actor(&mut repo, session.impersonation.as_ref()).await?;
+
             // The session might not have a user on it (for Client Credentials grants for
             // example), so we're optionally fetching the user
             let (sub, username) = if let Some(user_id) = session.user_id {
@@ -473,6 +532,10 @@ pub(crate) async fn post(
                 iss: None,
                 jti: Some(refresh_token.jti()),
                 device_id: None,
+                cnf: refresh_token
+                    .dpop_jkt
+                    .map(|jkt| Confirmation { jkt: Some(jkt) }),
+                act,
             }
         }
 
@@ -493,10 +556,17 @@ pub(crate) async fn post(
                 .await?
                 .ok_or(RouteError::CantLoadCompatSession(access_token.session_id))?;
 
-            if !session.is_valid() {
+            if !session.is_valid()
+                || session
+                    .impersonation
+                    .as_ref()
+                    .is_some_and(|impersonation| impersonation.is_expired(clock.now()))
+            {
                 return Err(RouteError::InvalidCompatSession(session.id));
             }
 
+            let act = impersonation_actor(&mut repo, session.impersonation.as_ref()).await?;
+
             let user = repo
                 .user()
                 .lookup(session.user_id)
@@ -557,6 +627,8 @@ pub(crate) async fn post(
                 iss: None,
                 jti: None,
                 device_id: session.device.map(Device::into),
+                cnf: None,
+                act,
             }
         }
 
@@ -577,10 +649,17 @@ pub(crate) async fn post(
                 .await?
                 .ok_or(RouteError::CantLoadCompatSession(refresh_token.session_id))?;
 
-            if !session.is_valid() {
+            if !session.is_valid()
+                || session
+                    .impersonation
+                    .as_ref()
+                    .is_some_and(|impersonation| impersonation.is_expired(clock.now()))
+            {
                 return Err(RouteError::InvalidCompatSession(session.id));
             }
 
+            let act = impersonation_actor(&mut repo, session.impersonation.as_ref()).await?;
+
             let user = repo
                 .user()
                 .lookup(session.user_id)
@@ -639,6 +718,8 @@ pub(crate) async fn post(
                 iss: None,
                 jti: None,
                 device_id: session.device.map(Device::into),
+                cnf: None,
+                act,
             }
         }
 
@@ -730,6 +811,8 @@ pub(crate) async fn post(
                 iss: None,
                 jti: None,
                 device_id: None,
+                cnf: None,
+                act: None,
             }
         }
     };
@@ -840,7 +923,9 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                TokenType::AccessToken.generate(&mut state.rng()),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
+                None,
             )
             .await
             .unwrap();
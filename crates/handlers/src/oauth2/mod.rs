@@ -9,24 +9,29 @@ use std::collections::HashMap;
 use chrono::Duration;
 use mas_data_model::{
     AccessToken, Authentication, AuthorizationGrant, BrowserSession, Client, Clock, RefreshToken,
-    Session, TokenType,
+    Session, SiteConfig, TokenType, User,
 };
 use mas_iana::jose::JsonWebSignatureAlg;
 use mas_jose::{
-    claims::{self, hash_token},
+    claims::{self, Claim, hash_token},
     constraints::Constrainable,
     jwt::{JsonWebSignatureHeader, Jwt},
 };
 use mas_keystore::Keystore;
 use mas_router::UrlBuilder;
 use mas_storage::RepositoryAccess;
+use oauth2_types::requests::Confirmation;
 use thiserror::Error;
+use ulid::Ulid;
 
 pub mod authorization;
+pub mod client_configuration;
 pub mod device;
 pub mod discovery;
+pub mod end_session;
 pub mod introspection;
 pub mod keys;
+pub mod par;
 pub mod registration;
 pub mod revoke;
 pub mod token;
@@ -62,6 +67,8 @@ pub(crate) fn generate_id_token(
     claims::AUD.insert(&mut claims, client.client_id.clone())?;
     claims::IAT.insert(&mut claims, now)?;
     claims::EXP.insert(&mut claims, now + Duration::try_hours(1).unwrap())?;
+    // Identifies the browser session in back-channel logout tokens
+    claims::SID.insert(&mut claims, browser_session.id.to_string())?;
 
     if let Some(nonce) = grant.and_then(|grant| grant.nonce.as_ref()) {
         claims::NONCE.insert(&mut claims, nonce)?;
@@ -69,6 +76,12 @@ pub(crate) fn generate_id_token(
 
     if let Some(last_authentication) = last_authentication {
         claims::AUTH_TIME.insert(&mut claims, last_authentication.created_at)?;
+
+        let amr = last_authentication.authentication_method.amr();
+        if !amr.is_empty() {
+            let amr: Vec<String> = amr.iter().map(|&value| value.to_owned()).collect();
+            claims::AMR.insert(&mut claims, amr)?;
+        }
     }
 
     let alg = client
@@ -95,19 +108,103 @@ pub(crate) fn generate_id_token(
     Ok(id_token.into_string())
 }
 
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub(crate) enum AccessTokenSignatureError {
+    #[error("The signing key is invalid")]
+    InvalidSigningKey,
+    Claim(#[from] mas_jose::claims::ClaimError),
+    JwtSignature(#[from] mas_jose::jwt::JwtSignatureError),
+    WrongAlgorithm(#[from] mas_keystore::WrongAlgorithmError),
+}
+
+const CLIENT_ID: Claim<String> = Claim::new("client_id");
+const SCOPE: Claim<String> = Claim::new("scope");
+const CNF: Claim<Confirmation> = Claim::new("cnf");
+
+/// Generate the access token for a session.
+///
+/// If the client opted into JWT access tokens, this is a JWT as described in
+/// [RFC 9068], otherwise an opaque token. In both cases, the token is stored in
+/// the database, so it can be introspected and revoked like any other token.
+///
+/// The audience of the JWT is the resource server the token is meant for,
+/// which is the homeserver, identified by its server name.
+///
+/// [RFC 9068]: https://www.rfc-editor.org/rfc/rfc9068
+pub(crate) fn generate_access_token(
+    rng: &mut (impl rand::RngCore + rand::CryptoRng),
+    clock: &impl Clock,
+    url_builder: &UrlBuilder,
+    key_store: &Keystore,
+    client: &Client,
+    session: &Session,
+    user: Option<&User>,
+    site_config: &SiteConfig,
+    dpop_jkt: Option<&str>,
+) -> Result<String, AccessTokenSignatureError> {
+    let Some(alg) = &client.access_token_signed_response_alg else {
+        return Ok(TokenType::AccessToken.generate(rng));
+    };
+
+    let mut claims = HashMap::new();
+    let now = clock.now();
+    let jti = Ulid::from_datetime_with_source(now.into(), rng);
+    claims::ISS.insert(&mut claims, url_builder.oidc_issuer().to_string())?;
+    // Sessions from the client credentials grant don't have a user, in which case
+    // the subject is the client itself
+    let sub = user.map_or_else(|| client.client_id.clone(), |user| user.sub.clone());
+    claims::SUB.insert(&mut claims, sub)?;
+    claims::AUD.insert(&mut claims, site_config.server_name.clone())?;
+    CLIENT_ID.insert(&mut claims, client.client_id.clone())?;
+    claims::IAT.insert(&mut claims, now)?;
+    claims::EXP.insert(&mut claims, now + site_config.access_token_ttl)?;
+    claims::JTI.insert(&mut claims, jti.to_string())?;
+    SCOPE.insert(&mut claims, session.scope.to_string())?;
+
+    // Same as in the ID token, this identifies the browser session
+    if let Some(user_session_id) = session.user_session_id {
+        claims::SID.insert(&mut claims, user_session_id.to_string())?;
+    }
+
+    if let Some(jkt) = dpop_jkt {
+        CNF.insert(
+            &mut claims,
+            Confirmation {
+                jkt: Some(jkt.to_owned()),
+            },
+        )?;
+    }
+
+    let key = key_store
+        .signing_key_for_algorithm(alg)
+        .ok_or(AccessTokenSignatureError::InvalidSigningKey)?;
+    let signer = key.params().signing_key_for_alg(alg)?;
+    let header = JsonWebSignatureHeader::new(alg.clone())
+        .with_kid(
+            key.kid()
+                .ok_or(AccessTokenSignatureError::InvalidSigningKey)?,
+        )
+        .with_typ("at+jwt".to_owned());
+    let access_token = Jwt::sign_with_rng(rng, header, claims, &signer)?;
+
+    Ok(access_token.into_string())
+}
+
 pub(crate) async fn generate_token_pair<R: RepositoryAccess>(
     rng: &mut (impl rand::RngCore + Send),
     clock: &impl Clock,
     repo: &mut R,
     session: &Session,
+    access_token_str: String,
     ttl: Duration,
+    dpop_jkt: Option<String>,
 ) -> Result<(AccessToken, RefreshToken), R::Error> {
-    let access_token_str = TokenType::AccessToken.generate(rng);
     let refresh_token_str = TokenType::RefreshToken.generate(rng);
 
     let access_token = repo
         .oauth2_access_token()
-        .add(rng, clock, session, access_token_str, Some(ttl))
+        .add(rng, clock, session, access_token_str, Some(ttl), dpop_jkt)
         .await?;
 
     let refresh_token = repo
@@ -117,3 +214,18 @@ pub(crate) async fn generate_token_pair<R: RepositoryAccess>(
 
     Ok((access_token, refresh_token))
 }
+
+/// Whether the browser session was last authenticated with a second factor,
+/// which the policy engine can require for some grants
+pub(crate) async fn is_mfa_authenticated<R: RepositoryAccess>(
+    repo: &mut R,
+    browser_session: &BrowserSession,
+) -> Result<bool, R::Error> {
+    let authentication = repo
+        .browser_session()
+        .get_last_authentication(browser_session)
+        .await?;
+
+    Ok(authentication
+        .is_some_and(|authentication| authentication.authentication_method.is_multi_factor()))
+}
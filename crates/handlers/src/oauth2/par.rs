@@ -0,0 +1,329 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Handler for the [Pushed Authorization Request Endpoint]
+//!
+//! [Pushed Authorization Request Endpoint]: https://www.rfc-editor.org/rfc/rfc9126.html
+
+use std::collections::BTreeMap;
+
+use axum::{Json, extract::State, response::IntoResponse};
+use axum_extra::typed_header::TypedHeader;
+use headers::{CacheControl, Pragma};
+use hyper::StatusCode;
+use mas_axum_utils::{
+    client_authorization::{ClientAuthorization, CredentialsVerificationError},
+    record_error,
+};
+use mas_data_model::{BoxClock, BoxRng, PushedAuthorizationRequest};
+use mas_keystore::Encrypter;
+use mas_router::UrlBuilder;
+use mas_storage::BoxRepository;
+use oauth2_types::{
+    errors::{ClientError, ClientErrorCode},
+    requests::PushedAuthorizationResponse,
+};
+use rand::distributions::{Alphanumeric, DistString};
+use thiserror::Error;
+use ulid::Ulid;
+
+use super::authorization::{
+    Params,
+    request_object::{self, RequestObjectError},
+};
+use crate::impl_from_error_for_route;
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error("client not found")]
+    ClientNotFound,
+
+    #[error("client {0} is not allowed to push authorization requests")]
+    ClientNotAllowed(Ulid),
+
+    #[error("invalid client credentials for client {client_id}")]
+    InvalidClientCredentials {
+        client_id: Ulid,
+        #[source]
+        source: CredentialsVerificationError,
+    },
+
+    #[error("could not verify client credentials for client {client_id}")]
+    ClientCredentialsVerification {
+        client_id: Ulid,
+        #[source]
+        source: CredentialsVerificationError,
+    },
+
+    #[error("the request_uri parameter can't be pushed")]
+    RequestUriPushed,
+
+    #[error("invalid authorization request parameters")]
+    InvalidParameters(#[source] serde_urlencoded::de::Error),
+
+    #[error("invalid request object")]
+    InvalidRequestObject(#[from] RequestObjectError),
+
+    #[error("invalid redirect uri")]
+    UnknownRedirectUri(#[from] mas_data_model::InvalidRedirectUriError),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        let sentry_event_id = record_error!(self, Self::Internal(_));
+
+        let response = match self {
+            Self::Internal(_) | Self::ClientCredentialsVerification { .. } => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ClientError::from(ClientErrorCode::ServerError)),
+            ),
+            Self::ClientNotFound | Self::InvalidClientCredentials { .. } => (
+                StatusCode::UNAUTHORIZED,
+                Json(ClientError::from(ClientErrorCode::InvalidClient)),
+            ),
+            Self::ClientNotAllowed(_) => (
+                StatusCode::UNAUTHORIZED,
+                Json(ClientError::from(ClientErrorCode::UnauthorizedClient)),
+            ),
+            Self::RequestUriPushed | Self::InvalidParameters(_) | Self::UnknownRedirectUri(_) => (
+                StatusCode::BAD_REQUEST,
+                Json(ClientError::from(ClientErrorCode::InvalidRequest)),
+            ),
+            Self::InvalidRequestObject(ref e) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequestObject)
+                        .with_description(e.to_string()),
+                ),
+            ),
+        };
+
+        (sentry_event_id, response).into_response()
+    }
+}
+
+#[tracing::instrument(
+    name = "handlers.oauth2.par.post",
+    fields(client.id = client_authorization.client_id()),
+    skip_all,
+)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    mut repo: BoxRepository,
+    State(http_client): State<reqwest::Client>,
+    State(encrypter): State<Encrypter>,
+    State(url_builder): State<UrlBuilder>,
+    client_authorization: ClientAuthorization<BTreeMap<String, String>>,
+) -> Result<impl IntoResponse, RouteError> {
+    let client = client_authorization
+        .credentials
+        .fetch(&mut repo)
+        .await?
+        .ok_or(RouteError::ClientNotFound)?;
+
+    // Reuse the token endpoint auth method to verify the client
+    let method = client
+        .token_endpoint_auth_method
+        .as_ref()
+        .ok_or(RouteError::ClientNotAllowed(client.id))?;
+
+    client_authorization
+        .credentials
+        .verify(&http_client, &encrypter, method, &client)
+        .await
+        .map_err(|err| {
+            if err.is_internal() {
+                RouteError::ClientCredentialsVerification {
+                    client_id: client.id,
+                    source: err,
+                }
+            } else {
+                RouteError::InvalidClientCredentials {
+                    client_id: client.id,
+                    source: err,
+                }
+            }
+        })?;
+
+    let mut parameters = client_authorization.form.unwrap_or_default();
+
+    // A pushed request can't itself reference another request
+    if parameters.contains_key("request_uri") {
+        return Err(RouteError::RequestUriPushed);
+    }
+
+    // The client_id was consumed by the client authentication, put it back so
+    // that the stored request is a complete authorization request
+    parameters.insert("client_id".to_owned(), client.client_id.clone());
+
+    // Validate the request early, so that the client gets the error here instead
+    // of the user in the browser. The request object is kept as-is in the stored
+    // request, and verified again when the request is used.
+    let merged = request_object::merge(
+        &http_client,
+        &clock,
+        url_builder.oidc_issuer().as_str(),
+        &client,
+        parameters.clone(),
+    )
+    .await?;
+    let params = Params::from_parameters(&merged).map_err(RouteError::InvalidParameters)?;
+    client.resolve_redirect_uri(&params.auth.redirect_uri)?;
+
+    let request_uri = format!(
+        "{}{}",
+        PushedAuthorizationRequest::REQUEST_URI_PREFIX,
+        Alphanumeric.sample_string(&mut rng, 32)
+    );
+
+    let request = repo
+        .oauth2_pushed_authorization_request()
+        .add(&mut rng, &clock, &client, request_uri, parameters)
+        .await?;
+
+    repo.save().await?;
+
+    let response = PushedAuthorizationResponse {
+        request_uri: request.request_uri,
+        expires_in: PushedAuthorizationRequest::VALIDITY,
+    };
+
+    Ok((
+        StatusCode::CREATED,
+        TypedHeader(CacheControl::new().with_no_store()),
+        TypedHeader(Pragma::no_cache()),
+        Json(response),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{Request, StatusCode};
+    use mas_router::SimpleRoute;
+    use oauth2_types::{
+        errors::{ClientError, ClientErrorCode},
+        registration::ClientRegistrationResponse,
+        requests::PushedAuthorizationResponse,
+    };
+    use sqlx::PgPool;
+
+    use crate::test_utils::{RequestBuilderExt, ResponseExt, TestState, setup};
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_pushed_authorization_request(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "require_pushed_authorization_requests": true,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+
+        // The client can't skip the pushed authorization request
+        let request = Request::get(format!(
+            "{}?client_id={client_id}&response_type=code&scope=openid&redirect_uri=https://example.com/callback",
+            mas_router::OAuth2AuthorizationEndpoint::PATH,
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(hyper::header::LOCATION).unwrap();
+        assert!(
+            location
+                .to_str()
+                .unwrap()
+                .starts_with("https://example.com/callback?error=invalid_request")
+        );
+
+        // Pushing a request with an unknown redirect_uri fails
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "response_type": "code",
+                "scope": "openid",
+                "redirect_uri": "https://example.org/callback",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let response: ClientError = response.json();
+        assert_eq!(response.error, ClientErrorCode::InvalidRequest);
+
+        // Pushing a request_uri is not allowed
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "request_uri": "urn:ietf:params:oauth:request_uri:abcdef",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Push a valid request
+        let request = Request::post(mas_router::OAuth2PushedAuthorizationRequestEndpoint::PATH)
+            .form(serde_json::json!({
+                "client_id": client_id,
+                "response_type": "code",
+                "scope": "openid",
+                "redirect_uri": "https://example.com/callback",
+                "state": "abcdef",
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+        let response: PushedAuthorizationResponse = response.json();
+        assert!(
+            response
+                .request_uri
+                .starts_with("urn:ietf:params:oauth:request_uri:")
+        );
+        let request_uri = response.request_uri;
+
+        // Another client can't use it
+        let request = Request::get(format!(
+            "{}?client_id=some-other-client&request_uri={request_uri}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH,
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+
+        // Use it to start an authorization request, which redirects to the login
+        // page
+        let request = Request::get(format!(
+            "{}?client_id={client_id}&request_uri={request_uri}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH,
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        let location = response.headers().get(hyper::header::LOCATION).unwrap();
+        assert!(location.to_str().unwrap().starts_with("/login"));
+
+        // It can only be used once
+        let request = Request::get(format!(
+            "{}?client_id={client_id}&request_uri={request_uri}",
+            mas_router::OAuth2AuthorizationEndpoint::PATH,
+        ))
+        .empty();
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
@@ -14,6 +14,7 @@ use mas_data_model::{BoxClock, BoxRng};
 use mas_iana::oauth::OAuthClientAuthenticationMethod;
 use mas_keystore::Encrypter;
 use mas_policy::{EvaluationResult, Policy};
+use mas_router::UrlBuilder;
 use mas_storage::{BoxRepository, oauth2::OAuth2ClientRepository};
 use oauth2_types::{
     errors::{ClientError, ClientErrorCode},
@@ -213,6 +214,54 @@ fn localised_url_has_public_suffix(url: &Localized<Url>) -> bool {
     url.iter().any(|(_lang, url)| host_is_public_suffix(url))
 }
 
+/// Find the first field of the client metadata which has a URL using a public
+/// suffix as host, if any
+pub(crate) fn public_suffix_field(metadata: &VerifiedClientMetadata) -> Option<&'static str> {
+    let localized = [
+        ("client_uri", &metadata.client_uri),
+        ("logo_uri", &metadata.logo_uri),
+        ("policy_uri", &metadata.policy_uri),
+        ("tos_uri", &metadata.tos_uri),
+    ];
+
+    for (field, url) in localized {
+        if url.as_ref().is_some_and(localised_url_has_public_suffix) {
+            return Some(field);
+        }
+    }
+
+    if metadata
+        .initiate_login_uri
+        .as_ref()
+        .is_some_and(host_is_public_suffix)
+    {
+        return Some("initiate_login_uri");
+    }
+
+    if metadata.redirect_uris().iter().any(host_is_public_suffix) {
+        return Some("redirect_uri");
+    }
+
+    if metadata
+        .post_logout_redirect_uris
+        .iter()
+        .flatten()
+        .any(host_is_public_suffix)
+    {
+        return Some("post_logout_redirect_uri");
+    }
+
+    if metadata
+        .backchannel_logout_uri
+        .as_ref()
+        .is_some_and(host_is_public_suffix)
+    {
+        return Some("backchannel_logout_uri");
+    }
+
+    None
+}
+
 #[tracing::instrument(name = "handlers.oauth2.registration.post", skip_all)]
 pub(crate) async fn post(
     mut rng: BoxRng,
@@ -222,6 +271,7 @@ pub(crate) async fn post(
     activity_tracker: BoundActivityTracker,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
     State(encrypter): State<Encrypter>,
+    State(url_builder): State<UrlBuilder>,
     body: Result<Json<ClientMetadata>, axum::extract::rejection::JsonRejection>,
 ) -> Result<impl IntoResponse, RouteError> {
     // Propagate any JSON extraction error
@@ -242,40 +292,8 @@ pub(crate) async fn post(
 
     // Some extra validation that is hard to do in OPA and not done by the
     // `validate` method either
-    if let Some(client_uri) = &metadata.client_uri
-        && localised_url_has_public_suffix(client_uri)
-    {
-        return Err(RouteError::UrlIsPublicSuffix("client_uri"));
-    }
-
-    if let Some(logo_uri) = &metadata.logo_uri
-        && localised_url_has_public_suffix(logo_uri)
-    {
-        return Err(RouteError::UrlIsPublicSuffix("logo_uri"));
-    }
-
-    if let Some(policy_uri) = &metadata.policy_uri
-        && localised_url_has_public_suffix(policy_uri)
-    {
-        return Err(RouteError::UrlIsPublicSuffix("policy_uri"));
-    }
-
-    if let Some(tos_uri) = &metadata.tos_uri
-        && localised_url_has_public_suffix(tos_uri)
-    {
-        return Err(RouteError::UrlIsPublicSuffix("tos_uri"));
-    }
-
-    if let Some(initiate_login_uri) = &metadata.initiate_login_uri
-        && host_is_public_suffix(initiate_login_uri)
-    {
-        return Err(RouteError::UrlIsPublicSuffix("initiate_login_uri"));
-    }
-
-    for redirect_uri in metadata.redirect_uris() {
-        if host_is_public_suffix(redirect_uri) {
-            return Err(RouteError::UrlIsPublicSuffix("redirect_uri"));
-        }
+    if let Some(field) = public_suffix_field(&metadata) {
+        return Err(RouteError::UrlIsPublicSuffix(field));
     }
 
     let res = policy
@@ -322,10 +340,12 @@ pub(crate) async fn post(
         (None, None)
     };
 
-    let client = if let Some(client) = existing_client {
+    // Reused clients don't get a registration access token, as they may be shared
+    // by multiple registrations
+    let (client, registration_access_token) = if let Some(client) = existing_client {
         tracing::info!(%client.id, "Reusing existing client");
         REGISTRATION_COUNTER.add(1, &[KeyValue::new(RESULT, "reused")]);
-        client
+        (client, None)
     } else {
         let client = repo
             .oauth2_client()
@@ -354,19 +374,40 @@ pub(crate) async fn post(
                 metadata.token_endpoint_auth_method.clone(),
                 metadata.token_endpoint_auth_signing_alg.clone(),
                 metadata.initiate_login_uri.clone(),
+                metadata
+                    .post_logout_redirect_uris
+                    .clone()
+                    .unwrap_or_default(),
+                metadata.backchannel_logout_uri.clone(),
+                metadata.backchannel_logout_session_required(),
+                metadata.require_pushed_authorization_requests(),
+                metadata.require_signed_request_object(),
+                metadata.access_token_signed_response_alg.clone(),
             )
             .await?;
+
+        let registration_access_token = Alphanumeric.sample_string(&mut rng, 32);
+        repo.oauth2_client()
+            .set_registration_access_token(&client, Some(registration_access_token.clone()))
+            .await?;
+
         tracing::info!(%client.id, "Registered new client");
         REGISTRATION_COUNTER.add(1, &[KeyValue::new(RESULT, "created")]);
-        client
+        (client, Some(registration_access_token))
     };
 
+    let registration_client_uri = registration_access_token
+        .is_some()
+        .then(|| url_builder.oauth_client_configuration_endpoint(client.id));
+
     let response = ClientRegistrationResponse {
         client_id: client.client_id.clone(),
         client_secret,
         // XXX: we should have a `created_at` field on the clients
         client_id_issued_at: Some(client.id.datetime().into()),
         client_secret_expires_at: None,
+        registration_access_token,
+        registration_client_uri,
     };
 
     // We round-trip back to the metadata to output it in the response
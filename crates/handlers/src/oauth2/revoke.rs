@@ -331,7 +331,9 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                TokenType::AccessToken.generate(&mut state.rng()),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
+                None,
             )
             .await
             .unwrap();
@@ -398,7 +400,9 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                TokenType::AccessToken.generate(&mut state.rng()),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
+                None,
             )
             .await
             .unwrap();
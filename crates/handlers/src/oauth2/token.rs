@@ -10,16 +10,18 @@ use axum::{Json, extract::State, response::IntoResponse};
 use axum_extra::typed_header::TypedHeader;
 use chrono::Duration;
 use headers::{CacheControl, HeaderMap, HeaderMapExt, Pragma};
-use hyper::StatusCode;
+use hyper::{Method, StatusCode};
 use mas_axum_utils::{
     client_authorization::{ClientAuthorization, CredentialsVerificationError},
+    dpop::{DPoPError, DPoPProof, DPoPReplayCache},
     record_error,
 };
 use mas_data_model::{
-    AuthorizationGrantStage, BoxClock, BoxRng, Client, Clock, Device, DeviceCodeGrantState,
-    SiteConfig, TokenType,
+    AccessToken, AuthorizationGrantStage, BoxClock, BoxRng, Client, Clock, Device,
+    DeviceCodeGrantState, SiteConfig, TokenType,
 };
 use mas_i18n::DataLocale;
+use mas_iana::oauth::OAuthAccessTokenType;
 use mas_keystore::{Encrypter, Keystore};
 use mas_matrix::HomeserverConnection;
 use mas_oidc_client::types::scope::ScopeToken;
@@ -28,10 +30,10 @@ use mas_router::UrlBuilder;
 use mas_storage::{
     BoxRepository, RepositoryAccess,
     oauth2::{
-        OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository,
+        OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
         OAuth2RefreshTokenRepository, OAuth2SessionRepository,
     },
-    user::BrowserSessionRepository,
+    user::{BrowserSessionRepository, UserRepository},
 };
 use mas_templates::{DeviceNameContext, TemplateContext, Templates};
 use oauth2_types::{
@@ -39,7 +41,7 @@ use oauth2_types::{
     pkce::CodeChallengeError,
     requests::{
         AccessTokenRequest, AccessTokenResponse, AuthorizationCodeGrant, ClientCredentialsGrant,
-        DeviceCodeGrant, GrantType, RefreshTokenGrant,
+        DeviceCodeGrant, GrantType, RefreshTokenGrant, TokenExchangeGrant, TokenTypeIdentifier,
     },
     scope,
 };
@@ -48,7 +50,7 @@ use thiserror::Error;
 use tracing::{debug, info, warn};
 use ulid::Ulid;
 
-use super::{generate_id_token, generate_token_pair};
+use super::{generate_access_token, generate_id_token, generate_token_pair};
 use crate::{BoundActivityTracker, METER, impl_from_error_for_route};
 
 static TOKEN_REQUEST_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
@@ -128,6 +130,12 @@ pub(crate) enum RouteError {
     #[error("failed to load oauth session {0}")]
     NoSuchOAuthSession(Ulid),
 
+    #[error("failed to load oauth client {0}")]
+    NoSuchOAuthClient(Ulid),
+
+    #[error("failed to load user {0}")]
+    NoSuchUser(Ulid),
+
     #[error(
         "failed to load the next refresh token ({next:?}) from the previous one ({previous:?})"
     )]
@@ -158,6 +166,27 @@ pub(crate) enum RouteError {
 
     #[error("failed to provision device")]
     ProvisionDeviceFailed(#[source] anyhow::Error),
+
+    #[error("invalid DPoP proof")]
+    InvalidDPoPProof(#[source] DPoPError),
+
+    #[error("subject token is invalid")]
+    InvalidSubjectToken,
+
+    #[error("subject token belongs to an impersonation session")]
+    ImpersonationSubjectToken,
+
+    #[error("unsupported subject token type {0}")]
+    UnsupportedSubjectTokenType(TokenTypeIdentifier),
+
+    #[error("unsupported requested token type {0}")]
+    UnsupportedRequestedTokenType(TokenTypeIdentifier),
+
+    #[error("actor tokens are not supported")]
+    ActorTokenNotSupported,
+
+    #[error("requested scope was not granted to the subject token")]
+    ScopeNotGranted,
 }
 
 impl IntoResponse for RouteError {
@@ -168,6 +197,8 @@ impl IntoResponse for RouteError {
                 | Self::ClientCredentialsVerification { .. }
                 | Self::NoSuchBrowserSession(_)
                 | Self::NoSuchOAuthSession(_)
+                | Self::NoSuchOAuthClient(_)
+                | Self::NoSuchUser(_)
                 | Self::ProvisionDeviceFailed(_)
                 | Self::NoSuchNextRefreshToken { .. }
                 | Self::NoSuchNextAccessToken { .. }
@@ -181,6 +212,8 @@ impl IntoResponse for RouteError {
             | Self::ClientCredentialsVerification { .. }
             | Self::NoSuchBrowserSession(_)
             | Self::NoSuchOAuthSession(_)
+            | Self::NoSuchOAuthClient(_)
+            | Self::NoSuchUser(_)
             | Self::ProvisionDeviceFailed(_)
             | Self::NoSuchNextRefreshToken { .. }
             | Self::NoSuchNextAccessToken { .. }
@@ -258,6 +291,35 @@ impl IntoResponse for RouteError {
                 StatusCode::BAD_REQUEST,
                 Json(ClientError::from(ClientErrorCode::UnsupportedGrantType)),
             ),
+
+            Self::InvalidDPoPProof(err) => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidDpopProof)
+                        .with_description(err.to_string()),
+                ),
+            ),
+
+            // As per https://www.rfc-editor.org/rfc/rfc8693#section-2.2.2
+            Self::InvalidSubjectToken
+            | Self::ImpersonationSubjectToken
+            | Self::UnsupportedSubjectTokenType(_)
+            | Self::UnsupportedRequestedTokenType(_)
+            | Self::ActorTokenNotSupported => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidRequest)
+                        .with_description(self.to_string()),
+                ),
+            ),
+
+            Self::ScopeNotGranted => (
+                StatusCode::BAD_REQUEST,
+                Json(
+                    ClientError::from(ClientErrorCode::InvalidScope)
+                        .with_description(self.to_string()),
+                ),
+            ),
         };
 
         (sentry_event_id, response).into_response()
@@ -269,6 +331,7 @@ impl_from_error_for_route!(mas_templates::TemplateError);
 impl_from_error_for_route!(mas_storage::RepositoryError);
 impl_from_error_for_route!(mas_policy::EvaluationError);
 impl_from_error_for_route!(super::IdTokenSignatureError);
+impl_from_error_for_route!(super::AccessTokenSignatureError);
 
 #[tracing::instrument(
     name = "handlers.oauth2.token.post",
@@ -289,9 +352,30 @@ pub(crate) async fn post(
     State(templates): State<Templates>,
     policy: Policy,
     user_agent: Option<TypedHeader<headers::UserAgent>>,
+    State(dpop_replay_cache): State<DPoPReplayCache>,
+    dpop_proof: Result<Option<DPoPProof>, DPoPError>,
     client_authorization: ClientAuthorization<AccessTokenRequest>,
 ) -> Result<impl IntoResponse, RouteError> {
     let user_agent = user_agent.map(|ua| ua.as_str().to_owned());
+
+    // If the client sent a DPoP proof, the tokens we issue will be bound to the key
+    // which signed it
+    let dpop_jkt = dpop_proof
+        .and_then(|proof| {
+            proof
+                .map(|proof| {
+                    proof.verify(
+                        &Method::POST,
+                        &url_builder.oauth_token_endpoint(),
+                        None,
+                        clock.now(),
+                        &dpop_replay_cache,
+                    )
+                })
+                .transpose()
+        })
+        .map_err(RouteError::InvalidDPoPProof)?;
+
     let client = client_authorization
         .credentials
         .fetch(&mut repo)
@@ -342,6 +426,7 @@ pub(crate) async fn post(
                 &homeserver,
                 &templates,
                 user_agent,
+                dpop_jkt,
             )
             .await?
         }
@@ -352,9 +437,12 @@ pub(crate) async fn post(
                 &activity_tracker,
                 &grant,
                 &client,
+                &key_store,
+                &url_builder,
                 &site_config,
                 repo,
                 user_agent,
+                dpop_jkt,
             )
             .await?
         }
@@ -365,10 +453,13 @@ pub(crate) async fn post(
                 &activity_tracker,
                 &grant,
                 &client,
+                &key_store,
+                &url_builder,
                 &site_config,
                 repo,
                 policy,
                 user_agent,
+                dpop_jkt,
             )
             .await?
         }
@@ -385,6 +476,24 @@ pub(crate) async fn post(
                 repo,
                 &homeserver,
                 user_agent,
+                dpop_jkt,
+            )
+            .await?
+        }
+        AccessTokenRequest::TokenExchange(grant) => {
+            token_exchange_grant(
+                &mut rng,
+                &clock,
+                &activity_tracker,
+                &grant,
+                &client,
+                &key_store,
+                &url_builder,
+                &site_config,
+                repo,
+                policy,
+                user_agent,
+                dpop_jkt,
             )
             .await?
         }
@@ -410,6 +519,30 @@ pub(crate) async fn post(
     Ok((headers, Json(reply)))
 }
 
+/// The type of the access token to advertise in the token response, depending
+/// on whether it is bound to a DPoP key
+fn token_type_for(access_token: &AccessToken) -> OAuthAccessTokenType {
+    if access_token.dpop_jkt.is_some() {
+        OAuthAccessTokenType::DPoP
+    } else {
+        OAuthAccessTokenType::Bearer
+    }
+}
+
+/// Check that a token bound to a DPoP key is presented along with a proof
+/// signed by the same key
+fn verify_dpop_binding(bound_jkt: Option<&str>, dpop_jkt: Option<&str>) -> Result<(), RouteError> {
+    let Some(bound_jkt) = bound_jkt else {
+        return Ok(());
+    };
+
+    match dpop_jkt {
+        None => Err(RouteError::InvalidDPoPProof(DPoPError::MissingProof)),
+        Some(jkt) if jkt != bound_jkt => Err(RouteError::InvalidDPoPProof(DPoPError::KeyMismatch)),
+        Some(_) => Ok(()),
+    }
+}
+
 async fn authorization_code_grant(
     mut rng: &mut BoxRng,
     clock: &impl Clock,
@@ -423,6 +556,7 @@ async fn authorization_code_grant(
     homeserver: &Arc<dyn HomeserverConnection>,
     templates: &Templates,
     user_agent: Option<String>,
+    dpop_jkt: Option<String>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::AuthorizationCode) {
@@ -541,8 +675,27 @@ async fn authorization_code_grant(
         .await?;
 
     let ttl = site_config.access_token_ttl;
-    let (access_token, refresh_token) =
-        generate_token_pair(&mut rng, clock, &mut repo, &session, ttl).await?;
+    let access_token_str = generate_access_token(
+        &mut rng,
+        clock,
+        url_builder,
+        key_store,
+        client,
+        &session,
+        Some(&browser_session.user),
+        site_config,
+        dpop_jkt.as_deref(),
+    )?;
+    let (access_token, refresh_token) = generate_token_pair(
+        &mut rng,
+        clock,
+        &mut repo,
+        &session,
+        access_token_str,
+        ttl,
+        dpop_jkt,
+    )
+    .await?;
 
     let id_token = if session.scope.contains(&scope::OPENID) {
         Some(generate_id_token(
@@ -560,7 +713,8 @@ async fn authorization_code_grant(
         None
     };
 
-    let mut params = AccessTokenResponse::new(access_token.access_token)
+    let mut params = AccessTokenResponse::new(access_token.access_token.clone())
+        .with_token_type(token_type_for(&access_token))
         .with_expires_in(ttl)
         .with_refresh_token(refresh_token.refresh_token)
         .with_scope(session.scope.clone());
@@ -608,9 +762,12 @@ async fn refresh_token_grant(
     activity_tracker: &BoundActivityTracker,
     grant: &RefreshTokenGrant,
     client: &Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
     site_config: &SiteConfig,
     mut repo: BoxRepository,
     user_agent: Option<String>,
+    dpop_jkt: Option<String>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::RefreshToken) {
@@ -650,6 +807,10 @@ async fn refresh_token_grant(
         });
     }
 
+    // If the refresh token is bound to a DPoP key, the request must have a proof
+    // signed with the same key
+    verify_dpop_binding(refresh_token.dpop_jkt.as_deref(), dpop_jkt.as_deref())?;
+
     if !refresh_token.is_valid() {
         // We're seing a refresh token that already has been consumed, this might be a
         // double-refresh or a replay attack
@@ -725,9 +886,39 @@ async fn refresh_token_grant(
         .record_oauth2_session(clock, &session)
         .await;
 
+    // The user is only needed for the subject of JWT access tokens
+    let user = match session.user_id {
+        Some(user_id) if client.access_token_signed_response_alg.is_some() => Some(
+            repo.user()
+                .lookup(user_id)
+                .await?
+                .ok_or(RouteError::NoSuchUser(user_id))?,
+        ),
+        _ => None,
+    };
+
     let ttl = site_config.access_token_ttl;
-    let (new_access_token, new_refresh_token) =
-        generate_token_pair(rng, clock, &mut repo, &session, ttl).await?;
+    let access_token_str = generate_access_token(
+        rng,
+        clock,
+        url_builder,
+        key_store,
+        client,
+        &session,
+        user.as_ref(),
+        site_config,
+        dpop_jkt.as_deref(),
+    )?;
+    let (new_access_token, new_refresh_token) = generate_token_pair(
+        rng,
+        clock,
+        &mut repo,
+        &session,
+        access_token_str,
+        ttl,
+        dpop_jkt,
+    )
+    .await?;
 
     let refresh_token = repo
         .oauth2_refresh_token()
@@ -746,7 +937,8 @@ async fn refresh_token_grant(
         }
     }
 
-    let params = AccessTokenResponse::new(new_access_token.access_token)
+    let params = AccessTokenResponse::new(new_access_token.access_token.clone())
+        .with_token_type(token_type_for(&new_access_token))
         .with_expires_in(ttl)
         .with_refresh_token(new_refresh_token.refresh_token)
         .with_scope(session.scope);
@@ -760,10 +952,13 @@ async fn client_credentials_grant(
     activity_tracker: &BoundActivityTracker,
     grant: &ClientCredentialsGrant,
     client: &Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
     site_config: &SiteConfig,
     mut repo: BoxRepository,
     mut policy: Policy,
     user_agent: Option<String>,
+    dpop_jkt: Option<String>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::ClientCredentials) {
@@ -780,9 +975,11 @@ async fn client_credentials_grant(
     let res = policy
         .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
             user: None,
+            mfa_authenticated: false,
             client,
             scope: &scope,
             grant_type: mas_policy::GrantType::ClientCredentials,
+            subject: None,
             requester: mas_policy::Requester {
                 ip_address: activity_tracker.ip(),
                 user_agent: user_agent.clone(),
@@ -807,14 +1004,246 @@ async fn client_credentials_grant(
     }
 
     let ttl = site_config.access_token_ttl;
-    let access_token_str = TokenType::AccessToken.generate(rng);
+    let access_token_str = generate_access_token(
+        rng,
+        clock,
+        url_builder,
+        key_store,
+        client,
+        &session,
+        None,
+        site_config,
+        dpop_jkt.as_deref(),
+    )?;
+
+    let access_token = repo
+        .oauth2_access_token()
+        .add(rng, clock, &session, access_token_str, Some(ttl), dpop_jkt)
+        .await?;
+
+    let mut params = AccessTokenResponse::new(access_token.access_token.clone())
+        .with_token_type(token_type_for(&access_token))
+        .with_expires_in(ttl);
+
+    // XXX: there is a potential (but unlikely) race here, where the activity for
+    // the session is recorded before the transaction is committed. We would have to
+    // save the repository here to fix that.
+    activity_tracker
+        .record_oauth2_session(clock, &session)
+        .await;
+
+    if !session.scope.is_empty() {
+        // We only return the scope if it's not empty
+        params = params.with_scope(session.scope);
+    }
+
+    Ok((params, repo))
+}
+
+async fn token_exchange_grant(
+    rng: &mut BoxRng,
+    clock: &impl Clock,
+    activity_tracker: &BoundActivityTracker,
+    grant: &TokenExchangeGrant,
+    client: &Client,
+    key_store: &Keystore,
+    url_builder: &UrlBuilder,
+    site_config: &SiteConfig,
+    mut repo: BoxRepository,
+    mut policy: Policy,
+    user_agent: Option<String>,
+    dpop_jkt: Option<String>,
+) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
+    // Check that the client is allowed to use this grant type
+    if !client.grant_types.contains(&GrantType::TokenExchange) {
+        return Err(RouteError::UnauthorizedClient(client.id));
+    }
+
+    // We only support impersonation, not delegation
+    if grant.actor_token.is_some() || grant.actor_token_type.is_some() {
+        return Err(RouteError::ActorTokenNotSupported);
+    }
+
+    // We always issue an access token, and a refresh token alongside it only if
+    // the client explicitly asks for one
+    let with_refresh_token = match &grant.requested_token_type {
+        None | Some(TokenTypeIdentifier::AccessToken) => false,
+        Some(TokenTypeIdentifier::RefreshToken)
+            if client.grant_types.contains(&GrantType::RefreshToken) =>
+        {
+            true
+        }
+        Some(requested_token_type) => {
+            return Err(RouteError::UnsupportedRequestedTokenType(
+                requested_token_type.clone(),
+            ));
+        }
+    };
+
+    // Find the session behind the subject token
+    let (subject_session_id, subject_jkt) = match &grant.subject_token_type {
+        TokenTypeIdentifier::AccessToken => {
+            let token = repo
+                .oauth2_access_token()
+                .find_by_token(&grant.subject_token)
+                .await?
+                .ok_or(RouteError::InvalidSubjectToken)?;
+
+            if !token.is_valid(clock.now()) {
+                return Err(RouteError::InvalidSubjectToken);
+            }
+
+            (token.session_id, token.dpop_jkt)
+        }
+        TokenTypeIdentifier::RefreshToken => {
+            let token = repo
+                .oauth2_refresh_token()
+                .find_by_token(&grant.subject_token)
+                .await?
+                .ok_or(RouteError::InvalidSubjectToken)?;
+
+            if !token.is_valid() {
+                return Err(RouteError::InvalidSubjectToken);
+            }
+
+            (token.session_id, token.dpop_jkt)
+        }
+        other => return Err(RouteError::UnsupportedSubjectTokenType(other.clone())),
+    };
+
+    // If the subject token is bound to a DPoP key, the request must have a proof
+    // signed with the same key
+    verify_dpop_binding(subject_jkt.as_deref(), dpop_jkt.as_deref())?;
+
+    let subject_session = repo
+        .oauth2_session()
+        .lookup(subject_session_id)
+        .await?
+        .ok_or(RouteError::NoSuchOAuthSession(subject_session_id))?;
+
+    if !subject_session.is_valid() {
+        return Err(RouteError::InvalidSubjectToken);
+    }
+
+    // Impersonation sessions are time-limited and attributed to an admin, which
+    // the exchanged session would not carry over
+    if subject_session.impersonation.is_some() {
+        return Err(RouteError::ImpersonationSubjectToken);
+    }
+
+    let subject_client = repo
+        .oauth2_client()
+        .lookup(subject_session.client_id)
+        .await?
+        .ok_or(RouteError::NoSuchOAuthClient(subject_session.client_id))?;
+
+    let user = if let Some(user_id) = subject_session.user_id {
+        let user = repo
+            .user()
+            .lookup(user_id)
+            .await?
+            .ok_or(RouteError::NoSuchUser(user_id))?;
+
+        if !user.is_valid() {
+            return Err(RouteError::InvalidSubjectToken);
+        }
+
+        Some(user)
+    } else {
+        None
+    };
+
+    // The new session can only narrow down the scope of the subject token.
+    // Default to the scope of the subject token if none is provided, minus the
+    // scopes which can't be exchanged
+    let scope = if let Some(scope) = &grant.scope {
+        if !scope
+            .iter()
+            .all(|token| subject_session.scope.contains(token.as_str()))
+        {
+            return Err(RouteError::ScopeNotGranted);
+        }
+
+        scope.clone()
+    } else {
+        subject_session
+            .scope
+            .iter()
+            .filter(|token| is_exchangeable_scope(token))
+            .cloned()
+            .collect()
+    };
+
+    // Make the request go through the policy engine
+    let res = policy
+        .evaluate_authorization_grant(mas_policy::AuthorizationGrantInput {
+            user: user.as_ref(),
+            mfa_authenticated: false,
+            client,
+            scope: &scope,
+            grant_type: mas_policy::GrantType::TokenExchange,
+            subject: Some(mas_policy::TokenExchangeSubject {
+                client: &subject_client,
+                scope: &subject_session.scope,
+            }),
+            requester: mas_policy::Requester {
+                ip_address: activity_tracker.ip(),
+                user_agent: user_agent.clone(),
+            },
+        })
+        .await?;
+    if !res.valid() {
+        return Err(RouteError::DeniedByPolicy(res));
+    }
+
+    // Start the session, recording which session it was derived from
+    let mut session = repo
+        .oauth2_session()
+        .add_from_token_exchange(rng, clock, client, &subject_session, scope)
+        .await?;
+
+    if let Some(user_agent) = user_agent {
+        session = repo
+            .oauth2_session()
+            .record_user_agent(session, user_agent)
+            .await?;
+    }
+
+    let ttl = site_config.access_token_ttl;
+    let access_token_str = generate_access_token(
+        rng,
+        clock,
+        url_builder,
+        key_store,
+        client,
+        &session,
+        user.as_ref(),
+        site_config,
+        dpop_jkt.as_deref(),
+    )?;
 
     let access_token = repo
         .oauth2_access_token()
-        .add(rng, clock, &session, access_token_str, Some(ttl))
+        .add(rng, clock, &session, access_token_str, Some(ttl), dpop_jkt)
         .await?;
 
-    let mut params = AccessTokenResponse::new(access_token.access_token).with_expires_in(ttl);
+    let mut params = AccessTokenResponse::new(access_token.access_token.clone())
+        .with_token_type(token_type_for(&access_token))
+        .with_issued_token_type(TokenTypeIdentifier::AccessToken)
+        .with_expires_in(ttl);
+
+    // Exchanged tokens are meant to be short-lived, so we only generate a refresh
+    // token if it was requested
+    if with_refresh_token {
+        let refresh_token_str = TokenType::RefreshToken.generate(rng);
+
+        let refresh_token = repo
+            .oauth2_refresh_token()
+            .add(rng, clock, &session, &access_token, refresh_token_str)
+            .await?;
+
+        params = params.with_refresh_token(refresh_token.refresh_token);
+    }
 
     // XXX: there is a potential (but unlikely) race here, where the activity for
     // the session is recorded before the transaction is committed. We would have to
@@ -831,6 +1260,20 @@ async fn client_credentials_grant(
     Ok((params, repo))
 }
 
+/// Whether a scope of a session can be carried over to a session obtained by
+/// exchanging one of its tokens.
+///
+/// Admin scopes and device scopes are bound to the original session, so this
+/// mirrors what the `authorization_grant` policy allows.
+fn is_exchangeable_scope(token: &ScopeToken) -> bool {
+    let token = token.as_str();
+    token != "urn:mas:admin"
+        && !token.starts_with("urn:mas:admin:")
+        && !token.starts_with("urn:synapse:admin:")
+        && !token.starts_with("urn:matrix:client:device:")
+        && !token.starts_with("urn:matrix:org.matrix.msc2967.client:device:")
+}
+
 async fn device_code_grant(
     rng: &mut BoxRng,
     clock: &impl Clock,
@@ -843,6 +1286,7 @@ async fn device_code_grant(
     mut repo: BoxRepository,
     homeserver: &Arc<dyn HomeserverConnection>,
     user_agent: Option<String>,
+    dpop_jkt: Option<String>,
 ) -> Result<(AccessTokenResponse, BoxRepository), RouteError> {
     // Check that the client is allowed to use this grant type
     if !client.grant_types.contains(&GrantType::DeviceCode) {
@@ -907,15 +1351,26 @@ async fn device_code_grant(
     }
 
     let ttl = site_config.access_token_ttl;
-    let access_token_str = TokenType::AccessToken.generate(rng);
+    let access_token_str = generate_access_token(
+        rng,
+        clock,
+        url_builder,
+        key_store,
+        client,
+        &session,
+        Some(&browser_session.user),
+        site_config,
+        dpop_jkt.as_deref(),
+    )?;
 
     let access_token = repo
         .oauth2_access_token()
-        .add(rng, clock, &session, access_token_str, Some(ttl))
+        .add(rng, clock, &session, access_token_str, Some(ttl), dpop_jkt)
         .await?;
 
-    let mut params =
-        AccessTokenResponse::new(access_token.access_token.clone()).with_expires_in(ttl);
+    let mut params = AccessTokenResponse::new(access_token.access_token.clone())
+        .with_token_type(token_type_for(&access_token))
+        .with_expires_in(ttl);
 
     // If the client uses the refresh token grant type, we also generate a refresh
     // token
@@ -979,12 +1434,20 @@ async fn device_code_grant(
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use hyper::Request;
-    use mas_data_model::{AccessToken, AuthorizationCode, RefreshToken};
+    use mas_data_model::{AuthorizationCode, Impersonation, RefreshToken};
+    use mas_iana::jose::JsonWebSignatureAlg;
+    use mas_jose::{
+        jwk::{PublicJsonWebKey, Thumbprint},
+        jwt::{JsonWebSignatureHeader, Jwt},
+    };
+    use mas_keystore::PrivateKey;
     use mas_router::SimpleRoute;
     use oauth2_types::{
         registration::ClientRegistrationResponse,
-        requests::{DeviceAuthorizationResponse, ResponseMode},
+        requests::{DeviceAuthorizationResponse, IntrospectionResponse, ResponseMode},
         scope::{OPENID, Scope},
     };
     use sqlx::PgPool;
@@ -1264,7 +1727,9 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                TokenType::AccessToken.generate(&mut state.rng()),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
+                None,
             )
             .await
             .unwrap();
@@ -1386,7 +1851,9 @@ mod tests {
                 &state.clock,
                 &mut repo,
                 &session,
+                TokenType::AccessToken.generate(&mut state.rng()),
                 Duration::microseconds(5 * 60 * 1000 * 1000),
+                None,
             )
             .await
             .unwrap();
@@ -1621,6 +2088,564 @@ mod tests {
         response.assert_status(StatusCode::OK);
     }
 
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_token_exchange(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision two clients which can use the token exchange grant
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let request = Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(
+                serde_json::json!({
+                    "client_uri": "https://example.com/",
+                    "token_endpoint_auth_method": "client_secret_post",
+                    "grant_types": [
+                        "client_credentials",
+                        "urn:ietf:params:oauth:grant-type:token-exchange",
+                    ],
+                }),
+            );
+
+            let response = state.request(request).await;
+            response.assert_status(StatusCode::CREATED);
+
+            let response: ClientRegistrationResponse = response.json();
+            let client_secret = response.client_secret.expect("to have a client secret");
+            clients.push((response.client_id, client_secret));
+        }
+        let (client_id, client_secret) = clients[0].clone();
+        let (other_client_id, other_client_secret) = clients[1].clone();
+
+        // Get a token through the client credentials grant
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:mas:graphql:*",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let AccessTokenResponse {
+            access_token: subject_token,
+            ..
+        } = response.json();
+
+        // Exchange it for a new one
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": subject_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let response: AccessTokenResponse = response.json();
+        assert_eq!(
+            response.issued_token_type,
+            Some(TokenTypeIdentifier::AccessToken)
+        );
+        assert_eq!(response.scope, Some("urn:mas:graphql:*".parse().unwrap()));
+        assert_ne!(response.access_token, subject_token);
+        // No refresh token is issued unless requested
+        assert!(response.refresh_token.is_none());
+
+        // The new session should record where it came from
+        let mut repo = state.repository().await.unwrap();
+        let subject = repo
+            .oauth2_access_token()
+            .find_by_token(&subject_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let exchanged = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .lookup(exchanged.session_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(session.id, subject.session_id);
+        assert_eq!(session.parent_session_id, Some(subject.session_id));
+        repo.save().await.unwrap();
+
+        // Asking for a scope the subject token doesn't have is not allowed
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": subject_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "scope": "urn:mas:admin",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidScope);
+
+        // Asking for a refresh token requires the client to use the refresh
+        // token grant
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": subject_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+                "requested_token_type": "urn:ietf:params:oauth:token-type:refresh_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidRequest);
+
+        // Unknown tokens are rejected
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": "mat_invalid",
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidRequest);
+
+        // Another client can't exchange this token by default
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": other_client_id,
+                "client_secret": other_client_secret,
+                "subject_token": subject_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        // Unless the policy allows it
+        let state = {
+            let mut state = state;
+            state.policy_factory = crate::test_utils::policy_factory(
+                "example.com",
+                serde_json::json!({
+                    "token_exchange_clients": [other_client_id]
+                }),
+            )
+            .await
+            .unwrap();
+            state
+        };
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": other_client_id,
+                "client_secret": other_client_secret,
+                "subject_token": subject_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_token_exchange_impersonation(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["urn:ietf:params:oauth:grant-type:token-exchange"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        // Create an impersonation session for a user, as the admin API would
+        let mut repo = state.repository().await.unwrap();
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let session = repo
+            .oauth2_session()
+            .add_impersonation(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &user,
+                "urn:matrix:client:api:*".parse().unwrap(),
+                Impersonation {
+                    impersonator_user_id: None,
+                    impersonator_client_id: Some(client.id),
+                    expires_at: state.clock.now() + Duration::minutes(10),
+                },
+            )
+            .await
+            .unwrap();
+        let subject_token = TokenType::AccessToken.generate(&mut state.rng());
+        repo.oauth2_access_token()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                &session,
+                subject_token.clone(),
+                Some(Duration::minutes(10)),
+                None,
+            )
+            .await
+            .unwrap();
+        repo.save().await.unwrap();
+
+        // Exchanging the impersonation token is not allowed
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "subject_token": subject_token,
+                "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidRequest);
+    }
+
+    /// Build a DPoP proof for a request to the given URL
+    fn dpop_proof(
+        rng: &mut (impl rand::RngCore + rand::CryptoRng),
+        key: &PrivateKey,
+        htu: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> String {
+        let alg = JsonWebSignatureAlg::Es256;
+        let header = JsonWebSignatureHeader::new(alg.clone())
+            .with_typ("dpop+jwt".to_owned())
+            .with_jwk(PublicJsonWebKey::new(key.into()));
+        let claims = serde_json::json!({
+            "jti": Ulid::from_datetime_with_source(now.into(), rng).to_string(),
+            "htm": "POST",
+            "htu": htu,
+            "iat": now.timestamp(),
+        });
+        let signer = key.signing_key_for_alg(&alg).unwrap();
+        Jwt::sign_with_rng(rng, header, claims, &signer)
+            .unwrap()
+            .into_string()
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_client_credentials_dpop(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["client_credentials"],
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        let mut rng = state.rng();
+        let key = PrivateKey::generate_ec_p256(&mut rng);
+        let token_endpoint = state.url_builder.oauth_token_endpoint();
+        let now = state.clock.now();
+
+        // A proof for another URL should be rejected
+        let request = Request::post(mas_router::OAuth2TokenEndpoint::PATH)
+            .header(
+                "DPoP",
+                dpop_proof(&mut rng, &key, "https://example.com/", now),
+            )
+            .form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let ClientError { error, .. } = response.json();
+        assert_eq!(error, ClientErrorCode::InvalidDpopProof);
+
+        // With a valid proof, we should get a DPoP-bound token
+        let request = Request::post(mas_router::OAuth2TokenEndpoint::PATH)
+            .header(
+                "DPoP",
+                dpop_proof(&mut rng, &key, token_endpoint.as_str(), now),
+            )
+            .form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+        assert_eq!(response.token_type, OAuthAccessTokenType::DPoP);
+
+        let mut repo = state.repository().await.unwrap();
+        let access_token = repo
+            .oauth2_access_token()
+            .find_by_token(&response.access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(access_token.dpop_jkt, Some(key.thumbprint_sha256_base64()));
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_jwt_access_token(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client which opts into JWT access tokens
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "token_endpoint_auth_method": "client_secret_post",
+                "grant_types": ["client_credentials"],
+                "access_token_signed_response_alg": "RS256",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let response: ClientRegistrationResponse = response.json();
+        let client_id = response.client_id;
+        let client_secret = response.client_secret.expect("to have a client secret");
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "client_credentials",
+                "client_id": client_id,
+                "client_secret": client_secret,
+                "scope": "urn:mas:graphql:*",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: AccessTokenResponse = response.json();
+        let access_token = response.access_token;
+
+        // The access token is a JWT signed by our keys
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(access_token.as_str()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(jwt.header().typ(), Some("at+jwt"));
+        let claims = jwt.payload();
+        assert_eq!(claims["sub"], client_id);
+        assert_eq!(claims["client_id"], client_id);
+        assert_eq!(claims["aud"], state.site_config.server_name);
+        assert_eq!(claims["scope"], "urn:mas:graphql:*");
+        let jti = claims["jti"].as_str().unwrap().to_owned();
+
+        // It can be introspected like an opaque token
+        let request =
+            Request::post(mas_router::OAuth2Introspection::PATH).form(serde_json::json!({
+                "token": access_token,
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(response.active);
+        assert_eq!(response.jti, Some(jti));
+
+        // Once revoked, it is no longer active
+        let request = Request::post(mas_router::OAuth2Revocation::PATH).form(serde_json::json!({
+            "token": access_token,
+            "client_id": client_id,
+            "client_secret": client_secret,
+        }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+
+        let request =
+            Request::post(mas_router::OAuth2Introspection::PATH).form(serde_json::json!({
+                "token": access_token,
+                "client_id": client_id,
+                "client_secret": client_secret,
+            }));
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let response: IntrospectionResponse = response.json();
+        assert!(!response.active);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_jwt_access_token_claims(pool: PgPool) {
+        setup();
+        let state = TestState::from_pool(pool).await.unwrap();
+
+        // Provision a client which opts into JWT access tokens
+        let request =
+            Request::post(mas_router::OAuth2RegistrationEndpoint::PATH).json(serde_json::json!({
+                "client_uri": "https://example.com/",
+                "redirect_uris": ["https://example.com/callback"],
+                "token_endpoint_auth_method": "none",
+                "response_types": ["code"],
+                "grant_types": ["authorization_code"],
+                "access_token_signed_response_alg": "RS256",
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::CREATED);
+
+        let ClientRegistrationResponse { client_id, .. } = response.json();
+
+        // Provision a user with a browser session, and a fulfilled grant
+        let mut repo = state.repository().await.unwrap();
+
+        let user = repo
+            .user()
+            .add(&mut state.rng(), &state.clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        let browser_session = repo
+            .browser_session()
+            .add(&mut state.rng(), &state.clock, &user, None)
+            .await
+            .unwrap();
+
+        let client = repo
+            .oauth2_client()
+            .find_by_client_id(&client_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let code = "thisisaverysecurecode";
+        let grant = repo
+            .oauth2_authorization_grant()
+            .add(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                "https://example.com/callback".parse().unwrap(),
+                Scope::from_iter([OPENID]),
+                Some(AuthorizationCode {
+                    code: code.to_owned(),
+                    pkce: None,
+                }),
+                Some("state".to_owned()),
+                Some("nonce".to_owned()),
+                ResponseMode::Query,
+                false,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let session = repo
+            .oauth2_session()
+            .add_from_browser_session(
+                &mut state.rng(),
+                &state.clock,
+                &client,
+                &browser_session,
+                grant.scope.clone(),
+            )
+            .await
+            .unwrap();
+
+        let grant = repo
+            .oauth2_authorization_grant()
+            .fulfill(&state.clock, &session, grant)
+            .await
+            .unwrap();
+
+        repo.save().await.unwrap();
+
+        let request =
+            Request::post(mas_router::OAuth2TokenEndpoint::PATH).form(serde_json::json!({
+                "grant_type": "authorization_code",
+                "code": code,
+                "redirect_uri": grant.redirect_uri,
+                "client_id": client.client_id,
+            }));
+
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        let AccessTokenResponse { access_token, .. } = response.json();
+
+        // The access token is a JWT meant for the homeserver, as described in RFC 9068
+        let jwt: Jwt<'_, HashMap<String, serde_json::Value>> =
+            Jwt::try_from(access_token.as_str()).unwrap();
+        jwt.verify_with_jwks(&state.key_store.public_jwks())
+            .unwrap();
+        assert_eq!(jwt.header().typ(), Some("at+jwt"));
+        let claims = jwt.payload();
+        assert_eq!(claims["aud"], state.site_config.server_name);
+        assert_ne!(claims["aud"], client.client_id);
+        assert_eq!(claims["client_id"], client.client_id);
+        assert_eq!(claims["sub"], user.sub);
+        assert_eq!(claims["scope"], "openid");
+        assert_eq!(claims["sid"], browser_session.id.to_string());
+
+        // Each token gets a unique identifier
+        let jti = claims["jti"].as_str().unwrap();
+        assert!(jti.parse::<Ulid>().is_ok());
+
+        let mut repo = state.repository().await.unwrap();
+        let stored = repo
+            .oauth2_access_token()
+            .find_by_token(&access_token)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.session_id, session.id);
+        repo.save().await.unwrap();
+    }
+
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
     async fn test_device_code_grant(pool: PgPool) {
         setup();
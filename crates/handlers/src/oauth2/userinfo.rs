@@ -11,6 +11,7 @@ use axum::{
 };
 use hyper::StatusCode;
 use mas_axum_utils::{
+    dpop::DPoPReplayCache,
     jwt::JwtResponse,
     record_error,
     user_authorization::{AuthorizationVerificationError, UserAuthorization},
@@ -105,9 +106,17 @@ pub async fn get(
     activity_tracker: BoundActivityTracker,
     mut repo: BoxRepository,
     State(key_store): State<Keystore>,
+    State(dpop_replay_cache): State<DPoPReplayCache>,
     user_authorization: UserAuthorization,
 ) -> Result<Response, RouteError> {
-    let session = user_authorization.protected(&mut repo, &clock).await?;
+    let session = user_authorization
+        .protected(
+            &mut repo,
+            &clock,
+            &url_builder.oidc_userinfo_endpoint(),
+            &dpop_replay_cache,
+        )
+        .await?;
 
     // This endpoint requires the `openid` scope.
     if !session.scope.contains("openid") {
@@ -9,12 +9,19 @@ use std::{collections::HashMap, sync::Arc};
 use anyhow::Context;
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
 use futures_util::future::OptionFuture;
+use mas_data_model::{Clock, Password, User};
+use mas_storage::{BoxRepository, RepositoryAccess, user::UserPasswordRepository};
 use pbkdf2::{Pbkdf2, password_hash};
 use rand::{CryptoRng, RngCore, SeedableRng, distributions::Standard, prelude::Distribution};
 use thiserror::Error;
 use zeroize::Zeroizing;
 use zxcvbn::zxcvbn;
 
+use self::{breached::BreachedPasswords, ldap::Ldap};
+
+pub mod breached;
+pub mod ldap;
+
 pub type SchemeVersion = u16;
 
 /// The result of a password verification, which is `true` if the password
@@ -73,6 +80,19 @@ pub struct PasswordManagerDisabledError;
 #[derive(Clone)]
 pub struct PasswordManager {
     inner: Option<Arc<InnerPasswordManager>>,
+
+    /// The directory to check passwords against, instead of the local password
+    /// hashes
+    ldap: Option<Ldap>,
+
+    /// The corpus of breached passwords new passwords are checked against
+    breached_passwords: Option<BreachedPasswords>,
+
+    /// Number of previous passwords which can't be reused
+    history: usize,
+
+    /// Maximum age of a password before the user has to change it
+    max_age: Option<chrono::Duration>,
 }
 
 struct InnerPasswordManager {
@@ -115,13 +135,59 @@ impl PasswordManager {
                 current_version,
                 other_hashers,
             })),
+            ldap: None,
+            breached_passwords: None,
+            history: 0,
+            max_age: None,
         })
     }
 
     /// Creates a new disabled password manager
     #[must_use]
     pub const fn disabled() -> Self {
-        Self { inner: None }
+        Self {
+            inner: None,
+            ldap: None,
+            breached_passwords: None,
+            history: 0,
+            max_age: None,
+        }
+    }
+
+    /// Check passwords against the given LDAP directory, instead of the local
+    /// password hashes
+    #[must_use]
+    pub fn with_ldap(mut self, ldap: Ldap) -> Self {
+        self.ldap = Some(ldap);
+        self
+    }
+
+    /// Get the LDAP directory passwords are checked against, if any
+    #[must_use]
+    pub fn ldap(&self) -> Option<&Ldap> {
+        self.ldap.as_ref()
+    }
+
+    /// Reject new passwords which appear in the given corpus of breached
+    /// passwords
+    #[must_use]
+    pub fn with_breached_passwords(mut self, breached_passwords: BreachedPasswords) -> Self {
+        self.breached_passwords = Some(breached_passwords);
+        self
+    }
+
+    /// Prevent users from reusing their last `history` passwords
+    #[must_use]
+    pub fn with_history(mut self, history: usize) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Require users to change passwords older than `max_age`
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: chrono::Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
     }
 
     /// Checks if the password manager is enabled or not
@@ -154,6 +220,81 @@ impl PasswordManager {
         Ok(u8::from(score.score()) >= inner.minimum_complexity)
     }
 
+    /// Checks if a new password appears in the corpus of breached passwords.
+    ///
+    /// Always returns `false` if no corpus is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the corpus could not be read
+    pub async fn is_password_breached(&self, password: &str) -> Result<bool, anyhow::Error> {
+        let Some(breached_passwords) = &self.breached_passwords else {
+            return Ok(false);
+        };
+
+        breached_passwords.contains(password).await
+    }
+
+    /// Checks if a new password is one of the last passwords of the user,
+    /// according to the configured history size.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the passwords could not be loaded or checked
+    pub async fn is_password_reused(
+        &self,
+        repo: &mut BoxRepository,
+        user: &User,
+        password: &str,
+    ) -> Result<bool, anyhow::Error> {
+        if self.history == 0 {
+            return Ok(false);
+        }
+
+        let previous_passwords = repo.user_password().history(user, self.history).await?;
+        for previous_password in previous_passwords {
+            let result = self
+                .verify(
+                    previous_password.version,
+                    Zeroizing::new(password.to_owned()),
+                    previous_password.hashed_password,
+                )
+                .await;
+
+            match result {
+                Ok(result) if result.is_success() => return Ok(true),
+                Ok(_) => {}
+                // This happens if the hashing scheme of an old password was removed
+                Err(e) => tracing::warn!(
+                    error = &*e as &dyn std::error::Error,
+                    user_password.id = %previous_password.id,
+                    "Failed to check a previous password"
+                ),
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Checks if a password is older than the configured maximum age.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the age of the password could not be loaded
+    pub async fn is_password_expired(
+        &self,
+        clock: &impl Clock,
+        repo: &mut BoxRepository,
+        password: &Password,
+    ) -> Result<bool, anyhow::Error> {
+        let Some(max_age) = self.max_age else {
+            return Ok(false);
+        };
+
+        let set_at = repo.user_password().set_at(password).await?;
+        Ok(clock.now() - set_at > max_age)
+    }
+
     /// Hash a password with the default hashing scheme.
     /// Returns the version of the hashing scheme used and the hashed password.
     ///
@@ -0,0 +1,271 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Lookup of passwords in a local mirror of a breached passwords corpus
+//!
+//! The corpus follows the format of the Have I Been Pwned "Pwned Passwords"
+//! dataset, in which passwords are identified by their uppercase hex-encoded
+//! SHA-1 hash. Lookups never leave the server.
+
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
+};
+
+use anyhow::Context as _;
+use camino::{Utf8Path, Utf8PathBuf};
+use sha1::{Digest, Sha1};
+
+/// Length of the hash prefix used to name the range files
+const PREFIX_LENGTH: usize = 5;
+
+/// Length of an hex-encoded SHA-1 hash
+const HASH_LENGTH: usize = 40;
+
+#[derive(Debug, Clone)]
+enum Corpus {
+    /// A directory of range files, named after the prefix of the hashes they
+    /// contain
+    Ranges(Utf8PathBuf),
+
+    /// A single file of full hashes, sorted by hash
+    Sorted(Utf8PathBuf),
+}
+
+/// A local corpus of breached passwords
+#[derive(Debug, Clone)]
+pub struct BreachedPasswords {
+    corpus: Corpus,
+}
+
+impl BreachedPasswords {
+    /// Open the corpus at the given path, which can either be a directory of
+    /// range files or a single sorted file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path does not exist
+    pub fn open(path: &Utf8Path) -> Result<Self, anyhow::Error> {
+        let metadata = std::fs::metadata(path)
+            .with_context(|| format!("Could not open the breached passwords corpus at {path}"))?;
+
+        let corpus = if metadata.is_dir() {
+            Corpus::Ranges(path.to_owned())
+        } else {
+            Corpus::Sorted(path.to_owned())
+        };
+
+        Ok(Self { corpus })
+    }
+
+    /// Check whether a password appears in the corpus
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the corpus could not be read
+    #[tracing::instrument(name = "passwords.breached.contains", skip_all)]
+    pub async fn contains(&self, password: &str) -> Result<bool, anyhow::Error> {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let corpus = self.corpus.clone();
+        let span = tracing::Span::current();
+
+        let found = tokio::task::spawn_blocking(move || {
+            span.in_scope(move || match corpus {
+                Corpus::Ranges(directory) => search_ranges(&directory, &hash),
+                Corpus::Sorted(path) => search_sorted(&path, &hash),
+            })
+        })
+        .await??;
+
+        Ok(found)
+    }
+}
+
+/// Extract the hash part of a line of the corpus, before the `:count` suffix
+fn line_hash(line: &str) -> &str {
+    let line = line.trim_end_matches(['\r', '\n']);
+    line.split_once(':').map_or(line, |(hash, _count)| hash)
+}
+
+/// Look for a hash in a directory of range files
+fn search_ranges(directory: &Utf8Path, hash: &str) -> io::Result<bool> {
+    let (prefix, suffix) = hash.split_at(PREFIX_LENGTH);
+
+    // The official downloader names the files `<prefix>.txt`, but the range
+    // API serves them without extension
+    let path = directory.join(format!("{prefix}.txt"));
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => match File::open(directory.join(prefix)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                tracing::warn!(%path, "Range file missing from the breached passwords corpus");
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        },
+        Err(e) => return Err(e),
+    };
+
+    for line in BufReader::new(file).lines() {
+        if line_hash(&line?).eq_ignore_ascii_case(suffix) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Read the first complete line starting at or after `offset`
+///
+/// Returns `None` if there is no such line
+fn line_at(reader: &mut BufReader<File>, offset: u64) -> io::Result<Option<String>> {
+    let mut buffer = Vec::new();
+    if offset == 0 {
+        reader.seek(SeekFrom::Start(0))?;
+    } else {
+        // Skip the end of the line which contains the previous byte. If that
+        // byte is a newline, this only consumes it.
+        reader.seek(SeekFrom::Start(offset - 1))?;
+        reader.read_until(b'\n', &mut buffer)?;
+        buffer.clear();
+    }
+
+    if reader.read_until(b'\n', &mut buffer)? == 0 {
+        return Ok(None);
+    }
+
+    String::from_utf8(buffer)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Compare the hash of a line to the hash we are looking for
+fn compare(line: &str, hash: &str) -> Ordering {
+    let line = line_hash(line);
+    let line = line.get(..HASH_LENGTH).unwrap_or(line);
+    line.to_ascii_uppercase().as_str().cmp(hash)
+}
+
+/// Look for a hash in a single file sorted by hash, with a binary search on
+/// the byte offsets of the file
+fn search_sorted(path: &Utf8Path, hash: &str) -> io::Result<bool> {
+    let file = File::open(path)?;
+    let length = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    // Find the smallest offset at which the next line is not lower than the
+    // hash. This is a valid binary search, as the line found at an offset only
+    // moves forward as the offset grows.
+    let (mut low, mut high) = (0, length);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match line_at(&mut reader, middle)? {
+            Some(line) if compare(&line, hash) == Ordering::Less => low = middle + 1,
+            _ => high = middle,
+        }
+    }
+
+    Ok(line_at(&mut reader, low)?.is_some_and(|line| compare(&line, hash) == Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write as _;
+
+    use super::*;
+
+    /// Create an empty directory for a test, removing any leftover from a
+    /// previous run
+    fn test_directory(name: &str) -> Utf8PathBuf {
+        let directory = Utf8PathBuf::try_from(std::env::temp_dir())
+            .unwrap()
+            .join(format!(
+                "mas-breached-passwords-{name}-{}",
+                std::process::id()
+            ));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn hash(password: &str) -> String {
+        hex::encode_upper(Sha1::digest(password.as_bytes()))
+    }
+
+    const BREACHED: &[&str] = &["password", "123456", "hunter2", "correct horse"];
+
+    #[tokio::test]
+    async fn test_sorted_file() {
+        let directory = test_directory("sorted");
+        let path = directory.join("pwned-passwords-sha1-ordered-by-hash.txt");
+
+        // Surround the breached passwords with some noise, to exercise the
+        // binary search
+        let mut hashes: Vec<String> = BREACHED.iter().map(|p| hash(p)).collect();
+        hashes.extend((0..500).map(|i| hash(&format!("noise-{i}"))));
+        hashes.sort();
+        let mut content = String::new();
+        for (i, hash) in hashes.iter().enumerate() {
+            writeln!(content, "{hash}:{i}\r").unwrap();
+        }
+        std::fs::write(&path, content).unwrap();
+
+        let corpus = BreachedPasswords::open(&path).unwrap();
+        for password in BREACHED {
+            assert!(corpus.contains(password).await.unwrap(), "{password}");
+        }
+        for password in ["not-breached", "correct horse battery staple", ""] {
+            assert!(!corpus.contains(password).await.unwrap(), "{password}");
+        }
+
+        // The first and last hashes are found
+        let first = hashes.first().unwrap();
+        let last = hashes.last().unwrap();
+        assert!(search_sorted(&path, first).unwrap());
+        assert!(search_sorted(&path, last).unwrap());
+        assert!(!search_sorted(&path, &"0".repeat(HASH_LENGTH)).unwrap());
+        assert!(!search_sorted(&path, &"F".repeat(HASH_LENGTH)).unwrap());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ranges_directory() {
+        let directory = test_directory("ranges");
+
+        for password in BREACHED {
+            let hash = hash(password);
+            let (prefix, suffix) = hash.split_at(PREFIX_LENGTH);
+            // Mix both naming conventions, as well as lowercase hashes
+            let file = if password.len() % 2 == 0 {
+                format!("{prefix}.txt")
+            } else {
+                prefix.to_owned()
+            };
+            let content = format!(
+                "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n{}:42\r\n",
+                suffix.to_ascii_lowercase()
+            );
+            std::fs::write(directory.join(file), content).unwrap();
+        }
+
+        let corpus = BreachedPasswords::open(&directory).unwrap();
+        for password in BREACHED {
+            assert!(corpus.contains(password).await.unwrap(), "{password}");
+        }
+        assert!(!corpus.contains("not-breached").await.unwrap());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_missing_corpus() {
+        let directory = test_directory("missing");
+        assert!(BreachedPasswords::open(&directory.join("nope")).is_err());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,794 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Password authentication against an LDAP directory
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use ldap3::{LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry, ldap_escape};
+use mas_config::LdapConfig;
+use mas_data_model::{Clock, User, UserLdapLink};
+use mas_matrix::HomeserverConnection;
+use mas_storage::{
+    BoxRepository, RepositoryAccess,
+    queue::{DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _},
+    user::{UserEmailRepository, UserLdapLinkRepository, UserRepository},
+};
+use minijinja::Environment;
+use rand::{CryptoRng, RngCore};
+use rustls_platform_verifier::ConfigVerifierExt;
+use thiserror::Error;
+use url::Url;
+use zeroize::Zeroizing;
+
+use crate::{
+    Limiter, RequesterFingerprint,
+    rate_limit::PasswordCheckLimitedError,
+    upstream_oauth2::template::{AttributeMappingContext, environment},
+};
+
+/// Timeout applied to every operation with the directory server
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+const DEFAULT_SUBJECT_TEMPLATE: &str = "{{ user.dn }}";
+const DEFAULT_LOCALPART_TEMPLATE: &str = "{{ user.uid }}";
+
+/// A client for the LDAP directory users authenticate against
+#[derive(Clone)]
+pub struct Ldap {
+    inner: Arc<InnerLdap>,
+}
+
+struct InnerLdap {
+    url: Url,
+    starttls: bool,
+    tls_config: Arc<rustls::ClientConfig>,
+
+    /// The DN and password to bind with when searching users
+    bind: Option<(String, Zeroizing<String>)>,
+    base_dn: String,
+    user_filter: String,
+    link_existing_users: bool,
+
+    environment: Environment<'static>,
+    subject_template: String,
+    localpart_template: String,
+    displayname_template: Option<String>,
+    email_template: Option<String>,
+}
+
+/// An entry found in the directory
+#[derive(Debug, Clone)]
+pub struct LdapEntry {
+    dn: String,
+    attributes: HashMap<String, Vec<String>>,
+}
+
+impl LdapEntry {
+    /// The DN of the entry
+    #[must_use]
+    pub fn dn(&self) -> &str {
+        &self.dn
+    }
+
+    /// Build the context passed to the attribute mapping templates.
+    ///
+    /// Attributes with a single value are exposed as strings, and attributes
+    /// with multiple values as lists.
+    fn context(&self) -> minijinja::Value {
+        let mut user: serde_json::Map<String, serde_json::Value> = self
+            .attributes
+            .iter()
+            .map(|(name, values)| {
+                let value = if let [value] = values.as_slice() {
+                    serde_json::Value::String(value.clone())
+                } else {
+                    serde_json::Value::from(values.clone())
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        user.insert("dn".to_owned(), self.dn.clone().into());
+
+        AttributeMappingContext::new()
+            .with_userinfo_claims(serde_json::Value::Object(user))
+            .build()
+    }
+}
+
+impl Ldap {
+    /// Create a new [`Ldap`] client from its configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TLS configuration could not be created
+    pub fn new(config: &LdapConfig, bind_password: Option<String>) -> Result<Self, anyhow::Error> {
+        let tls_config = rustls::ClientConfig::with_platform_verifier()
+            .context("Failed to create the TLS configuration for the LDAP client")?;
+
+        let bind = config
+            .bind_dn
+            .clone()
+            .zip(bind_password.map(Zeroizing::new));
+
+        let attributes = &config.attributes;
+
+        Ok(Self {
+            inner: Arc::new(InnerLdap {
+                url: config.url.clone(),
+                starttls: config.starttls,
+                tls_config: Arc::new(tls_config),
+                bind,
+                base_dn: config.base_dn.clone(),
+                user_filter: config.user_filter.clone(),
+                link_existing_users: config.link_existing_users,
+                environment: environment(),
+                subject_template: attributes
+                    .subject
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_SUBJECT_TEMPLATE.to_owned()),
+                localpart_template: attributes
+                    .localpart
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_LOCALPART_TEMPLATE.to_owned()),
+                displayname_template: attributes.displayname.clone(),
+                email_template: attributes.email.clone(),
+            }),
+        })
+    }
+
+    /// Open a new connection to the directory server
+    async fn connect(&self) -> Result<ldap3::Ldap, LdapError> {
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(TIMEOUT)
+            .set_starttls(self.inner.starttls)
+            .set_config(self.inner.tls_config.clone());
+
+        let (conn, ldap) = LdapConnAsync::from_url_with_settings(settings, &self.inner.url).await?;
+
+        // The connection has to be driven in the background for the requests to
+        // make progress
+        tokio::spawn(async move {
+            if let Err(e) = conn.drive().await {
+                tracing::warn!(
+                    error = &e as &dyn std::error::Error,
+                    "Error while driving the LDAP connection"
+                );
+            }
+        });
+
+        Ok(ldap)
+    }
+
+    /// Find the entry of a user in the directory
+    ///
+    /// Returns `None` if no entry, or more than one entry, matches the
+    /// username.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory server could not be reached or
+    /// rejected the search
+    pub async fn find(&self, username: &str) -> Result<Option<LdapEntry>, LdapError> {
+        let mut ldap = self.connect().await?;
+
+        if let Some((bind_dn, bind_password)) = &self.inner.bind {
+            ldap.with_timeout(TIMEOUT)
+                .simple_bind(bind_dn, bind_password)
+                .await?
+                .success()?;
+        }
+
+        let filter = self
+            .inner
+            .user_filter
+            .replace("{username}", &ldap_escape(username));
+
+        let (entries, _result) = ldap
+            .with_timeout(TIMEOUT)
+            .search(&self.inner.base_dn, Scope::Subtree, &filter, vec!["*"])
+            .await?
+            .success()?;
+
+        if let Err(e) = ldap.unbind().await {
+            tracing::debug!(
+                error = &e as &dyn std::error::Error,
+                "Failed to unbind from the directory"
+            );
+        }
+
+        let mut entries = entries
+            .into_iter()
+            .filter(|entry| !entry.is_ref() && !entry.is_intermediate())
+            .map(SearchEntry::construct);
+
+        let Some(entry) = entries.next() else {
+            return Ok(None);
+        };
+
+        if entries.next().is_some() {
+            tracing::warn!(%filter, "More than one directory entry matches the username");
+            return Ok(None);
+        }
+
+        Ok(Some(LdapEntry {
+            dn: entry.dn,
+            attributes: entry.attrs,
+        }))
+    }
+
+    /// Check the password of a directory entry, by binding as that entry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory server could not be reached or
+    /// failed for another reason than invalid credentials
+    pub async fn check_password(
+        &self,
+        entry: &LdapEntry,
+        password: &str,
+    ) -> Result<bool, LdapError> {
+        // A simple bind with an empty password is an unauthenticated bind, which
+        // most servers accept
+        if password.is_empty() {
+            return Ok(false);
+        }
+
+        let mut ldap = self.connect().await?;
+
+        let result = ldap
+            .with_timeout(TIMEOUT)
+            .simple_bind(&entry.dn, password)
+            .await?;
+
+        if let Err(e) = ldap.unbind().await {
+            tracing::debug!(
+                error = &e as &dyn std::error::Error,
+                "Failed to unbind from the directory"
+            );
+        }
+
+        // 49 is invalidCredentials
+        if result.rc == 49 {
+            return Ok(false);
+        }
+
+        result.success()?;
+        Ok(true)
+    }
+
+    /// Render a required attribute template
+    fn render_required(
+        &self,
+        template: &str,
+        context: &minijinja::Value,
+    ) -> Result<String, LdapLoginError> {
+        match self.inner.environment.render_str(template, context) {
+            Ok(value) if value.is_empty() => Err(LdapLoginError::RequiredAttributeEmpty {
+                template: template.to_owned(),
+            }),
+            Ok(value) => Ok(value),
+            Err(source) => Err(LdapLoginError::RequiredAttributeRender {
+                template: template.to_owned(),
+                source,
+            }),
+        }
+    }
+
+    /// Render an optional attribute template, ignoring rendering errors
+    fn render_optional(
+        &self,
+        template: Option<&str>,
+        context: &minijinja::Value,
+    ) -> Option<String> {
+        let template = template?;
+        match self.inner.environment.render_str(template, context) {
+            Ok(value) if value.is_empty() => None,
+            Ok(value) => Some(value),
+            Err(source) => {
+                tracing::warn!(error = &source as &dyn std::error::Error, %template, "Error while rendering template");
+                None
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum LdapLoginError {
+    #[error("user not found in the directory")]
+    UserNotFound,
+
+    #[error("password verification failed")]
+    PasswordMismatch,
+
+    #[error("request rate limited")]
+    RateLimited(#[from] PasswordCheckLimitedError),
+
+    #[error("localpart {localpart:?} is not available")]
+    UsernameNotAvailable { localpart: String },
+
+    /// Required attribute rendered to an empty string
+    #[error("Template {template:?} rendered to an empty string")]
+    RequiredAttributeEmpty { template: String },
+
+    /// Required attribute could not be rendered from the directory entry
+    #[error("Template {template:?} could not be rendered from the directory entry")]
+    RequiredAttributeRender {
+        template: String,
+
+        #[source]
+        source: minijinja::Error,
+    },
+
+    #[error("failed to query the directory")]
+    Directory(#[from] LdapError),
+
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+impl From<mas_storage::RepositoryError> for LdapLoginError {
+    fn from(err: mas_storage::RepositoryError) -> Self {
+        Self::Internal(Box::new(err))
+    }
+}
+
+/// Authenticate a user against the directory
+///
+/// On the first login of a directory entry, a local user is provisioned for
+/// it, or, if allowed by the configuration, an existing user with the same
+/// localpart is linked to it.
+///
+/// This does not check whether the user is locked or deactivated, which is
+/// left to the caller.
+///
+/// # Errors
+///
+/// Returns an error if the credentials are invalid, if the request is rate
+/// limited, or if the user could not be provisioned
+#[allow(clippy::too_many_arguments)]
+pub async fn login(
+    mut rng: &mut (impl RngCore + CryptoRng + Send),
+    clock: &impl Clock,
+    repo: &mut BoxRepository,
+    homeserver: &dyn HomeserverConnection,
+    limiter: &Limiter,
+    requester: RequesterFingerprint,
+    ldap: &Ldap,
+    username: &str,
+    password: &str,
+) -> Result<(User, UserLdapLink), LdapLoginError> {
+    let Some(entry) = ldap.find(username).await? else {
+        limiter.check_password_for_requester(requester).await?;
+        return Err(LdapLoginError::UserNotFound);
+    };
+
+    let context = entry.context();
+    let subject = ldap.render_required(&ldap.inner.subject_template, &context)?;
+
+    let link = repo.user_ldap_link().find_by_subject(&subject).await?;
+    let linked_user = if let Some(link) = &link {
+        let user = repo
+            .user()
+            .lookup(link.user_id)
+            .await?
+            .context("Failed to load the user linked to the directory entry")
+            .map_err(|e| LdapLoginError::Internal(e.into()))?;
+        Some(user)
+    } else {
+        None
+    };
+
+    // Check the rate limit
+    if let Some(user) = &linked_user {
+        limiter.check_password(requester, user).await?;
+    } else {
+        limiter.check_password_for_requester(requester).await?;
+    }
+
+    if !ldap.check_password(&entry, password).await? {
+        return Err(LdapLoginError::PasswordMismatch);
+    }
+
+    if let (Some(link), Some(user)) = (link, linked_user) {
+        return Ok((user, link));
+    }
+
+    // This is the first login of this directory entry, find or provision the
+    // local user
+    let localpart = ldap.render_required(&ldap.inner.localpart_template, &context)?;
+
+    let existing_user = repo.user().find_by_username(&localpart).await?;
+    if let Some(user) = existing_user {
+        let already_linked = repo.user_ldap_link().find_for_user(&user).await?.is_some();
+        if !ldap.inner.link_existing_users || already_linked {
+            tracing::warn!(
+                %localpart,
+                %subject,
+                "A user with the same localpart as the directory entry already exists"
+            );
+            return Err(LdapLoginError::UsernameNotAvailable { localpart });
+        }
+
+        let link = repo
+            .user_ldap_link()
+            .add(&mut rng, clock, &user, subject)
+            .await?;
+
+        return Ok((user, link));
+    }
+
+    let available = homeserver
+        .is_localpart_available(&localpart)
+        .await
+        .map_err(|e| LdapLoginError::Internal(e.into()))?;
+    if !available {
+        return Err(LdapLoginError::UsernameNotAvailable { localpart });
+    }
+
+    let displayname = ldap.render_optional(ldap.inner.displayname_template.as_deref(), &context);
+    let email = ldap.render_optional(ldap.inner.email_template.as_deref(), &context);
+
+    let user = repo.user().add(&mut rng, clock, localpart).await?;
+
+    // Schedule the job to provision it
+    let mut job = ProvisionUserJob::new(&user);
+    if let Some(displayname) = displayname {
+        job = job.set_display_name(displayname);
+    }
+    repo.queue_job().schedule_job(&mut rng, clock, job).await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            clock,
+            DispatchWebhookEventJob::user_registered(&user),
+        )
+        .await?;
+
+    if let Some(email) = email {
+        repo.user_email().add(&mut rng, clock, &user, email).await?;
+    }
+
+    let link = repo
+        .user_ldap_link()
+        .add(&mut rng, clock, &user, subject)
+        .await?;
+
+    Ok((user, link))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use futures_util::{SinkExt as _, StreamExt as _};
+    use ldap3_proto::{
+        LdapCodec, LdapFilter, LdapPartialAttribute, LdapSearchResultEntry, ServerOps,
+    };
+    use mas_config::{LdapAttributeMapping, RateLimitingConfig};
+    use mas_data_model::{AuthenticationMethod, clock::MockClock};
+    use mas_matrix::MockHomeserverConnection;
+    use mas_storage::user::BrowserSessionRepository;
+    use mas_storage_pg::PgRepositoryFactory;
+    use rand::SeedableRng;
+    use sqlx::PgPool;
+    use tokio::net::TcpListener;
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    use super::*;
+
+    const ADMIN_DN: &str = "cn=admin,dc=example,dc=com";
+    const ADMIN_PASSWORD: &str = "admin-password";
+
+    struct DirectoryEntry {
+        dn: &'static str,
+        password: &'static str,
+        attributes: &'static [(&'static str, &'static [&'static str])],
+    }
+
+    const DIRECTORY: &[DirectoryEntry] = &[
+        DirectoryEntry {
+            dn: "uid=alice,ou=people,dc=example,dc=com",
+            password: "alice-password",
+            attributes: &[
+                ("uid", &["alice"]),
+                ("cn", &["Alice"]),
+                ("mail", &["alice@example.com"]),
+                ("objectClass", &["top", "inetOrgPerson"]),
+            ],
+        },
+        DirectoryEntry {
+            dn: "uid=bob,ou=people,dc=example,dc=com",
+            password: "bob-password",
+            attributes: &[("uid", &["bob"]), ("cn", &["Bob"])],
+        },
+        DirectoryEntry {
+            dn: "uid=twin,ou=people,dc=example,dc=com",
+            password: "twin-password",
+            attributes: &[("uid", &["twin"])],
+        },
+        DirectoryEntry {
+            dn: "uid=twin,ou=others,dc=example,dc=com",
+            password: "twin-password",
+            attributes: &[("uid", &["twin"])],
+        },
+    ];
+
+    /// Start a minimal directory server serving [`DIRECTORY`], which only
+    /// answers searches after a bind as [`ADMIN_DN`], and only understands
+    /// equality filters
+    async fn start_directory() -> Url {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let (read, write) = stream.into_split();
+                    let mut reader = FramedRead::new(read, LdapCodec::default());
+                    let mut writer = FramedWrite::new(write, LdapCodec::default());
+                    let mut bound_as_admin = false;
+
+                    while let Some(Ok(msg)) = reader.next().await {
+                        let responses = match ServerOps::try_from(msg) {
+                            Ok(ServerOps::SimpleBind(req)) => {
+                                let valid = (req.dn == ADMIN_DN && req.pw == ADMIN_PASSWORD)
+                                    || DIRECTORY
+                                        .iter()
+                                        .any(|e| e.dn == req.dn && e.password == req.pw);
+                                bound_as_admin = valid && req.dn == ADMIN_DN;
+                                if valid {
+                                    vec![req.gen_success()]
+                                } else {
+                                    vec![req.gen_invalid_cred()]
+                                }
+                            }
+                            Ok(ServerOps::Search(req)) => {
+                                let mut responses = Vec::new();
+                                if let (true, LdapFilter::Equality(attr, value)) =
+                                    (bound_as_admin, &req.filter)
+                                {
+                                    for entry in DIRECTORY {
+                                        let matches = entry.attributes.iter().any(|(a, vals)| {
+                                            a.eq_ignore_ascii_case(attr)
+                                                && vals.contains(&value.as_str())
+                                        });
+                                        if matches {
+                                            responses.push(
+                                                req.gen_result_entry(LdapSearchResultEntry {
+                                                    dn: entry.dn.to_owned(),
+                                                    attributes: entry
+                                                        .attributes
+                                                        .iter()
+                                                        .map(|(atype, vals)| LdapPartialAttribute {
+                                                            atype: (*atype).to_owned(),
+                                                            vals: vals
+                                                                .iter()
+                                                                .map(|v| v.as_bytes().to_vec())
+                                                                .collect(),
+                                                        })
+                                                        .collect(),
+                                                }),
+                                            );
+                                        }
+                                    }
+                                }
+                                responses.push(req.gen_success());
+                                responses
+                            }
+                            _ => break,
+                        };
+
+                        for response in responses {
+                            writer.send(response).await.unwrap();
+                        }
+                    }
+                });
+            }
+        });
+
+        format!("ldap://{addr}").parse().unwrap()
+    }
+
+    /// Each login is done from a different address, to stay clear of the
+    /// rate limiter
+    fn requester(n: u8) -> RequesterFingerprint {
+        RequesterFingerprint::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, n)))
+    }
+
+    async fn ldap(link_existing_users: bool) -> Ldap {
+        let url = start_directory().await;
+        let config = LdapConfig {
+            url,
+            starttls: false,
+            bind_dn: Some(ADMIN_DN.to_owned()),
+            bind_password: None,
+            bind_password_file: None,
+            base_dn: "dc=example,dc=com".to_owned(),
+            user_filter: "(uid={username})".to_owned(),
+            link_existing_users,
+            attributes: LdapAttributeMapping {
+                subject: None,
+                localpart: None,
+                displayname: Some("{{ user.cn }}".to_owned()),
+                email: Some("{{ user.mail }}".to_owned()),
+            },
+        };
+
+        Ldap::new(&config, Some(ADMIN_PASSWORD.to_owned())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_find_and_check_password() {
+        let ldap = ldap(false).await;
+
+        let entry = ldap.find("alice").await.unwrap().unwrap();
+        assert_eq!(entry.dn(), "uid=alice,ou=people,dc=example,dc=com");
+
+        assert!(ldap.check_password(&entry, "alice-password").await.unwrap());
+        assert!(!ldap.check_password(&entry, "wrong").await.unwrap());
+        assert!(!ldap.check_password(&entry, "").await.unwrap());
+
+        // Unknown users and ambiguous usernames are not found
+        assert!(ldap.find("mallory").await.unwrap().is_none());
+        assert!(ldap.find("twin").await.unwrap().is_none());
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_login(pool: PgPool) {
+        let ldap = ldap(false).await;
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let homeserver = MockHomeserverConnection::new("example.com");
+        let limiter = Limiter::new(&RateLimitingConfig::default()).unwrap();
+        let mut repo = PgRepositoryFactory::new(pool)
+            .boxed()
+            .create()
+            .await
+            .unwrap();
+
+        // A wrong password is rejected, and doesn't provision the user
+        let res = login(
+            &mut rng,
+            &clock,
+            &mut repo,
+            &homeserver,
+            &limiter,
+            requester(1),
+            &ldap,
+            "alice",
+            "wrong",
+        )
+        .await;
+        assert!(matches!(res, Err(LdapLoginError::PasswordMismatch)));
+        assert!(
+            repo.user()
+                .find_by_username("alice")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Unknown users are rejected
+        let res = login(
+            &mut rng,
+            &clock,
+            &mut repo,
+            &homeserver,
+            &limiter,
+            requester(2),
+            &ldap,
+            "mallory",
+            "password",
+        )
+        .await;
+        assert!(matches!(res, Err(LdapLoginError::UserNotFound)));
+
+        // The first login provisions the user
+        let (user, link) = login(
+            &mut rng,
+            &clock,
+            &mut repo,
+            &homeserver,
+            &limiter,
+            requester(3),
+            &ldap,
+            "alice",
+            "alice-password",
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.username, "alice");
+        assert_eq!(link.user_id, user.id);
+        assert_eq!(link.subject, "uid=alice,ou=people,dc=example,dc=com");
+
+        let emails = repo
+            .user_email()
+            .all(&user)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|email| email.email)
+            .collect::<Vec<_>>();
+        assert_eq!(emails, vec!["alice@example.com".to_owned()]);
+
+        // The next login finds the same user
+        let (user2, link2) = login(
+            &mut rng,
+            &clock,
+            &mut repo,
+            &homeserver,
+            &limiter,
+            requester(4),
+            &ldap,
+            "alice",
+            "alice-password",
+        )
+        .await
+        .unwrap();
+        assert_eq!(user2.id, user.id);
+        assert_eq!(link2.id, link.id);
+
+        // The link can be used to authenticate a browser session
+        let session = repo
+            .browser_session()
+            .add(&mut rng, &clock, &user, None)
+            .await
+            .unwrap();
+        let authentication = repo
+            .browser_session()
+            .authenticate_with_ldap(&mut rng, &clock, &session, &link)
+            .await
+            .unwrap();
+        assert_eq!(
+            authentication.authentication_method,
+            AuthenticationMethod::Ldap {
+                user_ldap_link_id: link.id
+            }
+        );
+
+        // A local user with the same localpart is not linked by default
+        repo.user()
+            .add(&mut rng, &clock, "bob".to_owned())
+            .await
+            .unwrap();
+        let res = login(
+            &mut rng,
+            &clock,
+            &mut repo,
+            &homeserver,
+            &limiter,
+            requester(5),
+            &ldap,
+            "bob",
+            "bob-password",
+        )
+        .await;
+        assert!(matches!(
+            res,
+            Err(LdapLoginError::UsernameNotAvailable { .. })
+        ));
+
+        // ...unless explicitly allowed
+        let ldap = self::ldap(true).await;
+        let (user, _link) = login(
+            &mut rng,
+            &clock,
+            &mut repo,
+            &homeserver,
+            &limiter,
+            requester(6),
+            &ldap,
+            "bob",
+            "bob-password",
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.username, "bob");
+    }
+}
@@ -4,13 +4,32 @@
 // SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
 // Please see LICENSE files in the repository root for full details.
 
-use std::{net::IpAddr, sync::Arc, time::Duration};
-
-use governor::{RateLimiter, clock::QuantaClock, state::keyed::DashMapStateStore};
+use std::{
+    fmt::Display,
+    hash::Hash,
+    net::IpAddr,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use governor::{Quota, RateLimiter, clock::QuantaClock, state::keyed::DashMapStateStore};
 use mas_config::RateLimitingConfig;
-use mas_data_model::{User, UserEmailAuthentication};
+use mas_data_model::{SystemClock, User, UserEmailAuthentication};
+use mas_storage::{BoxRepositoryFactory, RepositoryError};
+use opentelemetry::{Key, KeyValue, metrics::Counter};
 use ulid::Ulid;
 
+use crate::METER;
+
+static SHARED_FALLBACK_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    METER
+        .u64_counter("mas.rate_limit.shared_fallback")
+        .with_description("Number of rate limit checks which fell back to the in-memory state")
+        .with_unit("{check}")
+        .build()
+});
+const LIMITER: Key = Key::from_static_str("limiter");
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum AccountRecoveryLimitedError {
     #[error("Too many account recovery requests for requester {0}")]
@@ -81,65 +100,219 @@ pub struct Limiter {
     inner: Arc<LimiterInner>,
 }
 
-type KeyedRateLimiter<K> = RateLimiter<K, DashMapStateStore<K>, QuantaClock>;
+/// Where the state of the rate limiters is kept
+enum Backend {
+    /// In the memory of this process
+    Memory,
+
+    /// In the database, shared with the other replicas
+    Shared(BoxRepositoryFactory),
+}
+
+impl std::fmt::Debug for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Memory => f.write_str("Memory"),
+            Self::Shared(_) => f.write_str("Shared"),
+        }
+    }
+}
+
+/// The action was rate limited
+struct RateLimited;
+
+/// A rate limiter for one kind of action, keyed by `K`
+#[derive(Debug)]
+struct KeyedLimiter<K: Hash + Eq + Clone> {
+    /// The name of the limiter, used to separate the keys in the shared state
+    name: &'static str,
+    quota: Quota,
+    /// The in-memory state, used by the memory backend, and as a fallback
+    /// when the shared state can't be reached
+    memory: RateLimiter<K, DashMapStateStore<K>, QuantaClock>,
+}
+
+impl<K: Hash + Eq + Clone + Display + Sync> KeyedLimiter<K> {
+    fn new(name: &'static str, quota: Quota) -> Self {
+        Self {
+            name,
+            quota,
+            memory: RateLimiter::keyed(quota),
+        }
+    }
+
+    /// Check if an action can be performed for the given key, and record it
+    async fn check_key(&self, backend: &Backend, key: &K) -> Result<(), RateLimited> {
+        let Backend::Shared(repository_factory) = backend else {
+            return self.memory.check_key(key).map_err(|_| RateLimited);
+        };
+
+        match self.check_shared(repository_factory, key).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(RateLimited),
+            Err(e) => {
+                // Don't lock everyone out if the database is having issues, but
+                // don't let the action through unchecked either: fall back to
+                // the state kept by this replica
+                SHARED_FALLBACK_COUNTER.add(1, &[KeyValue::new(LIMITER, self.name)]);
+                tracing::warn!(
+                    error = &e as &dyn std::error::Error,
+                    limiter = self.name,
+                    "Failed to check the shared rate limiter, falling back to the in-memory one"
+                );
+                self.memory.check_key(key).map_err(|_| RateLimited)
+            }
+        }
+    }
+
+    async fn check_shared(
+        &self,
+        repository_factory: &BoxRepositoryFactory,
+        key: &K,
+    ) -> Result<bool, RepositoryError> {
+        let interval = chrono::Duration::from_std(self.quota.replenish_interval())
+            .unwrap_or(chrono::Duration::MAX);
+
+        let mut repo = repository_factory.create().await?;
+        let allowed = repo
+            .rate_limit()
+            .check(
+                &SystemClock::default(),
+                self.name,
+                &key.to_string(),
+                interval,
+                self.quota.burst_size(),
+            )
+            .await?;
+        repo.save().await?;
+
+        Ok(allowed)
+    }
+}
 
 #[derive(Debug)]
 struct LimiterInner {
-    account_recovery_per_requester: KeyedRateLimiter<RequesterFingerprint>,
-    account_recovery_per_email: KeyedRateLimiter<String>,
-    password_check_for_requester: KeyedRateLimiter<RequesterFingerprint>,
-    password_check_for_user: KeyedRateLimiter<Ulid>,
-    registration_per_requester: KeyedRateLimiter<RequesterFingerprint>,
-    email_authentication_per_requester: KeyedRateLimiter<RequesterFingerprint>,
-    email_authentication_per_email: KeyedRateLimiter<String>,
-    email_authentication_emails_per_session: KeyedRateLimiter<Ulid>,
-    email_authentication_attempt_per_session: KeyedRateLimiter<Ulid>,
+    backend: Backend,
+    account_recovery_per_requester: KeyedLimiter<RequesterFingerprint>,
+    account_recovery_per_email: KeyedLimiter<String>,
+    password_check_for_requester: KeyedLimiter<RequesterFingerprint>,
+    password_check_for_user: KeyedLimiter<Ulid>,
+    registration_per_requester: KeyedLimiter<RequesterFingerprint>,
+    email_authentication_per_requester: KeyedLimiter<RequesterFingerprint>,
+    email_authentication_per_email: KeyedLimiter<String>,
+    email_authentication_emails_per_session: KeyedLimiter<Ulid>,
+    email_authentication_attempt_per_session: KeyedLimiter<Ulid>,
 }
 
 impl LimiterInner {
-    fn new(config: &RateLimitingConfig) -> Option<Self> {
+    fn new(config: &RateLimitingConfig, backend: Backend) -> Option<Self> {
         Some(Self {
-            account_recovery_per_requester: RateLimiter::keyed(
+            backend,
+            account_recovery_per_requester: KeyedLimiter::new(
+                "account_recovery_per_requester",
                 config.account_recovery.per_ip.to_quota()?,
             ),
-            account_recovery_per_email: RateLimiter::keyed(
+            account_recovery_per_email: KeyedLimiter::new(
+                "account_recovery_per_email",
                 config.account_recovery.per_address.to_quota()?,
             ),
-            password_check_for_requester: RateLimiter::keyed(config.login.per_ip.to_quota()?),
-            password_check_for_user: RateLimiter::keyed(config.login.per_account.to_quota()?),
-            registration_per_requester: RateLimiter::keyed(config.registration.to_quota()?),
-            email_authentication_per_email: RateLimiter::keyed(
+            password_check_for_requester: KeyedLimiter::new(
+                "password_check_for_requester",
+                config.login.per_ip.to_quota()?,
+            ),
+            password_check_for_user: KeyedLimiter::new(
+                "password_check_for_user",
+                config.login.per_account.to_quota()?,
+            ),
+            registration_per_requester: KeyedLimiter::new(
+                "registration_per_requester",
+                config.registration.to_quota()?,
+            ),
+            email_authentication_per_email: KeyedLimiter::new(
+                "email_authentication_per_email",
                 config.email_authentication.per_address.to_quota()?,
             ),
-            email_authentication_per_requester: RateLimiter::keyed(
+            email_authentication_per_requester: KeyedLimiter::new(
+                "email_authentication_per_requester",
                 config.email_authentication.per_ip.to_quota()?,
             ),
-            email_authentication_emails_per_session: RateLimiter::keyed(
+            email_authentication_emails_per_session: KeyedLimiter::new(
+                "email_authentication_emails_per_session",
                 config.email_authentication.emails_per_session.to_quota()?,
             ),
-            email_authentication_attempt_per_session: RateLimiter::keyed(
+            email_authentication_attempt_per_session: KeyedLimiter::new(
+                "email_authentication_attempt_per_session",
                 config.email_authentication.attempt_per_session.to_quota()?,
             ),
         })
     }
+
+    /// Remove old entries from the in-memory rate limiters
+    fn retain_recent(&self) {
+        self.account_recovery_per_email.memory.retain_recent();
+        self.account_recovery_per_requester.memory.retain_recent();
+        self.password_check_for_requester.memory.retain_recent();
+        self.password_check_for_user.memory.retain_recent();
+        self.registration_per_requester.memory.retain_recent();
+        self.email_authentication_per_email.memory.retain_recent();
+        self.email_authentication_per_requester
+            .memory
+            .retain_recent();
+        self.email_authentication_emails_per_session
+            .memory
+            .retain_recent();
+        self.email_authentication_attempt_per_session
+            .memory
+            .retain_recent();
+    }
+
+    /// Remove the fully replenished keys from the shared state
+    async fn cleanup_shared(
+        repository_factory: &BoxRepositoryFactory,
+    ) -> Result<usize, RepositoryError> {
+        let mut repo = repository_factory.create().await?;
+        let count = repo.rate_limit().cleanup(&SystemClock::default()).await?;
+        repo.save().await?;
+        Ok(count)
+    }
 }
 
 impl Limiter {
-    /// Creates a new `Limiter` based on a `RateLimitingConfig`.
+    /// Creates a new `Limiter` based on a `RateLimitingConfig`, keeping its
+    /// state in memory.
     ///
     /// If the config is not valid, returns `None`.
     /// (This should not happen if the config was validated, though.)
     #[must_use]
     pub fn new(config: &RateLimitingConfig) -> Option<Self> {
         Some(Self {
-            inner: Arc::new(LimiterInner::new(config)?),
+            inner: Arc::new(LimiterInner::new(config, Backend::Memory)?),
+        })
+    }
+
+    /// Creates a new `Limiter` based on a `RateLimitingConfig`, keeping its
+    /// state in the database so that it is shared between replicas.
+    ///
+    /// If the config is not valid, returns `None`.
+    /// (This should not happen if the config was validated, though.)
+    #[must_use]
+    pub fn new_shared(
+        config: &RateLimitingConfig,
+        repository_factory: BoxRepositoryFactory,
+    ) -> Option<Self> {
+        Some(Self {
+            inner: Arc::new(LimiterInner::new(
+                config,
+                Backend::Shared(repository_factory),
+            )?),
         })
     }
 
     /// Start the rate limiter housekeeping task
     ///
     /// This task will periodically remove old entries from the rate limiters,
-    /// to make sure we don't build up a huge number of entries in memory.
+    /// to make sure we don't build up a huge number of entries in memory or
+    /// in the database.
     pub fn start(&self) {
         // Spawn a task that will periodically clean the rate limiters
         let this = self.clone();
@@ -149,22 +322,26 @@ impl Limiter {
             interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
             loop {
-                // Call the retain_recent method on each rate limiter
-                this.inner.account_recovery_per_email.retain_recent();
-                this.inner.account_recovery_per_requester.retain_recent();
-                this.inner.password_check_for_requester.retain_recent();
-                this.inner.password_check_for_user.retain_recent();
-                this.inner.registration_per_requester.retain_recent();
-                this.inner.email_authentication_per_email.retain_recent();
-                this.inner
-                    .email_authentication_per_requester
-                    .retain_recent();
-                this.inner
-                    .email_authentication_emails_per_session
-                    .retain_recent();
-                this.inner
-                    .email_authentication_attempt_per_session
-                    .retain_recent();
+                // The in-memory state is also used as a fallback by the shared
+                // backend, so it always needs to be cleaned up
+                this.inner.retain_recent();
+
+                match &this.inner.backend {
+                    Backend::Memory => {}
+                    Backend::Shared(repository_factory) => {
+                        match LimiterInner::cleanup_shared(repository_factory).await {
+                            Ok(count) => {
+                                tracing::debug!(count, "Cleaned up shared rate limiters");
+                            }
+                            Err(e) => {
+                                tracing::warn!(
+                                    error = &e as &dyn std::error::Error,
+                                    "Failed to clean up shared rate limiters"
+                                );
+                            }
+                        }
+                    }
+                }
 
                 interval.tick().await;
             }
@@ -176,14 +353,15 @@ impl Limiter {
     /// # Errors
     ///
     /// Returns an error if the operation is rate limited.
-    pub fn check_account_recovery(
+    pub async fn check_account_recovery(
         &self,
         requester: RequesterFingerprint,
         email_address: &str,
     ) -> Result<(), AccountRecoveryLimitedError> {
         self.inner
             .account_recovery_per_requester
-            .check_key(&requester)
+            .check_key(&self.inner.backend, &requester)
+            .await
             .map_err(|_| AccountRecoveryLimitedError::Requester(requester))?;
 
         // Convert to lowercase to prevent bypassing the limit by enumerating different
@@ -192,7 +370,8 @@ impl Limiter {
         let canonical_email = email_address.to_lowercase();
         self.inner
             .account_recovery_per_email
-            .check_key(&canonical_email)
+            .check_key(&self.inner.backend, &canonical_email)
+            .await
             .map_err(|_| AccountRecoveryLimitedError::Email(canonical_email))?;
 
         Ok(())
@@ -203,36 +382,56 @@ impl Limiter {
     /// # Errors
     ///
     /// Returns an error if the operation is rate limited
-    pub fn check_password(
+    pub async fn check_password(
         &self,
         key: RequesterFingerprint,
         user: &User,
     ) -> Result<(), PasswordCheckLimitedError> {
         self.inner
             .password_check_for_requester
-            .check_key(&key)
+            .check_key(&self.inner.backend, &key)
+            .await
             .map_err(|_| PasswordCheckLimitedError::Requester(key))?;
 
         self.inner
             .password_check_for_user
-            .check_key(&user.id)
+            .check_key(&self.inner.backend, &user.id)
+            .await
             .map_err(|_| PasswordCheckLimitedError::User(user.id))?;
 
         Ok(())
     }
 
+    /// Check if a password check can be performed, when it is not yet known
+    /// which user the password belongs to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation is rate limited
+    pub async fn check_password_for_requester(
+        &self,
+        key: RequesterFingerprint,
+    ) -> Result<(), PasswordCheckLimitedError> {
+        self.inner
+            .password_check_for_requester
+            .check_key(&self.inner.backend, &key)
+            .await
+            .map_err(|_| PasswordCheckLimitedError::Requester(key))
+    }
+
     /// Check if an account registration can be performed
     ///
     /// # Errors
     ///
     /// Returns an error if the operation is rate limited.
-    pub fn check_registration(
+    pub async fn check_registration(
         &self,
         requester: RequesterFingerprint,
     ) -> Result<(), RegistrationLimitedError> {
         self.inner
             .registration_per_requester
-            .check_key(&requester)
+            .check_key(&self.inner.backend, &requester)
+            .await
             .map_err(|_| RegistrationLimitedError::Requester(requester))?;
 
         Ok(())
@@ -244,14 +443,15 @@ impl Limiter {
     /// # Errors
     ///
     /// Returns an error if the operation is rate limited.
-    pub fn check_email_authentication_email(
+    pub async fn check_email_authentication_email(
         &self,
         requester: RequesterFingerprint,
         email: &str,
     ) -> Result<(), EmailAuthenticationLimitedError> {
         self.inner
             .email_authentication_per_requester
-            .check_key(&requester)
+            .check_key(&self.inner.backend, &requester)
+            .await
             .map_err(|_| EmailAuthenticationLimitedError::Requester(requester))?;
 
         // Convert to lowercase to prevent bypassing the limit by enumerating different
@@ -260,7 +460,8 @@ impl Limiter {
         let canonical_email = email.to_lowercase();
         self.inner
             .email_authentication_per_email
-            .check_key(&canonical_email)
+            .check_key(&self.inner.backend, &canonical_email)
+            .await
             .map_err(|_| EmailAuthenticationLimitedError::Email(email.to_owned()))?;
         Ok(())
     }
@@ -270,13 +471,14 @@ impl Limiter {
     /// # Errors
     ///
     /// Returns an error if the operation is rate limited.
-    pub fn check_email_authentication_attempt(
+    pub async fn check_email_authentication_attempt(
         &self,
         authentication: &UserEmailAuthentication,
     ) -> Result<(), EmailAuthenticationLimitedError> {
         self.inner
             .email_authentication_attempt_per_session
-            .check_key(&authentication.id)
+            .check_key(&self.inner.backend, &authentication.id)
+            .await
             .map_err(|_| EmailAuthenticationLimitedError::Authentication(authentication.id))
     }
 
@@ -286,15 +488,17 @@ impl Limiter {
     /// # Errors
     ///
     /// Returns an error if the operation is rate limited.
-    pub fn check_email_authentication_send_code(
+    pub async fn check_email_authentication_send_code(
         &self,
         requester: RequesterFingerprint,
         authentication: &UserEmailAuthentication,
     ) -> Result<(), EmailAuthenticationLimitedError> {
-        self.check_email_authentication_email(requester, &authentication.email)?;
+        self.check_email_authentication_email(requester, &authentication.email)
+            .await?;
         self.inner
             .email_authentication_emails_per_session
-            .check_key(&authentication.id)
+            .check_key(&self.inner.backend, &authentication.id)
+            .await
             .map_err(|_| EmailAuthenticationLimitedError::Authentication(authentication.id))
     }
 }
@@ -302,12 +506,14 @@ impl Limiter {
 #[cfg(test)]
 mod tests {
     use mas_data_model::{Clock, User, clock::MockClock};
+    use mas_storage_pg::PgRepositoryFactory;
     use rand::SeedableRng;
+    use sqlx::PgPool;
 
     use super::*;
 
-    #[test]
-    fn test_password_check_limiter() {
+    #[tokio::test]
+    async fn test_password_check_limiter() {
         let now = MockClock::default().now();
         let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
 
@@ -327,6 +533,7 @@ mod tests {
             created_at: now,
             locked_at: None,
             deactivated_at: None,
+            erased_at: None,
             can_request_admin: false,
             is_guest: true,
         };
@@ -338,39 +545,100 @@ mod tests {
             created_at: now,
             locked_at: None,
             deactivated_at: None,
+            erased_at: None,
             can_request_admin: false,
             is_guest: true,
         };
 
         // Three times the same IP address should be allowed
-        assert!(limiter.check_password(requesters[0], &alice).is_ok());
-        assert!(limiter.check_password(requesters[0], &alice).is_ok());
-        assert!(limiter.check_password(requesters[0], &alice).is_ok());
+        assert!(limiter.check_password(requesters[0], &alice).await.is_ok());
+        assert!(limiter.check_password(requesters[0], &alice).await.is_ok());
+        assert!(limiter.check_password(requesters[0], &alice).await.is_ok());
 
         // But the fourth time should be rejected
-        assert!(limiter.check_password(requesters[0], &alice).is_err());
+        assert!(limiter.check_password(requesters[0], &alice).await.is_err());
         // Using another user should also be rejected
-        assert!(limiter.check_password(requesters[0], &bob).is_err());
+        assert!(limiter.check_password(requesters[0], &bob).await.is_err());
 
         // Using a different IP address should be allowed, the account isn't locked yet
-        assert!(limiter.check_password(requesters[1], &alice).is_ok());
+        assert!(limiter.check_password(requesters[1], &alice).await.is_ok());
 
         // At this point, we consumed 4 cells out of 1800 on alice, let's distribute the
         // requests with other IPs so that we get rate-limited on the account-level
         for requester in requesters.iter().skip(2).take(598) {
-            assert!(limiter.check_password(*requester, &alice).is_ok());
-            assert!(limiter.check_password(*requester, &alice).is_ok());
-            assert!(limiter.check_password(*requester, &alice).is_ok());
-            assert!(limiter.check_password(*requester, &alice).is_err());
+            assert!(limiter.check_password(*requester, &alice).await.is_ok());
+            assert!(limiter.check_password(*requester, &alice).await.is_ok());
+            assert!(limiter.check_password(*requester, &alice).await.is_ok());
+            assert!(limiter.check_password(*requester, &alice).await.is_err());
         }
 
         // We now have consumed 4+598*3 = 1798 cells on the account, so we should be
         // rejected soon
-        assert!(limiter.check_password(requesters[600], &alice).is_ok());
-        assert!(limiter.check_password(requesters[601], &alice).is_ok());
-        assert!(limiter.check_password(requesters[602], &alice).is_err());
+        assert!(
+            limiter
+                .check_password(requesters[600], &alice)
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_password(requesters[601], &alice)
+                .await
+                .is_ok()
+        );
+        assert!(
+            limiter
+                .check_password(requesters[602], &alice)
+                .await
+                .is_err()
+        );
 
         // The other account isn't rate-limited
-        assert!(limiter.check_password(requesters[603], &bob).is_ok());
+        assert!(limiter.check_password(requesters[603], &bob).await.is_ok());
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_shared_limiter(pool: PgPool) {
+        let config = RateLimitingConfig::default();
+
+        // Two limiters sharing the same database, like two replicas would
+        let first =
+            Limiter::new_shared(&config, PgRepositoryFactory::new(pool.clone()).boxed()).unwrap();
+        let second = Limiter::new_shared(&config, PgRepositoryFactory::new(pool).boxed()).unwrap();
+
+        let requester = RequesterFingerprint::new([127, 0, 0, 1].into());
+        let other = RequesterFingerprint::new([127, 0, 0, 2].into());
+
+        // The default allows three registrations in a burst, across replicas
+        assert!(first.check_registration(requester).await.is_ok());
+        assert!(second.check_registration(requester).await.is_ok());
+        assert!(first.check_registration(requester).await.is_ok());
+        assert!(second.check_registration(requester).await.is_err());
+        assert!(first.check_registration(requester).await.is_err());
+
+        // Other requesters are not affected
+        assert!(second.check_registration(other).await.is_ok());
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_shared_limiter_database_error(pool: PgPool) {
+        let config = RateLimitingConfig::default();
+        let limiter =
+            Limiter::new_shared(&config, PgRepositoryFactory::new(pool.clone()).boxed()).unwrap();
+
+        // Make every database operation fail
+        pool.close().await;
+
+        let requester = RequesterFingerprint::new([127, 0, 0, 1].into());
+        let other = RequesterFingerprint::new([127, 0, 0, 2].into());
+
+        // The limiter falls back to its in-memory state, which still enforces the
+        // limits instead of letting everything through
+        assert!(limiter.check_registration(requester).await.is_ok());
+        assert!(limiter.check_registration(requester).await.is_ok());
+        assert!(limiter.check_registration(requester).await.is_ok());
+        assert!(limiter.check_registration(requester).await.is_err());
+
+        assert!(limiter.check_registration(other).await.is_ok());
     }
 }
@@ -52,7 +52,7 @@ use tower::{Layer, Service, ServiceExt};
 use url::Url;
 
 use crate::{
-    ActivityTracker, BoundActivityTracker, Limiter, RequesterFingerprint, graphql,
+    ActivityTracker, BoundActivityTracker, DPoPReplayCache, Limiter, RequesterFingerprint, graphql,
     passwords::{Hasher, PasswordManager},
     upstream_oauth2::cache::MetadataCache,
 };
@@ -108,6 +108,7 @@ pub(crate) struct TestState {
     pub site_config: SiteConfig,
     pub activity_tracker: ActivityTracker,
     pub limiter: Limiter,
+    pub dpop_replay_cache: DPoPReplayCache,
     pub clock: Arc<MockClock>,
     pub rng: Arc<Mutex<ChaChaRng>>,
     pub http_client: reqwest::Client,
@@ -148,6 +149,10 @@ pub fn test_site_config() -> SiteConfig {
         session_expiration: None,
         login_with_email_allowed: true,
         plan_management_iframe_uri: None,
+        passkeys_enabled: true,
+        totp_enabled: true,
+        deactivated_user_retention: None,
+        webhooks: Vec::new(),
     }
 }
 
@@ -226,6 +231,7 @@ impl TestState {
             password_manager: password_manager.clone(),
             url_builder: url_builder.clone(),
             limiter: limiter.clone(),
+            encrypter: encrypter.clone(),
         };
         let state: crate::graphql::BoxState = Box::new(graphql_state);
 
@@ -252,6 +258,8 @@ impl TestState {
             homeserver_connection.clone(),
             url_builder.clone(),
             &site_config,
+            key_store.clone(),
+            http_client.clone(),
             shutdown_token.child_token(),
         )
         .await
@@ -274,6 +282,7 @@ impl TestState {
             site_config,
             activity_tracker,
             limiter,
+            dpop_replay_cache: DPoPReplayCache::new(),
             clock,
             rng,
             http_client,
@@ -439,6 +448,7 @@ struct TestGraphQLState {
     password_manager: PasswordManager,
     url_builder: UrlBuilder,
     limiter: Limiter,
+    encrypter: Encrypter,
 }
 
 #[async_trait::async_trait]
@@ -475,6 +485,10 @@ impl graphql::State for TestGraphQLState {
         &self.limiter
     }
 
+    fn encrypter(&self) -> &Encrypter {
+        &self.encrypter
+    }
+
     fn rng(&self) -> BoxRng {
         let mut parent_rng = self.rng.lock().expect("Failed to lock RNG");
         let rng = ChaChaRng::from_rng(&mut *parent_rng).expect("Failed to seed RNG");
@@ -572,6 +586,12 @@ impl FromRef<TestState> for Limiter {
     }
 }
 
+impl FromRef<TestState> for DPoPReplayCache {
+    fn from_ref(input: &TestState) -> Self {
+        input.dpop_replay_cache.clone()
+    }
+}
+
 impl FromRef<TestState> for reqwest::Client {
     fn from_ref(input: &TestState) -> Self {
         input.http_client.clone()
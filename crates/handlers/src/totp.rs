@@ -0,0 +1,360 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Time-based one-time passwords (RFC 6238) and recovery codes, used as a
+//! second authentication factor after the password.
+//!
+//! We only support the parameters every authenticator app understands: HMAC
+//! SHA-1, 6 digits and a 30 seconds period.
+
+use base64ct::{Base64, Encoding};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use qrcode::{QrCode, render::svg, types::QrError};
+use rand::{Rng, RngCore, distributions::Uniform};
+use sha1::Sha1;
+use url::Url;
+
+/// Length of the shared secret, in bytes. RFC 4226 recommends 160 bits
+const SECRET_LENGTH: usize = 20;
+
+/// Number of digits in a code
+const DIGITS: u32 = 6;
+
+/// Duration of a time step, in seconds
+const PERIOD: i64 = 30;
+
+/// How many time steps before and after the current one are accepted, to
+/// account for clock drift and slow typers
+const SKEW: i64 = 1;
+
+/// Number of recovery codes generated at once
+const RECOVERY_CODES_COUNT: usize = 10;
+
+/// Alphabet used for recovery codes, without characters which are easily
+/// confused with one another
+const RECOVERY_CODE_ALPHABET: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+
+/// A TOTP generator, derived from a shared secret
+#[derive(Clone)]
+pub struct Totp {
+    secret: Vec<u8>,
+}
+
+impl std::fmt::Debug for Totp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Totp").finish_non_exhaustive()
+    }
+}
+
+impl Totp {
+    /// Generate a new random shared secret
+    pub fn generate(rng: &mut (impl RngCore + ?Sized)) -> Self {
+        let mut secret = vec![0; SECRET_LENGTH];
+        rng.fill_bytes(&mut secret);
+        Self { secret }
+    }
+
+    /// Create a TOTP generator from an existing shared secret
+    pub fn from_secret(secret: Vec<u8>) -> Self {
+        Self { secret }
+    }
+
+    /// The raw shared secret, to be encrypted before being stored
+    pub fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    /// The shared secret, encoded in base32 as authenticator apps expect it
+    /// when entered manually
+    pub fn secret_base32(&self) -> String {
+        base32_encode(&self.secret)
+    }
+
+    /// The `otpauth://` URI, as understood by authenticator apps
+    ///
+    /// See <https://github.com/google/google-authenticator/wiki/Key-Uri-Format>
+    pub fn uri(&self, issuer: &str, account: &str) -> Url {
+        // The label is part of the path, so it needs to be percent-encoded,
+        // which `set_path` takes care of
+        let mut url = Url::parse("otpauth://totp/").expect("valid base URL");
+        url.set_path(&format!("{issuer}:{account}"));
+        url.query_pairs_mut()
+            .append_pair("secret", &self.secret_base32())
+            .append_pair("issuer", issuer);
+        url
+    }
+
+    /// Render the `otpauth://` URI as a QR code, as an SVG `data:` URI which
+    /// can be used directly as an image source
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URI is too long to fit in a QR code
+    pub fn qr_code(&self, issuer: &str, account: &str) -> Result<String, QrError> {
+        let code = QrCode::new(self.uri(issuer, account).as_str())?;
+        let image = code
+            .render::<svg::Color<'_>>()
+            .min_dimensions(200, 200)
+            .build();
+        Ok(format!(
+            "data:image/svg+xml;base64,{}",
+            Base64::encode_string(image.as_bytes())
+        ))
+    }
+
+    /// Compute the code for the given time step
+    fn code_at_step(&self, step: i64) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(&self.secret).expect("HMAC accepts any key");
+        mac.update(&step.to_be_bytes());
+        let hash = mac.finalize().into_bytes();
+
+        // Dynamic truncation, see RFC 4226 section 5.3
+        let offset = usize::from(hash[hash.len() - 1] & 0x0f);
+        let binary = u32::from_be_bytes([
+            hash[offset] & 0x7f,
+            hash[offset + 1],
+            hash[offset + 2],
+            hash[offset + 3],
+        ]);
+
+        format!(
+            "{:0width$}",
+            binary % 10u32.pow(DIGITS),
+            width = DIGITS as usize
+        )
+    }
+
+    /// Compute the code valid at the given instant
+    #[cfg(test)]
+    pub fn code_at(&self, now: DateTime<Utc>) -> String {
+        self.code_at_step(step(now))
+    }
+
+    /// Check a code submitted by the user
+    ///
+    /// Returns the time step of the code if it is valid. Codes from time steps
+    /// at or before the one of the last code used are rejected, so that a
+    /// code can't be replayed. The returned step must be recorded, with a
+    /// check that it still moves forward, for this to hold.
+    pub fn verify(
+        &self,
+        code: &str,
+        now: DateTime<Utc>,
+        last_used_step: Option<i64>,
+    ) -> Option<i64> {
+        let code = code.trim();
+        if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let current = step(now);
+
+        (current - SKEW..=current + SKEW)
+            .filter(|s| last_used_step.is_none_or(|last| *s > last))
+            .find(|s| constant_time_eq(self.code_at_step(*s).as_bytes(), code.as_bytes()))
+    }
+}
+
+/// Generate a fresh set of recovery codes, formatted as `xxxx-xxxx`
+pub fn generate_recovery_codes(rng: &mut (impl RngCore + ?Sized)) -> Vec<String> {
+    let distribution = Uniform::from(0..RECOVERY_CODE_ALPHABET.len());
+    (0..RECOVERY_CODES_COUNT)
+        .map(|_| {
+            let chars: Vec<char> = (0..8)
+                .map(|_| char::from(RECOVERY_CODE_ALPHABET[rng.sample(distribution)]))
+                .collect();
+            format!(
+                "{}-{}",
+                chars[..4].iter().collect::<String>(),
+                chars[4..].iter().collect::<String>()
+            )
+        })
+        .collect()
+}
+
+/// Normalize a recovery code entered by the user, so that it can be compared
+/// with the generated one
+pub fn normalize_recovery_code(code: &str) -> String {
+    let code: String = code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    if code.len() == 8 {
+        format!("{}-{}", &code[..4], &code[4..])
+    } else {
+        code
+    }
+}
+
+/// Whether the input looks like a TOTP code rather than a recovery code
+pub fn looks_like_totp_code(code: &str) -> bool {
+    let code = code.trim();
+    code.len() == DIGITS as usize && code.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn step(now: DateTime<Utc>) -> i64 {
+    now.timestamp().div_euclid(PERIOD)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Encode bytes in base32 (RFC 4648), without padding
+fn base32_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer: u16 = 0;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(char::from(ALPHABET[usize::from((buffer >> bits) & 0x1f)]));
+        }
+    }
+
+    if bits > 0 {
+        out.push(char::from(
+            ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)],
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn rfc_totp() -> Totp {
+        // The SHA-1 secret from the RFC 6238 test vectors
+        Totp::from_secret(b"12345678901234567890".to_vec())
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        // RFC 6238 uses 8 digits, the last 6 are what we compute
+        let vectors = [
+            (59, "94287082"),
+            (1_111_111_109, "07081804"),
+            (1_111_111_111, "14050471"),
+            (1_234_567_890, "89005924"),
+            (2_000_000_000, "69279037"),
+            (20_000_000_000, "65353130"),
+        ];
+
+        let totp = rfc_totp();
+        for (timestamp, expected) in vectors {
+            let now = Utc.timestamp_opt(timestamp, 0).unwrap();
+            assert_eq!(totp.code_at(now), &expected[2..]);
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let totp = rfc_totp();
+        let now = Utc.timestamp_opt(1_111_111_109, 0).unwrap();
+        let current = step(now);
+        let code = totp.code_at(now);
+
+        assert_eq!(totp.verify(&code, now, None), Some(current));
+        assert_eq!(totp.verify(&format!(" {code} "), now, None), Some(current));
+        assert_eq!(totp.verify("000000", now, None), None);
+        assert_eq!(totp.verify("abcdef", now, None), None);
+
+        // Accepted one step later, but not two
+        assert_eq!(
+            totp.verify(&code, now + chrono::Duration::seconds(30), None),
+            Some(current)
+        );
+        assert_eq!(
+            totp.verify(&code, now + chrono::Duration::seconds(90), None),
+            None
+        );
+
+        // Can't be replayed once used
+        assert_eq!(totp.verify(&code, now, Some(current)), None);
+        let next = totp.code_at(now + chrono::Duration::seconds(30));
+        assert_eq!(
+            totp.verify(&next, now + chrono::Duration::seconds(30), Some(current)),
+            Some(current + 1)
+        );
+    }
+
+    #[test]
+    fn test_verify_skew_replay() {
+        let totp = rfc_totp();
+        let now = Utc.timestamp_opt(1_111_111_109, 0).unwrap();
+        let current = step(now);
+
+        // A code from the next step is accepted because of clock drift...
+        let next = totp.code_at(now + chrono::Duration::seconds(30));
+        assert_eq!(totp.verify(&next, now, None), Some(current + 1));
+
+        // ...after which it can't be used again, even once it is the current
+        // code, and neither can the code from the current step
+        let code = totp.code_at(now);
+        assert_eq!(totp.verify(&next, now, Some(current + 1)), None);
+        assert_eq!(
+            totp.verify(
+                &next,
+                now + chrono::Duration::seconds(30),
+                Some(current + 1)
+            ),
+            None
+        );
+        assert_eq!(totp.verify(&code, now, Some(current + 1)), None);
+    }
+
+    #[test]
+    fn test_base32() {
+        // Test vectors from RFC 4648, without the padding
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"fo"), "MZXQ");
+        assert_eq!(base32_encode(b"foo"), "MZXW6");
+        assert_eq!(base32_encode(b"foob"), "MZXW6YQ");
+        assert_eq!(base32_encode(b"fooba"), "MZXW6YTB");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn test_uri() {
+        let totp = rfc_totp();
+        assert_eq!(
+            totp.uri("example.com", "john").as_str(),
+            "otpauth://totp/example.com:john?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=example.com"
+        );
+        assert!(
+            totp.qr_code("example.com", "john")
+                .unwrap()
+                .starts_with("data:image/svg+xml;base64,")
+        );
+    }
+
+    #[test]
+    fn test_recovery_codes() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let codes = generate_recovery_codes(&mut rng);
+        assert_eq!(codes.len(), RECOVERY_CODES_COUNT);
+        for code in &codes {
+            assert_eq!(code.len(), 9);
+            assert_eq!(normalize_recovery_code(code), *code);
+            assert_eq!(normalize_recovery_code(&code.to_uppercase()), *code);
+            assert_eq!(normalize_recovery_code(&code.replace('-', " ")), *code);
+            assert!(!looks_like_totp_code(code));
+        }
+        assert!(looks_like_totp_code("123456"));
+    }
+}
@@ -6,27 +6,41 @@
 
 use axum::{
     extract::{Path, State},
-    response::{IntoResponse, Redirect},
+    response::{Html, IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::Query;
 use hyper::StatusCode;
 use mas_axum_utils::{GenericError, InternalError, cookies::CookieJar};
-use mas_data_model::{BoxClock, BoxRng, UpstreamOAuthProvider};
+use mas_data_model::{BoxClock, BoxRng, Clock, UpstreamOAuthProvider};
+use mas_keystore::Keystore;
 use mas_oidc_client::requests::authorization_code::AuthorizationRequestData;
 use mas_router::{PostAuthAction, UrlBuilder};
 use mas_storage::{
     BoxRepository,
     upstream_oauth2::{UpstreamOAuthProviderRepository, UpstreamOAuthSessionRepository},
 };
+use mas_templates::{FormPostContext, Templates};
+use rand::distributions::{Alphanumeric, DistString};
+use serde::Serialize;
 use thiserror::Error;
 use ulid::Ulid;
 
-use super::{UpstreamSessionsCookie, cache::LazyProviderInfos};
+use super::{UpstreamSessionsCookie, cache::LazyProviderInfos, saml};
 use crate::{
-    impl_from_error_for_route, upstream_oauth2::cache::MetadataCache,
+    PreferredLanguage, impl_from_error_for_route, upstream_oauth2::cache::MetadataCache,
     views::shared::OptionalPostAuthAction,
 };
 
+/// The parameters of the HTTP-POST binding of SAML 2.0
+#[derive(Serialize)]
+struct SamlRequestForm {
+    #[serde(rename = "SAMLRequest")]
+    saml_request: String,
+
+    #[serde(rename = "RelayState")]
+    relay_state: String,
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum RouteError {
     #[error("Provider not found")]
@@ -39,6 +53,8 @@ pub(crate) enum RouteError {
 impl_from_error_for_route!(mas_oidc_client::error::DiscoveryError);
 impl_from_error_for_route!(mas_oidc_client::error::AuthorizationError);
 impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(mas_templates::TemplateError);
+impl_from_error_for_route!(saml::MissingSigningKey);
 
 impl IntoResponse for RouteError {
     fn into_response(self) -> axum::response::Response {
@@ -56,6 +72,7 @@ impl IntoResponse for RouteError {
     fields(upstream_oauth_provider.id = %provider_id),
     skip_all,
 )]
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn get(
     mut rng: BoxRng,
     clock: BoxClock,
@@ -63,10 +80,13 @@ pub(crate) async fn get(
     mut repo: BoxRepository,
     State(url_builder): State<UrlBuilder>,
     State(http_client): State<reqwest::Client>,
+    State(keystore): State<Keystore>,
+    State(templates): State<Templates>,
+    PreferredLanguage(locale): PreferredLanguage,
     cookie_jar: CookieJar,
     Path(provider_id): Path<Ulid>,
     Query(query): Query<OptionalPostAuthAction>,
-) -> Result<impl IntoResponse, RouteError> {
+) -> Result<Response, RouteError> {
     let provider = repo
         .upstream_oauth_provider()
         .lookup(provider_id)
@@ -74,6 +94,57 @@ pub(crate) async fn get(
         .filter(UpstreamOAuthProvider::enabled)
         .ok_or(RouteError::ProviderNotFound)?;
 
+    // SAML 2.0 providers get a signed AuthnRequest, posted through the browser
+    if let Some(settings) = &provider.saml {
+        let request_id = saml::generate_request_id(&mut rng);
+        let relay_state = Alphanumeric.sample_string(&mut rng, 16);
+
+        let saml_request = saml::AuthnRequest {
+            id: &request_id,
+            issue_instant: clock.now(),
+            destination: &settings.sso_url,
+            assertion_consumer_service_url: &url_builder.upstream_saml_acs(provider.id),
+            issuer: &provider.client_id,
+            name_id_format: settings.name_id_format.as_deref(),
+        }
+        .sign(&mut rng, saml::signing_key(&keystore)?);
+
+        // The request ID is saved as the nonce, to check the `InResponseTo` of
+        // the response
+        let session = repo
+            .upstream_oauth_session()
+            .add(
+                &mut rng,
+                &clock,
+                &provider,
+                relay_state.clone(),
+                None,
+                Some(request_id),
+            )
+            .await?;
+
+        let cookie_jar = UpstreamSessionsCookie::load(&cookie_jar)
+            .add(
+                session.id,
+                provider.id,
+                relay_state.clone(),
+                query.post_auth_action,
+            )
+            .save(cookie_jar, &clock);
+
+        repo.save().await?;
+
+        let form = SamlRequestForm {
+            saml_request,
+            relay_state,
+        };
+        let context =
+            FormPostContext::new_for_url(settings.sso_url.clone(), form).with_language(&locale);
+        let html = templates.render_form_post(&context)?;
+
+        return Ok((cookie_jar, Html(html)).into_response());
+    }
+
     // First, discover the provider
     // This is done lazyly according to provider.discovery_mode and the various
     // endpoint overrides
@@ -145,5 +216,5 @@ pub(crate) async fn get(
 
     repo.save().await?;
 
-    Ok((cookie_jar, Redirect::temporary(url.as_str())))
+    Ok((cookie_jar, Redirect::temporary(url.as_str())).into_response())
 }
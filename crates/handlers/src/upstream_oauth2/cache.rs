@@ -268,6 +268,14 @@ impl MetadataCache {
         Ok(metadata)
     }
 
+    /// Remove the metadata for the given issuer from the cache, so that it is
+    /// fetched again the next time it is needed.
+    #[tracing::instrument(name = "metadata_cache.invalidate", fields(%issuer), skip_all)]
+    pub async fn invalidate(&self, issuer: &str) {
+        self.cache.write().await.remove(issuer);
+        self.insecure_cache.write().await.remove(issuer);
+    }
+
     #[tracing::instrument(name = "metadata_cache.refresh_all", skip_all)]
     async fn refresh_all(&self, client: &reqwest::Client) {
         // Grab all the keys first to avoid locking the cache for too long
@@ -330,7 +338,7 @@ mod tests {
             .await
             .unwrap_err();
 
-        let expected_calls = 3;
+        let expected_calls = 4;
         let mut calls = 0;
         let _mock_guard = Mock::given(method("GET"))
             .and(path("/.well-known/openid-configuration"))
@@ -376,6 +384,14 @@ mod tests {
         cache.refresh_all(&http_client).await;
         calls += 1;
 
+        // Invalidating the issuer should trigger a new fetch on the next call
+        cache.invalidate(&mock_server.uri()).await;
+        cache
+            .get(&http_client, &mock_server.uri(), false)
+            .await
+            .unwrap();
+        calls += 1;
+
         assert_eq!(calls, expected_calls);
     }
 
@@ -410,6 +426,7 @@ mod tests {
         let clock = MockClock::default();
         let provider = UpstreamOAuthProvider {
             id: Ulid::nil(),
+            is_static: true,
             issuer: Some(mock_server.uri()),
             human_name: Some("Example Ltd.".to_owned()),
             brand_name: None,
@@ -434,6 +451,7 @@ mod tests {
             additional_authorization_parameters: Vec::new(),
             forward_login_hint: false,
             on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
         };
 
         // Without any override, it should just use discovery
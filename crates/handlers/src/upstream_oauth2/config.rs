@@ -0,0 +1,188 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Conversion of upstream OAuth 2.0 providers, as defined in the configuration
+//! file, to the parameters stored in the database.
+
+use mas_config::UpstreamOAuth2Provider;
+use mas_storage::upstream_oauth2::UpstreamOAuthProviderParams;
+use oauth2_types::scope::InvalidScope;
+
+fn map_import_action(
+    config: mas_config::UpstreamOAuth2ImportAction,
+) -> mas_data_model::UpstreamOAuthProviderImportAction {
+    match config {
+        mas_config::UpstreamOAuth2ImportAction::Ignore => {
+            mas_data_model::UpstreamOAuthProviderImportAction::Ignore
+        }
+        mas_config::UpstreamOAuth2ImportAction::Suggest => {
+            mas_data_model::UpstreamOAuthProviderImportAction::Suggest
+        }
+        mas_config::UpstreamOAuth2ImportAction::Force => {
+            mas_data_model::UpstreamOAuthProviderImportAction::Force
+        }
+        mas_config::UpstreamOAuth2ImportAction::Require => {
+            mas_data_model::UpstreamOAuthProviderImportAction::Require
+        }
+    }
+}
+
+fn map_import_on_conflict(
+    config: mas_config::UpstreamOAuth2OnConflict,
+) -> mas_data_model::UpstreamOAuthProviderOnConflict {
+    match config {
+        mas_config::UpstreamOAuth2OnConflict::Add => {
+            mas_data_model::UpstreamOAuthProviderOnConflict::Add
+        }
+        mas_config::UpstreamOAuth2OnConflict::Fail => {
+            mas_data_model::UpstreamOAuthProviderOnConflict::Fail
+        }
+    }
+}
+
+fn map_claims_imports(
+    config: &mas_config::UpstreamOAuth2ClaimsImports,
+) -> mas_data_model::UpstreamOAuthProviderClaimsImports {
+    mas_data_model::UpstreamOAuthProviderClaimsImports {
+        subject: mas_data_model::UpstreamOAuthProviderSubjectPreference {
+            template: config.subject.template.clone(),
+        },
+        localpart: mas_data_model::UpstreamOAuthProviderLocalpartPreference {
+            action: map_import_action(config.localpart.action),
+            template: config.localpart.template.clone(),
+            on_conflict: map_import_on_conflict(config.localpart.on_conflict),
+        },
+        displayname: mas_data_model::UpstreamOAuthProviderImportPreference {
+            action: map_import_action(config.displayname.action),
+            template: config.displayname.template.clone(),
+        },
+        email: mas_data_model::UpstreamOAuthProviderImportPreference {
+            action: map_import_action(config.email.action),
+            template: config.email.template.clone(),
+        },
+        account_name: mas_data_model::UpstreamOAuthProviderSubjectPreference {
+            template: config.account_name.template.clone(),
+        },
+    }
+}
+
+/// Convert a provider from the configuration to the parameters stored in the
+/// database.
+///
+/// The client secret must already be encrypted, as the way it is obtained
+/// depends on the caller.
+///
+/// # Errors
+///
+/// Returns an error if the scope of the provider is invalid
+pub fn provider_params_from_config(
+    provider: UpstreamOAuth2Provider,
+    encrypted_client_secret: Option<String>,
+    ui_order: i32,
+) -> Result<UpstreamOAuthProviderParams, InvalidScope> {
+    let discovery_mode = match provider.discovery_mode {
+        mas_config::UpstreamOAuth2DiscoveryMode::Oidc => {
+            mas_data_model::UpstreamOAuthProviderDiscoveryMode::Oidc
+        }
+        mas_config::UpstreamOAuth2DiscoveryMode::Insecure => {
+            mas_data_model::UpstreamOAuthProviderDiscoveryMode::Insecure
+        }
+        mas_config::UpstreamOAuth2DiscoveryMode::Disabled => {
+            mas_data_model::UpstreamOAuthProviderDiscoveryMode::Disabled
+        }
+    };
+
+    let token_endpoint_auth_method = match provider.token_endpoint_auth_method {
+        mas_config::UpstreamOAuth2TokenAuthMethod::None => {
+            mas_data_model::UpstreamOAuthProviderTokenAuthMethod::None
+        }
+        mas_config::UpstreamOAuth2TokenAuthMethod::ClientSecretBasic => {
+            mas_data_model::UpstreamOAuthProviderTokenAuthMethod::ClientSecretBasic
+        }
+        mas_config::UpstreamOAuth2TokenAuthMethod::ClientSecretPost => {
+            mas_data_model::UpstreamOAuthProviderTokenAuthMethod::ClientSecretPost
+        }
+        mas_config::UpstreamOAuth2TokenAuthMethod::ClientSecretJwt => {
+            mas_data_model::UpstreamOAuthProviderTokenAuthMethod::ClientSecretJwt
+        }
+        mas_config::UpstreamOAuth2TokenAuthMethod::PrivateKeyJwt => {
+            mas_data_model::UpstreamOAuthProviderTokenAuthMethod::PrivateKeyJwt
+        }
+        mas_config::UpstreamOAuth2TokenAuthMethod::SignInWithApple => {
+            mas_data_model::UpstreamOAuthProviderTokenAuthMethod::SignInWithApple
+        }
+    };
+
+    let response_mode = provider
+        .response_mode
+        .map(|response_mode| match response_mode {
+            mas_config::UpstreamOAuth2ResponseMode::Query => {
+                mas_data_model::UpstreamOAuthProviderResponseMode::Query
+            }
+            mas_config::UpstreamOAuth2ResponseMode::FormPost => {
+                mas_data_model::UpstreamOAuthProviderResponseMode::FormPost
+            }
+        });
+
+    let pkce_mode = match provider.pkce_method {
+        mas_config::UpstreamOAuth2PkceMethod::Auto => {
+            mas_data_model::UpstreamOAuthProviderPkceMode::Auto
+        }
+        mas_config::UpstreamOAuth2PkceMethod::Always => {
+            mas_data_model::UpstreamOAuthProviderPkceMode::S256
+        }
+        mas_config::UpstreamOAuth2PkceMethod::Never => {
+            mas_data_model::UpstreamOAuthProviderPkceMode::Disabled
+        }
+    };
+
+    let on_backchannel_logout = match provider.on_backchannel_logout {
+        mas_config::UpstreamOAuth2OnBackchannelLogout::DoNothing => {
+            mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing
+        }
+        mas_config::UpstreamOAuth2OnBackchannelLogout::LogoutBrowserOnly => {
+            mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::LogoutBrowserOnly
+        }
+        mas_config::UpstreamOAuth2OnBackchannelLogout::LogoutAll => {
+            mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::LogoutAll
+        }
+    };
+
+    Ok(UpstreamOAuthProviderParams {
+        issuer: provider.issuer,
+        human_name: provider.human_name,
+        brand_name: provider.brand_name,
+        scope: provider.scope.parse()?,
+        token_endpoint_auth_method,
+        token_endpoint_signing_alg: provider.token_endpoint_auth_signing_alg,
+        id_token_signed_response_alg: provider.id_token_signed_response_alg,
+        client_id: provider.client_id,
+        encrypted_client_secret,
+        claims_imports: map_claims_imports(&provider.claims_imports),
+        token_endpoint_override: provider.token_endpoint,
+        userinfo_endpoint_override: provider.userinfo_endpoint,
+        authorization_endpoint_override: provider.authorization_endpoint,
+        jwks_uri_override: provider.jwks_uri,
+        discovery_mode,
+        pkce_mode,
+        fetch_userinfo: provider.fetch_userinfo,
+        userinfo_signed_response_alg: provider.userinfo_signed_response_alg,
+        response_mode,
+        additional_authorization_parameters: provider
+            .additional_authorization_parameters
+            .into_iter()
+            .collect(),
+        forward_login_hint: provider.forward_login_hint,
+        ui_order,
+        on_backchannel_logout,
+        saml: provider
+            .saml
+            .map(|saml| mas_data_model::UpstreamOAuthProviderSamlSettings {
+                sso_url: saml.sso_url,
+                certificates: saml.certificates,
+                name_id_format: saml.name_id_format,
+            }),
+    })
+}
@@ -19,14 +19,17 @@ use mas_axum_utils::{
     csrf::{CsrfExt, ProtectedForm},
     record_error,
 };
-use mas_data_model::{BoxClock, BoxRng, UpstreamOAuthProviderOnConflict};
+use mas_data_model::{BoxClock, BoxRng, SecurityNotification, UpstreamOAuthProviderOnConflict};
 use mas_jose::jwt::Jwt;
 use mas_matrix::HomeserverConnection;
 use mas_policy::Policy;
 use mas_router::UrlBuilder;
 use mas_storage::{
     BoxRepository, RepositoryAccess,
-    queue::{ProvisionUserJob, QueueJobRepositoryExt as _},
+    queue::{
+        DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _,
+        SendSecurityNotificationJob,
+    },
     upstream_oauth2::{UpstreamOAuthLinkRepository, UpstreamOAuthSessionRepository},
     user::{BrowserSessionRepository, UserEmailRepository, UserRepository},
 };
@@ -349,6 +352,14 @@ pub(crate) async fn get(
                 .add(&mut rng, &clock, &user, user_agent)
                 .await?;
 
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    DispatchWebhookEventJob::browser_session_created(&session),
+                )
+                .await?;
+
             let upstream_session = repo
                 .upstream_oauth_session()
                 .consume(&clock, upstream_session)
@@ -649,6 +660,24 @@ pub(crate) async fn post(
                 .associate_to_user(&link, &session.user)
                 .await?;
 
+            let provider = repo
+                .upstream_oauth_provider()
+                .lookup(link.provider_id)
+                .await?
+                .ok_or(RouteError::ProviderNotFound(link.provider_id))?;
+
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    SendSecurityNotificationJob::new(
+                        &session.user,
+                        SecurityNotification::upstream_account_linked(&provider),
+                    )
+                    .with_language(locale.to_string()),
+                )
+                .await?;
+
             session
         }
 
@@ -706,7 +735,7 @@ pub(crate) async fn post(
                 return Err(RouteError::InvalidFormAction);
             };
 
-            let on_conflict = provider.claims_imports.localpart.on_conflict;
+            let on_conflict = &provider.claims_imports.localpart.on_conflict;
 
             match on_conflict {
                 UpstreamOAuthProviderOnConflict::Fail => {
@@ -719,9 +748,32 @@ pub(crate) async fn post(
                         .associate_to_user(&link, &user)
                         .await?;
 
-                    repo.browser_session()
+                    repo.queue_job()
+                        .schedule_job(
+                            &mut rng,
+                            &clock,
+                            SendSecurityNotificationJob::new(
+                                &user,
+                                SecurityNotification::upstream_account_linked(&provider),
+                            )
+                            .with_language(locale.to_string()),
+                        )
+                        .await?;
+
+                    let session = repo
+                        .browser_session()
                         .add(&mut rng, &clock, &user, user_agent)
-                        .await?
+                        .await?;
+
+                    repo.queue_job()
+                        .schedule_job(
+                            &mut rng,
+                            &clock,
+                            DispatchWebhookEventJob::browser_session_created(&session),
+                        )
+                        .await?;
+
+                    session
                 }
             }
         }
@@ -969,6 +1021,14 @@ pub(crate) async fn post(
 
             repo.queue_job().schedule_job(&mut rng, &clock, job).await?;
 
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    DispatchWebhookEventJob::user_registered(&user),
+                )
+                .await?;
+
             // If we have an email, add it to the user
             if let Some(email) = email {
                 repo.user_email()
@@ -980,9 +1040,20 @@ pub(crate) async fn post(
                 .associate_to_user(&link, &user)
                 .await?;
 
-            repo.browser_session()
+            let session = repo
+                .browser_session()
                 .add(&mut rng, &clock, &user, user_agent)
-                .await?
+                .await?;
+
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    DispatchWebhookEventJob::browser_session_created(&session),
+                )
+                .await?;
+
+            session
         }
 
         _ => return Err(RouteError::InvalidFormAction),
@@ -1105,6 +1176,7 @@ mod tests {
                     ui_order: 0,
                     on_backchannel_logout:
                         mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                 },
             )
             .await
@@ -1280,6 +1352,7 @@ mod tests {
                     forward_login_hint: false,
                     on_backchannel_logout:
                         mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                     ui_order: 0,
                 },
             )
@@ -1418,6 +1491,7 @@ mod tests {
                     forward_login_hint: false,
                     on_backchannel_logout:
                         mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                     ui_order: 0,
                 },
             )
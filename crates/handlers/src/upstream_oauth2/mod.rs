@@ -19,9 +19,11 @@ pub(crate) mod authorize;
 pub(crate) mod backchannel_logout;
 pub(crate) mod cache;
 pub(crate) mod callback;
+pub mod config;
 mod cookie;
 pub(crate) mod link;
-mod template;
+pub(crate) mod saml;
+pub(crate) mod template;
 
 use self::cookie::UpstreamSessions as UpstreamSessionsCookie;
 
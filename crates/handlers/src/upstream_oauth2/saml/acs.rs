@@ -0,0 +1,297 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::sync::LazyLock;
+
+use axum::{
+    Form,
+    extract::{Path, State},
+    response::{Html, IntoResponse, Response},
+};
+use hyper::StatusCode;
+use mas_axum_utils::{GenericError, InternalError, cookies::CookieJar};
+use mas_data_model::{BoxClock, BoxRng, Clock, UpstreamOAuthProvider};
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxRepository,
+    upstream_oauth2::{
+        UpstreamOAuthLinkRepository, UpstreamOAuthProviderRepository,
+        UpstreamOAuthSessionRepository,
+    },
+};
+use mas_templates::{FormPostContext, Templates};
+use opentelemetry::{Key, KeyValue, metrics::Counter};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use ulid::Ulid;
+
+use super::{
+    certificate_keys,
+    response::{Expectations, ResponseError},
+};
+use crate::{
+    METER, PreferredLanguage, impl_from_error_for_route,
+    upstream_oauth2::{
+        UpstreamSessionsCookie,
+        template::{AttributeMappingContext, environment},
+    },
+};
+
+static ACS_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    METER
+        .u64_counter("mas.upstream_saml.acs")
+        .with_description("Number of requests to the upstream SAML 2.0 assertion consumer service")
+        .build()
+});
+const PROVIDER: Key = Key::from_static_str("provider");
+const RESULT: Key = Key::from_static_str("result");
+
+#[derive(Serialize, Deserialize)]
+pub struct Params {
+    #[serde(rename = "SAMLResponse")]
+    saml_response: String,
+
+    #[serde(rename = "RelayState", skip_serializing_if = "Option::is_none")]
+    relay_state: Option<String>,
+
+    /// An extra parameter to track whether the POST request was re-made by us
+    /// to the same URL to escape Same-Site cookies restrictions
+    #[serde(default)]
+    did_mas_repost_to_itself: bool,
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error("Session not found")]
+    SessionNotFound,
+
+    #[error("Provider not found")]
+    ProviderNotFound,
+
+    #[error("Provider mismatch")]
+    ProviderMismatch,
+
+    #[error("Session already completed")]
+    AlreadyCompleted,
+
+    #[error("RelayState parameter mismatch")]
+    StateMismatch,
+
+    #[error("Missing RelayState parameter")]
+    MissingState,
+
+    #[error("Missing session cookie")]
+    MissingCookie,
+
+    #[error("Invalid SAML response")]
+    InvalidResponse(#[from] ResponseError),
+
+    #[error("Could not extract subject from the assertion")]
+    ExtractSubject(#[source] minijinja::Error),
+
+    #[error("Subject is empty")]
+    EmptySubject,
+
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
+}
+
+impl_from_error_for_route!(mas_templates::TemplateError);
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(super::CertificateError);
+impl_from_error_for_route!(crate::upstream_oauth2::cookie::UpstreamSessionNotFound);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Internal(e) => InternalError::new(e).into_response(),
+            e @ (Self::ProviderNotFound | Self::SessionNotFound) => {
+                GenericError::new(StatusCode::NOT_FOUND, e).into_response()
+            }
+            e => GenericError::new(StatusCode::BAD_REQUEST, e).into_response(),
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "handlers.upstream_oauth2.saml.acs.post",
+    fields(upstream_oauth_provider.id = %provider_id),
+    skip_all,
+)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    mut repo: BoxRepository,
+    State(url_builder): State<UrlBuilder>,
+    State(templates): State<Templates>,
+    PreferredLanguage(locale): PreferredLanguage,
+    cookie_jar: CookieJar,
+    Path(provider_id): Path<Ulid>,
+    Form(params): Form<Params>,
+) -> Result<Response, RouteError> {
+    let provider = repo
+        .upstream_oauth_provider()
+        .lookup(provider_id)
+        .await?
+        .filter(UpstreamOAuthProvider::enabled)
+        .ok_or(RouteError::ProviderNotFound)?;
+
+    let Some(settings) = &provider.saml else {
+        return Err(RouteError::ProviderNotFound);
+    };
+
+    let sessions_cookie = UpstreamSessionsCookie::load(&cookie_jar);
+
+    // The identity provider makes a cross-site POST to this endpoint, which
+    // doesn't carry our `Same-Site=Lax` cookies. Like in the OAuth 2.0
+    // callback, we post the same form to ourselves to get them.
+    if sessions_cookie.is_empty() && !params.did_mas_repost_to_itself {
+        let params = Params {
+            did_mas_repost_to_itself: true,
+            ..params
+        };
+        let context = FormPostContext::new_for_current_url(params).with_language(&locale);
+        let html = templates.render_form_post(&context)?;
+        return Ok(Html(html).into_response());
+    }
+
+    let Some(relay_state) = params.relay_state else {
+        return Err(RouteError::MissingState);
+    };
+
+    let (session_id, _post_auth_action) = sessions_cookie
+        .find_session(provider_id, &relay_state)
+        .map_err(|_| RouteError::MissingCookie)?;
+
+    let session = repo
+        .upstream_oauth_session()
+        .lookup(session_id)
+        .await?
+        .ok_or(RouteError::SessionNotFound)?;
+
+    if provider.id != session.provider_id {
+        return Err(RouteError::ProviderMismatch);
+    }
+
+    if relay_state != session.state_str {
+        return Err(RouteError::StateMismatch);
+    }
+
+    if !session.is_pending() {
+        return Err(RouteError::AlreadyCompleted);
+    }
+
+    // The ID of the AuthnRequest was saved as the nonce of the session
+    let request_id = session
+        .nonce
+        .as_deref()
+        .ok_or(ResponseError::InResponseTo)?;
+
+    let keys = certificate_keys(settings)?;
+    let recipient = url_builder.upstream_saml_acs(provider.id);
+    let expectations = Expectations {
+        issuer: provider.issuer.as_deref().unwrap_or_default(),
+        audience: &provider.client_id,
+        recipient: recipient.as_str(),
+        request_id,
+        keys: &keys,
+        now: clock.now(),
+    };
+
+    let attributes = match expectations.validate(&params.saml_response) {
+        Ok(attributes) => attributes,
+        Err(e) => {
+            ACS_COUNTER.add(
+                1,
+                &[
+                    KeyValue::new(PROVIDER, provider_id.to_string()),
+                    KeyValue::new(RESULT, "error"),
+                ],
+            );
+            return Err(e.into());
+        }
+    };
+
+    ACS_COUNTER.add(
+        1,
+        &[
+            KeyValue::new(PROVIDER, provider_id.to_string()),
+            KeyValue::new(RESULT, "success"),
+        ],
+    );
+
+    // The attributes are saved as the userinfo of the session, so that they
+    // are available as `user` in the claims imports templates
+    let userinfo = serde_json::Value::Object(attributes);
+    let context = AttributeMappingContext::new()
+        .with_userinfo_claims(userinfo.clone())
+        .build();
+
+    let env = environment();
+
+    let template = provider
+        .claims_imports
+        .subject
+        .template
+        .as_deref()
+        .unwrap_or("{{ user.sub }}");
+    let subject = env
+        .render_str(template, context.clone())
+        .map_err(RouteError::ExtractSubject)?;
+
+    if subject.is_empty() {
+        return Err(RouteError::EmptySubject);
+    }
+
+    // Look for an existing link
+    let maybe_link = repo
+        .upstream_oauth_link()
+        .find_by_subject(&provider, &subject)
+        .await?;
+
+    let link = if let Some(link) = maybe_link {
+        link
+    } else {
+        // Try to render the human account name if we have one,
+        // but just log if it fails
+        let human_account_name = provider
+            .claims_imports
+            .account_name
+            .template
+            .as_deref()
+            .and_then(|template| match env.render_str(template, context) {
+                Ok(name) => Some(name),
+                Err(e) => {
+                    tracing::warn!(
+                        error = &e as &dyn std::error::Error,
+                        "Failed to render account name"
+                    );
+                    None
+                }
+            });
+
+        repo.upstream_oauth_link()
+            .add(&mut rng, &clock, &provider, subject, human_account_name)
+            .await?
+    };
+
+    let session = repo
+        .upstream_oauth_session()
+        .complete_with_link(&clock, session, &link, None, None, None, Some(userinfo))
+        .await?;
+
+    let cookie_jar = sessions_cookie
+        .add_link_to_session(session.id, link.id)?
+        .save(cookie_jar, &clock);
+
+    repo.save().await?;
+
+    Ok((
+        cookie_jar,
+        url_builder.redirect(&mas_router::UpstreamOAuth2Link::new(link.id)),
+    )
+        .into_response())
+}
@@ -0,0 +1,356 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Enveloped [XML signatures], restricted to what SAML 2.0 deployments use in
+//! practice: RSA PKCS#1 v1.5 signatures with SHA-2 digests, a single reference
+//! to the signed element and exclusive canonicalization.
+//!
+//! [XML signatures]: https://www.w3.org/TR/xmldsig-core1/
+
+use base64ct::{Base64, Encoding};
+use rand::{CryptoRng, RngCore};
+use rsa::{
+    RsaPrivateKey, RsaPublicKey,
+    pkcs1v15::{Signature, SigningKey, VerifyingKey},
+    signature::{RandomizedSigner, SignatureEncoding, Verifier},
+};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use thiserror::Error;
+
+use super::xml::{Element, escape};
+
+pub const XMLDSIG_NAMESPACE: &str = "http://www.w3.org/2000/09/xmldsig#";
+const EXCLUSIVE_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    #[error("Missing signature")]
+    Missing,
+
+    #[error("Malformed signature: {0}")]
+    Malformed(&'static str),
+
+    #[error("Unsupported algorithm {0:?}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("The signature does not reference the signed element")]
+    ReferenceMismatch,
+
+    #[error("The digest of the signed element does not match")]
+    DigestMismatch,
+
+    #[error("The signature could not be verified with any of the certificates")]
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DigestAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    fn from_uri(uri: &str) -> Result<Self, SignatureError> {
+        match uri {
+            "http://www.w3.org/2001/04/xmlenc#sha256" => Ok(Self::Sha256),
+            "http://www.w3.org/2001/04/xmldsig-more#sha384" => Ok(Self::Sha384),
+            "http://www.w3.org/2001/04/xmlenc#sha512" => Ok(Self::Sha512),
+            _ => Err(SignatureError::UnsupportedAlgorithm(uri.to_owned())),
+        }
+    }
+
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha384 => Sha384::digest(data).to_vec(),
+            Self::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SignatureAlgorithm {
+    RsaSha256,
+    RsaSha384,
+    RsaSha512,
+}
+
+impl SignatureAlgorithm {
+    fn from_uri(uri: &str) -> Result<Self, SignatureError> {
+        match uri {
+            "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256" => Ok(Self::RsaSha256),
+            "http://www.w3.org/2001/04/xmldsig-more#rsa-sha384" => Ok(Self::RsaSha384),
+            "http://www.w3.org/2001/04/xmldsig-more#rsa-sha512" => Ok(Self::RsaSha512),
+            _ => Err(SignatureError::UnsupportedAlgorithm(uri.to_owned())),
+        }
+    }
+
+    fn verify(self, key: &RsaPublicKey, message: &[u8], signature: &Signature) -> bool {
+        let key = key.clone();
+        match self {
+            Self::RsaSha256 => VerifyingKey::<Sha256>::new(key)
+                .verify(message, signature)
+                .is_ok(),
+            Self::RsaSha384 => VerifyingKey::<Sha384>::new(key)
+                .verify(message, signature)
+                .is_ok(),
+            Self::RsaSha512 => VerifyingKey::<Sha512>::new(key)
+                .verify(message, signature)
+                .is_ok(),
+        }
+    }
+}
+
+/// Decode a base64 value from an XML document, which may be split across
+/// multiple lines
+pub fn decode_base64(value: &str) -> Option<Vec<u8>> {
+    let value: String = value.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    Base64::decode_vec(&value).ok()
+}
+
+fn algorithm<'a>(element: &'a Element, local_name: &str) -> Result<&'a str, SignatureError> {
+    element
+        .child(XMLDSIG_NAMESPACE, local_name)
+        .and_then(|method| method.attribute("Algorithm"))
+        .ok_or(SignatureError::Malformed("missing algorithm"))
+}
+
+/// Get the `InclusiveNamespaces PrefixList` of an exclusive canonicalization
+/// method or transform
+fn inclusive_prefixes(method: &Element) -> Vec<&str> {
+    method
+        .child(EXCLUSIVE_C14N, "InclusiveNamespaces")
+        .and_then(|inclusive| inclusive.attribute("PrefixList"))
+        .map(|list| list.split_ascii_whitespace().collect())
+        .unwrap_or_default()
+}
+
+/// Verify the enveloped signature of an element, against a list of trusted
+/// keys.
+///
+/// Callers must only trust the content of the element passed here, as the
+/// signature only covers it.
+///
+/// # Errors
+///
+/// Returns an error if the element has no signature, or if it is not valid
+pub fn verify(element: &Element, keys: &[RsaPublicKey]) -> Result<(), SignatureError> {
+    let mut signatures = element.children_named(XMLDSIG_NAMESPACE, "Signature");
+    let signature = signatures.next().ok_or(SignatureError::Missing)?;
+    if signatures.next().is_some() {
+        return Err(SignatureError::Malformed("multiple signatures"));
+    }
+
+    let signed_info = signature
+        .child(XMLDSIG_NAMESPACE, "SignedInfo")
+        .ok_or(SignatureError::Malformed("missing SignedInfo"))?;
+
+    let canonicalization_method = signed_info
+        .child(XMLDSIG_NAMESPACE, "CanonicalizationMethod")
+        .ok_or(SignatureError::Malformed("missing CanonicalizationMethod"))?;
+    let canonicalization_algorithm = algorithm(signed_info, "CanonicalizationMethod")?;
+    if canonicalization_algorithm != EXCLUSIVE_C14N {
+        return Err(SignatureError::UnsupportedAlgorithm(
+            canonicalization_algorithm.to_owned(),
+        ));
+    }
+
+    let signature_algorithm =
+        SignatureAlgorithm::from_uri(algorithm(signed_info, "SignatureMethod")?)?;
+
+    // There must be exactly one reference, pointing to the element itself
+    let mut references = signed_info.children_named(XMLDSIG_NAMESPACE, "Reference");
+    let reference = references
+        .next()
+        .ok_or(SignatureError::Malformed("missing Reference"))?;
+    if references.next().is_some() {
+        return Err(SignatureError::Malformed("multiple references"));
+    }
+
+    let id = element
+        .attribute("ID")
+        .ok_or(SignatureError::ReferenceMismatch)?;
+    if reference
+        .attribute("URI")
+        .and_then(|uri| uri.strip_prefix('#'))
+        != Some(id)
+    {
+        return Err(SignatureError::ReferenceMismatch);
+    }
+
+    let transforms = reference
+        .child(XMLDSIG_NAMESPACE, "Transforms")
+        .ok_or(SignatureError::Malformed("missing Transforms"))?;
+    let mut enveloped = false;
+    let mut canonicalization = None;
+    for transform in transforms.children_named(XMLDSIG_NAMESPACE, "Transform") {
+        match transform.attribute("Algorithm") {
+            Some(ENVELOPED_SIGNATURE) => enveloped = true,
+            Some(EXCLUSIVE_C14N) => canonicalization = Some(inclusive_prefixes(transform)),
+            Some(other) => return Err(SignatureError::UnsupportedAlgorithm(other.to_owned())),
+            None => return Err(SignatureError::Malformed("missing algorithm")),
+        }
+    }
+
+    if !enveloped {
+        return Err(SignatureError::Malformed("not an enveloped signature"));
+    }
+
+    let canonicalization =
+        canonicalization.ok_or(SignatureError::Malformed("missing canonicalization"))?;
+
+    let digest_algorithm = DigestAlgorithm::from_uri(algorithm(reference, "DigestMethod")?)?;
+    let expected_digest = reference
+        .child(XMLDSIG_NAMESPACE, "DigestValue")
+        .and_then(|value| decode_base64(&value.text()))
+        .ok_or(SignatureError::Malformed("invalid DigestValue"))?;
+
+    let canonical = element.canonicalize(Some(signature), &canonicalization);
+    if digest_algorithm.digest(canonical.as_bytes()) != expected_digest {
+        return Err(SignatureError::DigestMismatch);
+    }
+
+    let signature_value = signature
+        .child(XMLDSIG_NAMESPACE, "SignatureValue")
+        .and_then(|value| decode_base64(&value.text()))
+        .ok_or(SignatureError::Malformed("invalid SignatureValue"))?;
+    let signature_value = Signature::try_from(signature_value.as_slice())
+        .map_err(|_| SignatureError::Malformed("invalid SignatureValue"))?;
+
+    let canonical_signed_info =
+        signed_info.canonicalize(None, &inclusive_prefixes(canonicalization_method));
+
+    if keys.iter().any(|key| {
+        signature_algorithm.verify(key, canonical_signed_info.as_bytes(), &signature_value)
+    }) {
+        Ok(())
+    } else {
+        Err(SignatureError::Invalid)
+    }
+}
+
+/// Build an enveloped `ds:Signature` element for the element with the given
+/// `ID`, whose canonical form (without the signature) is `canonical`, using
+/// RSA-SHA256.
+pub fn sign<R: RngCore + CryptoRng>(
+    rng: &mut R,
+    key: &RsaPrivateKey,
+    id: &str,
+    canonical: &str,
+) -> String {
+    let digest = Base64::encode_string(&Sha256::digest(canonical.as_bytes()));
+    let signed_info = format!(
+        concat!(
+            r#"<ds:SignedInfo xmlns:ds="{ns}">"#,
+            r#"<ds:CanonicalizationMethod Algorithm="{c14n}"></ds:CanonicalizationMethod>"#,
+            r#"<ds:SignatureMethod Algorithm="http://www.w3.org/2001/04/xmldsig-more#rsa-sha256"></ds:SignatureMethod>"#,
+            r##"<ds:Reference URI="#{id}">"##,
+            r#"<ds:Transforms>"#,
+            r#"<ds:Transform Algorithm="{enveloped}"></ds:Transform>"#,
+            r#"<ds:Transform Algorithm="{c14n}"></ds:Transform>"#,
+            r#"</ds:Transforms>"#,
+            r#"<ds:DigestMethod Algorithm="http://www.w3.org/2001/04/xmlenc#sha256"></ds:DigestMethod>"#,
+            r#"<ds:DigestValue>{digest}</ds:DigestValue>"#,
+            r#"</ds:Reference>"#,
+            r#"</ds:SignedInfo>"#,
+        ),
+        ns = XMLDSIG_NAMESPACE,
+        c14n = EXCLUSIVE_C14N,
+        enveloped = ENVELOPED_SIGNATURE,
+        id = escape(id),
+        digest = digest,
+    );
+
+    // The SignedInfo element above is already in its canonical form: it
+    // declares the only namespace it uses, and has no attributes to reorder
+    let signature =
+        SigningKey::<Sha256>::new(key.clone()).sign_with_rng(rng, signed_info.as_bytes());
+    let signature = Base64::encode_string(&signature.to_bytes());
+
+    // Move the namespace declaration to the Signature element
+    let signed_info = signed_info.replacen(&format!(r#" xmlns:ds="{XMLDSIG_NAMESPACE}""#), "", 1);
+    format!(
+        r#"<ds:Signature xmlns:ds="{XMLDSIG_NAMESPACE}">{signed_info}<ds:SignatureValue>{signature}</ds:SignatureValue></ds:Signature>"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rsa::RsaPrivateKey;
+
+    use super::*;
+
+    fn sign_document(rng: &mut rand_chacha::ChaChaRng, key: &RsaPrivateKey) -> String {
+        let unsigned = r#"<r:Root xmlns:r="urn:root" ID="_abc"><r:Issuer>me</r:Issuer><r:Body attr="value">Hello</r:Body></r:Root>"#;
+        let canonical = Element::parse(unsigned).unwrap().canonicalize(None, &[]);
+        let signature = sign(rng, key, "_abc", &canonical);
+        unsigned.replacen("</r:Issuer>", &format!("</r:Issuer>{signature}"), 1)
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let other_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let document = sign_document(&mut rng, &key);
+
+        let root = Element::parse(&document).unwrap();
+        verify(&root, &[key.to_public_key()]).unwrap();
+        verify(&root, &[other_key.to_public_key(), key.to_public_key()]).unwrap();
+        assert!(matches!(
+            verify(&root, &[other_key.to_public_key()]),
+            Err(SignatureError::Invalid)
+        ));
+
+        // Whitespace in the signed element changes the digest
+        let tampered = document.replace("Hello", "Hello ");
+        let root = Element::parse(&tampered).unwrap();
+        assert!(matches!(
+            verify(&root, &[key.to_public_key()]),
+            Err(SignatureError::DigestMismatch)
+        ));
+
+        // The reference must point to the element itself
+        let tampered = document.replace(r#"ID="_abc""#, r#"ID="_other""#);
+        let root = Element::parse(&tampered).unwrap();
+        assert!(matches!(
+            verify(&root, &[key.to_public_key()]),
+            Err(SignatureError::ReferenceMismatch)
+        ));
+
+        // Unsigned elements are rejected
+        let root = Element::parse(r#"<Root ID="_abc"/>"#).unwrap();
+        assert!(matches!(
+            verify(&root, &[key.to_public_key()]),
+            Err(SignatureError::Missing)
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_prefixes_declared_on_ancestors() {
+        // The signed element is extracted from a bigger document, which
+        // declares the namespaces on the root element
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let document = sign_document(&mut rng, &key);
+        let signed = document.replacen(r#" xmlns:r="urn:root""#, "", 1).replacen(
+            &format!(r#" xmlns:ds="{XMLDSIG_NAMESPACE}""#),
+            "",
+            1,
+        );
+        let wrapper = format!(
+            r#"<w:Wrapper xmlns:w="urn:wrapper" xmlns:r="urn:root" xmlns:ds="{XMLDSIG_NAMESPACE}">
+    {signed}
+</w:Wrapper>"#
+        );
+
+        let wrapper = Element::parse(&wrapper).unwrap();
+        let root = wrapper.child("urn:root", "Root").unwrap();
+        verify(root, &[key.to_public_key()]).unwrap();
+    }
+}
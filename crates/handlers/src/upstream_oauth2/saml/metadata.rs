@@ -0,0 +1,76 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use axum::{
+    extract::{Path, State},
+    http::header::CONTENT_TYPE,
+    response::IntoResponse,
+};
+use hyper::StatusCode;
+use mas_axum_utils::{GenericError, InternalError};
+use mas_data_model::UpstreamOAuthProvider;
+use mas_keystore::Keystore;
+use mas_router::UrlBuilder;
+use mas_storage::{BoxRepository, upstream_oauth2::UpstreamOAuthProviderRepository};
+use thiserror::Error;
+use ulid::Ulid;
+
+use super::{request::service_provider_metadata, signing_key};
+use crate::impl_from_error_for_route;
+
+#[derive(Debug, Error)]
+pub(crate) enum RouteError {
+    #[error("Provider not found")]
+    ProviderNotFound,
+
+    #[error(transparent)]
+    Internal(Box<dyn std::error::Error>),
+}
+
+impl_from_error_for_route!(mas_storage::RepositoryError);
+impl_from_error_for_route!(super::MissingSigningKey);
+
+impl IntoResponse for RouteError {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            e @ Self::ProviderNotFound => {
+                GenericError::new(StatusCode::NOT_FOUND, e).into_response()
+            }
+            Self::Internal(e) => InternalError::new(e).into_response(),
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "handlers.upstream_oauth2.saml.metadata.get",
+    fields(upstream_oauth_provider.id = %provider_id),
+    skip_all,
+)]
+pub(crate) async fn get(
+    mut repo: BoxRepository,
+    State(url_builder): State<UrlBuilder>,
+    State(keystore): State<Keystore>,
+    Path(provider_id): Path<Ulid>,
+) -> Result<impl IntoResponse, RouteError> {
+    let provider = repo
+        .upstream_oauth_provider()
+        .lookup(provider_id)
+        .await?
+        .filter(UpstreamOAuthProvider::enabled)
+        .ok_or(RouteError::ProviderNotFound)?;
+
+    let saml = provider.saml.as_ref().ok_or(RouteError::ProviderNotFound)?;
+
+    let key = signing_key(&keystore)?.to_public_key();
+
+    let metadata = service_provider_metadata(
+        &provider.client_id,
+        &url_builder.upstream_saml_acs(provider.id),
+        &key,
+        saml.name_id_format.as_deref(),
+    );
+
+    Ok(([(CONTENT_TYPE, "application/samlmetadata+xml")], metadata))
+}
@@ -0,0 +1,89 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Support for upstream identity providers speaking SAML 2.0.
+//!
+//! Those are regular upstream providers with SAML settings: we act as a
+//! service provider, send them signed `AuthnRequest` using the HTTP-POST
+//! binding, and receive their `Response` on the assertion consumer service.
+//! The attributes of the assertion are then stored as the `userinfo` of the
+//! upstream session, so that the usual claims imports and link flow apply.
+
+use mas_data_model::UpstreamOAuthProviderSamlSettings;
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_keystore::{JsonWebKey, Keystore, PrivateKey};
+use rand::{
+    Rng,
+    distributions::{Alphanumeric, DistString},
+};
+use rsa::{RsaPrivateKey, RsaPublicKey, pkcs8::DecodePublicKey};
+use thiserror::Error;
+use x509_cert::{
+    Certificate,
+    der::{DecodePem, Encode},
+};
+
+pub(crate) mod acs;
+mod dsig;
+pub(crate) mod metadata;
+mod request;
+mod response;
+mod xml;
+
+pub(crate) use self::request::AuthnRequest;
+
+const PROTOCOL_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+const ASSERTION_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+const METADATA_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:metadata";
+const POST_BINDING: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+
+#[derive(Debug, Error)]
+pub(crate) enum CertificateError {
+    #[error("Invalid certificate")]
+    Certificate(#[from] x509_cert::der::Error),
+
+    #[error("Unsupported certificate key, only RSA keys are supported")]
+    Key(#[from] rsa::pkcs8::spki::Error),
+}
+
+#[derive(Debug, Error)]
+#[error("No RSA key available to sign SAML 2.0 requests")]
+pub(crate) struct MissingSigningKey;
+
+/// Get the key we sign requests with, which is the key used for `RS256` in the
+/// keystore
+pub(crate) fn signing_key(keystore: &Keystore) -> Result<&RsaPrivateKey, MissingSigningKey> {
+    match keystore
+        .signing_key_for_algorithm(&JsonWebSignatureAlg::Rs256)
+        .map(JsonWebKey::params)
+    {
+        Some(PrivateKey::Rsa(key)) => Ok(key),
+        _ => Err(MissingSigningKey),
+    }
+}
+
+/// Parse the certificates the identity provider signs its responses with
+fn certificate_keys(
+    settings: &UpstreamOAuthProviderSamlSettings,
+) -> Result<Vec<RsaPublicKey>, CertificateError> {
+    settings
+        .certificates
+        .iter()
+        .map(|pem| {
+            let certificate = Certificate::from_pem(pem)?;
+            let public_key_info = certificate
+                .tbs_certificate
+                .subject_public_key_info
+                .to_der()?;
+            Ok(RsaPublicKey::from_public_key_der(&public_key_info)?)
+        })
+        .collect()
+}
+
+/// Generate an ID for an `AuthnRequest`. IDs must not start with a digit, so
+/// they are prefixed with an underscore.
+pub(crate) fn generate_request_id(rng: &mut impl Rng) -> String {
+    format!("_{}", Alphanumeric.sample_string(rng, 32))
+}
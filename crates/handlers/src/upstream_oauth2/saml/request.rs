@@ -0,0 +1,203 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Messages sent to identity providers: the authentication request, and the
+//! metadata describing us as a service provider.
+
+use base64ct::{Base64, Encoding};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::{CryptoRng, RngCore};
+use rsa::{RsaPrivateKey, RsaPublicKey, traits::PublicKeyParts};
+use url::Url;
+
+use super::{
+    ASSERTION_NAMESPACE, METADATA_NAMESPACE, POST_BINDING, PROTOCOL_NAMESPACE,
+    dsig::{self, XMLDSIG_NAMESPACE},
+    xml::{Element, escape},
+};
+
+/// A SAML 2.0 `AuthnRequest`
+pub struct AuthnRequest<'a> {
+    pub id: &'a str,
+    pub issue_instant: DateTime<Utc>,
+    pub destination: &'a Url,
+    pub assertion_consumer_service_url: &'a Url,
+    pub issuer: &'a str,
+    pub name_id_format: Option<&'a str>,
+}
+
+impl AuthnRequest<'_> {
+    /// Serialize and sign the request, returning it encoded for the HTTP-POST
+    /// binding
+    pub fn sign<R: RngCore + CryptoRng>(&self, rng: &mut R, key: &RsaPrivateKey) -> String {
+        let name_id_policy = match self.name_id_format {
+            Some(format) => format!(
+                r#"<samlp:NameIDPolicy AllowCreate="true" Format="{}"></samlp:NameIDPolicy>"#,
+                escape(format)
+            ),
+            None => r#"<samlp:NameIDPolicy AllowCreate="true"></samlp:NameIDPolicy>"#.to_owned(),
+        };
+
+        let unsigned = format!(
+            concat!(
+                r#"<samlp:AuthnRequest xmlns:samlp="{protocol}" xmlns:saml="{assertion}""#,
+                r#" AssertionConsumerServiceURL="{acs}" Destination="{destination}" ID="{id}""#,
+                r#" IssueInstant="{issue_instant}" ProtocolBinding="{binding}" Version="2.0">"#,
+                r#"<saml:Issuer>{issuer}</saml:Issuer>"#,
+                r#"{name_id_policy}"#,
+                r#"</samlp:AuthnRequest>"#,
+            ),
+            protocol = PROTOCOL_NAMESPACE,
+            assertion = ASSERTION_NAMESPACE,
+            acs = escape(self.assertion_consumer_service_url.as_str()),
+            destination = escape(self.destination.as_str()),
+            id = escape(self.id),
+            issue_instant = self
+                .issue_instant
+                .to_rfc3339_opts(SecondsFormat::Secs, true),
+            binding = POST_BINDING,
+            issuer = escape(self.issuer),
+            name_id_policy = name_id_policy,
+        );
+
+        // Canonicalize the request by parsing it back, which also makes sure we
+        // generated a valid document
+        let canonical = Element::parse(&unsigned)
+            .expect("generated AuthnRequest should be valid XML")
+            .canonicalize(None, &[]);
+
+        // The signature must be placed right after the issuer
+        let signature = dsig::sign(rng, key, self.id, &canonical);
+        let signed = unsigned.replacen("</saml:Issuer>", &format!("</saml:Issuer>{signature}"), 1);
+
+        Base64::encode_string(signed.as_bytes())
+    }
+}
+
+/// Generate the metadata describing us as a service provider
+pub fn service_provider_metadata(
+    entity_id: &str,
+    assertion_consumer_service_url: &Url,
+    key: &RsaPublicKey,
+    name_id_format: Option<&str>,
+) -> String {
+    let key_descriptor = format!(
+        concat!(
+            r#"<md:KeyDescriptor use="signing">"#,
+            r#"<ds:KeyInfo><ds:KeyValue><ds:RSAKeyValue>"#,
+            r#"<ds:Modulus>{modulus}</ds:Modulus>"#,
+            r#"<ds:Exponent>{exponent}</ds:Exponent>"#,
+            r#"</ds:RSAKeyValue></ds:KeyValue></ds:KeyInfo>"#,
+            r#"</md:KeyDescriptor>"#,
+        ),
+        modulus = Base64::encode_string(&key.n().to_bytes_be()),
+        exponent = Base64::encode_string(&key.e().to_bytes_be()),
+    );
+
+    let name_id_format = name_id_format.map_or_else(String::new, |format| {
+        format!("<md:NameIDFormat>{}</md:NameIDFormat>", escape(format))
+    });
+
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<md:EntityDescriptor xmlns:md="{metadata}" xmlns:ds="{dsig}" entityID="{entity_id}">"#,
+            r#"<md:SPSSODescriptor AuthnRequestsSigned="true" WantAssertionsSigned="true""#,
+            r#" protocolSupportEnumeration="{protocol}">"#,
+            r#"{key_descriptor}"#,
+            r#"{name_id_format}"#,
+            r#"<md:AssertionConsumerService Binding="{binding}" Location="{acs}" index="0" isDefault="true"/>"#,
+            r#"</md:SPSSODescriptor>"#,
+            r#"</md:EntityDescriptor>"#,
+        ),
+        metadata = METADATA_NAMESPACE,
+        dsig = XMLDSIG_NAMESPACE,
+        entity_id = escape(entity_id),
+        protocol = PROTOCOL_NAMESPACE,
+        key_descriptor = key_descriptor,
+        name_id_format = name_id_format,
+        binding = POST_BINDING,
+        acs = escape(assertion_consumer_service_url.as_str()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_signed_authn_request() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let destination = Url::parse("https://idp.example.com/sso?a=1&b=2").unwrap();
+        let acs =
+            Url::parse("https://mas.example.com/upstream/saml/acs/01H8PKNWKKRPCBW4YGH1RWV279")
+                .unwrap();
+
+        let request = AuthnRequest {
+            id: "_request",
+            issue_instant: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+            destination: &destination,
+            assertion_consumer_service_url: &acs,
+            issuer: "https://mas.example.com/",
+            name_id_format: Some("urn:oasis:names:tc:SAML:2.0:nameid-format:persistent"),
+        };
+
+        let encoded = request.sign(&mut rng, &key);
+        let xml = String::from_utf8(Base64::decode_vec(&encoded).unwrap()).unwrap();
+        let root = Element::parse(&xml).unwrap();
+
+        assert!(root.is(PROTOCOL_NAMESPACE, "AuthnRequest"));
+        assert_eq!(root.attribute("ID"), Some("_request"));
+        assert_eq!(root.attribute("IssueInstant"), Some("2025-01-01T00:00:00Z"));
+        assert_eq!(
+            root.attribute("Destination"),
+            Some("https://idp.example.com/sso?a=1&b=2")
+        );
+        assert_eq!(
+            root.child(ASSERTION_NAMESPACE, "Issuer").unwrap().text(),
+            "https://mas.example.com/"
+        );
+
+        // The signature comes right after the issuer
+        let children: Vec<_> = root.child_elements().map(Element::local_name).collect();
+        assert_eq!(children, ["Issuer", "Signature", "NameIDPolicy"]);
+
+        dsig::verify(&root, &[key.to_public_key()]).unwrap();
+    }
+
+    #[test]
+    fn test_service_provider_metadata() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let acs =
+            Url::parse("https://mas.example.com/upstream/saml/acs/01H8PKNWKKRPCBW4YGH1RWV279")
+                .unwrap();
+
+        let metadata =
+            service_provider_metadata("https://mas.example.com/", &acs, &key.to_public_key(), None);
+        let root = Element::parse(&metadata).unwrap();
+        assert!(root.is(METADATA_NAMESPACE, "EntityDescriptor"));
+        assert_eq!(root.attribute("entityID"), Some("https://mas.example.com/"));
+
+        let descriptor = root.child(METADATA_NAMESPACE, "SPSSODescriptor").unwrap();
+        assert_eq!(descriptor.attribute("AuthnRequestsSigned"), Some("true"));
+        assert!(
+            descriptor
+                .child(METADATA_NAMESPACE, "KeyDescriptor")
+                .is_some()
+        );
+
+        let acs_element = descriptor
+            .child(METADATA_NAMESPACE, "AssertionConsumerService")
+            .unwrap();
+        assert_eq!(acs_element.attribute("Location"), Some(acs.as_str()));
+        assert_eq!(acs_element.attribute("Binding"), Some(POST_BINDING));
+    }
+}
@@ -0,0 +1,496 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Validation of the `Response` sent by identity providers to the assertion
+//! consumer service, following the [Web Browser SSO profile].
+//!
+//! [Web Browser SSO profile]: https://docs.oasis-open.org/security/saml/v2.0/saml-profiles-2.0-os.pdf
+
+use chrono::{DateTime, Duration, Utc};
+use rsa::RsaPublicKey;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use super::{
+    ASSERTION_NAMESPACE, PROTOCOL_NAMESPACE,
+    dsig::{self, SignatureError, XMLDSIG_NAMESPACE, decode_base64},
+    xml::{Element, XmlError},
+};
+
+const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+const BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+
+/// How much clock skew with the identity provider we tolerate
+const ALLOWED_CLOCK_SKEW: Duration = Duration::minutes(3);
+
+#[derive(Debug, Error)]
+pub enum ResponseError {
+    #[error("Invalid response encoding")]
+    Encoding,
+
+    #[error(transparent)]
+    Xml(#[from] XmlError),
+
+    #[error("Not a SAML 2.0 response")]
+    NotAResponse,
+
+    #[error("Unexpected response destination")]
+    Destination,
+
+    #[error("The response is not for the expected request")]
+    InResponseTo,
+
+    #[error("Unexpected issuer")]
+    Issuer,
+
+    #[error("The identity provider returned an error: {0}")]
+    Status(String),
+
+    #[error("Encrypted assertions are not supported")]
+    EncryptedAssertion,
+
+    #[error("Expected exactly one assertion")]
+    AssertionCount,
+
+    #[error("Neither the response nor the assertion are signed")]
+    Unsigned,
+
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+
+    #[error("Missing subject name identifier")]
+    MissingNameId,
+
+    #[error("No valid bearer subject confirmation")]
+    SubjectConfirmation,
+
+    #[error("The assertion is not valid at this time")]
+    Conditions,
+
+    #[error("The assertion is not intended for this service provider")]
+    Audience,
+
+    #[error("Invalid timestamp")]
+    Timestamp,
+}
+
+/// What the response is checked against
+pub struct Expectations<'a> {
+    /// The entity ID of the identity provider
+    pub issuer: &'a str,
+
+    /// Our entity ID
+    pub audience: &'a str,
+
+    /// The URL of our assertion consumer service
+    pub recipient: &'a str,
+
+    /// The ID of the `AuthnRequest` we sent
+    pub request_id: &'a str,
+
+    /// The keys the identity provider signs its responses with
+    pub keys: &'a [RsaPublicKey],
+
+    pub now: DateTime<Utc>,
+}
+
+fn timestamp(value: &str) -> Result<DateTime<Utc>, ResponseError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| ResponseError::Timestamp)
+}
+
+impl Expectations<'_> {
+    fn check_issuer(&self, element: &Element) -> Result<(), ResponseError> {
+        match element.child(ASSERTION_NAMESPACE, "Issuer") {
+            Some(issuer) if issuer.text().trim() != self.issuer => Err(ResponseError::Issuer),
+            _ => Ok(()),
+        }
+    }
+
+    fn not_before(&self, value: Option<&str>) -> Result<bool, ResponseError> {
+        let Some(value) = value else { return Ok(true) };
+        Ok(timestamp(value)? <= self.now + ALLOWED_CLOCK_SKEW)
+    }
+
+    fn not_on_or_after(&self, value: Option<&str>) -> Result<bool, ResponseError> {
+        let Some(value) = value else { return Ok(true) };
+        Ok(self.now - ALLOWED_CLOCK_SKEW < timestamp(value)?)
+    }
+
+    fn check_subject_confirmation(&self, confirmation: &Element) -> Result<bool, ResponseError> {
+        if confirmation.attribute("Method") != Some(BEARER) {
+            return Ok(false);
+        }
+
+        let Some(data) = confirmation.child(ASSERTION_NAMESPACE, "SubjectConfirmationData") else {
+            return Ok(false);
+        };
+
+        // The profile requires both the `Recipient` and `NotOnOrAfter`
+        // attributes on bearer confirmations
+        if data.attribute("Recipient") != Some(self.recipient)
+            || data.attribute("NotOnOrAfter").is_none()
+        {
+            return Ok(false);
+        }
+
+        if data
+            .attribute("InResponseTo")
+            .is_some_and(|id| id != self.request_id)
+        {
+            return Ok(false);
+        }
+
+        Ok(self.not_before(data.attribute("NotBefore"))?
+            && self.not_on_or_after(data.attribute("NotOnOrAfter"))?)
+    }
+
+    fn check_conditions(&self, assertion: &Element) -> Result<(), ResponseError> {
+        let conditions = assertion
+            .child(ASSERTION_NAMESPACE, "Conditions")
+            .ok_or(ResponseError::Audience)?;
+
+        if !self.not_before(conditions.attribute("NotBefore"))?
+            || !self.not_on_or_after(conditions.attribute("NotOnOrAfter"))?
+        {
+            return Err(ResponseError::Conditions);
+        }
+
+        // There must be at least one audience restriction, and each of them
+        // must include us
+        let mut restrictions = conditions
+            .children_named(ASSERTION_NAMESPACE, "AudienceRestriction")
+            .peekable();
+        if restrictions.peek().is_none() {
+            return Err(ResponseError::Audience);
+        }
+
+        for restriction in restrictions {
+            if !restriction
+                .children_named(ASSERTION_NAMESPACE, "Audience")
+                .any(|audience| audience.text().trim() == self.audience)
+            {
+                return Err(ResponseError::Audience);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate an encoded response, returning the attributes of the user,
+    /// with the name identifier as `sub`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is invalid, is not signed by the
+    /// identity provider, or doesn't match the expectations
+    pub fn validate(&self, encoded: &str) -> Result<Map<String, Value>, ResponseError> {
+        let document = decode_base64(encoded)
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(ResponseError::Encoding)?;
+        let response = Element::parse(&document)?;
+
+        if !response.is(PROTOCOL_NAMESPACE, "Response") {
+            return Err(ResponseError::NotAResponse);
+        }
+
+        if response
+            .attribute("Destination")
+            .is_some_and(|destination| destination != self.recipient)
+        {
+            return Err(ResponseError::Destination);
+        }
+
+        if response.attribute("InResponseTo") != Some(self.request_id) {
+            return Err(ResponseError::InResponseTo);
+        }
+
+        self.check_issuer(&response)?;
+
+        let status = response
+            .child(PROTOCOL_NAMESPACE, "Status")
+            .and_then(|status| status.child(PROTOCOL_NAMESPACE, "StatusCode"))
+            .and_then(|code| code.attribute("Value"));
+        if status != Some(STATUS_SUCCESS) {
+            return Err(ResponseError::Status(
+                status.unwrap_or("missing status").to_owned(),
+            ));
+        }
+
+        if response
+            .children_named(ASSERTION_NAMESPACE, "EncryptedAssertion")
+            .next()
+            .is_some()
+        {
+            return Err(ResponseError::EncryptedAssertion);
+        }
+
+        let mut assertions = response.children_named(ASSERTION_NAMESPACE, "Assertion");
+        let assertion = assertions.next().ok_or(ResponseError::AssertionCount)?;
+        if assertions.next().is_some() {
+            return Err(ResponseError::AssertionCount);
+        }
+
+        // Either the response or the assertion must be signed. We only ever
+        // look at elements within the one which carries the signature, as
+        // `dsig::verify` checks that the signature references it.
+        let response_signed = response.child(XMLDSIG_NAMESPACE, "Signature").is_some();
+        let assertion_signed = assertion.child(XMLDSIG_NAMESPACE, "Signature").is_some();
+        if !response_signed && !assertion_signed {
+            return Err(ResponseError::Unsigned);
+        }
+
+        if response_signed {
+            dsig::verify(&response, self.keys)?;
+        }
+
+        if assertion_signed {
+            dsig::verify(assertion, self.keys)?;
+        }
+
+        if assertion.child(ASSERTION_NAMESPACE, "Issuer").is_none() {
+            return Err(ResponseError::Issuer);
+        }
+        self.check_issuer(assertion)?;
+
+        let subject = assertion
+            .child(ASSERTION_NAMESPACE, "Subject")
+            .ok_or(ResponseError::MissingNameId)?;
+
+        let name_id = subject
+            .child(ASSERTION_NAMESPACE, "NameID")
+            .map(|name_id| name_id.text().trim().to_owned())
+            .filter(|name_id| !name_id.is_empty())
+            .ok_or(ResponseError::MissingNameId)?;
+
+        let mut confirmed = false;
+        for confirmation in subject.children_named(ASSERTION_NAMESPACE, "SubjectConfirmation") {
+            if self.check_subject_confirmation(confirmation)? {
+                confirmed = true;
+                break;
+            }
+        }
+        if !confirmed {
+            return Err(ResponseError::SubjectConfirmation);
+        }
+
+        self.check_conditions(assertion)?;
+
+        Ok(attributes(assertion, name_id))
+    }
+}
+
+/// Collect the attributes of the assertion. Attributes with a single value are
+/// mapped to a string, others to an array of strings. They are available under
+/// their `Name`, and their `FriendlyName` if it doesn't clash with another
+/// attribute.
+fn attributes(assertion: &Element, name_id: String) -> Map<String, Value> {
+    let mut attributes = Map::new();
+    let mut friendly_names = Vec::new();
+
+    let statements = assertion.children_named(ASSERTION_NAMESPACE, "AttributeStatement");
+    for attribute in
+        statements.flat_map(|statement| statement.children_named(ASSERTION_NAMESPACE, "Attribute"))
+    {
+        let Some(name) = attribute.attribute("Name") else {
+            continue;
+        };
+
+        let mut values: Vec<Value> = attribute
+            .children_named(ASSERTION_NAMESPACE, "AttributeValue")
+            .map(|value| Value::String(value.text()))
+            .collect();
+        let value = if values.len() == 1 {
+            values.remove(0)
+        } else {
+            Value::Array(values)
+        };
+
+        if let Some(friendly_name) = attribute.attribute("FriendlyName") {
+            friendly_names.push((friendly_name.to_owned(), value.clone()));
+        }
+
+        attributes.insert(name.to_owned(), value);
+    }
+
+    for (friendly_name, value) in friendly_names {
+        attributes.entry(friendly_name).or_insert(value);
+    }
+
+    attributes.insert("sub".to_owned(), Value::String(name_id));
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use base64ct::{Base64, Encoding};
+    use chrono::TimeZone;
+    use rand::SeedableRng;
+    use rsa::RsaPrivateKey;
+    use serde_json::json;
+
+    use super::*;
+
+    const RECIPIENT: &str = "https://mas.example.com/upstream/saml/acs/01H8PKNWKKRPCBW4YGH1RWV279";
+
+    fn assertion(id: &str) -> String {
+        format!(
+            r#"<saml:Assertion xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{id}" IssueInstant="2025-01-01T00:00:00Z" Version="2.0">
+    <saml:Issuer>https://idp.example.com/</saml:Issuer>
+    <saml:Subject>
+      <saml:NameID Format="urn:oasis:names:tc:SAML:2.0:nameid-format:persistent">alice-id</saml:NameID>
+      <saml:SubjectConfirmation Method="urn:oasis:names:tc:SAML:2.0:cm:bearer">
+        <saml:SubjectConfirmationData InResponseTo="_request" NotOnOrAfter="2025-01-01T00:05:00Z" Recipient="{RECIPIENT}"/>
+      </saml:SubjectConfirmation>
+    </saml:Subject>
+    <saml:Conditions NotBefore="2025-01-01T00:00:00Z" NotOnOrAfter="2025-01-01T00:05:00Z">
+      <saml:AudienceRestriction>
+        <saml:Audience>https://mas.example.com/</saml:Audience>
+      </saml:AudienceRestriction>
+    </saml:Conditions>
+    <saml:AttributeStatement>
+      <saml:Attribute Name="urn:oid:0.9.2342.19200300.100.1.3" FriendlyName="mail">
+        <saml:AttributeValue>alice@example.com</saml:AttributeValue>
+      </saml:Attribute>
+      <saml:Attribute Name="groups">
+        <saml:AttributeValue>admins</saml:AttributeValue>
+        <saml:AttributeValue>users</saml:AttributeValue>
+      </saml:Attribute>
+    </saml:AttributeStatement>
+  </saml:Assertion>"#
+        )
+    }
+
+    fn signed_assertion(rng: &mut rand_chacha::ChaChaRng, key: &RsaPrivateKey, id: &str) -> String {
+        let unsigned = assertion(id);
+        let canonical = Element::parse(&unsigned).unwrap().canonicalize(None, &[]);
+        let signature = dsig::sign(rng, key, id, &canonical);
+        unsigned.replacen("</saml:Issuer>", &format!("</saml:Issuer>{signature}"), 1)
+    }
+
+    fn response(assertions: &str) -> String {
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" ID="_response" InResponseTo="_request" Destination="{RECIPIENT}" IssueInstant="2025-01-01T00:00:00Z" Version="2.0">
+  <saml:Issuer xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion">https://idp.example.com/</saml:Issuer>
+  <samlp:Status><samlp:StatusCode Value="urn:oasis:names:tc:SAML:2.0:status:Success"/></samlp:Status>
+  {assertions}
+</samlp:Response>"#
+        );
+        Base64::encode_string(document.as_bytes())
+    }
+
+    fn expectations(keys: &[RsaPublicKey]) -> Expectations<'_> {
+        Expectations {
+            issuer: "https://idp.example.com/",
+            audience: "https://mas.example.com/",
+            recipient: RECIPIENT,
+            request_id: "_request",
+            keys,
+            now: Utc.with_ymd_and_hms(2025, 1, 1, 0, 1, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_valid_response() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let keys = [key.to_public_key()];
+
+        let encoded = response(&signed_assertion(&mut rng, &key, "_assertion"));
+        let attributes = expectations(&keys).validate(&encoded).unwrap();
+
+        assert_eq!(
+            Value::Object(attributes),
+            json!({
+                "sub": "alice-id",
+                "urn:oid:0.9.2342.19200300.100.1.3": "alice@example.com",
+                "mail": "alice@example.com",
+                "groups": ["admins", "users"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_responses() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let keys = [key.to_public_key()];
+        let signed = signed_assertion(&mut rng, &key, "_assertion");
+
+        // Unsigned assertion
+        let encoded = response(&assertion("_assertion"));
+        assert!(matches!(
+            expectations(&keys).validate(&encoded),
+            Err(ResponseError::Unsigned)
+        ));
+
+        // Signed by someone else
+        let other_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let encoded = response(&signed);
+        assert!(matches!(
+            expectations(&[other_key.to_public_key()]).validate(&encoded),
+            Err(ResponseError::Signature(SignatureError::Invalid))
+        ));
+
+        // Signature wrapping: a second, forged assertion next to the signed one
+        let encoded = response(&format!("{signed}{}", assertion("_forged")));
+        assert!(matches!(
+            expectations(&keys).validate(&encoded),
+            Err(ResponseError::AssertionCount)
+        ));
+
+        // Tampered attribute
+        let encoded = response(&signed.replace("alice@example.com", "mallory@example.com"));
+        assert!(matches!(
+            expectations(&keys).validate(&encoded),
+            Err(ResponseError::Signature(SignatureError::DigestMismatch))
+        ));
+
+        // Response to another request
+        let encoded = response(&signed);
+        let mut expected = expectations(&keys);
+        expected.request_id = "_other";
+        assert!(matches!(
+            expected.validate(&encoded),
+            Err(ResponseError::InResponseTo)
+        ));
+
+        // Expired assertion
+        let mut expected = expectations(&keys);
+        expected.now = Utc.with_ymd_and_hms(2025, 1, 1, 1, 0, 0).unwrap();
+        assert!(matches!(
+            expected.validate(&encoded),
+            Err(ResponseError::SubjectConfirmation)
+        ));
+
+        // Another audience
+        let mut expected = expectations(&keys);
+        expected.audience = "https://other.example.com/";
+        assert!(matches!(
+            expected.validate(&encoded),
+            Err(ResponseError::Audience)
+        ));
+
+        // Another identity provider
+        let mut expected = expectations(&keys);
+        expected.issuer = "https://other-idp.example.com/";
+        assert!(matches!(
+            expected.validate(&encoded),
+            Err(ResponseError::Issuer)
+        ));
+
+        // Error status
+        let document = String::from_utf8(Base64::decode_vec(&response(&signed)).unwrap())
+            .unwrap()
+            .replace("status:Success", "status:Requester");
+        let encoded = Base64::encode_string(document.as_bytes());
+        assert!(matches!(
+            expectations(&keys).validate(&encoded),
+            Err(ResponseError::Status(_))
+        ));
+    }
+}
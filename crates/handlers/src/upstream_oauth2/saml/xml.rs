@@ -0,0 +1,582 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A minimal XML document model, good enough for SAML messages, and its
+//! [exclusive canonicalization].
+//!
+//! Document type declarations are rejected, so that no custom entity can be
+//! defined, and processing instructions inside the document element are
+//! rejected as they are never used by SAML. Comments are dropped, which is what
+//! canonicalization without comments does anyway.
+//!
+//! [exclusive canonicalization]: https://www.w3.org/TR/xml-exc-c14n/
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use quick_xml::{Reader, events::Event};
+use thiserror::Error;
+
+/// The namespace bound to the `xml` prefix
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+#[derive(Debug, Error)]
+pub enum XmlError {
+    #[error(transparent)]
+    Parse(#[from] quick_xml::Error),
+
+    #[error("Document type declarations are not allowed")]
+    DocumentType,
+
+    #[error("Processing instructions are not allowed")]
+    ProcessingInstruction,
+
+    #[error("Unknown entity {0:?}")]
+    UnknownEntity(String),
+
+    #[error("Unbound namespace prefix {0:?}")]
+    UnboundPrefix(String),
+
+    #[error("Unexpected content outside of the document element")]
+    UnexpectedContent,
+
+    #[error("Missing document element")]
+    MissingDocumentElement,
+
+    #[error("Duplicate ID {0:?}")]
+    DuplicateId(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Attribute {
+    prefix: Option<String>,
+    local_name: String,
+    namespace: Option<String>,
+    value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Element {
+    prefix: Option<String>,
+    local_name: String,
+    namespace: Option<String>,
+    attributes: Vec<Attribute>,
+
+    /// The namespaces in scope for this element, including the ones declared
+    /// on it. The default namespace uses the empty string as prefix.
+    in_scope: BTreeMap<String, String>,
+
+    children: Vec<Node>,
+}
+
+/// Split a qualified name in its prefix and local name
+fn split_qname(qname: &str) -> (Option<&str>, &str) {
+    match qname.split_once(':') {
+        Some((prefix, local_name)) => (Some(prefix), local_name),
+        None => (None, qname),
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str, XmlError> {
+    std::str::from_utf8(bytes)
+        .map_err(|e| quick_xml::Error::from(quick_xml::encoding::EncodingError::from(e)).into())
+}
+
+/// Normalize the whitespace of a raw attribute value and unescape it, as
+/// described in <https://www.w3.org/TR/xml/#AVNormalize>
+fn normalize_attribute_value(raw: &str) -> Result<String, XmlError> {
+    let raw = raw.replace("\r\n", "\n").replace(['\t', '\n', '\r'], " ");
+    let value = quick_xml::escape::unescape(&raw).map_err(quick_xml::Error::from)?;
+    Ok(value.into_owned())
+}
+
+impl Element {
+    fn from_start(
+        start: &quick_xml::events::BytesStart<'_>,
+        mut in_scope: BTreeMap<String, String>,
+    ) -> Result<Self, XmlError> {
+        let mut raw_attributes = Vec::new();
+
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(quick_xml::Error::from)?;
+            let key = utf8(attribute.key.as_ref())?.to_owned();
+            let value = normalize_attribute_value(utf8(&attribute.value)?)?;
+
+            if key == "xmlns" {
+                in_scope.insert(String::new(), value);
+            } else if let Some(prefix) = key.strip_prefix("xmlns:") {
+                in_scope.insert(prefix.to_owned(), value);
+            } else {
+                raw_attributes.push((key, value));
+            }
+        }
+
+        // An empty default namespace declaration undeclares it
+        if in_scope.get("").is_some_and(String::is_empty) {
+            in_scope.remove("");
+        }
+
+        let resolve = |prefix: &str| -> Result<String, XmlError> {
+            if prefix == "xml" {
+                return Ok(XML_NAMESPACE.to_owned());
+            }
+
+            in_scope
+                .get(prefix)
+                .cloned()
+                .ok_or_else(|| XmlError::UnboundPrefix(prefix.to_owned()))
+        };
+
+        let name = utf8(start.name().as_ref())?.to_owned();
+        let (prefix, local_name) = split_qname(&name);
+        let namespace = match prefix {
+            Some(prefix) => Some(resolve(prefix)?),
+            None => in_scope.get("").cloned(),
+        };
+
+        let attributes = raw_attributes
+            .into_iter()
+            .map(|(key, value)| {
+                let (prefix, local_name) = split_qname(&key);
+                let namespace = prefix.map(resolve).transpose()?;
+                Ok(Attribute {
+                    prefix: prefix.map(ToOwned::to_owned),
+                    local_name: local_name.to_owned(),
+                    namespace,
+                    value,
+                })
+            })
+            .collect::<Result<_, XmlError>>()?;
+
+        Ok(Self {
+            prefix: prefix.map(ToOwned::to_owned),
+            local_name: local_name.to_owned(),
+            namespace,
+            attributes,
+            in_scope,
+            children: Vec::new(),
+        })
+    }
+
+    /// Parse a document, returning its document element
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not well-formed, uses unsupported
+    /// constructs, or has two elements with the same `ID`
+    pub fn parse(document: &str) -> Result<Self, XmlError> {
+        let mut reader = Reader::from_str(document);
+        let mut stack: Vec<Element> = Vec::new();
+        let mut root = None;
+
+        loop {
+            let event = reader.read_event()?;
+            match event {
+                Event::Start(start) => {
+                    if root.is_some() {
+                        return Err(XmlError::UnexpectedContent);
+                    }
+                    let scope = stack.last().map(|e| e.in_scope.clone()).unwrap_or_default();
+                    stack.push(Element::from_start(&start, scope)?);
+                }
+
+                Event::Empty(start) => {
+                    if root.is_some() {
+                        return Err(XmlError::UnexpectedContent);
+                    }
+                    let scope = stack.last().map(|e| e.in_scope.clone()).unwrap_or_default();
+                    let element = Element::from_start(&start, scope)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(Node::Element(element)),
+                        None => root = Some(element),
+                    }
+                }
+
+                Event::End(_) => {
+                    // The reader already checks that end tags match start tags
+                    let element = stack.pop().ok_or(XmlError::UnexpectedContent)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(Node::Element(element)),
+                        None => root = Some(element),
+                    }
+                }
+
+                Event::Text(text) => {
+                    let text = text.xml10_content().map_err(quick_xml::Error::from)?;
+                    push_text(&mut stack, &text)?;
+                }
+
+                Event::CData(data) => {
+                    let text = data.xml10_content().map_err(quick_xml::Error::from)?;
+                    push_text(&mut stack, &text)?;
+                }
+
+                Event::GeneralRef(reference) => {
+                    let text = if let Some(c) = reference.resolve_char_ref()? {
+                        c.to_string()
+                    } else {
+                        let name = reference.decode().map_err(quick_xml::Error::from)?;
+                        match &*name {
+                            "lt" => "<".to_owned(),
+                            "gt" => ">".to_owned(),
+                            "amp" => "&".to_owned(),
+                            "apos" => "'".to_owned(),
+                            "quot" => "\"".to_owned(),
+                            _ => return Err(XmlError::UnknownEntity(name.into_owned())),
+                        }
+                    };
+                    push_text(&mut stack, &text)?;
+                }
+
+                Event::DocType(_) => return Err(XmlError::DocumentType),
+
+                Event::PI(_) => {
+                    if !stack.is_empty() {
+                        return Err(XmlError::ProcessingInstruction);
+                    }
+                }
+
+                Event::Comment(_) | Event::Decl(_) => {}
+
+                Event::Eof => break,
+            }
+        }
+
+        if !stack.is_empty() {
+            return Err(XmlError::UnexpectedContent);
+        }
+
+        let root = root.ok_or(XmlError::MissingDocumentElement)?;
+
+        // Make sure IDs are unique in the document, so that references in
+        // signatures are never ambiguous
+        let mut ids = HashSet::new();
+        root.check_unique_ids(&mut ids)?;
+
+        Ok(root)
+    }
+
+    fn check_unique_ids<'a>(&'a self, ids: &mut HashSet<&'a str>) -> Result<(), XmlError> {
+        if let Some(id) = self.attribute("ID")
+            && !ids.insert(id)
+        {
+            return Err(XmlError::DuplicateId(id.to_owned()));
+        }
+
+        for child in self.child_elements() {
+            child.check_unique_ids(ids)?;
+        }
+
+        Ok(())
+    }
+
+    /// The local name of the element
+    #[cfg(test)]
+    pub fn local_name(&self) -> &str {
+        &self.local_name
+    }
+
+    /// Whether the element has the given namespace and local name
+    pub fn is(&self, namespace: &str, local_name: &str) -> bool {
+        self.namespace.as_deref() == Some(namespace) && self.local_name == local_name
+    }
+
+    /// Get the value of an unqualified attribute
+    pub fn attribute(&self, local_name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|a| a.namespace.is_none() && a.local_name == local_name)
+            .map(|a| a.value.as_str())
+    }
+
+    /// Iterate over the child elements
+    pub fn child_elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// Iterate over the child elements with the given namespace and local name
+    pub fn children_named<'a>(
+        &'a self,
+        namespace: &'a str,
+        local_name: &'a str,
+    ) -> impl Iterator<Item = &'a Element> {
+        self.child_elements()
+            .filter(move |e| e.is(namespace, local_name))
+    }
+
+    /// Get the first child element with the given namespace and local name
+    pub fn child(&self, namespace: &str, local_name: &str) -> Option<&Element> {
+        self.child_elements()
+            .find(|child| child.is(namespace, local_name))
+    }
+
+    /// Get the text content of the element
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.children {
+            match node {
+                Node::Text(t) => text.push_str(t),
+                Node::Element(e) => text.push_str(&e.text()),
+            }
+        }
+        text
+    }
+
+    /// Canonicalize this element using the exclusive XML canonicalization
+    /// method, without comments.
+    ///
+    /// The `exclude` element, if any, is omitted from the output; this is used
+    /// for the enveloped signature transform. `inclusive_prefixes` is the
+    /// `InclusiveNamespaces PrefixList`, where `#default` refers to the
+    /// default namespace.
+    pub fn canonicalize(&self, exclude: Option<&Element>, inclusive_prefixes: &[&str]) -> String {
+        let inclusive_prefixes: BTreeSet<&str> = inclusive_prefixes
+            .iter()
+            .map(|prefix| if *prefix == "#default" { "" } else { *prefix })
+            .collect();
+
+        let mut output = String::new();
+        self.write_canonical(&mut output, &BTreeMap::new(), exclude, &inclusive_prefixes);
+        output
+    }
+
+    fn write_canonical(
+        &self,
+        output: &mut String,
+        rendered: &BTreeMap<String, String>,
+        exclude: Option<&Element>,
+        inclusive_prefixes: &BTreeSet<&str>,
+    ) {
+        // Namespaces which are visibly utilized by the element and its
+        // attributes, plus the ones in the inclusive prefix list
+        let mut prefixes: BTreeSet<&str> = inclusive_prefixes
+            .iter()
+            .copied()
+            .filter(|prefix| self.in_scope.contains_key(*prefix))
+            .collect();
+        prefixes.insert(self.prefix.as_deref().unwrap_or(""));
+        for attribute in &self.attributes {
+            if let Some(prefix) = attribute.prefix.as_deref()
+                && prefix != "xml"
+            {
+                prefixes.insert(prefix);
+            }
+        }
+
+        let mut rendered = rendered.clone();
+        let mut declarations = Vec::new();
+        for prefix in prefixes {
+            let value = self.in_scope.get(prefix).map_or("", String::as_str);
+            let previous = rendered.get(prefix).map_or("", String::as_str);
+
+            // Only the default namespace can be empty, and it only needs to be
+            // rendered if it was rendered with another value by an ancestor
+            if value != previous {
+                declarations.push((prefix, value));
+                rendered.insert(prefix.to_owned(), value.to_owned());
+            }
+        }
+
+        let qname = match &self.prefix {
+            Some(prefix) => format!("{prefix}:{}", self.local_name),
+            None => self.local_name.clone(),
+        };
+
+        output.push('<');
+        output.push_str(&qname);
+
+        // Declarations are sorted by prefix, with the default namespace first,
+        // which is what the BTreeSet iteration gives us
+        for (prefix, value) in declarations {
+            if prefix.is_empty() {
+                output.push_str(" xmlns=\"");
+            } else {
+                output.push_str(" xmlns:");
+                output.push_str(prefix);
+                output.push_str("=\"");
+            }
+            escape_attribute(output, value);
+            output.push('"');
+        }
+
+        let mut attributes: Vec<&Attribute> = self.attributes.iter().collect();
+        attributes.sort_by(|a, b| {
+            (a.namespace.as_deref().unwrap_or(""), &a.local_name)
+                .cmp(&(b.namespace.as_deref().unwrap_or(""), &b.local_name))
+        });
+        for attribute in attributes {
+            output.push(' ');
+            if let Some(prefix) = &attribute.prefix {
+                output.push_str(prefix);
+                output.push(':');
+            }
+            output.push_str(&attribute.local_name);
+            output.push_str("=\"");
+            escape_attribute(output, &attribute.value);
+            output.push('"');
+        }
+
+        output.push('>');
+
+        for child in &self.children {
+            match child {
+                Node::Text(text) => escape_text(output, text),
+                Node::Element(element) => {
+                    if exclude.is_some_and(|exclude| std::ptr::eq(exclude, element)) {
+                        continue;
+                    }
+                    element.write_canonical(output, &rendered, exclude, inclusive_prefixes);
+                }
+            }
+        }
+
+        output.push_str("</");
+        output.push_str(&qname);
+        output.push('>');
+    }
+}
+
+fn push_text(stack: &mut [Element], text: &str) -> Result<(), XmlError> {
+    let Some(parent) = stack.last_mut() else {
+        // Only whitespace is allowed outside of the document element
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        return Err(XmlError::UnexpectedContent);
+    };
+
+    if let Some(Node::Text(previous)) = parent.children.last_mut() {
+        previous.push_str(text);
+    } else {
+        parent.children.push(Node::Text(text.to_owned()));
+    }
+
+    Ok(())
+}
+
+fn escape_text(output: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '\r' => output.push_str("&#xD;"),
+            c => output.push(c),
+        }
+    }
+}
+
+fn escape_attribute(output: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '"' => output.push_str("&quot;"),
+            '\t' => output.push_str("&#x9;"),
+            '\n' => output.push_str("&#xA;"),
+            '\r' => output.push_str("&#xD;"),
+            c => output.push(c),
+        }
+    }
+}
+
+/// Escape a value to be used in a generated XML document, either as text or
+/// as an attribute value
+pub fn escape(value: &str) -> std::borrow::Cow<'_, str> {
+    quick_xml::escape::escape(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_namespaces() {
+        // Namespaces declared on ancestors are rendered on the apex element if
+        // they are visibly utilized, and unused ones are dropped
+        let document = r#"<?xml version="1.0"?>
+<a:Root xmlns:a="urn:a" xmlns:b="urn:b" xmlns:unused="urn:unused">
+  <b:Child b:attr="1" plain='x'><a:Leaf/></b:Child>
+</a:Root>"#;
+        let root = Element::parse(document).unwrap();
+        let child = root.child("urn:b", "Child").unwrap();
+
+        assert_eq!(
+            child.canonicalize(None, &[]),
+            r#"<b:Child xmlns:b="urn:b" plain="x" b:attr="1"><a:Leaf xmlns:a="urn:a"></a:Leaf></b:Child>"#,
+        );
+
+        assert_eq!(
+            child.canonicalize(None, &["unused"]),
+            r#"<b:Child xmlns:b="urn:b" xmlns:unused="urn:unused" plain="x" b:attr="1"><a:Leaf xmlns:a="urn:a"></a:Leaf></b:Child>"#,
+        );
+
+        assert_eq!(
+            root.canonicalize(None, &[]),
+            "<a:Root xmlns:a=\"urn:a\">\n  <b:Child xmlns:b=\"urn:b\" plain=\"x\" b:attr=\"1\"><a:Leaf></a:Leaf></b:Child>\n</a:Root>",
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_default_namespace() {
+        let document = r#"<Root xmlns="urn:root"><Child xmlns=""><Leaf/></Child><Other/></Root>"#;
+        let root = Element::parse(document).unwrap();
+        assert_eq!(
+            root.canonicalize(None, &[]),
+            r#"<Root xmlns="urn:root"><Child xmlns=""><Leaf></Leaf></Child><Other></Other></Root>"#,
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_escaping() {
+        let document = "<Root attr=\"a&amp;b&#9;c\r\nd\" other=\"&quot;&lt;&gt;\">x &amp; y &gt; &#13;<![CDATA[<z>]]>\r\n</Root>";
+        let root = Element::parse(document).unwrap();
+        assert_eq!(
+            root.canonicalize(None, &[]),
+            "<Root attr=\"a&amp;b&#x9;c d\" other=\"&quot;&lt;>\">x &amp; y &gt; &#xD;&lt;z&gt;\n</Root>",
+        );
+        assert_eq!(root.text(), "x & y > \r<z>\n");
+    }
+
+    #[test]
+    fn test_canonicalize_exclude() {
+        let document = r#"<Root ID="a"><Keep/><Drop><Deep/></Drop></Root>"#;
+        let root = Element::parse(document).unwrap();
+        let drop = root.child_elements().nth(1).unwrap();
+        assert_eq!(
+            root.canonicalize(Some(drop), &[]),
+            r#"<Root ID="a"><Keep></Keep></Root>"#,
+        );
+    }
+
+    #[test]
+    fn test_reject_unsafe_documents() {
+        assert!(matches!(
+            Element::parse(r#"<!DOCTYPE Root [<!ENTITY x "y">]><Root>&x;</Root>"#),
+            Err(XmlError::DocumentType)
+        ));
+        assert!(matches!(
+            Element::parse("<Root>&x;</Root>"),
+            Err(XmlError::UnknownEntity(_))
+        ));
+        assert!(matches!(
+            Element::parse(r#"<Root ID="a"><Child ID="a"/></Root>"#),
+            Err(XmlError::DuplicateId(_))
+        ));
+        assert!(matches!(
+            Element::parse("<a:Root/>"),
+            Err(XmlError::UnboundPrefix(_))
+        ));
+        assert!(Element::parse("<Root/><Other/>").is_err());
+    }
+}
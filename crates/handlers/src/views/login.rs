@@ -6,7 +6,9 @@
 
 use std::sync::{Arc, LazyLock};
 
+use anyhow::Context as _;
 use axum::{
+    Json,
     extract::{Form, State},
     response::{Html, IntoResponse, Response},
 };
@@ -17,29 +19,45 @@ use mas_axum_utils::{
     cookies::CookieJar,
     csrf::{CsrfExt, ProtectedForm},
 };
-use mas_data_model::{BoxClock, BoxRng, Clock, oauth2::LoginHint};
+use mas_data_model::{
+    BoxClock, BoxRng, Clock, Password, SecurityNotification, UserLdapLink, UserPasskey,
+    oauth2::LoginHint,
+};
 use mas_i18n::DataLocale;
 use mas_matrix::HomeserverConnection;
 use mas_router::{UpstreamOAuth2Authorize, UrlBuilder};
 use mas_storage::{
     BoxRepository, RepositoryAccess,
+    queue::{DispatchWebhookEventJob, QueueJobRepositoryExt as _, SendSecurityNotificationJob},
     upstream_oauth2::UpstreamOAuthProviderRepository,
-    user::{BrowserSessionRepository, UserPasswordRepository, UserRepository},
+    user::{
+        BrowserSessionRepository, UserPasskeyRepository, UserPasswordRepository, UserRepository,
+        UserTotpRepository,
+    },
 };
 use mas_templates::{
     AccountInactiveContext, FieldError, FormError, FormState, LoginContext, LoginFormField,
-    PostAuthContext, PostAuthContextInner, TemplateContext, Templates, ToFormState,
+    PasskeyLoginContext, PostAuthContext, PostAuthContextInner, TemplateContext, Templates,
+    ToFormState,
 };
 use opentelemetry::{Key, KeyValue, metrics::Counter};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use ulid::Ulid;
 use zeroize::Zeroizing;
 
-use super::shared::OptionalPostAuthAction;
+use super::{
+    login_mfa::PendingMfaLogin, login_password_expired::PendingPasswordChange,
+    shared::OptionalPostAuthAction,
+};
 use crate::{
     BoundActivityTracker, Limiter, METER, PreferredLanguage, RequesterFingerprint, SiteConfig,
-    passwords::{PasswordManager, PasswordVerificationResult},
+    passwords::{
+        PasswordManager, PasswordVerificationResult,
+        ldap::{self, LdapLoginError},
+    },
     session::{SessionOrFallback, load_session_or_fallback},
+    webauthn::Webauthn,
 };
 
 static PASSWORD_LOGIN_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
@@ -49,14 +67,36 @@ static PASSWORD_LOGIN_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
         .with_unit("{attempt}")
         .build()
 });
+static PASSKEY_LOGIN_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    METER
+        .u64_counter("mas.user.passkey_login_attempt")
+        .with_description("Number of passkey login attempts")
+        .with_unit("{attempt}")
+        .build()
+});
 const RESULT: Key = Key::from_static_str("result");
 
+/// How the password of the user was checked
+enum PasswordAuthentication {
+    /// Against the local password hash
+    Password(Password),
+
+    /// Against the LDAP directory
+    Ldap(UserLdapLink),
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub(crate) struct LoginForm {
     username: String,
     password: String,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct PasskeyLoginForm {
+    challenge_id: Ulid,
+    response: String,
+}
+
 impl ToFormState for LoginForm {
     type Field = LoginFormField;
 }
@@ -122,6 +162,7 @@ pub(crate) async fn get(
         &clock,
         &mut rng,
         &templates,
+        &url_builder,
         &homeserver,
         &site_config,
     )
@@ -178,6 +219,7 @@ pub(crate) async fn post(
             &clock,
             &mut rng,
             &templates,
+            &url_builder,
             &homeserver,
             &site_config,
         )
@@ -189,99 +231,67 @@ pub(crate) async fn post(
         .localpart(&form.username)
         .unwrap_or(&form.username);
 
-    // First, lookup the user
-    let Some(user) = get_user_by_email_or_by_username(&site_config, &mut repo, username).await?
-    else {
-        tracing::warn!(username, "User not found");
-        let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
-        PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
-        return render(
-            locale,
-            cookie_jar,
-            form_state,
-            query,
-            &mut repo,
-            &clock,
+    let (user, authentication) = if let Some(ldap) = password_manager.ldap() {
+        let password = Zeroizing::new(form.password);
+        match ldap::login(
             &mut rng,
-            &templates,
-            &homeserver,
-            &site_config,
-        )
-        .await;
-    };
-
-    // Check the rate limit
-    if let Err(e) = limiter.check_password(requester, &user) {
-        tracing::warn!(error = &e as &dyn std::error::Error, "ratelimit exceeded");
-        let form_state = form_state.with_error_on_form(FormError::RateLimitExceeded);
-        PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
-        return render(
-            locale,
-            cookie_jar,
-            form_state,
-            query,
-            &mut repo,
             &clock,
-            &mut rng,
-            &templates,
-            &homeserver,
-            &site_config,
-        )
-        .await;
-    }
-
-    // And its password
-    let Some(user_password) = repo.user_password().active(&user).await? else {
-        // There is no password for this user, but we don't want to disclose that. Show
-        // a generic 'invalid credentials' error instead
-        tracing::warn!(username, "No password for user");
-        let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
-        PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
-        return render(
-            locale,
-            cookie_jar,
-            form_state,
-            query,
             &mut repo,
-            &clock,
-            &mut rng,
-            &templates,
-            &homeserver,
-            &site_config,
-        )
-        .await;
-    };
-
-    let password = Zeroizing::new(form.password);
-
-    // Verify the password, and upgrade it on-the-fly if needed
-    let user_password = match password_manager
-        .verify_and_upgrade(
-            &mut rng,
-            user_password.version,
-            password,
-            user_password.hashed_password.clone(),
+            &*homeserver,
+            &limiter,
+            requester,
+            ldap,
+            username,
+            &password,
         )
         .await
-    {
-        Ok(PasswordVerificationResult::Success(Some((version, new_password_hash)))) => {
-            // Save the upgraded password
-            repo.user_password()
-                .add(
-                    &mut rng,
+        {
+            Ok((user, user_ldap_link)) => (user, PasswordAuthentication::Ldap(user_ldap_link)),
+            Err(e) => {
+                let form_error = match e {
+                    LdapLoginError::RateLimited(e) => {
+                        tracing::warn!(error = &e as &dyn std::error::Error, "ratelimit exceeded");
+                        FormError::RateLimitExceeded
+                    }
+                    LdapLoginError::UserNotFound
+                    | LdapLoginError::PasswordMismatch
+                    | LdapLoginError::UsernameNotAvailable { .. } => {
+                        tracing::warn!(
+                            username,
+                            error = &e as &dyn std::error::Error,
+                            "Failed to authenticate user against the directory"
+                        );
+                        FormError::InvalidCredentials
+                    }
+                    e => return Err(e.into()),
+                };
+
+                let form_state = form_state.with_error_on_form(form_error);
+                PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+                return render(
+                    locale,
+                    cookie_jar,
+                    form_state,
+                    query,
+                    &mut repo,
                     &clock,
-                    &user,
-                    version,
-                    new_password_hash,
-                    Some(&user_password),
+                    &mut rng,
+                    &templates,
+                    &url_builder,
+                    &homeserver,
+                    &site_config,
                 )
-                .await?
+                .await;
+            }
         }
-        Ok(PasswordVerificationResult::Success(None)) => user_password,
-        Ok(PasswordVerificationResult::Failure) => {
-            tracing::warn!(username, "Failed to verify/upgrade password for user");
+    } else {
+        // First, lookup the user
+        let Some(user) =
+            get_user_by_email_or_by_username(&site_config, &mut repo, username).await?
+        else {
+            tracing::warn!(username, "User not found");
             let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
-            PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "mismatch")]);
+            PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
             return render(
                 locale,
                 cookie_jar,
@@ -291,12 +301,106 @@ pub(crate) async fn post(
                 &clock,
                 &mut rng,
                 &templates,
+                &url_builder,
+                &homeserver,
+                &site_config,
+            )
+            .await;
+        };
+
+        // Check the rate limit
+        if let Err(e) = limiter.check_password(requester, &user).await {
+            tracing::warn!(error = &e as &dyn std::error::Error, "ratelimit exceeded");
+            let form_state = form_state.with_error_on_form(FormError::RateLimitExceeded);
+            PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+            return render(
+                locale,
+                cookie_jar,
+                form_state,
+                query,
+                &mut repo,
+                &clock,
+                &mut rng,
+                &templates,
+                &url_builder,
                 &homeserver,
                 &site_config,
             )
             .await;
         }
-        Err(err) => return Err(InternalError::from_anyhow(err)),
+
+        // And its password
+        let Some(user_password) = repo.user_password().active(&user).await? else {
+            // There is no password for this user, but we don't want to disclose that. Show
+            // a generic 'invalid credentials' error instead
+            tracing::warn!(username, "No password for user");
+            let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
+            PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+            return render(
+                locale,
+                cookie_jar,
+                form_state,
+                query,
+                &mut repo,
+                &clock,
+                &mut rng,
+                &templates,
+                &url_builder,
+                &homeserver,
+                &site_config,
+            )
+            .await;
+        };
+
+        let password = Zeroizing::new(form.password);
+
+        // Verify the password, and upgrade it on-the-fly if needed
+        let user_password = match password_manager
+            .verify_and_upgrade(
+                &mut rng,
+                user_password.version,
+                password,
+                user_password.hashed_password.clone(),
+            )
+            .await
+        {
+            Ok(PasswordVerificationResult::Success(Some((version, new_password_hash)))) => {
+                // Save the upgraded password
+                repo.user_password()
+                    .add(
+                        &mut rng,
+                        &clock,
+                        &user,
+                        version,
+                        new_password_hash,
+                        Some(&user_password),
+                    )
+                    .await?
+            }
+            Ok(PasswordVerificationResult::Success(None)) => user_password,
+            Ok(PasswordVerificationResult::Failure) => {
+                tracing::warn!(username, "Failed to verify/upgrade password for user");
+                let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
+                PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "mismatch")]);
+                return render(
+                    locale,
+                    cookie_jar,
+                    form_state,
+                    query,
+                    &mut repo,
+                    &clock,
+                    &mut rng,
+                    &templates,
+                    &url_builder,
+                    &homeserver,
+                    &site_config,
+                )
+                .await;
+            }
+            Err(err) => return Err(InternalError::from_anyhow(err)),
+        };
+
+        (user, PasswordAuthentication::Password(user_password))
     };
 
     // Now that we have checked the user password, we now want to show an error if
@@ -327,6 +431,39 @@ pub(crate) async fn post(
     // want it to crash in tests/debug builds
     debug_assert!(user.is_valid());
 
+    // If the password is too old, the user has to change it before going further.
+    // Users who enrolled a second factor also provide it on that page, before
+    // their password is changed.
+    if let PasswordAuthentication::Password(user_password) = &authentication
+        && password_manager
+            .is_password_expired(&clock, &mut repo, user_password)
+            .await
+            .map_err(InternalError::from_anyhow)?
+    {
+        // Save the password upgrade, if any
+        repo.save().await?;
+
+        PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "password_expired")]);
+
+        let cookie_jar = PendingPasswordChange::new(&user, user_password, &clock).save(cookie_jar);
+        let destination = mas_router::LoginPasswordExpired::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    }
+
+    // If the user enrolled a second factor, they have to provide it before we
+    // start a session
+    let user_totp = repo.user_totp().find_for_user(&user).await?;
+    if user_totp.is_some_and(|user_totp| user_totp.is_confirmed()) {
+        // Save the password upgrade, if any
+        repo.save().await?;
+
+        PASSWORD_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "mfa_required")]);
+
+        let cookie_jar = PendingMfaLogin::new(&user, &clock).save(cookie_jar);
+        let destination = mas_router::LoginMfa::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    }
+
     // Start a new session
     let user_session = repo
         .browser_session()
@@ -334,8 +471,37 @@ pub(crate) async fn post(
         .await?;
 
     // And mark it as authenticated by the password
-    repo.browser_session()
-        .authenticate_with_password(&mut rng, &clock, &user_session, &user_password)
+    match authentication {
+        PasswordAuthentication::Password(user_password) => {
+            repo.browser_session()
+                .authenticate_with_password(&mut rng, &clock, &user_session, &user_password)
+                .await?;
+        }
+        PasswordAuthentication::Ldap(user_ldap_link) => {
+            repo.browser_session()
+                .authenticate_with_ldap(&mut rng, &clock, &user_session, &user_ldap_link)
+                .await?;
+        }
+    }
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(
+                &user,
+                SecurityNotification::new_browser_session(&user_session),
+            )
+            .with_language(locale.to_string()),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::browser_session_created(&user_session),
+        )
         .await?;
 
     repo.save().await?;
@@ -351,6 +517,241 @@ pub(crate) async fn post(
     Ok((cookie_jar, reply).into_response())
 }
 
+#[tracing::instrument(name = "handlers.views.login.post_passkey", skip_all)]
+pub(crate) async fn post_passkey(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(site_config): State<SiteConfig>,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    State(homeserver): State<Arc<dyn HomeserverConnection>>,
+    mut repo: BoxRepository,
+    activity_tracker: BoundActivityTracker,
+    Query(query): Query<OptionalPostAuthAction>,
+    cookie_jar: CookieJar,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    Form(form): Form<ProtectedForm<PasskeyLoginForm>>,
+) -> Result<Response, InternalError> {
+    let user_agent = user_agent.map(|ua| ua.as_str().to_owned());
+    if !site_config.passkeys_enabled {
+        return Ok(StatusCode::METHOD_NOT_ALLOWED.into_response());
+    }
+
+    let form = cookie_jar.verify_form(&clock, form)?;
+    let webauthn = Webauthn::new(&url_builder);
+
+    let Some((user_passkey, sign_count)) =
+        verify_passkey_assertion(&mut repo, &clock, &webauthn, &form).await?
+    else {
+        PASSKEY_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+        let form_state = FormState::default().with_error_on_form(FormError::InvalidCredentials);
+        let response = render(
+            locale,
+            cookie_jar,
+            form_state,
+            query,
+            &mut repo,
+            &clock,
+            &mut rng,
+            &templates,
+            &url_builder,
+            &homeserver,
+            &site_config,
+        )
+        .await?;
+        // Save the repository, so that the challenge stays consumed
+        repo.save().await?;
+        return Ok(response);
+    };
+
+    let user = repo
+        .user()
+        .lookup(user_passkey.user_id)
+        .await?
+        .context("Could not load the user owning the passkey")
+        .map_err(InternalError::from_anyhow)?;
+
+    let user_passkey = repo
+        .user_passkey()
+        .record_use(&clock, user_passkey, sign_count)
+        .await?;
+
+    if user.deactivated_at.is_some() {
+        tracing::warn!(username = user.username, "User is deactivated");
+        PASSKEY_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+        repo.save().await?;
+        let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
+        let ctx = AccountInactiveContext::new(user)
+            .with_csrf(csrf_token.form_value())
+            .with_language(locale);
+        let content = templates.render_account_deactivated(&ctx)?;
+        return Ok((cookie_jar, Html(content)).into_response());
+    }
+
+    if user.locked_at.is_some() {
+        tracing::warn!(username = user.username, "User is locked");
+        PASSKEY_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+        repo.save().await?;
+        let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
+        let ctx = AccountInactiveContext::new(user)
+            .with_csrf(csrf_token.form_value())
+            .with_language(locale);
+        let content = templates.render_account_locked(&ctx)?;
+        return Ok((cookie_jar, Html(content)).into_response());
+    }
+
+    debug_assert!(user.is_valid());
+
+    // Start a new session
+    let user_session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, user_agent)
+        .await?;
+
+    // And mark it as authenticated by the passkey
+    repo.browser_session()
+        .authenticate_with_passkey(&mut rng, &clock, &user_session, &user_passkey)
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(
+                &user,
+                SecurityNotification::new_browser_session(&user_session),
+            )
+            .with_language(locale.to_string()),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::browser_session_created(&user_session),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    PASSKEY_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "success")]);
+
+    activity_tracker
+        .record_browser_session(&clock, &user_session)
+        .await;
+
+    let cookie_jar = cookie_jar.set_session(&user_session);
+    let reply = query.go_next(&url_builder);
+    Ok((cookie_jar, reply).into_response())
+}
+
+#[derive(Debug, Serialize)]
+struct PasskeyChallengeResponse {
+    id: Ulid,
+    options: serde_json::Value,
+}
+
+/// Issue a fresh `WebAuthn` challenge, so that the user can authenticate with
+/// a passkey
+#[tracing::instrument(name = "handlers.views.login.post_passkey_challenge", skip_all)]
+pub(crate) async fn post_passkey_challenge(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    State(site_config): State<SiteConfig>,
+    State(url_builder): State<UrlBuilder>,
+    mut repo: BoxRepository,
+    cookie_jar: CookieJar,
+    Form(form): Form<ProtectedForm<()>>,
+) -> Result<Response, InternalError> {
+    if !site_config.passkeys_enabled {
+        return Ok(StatusCode::METHOD_NOT_ALLOWED.into_response());
+    }
+
+    cookie_jar.verify_form(&clock, form)?;
+
+    let webauthn = Webauthn::new(&url_builder);
+    let challenge = Webauthn::generate_challenge(&mut rng);
+    let challenge = repo
+        .user_passkey()
+        .add_challenge(&mut rng, &clock, challenge)
+        .await?;
+
+    repo.save().await?;
+
+    Ok(Json(PasskeyChallengeResponse {
+        id: challenge.id,
+        options: webauthn.request_options(&challenge),
+    })
+    .into_response())
+}
+
+/// Verify a passkey assertion, returning the passkey used and its new
+/// signature counter if it is valid
+async fn verify_passkey_assertion<R: RepositoryAccess>(
+    repo: &mut R,
+    clock: &impl Clock,
+    webauthn: &Webauthn,
+    form: &PasskeyLoginForm,
+) -> Result<Option<(UserPasskey, u32)>, R::Error> {
+    let Some(challenge) = repo
+        .user_passkey()
+        .lookup_challenge(form.challenge_id)
+        .await?
+    else {
+        tracing::warn!(challenge.id = %form.challenge_id, "Passkey challenge not found");
+        return Ok(None);
+    };
+
+    // Challenges bound to a browser session are for registering passkeys
+    if challenge.user_session_id.is_some() || !challenge.is_valid(clock.now()) {
+        tracing::warn!(challenge.id = %challenge.id, "Passkey challenge is not valid");
+        return Ok(None);
+    }
+
+    // Consume the challenge right away, so that it can't be replayed
+    let challenge = repo
+        .user_passkey()
+        .complete_challenge(clock, challenge)
+        .await?;
+
+    let response = match Webauthn::parse_authentication(&form.response) {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!(
+                error = &e as &dyn std::error::Error,
+                "Invalid passkey response"
+            );
+            return Ok(None);
+        }
+    };
+
+    let Some(user_passkey) = repo
+        .user_passkey()
+        .find_by_credential_id(response.credential_id())
+        .await?
+    else {
+        tracing::warn!(
+            credential_id = response.credential_id(),
+            "Passkey not found"
+        );
+        return Ok(None);
+    };
+
+    match webauthn.verify_authentication(&challenge, &response, &user_passkey) {
+        Ok(sign_count) => Ok(Some((user_passkey, sign_count))),
+        Err(e) => {
+            tracing::warn!(
+                error = &e as &dyn std::error::Error,
+                user_passkey.id = %user_passkey.id,
+                "Failed to verify passkey assertion"
+            );
+            Ok(None)
+        }
+    }
+}
+
 async fn get_user_by_email_or_by_username<R: RepositoryAccess>(
     site_config: &SiteConfig,
     repo: &mut R,
@@ -409,16 +810,25 @@ async fn render(
     clock: &impl Clock,
     rng: impl Rng,
     templates: &Templates,
+    url_builder: &UrlBuilder,
     homeserver: &dyn HomeserverConnection,
     site_config: &SiteConfig,
 ) -> Result<Response, InternalError> {
     let (csrf_token, cookie_jar) = cookie_jar.csrf_token(clock, rng);
     let providers = repo.upstream_oauth_provider().all_enabled().await?;
 
-    let ctx = LoginContext::default()
+    let mut ctx = LoginContext::default()
         .with_form_state(form_state)
         .with_upstream_providers(providers);
 
+    if site_config.passkeys_enabled {
+        let challenge_url = url_builder.relative_url_for(&mas_router::PasskeyLoginChallenge);
+        let login_url = url_builder.relative_url_for(&mas_router::PasskeyLogin::from(
+            action.post_auth_action.clone(),
+        ));
+        ctx = ctx.with_passkey_login(PasskeyLoginContext::new(challenge_url, login_url));
+    }
+
     let next = action
         .load_context(repo)
         .await
@@ -442,7 +852,7 @@ mod test {
         header::{CONTENT_TYPE, LOCATION},
     };
     use mas_data_model::{
-        UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderOnBackchannelLogout,
+        Clock, UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderOnBackchannelLogout,
         UpstreamOAuthProviderTokenAuthMethod,
     };
     use mas_iana::jose::JsonWebSignatureAlg;
@@ -461,6 +871,7 @@ mod test {
         test_utils::{
             CookieHelper, RequestBuilderExt, ResponseExt, TestState, setup, test_site_config,
         },
+        totp::Totp,
     };
 
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
@@ -520,6 +931,7 @@ mod test {
                     forward_login_hint: false,
                     ui_order: 0,
                     on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                 },
             )
             .await
@@ -563,6 +975,7 @@ mod test {
                     forward_login_hint: false,
                     ui_order: 1,
                     on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                 },
             )
             .await
@@ -875,6 +1288,227 @@ mod test {
         assert!(response.body().contains("Invalid credentials"));
     }
 
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_password_login_expired_password(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        state.password_manager = state
+            .password_manager
+            .clone()
+            .with_max_age(chrono::Duration::days(30));
+        let cookies = CookieHelper::new();
+
+        // Provision a user with a password, which then expires
+        user_with_password(&state, "john", "hunter2").await;
+        state.clock.advance(chrono::Duration::days(31));
+
+        let csrf_token = |body: &str| {
+            body.split("name=\"csrf\" value=\"")
+                .nth(1)
+                .unwrap()
+                .split('\"')
+                .next()
+                .unwrap()
+                .to_owned()
+        };
+
+        // Render the login page to get a CSRF token
+        let request = Request::get("/login").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let csrf = csrf_token(response.body());
+
+        // Submit the login form, which asks for a new password
+        let request = Request::post("/login").form(serde_json::json!({
+            "csrf": csrf,
+            "username": "john",
+            "password": "hunter2",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "/login/password-expired");
+
+        // No session was started yet
+        let request = Request::get("/").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        assert!(!response.body().contains("john"));
+
+        let request = Request::get("/login/password-expired").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let csrf = csrf_token(response.body());
+
+        // The expired password can't be set again
+        let request = Request::post("/login/password-expired").form(serde_json::json!({
+            "csrf": csrf,
+            "new_password": "hunter2",
+            "new_password_confirm": "hunter2",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        assert!(
+            response
+                .body()
+                .contains("You have recently used this password")
+        );
+        let csrf = csrf_token(response.body());
+
+        let request = Request::post("/login/password-expired").form(serde_json::json!({
+            "csrf": csrf,
+            "new_password": "correct horse battery staple",
+            "new_password_confirm": "correct horse battery staple",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+
+        // We should now be logged in
+        let request = Request::get("/").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        assert!(response.body().contains("john"));
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_password_login_expired_password_with_totp(pool: PgPool) {
+        setup();
+        let mut state = TestState::from_pool(pool).await.unwrap();
+        state.password_manager = state
+            .password_manager
+            .clone()
+            .with_max_age(chrono::Duration::days(30));
+        let cookies = CookieHelper::new();
+
+        // Provision a user with a password and a TOTP authenticator, and let the
+        // password expire
+        let user = user_with_password(&state, "john", "hunter2").await;
+        let totp = Totp::generate(&mut state.rng());
+        let mut repo = state.repository().await.unwrap();
+        let encrypted_secret = state.encrypter.encrypt_to_string(totp.secret()).unwrap();
+        let user_totp = repo
+            .user_totp()
+            .add(&mut state.rng(), &state.clock, &user, encrypted_secret)
+            .await
+            .unwrap();
+        repo.user_totp()
+            .confirm(&state.clock, user_totp, 0)
+            .await
+            .unwrap();
+        let expired_password = repo.user_password().active(&user).await.unwrap().unwrap();
+        repo.save().await.unwrap();
+        state.clock.advance(chrono::Duration::days(31));
+
+        let csrf_token = |body: &str| {
+            body.split("name=\"csrf\" value=\"")
+                .nth(1)
+                .unwrap()
+                .split('\"')
+                .next()
+                .unwrap()
+                .to_owned()
+        };
+
+        let request = Request::get("/login").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let csrf = csrf_token(response.body());
+
+        let request = Request::post("/login").form(serde_json::json!({
+            "csrf": csrf,
+            "username": "john",
+            "password": "hunter2",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+        response.assert_header_value(LOCATION, "/login/password-expired");
+
+        // The second factor is asked along with the new password
+        let request = Request::get("/login/password-expired").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        assert!(response.body().contains("name=\"code\""));
+        let csrf = csrf_token(response.body());
+
+        // Without the second factor, the password isn't changed
+        let request = Request::post("/login/password-expired").form(serde_json::json!({
+            "csrf": csrf,
+            "new_password": "correct horse battery staple",
+            "new_password_confirm": "correct horse battery staple",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        let csrf = csrf_token(response.body());
+
+        // Same with an invalid code
+        let request = Request::post("/login/password-expired").form(serde_json::json!({
+            "csrf": csrf,
+            "new_password": "correct horse battery staple",
+            "new_password_confirm": "correct horse battery staple",
+            "code": "000000",
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::OK);
+        assert!(response.body().contains("Invalid credentials"));
+        let csrf = csrf_token(response.body());
+
+        let mut repo = state.repository().await.unwrap();
+        let active_password = repo.user_password().active(&user).await.unwrap().unwrap();
+        assert_eq!(active_password.id, expired_password.id);
+        repo.save().await.unwrap();
+
+        // No session was started either
+        let request = Request::get("/").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        assert!(!response.body().contains("john"));
+
+        // With the second factor, the password is changed and the user logged in
+        let request = Request::post("/login/password-expired").form(serde_json::json!({
+            "csrf": csrf,
+            "new_password": "correct horse battery staple",
+            "new_password_confirm": "correct horse battery staple",
+            "code": totp.code_at(state.clock.now()),
+        }));
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        cookies.save_cookies(&response);
+        response.assert_status(StatusCode::SEE_OTHER);
+
+        let mut repo = state.repository().await.unwrap();
+        let active_password = repo.user_password().active(&user).await.unwrap().unwrap();
+        assert_ne!(active_password.id, expired_password.id);
+        repo.save().await.unwrap();
+
+        let request = Request::get("/").empty();
+        let request = cookies.with_cookies(request);
+        let response = state.request(request).await;
+        response.assert_status(StatusCode::OK);
+        assert!(response.body().contains("john"));
+    }
+
     #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
     async fn test_password_login_deactivated_account(pool: PgPool) {
         setup();
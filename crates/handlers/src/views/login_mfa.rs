@@ -0,0 +1,349 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Second step of the password login, for users who enrolled a TOTP
+//! authenticator
+
+use std::sync::LazyLock;
+
+use axum::{
+    extract::{Form, State},
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::{extract::Query, typed_header::TypedHeader};
+use chrono::{DateTime, Duration, Utc};
+use mas_axum_utils::{
+    InternalError, SessionInfoExt,
+    cookies::CookieJar,
+    csrf::{CsrfExt, ProtectedForm},
+};
+use mas_data_model::{
+    BoxClock, BoxRng, BrowserSession, Clock, SecurityNotification, User, UserRecoveryCode, UserTotp,
+};
+use mas_i18n::DataLocale;
+use mas_keystore::Encrypter;
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxRepository, RepositoryAccess, RepositoryError,
+    queue::{DispatchWebhookEventJob, QueueJobRepositoryExt as _, SendSecurityNotificationJob},
+    user::{BrowserSessionRepository, UserRepository, UserTotpRepository},
+};
+use mas_templates::{
+    FieldError, FormError, FormState, LoginMfaContext, LoginMfaFormField, TemplateContext,
+    Templates, ToFormState,
+};
+use opentelemetry::{Key, KeyValue, metrics::Counter};
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use super::shared::OptionalPostAuthAction;
+use crate::{
+    BoundActivityTracker, Limiter, METER, PreferredLanguage, RequesterFingerprint,
+    totp::{self, Totp},
+};
+
+static MFA_LOGIN_COUNTER: LazyLock<Counter<u64>> = LazyLock::new(|| {
+    METER
+        .u64_counter("mas.user.mfa_login_attempt")
+        .with_description("Number of second factor login attempts")
+        .with_unit("{attempt}")
+        .build()
+});
+const RESULT: Key = Key::from_static_str("result");
+
+/// Name of the cookie
+static COOKIE_NAME: &str = "pending-mfa-login";
+
+/// Users have ten minutes to provide their second factor after their password
+static PENDING_MAX_TIME: Duration = Duration::minutes(10);
+
+/// The content of the cookie, which remembers which user successfully
+/// provided their password
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct PendingMfaLogin {
+    user_id: Ulid,
+    created_at: DateTime<Utc>,
+}
+
+impl PendingMfaLogin {
+    /// Start a pending login for the given user
+    pub fn new(user: &User, clock: &impl Clock) -> Self {
+        Self {
+            user_id: user.id,
+            created_at: clock.now(),
+        }
+    }
+
+    /// Load the pending login from the cookie jar, if it has not expired
+    pub fn load(cookie_jar: &CookieJar, clock: &impl Clock) -> Option<Self> {
+        match cookie_jar.load::<Self>(COOKIE_NAME) {
+            Ok(Some(this)) if clock.now() - this.created_at < PENDING_MAX_TIME => Some(this),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(
+                    error = &e as &dyn std::error::Error,
+                    "Invalid pending MFA login cookie"
+                );
+                None
+            }
+        }
+    }
+
+    /// Save the pending login to the cookie jar
+    pub fn save(&self, cookie_jar: CookieJar) -> CookieJar {
+        cookie_jar.save(COOKIE_NAME, self, false)
+    }
+
+    /// Remove the pending login from the cookie jar
+    pub fn remove(cookie_jar: CookieJar) -> CookieJar {
+        cookie_jar.remove(COOKIE_NAME)
+    }
+}
+
+/// A second factor which the user successfully provided
+pub(crate) enum SecondFactor {
+    Totp(UserTotp),
+    RecoveryCode(UserRecoveryCode),
+}
+
+impl SecondFactor {
+    /// Check a TOTP or recovery code provided by the user, and consume it so
+    /// that it can't be used again.
+    ///
+    /// Returns `None` if the code is invalid.
+    pub(crate) async fn verify(
+        repo: &mut BoxRepository,
+        clock: &impl Clock,
+        encrypter: &Encrypter,
+        user: &User,
+        user_totp: UserTotp,
+        code: &str,
+    ) -> Result<Option<Self>, InternalError> {
+        if totp::looks_like_totp_code(code) {
+            let secret = encrypter.decrypt_string(&user_totp.encrypted_secret)?;
+            let step =
+                Totp::from_secret(secret).verify(code, clock.now(), user_totp.last_used_step);
+
+            // Recording the use fails if a concurrent request used a code from the
+            // same step in the meantime
+            let user_totp = match step {
+                Some(step) => repo.user_totp().record_use(clock, user_totp, step).await?,
+                None => None,
+            };
+
+            if user_totp.is_none() {
+                tracing::warn!(user.id = %user.id, "Invalid TOTP code");
+            }
+
+            Ok(user_totp.map(Self::Totp))
+        } else {
+            let code = totp::normalize_recovery_code(code);
+            let mut matching = None;
+            for recovery_code in repo.user_totp().unused_recovery_codes(user).await? {
+                let decrypted = encrypter.decrypt_string(&recovery_code.encrypted_code)?;
+                if decrypted == code.as_bytes() {
+                    matching = Some(recovery_code);
+                    break;
+                }
+            }
+
+            let Some(recovery_code) = matching else {
+                tracing::warn!(user.id = %user.id, "Invalid recovery code");
+                return Ok(None);
+            };
+
+            let recovery_code = repo
+                .user_totp()
+                .consume_recovery_code(clock, recovery_code)
+                .await?;
+
+            Ok(Some(Self::RecoveryCode(recovery_code)))
+        }
+    }
+
+    /// Mark the browser session as authenticated by this second factor
+    pub(crate) async fn authenticate(
+        &self,
+        repo: &mut BoxRepository,
+        rng: &mut (dyn RngCore + Send),
+        clock: &impl Clock,
+        user_session: &BrowserSession,
+    ) -> Result<(), RepositoryError> {
+        match self {
+            Self::Totp(user_totp) => {
+                repo.browser_session()
+                    .authenticate_with_totp(rng, clock, user_session, user_totp)
+                    .await?;
+            }
+            Self::RecoveryCode(recovery_code) => {
+                repo.browser_session()
+                    .authenticate_with_recovery_code(rng, clock, user_session, recovery_code)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct LoginMfaForm {
+    code: String,
+}
+
+impl ToFormState for LoginMfaForm {
+    type Field = LoginMfaFormField;
+}
+
+#[tracing::instrument(name = "handlers.views.login_mfa.get", skip_all)]
+pub(crate) async fn get(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    Query(query): Query<OptionalPostAuthAction>,
+    cookie_jar: CookieJar,
+) -> Result<Response, InternalError> {
+    if PendingMfaLogin::load(&cookie_jar, &clock).is_none() {
+        let destination = mas_router::Login::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    }
+
+    render(
+        locale,
+        cookie_jar,
+        FormState::default(),
+        &clock,
+        &mut rng,
+        &templates,
+    )
+}
+
+#[tracing::instrument(name = "handlers.views.login_mfa.post", skip_all)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    State(limiter): State<Limiter>,
+    State(encrypter): State<Encrypter>,
+    mut repo: BoxRepository,
+    activity_tracker: BoundActivityTracker,
+    requester: RequesterFingerprint,
+    Query(query): Query<OptionalPostAuthAction>,
+    cookie_jar: CookieJar,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    Form(form): Form<ProtectedForm<LoginMfaForm>>,
+) -> Result<Response, InternalError> {
+    let user_agent = user_agent.map(|ua| ua.as_str().to_owned());
+    let form = cookie_jar.verify_form(&clock, form)?;
+
+    // If the pending login expired, or if the user or its authenticator went
+    // away in the meantime, start over
+    let Some(pending) = PendingMfaLogin::load(&cookie_jar, &clock) else {
+        let destination = mas_router::Login::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    };
+
+    let user = repo.user().lookup(pending.user_id).await?;
+    let user_totp = match &user {
+        Some(user) if user.is_valid() => repo.user_totp().find_for_user(user).await?,
+        _ => None,
+    };
+    let (Some(user), Some(user_totp)) = (user, user_totp.filter(UserTotp::is_confirmed)) else {
+        let cookie_jar = PendingMfaLogin::remove(cookie_jar);
+        let destination = mas_router::Login::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    };
+
+    let form_state = form.to_form_state();
+    if form.code.trim().is_empty() {
+        let form_state =
+            form_state.with_error_on_field(LoginMfaFormField::Code, FieldError::Required);
+        return render(locale, cookie_jar, form_state, &clock, &mut rng, &templates);
+    }
+
+    // Codes are short, so they share the password rate limit to prevent brute
+    // forcing them
+    if let Err(e) = limiter.check_password(requester, &user).await {
+        tracing::warn!(error = &e as &dyn std::error::Error, "ratelimit exceeded");
+        MFA_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "error")]);
+        let form_state = form_state.with_error_on_form(FormError::RateLimitExceeded);
+        return render(locale, cookie_jar, form_state, &clock, &mut rng, &templates);
+    }
+
+    let Some(second_factor) =
+        SecondFactor::verify(&mut repo, &clock, &encrypter, &user, user_totp, &form.code).await?
+    else {
+        MFA_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "mismatch")]);
+        let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
+        return render(locale, cookie_jar, form_state, &clock, &mut rng, &templates);
+    };
+
+    // Start a new session, which will be authenticated by the second factor.
+    // The password was checked in the previous step, which the authentication
+    // method reflects.
+    let user_session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, user_agent)
+        .await?;
+    second_factor
+        .authenticate(&mut repo, &mut rng, &clock, &user_session)
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(
+                &user,
+                SecurityNotification::new_browser_session(&user_session),
+            )
+            .with_language(locale.to_string()),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::browser_session_created(&user_session),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    MFA_LOGIN_COUNTER.add(1, &[KeyValue::new(RESULT, "success")]);
+
+    activity_tracker
+        .record_browser_session(&clock, &user_session)
+        .await;
+
+    let cookie_jar = PendingMfaLogin::remove(cookie_jar).set_session(&user_session);
+    let reply = query.go_next(&url_builder);
+    Ok((cookie_jar, reply).into_response())
+}
+
+fn render(
+    locale: DataLocale,
+    cookie_jar: CookieJar,
+    form_state: FormState<LoginMfaFormField>,
+    clock: &impl Clock,
+    mut rng: impl Rng + Send,
+    templates: &Templates,
+) -> Result<Response, InternalError> {
+    let (csrf_token, cookie_jar) = cookie_jar.csrf_token(clock, &mut rng);
+    let ctx = LoginMfaContext::new()
+        .with_form_state(form_state)
+        .with_csrf(csrf_token.form_value())
+        .with_language(locale);
+
+    let content = templates.render_login_mfa(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
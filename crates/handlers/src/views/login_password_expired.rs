@@ -0,0 +1,421 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Forced password change during the password login, for users whose
+//! password is older than the configured maximum age
+
+use axum::{
+    extract::{Form, State},
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::{extract::Query, typed_header::TypedHeader};
+use chrono::{DateTime, Duration, Utc};
+use mas_axum_utils::{
+    InternalError, SessionInfoExt,
+    cookies::CookieJar,
+    csrf::{CsrfExt, ProtectedForm},
+};
+use mas_data_model::{BoxClock, BoxRng, Clock, Password, SecurityNotification, User, UserTotp};
+use mas_i18n::DataLocale;
+use mas_keystore::Encrypter;
+use mas_router::UrlBuilder;
+use mas_storage::{
+    BoxRepository, RepositoryAccess, RepositoryError,
+    queue::{DispatchWebhookEventJob, QueueJobRepositoryExt as _, SendSecurityNotificationJob},
+    user::{BrowserSessionRepository, UserPasswordRepository, UserRepository, UserTotpRepository},
+};
+use mas_templates::{
+    FieldError, FormError, FormState, LoginPasswordExpiredContext, LoginPasswordExpiredFormField,
+    TemplateContext, Templates, ToFormState,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use zeroize::Zeroizing;
+
+use super::{login_mfa::SecondFactor, shared::OptionalPostAuthAction};
+use crate::{
+    BoundActivityTracker, Limiter, PreferredLanguage, RequesterFingerprint,
+    passwords::PasswordManager,
+};
+
+/// Name of the cookie
+static COOKIE_NAME: &str = "pending-password-change";
+
+/// Users have ten minutes to change their password after providing the
+/// expired one
+static PENDING_MAX_TIME: Duration = Duration::minutes(10);
+
+/// The content of the cookie, which remembers which user successfully
+/// provided their expired password
+#[derive(Serialize, Deserialize, Debug)]
+pub(crate) struct PendingPasswordChange {
+    user_id: Ulid,
+    user_password_id: Ulid,
+    created_at: DateTime<Utc>,
+}
+
+impl PendingPasswordChange {
+    /// Start a pending password change for the given user and expired password
+    pub fn new(user: &User, password: &Password, clock: &impl Clock) -> Self {
+        Self {
+            user_id: user.id,
+            user_password_id: password.id,
+            created_at: clock.now(),
+        }
+    }
+
+    /// Load the pending password change from the cookie jar, if it has not
+    /// expired
+    pub fn load(cookie_jar: &CookieJar, clock: &impl Clock) -> Option<Self> {
+        match cookie_jar.load::<Self>(COOKIE_NAME) {
+            Ok(Some(this)) if clock.now() - this.created_at < PENDING_MAX_TIME => Some(this),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(
+                    error = &e as &dyn std::error::Error,
+                    "Invalid pending password change cookie"
+                );
+                None
+            }
+        }
+    }
+
+    /// Save the pending password change to the cookie jar
+    pub fn save(&self, cookie_jar: CookieJar) -> CookieJar {
+        cookie_jar.save(COOKIE_NAME, self, false)
+    }
+
+    /// Remove the pending password change from the cookie jar
+    pub fn remove(cookie_jar: CookieJar) -> CookieJar {
+        cookie_jar.remove(COOKIE_NAME)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct LoginPasswordExpiredForm {
+    new_password: String,
+    new_password_confirm: String,
+
+    /// The TOTP or recovery code, for users who enrolled a second factor
+    #[serde(default)]
+    code: String,
+}
+
+impl ToFormState for LoginPasswordExpiredForm {
+    type Field = LoginPasswordExpiredFormField;
+}
+
+/// Find the confirmed TOTP authenticator of the user, if any. Users who have
+/// one must provide their second factor before their password is changed.
+async fn confirmed_totp(
+    repo: &mut BoxRepository,
+    user: &User,
+) -> Result<Option<UserTotp>, RepositoryError> {
+    Ok(repo
+        .user_totp()
+        .find_for_user(user)
+        .await?
+        .filter(UserTotp::is_confirmed))
+}
+
+#[tracing::instrument(name = "handlers.views.login_password_expired.get", skip_all)]
+pub(crate) async fn get(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    mut repo: BoxRepository,
+    Query(query): Query<OptionalPostAuthAction>,
+    cookie_jar: CookieJar,
+) -> Result<Response, InternalError> {
+    let Some(pending) = PendingPasswordChange::load(&cookie_jar, &clock) else {
+        let destination = mas_router::Login::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    };
+
+    let user = repo.user().lookup(pending.user_id).await?;
+    let mfa_required = match user {
+        Some(user) => confirmed_totp(&mut repo, &user).await?.is_some(),
+        None => false,
+    };
+
+    render(
+        locale,
+        cookie_jar,
+        FormState::default(),
+        mfa_required,
+        &clock,
+        &mut rng,
+        &templates,
+    )
+}
+
+#[tracing::instrument(name = "handlers.views.login_password_expired.post", skip_all)]
+pub(crate) async fn post(
+    mut rng: BoxRng,
+    clock: BoxClock,
+    PreferredLanguage(locale): PreferredLanguage,
+    State(templates): State<Templates>,
+    State(url_builder): State<UrlBuilder>,
+    State(password_manager): State<PasswordManager>,
+    State(limiter): State<Limiter>,
+    State(encrypter): State<Encrypter>,
+    mut repo: BoxRepository,
+    activity_tracker: BoundActivityTracker,
+    requester: RequesterFingerprint,
+    Query(query): Query<OptionalPostAuthAction>,
+    cookie_jar: CookieJar,
+    user_agent: Option<TypedHeader<headers::UserAgent>>,
+    Form(form): Form<ProtectedForm<LoginPasswordExpiredForm>>,
+) -> Result<Response, InternalError> {
+    let user_agent = user_agent.map(|ua| ua.as_str().to_owned());
+    let form = cookie_jar.verify_form(&clock, form)?;
+
+    // If the pending change expired, or if the user or its password changed in
+    // the meantime, start over
+    let Some(pending) = PendingPasswordChange::load(&cookie_jar, &clock) else {
+        let destination = mas_router::Login::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    };
+
+    let user = repo.user().lookup(pending.user_id).await?;
+    let active_password = match &user {
+        Some(user) if user.is_valid() => repo.user_password().active(user).await?,
+        _ => None,
+    };
+    let (Some(user), Some(active_password)) = (
+        user,
+        active_password.filter(|password| password.id == pending.user_password_id),
+    ) else {
+        let cookie_jar = PendingPasswordChange::remove(cookie_jar);
+        let destination = mas_router::Login::from(query.post_auth_action);
+        return Ok((cookie_jar, url_builder.redirect(&destination)).into_response());
+    };
+
+    let user_totp = confirmed_totp(&mut repo, &user).await?;
+    let mfa_required = user_totp.is_some();
+
+    let mut form_state = form.to_form_state();
+
+    if form.new_password.is_empty() {
+        form_state.add_error_on_field(
+            LoginPasswordExpiredFormField::NewPassword,
+            FieldError::Required,
+        );
+    }
+
+    if form.new_password_confirm.is_empty() {
+        form_state.add_error_on_field(
+            LoginPasswordExpiredFormField::NewPasswordConfirm,
+            FieldError::Required,
+        );
+    }
+
+    if form.new_password != form.new_password_confirm {
+        form_state.add_error_on_field(
+            LoginPasswordExpiredFormField::NewPasswordConfirm,
+            FieldError::PasswordMismatch,
+        );
+    }
+
+    if mfa_required && form.code.trim().is_empty() {
+        form_state.add_error_on_field(LoginPasswordExpiredFormField::Code, FieldError::Required);
+    }
+
+    if !form_state.is_valid() {
+        return render(
+            locale,
+            cookie_jar,
+            form_state,
+            mfa_required,
+            &clock,
+            &mut rng,
+            &templates,
+        );
+    }
+
+    // The new password must not be the expired one, regardless of the
+    // configured history
+    let same_as_expired = password_manager
+        .verify(
+            active_password.version,
+            Zeroizing::new(form.new_password.clone()),
+            active_password.hashed_password,
+        )
+        .await
+        .is_ok_and(|result| result.is_success());
+
+    if !password_manager.is_password_complex_enough(&form.new_password)? {
+        // TODO localise this error
+        form_state.add_error_on_field(
+            LoginPasswordExpiredFormField::NewPassword,
+            FieldError::Policy {
+                code: None,
+                message: "Password is too weak".to_owned(),
+            },
+        );
+    } else if password_manager
+        .is_password_breached(&form.new_password)
+        .await
+        .map_err(InternalError::from_anyhow)?
+    {
+        form_state.add_error_on_field(
+            LoginPasswordExpiredFormField::NewPassword,
+            FieldError::PasswordBreached,
+        );
+    } else if same_as_expired
+        || password_manager
+            .is_password_reused(&mut repo, &user, &form.new_password)
+            .await
+            .map_err(InternalError::from_anyhow)?
+    {
+        form_state.add_error_on_field(
+            LoginPasswordExpiredFormField::NewPassword,
+            FieldError::PasswordReused,
+        );
+    }
+
+    if !form_state.is_valid() {
+        return render(
+            locale,
+            cookie_jar,
+            form_state,
+            mfa_required,
+            &clock,
+            &mut rng,
+            &templates,
+        );
+    }
+
+    // If the user enrolled a second factor, they have to provide it before their
+    // password is changed, else knowing the expired password would be enough to
+    // take over the account
+    let second_factor = if let Some(user_totp) = user_totp {
+        // Codes are short, so they share the password rate limit to prevent brute
+        // forcing them
+        if let Err(e) = limiter.check_password(requester, &user).await {
+            tracing::warn!(error = &e as &dyn std::error::Error, "ratelimit exceeded");
+            let form_state = form_state.with_error_on_form(FormError::RateLimitExceeded);
+            return render(
+                locale,
+                cookie_jar,
+                form_state,
+                mfa_required,
+                &clock,
+                &mut rng,
+                &templates,
+            );
+        }
+
+        let Some(second_factor) =
+            SecondFactor::verify(&mut repo, &clock, &encrypter, &user, user_totp, &form.code)
+                .await?
+        else {
+            let form_state = form_state.with_error_on_form(FormError::InvalidCredentials);
+            return render(
+                locale,
+                cookie_jar,
+                form_state,
+                mfa_required,
+                &clock,
+                &mut rng,
+                &templates,
+            );
+        };
+
+        Some(second_factor)
+    } else {
+        None
+    };
+
+    let (version, hashed_password) = password_manager
+        .hash(&mut rng, Zeroizing::new(form.new_password))
+        .await
+        .map_err(InternalError::from_anyhow)?;
+
+    let user_password = repo
+        .user_password()
+        .add(&mut rng, &clock, &user, version, hashed_password, None)
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(&user, SecurityNotification::PasswordChanged)
+                .with_language(locale.to_string()),
+        )
+        .await?;
+
+    let cookie_jar = PendingPasswordChange::remove(cookie_jar);
+
+    let user_session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, user_agent)
+        .await?;
+
+    repo.browser_session()
+        .authenticate_with_password(&mut rng, &clock, &user_session, &user_password)
+        .await?;
+
+    if let Some(second_factor) = second_factor {
+        second_factor
+            .authenticate(&mut repo, &mut rng, &clock, &user_session)
+            .await?;
+    }
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            SendSecurityNotificationJob::new(
+                &user,
+                SecurityNotification::new_browser_session(&user_session),
+            )
+            .with_language(locale.to_string()),
+        )
+        .await?;
+
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::browser_session_created(&user_session),
+        )
+        .await?;
+
+    repo.save().await?;
+
+    activity_tracker
+        .record_browser_session(&clock, &user_session)
+        .await;
+
+    let cookie_jar = cookie_jar.set_session(&user_session);
+    let reply = query.go_next(&url_builder);
+    Ok((cookie_jar, reply).into_response())
+}
+
+fn render(
+    locale: DataLocale,
+    cookie_jar: CookieJar,
+    form_state: FormState<LoginPasswordExpiredFormField>,
+    mfa_required: bool,
+    clock: &impl Clock,
+    mut rng: impl Rng + Send,
+    templates: &Templates,
+) -> Result<Response, InternalError> {
+    let (csrf_token, cookie_jar) = cookie_jar.csrf_token(clock, &mut rng);
+    let ctx = LoginPasswordExpiredContext::new()
+        .with_form_state(form_state)
+        .with_mfa_required(mfa_required)
+        .with_csrf(csrf_token.form_value())
+        .with_language(locale);
+
+    let content = templates.render_login_password_expired(&ctx)?;
+
+    Ok((cookie_jar, Html(content)).into_response())
+}
@@ -13,14 +13,19 @@ use mas_axum_utils::{
     cookies::CookieJar,
     csrf::{CsrfExt, ProtectedForm},
 };
-use mas_data_model::BoxClock;
+use mas_data_model::{BoxClock, BoxRng};
 use mas_router::{PostAuthAction, UrlBuilder};
-use mas_storage::{BoxRepository, user::BrowserSessionRepository};
+use mas_storage::{
+    BoxRepository,
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _},
+    user::BrowserSessionRepository,
+};
 
 use crate::BoundActivityTracker;
 
 #[tracing::instrument(name = "handlers.views.logout.post", skip_all)]
 pub(crate) async fn post(
+    mut rng: BoxRng,
     clock: BoxClock,
     mut repo: BoxRepository,
     cookie_jar: CookieJar,
@@ -41,7 +46,16 @@ pub(crate) async fn post(
                 .record_browser_session(&clock, &session)
                 .await;
 
-            repo.browser_session().finish(&clock, session).await?;
+            let session = repo.browser_session().finish(&clock, session).await?;
+
+            // Notify the clients which got a session through this browser session
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    &clock,
+                    BackchannelLogoutJob::for_browser_session(&session),
+                )
+                .await?;
         }
     }
 
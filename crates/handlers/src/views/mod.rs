@@ -7,6 +7,8 @@
 pub mod app;
 pub mod index;
 pub mod login;
+pub mod login_mfa;
+pub mod login_password_expired;
 pub mod logout;
 pub mod recovery;
 pub mod register;
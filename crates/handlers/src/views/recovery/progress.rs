@@ -125,7 +125,10 @@ pub(crate) async fn post(
     let () = cookie_jar.verify_form(&clock, form)?;
 
     // Check the rate limit if we are about to process the form
-    if let Err(e) = limiter.check_account_recovery(requester, &recovery_session.email) {
+    if let Err(e) = limiter
+        .check_account_recovery(requester, &recovery_session.email)
+        .await
+    {
         tracing::warn!(error = &e as &dyn std::error::Error);
         let context = RecoveryProgressContext::new(recovery_session, true)
             .with_csrf(csrf_token.form_value())
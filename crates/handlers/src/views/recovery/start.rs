@@ -115,7 +115,7 @@ pub(crate) async fn post(
 
     if form_state.is_valid() {
         // Check the rate limit if we are about to process the form
-        if let Err(e) = limiter.check_account_recovery(requester, &form.email) {
+        if let Err(e) = limiter.check_account_recovery(requester, &form.email).await {
             tracing::warn!(error = &e as &dyn std::error::Error);
             form_state.add_error_on_form(FormError::RateLimitExceeded);
         }
@@ -239,6 +239,12 @@ pub(crate) async fn post(
                     message: "Password is too weak".to_owned(),
                 },
             );
+        } else if password_manager
+            .is_password_breached(&form.password)
+            .await
+            .map_err(InternalError::from_anyhow)?
+        {
+            state.add_error_on_field(RegisterFormField::Password, FieldError::PasswordBreached);
         }
 
         // If the site has terms of service, the user must accept them
@@ -300,13 +306,15 @@ pub(crate) async fn post(
 
         if state.is_valid() {
             // Check the rate limit if we are about to process the form
-            if let Err(e) = limiter.check_registration(requester) {
+            if let Err(e) = limiter.check_registration(requester).await {
                 tracing::warn!(error = &e as &dyn std::error::Error);
                 state.add_error_on_form(FormError::RateLimitExceeded);
             }
 
             if let Some(email) = &email
-                && let Err(e) = limiter.check_email_authentication_email(requester, email)
+                && let Err(e) = limiter
+                    .check_email_authentication_email(requester, email)
+                    .await
             {
                 tracing::warn!(error = &e as &dyn std::error::Error);
                 state.add_error_on_form(FormError::RateLimitExceeded);
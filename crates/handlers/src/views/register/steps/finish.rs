@@ -18,7 +18,7 @@ use mas_matrix::HomeserverConnection;
 use mas_router::{PostAuthAction, UrlBuilder};
 use mas_storage::{
     BoxRepository,
-    queue::{ProvisionUserJob, QueueJobRepositoryExt as _},
+    queue::{DispatchWebhookEventJob, ProvisionUserJob, QueueJobRepositoryExt as _},
     user::UserEmailFilter,
 };
 use mas_templates::{RegisterStepsEmailInUseContext, TemplateContext as _, Templates};
@@ -246,11 +246,15 @@ pub(crate) async fn get(
         .add(&mut rng, &clock, &user, user_agent)
         .await?;
 
-    if let Some(email_authentication) = email_authentication {
-        repo.user_email()
+    let user_email = if let Some(email_authentication) = email_authentication {
+        let user_email = repo
+            .user_email()
             .add(&mut rng, &clock, &user, email_authentication.email)
             .await?;
-    }
+        Some(user_email)
+    } else {
+        None
+    };
 
     if let Some(password) = registration.password {
         let user_password = repo
@@ -284,6 +288,30 @@ pub(crate) async fn get(
     }
     repo.queue_job().schedule_job(&mut rng, &clock, job).await?;
 
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::user_registered(&user),
+        )
+        .await?;
+    if let Some(user_email) = &user_email {
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                &clock,
+                DispatchWebhookEventJob::user_email_verified(user_email),
+            )
+            .await?;
+    }
+    repo.queue_job()
+        .schedule_job(
+            &mut rng,
+            &clock,
+            DispatchWebhookEventJob::browser_session_created(&user_session),
+        )
+        .await?;
+
     repo.save().await?;
 
     activity_tracker
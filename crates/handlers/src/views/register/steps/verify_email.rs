@@ -163,7 +163,10 @@ pub(crate) async fn post(
         )));
     }
 
-    if let Err(e) = limiter.check_email_authentication_attempt(&email_authentication) {
+    if let Err(e) = limiter
+        .check_email_authentication_attempt(&email_authentication)
+        .await
+    {
         tracing::warn!(error = &e as &dyn std::error::Error);
         let (csrf_token, cookie_jar) = cookie_jar.csrf_token(&clock, &mut rng);
         let ctx = RegisterStepsVerifyEmailContext::new(email_authentication)
@@ -0,0 +1,776 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A minimal implementation of the `WebAuthn` relying party logic, used to
+//! register and authenticate with passkeys.
+//!
+//! We only support the `none` attestation conveyance, so that the attestation
+//! statement is ignored, and the ES256 and RS256 algorithms, which cover all
+//! the authenticators in the wild.
+
+use base64ct::{Base64UrlUnpadded, Encoding};
+use ciborium::Value;
+use mas_data_model::{User, UserPasskey, UserPasskeyChallenge};
+use mas_router::UrlBuilder;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+// https://www.w3.org/TR/webauthn-3/#sctn-alg-identifier
+const COSE_ALG_ES256: i64 = -7;
+const COSE_ALG_RS256: i64 = -257;
+
+// https://www.w3.org/TR/webauthn-3/#authdata-flags
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// How long the browser should wait for the user to interact with their
+/// authenticator, in milliseconds
+const TIMEOUT_MS: u64 = 5 * 60 * 1000;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("The WebAuthn response is malformed")]
+    Malformed,
+
+    #[error("The client data type is invalid, expected {expected:?}, got {got:?}")]
+    TypeMismatch { expected: &'static str, got: String },
+
+    #[error("The challenge in the response does not match the one issued")]
+    ChallengeMismatch,
+
+    #[error("The origin in the response ({got:?}) does not match the site origin ({expected:?})")]
+    OriginMismatch { expected: String, got: String },
+
+    #[error("The authenticator data is for another relying party")]
+    RpIdMismatch,
+
+    #[error("The user was not present during the ceremony")]
+    UserNotPresent,
+
+    #[error("The authenticator did not verify the user")]
+    UserNotVerified,
+
+    #[error("The authenticator did not return any credential")]
+    MissingCredential,
+
+    #[error("The credential uses an unsupported public key type or algorithm")]
+    UnsupportedAlgorithm,
+
+    #[error("The credential does not belong to the user")]
+    UserMismatch,
+
+    #[error("The signature is invalid")]
+    InvalidSignature,
+
+    #[error(
+        "The signature counter went backwards (stored {stored}, got {got}), the authenticator may have been cloned"
+    )]
+    SignCountMismatch { stored: u32, got: u32 },
+}
+
+/// The response of a `navigator.credentials.create()` call, as serialized by
+/// the browser
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationResponse {
+    id: String,
+    response: AuthenticatorAttestationResponse,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticatorAttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    attestation_object: String,
+    #[serde(default)]
+    transports: Vec<String>,
+}
+
+/// The response of a `navigator.credentials.get()` call, as serialized by the
+/// browser
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticationResponse {
+    id: String,
+    response: AuthenticatorAssertionResponse,
+}
+
+impl AuthenticationResponse {
+    /// The base64url-encoded ID of the credential used to authenticate
+    #[must_use]
+    pub fn credential_id(&self) -> &str {
+        &self.id
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthenticatorAssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    client_data_json: String,
+    authenticator_data: String,
+    signature: String,
+    #[serde(default)]
+    user_handle: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectedClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+/// A credential which was verified during a registration ceremony, ready to
+/// be saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedCredential {
+    pub credential_id: String,
+    pub transports: Vec<String>,
+    pub public_key: Vec<u8>,
+    pub aaguid: Option<String>,
+    pub sign_count: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CredentialDescriptor<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: &'a str,
+    transports: &'a [String],
+}
+
+/// Relying party parameters, derived from the public base URL of the service
+#[derive(Debug, Clone)]
+pub struct Webauthn {
+    rp_id: String,
+    origin: String,
+}
+
+impl Webauthn {
+    /// Create the relying party parameters from the [`UrlBuilder`]
+    #[must_use]
+    pub fn new(url_builder: &UrlBuilder) -> Self {
+        Self {
+            rp_id: url_builder.public_hostname().to_owned(),
+            origin: url_builder.http_base().origin().ascii_serialization(),
+        }
+    }
+
+    /// Generate a new random challenge
+    pub fn generate_challenge(rng: &mut (impl RngCore + ?Sized)) -> Vec<u8> {
+        let mut challenge = vec![0; 32];
+        rng.fill_bytes(&mut challenge);
+        challenge
+    }
+
+    /// Options to pass to `navigator.credentials.create()`, with binary values
+    /// encoded as base64url
+    #[must_use]
+    pub fn creation_options(
+        &self,
+        challenge: &UserPasskeyChallenge,
+        user: &User,
+        existing: &[UserPasskey],
+    ) -> serde_json::Value {
+        let exclude_credentials: Vec<_> = existing
+            .iter()
+            .map(|passkey| CredentialDescriptor {
+                kind: "public-key",
+                id: &passkey.credential_id,
+                transports: &passkey.transports,
+            })
+            .collect();
+
+        serde_json::json!({
+            "rp": {
+                "id": self.rp_id,
+                "name": self.rp_id,
+            },
+            "user": {
+                "id": Base64UrlUnpadded::encode_string(&user.id.to_bytes()),
+                "name": user.username,
+                "displayName": user.username,
+            },
+            "challenge": Base64UrlUnpadded::encode_string(&challenge.challenge),
+            "pubKeyCredParams": [
+                { "type": "public-key", "alg": COSE_ALG_ES256 },
+                { "type": "public-key", "alg": COSE_ALG_RS256 },
+            ],
+            "timeout": TIMEOUT_MS,
+            "excludeCredentials": exclude_credentials,
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "required",
+            },
+            "attestation": "none",
+        })
+    }
+
+    /// Options to pass to `navigator.credentials.get()`, with binary values
+    /// encoded as base64url
+    #[must_use]
+    pub fn request_options(&self, challenge: &UserPasskeyChallenge) -> serde_json::Value {
+        serde_json::json!({
+            "challenge": Base64UrlUnpadded::encode_string(&challenge.challenge),
+            "rpId": self.rp_id,
+            "timeout": TIMEOUT_MS,
+            "allowCredentials": [],
+            "userVerification": "required",
+        })
+    }
+
+    /// Parse a registration response sent by the browser
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is not valid JSON
+    pub fn parse_registration(response: &str) -> Result<RegistrationResponse, Error> {
+        serde_json::from_str(response).map_err(|_| Error::Malformed)
+    }
+
+    /// Parse an authentication response sent by the browser
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is not valid JSON
+    pub fn parse_authentication(response: &str) -> Result<AuthenticationResponse, Error> {
+        serde_json::from_str(response).map_err(|_| Error::Malformed)
+    }
+
+    fn verify_client_data(
+        &self,
+        client_data_json: &[u8],
+        expected_type: &'static str,
+        challenge: &UserPasskeyChallenge,
+    ) -> Result<(), Error> {
+        let client_data: CollectedClientData =
+            serde_json::from_slice(client_data_json).map_err(|_| Error::Malformed)?;
+
+        if client_data.kind != expected_type {
+            return Err(Error::TypeMismatch {
+                expected: expected_type,
+                got: client_data.kind,
+            });
+        }
+
+        let got_challenge = decode(&client_data.challenge)?;
+        if got_challenge != challenge.challenge {
+            return Err(Error::ChallengeMismatch);
+        }
+
+        if client_data.origin != self.origin {
+            return Err(Error::OriginMismatch {
+                expected: self.origin.clone(),
+                got: client_data.origin,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn verify_authenticator_data<'a>(
+        &self,
+        auth_data: &'a [u8],
+    ) -> Result<AuthenticatorData<'a>, Error> {
+        let auth_data = AuthenticatorData::parse(auth_data)?;
+
+        let rp_id_hash = Sha256::digest(self.rp_id.as_bytes());
+        if auth_data.rp_id_hash != rp_id_hash.as_slice() {
+            return Err(Error::RpIdMismatch);
+        }
+
+        if auth_data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(Error::UserNotPresent);
+        }
+
+        // Passkeys are used as a single factor, so the authenticator must have
+        // verified the user with a PIN or biometrics
+        if auth_data.flags & FLAG_USER_VERIFIED == 0 {
+            return Err(Error::UserNotVerified);
+        }
+
+        Ok(auth_data)
+    }
+
+    /// Verify a registration response against the challenge which was issued
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is invalid
+    pub fn verify_registration(
+        &self,
+        challenge: &UserPasskeyChallenge,
+        response: &RegistrationResponse,
+    ) -> Result<VerifiedCredential, Error> {
+        let client_data_json = decode(&response.response.client_data_json)?;
+        self.verify_client_data(&client_data_json, "webauthn.create", challenge)?;
+
+        let attestation_object = decode(&response.response.attestation_object)?;
+        let attestation_object: Value =
+            ciborium::from_reader(attestation_object.as_slice()).map_err(|_| Error::Malformed)?;
+        let auth_data = map_get(&attestation_object, &Value::Text("authData".to_owned()))
+            .and_then(Value::as_bytes)
+            .ok_or(Error::Malformed)?;
+
+        let auth_data = self.verify_authenticator_data(auth_data)?;
+        let credential = auth_data
+            .attested_credential
+            .ok_or(Error::MissingCredential)?;
+
+        // Make sure the public key can be used later on
+        CoseKey::parse(credential.public_key)?;
+
+        let credential_id = Base64UrlUnpadded::encode_string(credential.credential_id);
+        if credential_id != response.id.trim_end_matches('=') {
+            return Err(Error::Malformed);
+        }
+
+        let aaguid = (credential.aaguid != [0; 16]).then(|| format_aaguid(credential.aaguid));
+
+        Ok(VerifiedCredential {
+            credential_id,
+            transports: response.response.transports.clone(),
+            public_key: credential.public_key.to_vec(),
+            aaguid,
+            sign_count: auth_data.sign_count,
+        })
+    }
+
+    /// Verify an authentication response against the challenge which was
+    /// issued and the stored passkey
+    ///
+    /// Returns the new signature counter of the passkey
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response is invalid
+    pub fn verify_authentication(
+        &self,
+        challenge: &UserPasskeyChallenge,
+        response: &AuthenticationResponse,
+        passkey: &UserPasskey,
+    ) -> Result<u32, Error> {
+        let client_data_json = decode(&response.response.client_data_json)?;
+        self.verify_client_data(&client_data_json, "webauthn.get", challenge)?;
+
+        if let Some(user_handle) = &response.response.user_handle
+            && decode(user_handle)? != passkey.user_id.to_bytes()
+        {
+            return Err(Error::UserMismatch);
+        }
+
+        let raw_auth_data = decode(&response.response.authenticator_data)?;
+        let auth_data = self.verify_authenticator_data(&raw_auth_data)?;
+
+        let signature = decode(&response.response.signature)?;
+        let mut message = raw_auth_data.clone();
+        message.extend_from_slice(&Sha256::digest(&client_data_json));
+
+        CoseKey::parse(&passkey.public_key)?.verify(&message, &signature)?;
+
+        // Authenticators which don't support counters always return 0
+        if (auth_data.sign_count != 0 || passkey.sign_count != 0)
+            && auth_data.sign_count <= passkey.sign_count
+        {
+            return Err(Error::SignCountMismatch {
+                stored: passkey.sign_count,
+                got: auth_data.sign_count,
+            });
+        }
+
+        Ok(auth_data.sign_count)
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, Error> {
+    Base64UrlUnpadded::decode_vec(value.trim_end_matches('=')).map_err(|_| Error::Malformed)
+}
+
+fn map_get<'a>(value: &'a Value, key: &Value) -> Option<&'a Value> {
+    value
+        .as_map()?
+        .iter()
+        .find_map(|(k, v)| (k == key).then_some(v))
+}
+
+fn format_aaguid(aaguid: &[u8]) -> String {
+    let hex = hex::encode(aaguid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+struct AttestedCredential<'a> {
+    aaguid: &'a [u8],
+    credential_id: &'a [u8],
+    public_key: &'a [u8],
+}
+
+// https://www.w3.org/TR/webauthn-3/#sctn-authenticator-data
+struct AuthenticatorData<'a> {
+    rp_id_hash: &'a [u8],
+    flags: u8,
+    sign_count: u32,
+    attested_credential: Option<AttestedCredential<'a>>,
+}
+
+impl<'a> AuthenticatorData<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let (rp_id_hash, rest) = data.split_at_checked(32).ok_or(Error::Malformed)?;
+        let (&flags, rest) = rest.split_first().ok_or(Error::Malformed)?;
+        let (sign_count, rest) = rest.split_first_chunk::<4>().ok_or(Error::Malformed)?;
+        let sign_count = u32::from_be_bytes(*sign_count);
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA == 0 {
+            None
+        } else {
+            let (aaguid, rest) = rest.split_at_checked(16).ok_or(Error::Malformed)?;
+            let (length, rest) = rest.split_first_chunk::<2>().ok_or(Error::Malformed)?;
+            let length = usize::from(u16::from_be_bytes(*length));
+            let (credential_id, rest) = rest.split_at_checked(length).ok_or(Error::Malformed)?;
+
+            // The public key is a CBOR map, possibly followed by extensions: decode
+            // it to find out where it ends
+            let mut reader = rest;
+            let _: Value = ciborium::from_reader(&mut reader).map_err(|_| Error::Malformed)?;
+            let public_key = &rest[..rest.len() - reader.len()];
+
+            Some(AttestedCredential {
+                aaguid,
+                credential_id,
+                public_key,
+            })
+        };
+
+        Ok(Self {
+            rp_id_hash,
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+}
+
+// https://www.rfc-editor.org/rfc/rfc9053.html#section-7
+enum CoseKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    Rs256(rsa::pkcs1v15::VerifyingKey<Sha256>),
+}
+
+impl CoseKey {
+    fn parse(data: &[u8]) -> Result<Self, Error> {
+        let key: Value = ciborium::from_reader(data).map_err(|_| Error::Malformed)?;
+        let param = |label: i64| map_get(&key, &Value::Integer(label.into()));
+        let int_param = |label: i64| {
+            param(label)
+                .and_then(Value::as_integer)
+                .and_then(|i| i64::try_from(i).ok())
+        };
+        let bytes_param = |label: i64| {
+            param(label)
+                .and_then(Value::as_bytes)
+                .ok_or(Error::Malformed)
+        };
+
+        match (int_param(1), int_param(3)) {
+            // kty: EC2, alg: ES256
+            (Some(2), Some(COSE_ALG_ES256)) => {
+                // crv: P-256
+                if int_param(-1) != Some(1) {
+                    return Err(Error::UnsupportedAlgorithm);
+                }
+
+                let x = bytes_param(-2)?;
+                let y = bytes_param(-3)?;
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(Error::Malformed);
+                }
+
+                let point = p256::EncodedPoint::from_affine_coordinates(
+                    x.as_slice().into(),
+                    y.as_slice().into(),
+                    false,
+                );
+                let key = p256::ecdsa::VerifyingKey::from_encoded_point(&point)
+                    .map_err(|_| Error::Malformed)?;
+                Ok(Self::Es256(key))
+            }
+
+            // kty: RSA, alg: RS256
+            (Some(3), Some(COSE_ALG_RS256)) => {
+                let n = rsa::BigUint::from_bytes_be(bytes_param(-1)?);
+                let e = rsa::BigUint::from_bytes_be(bytes_param(-2)?);
+                let key = rsa::RsaPublicKey::new(n, e).map_err(|_| Error::Malformed)?;
+                Ok(Self::Rs256(rsa::pkcs1v15::VerifyingKey::new(key)))
+            }
+
+            _ => Err(Error::UnsupportedAlgorithm),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), Error> {
+        use rsa::signature::Verifier as _;
+
+        match self {
+            Self::Es256(key) => {
+                // WebAuthn ECDSA signatures are DER-encoded
+                let signature = p256::ecdsa::Signature::from_der(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify(message, &signature)
+                    .map_err(|_| Error::InvalidSignature)
+            }
+            Self::Rs256(key) => {
+                let signature = rsa::pkcs1v15::Signature::try_from(signature)
+                    .map_err(|_| Error::InvalidSignature)?;
+                key.verify(message, &signature)
+                    .map_err(|_| Error::InvalidSignature)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use p256::ecdsa::{SigningKey, signature::Signer as _};
+    use rand::SeedableRng;
+    use ulid::Ulid;
+
+    use super::*;
+
+    const ORIGIN: &str = "https://example.com";
+
+    fn webauthn() -> Webauthn {
+        Webauthn {
+            rp_id: "example.com".to_owned(),
+            origin: ORIGIN.to_owned(),
+        }
+    }
+
+    fn challenge() -> UserPasskeyChallenge {
+        UserPasskeyChallenge {
+            id: Ulid::nil(),
+            user_session_id: None,
+            challenge: vec![42; 32],
+            created_at: DateTime::<Utc>::UNIX_EPOCH,
+            completed_at: None,
+        }
+    }
+
+    fn client_data(kind: &str, challenge: &UserPasskeyChallenge) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": kind,
+            "challenge": Base64UrlUnpadded::encode_string(&challenge.challenge),
+            "origin": ORIGIN,
+            "crossOrigin": false,
+        }))
+        .unwrap()
+    }
+
+    fn cose_key(key: &SigningKey) -> Vec<u8> {
+        let point = key.verifying_key().to_encoded_point(false);
+        let value = Value::Map(vec![
+            (Value::Integer(1.into()), Value::Integer(2.into())),
+            (Value::Integer(3.into()), Value::Integer((-7).into())),
+            (Value::Integer((-1).into()), Value::Integer(1.into())),
+            (
+                Value::Integer((-2).into()),
+                Value::Bytes(point.x().unwrap().to_vec()),
+            ),
+            (
+                Value::Integer((-3).into()),
+                Value::Bytes(point.y().unwrap().to_vec()),
+            ),
+        ]);
+        let mut buf = Vec::new();
+        ciborium::into_writer(&value, &mut buf).unwrap();
+        buf
+    }
+
+    fn auth_data(flags: u8, sign_count: u32, credential: Option<(&[u8], &[u8])>) -> Vec<u8> {
+        let mut data = Sha256::digest(b"example.com").to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        if let Some((credential_id, public_key)) = credential {
+            data.extend_from_slice(&[0; 16]);
+            data.extend_from_slice(&u16::try_from(credential_id.len()).unwrap().to_be_bytes());
+            data.extend_from_slice(credential_id);
+            data.extend_from_slice(public_key);
+        }
+        data
+    }
+
+    fn register(key: &SigningKey, challenge: &UserPasskeyChallenge) -> VerifiedCredential {
+        let credential_id = [1, 2, 3, 4];
+        let auth_data = auth_data(
+            FLAG_USER_PRESENT | FLAG_USER_VERIFIED | FLAG_ATTESTED_CREDENTIAL_DATA,
+            0,
+            Some((&credential_id, &cose_key(key))),
+        );
+        let attestation_object = Value::Map(vec![
+            (
+                Value::Text("fmt".to_owned()),
+                Value::Text("none".to_owned()),
+            ),
+            (Value::Text("attStmt".to_owned()), Value::Map(Vec::new())),
+            (Value::Text("authData".to_owned()), Value::Bytes(auth_data)),
+        ]);
+        let mut attestation_object_bytes = Vec::new();
+        ciborium::into_writer(&attestation_object, &mut attestation_object_bytes).unwrap();
+
+        let response = serde_json::json!({
+            "id": Base64UrlUnpadded::encode_string(&credential_id),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": Base64UrlUnpadded::encode_string(&client_data("webauthn.create", challenge)),
+                "attestationObject": Base64UrlUnpadded::encode_string(&attestation_object_bytes),
+                "transports": ["internal"],
+            },
+        });
+        let response = Webauthn::parse_registration(&response.to_string()).unwrap();
+        webauthn()
+            .verify_registration(challenge, &response)
+            .unwrap()
+    }
+
+    fn passkey(credential: VerifiedCredential, user_id: Ulid) -> UserPasskey {
+        UserPasskey {
+            id: Ulid::nil(),
+            user_id,
+            credential_id: credential.credential_id,
+            name: "Test".to_owned(),
+            transports: credential.transports,
+            public_key: credential.public_key,
+            aaguid: credential.aaguid,
+            sign_count: credential.sign_count,
+            created_at: DateTime::<Utc>::UNIX_EPOCH,
+            last_used_at: None,
+        }
+    }
+
+    fn assertion(
+        key: &SigningKey,
+        challenge: &UserPasskeyChallenge,
+        sign_count: u32,
+        user_id: Ulid,
+    ) -> AuthenticationResponse {
+        assertion_with_flags(
+            key,
+            challenge,
+            FLAG_USER_PRESENT | FLAG_USER_VERIFIED,
+            sign_count,
+            user_id,
+        )
+    }
+
+    fn assertion_with_flags(
+        key: &SigningKey,
+        challenge: &UserPasskeyChallenge,
+        flags: u8,
+        sign_count: u32,
+        user_id: Ulid,
+    ) -> AuthenticationResponse {
+        let client_data = client_data("webauthn.get", challenge);
+        let auth_data = auth_data(flags, sign_count, None);
+        let mut message = auth_data.clone();
+        message.extend_from_slice(&Sha256::digest(&client_data));
+        let signature: p256::ecdsa::Signature = key.sign(&message);
+
+        let response = serde_json::json!({
+            "id": Base64UrlUnpadded::encode_string(&[1, 2, 3, 4]),
+            "type": "public-key",
+            "response": {
+                "clientDataJSON": Base64UrlUnpadded::encode_string(&client_data),
+                "authenticatorData": Base64UrlUnpadded::encode_string(&auth_data),
+                "signature": Base64UrlUnpadded::encode_string(signature.to_der().as_bytes()),
+                "userHandle": Base64UrlUnpadded::encode_string(&user_id.to_bytes()),
+            },
+        });
+        Webauthn::parse_authentication(&response.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_register_and_authenticate() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = SigningKey::random(&mut rng);
+        let user_id = Ulid::from_bytes([7; 16]);
+        let challenge = challenge();
+
+        let credential = register(&key, &challenge);
+        assert_eq!(credential.credential_id, "AQIDBA");
+        assert_eq!(credential.transports, vec!["internal".to_owned()]);
+        assert_eq!(credential.aaguid, None);
+        assert_eq!(credential.sign_count, 0);
+
+        let passkey = passkey(credential, user_id);
+        let response = assertion(&key, &challenge, 1, user_id);
+        assert_eq!(response.credential_id(), "AQIDBA");
+        let sign_count = webauthn()
+            .verify_authentication(&challenge, &response, &passkey)
+            .unwrap();
+        assert_eq!(sign_count, 1);
+    }
+
+    #[test]
+    fn test_authenticate_errors() {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let key = SigningKey::random(&mut rng);
+        let user_id = Ulid::from_bytes([7; 16]);
+        let challenge = challenge();
+        let mut passkey = passkey(register(&key, &challenge), user_id);
+
+        // Signed by another key
+        let other_key = SigningKey::random(&mut rng);
+        let response = assertion(&other_key, &challenge, 1, user_id);
+        assert!(matches!(
+            webauthn().verify_authentication(&challenge, &response, &passkey),
+            Err(Error::InvalidSignature)
+        ));
+
+        // Another challenge
+        let mut other_challenge = challenge.clone();
+        other_challenge.challenge = vec![0; 32];
+        let response = assertion(&key, &other_challenge, 1, user_id);
+        assert!(matches!(
+            webauthn().verify_authentication(&challenge, &response, &passkey),
+            Err(Error::ChallengeMismatch)
+        ));
+
+        // Another user
+        let response = assertion(&key, &challenge, 1, Ulid::nil());
+        assert!(matches!(
+            webauthn().verify_authentication(&challenge, &response, &passkey),
+            Err(Error::UserMismatch)
+        ));
+
+        // The user was not verified by the authenticator
+        let response = assertion_with_flags(&key, &challenge, FLAG_USER_PRESENT, 1, user_id);
+        assert!(matches!(
+            webauthn().verify_authentication(&challenge, &response, &passkey),
+            Err(Error::UserNotVerified)
+        ));
+
+        // Counter went backwards
+        passkey.sign_count = 5;
+        let response = assertion(&key, &challenge, 3, user_id);
+        assert!(matches!(
+            webauthn().verify_authentication(&challenge, &response, &passkey),
+            Err(Error::SignCountMismatch { stored: 5, got: 3 })
+        ));
+    }
+}
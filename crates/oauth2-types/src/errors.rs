@@ -265,6 +265,14 @@ pub enum ClientErrorCode {
     /// From [RFC7009](https://www.rfc-editor.org/rfc/rfc7009#section-2.2.1).
     UnsupportedTokenType,
 
+    /// `invalid_dpop_proof`
+    ///
+    /// The DPoP proof presented with the request is missing, malformed or
+    /// doesn't match the request.
+    ///
+    /// From [RFC9449](https://www.rfc-editor.org/rfc/rfc9449#section-5).
+    InvalidDpopProof,
+
     /// Another error code.
     Unknown(String),
 }
@@ -297,6 +305,7 @@ impl core::fmt::Display for ClientErrorCode {
             ClientErrorCode::SlowDown => f.write_str("slow_down"),
             ClientErrorCode::ExpiredToken => f.write_str("expired_token"),
             ClientErrorCode::UnsupportedTokenType => f.write_str("unsupported_token_type"),
+            ClientErrorCode::InvalidDpopProof => f.write_str("invalid_dpop_proof"),
             ClientErrorCode::Unknown(value) => f.write_str(value),
         }
     }
@@ -332,6 +341,7 @@ impl core::str::FromStr for ClientErrorCode {
             "slow_down" => Ok(ClientErrorCode::SlowDown),
             "expired_token" => Ok(ClientErrorCode::ExpiredToken),
             "unsupported_token_type" => Ok(ClientErrorCode::UnsupportedTokenType),
+            "invalid_dpop_proof" => Ok(ClientErrorCode::InvalidDpopProof),
             _ => Ok(ClientErrorCode::Unknown(s.to_owned())),
         }
     }
@@ -421,6 +431,7 @@ impl ClientErrorCode {
             ClientErrorCode::UnsupportedTokenType => {
                 "The authorization server does not support the revocation of the presented token type."
             }
+            ClientErrorCode::InvalidDpopProof => "The DPoP proof is invalid.",
             ClientErrorCode::Unknown(_) => "",
         }
     }
@@ -610,6 +621,10 @@ mod tests {
             serde_json::from_str::<ClientErrorCode>("\"invalid_client_metadata\"").unwrap(),
             ClientErrorCode::InvalidClientMetadata
         );
+        assert_eq!(
+            serde_json::from_str::<ClientErrorCode>("\"invalid_dpop_proof\"").unwrap(),
+            ClientErrorCode::InvalidDpopProof
+        );
 
         assert_eq!(
             serde_json::from_str::<ClientErrorCode>("\"unknown_error_code\"").unwrap(),
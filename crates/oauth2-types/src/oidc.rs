@@ -614,6 +614,25 @@ pub struct ProviderMetadata {
     /// [RP-Initiated Logout endpoint]: https://openid.net/specs/openid-connect-rpinitiated-1_0.html
     pub end_session_endpoint: Option<Url>,
 
+    /// Boolean value specifying whether the OP supports [Back-Channel Logout].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [Back-Channel Logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+    pub backchannel_logout_supported: Option<bool>,
+
+    /// Boolean value specifying whether the OP can pass a `sid` claim in the
+    /// logout token to identify the RP session with the OP.
+    ///
+    /// Defaults to `false`.
+    pub backchannel_logout_session_supported: Option<bool>,
+
+    /// JSON array containing a list of the JWS algorithms supported for [DPoP]
+    /// proof JWTs.
+    ///
+    /// [DPoP]: https://www.rfc-editor.org/rfc/rfc9449.html
+    pub dpop_signing_alg_values_supported: Option<Vec<JsonWebSignatureAlg>>,
+
     /// URL where the user is able to access the account management capabilities
     /// of this OP.
     ///
@@ -127,6 +127,9 @@ pub struct ClientMetadataSerdeHelper {
     introspection_encrypted_response_alg: Option<JsonWebEncryptionAlg>,
     introspection_encrypted_response_enc: Option<JsonWebEncryptionEnc>,
     post_logout_redirect_uris: Option<Vec<Url>>,
+    backchannel_logout_uri: Option<Url>,
+    backchannel_logout_session_required: Option<bool>,
+    access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
     #[serde(flatten)]
     extra: ClientMetadataLocalizedFields,
 }
@@ -178,6 +181,9 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            access_token_signed_response_alg,
         } = metadata;
 
         ClientMetadataSerdeHelper {
@@ -214,6 +220,9 @@ impl From<ClientMetadata> for ClientMetadataSerdeHelper {
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            access_token_signed_response_alg,
             extra: ClientMetadataLocalizedFields {
                 client_name,
                 logo_uri,
@@ -261,6 +270,9 @@ impl From<ClientMetadataSerdeHelper> for ClientMetadata {
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            access_token_signed_response_alg,
             extra:
                 ClientMetadataLocalizedFields {
                     client_name,
@@ -310,6 +322,9 @@ impl From<ClientMetadataSerdeHelper> for ClientMetadata {
             introspection_encrypted_response_alg,
             introspection_encrypted_response_enc,
             post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            access_token_signed_response_alg,
         }
     }
 }
@@ -429,6 +429,33 @@ pub struct ClientMetadata {
     ///
     /// [RP-Initiated Logout endpoint]: https://openid.net/specs/openid-connect-rpinitiated-1_0.html
     pub post_logout_redirect_uris: Option<Vec<Url>>,
+
+    /// URL that will cause the client to log itself out when sent a
+    /// [Logout Token] by the provider.
+    ///
+    /// This URL must not include a fragment component.
+    ///
+    /// [Logout Token]: https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken
+    pub backchannel_logout_uri: Option<Url>,
+
+    /// Whether the client requires that a `sid` (session ID) Claim be included
+    /// in the [Logout Token] to identify the client session with the provider
+    /// when the `backchannel_logout_uri` is used.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [Logout Token]: https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken
+    pub backchannel_logout_session_required: Option<bool>,
+
+    /// [JWS] `alg` algorithm for signing the access tokens issued to this
+    /// client.
+    ///
+    /// If this is provided, access tokens are issued as [JWT access tokens]
+    /// instead of opaque tokens.
+    ///
+    /// [JWS]: http://tools.ietf.org/html/draft-ietf-jose-json-web-signature
+    /// [JWT access tokens]: https://www.rfc-editor.org/rfc/rfc9068.html
+    pub access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
 }
 
 impl ClientMetadata {
@@ -481,6 +508,16 @@ impl ClientMetadata {
             }
         }
 
+        if let Some(uri) = self
+            .backchannel_logout_uri
+            .as_ref()
+            .filter(|uri| uri.fragment().is_some())
+        {
+            return Err(
+                ClientMetadataVerificationError::BackchannelLogoutUriWithFragment(uri.clone()),
+            );
+        }
+
         if self.jwks_uri.is_some() && self.jwks.is_some() {
             return Err(ClientMetadataVerificationError::JwksUriAndJwksMutuallyExclusive);
         }
@@ -503,6 +540,10 @@ impl ClientMetadata {
             return Err(ClientMetadataVerificationError::MissingJwksForTokenMethod);
         }
 
+        if self.require_signed_request_object() && self.jwks_uri.is_none() && self.jwks.is_none() {
+            return Err(ClientMetadataVerificationError::MissingJwksForRequestObject);
+        }
+
         if let Some(alg) = &self.token_endpoint_auth_signing_alg {
             if *alg == JsonWebSignatureAlg::None {
                 return Err(ClientMetadataVerificationError::UnauthorizedSigningAlgNone(
@@ -519,6 +560,12 @@ impl ClientMetadata {
             ));
         }
 
+        if self.access_token_signed_response_alg == Some(JsonWebSignatureAlg::None) {
+            return Err(ClientMetadataVerificationError::UnauthorizedSigningAlgNone(
+                "access_token",
+            ));
+        }
+
         if *self.id_token_signed_response_alg() == JsonWebSignatureAlg::None
             && response_types.iter().any(ResponseType::has_id_token)
         {
@@ -758,6 +805,16 @@ impl ClientMetadata {
         self.require_auth_time.unwrap_or_default()
     }
 
+    /// Whether the client requires a `sid` Claim in the [Logout Token].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [Logout Token]: https://openid.net/specs/openid-connect-backchannel-1_0.html#LogoutToken
+    #[must_use]
+    pub fn backchannel_logout_session_required(&self) -> bool {
+        self.backchannel_logout_session_required.unwrap_or_default()
+    }
+
     /// Whether the client will only send authorization requests as [Request
     /// Objects].
     ///
@@ -857,6 +914,10 @@ pub enum ClientMetadataVerificationError {
     #[error("redirect URI with fragment: {0}")]
     RedirectUriWithFragment(Url),
 
+    /// The back-channel logout URI has a fragment, which is not allowed.
+    #[error("backchannel logout URI with fragment: {0}")]
+    BackchannelLogoutUriWithFragment(Url),
+
     /// The given response type is not compatible with the grant types.
     #[error("'{0}' response type not compatible with grant types")]
     IncoherentResponseType(ResponseType),
@@ -874,6 +935,11 @@ pub enum ClientMetadataVerificationError {
     #[error("missing JWK Set for token auth method")]
     MissingJwksForTokenMethod,
 
+    /// No JWK Set was provided but one is required to verify signed request
+    /// objects.
+    #[error("missing JWK Set for signed request objects")]
+    MissingJwksForRequestObject,
+
     /// The given endpoint doesn't allow `none` as a signing algorithm.
     #[error("none signing alg unauthorized for {0}")]
     UnauthorizedSigningAlgNone(&'static str),
@@ -918,6 +984,16 @@ pub struct ClientRegistrationResponse {
     #[serde(default)]
     #[serde_as(as = "Option<TimestampSeconds<i64>>")]
     pub client_secret_expires_at: Option<DateTime<Utc>>,
+
+    /// A token that the client can use to manage its registration at the
+    /// `registration_client_uri`.
+    #[serde(default)]
+    pub registration_access_token: Option<String>,
+
+    /// The URL of the client configuration endpoint, where the client can read,
+    /// update and delete its registration.
+    #[serde(default)]
+    pub registration_client_uri: Option<Url>,
 }
 
 #[cfg(test)]
@@ -993,6 +1069,27 @@ mod tests {
         metadata.validate().unwrap();
     }
 
+    #[test]
+    fn validate_backchannel_logout_uri() {
+        let mut metadata = valid_client_metadata();
+
+        // Err - Fragment
+        let wrong_uri = Url::parse("http://localhost/logout#fragment").unwrap();
+        metadata.backchannel_logout_uri = Some(wrong_uri.clone());
+        let uri = assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::BackchannelLogoutUriWithFragment(uri)) => uri
+        );
+        assert_eq!(uri, wrong_uri);
+
+        // Ok - Query
+        metadata.backchannel_logout_uri =
+            Some(Url::parse("http://localhost/logout?backchannel").unwrap());
+        metadata.backchannel_logout_session_required = Some(true);
+        let metadata = metadata.validate().unwrap();
+        assert!(metadata.backchannel_logout_session_required());
+    }
+
     #[test]
     fn validate_response_types() {
         let mut metadata = valid_client_metadata();
@@ -1334,6 +1431,23 @@ mod tests {
         metadata.validate().unwrap();
     }
 
+    #[test]
+    fn validate_access_token_signed_response_alg() {
+        let mut metadata = valid_client_metadata();
+        metadata.access_token_signed_response_alg = Some(JsonWebSignatureAlg::None);
+
+        // Err - none
+        let field = assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::UnauthorizedSigningAlgNone(field)) => field
+        );
+        assert_eq!(field, "access_token");
+
+        // Ok - RS256
+        metadata.access_token_signed_response_alg = Some(JsonWebSignatureAlg::Rs256);
+        metadata.validate().unwrap();
+    }
+
     #[test]
     fn validate_id_token_encrypted_response() {
         let mut metadata = valid_client_metadata();
@@ -1385,6 +1499,27 @@ mod tests {
         metadata.validate().unwrap();
     }
 
+    #[test]
+    fn validate_require_signed_request_object() {
+        let mut metadata = valid_client_metadata();
+        metadata.require_signed_request_object = Some(true);
+
+        // Err - No JWKS
+        assert_matches!(
+            metadata.clone().validate(),
+            Err(ClientMetadataVerificationError::MissingJwksForRequestObject)
+        );
+
+        // Ok - jwks_uri
+        metadata.jwks_uri = Some(Url::parse("https://localhost/jwks").unwrap());
+        metadata.clone().validate().unwrap();
+
+        // Ok - jwks
+        metadata.jwks_uri = None;
+        metadata.jwks = Some(jwks());
+        metadata.validate().unwrap();
+    }
+
     #[test]
     fn validate_initiate_login_uri() {
         let mut metadata = valid_client_metadata();
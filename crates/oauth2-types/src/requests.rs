@@ -538,6 +538,120 @@ impl fmt::Debug for DeviceCodeGrant {
     }
 }
 
+/// All possible values for the token type identifiers used in a
+/// [Token Exchange].
+///
+/// [Token Exchange]: https://www.rfc-editor.org/rfc/rfc8693#section-3
+#[derive(
+    Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, SerializeDisplay, DeserializeFromStr,
+)]
+pub enum TokenTypeIdentifier {
+    /// `urn:ietf:params:oauth:token-type:access_token`
+    AccessToken,
+
+    /// `urn:ietf:params:oauth:token-type:refresh_token`
+    RefreshToken,
+
+    /// `urn:ietf:params:oauth:token-type:id_token`
+    IdToken,
+
+    /// `urn:ietf:params:oauth:token-type:saml1`
+    Saml1,
+
+    /// `urn:ietf:params:oauth:token-type:saml2`
+    Saml2,
+
+    /// `urn:ietf:params:oauth:token-type:jwt`
+    Jwt,
+
+    /// An unknown value.
+    Unknown(String),
+}
+
+impl core::fmt::Display for TokenTypeIdentifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TokenTypeIdentifier::AccessToken => {
+                f.write_str("urn:ietf:params:oauth:token-type:access_token")
+            }
+            TokenTypeIdentifier::RefreshToken => {
+                f.write_str("urn:ietf:params:oauth:token-type:refresh_token")
+            }
+            TokenTypeIdentifier::IdToken => {
+                f.write_str("urn:ietf:params:oauth:token-type:id_token")
+            }
+            TokenTypeIdentifier::Saml1 => f.write_str("urn:ietf:params:oauth:token-type:saml1"),
+            TokenTypeIdentifier::Saml2 => f.write_str("urn:ietf:params:oauth:token-type:saml2"),
+            TokenTypeIdentifier::Jwt => f.write_str("urn:ietf:params:oauth:token-type:jwt"),
+            TokenTypeIdentifier::Unknown(s) => f.write_str(s),
+        }
+    }
+}
+
+impl core::str::FromStr for TokenTypeIdentifier {
+    type Err = core::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "urn:ietf:params:oauth:token-type:access_token" => Ok(Self::AccessToken),
+            "urn:ietf:params:oauth:token-type:refresh_token" => Ok(Self::RefreshToken),
+            "urn:ietf:params:oauth:token-type:id_token" => Ok(Self::IdToken),
+            "urn:ietf:params:oauth:token-type:saml1" => Ok(Self::Saml1),
+            "urn:ietf:params:oauth:token-type:saml2" => Ok(Self::Saml2),
+            "urn:ietf:params:oauth:token-type:jwt" => Ok(Self::Jwt),
+            s => Ok(Self::Unknown(s.to_owned())),
+        }
+    }
+}
+
+/// A request to the [Token Endpoint] for the [Token Exchange] grant type.
+///
+/// [Token Endpoint]: https://www.rfc-editor.org/rfc/rfc6749#section-3.2
+/// [Token Exchange]: https://www.rfc-editor.org/rfc/rfc8693
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TokenExchangeGrant {
+    /// The token that represents the identity of the party on behalf of whom
+    /// the request is being made.
+    pub subject_token: String,
+
+    /// The type of the `subject_token`.
+    pub subject_token_type: TokenTypeIdentifier,
+
+    /// The token that represents the identity of the acting party.
+    pub actor_token: Option<String>,
+
+    /// The type of the `actor_token`.
+    pub actor_token_type: Option<TokenTypeIdentifier>,
+
+    /// The type of the requested security token.
+    pub requested_token_type: Option<TokenTypeIdentifier>,
+
+    /// The logical name of the target service where the client intends to use
+    /// the requested security token.
+    pub audience: Option<String>,
+
+    /// The URI of the target service or resource where the client intends to
+    /// use the requested security token.
+    pub resource: Option<Url>,
+
+    /// The scope of the requested security token.
+    pub scope: Option<Scope>,
+}
+
+impl fmt::Debug for TokenExchangeGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenExchangeGrant")
+            .field("subject_token_type", &self.subject_token_type)
+            .field("actor_token_type", &self.actor_token_type)
+            .field("requested_token_type", &self.requested_token_type)
+            .field("audience", &self.audience)
+            .field("resource", &self.resource)
+            .field("scope", &self.scope)
+            .finish_non_exhaustive()
+    }
+}
+
 /// All possible values for the `grant_type` parameter.
 #[derive(
     Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone, SerializeDisplay, DeserializeFromStr,
@@ -567,6 +681,9 @@ pub enum GrantType {
     /// [`urn:openid:params:grant-type:ciba`](https://openid.net/specs/openid-client-initiated-backchannel-authentication-core-1_0.html)
     ClientInitiatedBackchannelAuthentication,
 
+    /// [`urn:ietf:params:oauth:grant-type:token-exchange`](https://www.rfc-editor.org/rfc/rfc8693)
+    TokenExchange,
+
     /// An unknown value.
     Unknown(String),
 }
@@ -584,6 +701,9 @@ impl core::fmt::Display for GrantType {
             GrantType::ClientInitiatedBackchannelAuthentication => {
                 f.write_str("urn:openid:params:grant-type:ciba")
             }
+            GrantType::TokenExchange => {
+                f.write_str("urn:ietf:params:oauth:grant-type:token-exchange")
+            }
             GrantType::Unknown(s) => f.write_str(s),
         }
     }
@@ -604,6 +724,7 @@ impl core::str::FromStr for GrantType {
             "urn:openid:params:grant-type:ciba" => {
                 Ok(GrantType::ClientInitiatedBackchannelAuthentication)
             }
+            "urn:ietf:params:oauth:grant-type:token-exchange" => Ok(GrantType::TokenExchange),
             s => Ok(GrantType::Unknown(s.to_owned())),
         }
     }
@@ -629,6 +750,10 @@ pub enum AccessTokenRequest {
     #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
     DeviceCode(DeviceCodeGrant),
 
+    /// A request to exchange a token for another one.
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange(TokenExchangeGrant),
+
     /// An unsupported request.
     #[serde(skip_serializing, other)]
     Unsupported,
@@ -643,6 +768,7 @@ impl AccessTokenRequest {
             Self::RefreshToken(_) => "refresh_token",
             Self::ClientCredentials(_) => "client_credentials",
             Self::DeviceCode(_) => "urn:ietf:params:oauth:grant-type:device_code",
+            Self::TokenExchange(_) => "urn:ietf:params:oauth:grant-type:token-exchange",
             Self::Unsupported => "unsupported",
         }
     }
@@ -674,6 +800,9 @@ pub struct AccessTokenResponse {
 
     /// The scope of the access token.
     pub scope: Option<Scope>,
+
+    /// The type of the token issued in a token exchange.
+    pub issued_token_type: Option<TokenTypeIdentifier>,
 }
 
 impl AccessTokenResponse {
@@ -687,6 +816,7 @@ impl AccessTokenResponse {
             token_type: OAuthAccessTokenType::Bearer,
             expires_in: None,
             scope: None,
+            issued_token_type: None,
         }
     }
 
@@ -717,6 +847,21 @@ impl AccessTokenResponse {
         self.expires_in = Some(expires_in);
         self
     }
+
+    /// Sets the type of the access token in an `AccessTokenResponse`.
+    #[must_use]
+    pub fn with_token_type(mut self, token_type: OAuthAccessTokenType) -> Self {
+        self.token_type = token_type;
+        self
+    }
+
+    /// Sets the type of the token issued in a token exchange in an
+    /// `AccessTokenResponse`.
+    #[must_use]
+    pub fn with_issued_token_type(mut self, issued_token_type: TokenTypeIdentifier) -> Self {
+        self.issued_token_type = Some(issued_token_type);
+        self
+    }
 }
 
 impl fmt::Debug for AccessTokenResponse {
@@ -725,6 +870,7 @@ impl fmt::Debug for AccessTokenResponse {
             .field("token_type", &self.token_type)
             .field("expires_in", &self.expires_in)
             .field("scope", &self.scope)
+            .field("issued_token_type", &self.issued_token_type)
             .finish_non_exhaustive()
     }
 }
@@ -805,6 +951,40 @@ pub struct IntrospectionResponse {
     /// MAS extension: explicit device ID
     /// Only used for compatibility access and refresh tokens.
     pub device_id: Option<String>,
+
+    /// Confirmation of the key the token is bound to, if it is
+    /// sender-constrained.
+    pub cnf: Option<Confirmation>,
+
+    /// The party acting on behalf of the subject, if the token was issued to
+    /// impersonate it.
+    pub act: Option<Actor>,
+}
+
+/// The confirmation method of a sender-constrained token, as found in the
+/// `cnf` member of an [`IntrospectionResponse`].
+///
+/// See [RFC9449 section 6](https://www.rfc-editor.org/rfc/rfc9449#section-6).
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Confirmation {
+    /// The base64url-encoded SHA-256 JWK thumbprint of the DPoP public key the
+    /// token is bound to.
+    pub jkt: Option<String>,
+}
+
+/// The party acting on behalf of the subject of a token, as found in the
+/// `act` member of an [`IntrospectionResponse`].
+///
+/// See [RFC8693 section 4.1](https://www.rfc-editor.org/rfc/rfc8693#section-4.1).
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Actor {
+    /// The subject of the actor, if it is a user.
+    pub sub: Option<String>,
+
+    /// The client identifier of the actor, if it is a client.
+    pub client_id: Option<String>,
 }
 
 /// A request to the [Revocation Endpoint].
@@ -891,6 +1071,30 @@ mod tests {
         assert_serde_json(&req, expected);
     }
 
+    #[test]
+    fn serde_token_exchange_grant() {
+        let expected = json!({
+            "grant_type": "urn:ietf:params:oauth:grant-type:token-exchange",
+            "subject_token": "abcd",
+            "subject_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            "requested_token_type": "urn:ietf:params:oauth:token-type:access_token",
+            "scope": "openid",
+        });
+
+        let req = AccessTokenRequest::TokenExchange(TokenExchangeGrant {
+            subject_token: "abcd".into(),
+            subject_token_type: TokenTypeIdentifier::AccessToken,
+            actor_token: None,
+            actor_token_type: None,
+            requested_token_type: Some(TokenTypeIdentifier::AccessToken),
+            audience: None,
+            resource: None,
+            scope: Some(vec![OPENID].into_iter().collect()),
+        });
+
+        assert_serde_json(&req, expected);
+    }
+
     #[test]
     fn serialize_grant_type() {
         assert_eq!(
@@ -921,6 +1125,10 @@ mod tests {
             serde_json::to_string(&GrantType::ClientInitiatedBackchannelAuthentication).unwrap(),
             "\"urn:openid:params:grant-type:ciba\""
         );
+        assert_eq!(
+            serde_json::to_string(&GrantType::TokenExchange).unwrap(),
+            "\"urn:ietf:params:oauth:grant-type:token-exchange\""
+        );
     }
 
     #[test]
@@ -954,6 +1162,13 @@ mod tests {
             serde_json::from_str::<GrantType>("\"urn:openid:params:grant-type:ciba\"").unwrap(),
             GrantType::ClientInitiatedBackchannelAuthentication
         );
+        assert_eq!(
+            serde_json::from_str::<GrantType>(
+                "\"urn:ietf:params:oauth:grant-type:token-exchange\""
+            )
+            .unwrap(),
+            GrantType::TokenExchange
+        );
     }
 
     #[test]
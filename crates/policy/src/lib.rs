@@ -19,7 +19,8 @@ use tokio::io::{AsyncRead, AsyncReadExt};
 
 pub use self::model::{
     AuthorizationGrantInput, ClientRegistrationInput, Code as ViolationCode, EmailInput,
-    EvaluationResult, GrantType, RegisterInput, RegistrationMethod, Requester, Violation,
+    EvaluationResult, GrantType, RegisterInput, RegistrationMethod, Requester,
+    TokenExchangeSubject, Violation,
 };
 
 #[derive(Debug, Error)]
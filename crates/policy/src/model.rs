@@ -159,6 +159,21 @@ pub enum GrantType {
     ClientCredentials,
     #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
     DeviceCode,
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:token-exchange")]
+    TokenExchange,
+}
+
+/// The token presented in a token exchange grant
+#[derive(Serialize, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TokenExchangeSubject<'a> {
+    /// The client to which the subject token was issued
+    #[schemars(with = "std::collections::HashMap<String, serde_json::Value>")]
+    pub client: &'a Client,
+
+    /// The scope of the subject token
+    #[schemars(with = "String")]
+    pub scope: &'a Scope,
 }
 
 /// Input for the authorization grant policy.
@@ -168,6 +183,9 @@ pub struct AuthorizationGrantInput<'a> {
     #[schemars(with = "Option<std::collections::HashMap<String, serde_json::Value>>")]
     pub user: Option<&'a User>,
 
+    /// Whether the user logged in with a second factor in this browser session
+    pub mfa_authenticated: bool,
+
     #[schemars(with = "std::collections::HashMap<String, serde_json::Value>")]
     pub client: &'a Client,
 
@@ -176,6 +194,10 @@ pub struct AuthorizationGrantInput<'a> {
 
     pub grant_type: GrantType,
 
+    /// The token being exchanged, for the token exchange grant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<TokenExchangeSubject<'a>>,
+
     pub requester: Requester,
 }
 
@@ -131,6 +131,14 @@ impl SimpleRoute for OAuth2Revocation {
     const PATH: &'static str = "/oauth2/revoke";
 }
 
+/// `POST /oauth2/par`
+#[derive(Default, Debug, Clone)]
+pub struct OAuth2PushedAuthorizationRequestEndpoint;
+
+impl SimpleRoute for OAuth2PushedAuthorizationRequestEndpoint {
+    const PATH: &'static str = "/oauth2/par";
+}
+
 /// `POST /oauth2/token`
 #[derive(Default, Debug, Clone)]
 pub struct OAuth2TokenEndpoint;
@@ -147,6 +155,21 @@ impl SimpleRoute for OAuth2RegistrationEndpoint {
     const PATH: &'static str = "/oauth2/registration";
 }
 
+/// `GET|PUT|DELETE /oauth2/registration/{client_id}`
+#[derive(Debug, Clone)]
+pub struct OAuth2ClientConfigurationEndpoint(pub Ulid);
+
+impl Route for OAuth2ClientConfigurationEndpoint {
+    type Query = ();
+    fn route() -> &'static str {
+        "/oauth2/registration/{client_id}"
+    }
+
+    fn path(&self) -> std::borrow::Cow<'static, str> {
+        format!("/oauth2/registration/{}", self.0).into()
+    }
+}
+
 /// `GET /authorize`
 #[derive(Default, Debug, Clone)]
 pub struct OAuth2AuthorizationEndpoint;
@@ -155,6 +178,22 @@ impl SimpleRoute for OAuth2AuthorizationEndpoint {
     const PATH: &'static str = "/authorize";
 }
 
+/// `GET|POST /oauth2/logout`
+#[derive(Default, Debug, Clone)]
+pub struct OAuth2EndSessionEndpoint;
+
+impl SimpleRoute for OAuth2EndSessionEndpoint {
+    const PATH: &'static str = "/oauth2/logout";
+}
+
+/// `POST /oauth2/logout/confirm`
+#[derive(Default, Debug, Clone)]
+pub struct OAuth2EndSessionConfirm;
+
+impl SimpleRoute for OAuth2EndSessionConfirm {
+    const PATH: &'static str = "/oauth2/logout/confirm";
+}
+
 /// `GET /`
 #[derive(Default, Debug, Clone)]
 pub struct Index;
@@ -245,6 +284,86 @@ impl From<Option<PostAuthAction>> for Login {
     }
 }
 
+/// `POST /login/passkey`
+#[derive(Default, Debug, Clone)]
+pub struct PasskeyLogin {
+    post_auth_action: Option<PostAuthAction>,
+}
+
+impl Route for PasskeyLogin {
+    type Query = PostAuthAction;
+
+    fn route() -> &'static str {
+        "/login/passkey"
+    }
+
+    fn query(&self) -> Option<&Self::Query> {
+        self.post_auth_action.as_ref()
+    }
+}
+
+impl From<Option<PostAuthAction>> for PasskeyLogin {
+    fn from(post_auth_action: Option<PostAuthAction>) -> Self {
+        Self { post_auth_action }
+    }
+}
+
+/// `POST /login/passkey/challenge`
+#[derive(Default, Debug, Clone)]
+pub struct PasskeyLoginChallenge;
+
+impl SimpleRoute for PasskeyLoginChallenge {
+    const PATH: &'static str = "/login/passkey/challenge";
+}
+
+/// `GET|POST /login/mfa`
+#[derive(Default, Debug, Clone)]
+pub struct LoginMfa {
+    post_auth_action: Option<PostAuthAction>,
+}
+
+impl Route for LoginMfa {
+    type Query = PostAuthAction;
+
+    fn route() -> &'static str {
+        "/login/mfa"
+    }
+
+    fn query(&self) -> Option<&Self::Query> {
+        self.post_auth_action.as_ref()
+    }
+}
+
+impl From<Option<PostAuthAction>> for LoginMfa {
+    fn from(post_auth_action: Option<PostAuthAction>) -> Self {
+        Self { post_auth_action }
+    }
+}
+
+/// `GET|POST /login/password-expired`
+#[derive(Default, Debug, Clone)]
+pub struct LoginPasswordExpired {
+    post_auth_action: Option<PostAuthAction>,
+}
+
+impl Route for LoginPasswordExpired {
+    type Query = PostAuthAction;
+
+    fn route() -> &'static str {
+        "/login/password-expired"
+    }
+
+    fn query(&self) -> Option<&Self::Query> {
+        self.post_auth_action.as_ref()
+    }
+}
+
+impl From<Option<PostAuthAction>> for LoginPasswordExpired {
+    fn from(post_auth_action: Option<PostAuthAction>) -> Self {
+        Self { post_auth_action }
+    }
+}
+
 /// `POST /logout`
 #[derive(Default, Debug, Clone)]
 pub struct Logout;
@@ -761,6 +880,52 @@ impl Route for UpstreamOAuth2BackchannelLogout {
     }
 }
 
+/// `GET /upstream/saml/metadata/{id}`
+pub struct UpstreamSamlMetadata {
+    id: Ulid,
+}
+
+impl UpstreamSamlMetadata {
+    #[must_use]
+    pub const fn new(id: Ulid) -> Self {
+        Self { id }
+    }
+}
+
+impl Route for UpstreamSamlMetadata {
+    type Query = ();
+    fn route() -> &'static str {
+        "/upstream/saml/metadata/{provider_id}"
+    }
+
+    fn path(&self) -> std::borrow::Cow<'static, str> {
+        format!("/upstream/saml/metadata/{}", self.id).into()
+    }
+}
+
+/// `POST /upstream/saml/acs/{id}`
+pub struct UpstreamSamlAssertionConsumerService {
+    id: Ulid,
+}
+
+impl UpstreamSamlAssertionConsumerService {
+    #[must_use]
+    pub const fn new(id: Ulid) -> Self {
+        Self { id }
+    }
+}
+
+impl Route for UpstreamSamlAssertionConsumerService {
+    type Query = ();
+    fn route() -> &'static str {
+        "/upstream/saml/acs/{provider_id}"
+    }
+
+    fn path(&self) -> std::borrow::Cow<'static, str> {
+        format!("/upstream/saml/acs/{}", self.id).into()
+    }
+}
+
 /// `GET|POST /link`
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 pub struct DeviceCodeLink {
@@ -166,6 +166,27 @@ impl UrlBuilder {
         self.absolute_url_for(&crate::endpoints::OAuth2RegistrationEndpoint)
     }
 
+    /// OAuth 2.0 client configuration endpoint, where a dynamically registered
+    /// client can manage its registration
+    #[must_use]
+    pub fn oauth_client_configuration_endpoint(&self, client_id: Ulid) -> Url {
+        self.absolute_url_for(&crate::endpoints::OAuth2ClientConfigurationEndpoint(
+            client_id,
+        ))
+    }
+
+    /// OIDC RP-Initiated Logout endpoint
+    #[must_use]
+    pub fn oidc_end_session_endpoint(&self) -> Url {
+        self.absolute_url_for(&crate::endpoints::OAuth2EndSessionEndpoint)
+    }
+
+    /// OAuth 2.0 pushed authorization request endpoint
+    #[must_use]
+    pub fn oauth_pushed_authorization_request_endpoint(&self) -> Url {
+        self.absolute_url_for(&crate::endpoints::OAuth2PushedAuthorizationRequestEndpoint)
+    }
+
     /// OAuth 2.0 device authorization endpoint
     #[must_use]
     pub fn oauth_device_authorization_endpoint(&self) -> Url {
@@ -226,6 +247,18 @@ impl UrlBuilder {
         self.absolute_url_for(&crate::endpoints::UpstreamOAuth2Authorize::new(id))
     }
 
+    /// Upstream SAML 2.0 service provider metadata URI
+    #[must_use]
+    pub fn upstream_saml_metadata(&self, id: Ulid) -> Url {
+        self.absolute_url_for(&crate::endpoints::UpstreamSamlMetadata::new(id))
+    }
+
+    /// Upstream SAML 2.0 assertion consumer service URI
+    #[must_use]
+    pub fn upstream_saml_acs(&self, id: Ulid) -> Url {
+        self.absolute_url_for(&crate::endpoints::UpstreamSamlAssertionConsumerService::new(id))
+    }
+
     /// Account management URI
     #[must_use]
     pub fn account_management_uri(&self) -> Url {
@@ -0,0 +1,67 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- WebAuthn credentials (passkeys) registered by users
+CREATE TABLE "user_passkeys" (
+  "user_passkey_id" UUID PRIMARY KEY,
+
+  "user_id" UUID NOT NULL
+    REFERENCES "users" ("user_id") ON DELETE CASCADE,
+
+  -- The credential ID, base64url-encoded, as sent by the authenticator
+  "credential_id" TEXT NOT NULL UNIQUE,
+
+  -- A human-readable name for the passkey, chosen by the user
+  "name" TEXT NOT NULL,
+
+  -- The transports hinted by the authenticator during registration
+  "transports" TEXT[] NOT NULL,
+
+  -- The COSE-encoded public key of the credential
+  "public_key" BYTEA NOT NULL,
+
+  -- The AAGUID of the authenticator, which identifies its model
+  "aaguid" TEXT,
+
+  -- The last signature counter reported by the authenticator
+  "sign_count" BIGINT NOT NULL,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+  "last_used_at" TIMESTAMP WITH TIME ZONE
+);
+
+-- This is safe to create non-concurrently, as the table is empty at this point
+CREATE INDEX "user_passkeys_user_fk"
+  ON "user_passkeys" ("user_id");
+
+-- Challenges sent to the browser, either to register a passkey or to
+-- authenticate with one
+CREATE TABLE "user_passkey_challenges" (
+  "user_passkey_challenge_id" UUID PRIMARY KEY,
+
+  -- The browser session registering a passkey. This is NULL for challenges
+  -- used to authenticate
+  "user_session_id" UUID
+    REFERENCES "user_sessions" ("user_session_id") ON DELETE CASCADE,
+
+  "challenge" BYTEA NOT NULL,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+  "completed_at" TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX "user_passkey_challenges_user_session_fk"
+  ON "user_passkey_challenges" ("user_session_id");
+
+-- Used when cleaning up old challenges
+CREATE INDEX "user_passkey_challenges_created_at_idx"
+  ON "user_passkey_challenges" ("created_at");
+
+-- Record which passkey was used to authenticate a session
+-- A second migration will add the index for this foreign key
+ALTER TABLE "user_session_authentications"
+  ADD COLUMN "user_passkey_id" UUID
+    REFERENCES "user_passkeys" ("user_passkey_id")
+    ON DELETE SET NULL;
@@ -0,0 +1,53 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- TOTP secrets enrolled by users as a second authentication factor
+CREATE TABLE "user_totps" (
+  "user_totp_id" UUID PRIMARY KEY,
+
+  -- A user can only have one TOTP secret at a time
+  "user_id" UUID NOT NULL UNIQUE
+    REFERENCES "users" ("user_id") ON DELETE CASCADE,
+
+  -- The shared secret, encrypted with the site encryption key
+  "encrypted_secret" TEXT NOT NULL,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+  -- When the user proved they could generate codes with this secret. The
+  -- secret is not checked on login until then
+  "confirmed_at" TIMESTAMP WITH TIME ZONE,
+
+  -- Used to prevent the same code from being used twice
+  "last_used_at" TIMESTAMP WITH TIME ZONE
+);
+
+-- Single-use codes which can be used in place of a TOTP code
+CREATE TABLE "user_recovery_codes" (
+  "user_recovery_code_id" UUID PRIMARY KEY,
+
+  "user_id" UUID NOT NULL
+    REFERENCES "users" ("user_id") ON DELETE CASCADE,
+
+  -- The code, encrypted with the site encryption key
+  "encrypted_code" TEXT NOT NULL,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+  "used_at" TIMESTAMP WITH TIME ZONE
+);
+
+-- This is safe to create non-concurrently, as the table is empty at this point
+CREATE INDEX "user_recovery_codes_user_fk"
+  ON "user_recovery_codes" ("user_id");
+
+-- Record which second factor was used to authenticate a session
+-- A second migration will add the indexes for these foreign keys
+ALTER TABLE "user_session_authentications"
+  ADD COLUMN "user_totp_id" UUID
+    REFERENCES "user_totps" ("user_totp_id")
+    ON DELETE SET NULL,
+  ADD COLUMN "user_recovery_code_id" UUID
+    REFERENCES "user_recovery_codes" ("user_recovery_code_id")
+    ON DELETE SET NULL;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- URIs to which clients can ask to redirect the user after an RP-initiated
+-- logout
+ALTER TABLE "oauth2_clients"
+  ADD COLUMN "post_logout_redirect_uris" TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- URI to which we send logout tokens when a session of the client ends
+ALTER TABLE "oauth2_clients"
+  ADD COLUMN "backchannel_logout_uri" TEXT,
+  ADD COLUMN "backchannel_logout_session_required" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,34 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Authorization requests pushed by clients to the PAR endpoint (RFC 9126)
+CREATE TABLE "oauth2_pushed_authorization_requests" (
+  "oauth2_pushed_authorization_request_id" UUID PRIMARY KEY,
+
+  "oauth2_client_id" UUID NOT NULL
+    REFERENCES "oauth2_clients" ("oauth2_client_id") ON DELETE CASCADE,
+
+  -- The request_uri handed out to the client
+  "request_uri" TEXT NOT NULL UNIQUE,
+
+  -- The parameters of the authorization request, as a JSON object of strings
+  "parameters" JSONB NOT NULL,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+  "expires_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+  "consumed_at" TIMESTAMP WITH TIME ZONE
+);
+
+-- This is safe to create non-concurrently, as the table is empty at this point
+CREATE INDEX "oauth2_pushed_authorization_requests_oauth2_client_fk"
+  ON "oauth2_pushed_authorization_requests" ("oauth2_client_id");
+
+-- Used when cleaning up expired requests
+CREATE INDEX "oauth2_pushed_authorization_requests_expires_at_idx"
+  ON "oauth2_pushed_authorization_requests" ("expires_at");
+
+-- Whether the client must use the PAR endpoint for its authorization requests
+ALTER TABLE "oauth2_clients"
+  ADD COLUMN "require_pushed_authorization_requests" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,12 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Bind OAuth 2.0 access and refresh tokens to a DPoP key, through the JWK
+-- thumbprint of that key
+ALTER TABLE oauth2_access_tokens
+  ADD COLUMN dpop_jkt TEXT;
+
+ALTER TABLE oauth2_refresh_tokens
+  ADD COLUMN dpop_jkt TEXT;
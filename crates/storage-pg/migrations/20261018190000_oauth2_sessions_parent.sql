@@ -0,0 +1,11 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Record which session an OAuth 2.0 session was derived from, when it was
+-- created through a token exchange
+ALTER TABLE oauth2_sessions
+  ADD COLUMN parent_session_id UUID
+    REFERENCES oauth2_sessions (oauth2_session_id)
+    ON DELETE SET NULL;
@@ -0,0 +1,10 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Add a flag on oauth2_clients to indicate whether they must send their
+-- authorization requests as signed request objects
+ALTER TABLE oauth2_clients
+  ADD COLUMN require_signed_request_object BOOLEAN
+    NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Add the registration access token of dynamically registered clients, which
+-- they use to manage their registration through the client configuration
+-- endpoint
+ALTER TABLE oauth2_clients
+  ADD COLUMN registration_access_token TEXT;
@@ -0,0 +1,9 @@
+-- no-transaction
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+CREATE UNIQUE INDEX CONCURRENTLY
+  oauth2_clients_registration_access_token_unique
+  ON oauth2_clients (registration_access_token);
@@ -0,0 +1,12 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Track whether upstream OAuth providers are defined in the configuration file,
+-- or were created through the admin API. `config sync` only manages the former.
+ALTER TABLE upstream_oauth_providers
+  ADD COLUMN is_static BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Until now, providers could only be created by syncing the configuration
+UPDATE upstream_oauth_providers SET is_static = TRUE;
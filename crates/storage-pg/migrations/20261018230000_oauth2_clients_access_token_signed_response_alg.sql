@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- The algorithm used to sign JWT access tokens for this client.
+-- If NULL, the client gets opaque access tokens.
+ALTER TABLE oauth2_clients
+  ADD COLUMN access_token_signed_response_alg TEXT;
@@ -0,0 +1,7 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Settings for upstream providers which speak SAML 2.0 instead of OAuth 2.0
+ALTER TABLE upstream_oauth_providers ADD COLUMN saml JSONB;
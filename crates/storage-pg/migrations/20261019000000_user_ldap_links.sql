@@ -0,0 +1,26 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Links between users and entries of the LDAP directory used to check their
+-- passwords
+CREATE TABLE "user_ldap_links" (
+  "user_ldap_link_id" UUID PRIMARY KEY,
+
+  -- A user can only be linked to one directory entry
+  "user_id" UUID NOT NULL UNIQUE
+    REFERENCES "users" ("user_id") ON DELETE CASCADE,
+
+  -- The stable identifier of the directory entry
+  "subject" TEXT NOT NULL UNIQUE,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+-- Record which directory entry was used to authenticate a session
+-- A second migration will add the index for this foreign key
+ALTER TABLE "user_session_authentications"
+  ADD COLUMN "user_ldap_link_id" UUID
+    REFERENCES "user_ldap_links" ("user_ldap_link_id")
+    ON DELETE SET NULL;
@@ -0,0 +1,45 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Append-only log of security-relevant actions.
+--
+-- There are intentionally no foreign keys on this table: events must outlive
+-- the users, sessions and other objects they reference.
+CREATE TABLE "audit_events" (
+  "audit_event_id" UUID PRIMARY KEY,
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+  -- The kind of action, e.g. 'user.lock'
+  "action" TEXT NOT NULL,
+
+  -- Who performed the action. Both are NULL for actions performed by the
+  -- system, and the user is NULL for clients acting on their own behalf
+  "actor_user_id" UUID,
+  "actor_session_id" UUID,
+
+  -- Where the action was performed from
+  "ip_address" INET,
+  "user_agent" TEXT,
+
+  -- The user affected by the action, if any
+  "target_user_id" UUID,
+
+  -- The object affected by the action, if any
+  "target_id" UUID,
+
+  -- Additional, action-specific details
+  "details" JSONB NOT NULL DEFAULT '{}'::JSONB
+);
+
+CREATE INDEX "audit_events_actor_user_id_idx"
+  ON "audit_events" ("actor_user_id")
+  WHERE "actor_user_id" IS NOT NULL;
+
+CREATE INDEX "audit_events_target_user_id_idx"
+  ON "audit_events" ("target_user_id")
+  WHERE "target_user_id" IS NOT NULL;
+
+CREATE INDEX "audit_events_action_idx"
+  ON "audit_events" ("action");
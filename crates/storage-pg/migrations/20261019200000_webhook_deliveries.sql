@@ -0,0 +1,42 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Deliveries of lifecycle events to the configured webhook endpoints
+CREATE TABLE "webhook_deliveries" (
+  "webhook_delivery_id" UUID PRIMARY KEY,
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+  -- The kind of event, e.g. 'user.registered'
+  "event" TEXT NOT NULL,
+
+  -- The URL of the endpoint the event is delivered to
+  "url" TEXT NOT NULL,
+
+  -- The JSON payload sent to the endpoint
+  "payload" JSONB NOT NULL,
+
+  -- Set once the endpoint acknowledged the event
+  "delivered_at" TIMESTAMP WITH TIME ZONE,
+
+  -- Set once the delivery was abandoned
+  "failed_at" TIMESTAMP WITH TIME ZONE
+);
+
+-- Each attempt at delivering an event
+CREATE TABLE "webhook_delivery_attempts" (
+  "webhook_delivery_attempt_id" UUID PRIMARY KEY,
+  "webhook_delivery_id" UUID NOT NULL
+    REFERENCES "webhook_deliveries" ("webhook_delivery_id") ON DELETE CASCADE,
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+  -- The HTTP status code returned by the endpoint, if it responded
+  "status_code" INTEGER,
+
+  -- Why the attempt failed, if it did
+  "error" TEXT
+);
+
+CREATE INDEX "webhook_delivery_attempts_webhook_delivery_id_idx"
+  ON "webhook_delivery_attempts" ("webhook_delivery_id");
@@ -0,0 +1,23 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Record which admin created a session to impersonate its user, and until when
+ALTER TABLE oauth2_sessions
+  ADD COLUMN impersonator_user_id UUID
+    REFERENCES users (user_id)
+    ON DELETE SET NULL,
+  ADD COLUMN impersonator_client_id UUID
+    REFERENCES oauth2_clients (oauth2_client_id)
+    ON DELETE SET NULL,
+  ADD COLUMN impersonation_expires_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE compat_sessions
+  ADD COLUMN impersonator_user_id UUID
+    REFERENCES users (user_id)
+    ON DELETE SET NULL,
+  ADD COLUMN impersonator_client_id UUID
+    REFERENCES oauth2_clients (oauth2_client_id)
+    ON DELETE SET NULL,
+  ADD COLUMN impersonation_expires_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,21 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Exports of everything stored about a user, to answer subject access requests
+CREATE TABLE "user_exports" (
+  "user_export_id" UUID PRIMARY KEY,
+
+  -- The user being exported
+  "user_id" UUID NOT NULL
+    REFERENCES "users" ("user_id") ON DELETE CASCADE,
+
+  "created_at" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+  -- Set once the archive was built
+  "completed_at" TIMESTAMP WITH TIME ZONE,
+
+  -- The archive itself, once built
+  "archive" JSONB
+);
@@ -0,0 +1,11 @@
+-- no-transaction
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Used to find the registration which created a user
+CREATE INDEX CONCURRENTLY
+  user_registrations_username_idx
+  ON user_registrations (username)
+  WHERE completed_at IS NOT NULL;
@@ -0,0 +1,9 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Record when the personal data of a deactivated user was erased. The row
+-- itself is kept, so that the username can never be registered again.
+ALTER TABLE users
+  ADD COLUMN erased_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,11 @@
+-- no-transaction
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Used to find deactivated users which are due for erasure
+CREATE INDEX CONCURRENTLY
+  users_deactivated_at_idx
+  ON users (deactivated_at)
+  WHERE deactivated_at IS NOT NULL AND erased_at IS NULL;
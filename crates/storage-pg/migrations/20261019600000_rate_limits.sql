@@ -0,0 +1,21 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- State of the rate limiters, when shared between replicas through the
+-- database. Losing it on a crash only resets the limits, so it doesn't need to
+-- go through the write-ahead log.
+CREATE UNLOGGED TABLE "rate_limits" (
+  -- The name of the rate limiter
+  "bucket" TEXT NOT NULL,
+
+  -- The key being rate limited, like an IP address or a user ID
+  "key" TEXT NOT NULL,
+
+  -- The theoretical arrival time of the next action, as per the generic cell
+  -- rate algorithm
+  "tat" TIMESTAMP WITH TIME ZONE NOT NULL,
+
+  PRIMARY KEY ("bucket", "key")
+);
@@ -0,0 +1,10 @@
+-- no-transaction
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Used to remove the keys which are fully replenished
+CREATE INDEX CONCURRENTLY
+  rate_limits_tat_idx
+  ON rate_limits (tat);
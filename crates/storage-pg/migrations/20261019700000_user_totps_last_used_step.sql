@@ -0,0 +1,15 @@
+-- Copyright 2025 New Vector Ltd.
+--
+-- SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+-- Please see LICENSE in the repository root for full details.
+
+-- Record the time step of the last code used, rather than relying on when it
+-- was used: a code from the next time step is accepted to account for clock
+-- drift, and must not allow replaying codes from the current one
+ALTER TABLE "user_totps"
+  ADD COLUMN "last_used_step" BIGINT;
+
+-- Codes are valid for 30 seconds
+UPDATE "user_totps"
+  SET "last_used_step" = FLOOR(EXTRACT(EPOCH FROM "last_used_at") / 30)::BIGINT
+  WHERE "last_used_at" IS NOT NULL;
@@ -8,7 +8,7 @@
 
 use async_trait::async_trait;
 use mas_data_model::{
-    Clock, CompatSession, CompatSessionState, Device, Session, SessionState, User,
+    Clock, CompatSession, CompatSessionState, Device, Impersonation, Session, SessionState, User,
 };
 use mas_storage::{
     Page, Pagination,
@@ -78,6 +78,10 @@ mod priv_ {
         pub(super) user_agent: Option<String>,
         pub(super) last_active_at: Option<DateTime<Utc>>,
         pub(super) last_active_ip: Option<IpAddr>,
+        pub(super) parent_session_id: Option<Uuid>,
+        pub(super) impersonator_user_id: Option<Uuid>,
+        pub(super) impersonator_client_id: Option<Uuid>,
+        pub(super) impersonation_expires_at: Option<DateTime<Utc>>,
     }
 
     impl Node<Ulid> for AppSessionLookup {
@@ -111,9 +115,18 @@ impl TryFrom<AppSessionLookup> for AppSession {
             user_agent,
             last_active_at,
             last_active_ip,
+            parent_session_id,
+            impersonator_user_id,
+            impersonator_client_id,
+            impersonation_expires_at,
         } = value;
 
         let user_session_id = user_session_id.map(Ulid::from);
+        let impersonation = impersonation_expires_at.map(|expires_at| Impersonation {
+            impersonator_user_id: impersonator_user_id.map(Ulid::from),
+            impersonator_client_id: impersonator_client_id.map(Ulid::from),
+            expires_at,
+        });
 
         match (
             compat_session_id,
@@ -161,6 +174,7 @@ impl TryFrom<AppSessionLookup> for AppSession {
                     user_agent,
                     last_active_at,
                     last_active_ip,
+                    impersonation,
                 };
 
                 Ok(AppSession::Compat(Box::new(session)))
@@ -202,6 +216,8 @@ impl TryFrom<AppSessionLookup> for AppSession {
                     last_active_at,
                     last_active_ip,
                     human_name,
+                    parent_session_id: parent_session_id.map(Ulid::from),
+                    impersonation,
                 };
 
                 Ok(AppSession::OAuth2(Box::new(session)))
@@ -333,6 +349,25 @@ impl AppSessionRepository for PgAppSessionRepository<'_> {
                 Expr::col((OAuth2Sessions::Table, OAuth2Sessions::LastActiveIp)),
                 AppSessionLookupIden::LastActiveIp,
             )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::ParentSessionId)),
+                AppSessionLookupIden::ParentSessionId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::ImpersonatorUserId)),
+                AppSessionLookupIden::ImpersonatorUserId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::ImpersonatorClientId)),
+                AppSessionLookupIden::ImpersonatorClientId,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Sessions::Table,
+                    OAuth2Sessions::ImpersonationExpiresAt,
+                )),
+                AppSessionLookupIden::ImpersonationExpiresAt,
+            )
             .from(OAuth2Sessions::Table)
             .apply_filter(oauth2_filter)
             .clone();
@@ -389,6 +424,22 @@ impl AppSessionRepository for PgAppSessionRepository<'_> {
                 Expr::col((CompatSessions::Table, CompatSessions::LastActiveIp)),
                 AppSessionLookupIden::LastActiveIp,
             )
+            .expr_as(Expr::cust("NULL"), AppSessionLookupIden::ParentSessionId)
+            .expr_as(
+                Expr::col((CompatSessions::Table, CompatSessions::ImpersonatorUserId)),
+                AppSessionLookupIden::ImpersonatorUserId,
+            )
+            .expr_as(
+                Expr::col((CompatSessions::Table, CompatSessions::ImpersonatorClientId)),
+                AppSessionLookupIden::ImpersonatorClientId,
+            )
+            .expr_as(
+                Expr::col((
+                    CompatSessions::Table,
+                    CompatSessions::ImpersonationExpiresAt,
+                )),
+                AppSessionLookupIden::ImpersonationExpiresAt,
+            )
             .from(CompatSessions::Table)
             .apply_filter(compat_filter)
             .clone();
@@ -661,6 +712,12 @@ mod tests {
                 None,
                 None,
                 Some("https://example.com/login".parse().unwrap()),
+                Vec::new(),
+                None,
+                false,
+                false,
+                false,
+                None,
             )
             .await
             .unwrap();
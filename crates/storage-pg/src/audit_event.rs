@@ -0,0 +1,456 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A module containing the PostgreSQL implementation of the audit log
+
+use std::net::IpAddr;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{AuditAction, AuditActor, AuditEvent, Clock};
+use mas_storage::{
+    Page, Pagination,
+    audit_event::{AuditEventFilter, AuditEventRepository},
+    pagination::Node,
+};
+use rand::RngCore;
+use sea_query::{Expr, PostgresQueryBuilder, Query, enum_def};
+use sea_query_binder::SqlxBinder;
+use serde_json::Value;
+use sqlx::{PgConnection, types::Json};
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{
+    DatabaseError, DatabaseInconsistencyError,
+    filter::{Filter, StatementExt},
+    iden::AuditEvents,
+    pagination::QueryBuilderExt,
+    tracing::ExecuteExt,
+};
+
+/// An implementation of [`AuditEventRepository`] for a PostgreSQL connection
+pub struct PgAuditEventRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgAuditEventRepository<'c> {
+    /// Create a new [`PgAuditEventRepository`] from an active PostgreSQL
+    /// connection
+    #[must_use]
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+#[enum_def]
+struct AuditEventLookup {
+    audit_event_id: Uuid,
+    created_at: DateTime<Utc>,
+    action: String,
+    actor_user_id: Option<Uuid>,
+    actor_session_id: Option<Uuid>,
+    ip_address: Option<IpAddr>,
+    user_agent: Option<String>,
+    target_user_id: Option<Uuid>,
+    target_id: Option<Uuid>,
+    details: Json<Value>,
+}
+
+impl Node<Ulid> for AuditEventLookup {
+    fn cursor(&self) -> Ulid {
+        self.audit_event_id.into()
+    }
+}
+
+impl TryFrom<AuditEventLookup> for AuditEvent {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: AuditEventLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.audit_event_id);
+        let action = value.action.parse().map_err(|e| {
+            DatabaseInconsistencyError::on("audit_events")
+                .column("action")
+                .row(id)
+                .source(e)
+        })?;
+
+        Ok(AuditEvent {
+            id,
+            created_at: value.created_at,
+            action,
+            actor: AuditActor {
+                user_id: value.actor_user_id.map(Ulid::from),
+                session_id: value.actor_session_id.map(Ulid::from),
+                ip_address: value.ip_address,
+                user_agent: value.user_agent,
+            },
+            target_user_id: value.target_user_id.map(Ulid::from),
+            target_id: value.target_id.map(Ulid::from),
+            details: value.details.0,
+        })
+    }
+}
+
+impl Filter for AuditEventFilter<'_> {
+    fn generate_condition(&self, _has_joins: bool) -> impl sea_query::IntoCondition {
+        sea_query::Condition::all()
+            .add_option(self.actor_user().map(|user| {
+                Expr::col((AuditEvents::Table, AuditEvents::ActorUserId)).eq(Uuid::from(user.id))
+            }))
+            .add_option(self.target_user().map(|user| {
+                Expr::col((AuditEvents::Table, AuditEvents::TargetUserId)).eq(Uuid::from(user.id))
+            }))
+            .add_option(self.action().map(|action| {
+                Expr::col((AuditEvents::Table, AuditEvents::Action)).eq(action.as_str())
+            }))
+            .add_option(self.created_before().map(|created_before| {
+                Expr::col((AuditEvents::Table, AuditEvents::CreatedAt)).lt(created_before)
+            }))
+            .add_option(self.created_after().map(|created_after| {
+                Expr::col((AuditEvents::Table, AuditEvents::CreatedAt)).gt(created_after)
+            }))
+    }
+}
+
+#[async_trait]
+impl AuditEventRepository for PgAuditEventRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.audit_event.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            audit_event.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<AuditEvent>, Self::Error> {
+        let res = sqlx::query_as!(
+            AuditEventLookup,
+            r#"
+                SELECT audit_event_id
+                     , created_at
+                     , action
+                     , actor_user_id
+                     , actor_session_id
+                     , ip_address as "ip_address: IpAddr"
+                     , user_agent
+                     , target_user_id
+                     , target_id
+                     , details as "details: Json<Value>"
+                FROM audit_events
+                WHERE audit_event_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(res) = res else { return Ok(None) };
+
+        Ok(Some(res.try_into()?))
+    }
+
+    #[tracing::instrument(
+        name = "db.audit_event.add",
+        skip_all,
+        fields(
+            db.query.text,
+            audit_event.id,
+            audit_event.action = %action,
+            audit_event.actor.user_id = actor.user_id.map(tracing::field::display),
+            audit_event.target_user_id = target_user_id.map(tracing::field::display),
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        actor: AuditActor,
+        action: AuditAction,
+        target_user_id: Option<Ulid>,
+        target_id: Option<Ulid>,
+        details: Value,
+    ) -> Result<AuditEvent, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("audit_event.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO audit_events
+                    ( audit_event_id
+                    , created_at
+                    , action
+                    , actor_user_id
+                    , actor_session_id
+                    , ip_address
+                    , user_agent
+                    , target_user_id
+                    , target_id
+                    , details
+                    )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            Uuid::from(id),
+            created_at,
+            action.as_str(),
+            actor.user_id.map(Uuid::from),
+            actor.session_id.map(Uuid::from),
+            actor.ip_address as Option<IpAddr>,
+            actor.user_agent.as_deref(),
+            target_user_id.map(Uuid::from),
+            target_id.map(Uuid::from),
+            details,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(AuditEvent {
+            id,
+            created_at,
+            action,
+            actor,
+            target_user_id,
+            target_id,
+            details,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.audit_event.list",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn list(
+        &mut self,
+        filter: AuditEventFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<AuditEvent>, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::AuditEventId)),
+                AuditEventLookupIden::AuditEventId,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::CreatedAt)),
+                AuditEventLookupIden::CreatedAt,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::Action)),
+                AuditEventLookupIden::Action,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::ActorUserId)),
+                AuditEventLookupIden::ActorUserId,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::ActorSessionId)),
+                AuditEventLookupIden::ActorSessionId,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::IpAddress)),
+                AuditEventLookupIden::IpAddress,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::UserAgent)),
+                AuditEventLookupIden::UserAgent,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::TargetUserId)),
+                AuditEventLookupIden::TargetUserId,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::TargetId)),
+                AuditEventLookupIden::TargetId,
+            )
+            .expr_as(
+                Expr::col((AuditEvents::Table, AuditEvents::Details)),
+                AuditEventLookupIden::Details,
+            )
+            .from(AuditEvents::Table)
+            .apply_filter(filter)
+            .generate_pagination((AuditEvents::Table, AuditEvents::AuditEventId), pagination)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let edges: Vec<AuditEventLookup> = sqlx::query_as_with(&sql, arguments)
+            .traced()
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        let page = pagination.process(edges).try_map(AuditEvent::try_from)?;
+
+        Ok(page)
+    }
+
+    #[tracing::instrument(
+        name = "db.audit_event.count",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn count(&mut self, filter: AuditEventFilter<'_>) -> Result<usize, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr(Expr::col((AuditEvents::Table, AuditEvents::AuditEventId)).count())
+            .from(AuditEvents::Table)
+            .apply_filter(filter)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let count: i64 = sqlx::query_scalar_with(&sql, arguments)
+            .traced()
+            .fetch_one(&mut *self.conn)
+            .await?;
+
+        count
+            .try_into()
+            .map_err(DatabaseError::to_invalid_operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use chrono::Duration;
+    use mas_data_model::{AuditAction, AuditActor, clock::MockClock};
+    use mas_storage::{
+        Pagination, RepositoryAccess,
+        audit_event::{AuditEventFilter, AuditEventRepository},
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use serde_json::json;
+    use sqlx::PgPool;
+
+    use crate::PgRepository;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_audit_events(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        let admin = repo
+            .user()
+            .add(&mut rng, &clock, "admin".to_owned())
+            .await
+            .unwrap();
+        let alice = repo
+            .user()
+            .add(&mut rng, &clock, "alice".to_owned())
+            .await
+            .unwrap();
+
+        // The log starts empty
+        let all = AuditEventFilter::new();
+        assert_eq!(repo.audit_event().count(all).await.unwrap(), 0);
+
+        let actor = AuditActor {
+            user_id: Some(admin.id),
+            session_id: None,
+            ip_address: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            user_agent: Some("curl/8.7.1".to_owned()),
+        };
+
+        let lock = repo
+            .audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                actor.clone(),
+                AuditAction::UserLock,
+                Some(alice.id),
+                Some(alice.id),
+                json!({}),
+            )
+            .await
+            .unwrap();
+
+        clock.advance(Duration::minutes(1));
+
+        let set_admin = repo
+            .audit_event()
+            .add(
+                &mut rng,
+                &clock,
+                AuditActor {
+                    user_id: Some(alice.id),
+                    ..AuditActor::default()
+                },
+                AuditAction::UserSetAdmin,
+                Some(admin.id),
+                Some(admin.id),
+                json!({ "admin": false }),
+            )
+            .await
+            .unwrap();
+
+        // Lookup the events
+        let lookup = repo.audit_event().lookup(lock.id).await.unwrap().unwrap();
+        assert_eq!(lookup, lock);
+        assert_eq!(lookup.actor, actor);
+
+        let lookup = repo
+            .audit_event()
+            .lookup(set_admin.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup.details, json!({ "admin": false }));
+
+        // List and count with filters
+        assert_eq!(repo.audit_event().count(all).await.unwrap(), 2);
+
+        let page = repo
+            .audit_event()
+            .list(all, Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 2);
+        assert_eq!(page.edges[0].node, lock);
+        assert_eq!(page.edges[1].node, set_admin);
+
+        let filter = AuditEventFilter::new().for_actor_user(&admin);
+        let page = repo
+            .audit_event()
+            .list(filter, Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 1);
+        assert_eq!(page.edges[0].node, lock);
+
+        let filter = AuditEventFilter::new().for_target_user(&admin);
+        assert_eq!(repo.audit_event().count(filter).await.unwrap(), 1);
+
+        let filter = AuditEventFilter::new().with_action(AuditAction::UserLock);
+        assert_eq!(repo.audit_event().count(filter).await.unwrap(), 1);
+
+        let filter = AuditEventFilter::new().with_action(AuditAction::UserUnlock);
+        assert_eq!(repo.audit_event().count(filter).await.unwrap(), 0);
+
+        let filter = AuditEventFilter::new().with_created_after(lock.created_at);
+        let page = repo
+            .audit_event()
+            .list(filter, Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 1);
+        assert_eq!(page.edges[0].node, set_admin);
+
+        let filter = AuditEventFilter::new().with_created_before(set_admin.created_at);
+        assert_eq!(repo.audit_event().count(filter).await.unwrap(), 1);
+    }
+}
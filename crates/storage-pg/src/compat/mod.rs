@@ -20,7 +20,7 @@ pub use self::{
 #[cfg(test)]
 mod tests {
     use chrono::Duration;
-    use mas_data_model::{Clock, Device, clock::MockClock};
+    use mas_data_model::{Clock, Device, Impersonation, clock::MockClock};
     use mas_storage::{
         Pagination, RepositoryAccess,
         compat::{
@@ -310,6 +310,30 @@ mod tests {
         assert_eq!(affected, 1);
         assert_eq!(repo.compat_session().count(finished).await.unwrap(), 2);
         assert_eq!(repo.compat_session().count(active).await.unwrap(), 0);
+
+        // Start a session impersonating the user
+        let impersonation = Impersonation {
+            impersonator_user_id: Some(user.id),
+            impersonator_client_id: None,
+            expires_at: clock.now() + Duration::try_hours(1).unwrap(),
+        };
+        let device = Device::generate(&mut rng);
+        let session = repo
+            .compat_session()
+            .add_impersonation(&mut rng, &clock, &user, device, impersonation.clone())
+            .await
+            .unwrap();
+        assert_eq!(session.impersonation, Some(impersonation));
+        assert!(!session.is_synapse_admin);
+
+        let session_lookup = repo
+            .compat_session()
+            .lookup(session.id)
+            .await
+            .unwrap()
+            .expect("compat session not found");
+        assert_eq!(session, session_lookup);
+        assert_eq!(repo.compat_session().count(active).await.unwrap(), 1);
     }
 
     #[sqlx::test(migrator = "crate::MIGRATOR")]
@@ -10,7 +10,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use mas_data_model::{
     BrowserSession, Clock, CompatSession, CompatSessionState, CompatSsoLogin, CompatSsoLoginState,
-    Device, User,
+    Device, Impersonation, User,
 };
 use mas_storage::{
     Page, Pagination,
@@ -58,6 +58,9 @@ struct CompatSessionLookup {
     user_agent: Option<String>,
     last_active_at: Option<DateTime<Utc>>,
     last_active_ip: Option<IpAddr>,
+    impersonator_user_id: Option<Uuid>,
+    impersonator_client_id: Option<Uuid>,
+    impersonation_expires_at: Option<DateTime<Utc>>,
 }
 
 impl Node<Ulid> for CompatSessionLookup {
@@ -87,6 +90,13 @@ impl From<CompatSessionLookup> for CompatSession {
             user_agent: value.user_agent,
             last_active_at: value.last_active_at,
             last_active_ip: value.last_active_ip,
+            impersonation: value
+                .impersonation_expires_at
+                .map(|expires_at| Impersonation {
+                    impersonator_user_id: value.impersonator_user_id.map(Ulid::from),
+                    impersonator_client_id: value.impersonator_client_id.map(Ulid::from),
+                    expires_at,
+                }),
         }
     }
 }
@@ -105,6 +115,9 @@ struct CompatSessionAndSsoLoginLookup {
     user_agent: Option<String>,
     last_active_at: Option<DateTime<Utc>>,
     last_active_ip: Option<IpAddr>,
+    impersonator_user_id: Option<Uuid>,
+    impersonator_client_id: Option<Uuid>,
+    impersonation_expires_at: Option<DateTime<Utc>>,
     compat_sso_login_id: Option<Uuid>,
     compat_sso_login_token: Option<String>,
     compat_sso_login_redirect_uri: Option<String>,
@@ -142,6 +155,13 @@ impl TryFrom<CompatSessionAndSsoLoginLookup> for (CompatSession, Option<CompatSs
             user_agent: value.user_agent,
             last_active_at: value.last_active_at,
             last_active_ip: value.last_active_ip,
+            impersonation: value
+                .impersonation_expires_at
+                .map(|expires_at| Impersonation {
+                    impersonator_user_id: value.impersonator_user_id.map(Ulid::from),
+                    impersonator_client_id: value.impersonator_client_id.map(Ulid::from),
+                    expires_at,
+                }),
         };
 
         match (
@@ -296,6 +316,9 @@ impl CompatSessionRepository for PgCompatSessionRepository<'_> {
                      , user_agent
                      , last_active_at
                      , last_active_ip as "last_active_ip: IpAddr"
+                     , impersonator_user_id
+                     , impersonator_client_id
+                     , impersonation_expires_at
                 FROM compat_sessions
                 WHERE compat_session_id = $1
             "#,
@@ -368,6 +391,68 @@ impl CompatSessionRepository for PgCompatSessionRepository<'_> {
             user_agent: None,
             last_active_at: None,
             last_active_ip: None,
+            impersonation: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.compat_session.add_impersonation",
+        skip_all,
+        fields(
+            db.query.text,
+            compat_session.id,
+            %user.id,
+            %user.username,
+            compat_session.device.id = device.as_str(),
+        ),
+        err,
+    )]
+    async fn add_impersonation(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        device: Device,
+        impersonation: Impersonation,
+    ) -> Result<CompatSession, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("compat_session.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO compat_sessions
+                    (compat_session_id, user_id, device_id,
+                     created_at, is_synapse_admin,
+                     impersonator_user_id, impersonator_client_id,
+                     impersonation_expires_at)
+                VALUES ($1, $2, $3, $4, FALSE, $5, $6, $7)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            device.as_str(),
+            created_at,
+            impersonation.impersonator_user_id.map(Uuid::from),
+            impersonation.impersonator_client_id.map(Uuid::from),
+            impersonation.expires_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(CompatSession {
+            id,
+            state: CompatSessionState::default(),
+            user_id: user.id,
+            device: Some(device),
+            human_name: None,
+            user_session_id: None,
+            created_at,
+            is_synapse_admin: false,
+            user_agent: None,
+            last_active_at: None,
+            last_active_ip: None,
+            impersonation: Some(impersonation),
         })
     }
 
@@ -495,6 +580,21 @@ impl CompatSessionRepository for PgCompatSessionRepository<'_> {
                 Expr::col((CompatSessions::Table, CompatSessions::LastActiveIp)),
                 CompatSessionAndSsoLoginLookupIden::LastActiveIp,
             )
+            .expr_as(
+                Expr::col((CompatSessions::Table, CompatSessions::ImpersonatorUserId)),
+                CompatSessionAndSsoLoginLookupIden::ImpersonatorUserId,
+            )
+            .expr_as(
+                Expr::col((CompatSessions::Table, CompatSessions::ImpersonatorClientId)),
+                CompatSessionAndSsoLoginLookupIden::ImpersonatorClientId,
+            )
+            .expr_as(
+                Expr::col((
+                    CompatSessions::Table,
+                    CompatSessions::ImpersonationExpiresAt,
+                )),
+                CompatSessionAndSsoLoginLookupIden::ImpersonationExpiresAt,
+            )
             .expr_as(
                 Expr::col((CompatSsoLogins::Table, CompatSsoLogins::CompatSsoLoginId)),
                 CompatSessionAndSsoLoginLookupIden::CompatSsoLoginId,
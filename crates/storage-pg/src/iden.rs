@@ -38,6 +38,7 @@ pub enum Users {
     CreatedAt,
     LockedAt,
     DeactivatedAt,
+    ErasedAt,
     CanRequestAdmin,
     IsGuest,
 }
@@ -51,6 +52,21 @@ pub enum UserEmails {
     CreatedAt,
 }
 
+#[derive(sea_query::Iden)]
+pub enum UserPasskeys {
+    Table,
+    UserPasskeyId,
+    UserId,
+    CredentialId,
+    Name,
+    Transports,
+    PublicKey,
+    Aaguid,
+    SignCount,
+    CreatedAt,
+    LastUsedAt,
+}
+
 #[derive(sea_query::Iden)]
 pub enum CompatSessions {
     Table,
@@ -65,6 +81,9 @@ pub enum CompatSessions {
     UserAgent,
     LastActiveAt,
     LastActiveIp,
+    ImpersonatorUserId,
+    ImpersonatorClientId,
+    ImpersonationExpiresAt,
 }
 
 #[derive(sea_query::Iden)]
@@ -97,6 +116,10 @@ pub enum OAuth2Sessions {
     LastActiveAt,
     LastActiveIp,
     HumanName,
+    ParentSessionId,
+    ImpersonatorUserId,
+    ImpersonatorClientId,
+    ImpersonationExpiresAt,
 }
 
 #[derive(sea_query::Iden)]
@@ -106,6 +129,33 @@ pub enum OAuth2Clients {
     #[iden = "oauth2_client_id"]
     OAuth2ClientId,
     IsStatic,
+    MetadataDigest,
+    EncryptedClientSecret,
+    ApplicationType,
+    RedirectUris,
+    GrantTypeAuthorizationCode,
+    GrantTypeRefreshToken,
+    GrantTypeClientCredentials,
+    GrantTypeDeviceCode,
+    GrantTypeTokenExchange,
+    ClientName,
+    LogoUri,
+    ClientUri,
+    PolicyUri,
+    TosUri,
+    JwksUri,
+    Jwks,
+    IdTokenSignedResponseAlg,
+    UserinfoSignedResponseAlg,
+    TokenEndpointAuthMethod,
+    TokenEndpointAuthSigningAlg,
+    InitiateLoginUri,
+    PostLogoutRedirectUris,
+    BackchannelLogoutUri,
+    BackchannelLogoutSessionRequired,
+    RequirePushedAuthorizationRequests,
+    RequireSignedRequestObject,
+    AccessTokenSignedResponseAlg,
 }
 
 #[derive(sea_query::Iden)]
@@ -167,6 +217,8 @@ pub enum UpstreamOAuthProviders {
     AuthorizationEndpointOverride,
     UserinfoEndpointOverride,
     OnBackchannelLogout,
+    IsStatic,
+    Saml,
 }
 
 #[derive(sea_query::Iden)]
@@ -218,3 +270,30 @@ pub enum UserRegistrationTokens {
     ExpiresAt,
     RevokedAt,
 }
+
+#[derive(sea_query::Iden)]
+pub enum AuditEvents {
+    Table,
+    AuditEventId,
+    CreatedAt,
+    Action,
+    ActorUserId,
+    ActorSessionId,
+    IpAddress,
+    UserAgent,
+    TargetUserId,
+    TargetId,
+    Details,
+}
+
+#[derive(sea_query::Iden)]
+pub enum WebhookDeliveries {
+    Table,
+    WebhookDeliveryId,
+    CreatedAt,
+    Event,
+    Url,
+    Payload,
+    DeliveredAt,
+    FailedAt,
+}
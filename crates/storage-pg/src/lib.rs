@@ -170,14 +170,17 @@ pub mod queue;
 pub mod upstream_oauth2;
 pub mod user;
 
+pub(crate) mod audit_event;
 mod errors;
 pub(crate) mod filter;
 pub(crate) mod iden;
 pub(crate) mod pagination;
 pub(crate) mod policy_data;
+pub(crate) mod rate_limit;
 pub(crate) mod repository;
 pub(crate) mod telemetry;
 pub(crate) mod tracing;
+pub(crate) mod webhook;
 
 pub(crate) use self::errors::DatabaseInconsistencyError;
 pub use self::{
@@ -13,17 +13,29 @@ use async_trait::async_trait;
 use mas_data_model::{Client, Clock, JwksOrJwksUri};
 use mas_iana::{jose::JsonWebSignatureAlg, oauth::OAuthClientAuthenticationMethod};
 use mas_jose::jwk::PublicJsonWebKeySet;
-use mas_storage::oauth2::OAuth2ClientRepository;
+use mas_storage::{
+    Page, Pagination,
+    oauth2::{OAuth2ClientFilter, OAuth2ClientRepository},
+    pagination::Node,
+};
 use oauth2_types::{oidc::ApplicationType, requests::GrantType};
 use opentelemetry_semantic_conventions::attribute::DB_QUERY_TEXT;
 use rand::RngCore;
+use sea_query::{Expr, PostgresQueryBuilder, Query, enum_def};
+use sea_query_binder::SqlxBinder;
 use sqlx::PgConnection;
 use tracing::{Instrument, info_span};
 use ulid::Ulid;
 use url::Url;
 use uuid::Uuid;
 
-use crate::{DatabaseError, DatabaseInconsistencyError, tracing::ExecuteExt};
+use crate::{
+    DatabaseError, DatabaseInconsistencyError,
+    filter::{Filter, StatementExt},
+    iden::OAuth2Clients,
+    pagination::QueryBuilderExt,
+    tracing::ExecuteExt,
+};
 
 /// An implementation of [`OAuth2ClientRepository`] for a PostgreSQL connection
 pub struct PgOAuth2ClientRepository<'c> {
@@ -39,9 +51,11 @@ impl<'c> PgOAuth2ClientRepository<'c> {
 }
 
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Debug)]
+#[derive(Debug, sqlx::FromRow)]
+#[enum_def]
 struct OAuth2ClientLookup {
     oauth2_client_id: Uuid,
+    is_static: bool,
     metadata_digest: Option<String>,
     encrypted_client_secret: Option<String>,
     application_type: Option<String>,
@@ -50,6 +64,7 @@ struct OAuth2ClientLookup {
     grant_type_refresh_token: bool,
     grant_type_client_credentials: bool,
     grant_type_device_code: bool,
+    grant_type_token_exchange: bool,
     client_name: Option<String>,
     logo_uri: Option<String>,
     client_uri: Option<String>,
@@ -62,6 +77,18 @@ struct OAuth2ClientLookup {
     token_endpoint_auth_method: Option<String>,
     token_endpoint_auth_signing_alg: Option<String>,
     initiate_login_uri: Option<String>,
+    post_logout_redirect_uris: Vec<String>,
+    backchannel_logout_uri: Option<String>,
+    backchannel_logout_session_required: bool,
+    require_pushed_authorization_requests: bool,
+    require_signed_request_object: bool,
+    access_token_signed_response_alg: Option<String>,
+}
+
+impl Node<Ulid> for OAuth2ClientLookup {
+    fn cursor(&self) -> Ulid {
+        self.oauth2_client_id.into()
+    }
 }
 
 impl TryInto<Client> for OAuth2ClientLookup {
@@ -79,6 +106,18 @@ impl TryInto<Client> for OAuth2ClientLookup {
                 .source(e)
         })?;
 
+        let post_logout_redirect_uris: Result<Vec<Url>, _> = self
+            .post_logout_redirect_uris
+            .iter()
+            .map(|s| s.parse())
+            .collect();
+        let post_logout_redirect_uris = post_logout_redirect_uris.map_err(|e| {
+            DatabaseInconsistencyError::on("oauth2_clients")
+                .column("post_logout_redirect_uris")
+                .row(id)
+                .source(e)
+        })?;
+
         let application_type = self
             .application_type
             .map(|s| s.parse())
@@ -103,6 +142,9 @@ impl TryInto<Client> for OAuth2ClientLookup {
         if self.grant_type_device_code {
             grant_types.push(GrantType::DeviceCode);
         }
+        if self.grant_type_token_exchange {
+            grant_types.push(GrantType::TokenExchange);
+        }
 
         let logo_uri = self.logo_uri.map(|s| s.parse()).transpose().map_err(|e| {
             DatabaseInconsistencyError::on("oauth2_clients")
@@ -162,6 +204,17 @@ impl TryInto<Client> for OAuth2ClientLookup {
                     .source(e)
             })?;
 
+        let access_token_signed_response_alg = self
+            .access_token_signed_response_alg
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("access_token_signed_response_alg")
+                    .row(id)
+                    .source(e)
+            })?;
+
         let token_endpoint_auth_method = self
             .token_endpoint_auth_method
             .map(|s| s.parse())
@@ -195,6 +248,17 @@ impl TryInto<Client> for OAuth2ClientLookup {
                     .source(e)
             })?;
 
+        let backchannel_logout_uri = self
+            .backchannel_logout_uri
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("oauth2_clients")
+                    .column("backchannel_logout_uri")
+                    .row(id)
+                    .source(e)
+            })?;
+
         let jwks = match (self.jwks, self.jwks_uri) {
             (None, None) => None,
             (Some(jwks), None) => {
@@ -226,6 +290,7 @@ impl TryInto<Client> for OAuth2ClientLookup {
         Ok(Client {
             id,
             client_id: id.to_string(),
+            is_static: self.is_static,
             metadata_digest: self.metadata_digest,
             encrypted_client_secret: self.encrypted_client_secret,
             application_type,
@@ -242,10 +307,29 @@ impl TryInto<Client> for OAuth2ClientLookup {
             token_endpoint_auth_method,
             token_endpoint_auth_signing_alg,
             initiate_login_uri,
+            post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required: self.backchannel_logout_session_required,
+            require_pushed_authorization_requests: self.require_pushed_authorization_requests,
+            require_signed_request_object: self.require_signed_request_object,
+            access_token_signed_response_alg,
         })
     }
 }
 
+impl Filter for OAuth2ClientFilter<'_> {
+    fn generate_condition(&self, _has_joins: bool) -> impl sea_query::IntoCondition {
+        sea_query::Condition::all()
+            .add_option(self.is_static().map(|is_static| {
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::IsStatic)).eq(is_static)
+            }))
+            .add_option(self.token_endpoint_auth_method().map(|method| {
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::TokenEndpointAuthMethod))
+                    .eq(method.to_string())
+            }))
+    }
+}
+
 #[async_trait]
 impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
     type Error = DatabaseError;
@@ -264,6 +348,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             OAuth2ClientLookup,
             r#"
                 SELECT oauth2_client_id
+                     , is_static
                      , metadata_digest
                      , encrypted_client_secret
                      , application_type
@@ -272,6 +357,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , grant_type_refresh_token
                      , grant_type_client_credentials
                      , grant_type_device_code
+                     , grant_type_token_exchange
                      , client_name
                      , logo_uri
                      , client_uri
@@ -284,6 +370,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , token_endpoint_auth_method
                      , token_endpoint_auth_signing_alg
                      , initiate_login_uri
+                     , post_logout_redirect_uris
+                     , backchannel_logout_uri
+                     , backchannel_logout_session_required
+                     , require_pushed_authorization_requests
+                     , require_signed_request_object
+                     , access_token_signed_response_alg
                 FROM oauth2_clients c
 
                 WHERE oauth2_client_id = $1
@@ -315,6 +407,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             OAuth2ClientLookup,
             r#"
                 SELECT oauth2_client_id
+                    , is_static
                     , metadata_digest
                     , encrypted_client_secret
                     , application_type
@@ -323,6 +416,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , grant_type_refresh_token
                     , grant_type_client_credentials
                     , grant_type_device_code
+                    , grant_type_token_exchange
                     , client_name
                     , logo_uri
                     , client_uri
@@ -335,6 +429,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , token_endpoint_auth_method
                     , token_endpoint_auth_signing_alg
                     , initiate_login_uri
+                    , post_logout_redirect_uris
+                    , backchannel_logout_uri
+                    , backchannel_logout_session_required
+                    , require_pushed_authorization_requests
+                    , require_signed_request_object
+                    , access_token_signed_response_alg
                 FROM oauth2_clients
                 WHERE metadata_digest = $1
             "#,
@@ -349,6 +449,64 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         Ok(Some(res.try_into()?))
     }
 
+    #[tracing::instrument(
+        name = "db.oauth2_client.find_by_registration_access_token",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn find_by_registration_access_token(
+        &mut self,
+        registration_access_token: &str,
+    ) -> Result<Option<Client>, Self::Error> {
+        let res = sqlx::query_as!(
+            OAuth2ClientLookup,
+            r#"
+                SELECT oauth2_client_id
+                     , is_static
+                     , metadata_digest
+                     , encrypted_client_secret
+                     , application_type
+                     , redirect_uris
+                     , grant_type_authorization_code
+                     , grant_type_refresh_token
+                     , grant_type_client_credentials
+                     , grant_type_device_code
+                     , grant_type_token_exchange
+                     , client_name
+                     , logo_uri
+                     , client_uri
+                     , policy_uri
+                     , tos_uri
+                     , jwks_uri
+                     , jwks
+                     , id_token_signed_response_alg
+                     , userinfo_signed_response_alg
+                     , token_endpoint_auth_method
+                     , token_endpoint_auth_signing_alg
+                     , initiate_login_uri
+                     , post_logout_redirect_uris
+                     , backchannel_logout_uri
+                     , backchannel_logout_session_required
+                     , require_pushed_authorization_requests
+                     , require_signed_request_object
+                     , access_token_signed_response_alg
+                FROM oauth2_clients
+                WHERE registration_access_token = $1
+            "#,
+            registration_access_token,
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(res) = res else { return Ok(None) };
+
+        Ok(Some(res.try_into()?))
+    }
+
     #[tracing::instrument(
         name = "db.oauth2_client.load_batch",
         skip_all,
@@ -366,6 +524,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             OAuth2ClientLookup,
             r#"
                 SELECT oauth2_client_id
+                     , is_static
                      , metadata_digest
                      , encrypted_client_secret
                      , application_type
@@ -374,6 +533,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , grant_type_refresh_token
                      , grant_type_client_credentials
                      , grant_type_device_code
+                     , grant_type_token_exchange
                      , client_name
                      , logo_uri
                      , client_uri
@@ -386,6 +546,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , token_endpoint_auth_method
                      , token_endpoint_auth_signing_alg
                      , initiate_login_uri
+                     , post_logout_redirect_uris
+                     , backchannel_logout_uri
+                     , backchannel_logout_session_required
+                     , require_pushed_authorization_requests
+                     , require_signed_request_object
+                     , access_token_signed_response_alg
                 FROM oauth2_clients c
 
                 WHERE oauth2_client_id = ANY($1::uuid[])
@@ -436,6 +602,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
         token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
         initiate_login_uri: Option<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
     ) -> Result<Client, Self::Error> {
         let now = clock.now();
         let id = Ulid::from_datetime_with_source(now.into(), rng);
@@ -448,6 +620,10 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             .map_err(DatabaseError::to_invalid_operation)?;
 
         let redirect_uris_array = redirect_uris.iter().map(Url::to_string).collect::<Vec<_>>();
+        let post_logout_redirect_uris_array = post_logout_redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
 
         sqlx::query!(
             r#"
@@ -461,6 +637,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , grant_type_refresh_token
                     , grant_type_client_credentials
                     , grant_type_device_code
+                    , grant_type_token_exchange
                     , client_name
                     , logo_uri
                     , client_uri
@@ -473,11 +650,18 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , token_endpoint_auth_method
                     , token_endpoint_auth_signing_alg
                     , initiate_login_uri
+                    , post_logout_redirect_uris
+                    , backchannel_logout_uri
+                    , backchannel_logout_session_required
+                    , require_pushed_authorization_requests
+                    , require_signed_request_object
+                    , access_token_signed_response_alg
                     , is_static
                     )
                 VALUES
                     ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
-                    $14, $15, $16, $17, $18, $19, $20, $21, FALSE)
+                    $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25,
+                    $26, $27, $28, FALSE)
             "#,
             Uuid::from(id),
             metadata_digest,
@@ -488,6 +672,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             grant_types.contains(&GrantType::RefreshToken),
             grant_types.contains(&GrantType::ClientCredentials),
             grant_types.contains(&GrantType::DeviceCode),
+            grant_types.contains(&GrantType::TokenExchange),
             client_name,
             logo_uri.as_ref().map(Url::as_str),
             client_uri.as_ref().map(Url::as_str),
@@ -506,6 +691,14 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                 .as_ref()
                 .map(ToString::to_string),
             initiate_login_uri.as_ref().map(Url::as_str),
+            &post_logout_redirect_uris_array,
+            backchannel_logout_uri.as_ref().map(Url::as_str),
+            backchannel_logout_session_required,
+            require_pushed_authorization_requests,
+            require_signed_request_object,
+            access_token_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
         )
         .traced()
         .execute(&mut *self.conn)
@@ -521,6 +714,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         Ok(Client {
             id,
             client_id: id.to_string(),
+            is_static: false,
             metadata_digest: None,
             encrypted_client_secret,
             application_type,
@@ -537,6 +731,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             token_endpoint_auth_method,
             token_endpoint_auth_signing_alg,
             initiate_login_uri,
+            post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            require_pushed_authorization_requests,
+            require_signed_request_object,
+            access_token_signed_response_alg,
         })
     }
 
@@ -558,6 +758,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         jwks: Option<PublicJsonWebKeySet>,
         jwks_uri: Option<Url>,
         redirect_uris: Vec<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
     ) -> Result<Client, Self::Error> {
         let jwks_json = jwks
             .as_ref()
@@ -567,6 +773,10 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
 
         let client_auth_method = client_auth_method.to_string();
         let redirect_uris_array = redirect_uris.iter().map(Url::to_string).collect::<Vec<_>>();
+        let post_logout_redirect_uris_array = post_logout_redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
 
         sqlx::query!(
             r#"
@@ -578,14 +788,22 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                     , grant_type_refresh_token
                     , grant_type_client_credentials
                     , grant_type_device_code
+                    , grant_type_token_exchange
                     , token_endpoint_auth_method
                     , jwks
                     , client_name
                     , jwks_uri
+                    , post_logout_redirect_uris
+                    , backchannel_logout_uri
+                    , backchannel_logout_session_required
+                    , require_pushed_authorization_requests
+                    , require_signed_request_object
+                    , access_token_signed_response_alg
                     , is_static
                     )
                 VALUES
-                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, TRUE)
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                    $17, $18, TRUE)
                 ON CONFLICT (oauth2_client_id)
                 DO
                     UPDATE SET encrypted_client_secret = EXCLUDED.encrypted_client_secret
@@ -594,10 +812,17 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                              , grant_type_refresh_token = EXCLUDED.grant_type_refresh_token
                              , grant_type_client_credentials = EXCLUDED.grant_type_client_credentials
                              , grant_type_device_code = EXCLUDED.grant_type_device_code
+                             , grant_type_token_exchange = EXCLUDED.grant_type_token_exchange
                              , token_endpoint_auth_method = EXCLUDED.token_endpoint_auth_method
                              , jwks = EXCLUDED.jwks
                              , client_name = EXCLUDED.client_name
                              , jwks_uri = EXCLUDED.jwks_uri
+                             , post_logout_redirect_uris = EXCLUDED.post_logout_redirect_uris
+                             , backchannel_logout_uri = EXCLUDED.backchannel_logout_uri
+                             , backchannel_logout_session_required = EXCLUDED.backchannel_logout_session_required
+                             , require_pushed_authorization_requests = EXCLUDED.require_pushed_authorization_requests
+                             , require_signed_request_object = EXCLUDED.require_signed_request_object
+                             , access_token_signed_response_alg = EXCLUDED.access_token_signed_response_alg
                              , is_static = TRUE
             "#,
             Uuid::from(client_id),
@@ -607,10 +832,19 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             true,
             true,
             true,
+            true,
             client_auth_method,
             jwks_json,
             client_name,
             jwks_uri.as_ref().map(Url::as_str),
+            &post_logout_redirect_uris_array,
+            backchannel_logout_uri.as_ref().map(Url::as_str),
+            backchannel_logout_session_required,
+            require_pushed_authorization_requests,
+            require_signed_request_object,
+            access_token_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
         )
         .traced()
         .execute(&mut *self.conn)
@@ -626,6 +860,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
         Ok(Client {
             id: client_id,
             client_id: client_id.to_string(),
+            is_static: true,
             metadata_digest: None,
             encrypted_client_secret,
             application_type: None,
@@ -634,6 +869,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                 GrantType::AuthorizationCode,
                 GrantType::RefreshToken,
                 GrantType::ClientCredentials,
+                GrantType::TokenExchange,
             ],
             client_name,
             logo_uri: None,
@@ -646,6 +882,265 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             token_endpoint_auth_method: None,
             token_endpoint_auth_signing_alg: None,
             initiate_login_uri: None,
+            post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            require_pushed_authorization_requests,
+            require_signed_request_object,
+            access_token_signed_response_alg,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_client.update_metadata",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+            client.name = client_name
+        ),
+        err,
+    )]
+    async fn update_metadata(
+        &mut self,
+        client: Client,
+        redirect_uris: Vec<Url>,
+        application_type: Option<ApplicationType>,
+        grant_types: Vec<GrantType>,
+        client_name: Option<String>,
+        logo_uri: Option<Url>,
+        client_uri: Option<Url>,
+        policy_uri: Option<Url>,
+        tos_uri: Option<Url>,
+        jwks_uri: Option<Url>,
+        jwks: Option<PublicJsonWebKeySet>,
+        id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+        userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
+        token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
+        initiate_login_uri: Option<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+    ) -> Result<Client, Self::Error> {
+        let jwks_json = jwks
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(DatabaseError::to_invalid_operation)?;
+
+        let redirect_uris_array = redirect_uris.iter().map(Url::to_string).collect::<Vec<_>>();
+        let post_logout_redirect_uris_array = post_logout_redirect_uris
+            .iter()
+            .map(Url::to_string)
+            .collect::<Vec<_>>();
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_clients
+                SET metadata_digest = NULL
+                  , application_type = $2
+                  , redirect_uris = $3
+                  , grant_type_authorization_code = $4
+                  , grant_type_refresh_token = $5
+                  , grant_type_client_credentials = $6
+                  , grant_type_device_code = $7
+                  , grant_type_token_exchange = $8
+                  , client_name = $9
+                  , logo_uri = $10
+                  , client_uri = $11
+                  , policy_uri = $12
+                  , tos_uri = $13
+                  , jwks_uri = $14
+                  , jwks = $15
+                  , id_token_signed_response_alg = $16
+                  , userinfo_signed_response_alg = $17
+                  , token_endpoint_auth_signing_alg = $18
+                  , initiate_login_uri = $19
+                  , post_logout_redirect_uris = $20
+                  , backchannel_logout_uri = $21
+                  , backchannel_logout_session_required = $22
+                  , require_pushed_authorization_requests = $23
+                  , require_signed_request_object = $24
+                  , access_token_signed_response_alg = $25
+                WHERE oauth2_client_id = $1
+                  AND is_static = FALSE
+            "#,
+            Uuid::from(client.id),
+            application_type.as_ref().map(ToString::to_string),
+            &redirect_uris_array,
+            grant_types.contains(&GrantType::AuthorizationCode),
+            grant_types.contains(&GrantType::RefreshToken),
+            grant_types.contains(&GrantType::ClientCredentials),
+            grant_types.contains(&GrantType::DeviceCode),
+            grant_types.contains(&GrantType::TokenExchange),
+            client_name,
+            logo_uri.as_ref().map(Url::as_str),
+            client_uri.as_ref().map(Url::as_str),
+            policy_uri.as_ref().map(Url::as_str),
+            tos_uri.as_ref().map(Url::as_str),
+            jwks_uri.as_ref().map(Url::as_str),
+            jwks_json,
+            id_token_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            userinfo_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            token_endpoint_auth_signing_alg
+                .as_ref()
+                .map(ToString::to_string),
+            initiate_login_uri.as_ref().map(Url::as_str),
+            &post_logout_redirect_uris_array,
+            backchannel_logout_uri.as_ref().map(Url::as_str),
+            backchannel_logout_session_required,
+            require_pushed_authorization_requests,
+            require_signed_request_object,
+            access_token_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        let jwks = match (jwks, jwks_uri) {
+            (None, None) => None,
+            (Some(jwks), None) => Some(JwksOrJwksUri::Jwks(jwks)),
+            (None, Some(jwks_uri)) => Some(JwksOrJwksUri::JwksUri(jwks_uri)),
+            _ => return Err(DatabaseError::invalid_operation()),
+        };
+
+        Ok(Client {
+            metadata_digest: None,
+            application_type,
+            redirect_uris,
+            grant_types,
+            client_name,
+            logo_uri,
+            client_uri,
+            policy_uri,
+            tos_uri,
+            jwks,
+            id_token_signed_response_alg,
+            userinfo_signed_response_alg,
+            token_endpoint_auth_signing_alg,
+            initiate_login_uri,
+            post_logout_redirect_uris,
+            backchannel_logout_uri,
+            backchannel_logout_session_required,
+            require_pushed_authorization_requests,
+            require_signed_request_object,
+            access_token_signed_response_alg,
+            ..client
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_client.set_registration_access_token",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+        ),
+        err,
+    )]
+    async fn set_registration_access_token(
+        &mut self,
+        client: &Client,
+        registration_access_token: Option<String>,
+    ) -> Result<(), Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_clients
+                SET registration_access_token = $2
+                WHERE oauth2_client_id = $1
+            "#,
+            Uuid::from(client.id),
+            registration_access_token,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_client.set_client_secret",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+        ),
+        err,
+    )]
+    async fn set_client_secret(
+        &mut self,
+        client: Client,
+        encrypted_client_secret: String,
+    ) -> Result<Client, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_clients
+                SET encrypted_client_secret = $2
+                WHERE oauth2_client_id = $1
+            "#,
+            Uuid::from(client.id),
+            &encrypted_client_secret,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(Client {
+            encrypted_client_secret: Some(encrypted_client_secret),
+            ..client
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_client.set_redirect_uris",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+        ),
+        err,
+    )]
+    async fn set_redirect_uris(
+        &mut self,
+        client: Client,
+        redirect_uris: Vec<Url>,
+    ) -> Result<Client, Self::Error> {
+        let redirect_uris_array = redirect_uris.iter().map(Url::to_string).collect::<Vec<_>>();
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_clients
+                SET redirect_uris = $2
+                WHERE oauth2_client_id = $1
+            "#,
+            Uuid::from(client.id),
+            &redirect_uris_array,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(Client {
+            redirect_uris,
+            ..client
         })
     }
 
@@ -662,6 +1157,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             OAuth2ClientLookup,
             r#"
                 SELECT oauth2_client_id
+                     , is_static
                      , metadata_digest
                      , encrypted_client_secret
                      , application_type
@@ -670,6 +1166,7 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , grant_type_refresh_token
                      , grant_type_client_credentials
                      , grant_type_device_code
+                     , grant_type_token_exchange
                      , client_name
                      , logo_uri
                      , client_uri
@@ -682,6 +1179,12 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
                      , token_endpoint_auth_method
                      , token_endpoint_auth_signing_alg
                      , initiate_login_uri
+                     , post_logout_redirect_uris
+                     , backchannel_logout_uri
+                     , backchannel_logout_session_required
+                     , require_pushed_authorization_requests
+                     , require_signed_request_object
+                     , access_token_signed_response_alg
                 FROM oauth2_clients c
                 WHERE is_static = TRUE
             "#,
@@ -695,6 +1198,206 @@ impl OAuth2ClientRepository for PgOAuth2ClientRepository<'_> {
             .collect()
     }
 
+    #[tracing::instrument(
+        name = "db.oauth2_client.list",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn list(
+        &mut self,
+        filter: OAuth2ClientFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<Client>, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::OAuth2ClientId)),
+                OAuth2ClientLookupIden::Oauth2ClientId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::IsStatic)),
+                OAuth2ClientLookupIden::IsStatic,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::MetadataDigest)),
+                OAuth2ClientLookupIden::MetadataDigest,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::EncryptedClientSecret)),
+                OAuth2ClientLookupIden::EncryptedClientSecret,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::ApplicationType)),
+                OAuth2ClientLookupIden::ApplicationType,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::RedirectUris)),
+                OAuth2ClientLookupIden::RedirectUris,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::GrantTypeAuthorizationCode,
+                )),
+                OAuth2ClientLookupIden::GrantTypeAuthorizationCode,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::GrantTypeRefreshToken)),
+                OAuth2ClientLookupIden::GrantTypeRefreshToken,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::GrantTypeClientCredentials,
+                )),
+                OAuth2ClientLookupIden::GrantTypeClientCredentials,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::GrantTypeDeviceCode)),
+                OAuth2ClientLookupIden::GrantTypeDeviceCode,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::GrantTypeTokenExchange)),
+                OAuth2ClientLookupIden::GrantTypeTokenExchange,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::ClientName)),
+                OAuth2ClientLookupIden::ClientName,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::LogoUri)),
+                OAuth2ClientLookupIden::LogoUri,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::ClientUri)),
+                OAuth2ClientLookupIden::ClientUri,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::PolicyUri)),
+                OAuth2ClientLookupIden::PolicyUri,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::TosUri)),
+                OAuth2ClientLookupIden::TosUri,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::JwksUri)),
+                OAuth2ClientLookupIden::JwksUri,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::Jwks)),
+                OAuth2ClientLookupIden::Jwks,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::IdTokenSignedResponseAlg,
+                )),
+                OAuth2ClientLookupIden::IdTokenSignedResponseAlg,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::UserinfoSignedResponseAlg,
+                )),
+                OAuth2ClientLookupIden::UserinfoSignedResponseAlg,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::TokenEndpointAuthMethod)),
+                OAuth2ClientLookupIden::TokenEndpointAuthMethod,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::TokenEndpointAuthSigningAlg,
+                )),
+                OAuth2ClientLookupIden::TokenEndpointAuthSigningAlg,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::InitiateLoginUri)),
+                OAuth2ClientLookupIden::InitiateLoginUri,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::PostLogoutRedirectUris)),
+                OAuth2ClientLookupIden::PostLogoutRedirectUris,
+            )
+            .expr_as(
+                Expr::col((OAuth2Clients::Table, OAuth2Clients::BackchannelLogoutUri)),
+                OAuth2ClientLookupIden::BackchannelLogoutUri,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::BackchannelLogoutSessionRequired,
+                )),
+                OAuth2ClientLookupIden::BackchannelLogoutSessionRequired,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::RequirePushedAuthorizationRequests,
+                )),
+                OAuth2ClientLookupIden::RequirePushedAuthorizationRequests,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::RequireSignedRequestObject,
+                )),
+                OAuth2ClientLookupIden::RequireSignedRequestObject,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Clients::Table,
+                    OAuth2Clients::AccessTokenSignedResponseAlg,
+                )),
+                OAuth2ClientLookupIden::AccessTokenSignedResponseAlg,
+            )
+            .from(OAuth2Clients::Table)
+            .apply_filter(filter)
+            .generate_pagination(
+                (OAuth2Clients::Table, OAuth2Clients::OAuth2ClientId),
+                pagination,
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        let edges: Vec<OAuth2ClientLookup> = sqlx::query_as_with(&sql, arguments)
+            .traced()
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        let page = pagination.process(edges).try_map(TryInto::try_into)?;
+
+        Ok(page)
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_client.count",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn count(&mut self, filter: OAuth2ClientFilter<'_>) -> Result<usize, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr(Expr::col((OAuth2Clients::Table, OAuth2Clients::OAuth2ClientId)).count())
+            .from(OAuth2Clients::Table)
+            .apply_filter(filter)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let count: i64 = sqlx::query_scalar_with(&sql, arguments)
+            .traced()
+            .fetch_one(&mut *self.conn)
+            .await?;
+
+        count
+            .try_into()
+            .map_err(DatabaseError::to_invalid_operation)
+    }
+
     #[tracing::instrument(
         name = "db.oauth2_client.delete_by_id",
         skip_all,
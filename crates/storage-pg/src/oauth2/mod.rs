@@ -11,6 +11,7 @@ mod access_token;
 mod authorization_grant;
 mod client;
 mod device_code_grant;
+mod pushed_authorization_request;
 mod refresh_token;
 mod session;
 
@@ -18,16 +19,23 @@ pub use self::{
     access_token::PgOAuth2AccessTokenRepository,
     authorization_grant::PgOAuth2AuthorizationGrantRepository, client::PgOAuth2ClientRepository,
     device_code_grant::PgOAuth2DeviceCodeGrantRepository,
+    pushed_authorization_request::PgOAuth2PushedAuthorizationRequestRepository,
     refresh_token::PgOAuth2RefreshTokenRepository, session::PgOAuth2SessionRepository,
 };
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use chrono::Duration;
-    use mas_data_model::{AuthorizationCode, Clock, clock::MockClock};
+    use mas_data_model::{AuthorizationCode, Clock, Impersonation, clock::MockClock};
+    use mas_iana::oauth::OAuthClientAuthenticationMethod;
     use mas_storage::{
         Pagination,
-        oauth2::{OAuth2DeviceCodeGrantParams, OAuth2SessionFilter, OAuth2SessionRepository},
+        oauth2::{
+            OAuth2ClientFilter, OAuth2DeviceCodeGrantParams, OAuth2SessionFilter,
+            OAuth2SessionRepository,
+        },
     };
     use oauth2_types::{
         requests::{GrantType, ResponseMode},
@@ -81,6 +89,12 @@ mod tests {
                 None,
                 None,
                 Some("https://example.com/login".parse().unwrap()),
+                vec!["https://example.com/logged-out".parse().unwrap()],
+                Some("https://example.com/backchannel-logout".parse().unwrap()),
+                true,
+                false,
+                false,
+                None,
             )
             .await
             .unwrap();
@@ -103,6 +117,122 @@ mod tests {
             .expect("client not found");
         assert_eq!(client, client_lookup);
 
+        // Find the client by its registration access token
+        let client_lookup = repo
+            .oauth2_client()
+            .find_by_registration_access_token("registration-token")
+            .await
+            .unwrap();
+        assert_eq!(client_lookup, None);
+
+        repo.oauth2_client()
+            .set_registration_access_token(&client, Some("registration-token".to_owned()))
+            .await
+            .unwrap();
+
+        let client_lookup = repo
+            .oauth2_client()
+            .find_by_registration_access_token("registration-token")
+            .await
+            .unwrap()
+            .expect("client not found");
+        assert_eq!(client, client_lookup);
+
+        // Update the client metadata
+        let client = repo
+            .oauth2_client()
+            .update_metadata(
+                client,
+                vec!["https://example.com/other-redirect".parse().unwrap()],
+                None,
+                vec![GrantType::AuthorizationCode, GrantType::RefreshToken],
+                Some("Updated client".to_owned()),
+                None,
+                Some("https://example.com/".parse().unwrap()),
+                None,
+                None,
+                Some("https://example.com/jwks.json".parse().unwrap()),
+                None,
+                None,
+                None,
+                None,
+                None,
+                Vec::new(),
+                None,
+                false,
+                false,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(client.client_name.as_deref(), Some("Updated client"));
+        assert!(client.require_signed_request_object);
+
+        let client_lookup = repo
+            .oauth2_client()
+            .lookup(client.id)
+            .await
+            .unwrap()
+            .expect("client not found");
+        assert_eq!(client, client_lookup);
+
+        // Remove the registration access token
+        repo.oauth2_client()
+            .set_registration_access_token(&client, None)
+            .await
+            .unwrap();
+        let client_lookup = repo
+            .oauth2_client()
+            .find_by_registration_access_token("registration-token")
+            .await
+            .unwrap();
+        assert_eq!(client_lookup, None);
+
+        // List and count the clients
+        let all = OAuth2ClientFilter::new();
+        let page = repo
+            .oauth2_client()
+            .list(all, Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 1);
+        assert_eq!(page.edges[0].node, client);
+        assert_eq!(repo.oauth2_client().count(all).await.unwrap(), 1);
+
+        let static_only = all.static_only();
+        assert_eq!(repo.oauth2_client().count(static_only).await.unwrap(), 0);
+        let dynamic_only = all.dynamic_only();
+        assert_eq!(repo.oauth2_client().count(dynamic_only).await.unwrap(), 1);
+
+        let method = OAuthClientAuthenticationMethod::ClientSecretBasic;
+        let by_method = all.with_token_endpoint_auth_method(&method);
+        assert_eq!(repo.oauth2_client().count(by_method).await.unwrap(), 0);
+
+        // Replace the redirect URIs and the client secret
+        let client = repo
+            .oauth2_client()
+            .set_redirect_uris(client, vec!["https://example.com/other".parse().unwrap()])
+            .await
+            .unwrap();
+        let client = repo
+            .oauth2_client()
+            .set_client_secret(client, "encrypted-secret".to_owned())
+            .await
+            .unwrap();
+        assert_eq!(
+            client.encrypted_client_secret.as_deref(),
+            Some("encrypted-secret")
+        );
+
+        let client_lookup = repo
+            .oauth2_client()
+            .lookup(client.id)
+            .await
+            .unwrap()
+            .expect("client not found");
+        assert_eq!(client, client_lookup);
+
         // Lookup a non-existing grant
         let grant = repo
             .oauth2_authorization_grant()
@@ -206,6 +336,56 @@ mod tests {
             .unwrap()
             .expect("session not found");
         assert_eq!(session, session_lookup);
+        assert_eq!(session.parent_session_id, None);
+
+        // Derive a session from it, as done by a token exchange
+        let child_session = repo
+            .oauth2_session()
+            .add_from_token_exchange(&mut rng, &clock, &client, &session, session.scope.clone())
+            .await
+            .unwrap();
+        assert_eq!(child_session.parent_session_id, Some(session.id));
+        assert_eq!(child_session.user_id, session.user_id);
+        assert_eq!(child_session.user_session_id, session.user_session_id);
+
+        let child_session_lookup = repo
+            .oauth2_session()
+            .lookup(child_session.id)
+            .await
+            .unwrap()
+            .expect("session not found");
+        assert_eq!(child_session, child_session_lookup);
+
+        // Start a session impersonating the user
+        let impersonation = Impersonation {
+            impersonator_user_id: Some(user.id),
+            impersonator_client_id: Some(client.id),
+            expires_at: clock.now() + Duration::try_hours(1).unwrap(),
+        };
+        let impersonation_session = repo
+            .oauth2_session()
+            .add_impersonation(
+                &mut rng,
+                &clock,
+                &client,
+                &user,
+                session.scope.clone(),
+                impersonation.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(impersonation_session.user_id, Some(user.id));
+        assert_eq!(impersonation_session.user_session_id, None);
+        assert_eq!(impersonation_session.impersonation, Some(impersonation));
+        assert_eq!(session.impersonation, None);
+
+        let impersonation_session_lookup = repo
+            .oauth2_session()
+            .lookup(impersonation_session.id)
+            .await
+            .unwrap()
+            .expect("session not found");
+        assert_eq!(impersonation_session, impersonation_session_lookup);
 
         // Mark the grant as exchanged
         let grant = repo
@@ -240,6 +420,7 @@ mod tests {
                 &session,
                 "aabbcc".to_owned(),
                 Some(Duration::try_minutes(5).unwrap()),
+                None,
             )
             .await
             .unwrap();
@@ -425,6 +606,12 @@ mod tests {
                 None,
                 None,
                 Some("https://first.example.com/login".parse().unwrap()),
+                Vec::new(),
+                None,
+                false,
+                false,
+                false,
+                None,
             )
             .await
             .unwrap();
@@ -450,6 +637,12 @@ mod tests {
                 None,
                 None,
                 Some("https://second.example.com/login".parse().unwrap()),
+                Vec::new(),
+                None,
+                false,
+                false,
+                false,
+                None,
             )
             .await
             .unwrap();
@@ -721,6 +914,162 @@ mod tests {
     }
 
     /// Test the [`OAuth2DeviceCodeGrantRepository`] implementation
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_finish_exchanged_sessions(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        // Provision two clients
+        let client = repo
+            .oauth2_client()
+            .add(
+                &mut rng,
+                &clock,
+                Vec::new(),
+                None,
+                None,
+                None,
+                vec![GrantType::ClientCredentials, GrantType::TokenExchange],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Vec::new(),
+                None,
+                false,
+                false,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        let other_client = repo
+            .oauth2_client()
+            .add(
+                &mut rng,
+                &clock,
+                Vec::new(),
+                None,
+                None,
+                None,
+                vec![GrantType::ClientCredentials, GrantType::TokenExchange],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Vec::new(),
+                None,
+                false,
+                false,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let scope = Scope::from_iter([OPENID]);
+
+        // A session, a session exchanged from it, and one exchanged from that one
+        let parent = repo
+            .oauth2_session()
+            .add_from_client_credentials(&mut rng, &clock, &client, scope.clone())
+            .await
+            .unwrap();
+        let child = repo
+            .oauth2_session()
+            .add_from_token_exchange(&mut rng, &clock, &other_client, &parent, scope.clone())
+            .await
+            .unwrap();
+        let grandchild = repo
+            .oauth2_session()
+            .add_from_token_exchange(&mut rng, &clock, &other_client, &child, scope.clone())
+            .await
+            .unwrap();
+
+        // An unrelated session
+        let unrelated = repo
+            .oauth2_session()
+            .add_from_client_credentials(&mut rng, &clock, &other_client, scope.clone())
+            .await
+            .unwrap();
+
+        // Finishing the parent finishes the whole chain
+        repo.oauth2_session().finish(&clock, parent).await.unwrap();
+
+        for session_id in [child.id, grandchild.id] {
+            let session = repo
+                .oauth2_session()
+                .lookup(session_id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(session.is_finished());
+        }
+
+        let session = repo
+            .oauth2_session()
+            .lookup(unrelated.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.is_valid());
+
+        // Same when finishing sessions in bulk
+        let parent = repo
+            .oauth2_session()
+            .add_from_client_credentials(&mut rng, &clock, &client, scope.clone())
+            .await
+            .unwrap();
+        let child = repo
+            .oauth2_session()
+            .add_from_token_exchange(&mut rng, &clock, &other_client, &parent, scope.clone())
+            .await
+            .unwrap();
+
+        let affected = repo
+            .oauth2_session()
+            .finish_bulk(
+                &clock,
+                OAuth2SessionFilter::new().for_client(&client).active_only(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(affected, 1);
+
+        let session = repo
+            .oauth2_session()
+            .lookup(child.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.is_finished());
+
+        let session = repo
+            .oauth2_session()
+            .lookup(unrelated.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(session.is_valid());
+    }
+
     #[sqlx::test(migrator = "crate::MIGRATOR")]
     async fn test_device_code_grant_repository(pool: PgPool) {
         let mut rng = ChaChaRng::seed_from_u64(42);
@@ -750,6 +1099,12 @@ mod tests {
                 None,
                 None,
                 Some("https://example.com/login".parse().unwrap()),
+                Vec::new(),
+                None,
+                false,
+                false,
+                false,
+                None,
             )
             .await
             .unwrap();
@@ -944,4 +1299,145 @@ mod tests {
             .await;
         assert!(res.is_err());
     }
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_pushed_authorization_request_repository(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        // Provision a client
+        let client = repo
+            .oauth2_client()
+            .add(
+                &mut rng,
+                &clock,
+                vec!["https://example.com/redirect".parse().unwrap()],
+                None,
+                None,
+                None,
+                vec![GrantType::AuthorizationCode],
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Vec::new(),
+                None,
+                false,
+                true,
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(client.require_pushed_authorization_requests);
+        assert!(client.require_signed_request_object);
+
+        // Find a non-existing request
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .find_by_request_uri("urn:ietf:params:oauth:request_uri:unknown")
+            .await
+            .unwrap();
+        assert!(request.is_none());
+
+        let parameters = BTreeMap::from([
+            ("client_id".to_owned(), client.client_id.clone()),
+            ("response_type".to_owned(), "code".to_owned()),
+            ("scope".to_owned(), "openid".to_owned()),
+        ]);
+
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .add(
+                &mut rng,
+                &clock,
+                &client,
+                "urn:ietf:params:oauth:request_uri:abcdef".to_owned(),
+                parameters.clone(),
+            )
+            .await
+            .unwrap();
+        assert!(request.is_valid(clock.now()));
+
+        // Find it by its request_uri
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .find_by_request_uri("urn:ietf:params:oauth:request_uri:abcdef")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(request.client_id, client.id);
+        assert_eq!(request.parameters, parameters);
+
+        // Consume it
+        let id = request.id;
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .consume(&clock, request)
+            .await
+            .unwrap();
+        assert!(!request.is_valid(clock.now()));
+
+        // It can't be consumed twice
+        let res = repo
+            .oauth2_pushed_authorization_request()
+            .consume(&clock, request)
+            .await;
+        assert!(res.is_err());
+
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .lookup(id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(request.consumed_at.is_some());
+
+        // Add another one, which will expire
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .add(
+                &mut rng,
+                &clock,
+                &client,
+                "urn:ietf:params:oauth:request_uri:ghijkl".to_owned(),
+                parameters,
+            )
+            .await
+            .unwrap();
+
+        // Nothing to clean up yet
+        let count = repo
+            .oauth2_pushed_authorization_request()
+            .cleanup_expired(&clock)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        clock.advance(Duration::try_minutes(2).unwrap());
+        assert!(!request.is_valid(clock.now()));
+
+        let count = repo
+            .oauth2_pushed_authorization_request()
+            .cleanup_expired(&clock)
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        let request = repo
+            .oauth2_pushed_authorization_request()
+            .lookup(request.id)
+            .await
+            .unwrap();
+        assert!(request.is_none());
+    }
 }
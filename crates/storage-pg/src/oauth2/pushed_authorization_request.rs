@@ -0,0 +1,249 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{Client, Clock, PushedAuthorizationRequest};
+use mas_storage::oauth2::OAuth2PushedAuthorizationRequestRepository;
+use rand::RngCore;
+use sqlx::{PgConnection, types::Json};
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{DatabaseError, ExecuteExt};
+
+/// An implementation of [`OAuth2PushedAuthorizationRequestRepository`] for a
+/// PostgreSQL connection
+pub struct PgOAuth2PushedAuthorizationRequestRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgOAuth2PushedAuthorizationRequestRepository<'c> {
+    /// Create a new [`PgOAuth2PushedAuthorizationRequestRepository`] from an
+    /// active PostgreSQL connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+struct PushedAuthorizationRequestLookup {
+    oauth2_pushed_authorization_request_id: Uuid,
+    oauth2_client_id: Uuid,
+    request_uri: String,
+    parameters: Json<BTreeMap<String, String>>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    consumed_at: Option<DateTime<Utc>>,
+}
+
+impl From<PushedAuthorizationRequestLookup> for PushedAuthorizationRequest {
+    fn from(value: PushedAuthorizationRequestLookup) -> Self {
+        PushedAuthorizationRequest {
+            id: value.oauth2_pushed_authorization_request_id.into(),
+            client_id: value.oauth2_client_id.into(),
+            request_uri: value.request_uri,
+            parameters: value.parameters.0,
+            created_at: value.created_at,
+            expires_at: value.expires_at,
+            consumed_at: value.consumed_at,
+        }
+    }
+}
+
+#[async_trait]
+impl OAuth2PushedAuthorizationRequestRepository
+    for PgOAuth2PushedAuthorizationRequestRepository<'_>
+{
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.oauth2_pushed_authorization_request.add",
+        skip_all,
+        fields(
+            db.query.text,
+            oauth2_pushed_authorization_request.id,
+            oauth2_client.id = %client.id,
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        request_uri: String,
+        parameters: BTreeMap<String, String>,
+    ) -> Result<PushedAuthorizationRequest, Self::Error> {
+        let created_at = clock.now();
+        let expires_at = created_at + PushedAuthorizationRequest::VALIDITY;
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record(
+            "oauth2_pushed_authorization_request.id",
+            tracing::field::display(id),
+        );
+
+        sqlx::query!(
+            r#"
+                INSERT INTO oauth2_pushed_authorization_requests
+                    ( oauth2_pushed_authorization_request_id
+                    , oauth2_client_id
+                    , request_uri
+                    , parameters
+                    , created_at
+                    , expires_at
+                    )
+                VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            Uuid::from(id),
+            Uuid::from(client.id),
+            &request_uri,
+            Json(&parameters) as _,
+            created_at,
+            expires_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(PushedAuthorizationRequest {
+            id,
+            client_id: client.id,
+            request_uri,
+            parameters,
+            created_at,
+            expires_at,
+            consumed_at: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_pushed_authorization_request.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            oauth2_pushed_authorization_request.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(
+        &mut self,
+        id: Ulid,
+    ) -> Result<Option<PushedAuthorizationRequest>, Self::Error> {
+        let res = sqlx::query_as!(
+            PushedAuthorizationRequestLookup,
+            r#"
+                SELECT oauth2_pushed_authorization_request_id
+                     , oauth2_client_id
+                     , request_uri
+                     , parameters AS "parameters: Json<BTreeMap<String, String>>"
+                     , created_at
+                     , expires_at
+                     , consumed_at
+                FROM oauth2_pushed_authorization_requests
+                WHERE oauth2_pushed_authorization_request_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(Into::into))
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_pushed_authorization_request.find_by_request_uri",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn find_by_request_uri(
+        &mut self,
+        request_uri: &str,
+    ) -> Result<Option<PushedAuthorizationRequest>, Self::Error> {
+        let res = sqlx::query_as!(
+            PushedAuthorizationRequestLookup,
+            r#"
+                SELECT oauth2_pushed_authorization_request_id
+                     , oauth2_client_id
+                     , request_uri
+                     , parameters AS "parameters: Json<BTreeMap<String, String>>"
+                     , created_at
+                     , expires_at
+                     , consumed_at
+                FROM oauth2_pushed_authorization_requests
+                WHERE request_uri = $1
+            "#,
+            request_uri,
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(Into::into))
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_pushed_authorization_request.consume",
+        skip_all,
+        fields(
+            db.query.text,
+            oauth2_pushed_authorization_request.id = %request.id,
+        ),
+        err,
+    )]
+    async fn consume(
+        &mut self,
+        clock: &dyn Clock,
+        mut request: PushedAuthorizationRequest,
+    ) -> Result<PushedAuthorizationRequest, Self::Error> {
+        let consumed_at = clock.now();
+        let res = sqlx::query!(
+            r#"
+                UPDATE oauth2_pushed_authorization_requests
+                SET consumed_at = $2
+                WHERE oauth2_pushed_authorization_request_id = $1
+                  AND consumed_at IS NULL
+            "#,
+            Uuid::from(request.id),
+            consumed_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        request.consumed_at = Some(consumed_at);
+        Ok(request)
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_pushed_authorization_request.cleanup_expired",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn cleanup_expired(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM oauth2_pushed_authorization_requests
+                WHERE expires_at < $1
+            "#,
+            clock.now(),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
@@ -38,6 +38,7 @@ struct OAuth2RefreshTokenLookup {
     oauth2_access_token_id: Option<Uuid>,
     oauth2_session_id: Uuid,
     next_oauth2_refresh_token_id: Option<Uuid>,
+    dpop_jkt: Option<String>,
 }
 
 impl TryFrom<OAuth2RefreshTokenLookup> for RefreshToken {
@@ -74,6 +75,7 @@ impl TryFrom<OAuth2RefreshTokenLookup> for RefreshToken {
             refresh_token: value.refresh_token,
             created_at: value.created_at,
             access_token_id: value.oauth2_access_token_id.map(Ulid::from),
+            dpop_jkt: value.dpop_jkt,
         })
     }
 }
@@ -103,6 +105,7 @@ impl OAuth2RefreshTokenRepository for PgOAuth2RefreshTokenRepository<'_> {
                      , oauth2_access_token_id
                      , oauth2_session_id
                      , next_oauth2_refresh_token_id
+                     , dpop_jkt
                 FROM oauth2_refresh_tokens
 
                 WHERE oauth2_refresh_token_id = $1
@@ -141,6 +144,7 @@ impl OAuth2RefreshTokenRepository for PgOAuth2RefreshTokenRepository<'_> {
                      , oauth2_access_token_id
                      , oauth2_session_id
                      , next_oauth2_refresh_token_id
+                     , dpop_jkt
                 FROM oauth2_refresh_tokens
 
                 WHERE refresh_token = $1
@@ -183,15 +187,16 @@ impl OAuth2RefreshTokenRepository for PgOAuth2RefreshTokenRepository<'_> {
             r#"
                 INSERT INTO oauth2_refresh_tokens
                     (oauth2_refresh_token_id, oauth2_session_id, oauth2_access_token_id,
-                     refresh_token, created_at)
+                     refresh_token, created_at, dpop_jkt)
                 VALUES
-                    ($1, $2, $3, $4, $5)
+                    ($1, $2, $3, $4, $5, $6)
             "#,
             Uuid::from(id),
             Uuid::from(session.id),
             Uuid::from(access_token.id),
             refresh_token,
             created_at,
+            access_token.dpop_jkt.as_deref(),
         )
         .traced()
         .execute(&mut *self.conn)
@@ -204,6 +209,8 @@ impl OAuth2RefreshTokenRepository for PgOAuth2RefreshTokenRepository<'_> {
             refresh_token,
             access_token_id: Some(access_token.id),
             created_at,
+            // Refresh tokens are bound to the same key as their access token
+            dpop_jkt: access_token.dpop_jkt.clone(),
         })
     }
 
@@ -8,7 +8,7 @@ use std::net::IpAddr;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mas_data_model::{BrowserSession, Client, Clock, Session, SessionState, User};
+use mas_data_model::{BrowserSession, Client, Clock, Impersonation, Session, SessionState, User};
 use mas_storage::{
     Page, Pagination,
     oauth2::{OAuth2SessionFilter, OAuth2SessionRepository},
@@ -60,6 +60,10 @@ struct OAuthSessionLookup {
     last_active_at: Option<DateTime<Utc>>,
     last_active_ip: Option<IpAddr>,
     human_name: Option<String>,
+    parent_session_id: Option<Uuid>,
+    impersonator_user_id: Option<Uuid>,
+    impersonator_client_id: Option<Uuid>,
+    impersonation_expires_at: Option<DateTime<Utc>>,
 }
 
 impl Node<Ulid> for OAuthSessionLookup {
@@ -102,6 +106,14 @@ impl TryFrom<OAuthSessionLookup> for Session {
             last_active_at: value.last_active_at,
             last_active_ip: value.last_active_ip,
             human_name: value.human_name,
+            parent_session_id: value.parent_session_id.map(Ulid::from),
+            impersonation: value
+                .impersonation_expires_at
+                .map(|expires_at| Impersonation {
+                    impersonator_user_id: value.impersonator_user_id.map(Ulid::from),
+                    impersonator_client_id: value.impersonator_client_id.map(Ulid::from),
+                    expires_at,
+                }),
         })
     }
 }
@@ -227,6 +239,10 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
                      , last_active_at
                      , last_active_ip as "last_active_ip: IpAddr"
                      , human_name
+                     , parent_session_id
+                     , impersonator_user_id
+                     , impersonator_client_id
+                     , impersonation_expires_at
                 FROM oauth2_sessions
 
                 WHERE oauth2_session_id = $1
@@ -303,6 +319,147 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
             last_active_at: None,
             last_active_ip: None,
             human_name: None,
+            parent_session_id: None,
+            impersonation: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_session.add_from_token_exchange",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+            %parent.id,
+            session.id,
+            session.scope = %scope,
+        ),
+        err,
+    )]
+    async fn add_from_token_exchange(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        parent: &Session,
+        scope: Scope,
+    ) -> Result<Session, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("session.id", tracing::field::display(id));
+
+        let scope_list: Vec<String> = scope.iter().map(|s| s.as_str().to_owned()).collect();
+
+        sqlx::query!(
+            r#"
+                INSERT INTO oauth2_sessions
+                    ( oauth2_session_id
+                    , user_id
+                    , user_session_id
+                    , oauth2_client_id
+                    , scope_list
+                    , created_at
+                    , parent_session_id
+                    )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            Uuid::from(id),
+            parent.user_id.map(Uuid::from),
+            parent.user_session_id.map(Uuid::from),
+            Uuid::from(client.id),
+            &scope_list,
+            created_at,
+            Uuid::from(parent.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(Session {
+            id,
+            state: SessionState::Valid,
+            created_at,
+            user_id: parent.user_id,
+            user_session_id: parent.user_session_id,
+            client_id: client.id,
+            scope,
+            user_agent: None,
+            last_active_at: None,
+            last_active_ip: None,
+            human_name: None,
+            parent_session_id: Some(parent.id),
+            impersonation: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.oauth2_session.add_impersonation",
+        skip_all,
+        fields(
+            db.query.text,
+            %client.id,
+            %user.id,
+            session.id,
+            session.scope = %scope,
+        ),
+        err,
+    )]
+    async fn add_impersonation(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        user: &User,
+        scope: Scope,
+        impersonation: Impersonation,
+    ) -> Result<Session, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("session.id", tracing::field::display(id));
+
+        let scope_list: Vec<String> = scope.iter().map(|s| s.as_str().to_owned()).collect();
+
+        sqlx::query!(
+            r#"
+                INSERT INTO oauth2_sessions
+                    ( oauth2_session_id
+                    , user_id
+                    , oauth2_client_id
+                    , scope_list
+                    , created_at
+                    , impersonator_user_id
+                    , impersonator_client_id
+                    , impersonation_expires_at
+                    )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            Uuid::from(client.id),
+            &scope_list,
+            created_at,
+            impersonation.impersonator_user_id.map(Uuid::from),
+            impersonation.impersonator_client_id.map(Uuid::from),
+            impersonation.expires_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(Session {
+            id,
+            state: SessionState::Valid,
+            created_at,
+            user_id: Some(user.id),
+            user_session_id: None,
+            client_id: client.id,
+            scope,
+            user_agent: None,
+            last_active_at: None,
+            last_active_ip: None,
+            human_name: None,
+            parent_session_id: None,
+            impersonation: Some(impersonation),
         })
     }
 
@@ -331,6 +488,32 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
             .execute(&mut *self.conn)
             .await?;
 
+        // Finish the sessions obtained by exchanging a token of a finished
+        // session, directly or not
+        sqlx::query!(
+            r#"
+                WITH RECURSIVE descendants AS (
+                    SELECT s.oauth2_session_id
+                      FROM oauth2_sessions s
+                      JOIN oauth2_sessions p ON s.parent_session_id = p.oauth2_session_id
+                     WHERE p.finished_at IS NOT NULL
+                       AND s.finished_at IS NULL
+                    UNION
+                    SELECT s.oauth2_session_id
+                      FROM oauth2_sessions s
+                      JOIN descendants d ON s.parent_session_id = d.oauth2_session_id
+                )
+                UPDATE oauth2_sessions
+                SET finished_at = $1
+                WHERE oauth2_session_id IN (SELECT oauth2_session_id FROM descendants)
+                  AND finished_at IS NULL
+            "#,
+            finished_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
         Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
     }
 
@@ -366,6 +549,31 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
 
         DatabaseError::ensure_affected_rows(&res, 1)?;
 
+        // Sessions obtained by exchanging a token of this session, directly or
+        // not, can't outlive it
+        sqlx::query!(
+            r#"
+                WITH RECURSIVE descendants AS (
+                    SELECT oauth2_session_id
+                      FROM oauth2_sessions
+                     WHERE parent_session_id = $1
+                    UNION
+                    SELECT s.oauth2_session_id
+                      FROM oauth2_sessions s
+                      JOIN descendants d ON s.parent_session_id = d.oauth2_session_id
+                )
+                UPDATE oauth2_sessions
+                SET finished_at = $2
+                WHERE oauth2_session_id IN (SELECT oauth2_session_id FROM descendants)
+                  AND finished_at IS NULL
+            "#,
+            Uuid::from(session.id),
+            finished_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
         session
             .finish(finished_at)
             .map_err(DatabaseError::to_invalid_operation)
@@ -429,6 +637,25 @@ impl OAuth2SessionRepository for PgOAuth2SessionRepository<'_> {
                 Expr::col((OAuth2Sessions::Table, OAuth2Sessions::HumanName)),
                 OAuthSessionLookupIden::HumanName,
             )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::ParentSessionId)),
+                OAuthSessionLookupIden::ParentSessionId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::ImpersonatorUserId)),
+                OAuthSessionLookupIden::ImpersonatorUserId,
+            )
+            .expr_as(
+                Expr::col((OAuth2Sessions::Table, OAuth2Sessions::ImpersonatorClientId)),
+                OAuthSessionLookupIden::ImpersonatorClientId,
+            )
+            .expr_as(
+                Expr::col((
+                    OAuth2Sessions::Table,
+                    OAuth2Sessions::ImpersonationExpiresAt,
+                )),
+                OAuthSessionLookupIden::ImpersonationExpiresAt,
+            )
             .from(OAuth2Sessions::Table)
             .apply_filter(filter)
             .generate_pagination(
@@ -0,0 +1,186 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A module containing the PostgreSQL implementation of the shared rate
+//! limiters state.
+
+use std::num::NonZeroU32;
+
+use async_trait::async_trait;
+use chrono::Duration;
+use mas_data_model::Clock;
+use mas_storage::rate_limit::RateLimitRepository;
+use sqlx::PgConnection;
+
+use crate::{DatabaseError, ExecuteExt};
+
+/// An implementation of [`RateLimitRepository`] for a PostgreSQL connection.
+pub struct PgRateLimitRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgRateLimitRepository<'c> {
+    /// Create a new [`PgRateLimitRepository`] from an active PostgreSQL
+    /// connection.
+    #[must_use]
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+#[async_trait]
+impl RateLimitRepository for PgRateLimitRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.rate_limit.check",
+        skip_all,
+        fields(
+            db.query.text,
+            rate_limit.bucket = bucket,
+        ),
+        err,
+    )]
+    async fn check(
+        &mut self,
+        clock: &dyn Clock,
+        bucket: &str,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> Result<bool, Self::Error> {
+        let now = clock.now();
+        // How far in the future the theoretical arrival time can be for the
+        // action to be allowed
+        let tolerance = interval * i32::try_from(burst.get() - 1).unwrap_or(i32::MAX);
+        let interval = interval.as_seconds_f64();
+        let tolerance = tolerance.as_seconds_f64();
+
+        // This only updates the row if the action is allowed, in which case it
+        // gets returned
+        let res = sqlx::query_scalar!(
+            r#"
+                INSERT INTO rate_limits (bucket, key, tat)
+                VALUES ($1, $2, $3::timestamptz + make_interval(secs => $4))
+                ON CONFLICT (bucket, key) DO UPDATE
+                SET tat = GREATEST(rate_limits.tat, $3) + make_interval(secs => $4)
+                WHERE GREATEST(rate_limits.tat, $3) <= $3 + make_interval(secs => $5)
+                RETURNING tat
+            "#,
+            bucket,
+            key,
+            now,
+            interval,
+            tolerance,
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.is_some())
+    }
+
+    #[tracing::instrument(
+        name = "db.rate_limit.cleanup",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn cleanup(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM rate_limits
+                WHERE tat < $1
+            "#,
+            clock.now(),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use chrono::Duration;
+    use mas_data_model::clock::MockClock;
+    use mas_storage::rate_limit::RateLimitRepository;
+    use sqlx::PgPool;
+
+    use crate::rate_limit::PgRateLimitRepository;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_rate_limit(pool: PgPool) {
+        let clock = MockClock::default();
+        let mut conn = pool.acquire().await.unwrap();
+        let mut repo = PgRateLimitRepository::new(&mut conn);
+
+        let interval = Duration::try_seconds(10).unwrap();
+        let burst = NonZeroU32::new(3).unwrap();
+
+        // The first three actions are allowed, the fourth one is not
+        for _ in 0..3 {
+            assert!(
+                repo.check(&clock, "login", "alice", interval, burst)
+                    .await
+                    .unwrap()
+            );
+        }
+        assert!(
+            !repo
+                .check(&clock, "login", "alice", interval, burst)
+                .await
+                .unwrap()
+        );
+
+        // Other keys and buckets are not affected
+        assert!(
+            repo.check(&clock, "login", "bob", interval, burst)
+                .await
+                .unwrap()
+        );
+        assert!(
+            repo.check(&clock, "registration", "alice", interval, burst)
+                .await
+                .unwrap()
+        );
+
+        // After one interval, one more action is allowed
+        clock.advance(interval);
+        assert!(
+            repo.check(&clock, "login", "alice", interval, burst)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !repo
+                .check(&clock, "login", "alice", interval, burst)
+                .await
+                .unwrap()
+        );
+
+        // Nothing is fully replenished yet
+        assert_eq!(repo.cleanup(&clock).await.unwrap(), 0);
+
+        // Once the keys are fully replenished, they get cleaned up
+        clock.advance(Duration::try_minutes(1).unwrap());
+        assert_eq!(repo.cleanup(&clock).await.unwrap(), 3);
+
+        // And the full burst is allowed again
+        for _ in 0..3 {
+            assert!(
+                repo.check(&clock, "login", "alice", interval, burst)
+                    .await
+                    .unwrap()
+            );
+        }
+    }
+}
@@ -12,26 +12,31 @@ use mas_storage::{
     BoxRepository, BoxRepositoryFactory, MapErr, Repository, RepositoryAccess, RepositoryError,
     RepositoryFactory, RepositoryTransaction,
     app_session::AppSessionRepository,
+    audit_event::AuditEventRepository,
     compat::{
         CompatAccessTokenRepository, CompatRefreshTokenRepository, CompatSessionRepository,
         CompatSsoLoginRepository,
     },
     oauth2::{
         OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
-        OAuth2DeviceCodeGrantRepository, OAuth2RefreshTokenRepository, OAuth2SessionRepository,
+        OAuth2DeviceCodeGrantRepository, OAuth2PushedAuthorizationRequestRepository,
+        OAuth2RefreshTokenRepository, OAuth2SessionRepository,
     },
     personal::PersonalSessionRepository,
     policy_data::PolicyDataRepository,
     queue::{QueueJobRepository, QueueScheduleRepository, QueueWorkerRepository},
+    rate_limit::RateLimitRepository,
     upstream_oauth2::{
         UpstreamOAuthLinkRepository, UpstreamOAuthProviderRepository,
         UpstreamOAuthSessionRepository,
     },
     user::{
-        BrowserSessionRepository, UserEmailRepository, UserPasswordRepository,
-        UserRecoveryRepository, UserRegistrationRepository, UserRegistrationTokenRepository,
-        UserRepository, UserTermsRepository,
+        BrowserSessionRepository, UserEmailRepository, UserExportRepository,
+        UserLdapLinkRepository, UserPasskeyRepository, UserPasswordRepository, UserRecoveryRepository,
+        UserRegistrationRepository, UserRegistrationTokenRepository, UserRepository,
+        UserTermsRepository, UserTotpRepository,
     },
+    webhook::WebhookDeliveryRepository,
 };
 use sqlx::{PgConnection, PgPool, Postgres, Transaction};
 use tracing::Instrument;
@@ -39,6 +44,7 @@ use tracing::Instrument;
 use crate::{
     DatabaseError,
     app_session::PgAppSessionRepository,
+    audit_event::PgAuditEventRepository,
     compat::{
         PgCompatAccessTokenRepository, PgCompatRefreshTokenRepository, PgCompatSessionRepository,
         PgCompatSsoLoginRepository,
@@ -46,7 +52,8 @@ use crate::{
     oauth2::{
         PgOAuth2AccessTokenRepository, PgOAuth2AuthorizationGrantRepository,
         PgOAuth2ClientRepository, PgOAuth2DeviceCodeGrantRepository,
-        PgOAuth2RefreshTokenRepository, PgOAuth2SessionRepository,
+        PgOAuth2PushedAuthorizationRequestRepository, PgOAuth2RefreshTokenRepository,
+        PgOAuth2SessionRepository,
     },
     personal::{PgPersonalAccessTokenRepository, PgPersonalSessionRepository},
     policy_data::PgPolicyDataRepository,
@@ -54,16 +61,19 @@ use crate::{
         job::PgQueueJobRepository, schedule::PgQueueScheduleRepository,
         worker::PgQueueWorkerRepository,
     },
+    rate_limit::PgRateLimitRepository,
     telemetry::DB_CLIENT_CONNECTIONS_CREATE_TIME_HISTOGRAM,
     upstream_oauth2::{
         PgUpstreamOAuthLinkRepository, PgUpstreamOAuthProviderRepository,
         PgUpstreamOAuthSessionRepository,
     },
     user::{
-        PgBrowserSessionRepository, PgUserEmailRepository, PgUserPasswordRepository,
-        PgUserRecoveryRepository, PgUserRegistrationRepository, PgUserRegistrationTokenRepository,
-        PgUserRepository, PgUserTermsRepository,
+        PgBrowserSessionRepository, PgUserEmailRepository, PgUserExportRepository,
+        PgUserLdapLinkRepository, PgUserPasskeyRepository, PgUserPasswordRepository, PgUserRecoveryRepository,
+        PgUserRegistrationRepository, PgUserRegistrationTokenRepository, PgUserRepository,
+        PgUserTermsRepository, PgUserTotpRepository,
     },
+    webhook::PgWebhookDeliveryRepository,
 };
 
 /// An implementation of the [`RepositoryFactory`] trait backed by a PostgreSQL
@@ -236,6 +246,20 @@ where
         Box::new(PgUserPasswordRepository::new(self.conn.as_mut()))
     }
 
+    fn user_passkey<'c>(&'c mut self) -> Box<dyn UserPasskeyRepository<Error = Self::Error> + 'c> {
+        Box::new(PgUserPasskeyRepository::new(self.conn.as_mut()))
+    }
+
+    fn user_totp<'c>(&'c mut self) -> Box<dyn UserTotpRepository<Error = Self::Error> + 'c> {
+        Box::new(PgUserTotpRepository::new(self.conn.as_mut()))
+    }
+
+    fn user_ldap_link<'c>(
+        &'c mut self,
+    ) -> Box<dyn UserLdapLinkRepository<Error = Self::Error> + 'c> {
+        Box::new(PgUserLdapLinkRepository::new(self.conn.as_mut()))
+    }
+
     fn user_recovery<'c>(
         &'c mut self,
     ) -> Box<dyn UserRecoveryRepository<Error = Self::Error> + 'c> {
@@ -246,6 +270,10 @@ where
         Box::new(PgUserTermsRepository::new(self.conn.as_mut()))
     }
 
+    fn user_export<'c>(&'c mut self) -> Box<dyn UserExportRepository<Error = Self::Error> + 'c> {
+        Box::new(PgUserExportRepository::new(self.conn.as_mut()))
+    }
+
     fn user_registration<'c>(
         &'c mut self,
     ) -> Box<dyn UserRegistrationRepository<Error = Self::Error> + 'c> {
@@ -306,6 +334,14 @@ where
         Box::new(PgOAuth2DeviceCodeGrantRepository::new(self.conn.as_mut()))
     }
 
+    fn oauth2_pushed_authorization_request<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c> {
+        Box::new(PgOAuth2PushedAuthorizationRequestRepository::new(
+            self.conn.as_mut(),
+        ))
+    }
+
     fn compat_session<'c>(
         &'c mut self,
     ) -> Box<dyn CompatSessionRepository<Error = Self::Error> + 'c> {
@@ -360,4 +396,18 @@ where
     fn policy_data<'c>(&'c mut self) -> Box<dyn PolicyDataRepository<Error = Self::Error> + 'c> {
         Box::new(PgPolicyDataRepository::new(self.conn.as_mut()))
     }
+
+    fn audit_event<'c>(&'c mut self) -> Box<dyn AuditEventRepository<Error = Self::Error> + 'c> {
+        Box::new(PgAuditEventRepository::new(self.conn.as_mut()))
+    }
+
+    fn webhook_delivery<'c>(
+        &'c mut self,
+    ) -> Box<dyn WebhookDeliveryRepository<Error = Self::Error> + 'c> {
+        Box::new(PgWebhookDeliveryRepository::new(self.conn.as_mut()))
+    }
+
+    fn rate_limit<'c>(&'c mut self) -> Box<dyn RateLimitRepository<Error = Self::Error> + 'c> {
+        Box::new(PgRateLimitRepository::new(self.conn.as_mut()))
+    }
 }
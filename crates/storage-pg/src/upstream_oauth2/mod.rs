@@ -20,7 +20,7 @@ pub use self::{
 mod tests {
     use chrono::Duration;
     use mas_data_model::{
-        UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderOnBackchannelLogout,
+        Clock, UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderOnBackchannelLogout,
         UpstreamOAuthProviderTokenAuthMethod, clock::MockClock,
     };
     use mas_iana::jose::JsonWebSignatureAlg;
@@ -79,6 +79,7 @@ mod tests {
                     forward_login_hint: false,
                     ui_order: 0,
                     on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                 },
             )
             .await
@@ -351,6 +352,7 @@ mod tests {
                         forward_login_hint: false,
                         ui_order: 0,
                         on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                        saml: None,
                     },
                 )
                 .await
@@ -449,6 +451,107 @@ mod tests {
         );
     }
 
+    /// Test updating providers, and the distinction between providers defined
+    /// in the configuration and the ones created at runtime
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_provider_update_and_static(pool: PgPool) {
+        let mut rng = rand_chacha::ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap();
+
+        let params = || UpstreamOAuthProviderParams {
+            issuer: Some("https://example.com/".to_owned()),
+            human_name: None,
+            brand_name: None,
+            scope: Scope::from_iter([OPENID]),
+            token_endpoint_auth_method: UpstreamOAuthProviderTokenAuthMethod::None,
+            id_token_signed_response_alg: JsonWebSignatureAlg::Rs256,
+            fetch_userinfo: false,
+            userinfo_signed_response_alg: None,
+            token_endpoint_signing_alg: None,
+            client_id: "client-id".to_owned(),
+            encrypted_client_secret: None,
+            claims_imports: UpstreamOAuthProviderClaimsImports::default(),
+            token_endpoint_override: None,
+            authorization_endpoint_override: None,
+            userinfo_endpoint_override: None,
+            jwks_uri_override: None,
+            discovery_mode: mas_data_model::UpstreamOAuthProviderDiscoveryMode::Oidc,
+            pkce_mode: mas_data_model::UpstreamOAuthProviderPkceMode::Auto,
+            response_mode: None,
+            additional_authorization_parameters: Vec::new(),
+            forward_login_hint: false,
+            ui_order: 0,
+            on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+            saml: None,
+        };
+
+        // Providers added at runtime are not static
+        let dynamic = repo
+            .upstream_oauth_provider()
+            .add(&mut rng, &clock, params())
+            .await
+            .unwrap();
+        assert!(!dynamic.is_static);
+
+        // Providers upserted from the configuration are static
+        let static_id = ulid::Ulid::from_datetime_with_source(clock.now().into(), &mut rng);
+        let static_provider = repo
+            .upstream_oauth_provider()
+            .upsert(&clock, static_id, params())
+            .await
+            .unwrap();
+        assert!(static_provider.is_static);
+
+        let filter = UpstreamOAuthProviderFilter::new();
+        assert_eq!(
+            repo.upstream_oauth_provider()
+                .count(filter.static_only())
+                .await
+                .unwrap(),
+            1
+        );
+        let page = repo
+            .upstream_oauth_provider()
+            .list(filter.dynamic_only(), Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 1);
+        assert_eq!(page.edges[0].node.id, dynamic.id);
+
+        // Update the dynamic provider, after disabling it
+        let dynamic = repo
+            .upstream_oauth_provider()
+            .disable(&clock, dynamic)
+            .await
+            .unwrap();
+        let mut new_params = params();
+        new_params.human_name = Some("Example".to_owned());
+        new_params.additional_authorization_parameters =
+            vec![("prompt".to_owned(), "consent".to_owned())];
+        let dynamic = repo
+            .upstream_oauth_provider()
+            .update(dynamic, new_params)
+            .await
+            .unwrap();
+        assert_eq!(dynamic.human_name.as_deref(), Some("Example"));
+
+        // It stays disabled and dynamic
+        let dynamic = repo
+            .upstream_oauth_provider()
+            .lookup(dynamic.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!dynamic.enabled());
+        assert!(!dynamic.is_static);
+        assert_eq!(dynamic.human_name.as_deref(), Some("Example"));
+        assert_eq!(
+            dynamic.additional_authorization_parameters,
+            vec![("prompt".to_owned(), "consent".to_owned())]
+        );
+    }
+
     /// Test that the pagination works as expected in the upstream OAuth
     /// session repository
     #[sqlx::test(migrator = "crate::MIGRATOR")]
@@ -489,6 +592,7 @@ mod tests {
                     forward_login_hint: false,
                     ui_order: 0,
                     on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                    saml: None,
                 },
             )
             .await
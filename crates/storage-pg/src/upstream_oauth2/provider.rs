@@ -6,7 +6,10 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mas_data_model::{Clock, UpstreamOAuthProvider, UpstreamOAuthProviderClaimsImports};
+use mas_data_model::{
+    Clock, UpstreamOAuthProvider, UpstreamOAuthProviderClaimsImports,
+    UpstreamOAuthProviderSamlSettings,
+};
 use mas_storage::{
     Page, Pagination,
     pagination::Node,
@@ -73,6 +76,8 @@ struct ProviderLookup {
     additional_parameters: Option<Json<Vec<(String, String)>>>,
     forward_login_hint: bool,
     on_backchannel_logout: String,
+    is_static: bool,
+    saml: Option<Json<UpstreamOAuthProviderSamlSettings>>,
 }
 
 impl Node<Ulid> for ProviderLookup {
@@ -210,6 +215,7 @@ impl TryFrom<ProviderLookup> for UpstreamOAuthProvider {
 
         Ok(UpstreamOAuthProvider {
             id,
+            is_static: value.is_static,
             issuer: value.issuer,
             human_name: value.human_name,
             brand_name: value.brand_name,
@@ -234,20 +240,29 @@ impl TryFrom<ProviderLookup> for UpstreamOAuthProvider {
             additional_authorization_parameters,
             forward_login_hint: value.forward_login_hint,
             on_backchannel_logout,
+            saml: value.saml.map(|Json(x)| x),
         })
     }
 }
 
 impl Filter for UpstreamOAuthProviderFilter<'_> {
     fn generate_condition(&self, _has_joins: bool) -> impl sea_query::IntoCondition {
-        sea_query::Condition::all().add_option(self.enabled().map(|enabled| {
-            Expr::col((
-                UpstreamOAuthProviders::Table,
-                UpstreamOAuthProviders::DisabledAt,
-            ))
-            .is_null()
-            .eq(enabled)
-        }))
+        sea_query::Condition::all()
+            .add_option(self.enabled().map(|enabled| {
+                Expr::col((
+                    UpstreamOAuthProviders::Table,
+                    UpstreamOAuthProviders::DisabledAt,
+                ))
+                .is_null()
+                .eq(enabled)
+            }))
+            .add_option(self.is_static().map(|is_static| {
+                Expr::col((
+                    UpstreamOAuthProviders::Table,
+                    UpstreamOAuthProviders::IsStatic,
+                ))
+                .eq(is_static)
+            }))
     }
 }
 
@@ -293,7 +308,9 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
                     response_mode,
                     additional_parameters as "additional_parameters: Json<Vec<(String, String)>>",
                     forward_login_hint,
-                    on_backchannel_logout
+                    on_backchannel_logout,
+                    is_static,
+                    saml as "saml: Json<UpstreamOAuthProviderSamlSettings>"
                 FROM upstream_oauth_providers
                 WHERE upstream_oauth_provider_id = $1
             "#,
@@ -355,12 +372,16 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
                 discovery_mode,
                 pkce_mode,
                 response_mode,
+                additional_parameters,
                 forward_login_hint,
+                ui_order,
                 on_backchannel_logout,
-                created_at
+                created_at,
+                saml,
+                is_static
             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11,
                       $12, $13, $14, $15, $16, $17, $18, $19, $20,
-                      $21, $22, $23)
+                      $21, $22, $23, $24, $25, $26, FALSE)
         "#,
             Uuid::from(id),
             params.issuer.as_deref(),
@@ -397,9 +418,12 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
             params.discovery_mode.as_str(),
             params.pkce_mode.as_str(),
             params.response_mode.as_ref().map(ToString::to_string),
+            Json(&params.additional_authorization_parameters) as _,
             params.forward_login_hint,
+            params.ui_order,
             params.on_backchannel_logout.as_str(),
             created_at,
+            params.saml.as_ref().map(Json) as _,
         )
         .traced()
         .execute(&mut *self.conn)
@@ -407,6 +431,7 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
 
         Ok(UpstreamOAuthProvider {
             id,
+            is_static: false,
             issuer: params.issuer,
             human_name: params.human_name,
             brand_name: params.brand_name,
@@ -430,6 +455,7 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
             response_mode: params.response_mode,
             additional_authorization_parameters: params.additional_authorization_parameters,
             on_backchannel_logout: params.on_backchannel_logout,
+            saml: params.saml,
             forward_login_hint: params.forward_login_hint,
         })
     }
@@ -546,10 +572,12 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
                     forward_login_hint,
                     ui_order,
                     on_backchannel_logout,
-                    created_at
+                    created_at,
+                    saml,
+                    is_static
                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10,
                           $11, $12, $13, $14, $15, $16, $17, $18, $19, $20,
-                          $21, $22, $23, $24, $25)
+                          $21, $22, $23, $24, $25, $26, TRUE)
                 ON CONFLICT (upstream_oauth_provider_id)
                     DO UPDATE
                     SET
@@ -576,7 +604,9 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
                         additional_parameters = EXCLUDED.additional_parameters,
                         forward_login_hint = EXCLUDED.forward_login_hint,
                         ui_order = EXCLUDED.ui_order,
-                        on_backchannel_logout = EXCLUDED.on_backchannel_logout
+                        on_backchannel_logout = EXCLUDED.on_backchannel_logout,
+                        saml = EXCLUDED.saml,
+                        is_static = TRUE
                 RETURNING created_at
             "#,
             Uuid::from(id),
@@ -619,6 +649,7 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
             params.ui_order,
             params.on_backchannel_logout.as_str(),
             created_at,
+            params.saml.as_ref().map(Json) as _,
         )
         .traced()
         .fetch_one(&mut *self.conn)
@@ -626,6 +657,7 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
 
         Ok(UpstreamOAuthProvider {
             id,
+            is_static: true,
             issuer: params.issuer,
             human_name: params.human_name,
             brand_name: params.brand_name,
@@ -650,6 +682,128 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
             additional_authorization_parameters: params.additional_authorization_parameters,
             forward_login_hint: params.forward_login_hint,
             on_backchannel_logout: params.on_backchannel_logout,
+            saml: params.saml,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.upstream_oauth_provider.update",
+        skip_all,
+        fields(
+            db.query.text,
+            %upstream_oauth_provider.id,
+            upstream_oauth_provider.issuer = params.issuer,
+            upstream_oauth_provider.client_id = %params.client_id,
+        ),
+        err,
+    )]
+    async fn update(
+        &mut self,
+        upstream_oauth_provider: UpstreamOAuthProvider,
+        params: UpstreamOAuthProviderParams,
+    ) -> Result<UpstreamOAuthProvider, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE upstream_oauth_providers
+                SET
+                    issuer = $2,
+                    human_name = $3,
+                    brand_name = $4,
+                    scope = $5,
+                    token_endpoint_auth_method = $6,
+                    token_endpoint_signing_alg = $7,
+                    id_token_signed_response_alg = $8,
+                    fetch_userinfo = $9,
+                    userinfo_signed_response_alg = $10,
+                    client_id = $11,
+                    encrypted_client_secret = $12,
+                    claims_imports = $13,
+                    authorization_endpoint_override = $14,
+                    token_endpoint_override = $15,
+                    userinfo_endpoint_override = $16,
+                    jwks_uri_override = $17,
+                    discovery_mode = $18,
+                    pkce_mode = $19,
+                    response_mode = $20,
+                    additional_parameters = $21,
+                    forward_login_hint = $22,
+                    ui_order = $23,
+                    on_backchannel_logout = $24,
+                    saml = $25
+                WHERE upstream_oauth_provider_id = $1
+            "#,
+            Uuid::from(upstream_oauth_provider.id),
+            params.issuer.as_deref(),
+            params.human_name.as_deref(),
+            params.brand_name.as_deref(),
+            params.scope.to_string(),
+            params.token_endpoint_auth_method.to_string(),
+            params
+                .token_endpoint_signing_alg
+                .as_ref()
+                .map(ToString::to_string),
+            params.id_token_signed_response_alg.to_string(),
+            params.fetch_userinfo,
+            params
+                .userinfo_signed_response_alg
+                .as_ref()
+                .map(ToString::to_string),
+            &params.client_id,
+            params.encrypted_client_secret.as_deref(),
+            Json(&params.claims_imports) as _,
+            params
+                .authorization_endpoint_override
+                .as_ref()
+                .map(ToString::to_string),
+            params
+                .token_endpoint_override
+                .as_ref()
+                .map(ToString::to_string),
+            params
+                .userinfo_endpoint_override
+                .as_ref()
+                .map(ToString::to_string),
+            params.jwks_uri_override.as_ref().map(ToString::to_string),
+            params.discovery_mode.as_str(),
+            params.pkce_mode.as_str(),
+            params.response_mode.as_ref().map(ToString::to_string),
+            Json(&params.additional_authorization_parameters) as _,
+            params.forward_login_hint,
+            params.ui_order,
+            params.on_backchannel_logout.as_str(),
+            params.saml.as_ref().map(Json) as _,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(UpstreamOAuthProvider {
+            issuer: params.issuer,
+            human_name: params.human_name,
+            brand_name: params.brand_name,
+            scope: params.scope,
+            client_id: params.client_id,
+            encrypted_client_secret: params.encrypted_client_secret,
+            token_endpoint_signing_alg: params.token_endpoint_signing_alg,
+            token_endpoint_auth_method: params.token_endpoint_auth_method,
+            id_token_signed_response_alg: params.id_token_signed_response_alg,
+            fetch_userinfo: params.fetch_userinfo,
+            userinfo_signed_response_alg: params.userinfo_signed_response_alg,
+            claims_imports: params.claims_imports,
+            authorization_endpoint_override: params.authorization_endpoint_override,
+            token_endpoint_override: params.token_endpoint_override,
+            userinfo_endpoint_override: params.userinfo_endpoint_override,
+            jwks_uri_override: params.jwks_uri_override,
+            discovery_mode: params.discovery_mode,
+            pkce_mode: params.pkce_mode,
+            response_mode: params.response_mode,
+            additional_authorization_parameters: params.additional_authorization_parameters,
+            forward_login_hint: params.forward_login_hint,
+            on_backchannel_logout: params.on_backchannel_logout,
+            saml: params.saml,
+            ..upstream_oauth_provider
         })
     }
 
@@ -874,6 +1028,17 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
                 )),
                 ProviderLookupIden::OnBackchannelLogout,
             )
+            .expr_as(
+                Expr::col((
+                    UpstreamOAuthProviders::Table,
+                    UpstreamOAuthProviders::IsStatic,
+                )),
+                ProviderLookupIden::IsStatic,
+            )
+            .expr_as(
+                Expr::col((UpstreamOAuthProviders::Table, UpstreamOAuthProviders::Saml)),
+                ProviderLookupIden::Saml,
+            )
             .from(UpstreamOAuthProviders::Table)
             .apply_filter(filter)
             .generate_pagination(
@@ -968,7 +1133,9 @@ impl UpstreamOAuthProviderRepository for PgUpstreamOAuthProviderRepository<'_> {
                     response_mode,
                     additional_parameters as "additional_parameters: Json<Vec<(String, String)>>",
                     forward_login_hint,
-                    on_backchannel_logout
+                    on_backchannel_logout,
+                    is_static,
+                    saml as "saml: Json<UpstreamOAuthProviderSamlSettings>"
                 FROM upstream_oauth_providers
                 WHERE disabled_at IS NULL
                 ORDER BY ui_order ASC, upstream_oauth_provider_id ASC
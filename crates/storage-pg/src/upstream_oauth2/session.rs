@@ -40,6 +40,13 @@ impl Filter for UpstreamOAuthSessionFilter<'_> {
                 ))
                 .eq(Uuid::from(provider.id))
             }))
+            .add_option(self.link().map(|link| {
+                Expr::col((
+                    UpstreamOAuthAuthorizationSessions::Table,
+                    UpstreamOAuthAuthorizationSessions::UpstreamOAuthLinkId,
+                ))
+                .eq(Uuid::from(link.id))
+            }))
             .add_option(self.sub_claim().map(|sub| {
                 Expr::col((
                     UpstreamOAuthAuthorizationSessions::Table,
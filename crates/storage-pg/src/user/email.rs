@@ -630,6 +630,52 @@ impl UserEmailRepository for PgUserEmailRepository<'_> {
         Ok(res.map(UserEmailAuthentication::from))
     }
 
+    #[tracing::instrument(
+        name = "db.user_email.all_authentications",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn all_authentications(
+        &mut self,
+        user: &User,
+    ) -> Result<Vec<UserEmailAuthentication>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserEmailAuthenticationLookup,
+            r#"
+                SELECT user_email_authentication_id
+                     , user_session_id
+                     , user_registration_id
+                     , email
+                     , created_at
+                     , completed_at
+                FROM user_email_authentications
+                WHERE user_session_id IN (
+                        SELECT user_session_id
+                        FROM user_sessions
+                        WHERE user_id = $1
+                    )
+                   OR user_registration_id IN (
+                        SELECT user_registration_id
+                        FROM user_registrations
+                        WHERE username = $2
+                          AND completed_at IS NOT NULL
+                    )
+                ORDER BY user_email_authentication_id ASC
+            "#,
+            Uuid::from(user.id),
+            &user.username,
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(res.into_iter().map(UserEmailAuthentication::from).collect())
+    }
+
     #[tracing::instrument(
         name = "db.user_email.find_authentication_by_code",
         skip_all,
@@ -0,0 +1,165 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{Clock, User, UserExport};
+use mas_storage::user::UserExportRepository;
+use rand::RngCore;
+use sqlx::PgConnection;
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{DatabaseError, tracing::ExecuteExt};
+
+/// An implementation of [`UserExportRepository`] for a PostgreSQL connection
+pub struct PgUserExportRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgUserExportRepository<'c> {
+    /// Create a new [`PgUserExportRepository`] from an active PostgreSQL
+    /// connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+struct UserExportLookup {
+    user_export_id: Uuid,
+    user_id: Uuid,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+    archive: Option<serde_json::Value>,
+}
+
+impl From<UserExportLookup> for UserExport {
+    fn from(value: UserExportLookup) -> Self {
+        UserExport {
+            id: value.user_export_id.into(),
+            user_id: value.user_id.into(),
+            created_at: value.created_at,
+            completed_at: value.completed_at,
+            archive: value.archive,
+        }
+    }
+}
+
+#[async_trait]
+impl UserExportRepository for PgUserExportRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.user_export.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            user_export.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserExport>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserExportLookup,
+            r#"
+                SELECT user_export_id
+                     , user_id
+                     , created_at
+                     , completed_at
+                     , archive
+                FROM user_exports
+
+                WHERE user_export_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserExport::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_export.add",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            user_export.id,
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+    ) -> Result<UserExport, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("user_export.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_exports (user_export_id, user_id, created_at)
+                VALUES ($1, $2, $3)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(UserExport {
+            id,
+            user_id: user.id,
+            created_at,
+            completed_at: None,
+            archive: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.user_export.complete",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_export.id,
+        ),
+        err,
+    )]
+    async fn complete(
+        &mut self,
+        clock: &dyn Clock,
+        mut user_export: UserExport,
+        archive: serde_json::Value,
+    ) -> Result<UserExport, Self::Error> {
+        let completed_at = clock.now();
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_exports
+                SET completed_at = $2
+                  , archive = $3
+                WHERE user_export_id = $1 AND completed_at IS NULL
+            "#,
+            Uuid::from(user_export.id),
+            completed_at,
+            archive,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        user_export.completed_at = Some(completed_at);
+        user_export.archive = Some(archive);
+
+        Ok(user_export)
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{Clock, User, UserLdapLink};
+use mas_storage::user::UserLdapLinkRepository;
+use rand::RngCore;
+use sqlx::PgConnection;
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{DatabaseError, tracing::ExecuteExt};
+
+/// An implementation of [`UserLdapLinkRepository`] for a PostgreSQL
+/// connection
+pub struct PgUserLdapLinkRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgUserLdapLinkRepository<'c> {
+    /// Create a new [`PgUserLdapLinkRepository`] from an active PostgreSQL
+    /// connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+struct UserLdapLinkLookup {
+    user_ldap_link_id: Uuid,
+    user_id: Uuid,
+    subject: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<UserLdapLinkLookup> for UserLdapLink {
+    fn from(value: UserLdapLinkLookup) -> Self {
+        UserLdapLink {
+            id: value.user_ldap_link_id.into(),
+            user_id: value.user_id.into(),
+            subject: value.subject,
+            created_at: value.created_at,
+        }
+    }
+}
+
+#[async_trait]
+impl UserLdapLinkRepository for PgUserLdapLinkRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.user_ldap_link.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            user_ldap_link.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserLdapLink>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserLdapLinkLookup,
+            r#"
+                SELECT user_ldap_link_id
+                     , user_id
+                     , subject
+                     , created_at
+                FROM user_ldap_links
+
+                WHERE user_ldap_link_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserLdapLink::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_ldap_link.find_by_subject",
+        skip_all,
+        fields(
+            db.query.text,
+            user_ldap_link.subject = subject,
+        ),
+        err,
+    )]
+    async fn find_by_subject(
+        &mut self,
+        subject: &str,
+    ) -> Result<Option<UserLdapLink>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserLdapLinkLookup,
+            r#"
+                SELECT user_ldap_link_id
+                     , user_id
+                     , subject
+                     , created_at
+                FROM user_ldap_links
+
+                WHERE subject = $1
+            "#,
+            subject,
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserLdapLink::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_ldap_link.find_for_user",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn find_for_user(&mut self, user: &User) -> Result<Option<UserLdapLink>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserLdapLinkLookup,
+            r#"
+                SELECT user_ldap_link_id
+                     , user_id
+                     , subject
+                     , created_at
+                FROM user_ldap_links
+
+                WHERE user_id = $1
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserLdapLink::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_ldap_link.add",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            user_ldap_link.id,
+            user_ldap_link.subject = subject,
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        subject: String,
+    ) -> Result<UserLdapLink, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("user_ldap_link.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_ldap_links
+                    (user_ldap_link_id, user_id, subject, created_at)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            &subject,
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(UserLdapLink {
+            id,
+            user_id: user.id,
+            subject,
+            created_at,
+        })
+    }
+}
@@ -10,10 +10,12 @@
 use async_trait::async_trait;
 use mas_data_model::{Clock, User};
 use mas_storage::user::{UserFilter, UserRepository};
+use opentelemetry_semantic_conventions::attribute::DB_QUERY_TEXT;
 use rand::RngCore;
 use sea_query::{Expr, PostgresQueryBuilder, Query, extension::postgres::PgExpr as _};
 use sea_query_binder::SqlxBinder;
 use sqlx::PgConnection;
+use tracing::{Instrument, info_span};
 use ulid::Ulid;
 use uuid::Uuid;
 
@@ -26,21 +28,26 @@ use crate::{
 };
 
 mod email;
+mod export;
+mod ldap;
+mod passkey;
 mod password;
 mod recovery;
 mod registration;
 mod registration_token;
 mod session;
 mod terms;
+mod totp;
 
 #[cfg(test)]
 mod tests;
 
 pub use self::{
-    email::PgUserEmailRepository, password::PgUserPasswordRepository,
+    email::PgUserEmailRepository, export::PgUserExportRepository, ldap::PgUserLdapLinkRepository,
+    passkey::PgUserPasskeyRepository, password::PgUserPasswordRepository,
     recovery::PgUserRecoveryRepository, registration::PgUserRegistrationRepository,
     registration_token::PgUserRegistrationTokenRepository, session::PgBrowserSessionRepository,
-    terms::PgUserTermsRepository,
+    terms::PgUserTermsRepository, totp::PgUserTotpRepository,
 };
 
 /// An implementation of [`UserRepository`] for a PostgreSQL connection
@@ -74,6 +81,7 @@ mod priv_ {
         pub(super) created_at: DateTime<Utc>,
         pub(super) locked_at: Option<DateTime<Utc>>,
         pub(super) deactivated_at: Option<DateTime<Utc>>,
+        pub(super) erased_at: Option<DateTime<Utc>>,
         pub(super) can_request_admin: bool,
         pub(super) is_guest: bool,
     }
@@ -97,6 +105,7 @@ impl From<UserLookup> for User {
             created_at: value.created_at,
             locked_at: value.locked_at,
             deactivated_at: value.deactivated_at,
+            erased_at: value.erased_at,
             can_request_admin: value.can_request_admin,
             is_guest: value.is_guest,
         }
@@ -128,6 +137,16 @@ impl Filter for UserFilter<'_> {
                 self.is_guest()
                     .map(|is_guest| Expr::col((Users::Table, Users::IsGuest)).eq(is_guest)),
             )
+            .add_option(self.is_erased().map(|is_erased| {
+                if is_erased {
+                    Expr::col((Users::Table, Users::ErasedAt)).is_not_null()
+                } else {
+                    Expr::col((Users::Table, Users::ErasedAt)).is_null()
+                }
+            }))
+            .add_option(self.deactivated_before().map(|deactivated_before| {
+                Expr::col((Users::Table, Users::DeactivatedAt)).lt(deactivated_before)
+            }))
             .add_option(self.search().map(|search| {
                 Expr::col((Users::Table, Users::Username)).ilike(format!("%{search}%"))
             }))
@@ -156,6 +175,7 @@ impl UserRepository for PgUserRepository<'_> {
                      , created_at
                      , locked_at
                      , deactivated_at
+                     , erased_at
                      , can_request_admin
                      , is_guest
                 FROM users
@@ -193,6 +213,7 @@ impl UserRepository for PgUserRepository<'_> {
                      , created_at
                      , locked_at
                      , deactivated_at
+                     , erased_at
                      , can_request_admin
                      , is_guest
                 FROM users
@@ -267,6 +288,7 @@ impl UserRepository for PgUserRepository<'_> {
             created_at,
             locked_at: None,
             deactivated_at: None,
+            erased_at: None,
             can_request_admin: false,
             is_guest: false,
         })
@@ -434,6 +456,442 @@ impl UserRepository for PgUserRepository<'_> {
         Ok(user)
     }
 
+    #[tracing::instrument(
+        name = "db.user.erase",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn erase(&mut self, clock: &dyn Clock, mut user: User) -> Result<User, Self::Error> {
+        if user.erased_at.is_some() {
+            return Ok(user);
+        }
+
+        // Only deactivated users can be erased
+        let erased_at = clock.now();
+        let res = sqlx::query!(
+            r#"
+                UPDATE users
+                SET erased_at = $2
+                WHERE user_id = $1
+                  AND deactivated_at IS NOT NULL
+                  AND erased_at IS NULL
+            "#,
+            Uuid::from(user.id),
+            erased_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        // Remove the email addresses, along with the recovery sessions started
+        // for them
+        {
+            let span = info_span!(
+                "db.user.erase.emails",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_recovery_sessions
+                    WHERE email IN (
+                        SELECT email FROM user_emails WHERE user_id = $1
+                    )
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_email_confirmation_codes
+                    WHERE user_email_id IN (
+                        SELECT user_email_id FROM user_emails WHERE user_id = $1
+                    )
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_emails
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_unsupported_third_party_ids
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        // Remove the email authentications done by the user, and scrub the
+        // registration which created them. The username of the registration is
+        // kept, as it is the same as the one of the user.
+        {
+            let span = info_span!(
+                "db.user.erase.registrations",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_email_authentications
+                    WHERE user_session_id IN (
+                        SELECT user_session_id FROM user_sessions WHERE user_id = $1
+                    )
+                    OR user_registration_id IN (
+                        SELECT user_registration_id
+                        FROM user_registrations
+                        WHERE username = $2 AND completed_at IS NOT NULL
+                    )
+                "#,
+                Uuid::from(user.id),
+                &user.username,
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE user_registrations
+                    SET ip_address = NULL
+                      , user_agent = NULL
+                      , display_name = NULL
+                      , hashed_password = NULL
+                      , hashed_password_version = NULL
+                    WHERE username = $1 AND completed_at IS NOT NULL
+                "#,
+                &user.username,
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        // Remove the credentials of the user. The authentications of their
+        // sessions which used them are kept, without referencing them anymore.
+        {
+            let span = info_span!(
+                "db.user.erase.credentials",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_passwords
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_passkeys
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_totps
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_recovery_codes
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_ldap_links
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        // Remove the links to upstream identities, starting with the
+        // authorization sessions which hold the ID tokens and userinfo
+        {
+            let span = info_span!(
+                "db.user.erase.upstream_oauth_links",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM upstream_oauth_authorization_sessions
+                    WHERE upstream_oauth_link_id IN (
+                        SELECT upstream_oauth_link_id
+                        FROM upstream_oauth_links
+                        WHERE user_id = $1
+                    )
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM upstream_oauth_links
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        // Scrub the IP addresses, user agents and device names from the
+        // sessions of the user
+        {
+            let span = info_span!(
+                "db.user.erase.sessions",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    UPDATE user_sessions
+                    SET user_agent = NULL
+                      , last_active_ip = NULL
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE oauth2_device_code_grant
+                    SET user_agent = NULL
+                      , ip_address = NULL
+                    WHERE user_session_id IN (
+                        SELECT user_session_id FROM user_sessions WHERE user_id = $1
+                    )
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE oauth2_authorization_grants
+                    SET login_hint = NULL
+                    WHERE oauth2_session_id IN (
+                        SELECT oauth2_session_id FROM oauth2_sessions WHERE user_id = $1
+                    )
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE oauth2_sessions
+                    SET user_agent = NULL
+                      , last_active_ip = NULL
+                      , human_name = NULL
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE compat_sessions
+                    SET user_agent = NULL
+                      , last_active_ip = NULL
+                      , human_name = NULL
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE personal_sessions
+                    SET last_active_ip = NULL
+                    WHERE actor_user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        // Scrub the audit log: where the user did an action, where from, and
+        // the email addresses and upstream subjects mentioned in the details
+        // of actions on them
+        {
+            let span = info_span!(
+                "db.user.erase.audit_events",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    UPDATE audit_events
+                    SET ip_address = NULL
+                      , user_agent = NULL
+                    WHERE actor_user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span.clone())
+            .await?;
+
+            sqlx::query!(
+                r#"
+                    UPDATE audit_events
+                    SET details = details - '{email,subject}'::TEXT[]
+                    WHERE target_user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        // Scrub the email addresses from the events delivered to webhooks, and
+        // remove the exports of the user
+        {
+            let span = info_span!(
+                "db.user.erase.webhook_deliveries",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    UPDATE webhook_deliveries
+                    SET payload = jsonb_set(payload, '{data}', (payload -> 'data') - 'email')
+                    WHERE payload -> 'data' ->> 'user_id' = $1
+                      AND payload -> 'data' ? 'email'
+                "#,
+                user.id.to_string(),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        {
+            let span = info_span!(
+                "db.user.erase.exports",
+                user.id = %user.id,
+                { DB_QUERY_TEXT } = tracing::field::Empty,
+            );
+
+            sqlx::query!(
+                r#"
+                    DELETE FROM user_exports
+                    WHERE user_id = $1
+                "#,
+                Uuid::from(user.id),
+            )
+            .record(&span)
+            .execute(&mut *self.conn)
+            .instrument(span)
+            .await?;
+        }
+
+        user.erased_at = Some(erased_at);
+
+        Ok(user)
+    }
+
     #[tracing::instrument(
         name = "db.user.set_can_request_admin",
         skip_all,
@@ -503,6 +961,10 @@ impl UserRepository for PgUserRepository<'_> {
                 Expr::col((Users::Table, Users::DeactivatedAt)),
                 UserLookupIden::DeactivatedAt,
             )
+            .expr_as(
+                Expr::col((Users::Table, Users::ErasedAt)),
+                UserLookupIden::ErasedAt,
+            )
             .expr_as(
                 Expr::col((Users::Table, Users::CanRequestAdmin)),
                 UserLookupIden::CanRequestAdmin,
@@ -0,0 +1,697 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{BrowserSession, Clock, User, UserPasskey, UserPasskeyChallenge};
+use mas_storage::{
+    Page, Pagination,
+    pagination::Node,
+    user::{UserPasskeyFilter, UserPasskeyRepository},
+};
+use rand::RngCore;
+use sea_query::{Expr, PostgresQueryBuilder, Query, enum_def};
+use sea_query_binder::SqlxBinder;
+use sqlx::PgConnection;
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{
+    DatabaseError, DatabaseInconsistencyError,
+    filter::{Filter, StatementExt},
+    iden::UserPasskeys,
+    pagination::QueryBuilderExt,
+    tracing::ExecuteExt,
+};
+
+/// An implementation of [`UserPasskeyRepository`] for a PostgreSQL connection
+pub struct PgUserPasskeyRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgUserPasskeyRepository<'c> {
+    /// Create a new [`PgUserPasskeyRepository`] from an active PostgreSQL
+    /// connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+#[enum_def]
+struct UserPasskeyLookup {
+    user_passkey_id: Uuid,
+    user_id: Uuid,
+    credential_id: String,
+    name: String,
+    transports: Vec<String>,
+    public_key: Vec<u8>,
+    aaguid: Option<String>,
+    sign_count: i64,
+    created_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+impl Node<Ulid> for UserPasskeyLookup {
+    fn cursor(&self) -> Ulid {
+        self.user_passkey_id.into()
+    }
+}
+
+impl TryFrom<UserPasskeyLookup> for UserPasskey {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: UserPasskeyLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.user_passkey_id);
+        let sign_count = value.sign_count.try_into().map_err(|e| {
+            DatabaseInconsistencyError::on("user_passkeys")
+                .column("sign_count")
+                .row(id)
+                .source(e)
+        })?;
+
+        Ok(UserPasskey {
+            id,
+            user_id: value.user_id.into(),
+            credential_id: value.credential_id,
+            name: value.name,
+            transports: value.transports,
+            public_key: value.public_key,
+            aaguid: value.aaguid,
+            sign_count,
+            created_at: value.created_at,
+            last_used_at: value.last_used_at,
+        })
+    }
+}
+
+struct UserPasskeyChallengeLookup {
+    user_passkey_challenge_id: Uuid,
+    user_session_id: Option<Uuid>,
+    challenge: Vec<u8>,
+    created_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<UserPasskeyChallengeLookup> for UserPasskeyChallenge {
+    fn from(value: UserPasskeyChallengeLookup) -> Self {
+        UserPasskeyChallenge {
+            id: value.user_passkey_challenge_id.into(),
+            user_session_id: value.user_session_id.map(Ulid::from),
+            challenge: value.challenge,
+            created_at: value.created_at,
+            completed_at: value.completed_at,
+        }
+    }
+}
+
+impl Filter for UserPasskeyFilter<'_> {
+    fn generate_condition(&self, _has_joins: bool) -> impl sea_query::IntoCondition {
+        sea_query::Condition::all().add_option(self.user().map(|user| {
+            Expr::col((UserPasskeys::Table, UserPasskeys::UserId)).eq(Uuid::from(user.id))
+        }))
+    }
+}
+
+#[async_trait]
+impl UserPasskeyRepository for PgUserPasskeyRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.user_passkey.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            user_passkey.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserPasskey>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserPasskeyLookup,
+            r#"
+                SELECT user_passkey_id
+                     , user_id
+                     , credential_id
+                     , name
+                     , transports
+                     , public_key
+                     , aaguid
+                     , sign_count
+                     , created_at
+                     , last_used_at
+                FROM user_passkeys
+
+                WHERE user_passkey_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(user_passkey) = res else {
+            return Ok(None);
+        };
+
+        Ok(Some(user_passkey.try_into()?))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.find_by_credential_id",
+        skip_all,
+        fields(
+            db.query.text,
+            user_passkey.credential_id = credential_id,
+        ),
+        err,
+    )]
+    async fn find_by_credential_id(
+        &mut self,
+        credential_id: &str,
+    ) -> Result<Option<UserPasskey>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserPasskeyLookup,
+            r#"
+                SELECT user_passkey_id
+                     , user_id
+                     , credential_id
+                     , name
+                     , transports
+                     , public_key
+                     , aaguid
+                     , sign_count
+                     , created_at
+                     , last_used_at
+                FROM user_passkeys
+
+                WHERE credential_id = $1
+            "#,
+            credential_id,
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(user_passkey) = res else {
+            return Ok(None);
+        };
+
+        Ok(Some(user_passkey.try_into()?))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.all",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn all(&mut self, user: &User) -> Result<Vec<UserPasskey>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserPasskeyLookup,
+            r#"
+                SELECT user_passkey_id
+                     , user_id
+                     , credential_id
+                     , name
+                     , transports
+                     , public_key
+                     , aaguid
+                     , sign_count
+                     , created_at
+                     , last_used_at
+                FROM user_passkeys
+
+                WHERE user_id = $1
+
+                ORDER BY created_at ASC
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(res
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.list",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn list(
+        &mut self,
+        filter: UserPasskeyFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<UserPasskey>, DatabaseError> {
+        let (sql, arguments) = Query::select()
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::UserPasskeyId)),
+                UserPasskeyLookupIden::UserPasskeyId,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::UserId)),
+                UserPasskeyLookupIden::UserId,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::CredentialId)),
+                UserPasskeyLookupIden::CredentialId,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::Name)),
+                UserPasskeyLookupIden::Name,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::Transports)),
+                UserPasskeyLookupIden::Transports,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::PublicKey)),
+                UserPasskeyLookupIden::PublicKey,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::Aaguid)),
+                UserPasskeyLookupIden::Aaguid,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::SignCount)),
+                UserPasskeyLookupIden::SignCount,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::CreatedAt)),
+                UserPasskeyLookupIden::CreatedAt,
+            )
+            .expr_as(
+                Expr::col((UserPasskeys::Table, UserPasskeys::LastUsedAt)),
+                UserPasskeyLookupIden::LastUsedAt,
+            )
+            .from(UserPasskeys::Table)
+            .apply_filter(filter)
+            .generate_pagination(
+                (UserPasskeys::Table, UserPasskeys::UserPasskeyId),
+                pagination,
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        let edges: Vec<UserPasskeyLookup> = sqlx::query_as_with(&sql, arguments)
+            .traced()
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        let page = pagination.process(edges).try_map(UserPasskey::try_from)?;
+
+        Ok(page)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.count",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn count(&mut self, filter: UserPasskeyFilter<'_>) -> Result<usize, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr(Expr::col((UserPasskeys::Table, UserPasskeys::UserPasskeyId)).count())
+            .from(UserPasskeys::Table)
+            .apply_filter(filter)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let count: i64 = sqlx::query_scalar_with(&sql, arguments)
+            .traced()
+            .fetch_one(&mut *self.conn)
+            .await?;
+
+        count
+            .try_into()
+            .map_err(DatabaseError::to_invalid_operation)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.add",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            user_passkey.id,
+            user_passkey.name = name,
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        name: String,
+        credential_id: String,
+        transports: Vec<String>,
+        public_key: Vec<u8>,
+        aaguid: Option<String>,
+        sign_count: u32,
+    ) -> Result<UserPasskey, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("user_passkey.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_passkeys
+                    ( user_passkey_id
+                    , user_id
+                    , credential_id
+                    , name
+                    , transports
+                    , public_key
+                    , aaguid
+                    , sign_count
+                    , created_at
+                    )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            &credential_id,
+            &name,
+            &transports,
+            &public_key,
+            aaguid.as_deref(),
+            i64::from(sign_count),
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(UserPasskey {
+            id,
+            user_id: user.id,
+            credential_id,
+            name,
+            transports,
+            public_key,
+            aaguid,
+            sign_count,
+            created_at,
+            last_used_at: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.rename",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_passkey.id,
+            user_passkey.name = name,
+        ),
+        err,
+    )]
+    async fn rename(
+        &mut self,
+        mut user_passkey: UserPasskey,
+        name: String,
+    ) -> Result<UserPasskey, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_passkeys
+                SET name = $2
+                WHERE user_passkey_id = $1
+            "#,
+            Uuid::from(user_passkey.id),
+            &name,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        user_passkey.name = name;
+        Ok(user_passkey)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.record_use",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_passkey.id,
+            user_passkey.sign_count = sign_count,
+        ),
+        err,
+    )]
+    async fn record_use(
+        &mut self,
+        clock: &dyn Clock,
+        mut user_passkey: UserPasskey,
+        sign_count: u32,
+    ) -> Result<UserPasskey, Self::Error> {
+        let last_used_at = clock.now();
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_passkeys
+                SET sign_count = $2
+                  , last_used_at = $3
+                WHERE user_passkey_id = $1
+            "#,
+            Uuid::from(user_passkey.id),
+            i64::from(sign_count),
+            last_used_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        user_passkey.sign_count = sign_count;
+        user_passkey.last_used_at = Some(last_used_at);
+        Ok(user_passkey)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.remove",
+        skip_all,
+        fields(
+            db.query.text,
+            user.id = %user_passkey.user_id,
+            %user_passkey.id,
+        ),
+        err,
+    )]
+    async fn remove(&mut self, user_passkey: UserPasskey) -> Result<(), Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM user_passkeys
+                WHERE user_passkey_id = $1
+            "#,
+            Uuid::from(user_passkey.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.add_challenge",
+        skip_all,
+        fields(
+            db.query.text,
+            user_passkey_challenge.id,
+        ),
+        err,
+    )]
+    async fn add_challenge(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        challenge: Vec<u8>,
+    ) -> Result<UserPasskeyChallenge, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("user_passkey_challenge.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_passkey_challenges
+                    (user_passkey_challenge_id, challenge, created_at)
+                VALUES ($1, $2, $3)
+            "#,
+            Uuid::from(id),
+            &challenge,
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(UserPasskeyChallenge {
+            id,
+            user_session_id: None,
+            challenge,
+            created_at,
+            completed_at: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.add_challenge_for_session",
+        skip_all,
+        fields(
+            db.query.text,
+            %browser_session.id,
+            user_passkey_challenge.id,
+        ),
+        err,
+    )]
+    async fn add_challenge_for_session(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        challenge: Vec<u8>,
+        browser_session: &BrowserSession,
+    ) -> Result<UserPasskeyChallenge, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("user_passkey_challenge.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_passkey_challenges
+                    (user_passkey_challenge_id, user_session_id, challenge, created_at)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(browser_session.id),
+            &challenge,
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(UserPasskeyChallenge {
+            id,
+            user_session_id: Some(browser_session.id),
+            challenge,
+            created_at,
+            completed_at: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.lookup_challenge",
+        skip_all,
+        fields(
+            db.query.text,
+            user_passkey_challenge.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup_challenge(
+        &mut self,
+        id: Ulid,
+    ) -> Result<Option<UserPasskeyChallenge>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserPasskeyChallengeLookup,
+            r#"
+                SELECT user_passkey_challenge_id
+                     , user_session_id
+                     , challenge
+                     , created_at
+                     , completed_at
+                FROM user_passkey_challenges
+                WHERE user_passkey_challenge_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserPasskeyChallenge::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.complete_challenge",
+        skip_all,
+        fields(
+            db.query.text,
+            %challenge.id,
+        ),
+        err,
+    )]
+    async fn complete_challenge(
+        &mut self,
+        clock: &dyn Clock,
+        mut challenge: UserPasskeyChallenge,
+    ) -> Result<UserPasskeyChallenge, Self::Error> {
+        let completed_at = clock.now();
+
+        // The update will not affect any rows if the challenge was already
+        // completed, which will raise an error
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_passkey_challenges
+                SET completed_at = $2
+                WHERE user_passkey_challenge_id = $1
+                  AND completed_at IS NULL
+            "#,
+            Uuid::from(challenge.id),
+            completed_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        challenge.completed_at = Some(completed_at);
+        Ok(challenge)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_passkey.cleanup_challenges",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn cleanup_challenges(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error> {
+        // Keep challenges around for a bit longer than their validity, so that
+        // we don't remove them while they're being used
+        let threshold = clock.now() - UserPasskeyChallenge::VALIDITY * 2;
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM user_passkey_challenges
+                WHERE created_at < $1
+            "#,
+            threshold,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
@@ -36,6 +36,29 @@ struct UserPasswordLookup {
     created_at: DateTime<Utc>,
 }
 
+impl TryFrom<UserPasswordLookup> for Password {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: UserPasswordLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.user_password_id);
+
+        let version = value.version.try_into().map_err(|e| {
+            DatabaseInconsistencyError::on("user_passwords")
+                .column("version")
+                .row(id)
+                .source(e)
+        })?;
+
+        Ok(Password {
+            id,
+            hashed_password: value.hashed_password,
+            version,
+            upgraded_from_id: value.upgraded_from_id.map(Ulid::from),
+            created_at: value.created_at,
+        })
+    }
+}
+
 #[async_trait]
 impl UserPasswordRepository for PgUserPasswordRepository<'_> {
     type Error = DatabaseError;
@@ -72,26 +95,101 @@ impl UserPasswordRepository for PgUserPasswordRepository<'_> {
 
         let Some(res) = res else { return Ok(None) };
 
-        let id = Ulid::from(res.user_password_id);
+        Ok(Some(res.try_into()?))
+    }
 
-        let version = res.version.try_into().map_err(|e| {
-            DatabaseInconsistencyError::on("user_passwords")
-                .column("version")
-                .row(id)
-                .source(e)
-        })?;
+    #[tracing::instrument(
+        name = "db.user_password.history",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            %user.username,
+        ),
+        err,
+    )]
+    async fn history(&mut self, user: &User, limit: usize) -> Result<Vec<Password>, Self::Error> {
+        // Only the most recent hash of each password, which is the one no other
+        // password was upgraded from. Upgrades only ever happen on the active
+        // password, so ordering them by creation time orders the passwords
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        let res = sqlx::query_as!(
+            UserPasswordLookup,
+            r#"
+                SELECT up.user_password_id
+                     , up.hashed_password
+                     , up.version
+                     , up.upgraded_from_id
+                     , up.created_at
+                FROM user_passwords up
+                WHERE up.user_id = $1
+                  AND NOT EXISTS (
+                      SELECT 1
+                      FROM user_passwords upgraded
+                      WHERE upgraded.user_id = up.user_id
+                        AND upgraded.upgraded_from_id = up.user_password_id
+                  )
+                ORDER BY up.created_at DESC
+                LIMIT $2
+            "#,
+            Uuid::from(user.id),
+            limit,
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
 
-        let upgraded_from_id = res.upgraded_from_id.map(Ulid::from);
-        let created_at = res.created_at;
-        let hashed_password = res.hashed_password;
+        let passwords = res
+            .into_iter()
+            .map(Password::try_from)
+            .collect::<Result<_, _>>()?;
 
-        Ok(Some(Password {
-            id,
-            hashed_password,
-            version,
-            upgraded_from_id,
-            created_at,
-        }))
+        Ok(passwords)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_password.set_at",
+        skip_all,
+        fields(
+            db.query.text,
+            user_password.id = %password.id,
+        ),
+        err,
+    )]
+    async fn set_at(&mut self, password: &Password) -> Result<DateTime<Utc>, Self::Error> {
+        let Some(upgraded_from_id) = password.upgraded_from_id else {
+            return Ok(password.created_at);
+        };
+
+        let res = sqlx::query_scalar!(
+            r#"
+                WITH RECURSIVE chain AS (
+                    SELECT user_password_id
+                         , upgraded_from_id
+                         , created_at
+                    FROM user_passwords
+                    WHERE user_password_id = $1
+
+                    UNION ALL
+
+                    SELECT up.user_password_id
+                         , up.upgraded_from_id
+                         , up.created_at
+                    FROM user_passwords up
+                    INNER JOIN chain
+                        ON up.user_password_id = chain.upgraded_from_id
+                )
+                SELECT created_at AS "created_at!"
+                FROM chain
+                WHERE upgraded_from_id IS NULL
+            "#,
+            Uuid::from(upgraded_from_id),
+        )
+        .traced()
+        .fetch_one(&mut *self.conn)
+        .await?;
+
+        Ok(res)
     }
 
     #[tracing::instrument(
@@ -8,7 +8,7 @@ use std::net::IpAddr;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
-use mas_data_model::{Clock, UserEmail, UserRecoverySession, UserRecoveryTicket};
+use mas_data_model::{Clock, User, UserEmail, UserRecoverySession, UserRecoveryTicket};
 use mas_storage::user::UserRecoveryRepository;
 use rand::RngCore;
 use sqlx::PgConnection;
@@ -120,6 +120,50 @@ impl UserRecoveryRepository for PgUserRecoveryRepository<'_> {
         Ok(Some(row.into()))
     }
 
+    #[tracing::instrument(
+        name = "db.user_recovery.all_sessions",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn all_sessions(&mut self, user: &User) -> Result<Vec<UserRecoverySession>, Self::Error> {
+        let rows = sqlx::query_as!(
+            UserRecoverySessionRow,
+            r#"
+                SELECT
+                      user_recovery_session_id
+                    , email
+                    , user_agent
+                    , ip_address as "ip_address: IpAddr"
+                    , locale
+                    , created_at
+                    , consumed_at
+                FROM user_recovery_sessions
+                WHERE LOWER(email) IN (
+                        SELECT LOWER(email)
+                        FROM user_emails
+                        WHERE user_id = $1
+                    )
+                   OR user_recovery_session_id IN (
+                        SELECT t.user_recovery_session_id
+                        FROM user_recovery_tickets t
+                        INNER JOIN user_emails e USING (user_email_id)
+                        WHERE e.user_id = $1
+                    )
+                ORDER BY user_recovery_session_id ASC
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
     #[tracing::instrument(
         name = "db.user_recovery.add_session",
         skip_all,
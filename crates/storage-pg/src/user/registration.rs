@@ -150,6 +150,52 @@ impl UserRegistrationRepository for PgUserRegistrationRepository<'_> {
         Ok(Some(res.try_into()?))
     }
 
+    #[tracing::instrument(
+        name = "db.user_registration.find_completed_by_username",
+        skip_all,
+        fields(
+            db.query.text,
+            user_registration.username = username,
+        ),
+        err,
+    )]
+    async fn find_completed_by_username(
+        &mut self,
+        username: &str,
+    ) -> Result<Option<UserRegistration>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserRegistrationLookup,
+            r#"
+                SELECT user_registration_id
+                     , ip_address as "ip_address: IpAddr"
+                     , user_agent
+                     , post_auth_action
+                     , username
+                     , display_name
+                     , terms_url
+                     , email_authentication_id
+                     , user_registration_token_id
+                     , hashed_password
+                     , hashed_password_version
+                     , created_at
+                     , completed_at
+                FROM user_registrations
+                WHERE username = $1
+                  AND completed_at IS NOT NULL
+                ORDER BY completed_at DESC
+                LIMIT 1
+            "#,
+            username,
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(res) = res else { return Ok(None) };
+
+        Ok(Some(res.try_into()?))
+    }
+
     #[tracing::instrument(
         name = "db.user_registration.add",
         skip_all,
@@ -503,6 +549,22 @@ mod tests {
             .unwrap();
         assert_eq!(lookup.completed_at, registration.completed_at);
 
+        // The completed registration can be found by its username
+        let lookup = repo
+            .user_registration()
+            .find_completed_by_username("alice")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup.id, registration.id);
+
+        let lookup = repo
+            .user_registration()
+            .find_completed_by_username("bob")
+            .await
+            .unwrap();
+        assert!(lookup.is_none());
+
         // Do it again, it should fail
         let res = repo
             .user_registration()
@@ -10,7 +10,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use mas_data_model::{
     Authentication, AuthenticationMethod, BrowserSession, Clock, Password,
-    UpstreamOAuthAuthorizationSession, User,
+    UpstreamOAuthAuthorizationSession, User, UserLdapLink, UserPasskey, UserRecoveryCode, UserTotp,
 };
 use mas_storage::{
     Page, Pagination,
@@ -61,6 +61,7 @@ struct SessionLookup {
     user_created_at: DateTime<Utc>,
     user_locked_at: Option<DateTime<Utc>>,
     user_deactivated_at: Option<DateTime<Utc>>,
+    user_erased_at: Option<DateTime<Utc>>,
     user_can_request_admin: bool,
     user_is_guest: bool,
 }
@@ -83,6 +84,7 @@ impl TryFrom<SessionLookup> for BrowserSession {
             created_at: value.user_created_at,
             locked_at: value.user_locked_at,
             deactivated_at: value.user_deactivated_at,
+            erased_at: value.user_erased_at,
             can_request_admin: value.user_can_request_admin,
             is_guest: value.user_is_guest,
         };
@@ -104,6 +106,10 @@ struct AuthenticationLookup {
     created_at: DateTime<Utc>,
     user_password_id: Option<Uuid>,
     upstream_oauth_authorization_session_id: Option<Uuid>,
+    user_passkey_id: Option<Uuid>,
+    user_totp_id: Option<Uuid>,
+    user_recovery_code_id: Option<Uuid>,
+    user_ldap_link_id: Option<Uuid>,
 }
 
 impl TryFrom<AuthenticationLookup> for Authentication {
@@ -116,12 +122,34 @@ impl TryFrom<AuthenticationLookup> for Authentication {
             value
                 .upstream_oauth_authorization_session_id
                 .map(Into::into),
+            value.user_passkey_id.map(Into::into),
+            value.user_totp_id.map(Into::into),
+            value.user_recovery_code_id.map(Into::into),
+            value.user_ldap_link_id.map(Into::into),
         ) {
-            (Some(user_password_id), None) => AuthenticationMethod::Password { user_password_id },
-            (None, Some(upstream_oauth2_session_id)) => AuthenticationMethod::UpstreamOAuth2 {
-                upstream_oauth2_session_id,
-            },
-            (None, None) => AuthenticationMethod::Unknown,
+            (Some(user_password_id), None, None, None, None, None) => {
+                AuthenticationMethod::Password { user_password_id }
+            }
+            (None, Some(upstream_oauth2_session_id), None, None, None, None) => {
+                AuthenticationMethod::UpstreamOAuth2 {
+                    upstream_oauth2_session_id,
+                }
+            }
+            (None, None, Some(user_passkey_id), None, None, None) => {
+                AuthenticationMethod::Passkey { user_passkey_id }
+            }
+            (None, None, None, Some(user_totp_id), None, None) => {
+                AuthenticationMethod::Totp { user_totp_id }
+            }
+            (None, None, None, None, Some(user_recovery_code_id), None) => {
+                AuthenticationMethod::RecoveryCode {
+                    user_recovery_code_id,
+                }
+            }
+            (None, None, None, None, None, Some(user_ldap_link_id)) => {
+                AuthenticationMethod::Ldap { user_ldap_link_id }
+            }
+            (None, None, None, None, None, None) => AuthenticationMethod::Unknown,
             _ => {
                 return Err(DatabaseInconsistencyError::on("user_session_authentications").row(id));
             }
@@ -209,6 +237,7 @@ impl BrowserSessionRepository for PgBrowserSessionRepository<'_> {
                      , u.created_at            AS "user_created_at"
                      , u.locked_at             AS "user_locked_at"
                      , u.deactivated_at        AS "user_deactivated_at"
+                     , u.erased_at             AS "user_erased_at"
                      , u.can_request_admin     AS "user_can_request_admin"
                      , u.is_guest              AS "user_is_guest"
                 FROM user_sessions s
@@ -397,6 +426,10 @@ impl BrowserSessionRepository for PgBrowserSessionRepository<'_> {
                 Expr::col((Users::Table, Users::DeactivatedAt)),
                 SessionLookupIden::UserDeactivatedAt,
             )
+            .expr_as(
+                Expr::col((Users::Table, Users::ErasedAt)),
+                SessionLookupIden::UserErasedAt,
+            )
             .expr_as(
                 Expr::col((Users::Table, Users::CanRequestAdmin)),
                 SessionLookupIden::UserCanRequestAdmin,
@@ -553,6 +586,202 @@ impl BrowserSessionRepository for PgBrowserSessionRepository<'_> {
         })
     }
 
+    #[tracing::instrument(
+        name = "db.browser_session.authenticate_with_passkey",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_session.id,
+            %user_passkey.id,
+            user_session_authentication.id,
+        ),
+        err,
+    )]
+    async fn authenticate_with_passkey(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_passkey: &UserPasskey,
+    ) -> Result<Authentication, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record(
+            "user_session_authentication.id",
+            tracing::field::display(id),
+        );
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_session_authentications
+                    (user_session_authentication_id, user_session_id, created_at, user_passkey_id)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user_session.id),
+            created_at,
+            Uuid::from(user_passkey.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(Authentication {
+            id,
+            created_at,
+            authentication_method: AuthenticationMethod::Passkey {
+                user_passkey_id: user_passkey.id,
+            },
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.browser_session.authenticate_with_totp",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_session.id,
+            %user_totp.id,
+            user_session_authentication.id,
+        ),
+        err,
+    )]
+    async fn authenticate_with_totp(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_totp: &UserTotp,
+    ) -> Result<Authentication, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record(
+            "user_session_authentication.id",
+            tracing::field::display(id),
+        );
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_session_authentications
+                    (user_session_authentication_id, user_session_id, created_at, user_totp_id)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user_session.id),
+            created_at,
+            Uuid::from(user_totp.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(Authentication {
+            id,
+            created_at,
+            authentication_method: AuthenticationMethod::Totp {
+                user_totp_id: user_totp.id,
+            },
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.browser_session.authenticate_with_recovery_code",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_session.id,
+            %user_recovery_code.id,
+            user_session_authentication.id,
+        ),
+        err,
+    )]
+    async fn authenticate_with_recovery_code(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_recovery_code: &UserRecoveryCode,
+    ) -> Result<Authentication, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record(
+            "user_session_authentication.id",
+            tracing::field::display(id),
+        );
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_session_authentications
+                    (user_session_authentication_id, user_session_id, created_at, user_recovery_code_id)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user_session.id),
+            created_at,
+            Uuid::from(user_recovery_code.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(Authentication {
+            id,
+            created_at,
+            authentication_method: AuthenticationMethod::RecoveryCode {
+                user_recovery_code_id: user_recovery_code.id,
+            },
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.browser_session.authenticate_with_ldap",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_session.id,
+            %user_ldap_link.id,
+            user_session_authentication.id,
+        ),
+        err,
+    )]
+    async fn authenticate_with_ldap(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_ldap_link: &UserLdapLink,
+    ) -> Result<Authentication, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record(
+            "user_session_authentication.id",
+            tracing::field::display(id),
+        );
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_session_authentications
+                    (user_session_authentication_id, user_session_id, created_at, user_ldap_link_id)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user_session.id),
+            created_at,
+            Uuid::from(user_ldap_link.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(Authentication {
+            id,
+            created_at,
+            authentication_method: AuthenticationMethod::Ldap {
+                user_ldap_link_id: user_ldap_link.id,
+            },
+        })
+    }
+
     #[tracing::instrument(
         name = "db.browser_session.get_last_authentication",
         skip_all,
@@ -573,6 +802,10 @@ impl BrowserSessionRepository for PgBrowserSessionRepository<'_> {
                      , created_at
                      , user_password_id
                      , upstream_oauth_authorization_session_id
+                     , user_passkey_id
+                     , user_totp_id
+                     , user_recovery_code_id
+                     , user_ldap_link_id
                 FROM user_session_authentications
                 WHERE user_session_id = $1
                 ORDER BY created_at DESC
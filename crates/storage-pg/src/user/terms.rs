@@ -5,7 +5,8 @@
 // Please see LICENSE files in the repository root for full details.
 
 use async_trait::async_trait;
-use mas_data_model::{Clock, User};
+use chrono::{DateTime, Utc};
+use mas_data_model::{Clock, User, UserTerms};
 use mas_storage::user::UserTermsRepository;
 use rand::RngCore;
 use sqlx::PgConnection;
@@ -13,7 +14,7 @@ use ulid::Ulid;
 use url::Url;
 use uuid::Uuid;
 
-use crate::{DatabaseError, tracing::ExecuteExt};
+use crate::{DatabaseError, DatabaseInconsistencyError, tracing::ExecuteExt};
 
 /// An implementation of [`UserTermsRepository`] for a PostgreSQL connection
 pub struct PgUserTermsRepository<'c> {
@@ -28,6 +29,34 @@ impl<'c> PgUserTermsRepository<'c> {
     }
 }
 
+struct UserTermsLookup {
+    user_terms_id: Uuid,
+    user_id: Uuid,
+    terms_url: String,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<UserTermsLookup> for UserTerms {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: UserTermsLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.user_terms_id);
+        let terms_url = value.terms_url.parse().map_err(|e| {
+            DatabaseInconsistencyError::on("user_terms")
+                .column("terms_url")
+                .row(id)
+                .source(e)
+        })?;
+
+        Ok(UserTerms {
+            id,
+            user_id: Ulid::from(value.user_id),
+            terms_url,
+            created_at: value.created_at,
+        })
+    }
+}
+
 #[async_trait]
 impl UserTermsRepository for PgUserTermsRepository<'_> {
     type Error = DatabaseError;
@@ -71,4 +100,37 @@ impl UserTermsRepository for PgUserTermsRepository<'_> {
 
         Ok(())
     }
+
+    #[tracing::instrument(
+        name = "db.user_terms.all",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn all(&mut self, user: &User) -> Result<Vec<UserTerms>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserTermsLookup,
+            r#"
+            SELECT user_terms_id
+                 , user_id
+                 , terms_url
+                 , created_at
+            FROM user_terms
+            WHERE user_id = $1
+            ORDER BY created_at ASC, user_terms_id ASC
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(res
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()?)
+    }
 }
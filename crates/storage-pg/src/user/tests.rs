@@ -5,20 +5,23 @@
 // Please see LICENSE files in the repository root for full details.
 
 use chrono::Duration;
-use mas_data_model::{Clock, clock::MockClock};
+use mas_data_model::{AuthenticationMethod, Clock, UserTotp, WebhookEvent, clock::MockClock};
 use mas_iana::jose::JsonWebSignatureAlg;
 use mas_storage::{
     Pagination, RepositoryAccess,
     upstream_oauth2::{UpstreamOAuthProviderParams, UpstreamOAuthSessionFilter},
     user::{
-        BrowserSessionFilter, BrowserSessionRepository, UserEmailFilter, UserEmailRepository,
-        UserFilter, UserPasswordRepository, UserRepository,
+        BrowserSessionFilter, BrowserSessionRepository, USER_ARCHIVE_VERSION, UserEmailFilter,
+        UserEmailRepository, UserExportRepository, UserFilter, UserLdapLinkRepository,
+        UserPasskeyFilter, UserPasskeyRepository, UserPasswordRepository, UserRepository,
+        UserTermsRepository, UserTotpRepository, export_user,
     },
 };
 use oauth2_types::scope::{OPENID, Scope};
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use sqlx::PgPool;
+use ulid::Ulid;
 
 use crate::PgRepository;
 
@@ -525,6 +528,7 @@ async fn test_user_password_repo(pool: PgPool) {
     const USERNAME: &str = "john";
     const FIRST_PASSWORD_HASH: &str = "doesntmatter";
     const SECOND_PASSWORD_HASH: &str = "alsodoesntmatter";
+    const THIRD_PASSWORD_HASH: &str = "stilldoesntmatter";
 
     let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
     let mut rng = ChaChaRng::seed_from_u64(42);
@@ -599,6 +603,42 @@ async fn test_user_password_repo(pool: PgPool) {
         Some(first_password.id)
     );
 
+    // The upgrade doesn't change when the password was set
+    let set_at = repo
+        .user_password()
+        .set_at(&second_password_lookup)
+        .await
+        .unwrap();
+    assert_eq!(set_at, first_password.created_at);
+
+    clock.advance(Duration::microseconds(10 * 1000 * 1000));
+
+    // Set a brand new password
+    let third_password = repo
+        .user_password()
+        .add(
+            &mut rng,
+            &clock,
+            &user,
+            2,
+            THIRD_PASSWORD_HASH.to_owned(),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let set_at = repo.user_password().set_at(&third_password).await.unwrap();
+    assert_eq!(set_at, third_password.created_at);
+
+    // The history only has the most recent hash of each password
+    let history = repo.user_password().history(&user, 10).await.unwrap();
+    let history: Vec<_> = history.into_iter().map(|password| password.id).collect();
+    assert_eq!(history, vec![third_password.id, second_password.id]);
+
+    let history = repo.user_password().history(&user, 1).await.unwrap();
+    let history: Vec<_> = history.into_iter().map(|password| password.id).collect();
+    assert_eq!(history, vec![third_password.id]);
+
     repo.save().await.unwrap();
 }
 
@@ -771,6 +811,7 @@ async fn test_user_session(pool: PgPool) {
                 ui_order: 0,
                 on_backchannel_logout:
                     mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                saml: None,
             },
         )
         .await
@@ -868,6 +909,7 @@ async fn test_user_terms(pool: PgPool) {
         .unwrap();
 
     // Accepting a different terms should also work
+    clock.advance(Duration::try_minutes(1).unwrap());
     repo.user_terms()
         .accept_terms(
             &mut rng,
@@ -878,6 +920,13 @@ async fn test_user_terms(pool: PgPool) {
         .await
         .unwrap();
 
+    // Listing the accepted terms should return both, oldest first
+    let terms = repo.user_terms().all(&user).await.unwrap();
+    assert_eq!(terms.len(), 2);
+    assert_eq!(terms[0].terms_url.as_str(), "https://example.com/terms");
+    assert_eq!(terms[1].terms_url.as_str(), "https://example.com/terms?v=2");
+    assert!(terms.iter().all(|t| t.user_id == user.id));
+
     let mut conn = repo.into_inner();
 
     // We should have two rows, as the first terms was deduped
@@ -887,3 +936,815 @@ async fn test_user_terms(pool: PgPool) {
         .unwrap();
     assert_eq!(res, 2);
 }
+
+#[sqlx::test(migrator = "crate::MIGRATOR")]
+async fn test_user_passkey_repo(pool: PgPool) {
+    let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+    let mut rng = ChaChaRng::seed_from_u64(42);
+    let clock = MockClock::default();
+
+    let user = repo
+        .user()
+        .add(&mut rng, &clock, "john".to_owned())
+        .await
+        .unwrap();
+
+    let all = UserPasskeyFilter::new().for_user(&user);
+    assert_eq!(repo.user_passkey().count(all).await.unwrap(), 0);
+
+    let passkey = repo
+        .user_passkey()
+        .add(
+            &mut rng,
+            &clock,
+            &user,
+            "My phone".to_owned(),
+            "AQIDBA".to_owned(),
+            vec!["internal".to_owned(), "hybrid".to_owned()],
+            vec![1, 2, 3],
+            None,
+            0,
+        )
+        .await
+        .unwrap();
+    assert_eq!(passkey.user_id, user.id);
+    assert_eq!(passkey.name, "My phone");
+    assert_eq!(passkey.last_used_at, None);
+
+    assert_eq!(repo.user_passkey().count(all).await.unwrap(), 1);
+
+    let found = repo
+        .user_passkey()
+        .find_by_credential_id("AQIDBA")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found, passkey);
+    assert!(
+        repo.user_passkey()
+            .find_by_credential_id("unknown")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let passkey = repo
+        .user_passkey()
+        .rename(passkey, "My laptop".to_owned())
+        .await
+        .unwrap();
+    assert_eq!(passkey.name, "My laptop");
+
+    clock.advance(Duration::try_minutes(1).unwrap());
+    let passkey = repo
+        .user_passkey()
+        .record_use(&clock, passkey, 5)
+        .await
+        .unwrap();
+    assert_eq!(passkey.sign_count, 5);
+    assert_eq!(passkey.last_used_at, Some(clock.now()));
+
+    let page = repo
+        .user_passkey()
+        .list(all, Pagination::first(10))
+        .await
+        .unwrap();
+    assert_eq!(page.edges.len(), 1);
+    assert_eq!(page.edges[0].node, passkey);
+
+    // Authenticate a browser session with the passkey
+    let session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, None)
+        .await
+        .unwrap();
+    repo.browser_session()
+        .authenticate_with_passkey(&mut rng, &clock, &session, &passkey)
+        .await
+        .unwrap();
+    let authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        authentication.authentication_method,
+        AuthenticationMethod::Passkey {
+            user_passkey_id: passkey.id
+        }
+    );
+
+    // Challenges can only be completed once
+    let challenge = repo
+        .user_passkey()
+        .add_challenge(&mut rng, &clock, vec![42; 32])
+        .await
+        .unwrap();
+    assert!(challenge.is_valid(clock.now()));
+    assert_eq!(challenge.user_session_id, None);
+
+    let challenge = repo
+        .user_passkey()
+        .lookup_challenge(challenge.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let completed = repo
+        .user_passkey()
+        .complete_challenge(&clock, challenge.clone())
+        .await
+        .unwrap();
+    assert!(!completed.is_valid(clock.now()));
+    assert!(
+        repo.user_passkey()
+            .complete_challenge(&clock, challenge)
+            .await
+            .is_err()
+    );
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR")]
+async fn test_user_passkey_challenges(pool: PgPool) {
+    let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+    let mut rng = ChaChaRng::seed_from_u64(42);
+    let clock = MockClock::default();
+
+    let user = repo
+        .user()
+        .add(&mut rng, &clock, "john".to_owned())
+        .await
+        .unwrap();
+    let session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, None)
+        .await
+        .unwrap();
+
+    let challenge = repo
+        .user_passkey()
+        .add_challenge_for_session(&mut rng, &clock, vec![42; 32], &session)
+        .await
+        .unwrap();
+    assert_eq!(challenge.user_session_id, Some(session.id));
+
+    // Challenges expire after a while
+    clock.advance(Duration::try_minutes(11).unwrap());
+    assert!(!challenge.is_valid(clock.now()));
+
+    // ...and are cleaned up later on
+    assert_eq!(
+        repo.user_passkey()
+            .cleanup_challenges(&clock)
+            .await
+            .unwrap(),
+        0
+    );
+    clock.advance(Duration::try_minutes(10).unwrap());
+    assert_eq!(
+        repo.user_passkey()
+            .cleanup_challenges(&clock)
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(
+        repo.user_passkey()
+            .lookup_challenge(challenge.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[sqlx::test(migrator = "crate::MIGRATOR")]
+async fn test_user_totp_repo(pool: PgPool) {
+    let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+    let mut rng = ChaChaRng::seed_from_u64(42);
+    let clock = MockClock::default();
+
+    let user = repo
+        .user()
+        .add(&mut rng, &clock, "john".to_owned())
+        .await
+        .unwrap();
+
+    assert!(
+        repo.user_totp()
+            .find_for_user(&user)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let totp = repo
+        .user_totp()
+        .add(&mut rng, &clock, &user, "encrypted".to_owned())
+        .await
+        .unwrap();
+    assert!(!totp.is_confirmed());
+
+    let found = repo
+        .user_totp()
+        .find_for_user(&user)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found, totp);
+    let found = repo.user_totp().lookup(totp.id).await.unwrap().unwrap();
+    assert_eq!(found, totp);
+
+    // A TOTP can only be confirmed once
+    let totp = repo
+        .user_totp()
+        .confirm(&clock, totp.clone(), 100)
+        .await
+        .unwrap();
+    assert!(totp.is_confirmed());
+    assert_eq!(totp.last_used_at, Some(clock.now()));
+    assert_eq!(totp.last_used_step, Some(100));
+    assert!(
+        repo.user_totp()
+            .confirm(&clock, totp.clone(), 100)
+            .await
+            .is_err()
+    );
+
+    // The code used to confirm can't be used again
+    assert!(
+        repo.user_totp()
+            .record_use(&clock, totp.clone(), 100)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    clock.advance(Duration::try_minutes(1).unwrap());
+    let totp = repo
+        .user_totp()
+        .record_use(&clock, totp.clone(), 102)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(totp.last_used_at, Some(clock.now()));
+    assert_eq!(totp.last_used_step, Some(102));
+    let found = repo.user_totp().lookup(totp.id).await.unwrap().unwrap();
+    assert_eq!(found, totp);
+
+    // Codes from the same step or earlier ones are replays, even with a stale
+    // view of the TOTP
+    let stale = UserTotp {
+        last_used_step: Some(100),
+        ..totp.clone()
+    };
+    for step in [101, 102] {
+        assert!(
+            repo.user_totp()
+                .record_use(&clock, stale.clone(), step)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+    let found = repo.user_totp().lookup(totp.id).await.unwrap().unwrap();
+    assert_eq!(found, totp);
+
+    // Recovery codes
+    let codes = repo
+        .user_totp()
+        .replace_recovery_codes(
+            &mut rng,
+            &clock,
+            &user,
+            vec!["one".to_owned(), "two".to_owned(), "three".to_owned()],
+        )
+        .await
+        .unwrap();
+    assert_eq!(codes.len(), 3);
+
+    let unused = repo.user_totp().unused_recovery_codes(&user).await.unwrap();
+    assert_eq!(unused.len(), 3);
+
+    let code = unused[0].clone();
+    let code = repo
+        .user_totp()
+        .consume_recovery_code(&clock, code)
+        .await
+        .unwrap();
+    assert_eq!(code.used_at, Some(clock.now()));
+    assert!(
+        repo.user_totp()
+            .consume_recovery_code(&clock, unused[0].clone())
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        repo.user_totp()
+            .unused_recovery_codes(&user)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // Authenticate a browser session with the TOTP and a recovery code
+    let session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, None)
+        .await
+        .unwrap();
+    repo.browser_session()
+        .authenticate_with_totp(&mut rng, &clock, &session, &totp)
+        .await
+        .unwrap();
+    let authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        authentication.authentication_method,
+        AuthenticationMethod::Totp {
+            user_totp_id: totp.id
+        }
+    );
+    assert!(authentication.authentication_method.is_multi_factor());
+
+    clock.advance(Duration::try_minutes(1).unwrap());
+    repo.browser_session()
+        .authenticate_with_recovery_code(&mut rng, &clock, &session, &code)
+        .await
+        .unwrap();
+    let authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        authentication.authentication_method,
+        AuthenticationMethod::RecoveryCode {
+            user_recovery_code_id: code.id
+        }
+    );
+
+    // Removing everything
+    assert_eq!(
+        repo.user_totp().remove_recovery_codes(&user).await.unwrap(),
+        3
+    );
+    repo.user_totp().remove(totp.clone()).await.unwrap();
+    assert!(
+        repo.user_totp()
+            .find_for_user(&user)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(repo.user_totp().remove(totp).await.is_err());
+}
+
+/// Test the LDAP link repository, and authenticating a browser session with
+/// a link
+#[sqlx::test(migrator = "crate::MIGRATOR")]
+async fn test_user_ldap_link_repo(pool: PgPool) {
+    const SUBJECT: &str = "uid=john,ou=people,dc=example,dc=com";
+
+    let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+    let mut rng = ChaChaRng::seed_from_u64(42);
+    let clock = MockClock::default();
+
+    let user = repo
+        .user()
+        .add(&mut rng, &clock, "john".to_owned())
+        .await
+        .unwrap();
+
+    assert!(
+        repo.user_ldap_link()
+            .find_by_subject(SUBJECT)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        repo.user_ldap_link()
+            .find_for_user(&user)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let link = repo
+        .user_ldap_link()
+        .add(&mut rng, &clock, &user, SUBJECT.to_owned())
+        .await
+        .unwrap();
+    assert_eq!(link.user_id, user.id);
+    assert_eq!(link.subject, SUBJECT);
+
+    let found = repo.user_ldap_link().lookup(link.id).await.unwrap();
+    assert_eq!(found.as_ref(), Some(&link));
+    let found = repo
+        .user_ldap_link()
+        .find_by_subject(SUBJECT)
+        .await
+        .unwrap();
+    assert_eq!(found.as_ref(), Some(&link));
+    let found = repo.user_ldap_link().find_for_user(&user).await.unwrap();
+    assert_eq!(found.as_ref(), Some(&link));
+
+    let session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, None)
+        .await
+        .unwrap();
+    repo.browser_session()
+        .authenticate_with_ldap(&mut rng, &clock, &session, &link)
+        .await
+        .unwrap();
+    let authentication = repo
+        .browser_session()
+        .get_last_authentication(&session)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        authentication.authentication_method,
+        AuthenticationMethod::Ldap {
+            user_ldap_link_id: link.id
+        }
+    );
+    assert_eq!(authentication.authentication_method.amr(), ["pwd"]);
+
+    // Subjects are unique
+    let other = repo
+        .user()
+        .add(&mut rng, &clock, "alice".to_owned())
+        .await
+        .unwrap();
+    assert!(
+        repo.user_ldap_link()
+            .add(&mut rng, &clock, &other, SUBJECT.to_owned())
+            .await
+            .is_err()
+    );
+}
+
+/// Test the user export repository, and building an archive for a user
+#[sqlx::test(migrator = "crate::MIGRATOR")]
+async fn test_user_export_repo(pool: PgPool) {
+    let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+    let mut rng = ChaChaRng::seed_from_u64(42);
+    let clock = MockClock::default();
+
+    let user = repo
+        .user()
+        .add(&mut rng, &clock, "john".to_owned())
+        .await
+        .unwrap();
+    repo.user_email()
+        .add(&mut rng, &clock, &user, "john@example.com".to_owned())
+        .await
+        .unwrap();
+    repo.user_terms()
+        .accept_terms(
+            &mut rng,
+            &clock,
+            &user,
+            "https://example.com/terms".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+    let browser_session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, None)
+        .await
+        .unwrap();
+
+    // An email verification started from the browser session
+    repo.user_email()
+        .add_authentication_for_session(
+            &mut rng,
+            &clock,
+            "john@example.com".to_owned(),
+            &browser_session,
+        )
+        .await
+        .unwrap();
+
+    // An account recovery started for the email address of the user, and one for
+    // an unrelated address
+    repo.user_recovery()
+        .add_session(
+            &mut rng,
+            &clock,
+            "John@example.com".to_owned(),
+            "Mozilla/5.0".to_owned(),
+            Some("192.0.2.1".parse().unwrap()),
+            "en".to_owned(),
+        )
+        .await
+        .unwrap();
+    repo.user_recovery()
+        .add_session(
+            &mut rng,
+            &clock,
+            "jane@example.com".to_owned(),
+            "Mozilla/5.0".to_owned(),
+            None,
+            "en".to_owned(),
+        )
+        .await
+        .unwrap();
+
+    // A login through the legacy SSO login API
+    let sso_login = repo
+        .compat_sso_login()
+        .add(
+            &mut rng,
+            &clock,
+            "login-token".to_owned(),
+            "https://client.example.com/callback".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+    repo.compat_sso_login()
+        .fulfill(&clock, sso_login, &browser_session)
+        .await
+        .unwrap();
+
+    // A login through an upstream provider
+    let provider = repo
+        .upstream_oauth_provider()
+        .add(
+            &mut rng,
+            &clock,
+            UpstreamOAuthProviderParams {
+                issuer: None,
+                human_name: None,
+                brand_name: None,
+                scope: Scope::from_iter([OPENID]),
+                token_endpoint_auth_method:
+                    mas_data_model::UpstreamOAuthProviderTokenAuthMethod::None,
+                token_endpoint_signing_alg: None,
+                id_token_signed_response_alg: JsonWebSignatureAlg::Rs256,
+                fetch_userinfo: true,
+                userinfo_signed_response_alg: None,
+                client_id: "client".to_owned(),
+                encrypted_client_secret: None,
+                claims_imports: mas_data_model::UpstreamOAuthProviderClaimsImports::default(),
+                authorization_endpoint_override: None,
+                token_endpoint_override: None,
+                userinfo_endpoint_override: None,
+                jwks_uri_override: None,
+                discovery_mode: mas_data_model::UpstreamOAuthProviderDiscoveryMode::Disabled,
+                pkce_mode: mas_data_model::UpstreamOAuthProviderPkceMode::Disabled,
+                response_mode: None,
+                additional_authorization_parameters: Vec::new(),
+                forward_login_hint: false,
+                ui_order: 0,
+                on_backchannel_logout:
+                    mas_data_model::UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                saml: None,
+            },
+        )
+        .await
+        .unwrap();
+    let link = repo
+        .upstream_oauth_link()
+        .add(
+            &mut rng,
+            &clock,
+            &provider,
+            "john-upstream".to_owned(),
+            None,
+        )
+        .await
+        .unwrap();
+    repo.upstream_oauth_link()
+        .associate_to_user(&link, &user)
+        .await
+        .unwrap();
+    let upstream_session = repo
+        .upstream_oauth_session()
+        .add(&mut rng, &clock, &provider, "state".to_owned(), None, None)
+        .await
+        .unwrap();
+    repo.upstream_oauth_session()
+        .complete_with_link(
+            &clock,
+            upstream_session,
+            &link,
+            Some("id-token".to_owned()),
+            Some(serde_json::json!({ "sub": "john-upstream" })),
+            None,
+            Some(serde_json::json!({ "name": "John" })),
+        )
+        .await
+        .unwrap();
+
+    // An event about the user sent to a webhook, and one about someone else
+    repo.webhook_delivery()
+        .add(
+            &mut rng,
+            &clock,
+            WebhookEvent::UserRegistered,
+            "https://hooks.example.com/".parse().unwrap(),
+            serde_json::json!({
+                "type": "user.registered",
+                "data": { "user_id": user.id, "username": "john" },
+            }),
+        )
+        .await
+        .unwrap();
+    repo.webhook_delivery()
+        .add(
+            &mut rng,
+            &clock,
+            WebhookEvent::UserRegistered,
+            "https://hooks.example.com/".parse().unwrap(),
+            serde_json::json!({
+                "type": "user.registered",
+                "data": { "user_id": Ulid::nil(), "username": "jane" },
+            }),
+        )
+        .await
+        .unwrap();
+
+    // Looking up an unknown export should return None
+    let export = repo.user_export().lookup(Ulid::nil()).await.unwrap();
+    assert!(export.is_none());
+
+    let export = repo
+        .user_export()
+        .add(&mut rng, &clock, &user)
+        .await
+        .unwrap();
+    assert_eq!(export.user_id, user.id);
+    assert_eq!(export.created_at, clock.now());
+    assert!(!export.is_completed());
+    assert!(export.archive.is_none());
+
+    let archive = export_user(&mut repo, &clock, &user).await.unwrap();
+    assert_eq!(archive.version, USER_ARCHIVE_VERSION);
+    assert_eq!(archive.exported_at, clock.now());
+    assert_eq!(archive.user.id, user.id);
+    assert_eq!(archive.user.username, "john");
+    assert_eq!(archive.emails.len(), 1);
+    assert_eq!(archive.emails[0].email, "john@example.com");
+    assert_eq!(archive.terms.len(), 1);
+    assert_eq!(archive.browser_sessions.len(), 1);
+    assert!(archive.passwords.is_empty());
+    assert!(archive.oauth2_sessions.is_empty());
+    assert!(archive.registration.is_none());
+
+    assert_eq!(archive.email_authentications.len(), 1);
+    assert_eq!(archive.email_authentications[0].email, "john@example.com");
+
+    assert_eq!(archive.recovery_sessions.len(), 1);
+    assert_eq!(archive.recovery_sessions[0].email, "John@example.com");
+    assert_eq!(
+        archive.recovery_sessions[0].ip_address,
+        Some("192.0.2.1".parse().unwrap())
+    );
+    assert_eq!(archive.recovery_sessions[0].user_agent, "Mozilla/5.0");
+
+    assert_eq!(archive.compat_sso_logins.len(), 1);
+    assert_eq!(
+        archive.compat_sso_logins[0].redirect_uri.as_str(),
+        "https://client.example.com/callback"
+    );
+    assert!(archive.compat_sso_logins[0].fulfilled_at.is_some());
+
+    assert_eq!(archive.upstream_oauth_links.len(), 1);
+    assert_eq!(archive.upstream_oauth_sessions.len(), 1);
+    assert_eq!(archive.upstream_oauth_sessions[0].link_id, Some(link.id));
+    assert_eq!(
+        archive.upstream_oauth_sessions[0].id_token_claims,
+        Some(serde_json::json!({ "sub": "john-upstream" }))
+    );
+    assert_eq!(
+        archive.upstream_oauth_sessions[0].userinfo,
+        Some(serde_json::json!({ "name": "John" }))
+    );
+
+    assert_eq!(archive.webhook_deliveries.len(), 1);
+    assert_eq!(
+        archive.webhook_deliveries[0].payload["data"]["username"],
+        "john"
+    );
+
+    clock.advance(Duration::try_minutes(1).unwrap());
+    let archive = serde_json::to_value(&archive).unwrap();
+    let export = repo
+        .user_export()
+        .complete(&clock, export, archive.clone())
+        .await
+        .unwrap();
+    assert!(export.is_completed());
+    assert_eq!(export.completed_at, Some(clock.now()));
+
+    let lookup = repo
+        .user_export()
+        .lookup(export.id)
+        .await
+        .unwrap()
+        .expect("export to be found in the database");
+    assert_eq!(lookup.id, export.id);
+    assert_eq!(lookup.user_id, user.id);
+    assert_eq!(lookup.completed_at, export.completed_at);
+    assert_eq!(lookup.archive, Some(archive.clone()));
+
+    // Completing the export a second time should fail
+    let res = repo.user_export().complete(&clock, lookup, archive).await;
+    assert!(res.is_err());
+
+    repo.save().await.unwrap();
+}
+
+/// Test erasing the personal data of a deactivated user
+#[sqlx::test(migrator = "crate::MIGRATOR")]
+async fn test_user_erase(pool: PgPool) {
+    let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+    let mut rng = ChaChaRng::seed_from_u64(42);
+    let clock = MockClock::default();
+
+    let user = repo
+        .user()
+        .add(&mut rng, &clock, "john".to_owned())
+        .await
+        .unwrap();
+    repo.user_email()
+        .add(&mut rng, &clock, &user, "john@example.com".to_owned())
+        .await
+        .unwrap();
+    repo.user_password()
+        .add(&mut rng, &clock, &user, 1, "hashed".to_owned(), None)
+        .await
+        .unwrap();
+    let session = repo
+        .browser_session()
+        .add(&mut rng, &clock, &user, Some("Mozilla/5.0".to_owned()))
+        .await
+        .unwrap();
+
+    let all = UserFilter::new();
+    let erased = all.erased_only();
+    let non_erased = all.non_erased_only();
+
+    // Active users can't be erased
+    let res = repo.user().erase(&clock, user.clone()).await;
+    assert!(res.is_err());
+
+    let user = repo.user().deactivate(&clock, user).await.unwrap();
+    let deactivated_at = user.deactivated_at.unwrap();
+
+    let before = all.with_deactivated_before(deactivated_at);
+    let after = all.with_deactivated_before(deactivated_at + Duration::try_minutes(1).unwrap());
+    assert_eq!(repo.user().count(before).await.unwrap(), 0);
+    assert_eq!(repo.user().count(after).await.unwrap(), 1);
+    assert_eq!(repo.user().count(non_erased).await.unwrap(), 1);
+    assert_eq!(repo.user().count(erased).await.unwrap(), 0);
+
+    clock.advance(Duration::try_minutes(1).unwrap());
+    let user = repo.user().erase(&clock, user).await.unwrap();
+    assert_eq!(user.erased_at, Some(clock.now()));
+
+    assert_eq!(repo.user().count(non_erased).await.unwrap(), 0);
+    assert_eq!(repo.user().count(erased).await.unwrap(), 1);
+
+    // Erasing a second time is a no-op
+    let user = repo.user().erase(&clock, user).await.unwrap();
+    assert_eq!(user.erased_at, Some(clock.now()));
+
+    // The username is still reserved
+    assert!(repo.user().exists("john").await.unwrap());
+    let lookup = repo
+        .user()
+        .find_by_username("john")
+        .await
+        .unwrap()
+        .expect("user to be found in the database");
+    assert_eq!(lookup.erased_at, user.erased_at);
+
+    // The personal data is gone
+    let emails = repo
+        .user_email()
+        .count(UserEmailFilter::new().for_user(&user))
+        .await
+        .unwrap();
+    assert_eq!(emails, 0);
+    assert!(repo.user_password().active(&user).await.unwrap().is_none());
+    let session = repo
+        .browser_session()
+        .lookup(session.id)
+        .await
+        .unwrap()
+        .expect("session to be found in the database");
+    assert!(session.user_agent.is_none());
+    assert!(session.last_active_ip.is_none());
+
+    repo.save().await.unwrap();
+}
@@ -0,0 +1,459 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{Clock, User, UserRecoveryCode, UserTotp};
+use mas_storage::user::UserTotpRepository;
+use rand::RngCore;
+use sqlx::PgConnection;
+use ulid::Ulid;
+use uuid::Uuid;
+
+use crate::{DatabaseError, tracing::ExecuteExt};
+
+/// An implementation of [`UserTotpRepository`] for a PostgreSQL connection
+pub struct PgUserTotpRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgUserTotpRepository<'c> {
+    /// Create a new [`PgUserTotpRepository`] from an active PostgreSQL
+    /// connection
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+}
+
+struct UserTotpLookup {
+    user_totp_id: Uuid,
+    user_id: Uuid,
+    encrypted_secret: String,
+    created_at: DateTime<Utc>,
+    confirmed_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    last_used_step: Option<i64>,
+}
+
+impl From<UserTotpLookup> for UserTotp {
+    fn from(value: UserTotpLookup) -> Self {
+        UserTotp {
+            id: value.user_totp_id.into(),
+            user_id: value.user_id.into(),
+            encrypted_secret: value.encrypted_secret,
+            created_at: value.created_at,
+            confirmed_at: value.confirmed_at,
+            last_used_at: value.last_used_at,
+            last_used_step: value.last_used_step,
+        }
+    }
+}
+
+struct UserRecoveryCodeLookup {
+    user_recovery_code_id: Uuid,
+    user_id: Uuid,
+    encrypted_code: String,
+    created_at: DateTime<Utc>,
+    used_at: Option<DateTime<Utc>>,
+}
+
+impl From<UserRecoveryCodeLookup> for UserRecoveryCode {
+    fn from(value: UserRecoveryCodeLookup) -> Self {
+        UserRecoveryCode {
+            id: value.user_recovery_code_id.into(),
+            user_id: value.user_id.into(),
+            encrypted_code: value.encrypted_code,
+            created_at: value.created_at,
+            used_at: value.used_at,
+        }
+    }
+}
+
+#[async_trait]
+impl UserTotpRepository for PgUserTotpRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.user_totp.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            user_totp.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserTotp>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserTotpLookup,
+            r#"
+                SELECT user_totp_id
+                     , user_id
+                     , encrypted_secret
+                     , created_at
+                     , confirmed_at
+                     , last_used_at
+                     , last_used_step
+                FROM user_totps
+
+                WHERE user_totp_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserTotp::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.find_for_user",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn find_for_user(&mut self, user: &User) -> Result<Option<UserTotp>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserTotpLookup,
+            r#"
+                SELECT user_totp_id
+                     , user_id
+                     , encrypted_secret
+                     , created_at
+                     , confirmed_at
+                     , last_used_at
+                     , last_used_step
+                FROM user_totps
+
+                WHERE user_id = $1
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        Ok(res.map(UserTotp::from))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.add",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+            user_totp.id,
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        encrypted_secret: String,
+    ) -> Result<UserTotp, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("user_totp.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_totps
+                    (user_totp_id, user_id, encrypted_secret, created_at)
+                VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::from(id),
+            Uuid::from(user.id),
+            &encrypted_secret,
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(UserTotp {
+            id,
+            user_id: user.id,
+            encrypted_secret,
+            created_at,
+            confirmed_at: None,
+            last_used_at: None,
+            last_used_step: None,
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.confirm",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_totp.id,
+        ),
+        err,
+    )]
+    async fn confirm(
+        &mut self,
+        clock: &dyn Clock,
+        mut user_totp: UserTotp,
+        step: i64,
+    ) -> Result<UserTotp, Self::Error> {
+        let confirmed_at = clock.now();
+
+        // The code used to confirm the enrolment can't be used again to log in
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_totps
+                SET confirmed_at = $2
+                  , last_used_at = $2
+                  , last_used_step = $3
+                WHERE user_totp_id = $1
+                  AND confirmed_at IS NULL
+            "#,
+            Uuid::from(user_totp.id),
+            confirmed_at,
+            step,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        user_totp.confirmed_at = Some(confirmed_at);
+        user_totp.last_used_at = Some(confirmed_at);
+        user_totp.last_used_step = Some(step);
+        Ok(user_totp)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.record_use",
+        skip_all,
+        fields(
+            db.query.text,
+            %user_totp.id,
+        ),
+        err,
+    )]
+    async fn record_use(
+        &mut self,
+        clock: &dyn Clock,
+        mut user_totp: UserTotp,
+        step: i64,
+    ) -> Result<Option<UserTotp>, Self::Error> {
+        let last_used_at = clock.now();
+
+        // Only move the step forward, so that concurrent requests can't both
+        // use the same code
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_totps
+                SET last_used_at = $2
+                  , last_used_step = $3
+                WHERE user_totp_id = $1
+                  AND (last_used_step IS NULL OR last_used_step < $3)
+            "#,
+            Uuid::from(user_totp.id),
+            last_used_at,
+            step,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        if res.rows_affected() == 0 {
+            return Ok(None);
+        }
+
+        user_totp.last_used_at = Some(last_used_at);
+        user_totp.last_used_step = Some(step);
+        Ok(Some(user_totp))
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.remove",
+        skip_all,
+        fields(
+            db.query.text,
+            user.id = %user_totp.user_id,
+            %user_totp.id,
+        ),
+        err,
+    )]
+    async fn remove(&mut self, user_totp: UserTotp) -> Result<(), Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM user_totps
+                WHERE user_totp_id = $1
+            "#,
+            Uuid::from(user_totp.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.replace_recovery_codes",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn replace_recovery_codes(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        encrypted_codes: Vec<String>,
+    ) -> Result<Vec<UserRecoveryCode>, Self::Error> {
+        self.remove_recovery_codes(user).await?;
+
+        let created_at = clock.now();
+        let recovery_codes: Vec<UserRecoveryCode> = encrypted_codes
+            .into_iter()
+            .map(|encrypted_code| UserRecoveryCode {
+                id: Ulid::from_datetime_with_source(created_at.into(), rng),
+                user_id: user.id,
+                encrypted_code,
+                created_at,
+                used_at: None,
+            })
+            .collect();
+
+        let ids: Vec<Uuid> = recovery_codes.iter().map(|c| Uuid::from(c.id)).collect();
+        let codes: Vec<String> = recovery_codes
+            .iter()
+            .map(|c| c.encrypted_code.clone())
+            .collect();
+
+        sqlx::query!(
+            r#"
+                INSERT INTO user_recovery_codes
+                    (user_recovery_code_id, user_id, encrypted_code, created_at)
+                SELECT id, $2, code, $4
+                FROM UNNEST($1::uuid[], $3::text[]) AS t(id, code)
+            "#,
+            &ids,
+            Uuid::from(user.id),
+            &codes,
+            created_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(recovery_codes)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.unused_recovery_codes",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn unused_recovery_codes(
+        &mut self,
+        user: &User,
+    ) -> Result<Vec<UserRecoveryCode>, Self::Error> {
+        let res = sqlx::query_as!(
+            UserRecoveryCodeLookup,
+            r#"
+                SELECT user_recovery_code_id
+                     , user_id
+                     , encrypted_code
+                     , created_at
+                     , used_at
+                FROM user_recovery_codes
+
+                WHERE user_id = $1
+                  AND used_at IS NULL
+
+                ORDER BY user_recovery_code_id ASC
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        Ok(res.into_iter().map(UserRecoveryCode::from).collect())
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.consume_recovery_code",
+        skip_all,
+        fields(
+            db.query.text,
+            user.id = %recovery_code.user_id,
+            %recovery_code.id,
+        ),
+        err,
+    )]
+    async fn consume_recovery_code(
+        &mut self,
+        clock: &dyn Clock,
+        mut recovery_code: UserRecoveryCode,
+    ) -> Result<UserRecoveryCode, Self::Error> {
+        let used_at = clock.now();
+
+        // The update will not affect any rows if the code was already used,
+        // which will raise an error
+        let res = sqlx::query!(
+            r#"
+                UPDATE user_recovery_codes
+                SET used_at = $2
+                WHERE user_recovery_code_id = $1
+                  AND used_at IS NULL
+            "#,
+            Uuid::from(recovery_code.id),
+            used_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        recovery_code.used_at = Some(used_at);
+        Ok(recovery_code)
+    }
+
+    #[tracing::instrument(
+        name = "db.user_totp.remove_recovery_codes",
+        skip_all,
+        fields(
+            db.query.text,
+            %user.id,
+        ),
+        err,
+    )]
+    async fn remove_recovery_codes(&mut self, user: &User) -> Result<usize, Self::Error> {
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM user_recovery_codes
+                WHERE user_id = $1
+            "#,
+            Uuid::from(user.id),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(res.rows_affected().try_into().unwrap_or(usize::MAX))
+    }
+}
@@ -0,0 +1,689 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! A module containing the PostgreSQL implementation of the webhook
+//! deliveries
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{
+    Clock, WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryState, WebhookEvent,
+};
+use mas_storage::{
+    Page, Pagination,
+    pagination::Node,
+    webhook::{WebhookDeliveryFilter, WebhookDeliveryRepository},
+};
+use rand::RngCore;
+use sea_query::{Expr, PostgresQueryBuilder, Query, enum_def, extension::postgres::PgExpr};
+use sea_query_binder::SqlxBinder;
+use serde_json::Value;
+use sqlx::{PgConnection, types::Json};
+use ulid::Ulid;
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    DatabaseError, DatabaseInconsistencyError,
+    filter::{Filter, StatementExt},
+    iden::WebhookDeliveries,
+    pagination::QueryBuilderExt,
+    tracing::ExecuteExt,
+};
+
+/// An implementation of [`WebhookDeliveryRepository`] for a PostgreSQL
+/// connection
+pub struct PgWebhookDeliveryRepository<'c> {
+    conn: &'c mut PgConnection,
+}
+
+impl<'c> PgWebhookDeliveryRepository<'c> {
+    /// Create a new [`PgWebhookDeliveryRepository`] from an active PostgreSQL
+    /// connection
+    #[must_use]
+    pub fn new(conn: &'c mut PgConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Load the attempts of the given deliveries, grouped by delivery ID and
+    /// ordered from oldest to newest
+    async fn load_attempts(
+        &mut self,
+        ids: &[Uuid],
+    ) -> Result<HashMap<Ulid, Vec<WebhookDeliveryAttempt>>, DatabaseError> {
+        let rows = sqlx::query_as!(
+            WebhookDeliveryAttemptLookup,
+            r#"
+                SELECT webhook_delivery_attempt_id
+                     , webhook_delivery_id
+                     , created_at
+                     , status_code
+                     , error
+                FROM webhook_delivery_attempts
+                WHERE webhook_delivery_id = ANY($1)
+                ORDER BY webhook_delivery_attempt_id ASC
+            "#,
+            ids,
+        )
+        .traced()
+        .fetch_all(&mut *self.conn)
+        .await?;
+
+        let mut attempts: HashMap<Ulid, Vec<WebhookDeliveryAttempt>> = HashMap::new();
+        for row in rows {
+            let delivery_id = Ulid::from(row.webhook_delivery_id);
+            attempts
+                .entry(delivery_id)
+                .or_default()
+                .push(row.try_into()?);
+        }
+
+        Ok(attempts)
+    }
+}
+
+#[derive(sqlx::FromRow)]
+#[enum_def]
+struct WebhookDeliveryLookup {
+    webhook_delivery_id: Uuid,
+    created_at: DateTime<Utc>,
+    event: String,
+    url: String,
+    payload: Json<Value>,
+    delivered_at: Option<DateTime<Utc>>,
+    failed_at: Option<DateTime<Utc>>,
+}
+
+impl Node<Ulid> for WebhookDeliveryLookup {
+    fn cursor(&self) -> Ulid {
+        self.webhook_delivery_id.into()
+    }
+}
+
+impl TryFrom<WebhookDeliveryLookup> for WebhookDelivery {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: WebhookDeliveryLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.webhook_delivery_id);
+        let event: WebhookEvent = value.event.parse().map_err(|e| {
+            DatabaseInconsistencyError::on("webhook_deliveries")
+                .column("event")
+                .row(id)
+                .source(e)
+        })?;
+
+        let url: Url = value.url.parse().map_err(|e| {
+            DatabaseInconsistencyError::on("webhook_deliveries")
+                .column("url")
+                .row(id)
+                .source(e)
+        })?;
+
+        let state = match (value.delivered_at, value.failed_at) {
+            (None, None) => WebhookDeliveryState::Pending,
+            (Some(delivered_at), None) => WebhookDeliveryState::Delivered { delivered_at },
+            (None, Some(failed_at)) => WebhookDeliveryState::Failed { failed_at },
+            (Some(_), Some(_)) => {
+                return Err(DatabaseInconsistencyError::on("webhook_deliveries")
+                    .column("failed_at")
+                    .row(id));
+            }
+        };
+
+        Ok(WebhookDelivery {
+            id,
+            created_at: value.created_at,
+            state,
+            event,
+            url,
+            payload: value.payload.0,
+            attempts: Vec::new(),
+        })
+    }
+}
+
+struct WebhookDeliveryAttemptLookup {
+    webhook_delivery_attempt_id: Uuid,
+    webhook_delivery_id: Uuid,
+    created_at: DateTime<Utc>,
+    status_code: Option<i32>,
+    error: Option<String>,
+}
+
+impl TryFrom<WebhookDeliveryAttemptLookup> for WebhookDeliveryAttempt {
+    type Error = DatabaseInconsistencyError;
+
+    fn try_from(value: WebhookDeliveryAttemptLookup) -> Result<Self, Self::Error> {
+        let id = Ulid::from(value.webhook_delivery_attempt_id);
+        let status_code = value
+            .status_code
+            .map(u16::try_from)
+            .transpose()
+            .map_err(|e| {
+                DatabaseInconsistencyError::on("webhook_delivery_attempts")
+                    .column("status_code")
+                    .row(id)
+                    .source(e)
+            })?;
+
+        Ok(WebhookDeliveryAttempt {
+            id,
+            created_at: value.created_at,
+            status_code,
+            error: value.error,
+        })
+    }
+}
+
+impl Filter for WebhookDeliveryFilter<'_> {
+    fn generate_condition(&self, _has_joins: bool) -> impl sea_query::IntoCondition {
+        sea_query::Condition::all()
+            .add_option(self.event().map(|event| {
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::Event)).eq(event.as_str())
+            }))
+            .add_option(self.state().map(|state| {
+                let delivered_at =
+                    Expr::col((WebhookDeliveries::Table, WebhookDeliveries::DeliveredAt));
+                let failed_at = Expr::col((WebhookDeliveries::Table, WebhookDeliveries::FailedAt));
+                if state.is_delivered() {
+                    delivered_at.is_not_null()
+                } else if state.is_failed() {
+                    failed_at.is_not_null()
+                } else {
+                    delivered_at.is_null().and(failed_at.is_null())
+                }
+            }))
+            .add_option(self.user().map(|user| {
+                // All the events carry the ID of the user they are about
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::Payload))
+                    .get_json_field("data")
+                    .cast_json_field("user_id")
+                    .eq(user.id.to_string())
+            }))
+    }
+}
+
+#[async_trait]
+impl WebhookDeliveryRepository for PgWebhookDeliveryRepository<'_> {
+    type Error = DatabaseError;
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.lookup",
+        skip_all,
+        fields(
+            db.query.text,
+            webhook_delivery.id = %id,
+        ),
+        err,
+    )]
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<WebhookDelivery>, Self::Error> {
+        let res = sqlx::query_as!(
+            WebhookDeliveryLookup,
+            r#"
+                SELECT webhook_delivery_id
+                     , created_at
+                     , event
+                     , url
+                     , payload as "payload: Json<Value>"
+                     , delivered_at
+                     , failed_at
+                FROM webhook_deliveries
+                WHERE webhook_delivery_id = $1
+            "#,
+            Uuid::from(id),
+        )
+        .traced()
+        .fetch_optional(&mut *self.conn)
+        .await?;
+
+        let Some(res) = res else { return Ok(None) };
+
+        let mut delivery: WebhookDelivery = res.try_into()?;
+        let mut attempts = self.load_attempts(&[Uuid::from(id)]).await?;
+        delivery.attempts = attempts.remove(&id).unwrap_or_default();
+
+        Ok(Some(delivery))
+    }
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.add",
+        skip_all,
+        fields(
+            db.query.text,
+            webhook_delivery.id,
+            webhook_delivery.event = %event,
+            webhook_delivery.url = %url,
+        ),
+        err,
+    )]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        event: WebhookEvent,
+        url: Url,
+        payload: Value,
+    ) -> Result<WebhookDelivery, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("webhook_delivery.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO webhook_deliveries
+                    ( webhook_delivery_id
+                    , created_at
+                    , event
+                    , url
+                    , payload
+                    )
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            Uuid::from(id),
+            created_at,
+            event.as_str(),
+            url.as_str(),
+            payload,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        Ok(WebhookDelivery {
+            id,
+            created_at,
+            state: WebhookDeliveryState::Pending,
+            event,
+            url,
+            payload,
+            attempts: Vec::new(),
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.add_attempt",
+        skip_all,
+        fields(
+            db.query.text,
+            %delivery.id,
+            webhook_delivery_attempt.id,
+            webhook_delivery_attempt.status_code = status_code,
+        ),
+        err,
+    )]
+    async fn add_attempt(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        mut delivery: WebhookDelivery,
+        status_code: Option<u16>,
+        error: Option<String>,
+    ) -> Result<WebhookDelivery, Self::Error> {
+        let created_at = clock.now();
+        let id = Ulid::from_datetime_with_source(created_at.into(), rng);
+        tracing::Span::current().record("webhook_delivery_attempt.id", tracing::field::display(id));
+
+        sqlx::query!(
+            r#"
+                INSERT INTO webhook_delivery_attempts
+                    ( webhook_delivery_attempt_id
+                    , webhook_delivery_id
+                    , created_at
+                    , status_code
+                    , error
+                    )
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            Uuid::from(id),
+            Uuid::from(delivery.id),
+            created_at,
+            status_code.map(i32::from),
+            error.as_deref(),
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        delivery.attempts.push(WebhookDeliveryAttempt {
+            id,
+            created_at,
+            status_code,
+            error,
+        });
+
+        Ok(delivery)
+    }
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.mark_as_delivered",
+        skip_all,
+        fields(
+            db.query.text,
+            %delivery.id,
+        ),
+        err,
+    )]
+    async fn mark_as_delivered(
+        &mut self,
+        clock: &dyn Clock,
+        mut delivery: WebhookDelivery,
+    ) -> Result<WebhookDelivery, Self::Error> {
+        let delivered_at = clock.now();
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE webhook_deliveries
+                SET delivered_at = $2
+                WHERE webhook_delivery_id = $1
+                  AND delivered_at IS NULL
+                  AND failed_at IS NULL
+            "#,
+            Uuid::from(delivery.id),
+            delivered_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        delivery.state = delivery
+            .state
+            .deliver(delivered_at)
+            .map_err(DatabaseError::to_invalid_operation)?;
+
+        Ok(delivery)
+    }
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.mark_as_failed",
+        skip_all,
+        fields(
+            db.query.text,
+            %delivery.id,
+        ),
+        err,
+    )]
+    async fn mark_as_failed(
+        &mut self,
+        clock: &dyn Clock,
+        mut delivery: WebhookDelivery,
+    ) -> Result<WebhookDelivery, Self::Error> {
+        let failed_at = clock.now();
+
+        let res = sqlx::query!(
+            r#"
+                UPDATE webhook_deliveries
+                SET failed_at = $2
+                WHERE webhook_delivery_id = $1
+                  AND delivered_at IS NULL
+                  AND failed_at IS NULL
+            "#,
+            Uuid::from(delivery.id),
+            failed_at,
+        )
+        .traced()
+        .execute(&mut *self.conn)
+        .await?;
+
+        DatabaseError::ensure_affected_rows(&res, 1)?;
+
+        delivery.state = delivery
+            .state
+            .fail(failed_at)
+            .map_err(DatabaseError::to_invalid_operation)?;
+
+        Ok(delivery)
+    }
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.list",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn list(
+        &mut self,
+        filter: WebhookDeliveryFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<WebhookDelivery>, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr_as(
+                Expr::col((
+                    WebhookDeliveries::Table,
+                    WebhookDeliveries::WebhookDeliveryId,
+                )),
+                WebhookDeliveryLookupIden::WebhookDeliveryId,
+            )
+            .expr_as(
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::CreatedAt)),
+                WebhookDeliveryLookupIden::CreatedAt,
+            )
+            .expr_as(
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::Event)),
+                WebhookDeliveryLookupIden::Event,
+            )
+            .expr_as(
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::Url)),
+                WebhookDeliveryLookupIden::Url,
+            )
+            .expr_as(
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::Payload)),
+                WebhookDeliveryLookupIden::Payload,
+            )
+            .expr_as(
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::DeliveredAt)),
+                WebhookDeliveryLookupIden::DeliveredAt,
+            )
+            .expr_as(
+                Expr::col((WebhookDeliveries::Table, WebhookDeliveries::FailedAt)),
+                WebhookDeliveryLookupIden::FailedAt,
+            )
+            .from(WebhookDeliveries::Table)
+            .apply_filter(filter)
+            .generate_pagination(
+                (
+                    WebhookDeliveries::Table,
+                    WebhookDeliveries::WebhookDeliveryId,
+                ),
+                pagination,
+            )
+            .build_sqlx(PostgresQueryBuilder);
+
+        let edges: Vec<WebhookDeliveryLookup> = sqlx::query_as_with(&sql, arguments)
+            .traced()
+            .fetch_all(&mut *self.conn)
+            .await?;
+
+        let page = pagination
+            .process(edges)
+            .try_map(WebhookDelivery::try_from)?;
+
+        let ids: Vec<Uuid> = page
+            .edges
+            .iter()
+            .map(|edge| Uuid::from(edge.node.id))
+            .collect();
+        let mut attempts = self.load_attempts(&ids).await?;
+
+        Ok(page.map(|mut delivery| {
+            delivery.attempts = attempts.remove(&delivery.id).unwrap_or_default();
+            delivery
+        }))
+    }
+
+    #[tracing::instrument(
+        name = "db.webhook_delivery.count",
+        skip_all,
+        fields(
+            db.query.text,
+        ),
+        err,
+    )]
+    async fn count(&mut self, filter: WebhookDeliveryFilter<'_>) -> Result<usize, Self::Error> {
+        let (sql, arguments) = Query::select()
+            .expr(
+                Expr::col((
+                    WebhookDeliveries::Table,
+                    WebhookDeliveries::WebhookDeliveryId,
+                ))
+                .count(),
+            )
+            .from(WebhookDeliveries::Table)
+            .apply_filter(filter)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let count: i64 = sqlx::query_scalar_with(&sql, arguments)
+            .traced()
+            .fetch_one(&mut *self.conn)
+            .await?;
+
+        count
+            .try_into()
+            .map_err(DatabaseError::to_invalid_operation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use mas_data_model::{WebhookEvent, clock::MockClock};
+    use mas_storage::{
+        Pagination, RepositoryAccess,
+        webhook::{WebhookDeliveryFilter, WebhookDeliveryRepository},
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use serde_json::json;
+    use sqlx::PgPool;
+    use url::Url;
+
+    use crate::PgRepository;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn test_webhook_deliveries(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        let url = Url::parse("https://hooks.example.com/mas").unwrap();
+
+        let all = WebhookDeliveryFilter::new();
+        assert_eq!(repo.webhook_delivery().count(all).await.unwrap(), 0);
+
+        let registered = repo
+            .webhook_delivery()
+            .add(
+                &mut rng,
+                &clock,
+                WebhookEvent::UserRegistered,
+                url.clone(),
+                json!({ "type": "user.registered" }),
+            )
+            .await
+            .unwrap();
+        assert!(registered.state.is_pending());
+        assert!(registered.attempts.is_empty());
+
+        clock.advance(Duration::minutes(1));
+
+        let deactivated = repo
+            .webhook_delivery()
+            .add(
+                &mut rng,
+                &clock,
+                WebhookEvent::UserDeactivated,
+                url.clone(),
+                json!({ "type": "user.deactivated" }),
+            )
+            .await
+            .unwrap();
+
+        // Record a failed attempt, then a successful one
+        let registered = repo
+            .webhook_delivery()
+            .add_attempt(
+                &mut rng,
+                &clock,
+                registered,
+                Some(503),
+                Some("unexpected status code".to_owned()),
+            )
+            .await
+            .unwrap();
+        clock.advance(Duration::minutes(1));
+        let registered = repo
+            .webhook_delivery()
+            .add_attempt(&mut rng, &clock, registered, Some(200), None)
+            .await
+            .unwrap();
+        assert_eq!(registered.attempts.len(), 2);
+        assert!(!registered.attempts[0].is_success());
+        assert!(registered.attempts[1].is_success());
+
+        let registered = repo
+            .webhook_delivery()
+            .mark_as_delivered(&clock, registered)
+            .await
+            .unwrap();
+        assert!(!registered.state.is_pending());
+
+        // A delivery can't change state twice
+        let res = repo
+            .webhook_delivery()
+            .mark_as_failed(&clock, registered.clone())
+            .await;
+        assert!(res.is_err());
+
+        let deactivated = repo
+            .webhook_delivery()
+            .mark_as_failed(&clock, deactivated)
+            .await
+            .unwrap();
+
+        // Lookup includes the attempts
+        let lookup = repo
+            .webhook_delivery()
+            .lookup(registered.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lookup, registered);
+
+        // List and count with filters
+        assert_eq!(repo.webhook_delivery().count(all).await.unwrap(), 2);
+
+        let page = repo
+            .webhook_delivery()
+            .list(all, Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 2);
+        assert_eq!(page.edges[0].node, registered);
+        assert_eq!(page.edges[1].node, deactivated);
+
+        let filter = WebhookDeliveryFilter::new().with_event(WebhookEvent::UserDeactivated);
+        let page = repo
+            .webhook_delivery()
+            .list(filter, Pagination::first(10))
+            .await
+            .unwrap();
+        assert_eq!(page.edges.len(), 1);
+        assert_eq!(page.edges[0].node, deactivated);
+
+        let filter = WebhookDeliveryFilter::new().delivered_only();
+        assert_eq!(repo.webhook_delivery().count(filter).await.unwrap(), 1);
+
+        let filter = WebhookDeliveryFilter::new().failed_only();
+        assert_eq!(repo.webhook_delivery().count(filter).await.unwrap(), 1);
+
+        let filter = WebhookDeliveryFilter::new().pending_only();
+        assert_eq!(repo.webhook_delivery().count(filter).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Repositories to interact with the audit log
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mas_data_model::{AuditAction, AuditActor, AuditEvent, Clock, User};
+use rand_core::RngCore;
+use ulid::Ulid;
+
+use crate::{Page, Pagination, repository_impl};
+
+/// Filter parameters for listing audit events
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct AuditEventFilter<'a> {
+    actor_user: Option<&'a User>,
+    target_user: Option<&'a User>,
+    action: Option<AuditAction>,
+    created_before: Option<DateTime<Utc>>,
+    created_after: Option<DateTime<Utc>>,
+}
+
+impl<'a> AuditEventFilter<'a> {
+    /// Create a new [`AuditEventFilter`] with default values
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter for events performed by the given user
+    #[must_use]
+    pub fn for_actor_user(mut self, user: &'a User) -> Self {
+        self.actor_user = Some(user);
+        self
+    }
+
+    /// Get the actor user filter
+    ///
+    /// Returns [`None`] if no actor user filter was set
+    #[must_use]
+    pub fn actor_user(&self) -> Option<&User> {
+        self.actor_user
+    }
+
+    /// Filter for events affecting the given user
+    #[must_use]
+    pub fn for_target_user(mut self, user: &'a User) -> Self {
+        self.target_user = Some(user);
+        self
+    }
+
+    /// Get the target user filter
+    ///
+    /// Returns [`None`] if no target user filter was set
+    #[must_use]
+    pub fn target_user(&self) -> Option<&User> {
+        self.target_user
+    }
+
+    /// Filter for events of the given action
+    #[must_use]
+    pub fn with_action(mut self, action: AuditAction) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// Get the action filter
+    ///
+    /// Returns [`None`] if no action filter was set
+    #[must_use]
+    pub fn action(&self) -> Option<AuditAction> {
+        self.action
+    }
+
+    /// Only return events which happened before the given time
+    #[must_use]
+    pub fn with_created_before(mut self, created_before: DateTime<Utc>) -> Self {
+        self.created_before = Some(created_before);
+        self
+    }
+
+    /// Only return events which happened after the given time
+    #[must_use]
+    pub fn with_created_after(mut self, created_after: DateTime<Utc>) -> Self {
+        self.created_after = Some(created_after);
+        self
+    }
+
+    /// Get the created before filter
+    ///
+    /// Returns [`None`] if no created before filter was set
+    #[must_use]
+    pub fn created_before(&self) -> Option<DateTime<Utc>> {
+        self.created_before
+    }
+
+    /// Get the created after filter
+    ///
+    /// Returns [`None`] if no created after filter was set
+    #[must_use]
+    pub fn created_after(&self) -> Option<DateTime<Utc>> {
+        self.created_after
+    }
+}
+
+/// An [`AuditEventRepository`] helps interacting with the audit log saved in
+/// the storage backend.
+///
+/// The audit log is append-only: events can be recorded and queried, but
+/// never modified.
+#[async_trait]
+pub trait AuditEventRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Lookup an [`AuditEvent`] by its ID
+    ///
+    /// Returns `None` if no [`AuditEvent`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`AuditEvent`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<AuditEvent>, Self::Error>;
+
+    /// Record a new [`AuditEvent`]
+    ///
+    /// Returns the newly recorded [`AuditEvent`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `actor`: Who performed the action
+    /// * `action`: The action which was performed
+    /// * `target_user_id`: The ID of the user affected by the action, if any
+    /// * `target_id`: The ID of the object affected by the action, if any
+    /// * `details`: Additional, action-specific details
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    #[expect(clippy::too_many_arguments)]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        actor: AuditActor,
+        action: AuditAction,
+        target_user_id: Option<Ulid>,
+        target_id: Option<Ulid>,
+        details: serde_json::Value,
+    ) -> Result<AuditEvent, Self::Error>;
+
+    /// List [`AuditEvent`]s matching the given filter and pagination
+    /// parameters
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    /// * `pagination`: The pagination parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn list(
+        &mut self,
+        filter: AuditEventFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<AuditEvent>, Self::Error>;
+
+    /// Count the [`AuditEvent`]s matching the given filter
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn count(&mut self, filter: AuditEventFilter<'_>) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(AuditEventRepository:
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<AuditEvent>, Self::Error>;
+
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        actor: AuditActor,
+        action: AuditAction,
+        target_user_id: Option<Ulid>,
+        target_id: Option<Ulid>,
+        details: serde_json::Value,
+    ) -> Result<AuditEvent, Self::Error>;
+
+    async fn list(
+        &mut self,
+        filter: AuditEventFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<AuditEvent>, Self::Error>;
+
+    async fn count(&mut self, filter: AuditEventFilter<'_>) -> Result<usize, Self::Error>;
+);
@@ -8,7 +8,9 @@ use std::net::IpAddr;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mas_data_model::{BrowserSession, Clock, CompatSession, CompatSsoLogin, Device, User};
+use mas_data_model::{
+    BrowserSession, Clock, CompatSession, CompatSsoLogin, Device, Impersonation, User,
+};
 use rand_core::RngCore;
 use ulid::Ulid;
 
@@ -250,6 +252,30 @@ pub trait CompatSessionRepository: Send + Sync {
         human_name: Option<String>,
     ) -> Result<CompatSession, Self::Error>;
 
+    /// Start a new compat session on behalf of an admin impersonating a user
+    ///
+    /// Returns the newly created compat session
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user`: The user being impersonated
+    /// * `device`: The device ID of this session
+    /// * `impersonation`: Who impersonates the user, and until when
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_impersonation(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        device: Device,
+        impersonation: Impersonation,
+    ) -> Result<CompatSession, Self::Error>;
+
     /// End a compat session
     ///
     /// Returns the ended compat session
@@ -377,6 +403,15 @@ repository_impl!(CompatSessionRepository:
         human_name: Option<String>,
     ) -> Result<CompatSession, Self::Error>;
 
+    async fn add_impersonation(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        device: Device,
+        impersonation: Impersonation,
+    ) -> Result<CompatSession, Self::Error>;
+
     async fn finish(
         &mut self,
         clock: &dyn Clock,
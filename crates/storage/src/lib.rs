@@ -109,13 +109,16 @@ pub(crate) mod repository;
 mod utils;
 
 pub mod app_session;
+pub mod audit_event;
 pub mod compat;
 pub mod oauth2;
 pub mod personal;
 pub mod policy_data;
 pub mod queue;
+pub mod rate_limit;
 pub mod upstream_oauth2;
 pub mod user;
+pub mod webhook;
 
 pub use self::{
     pagination::{Page, Pagination},
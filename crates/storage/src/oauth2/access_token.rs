@@ -60,6 +60,8 @@ pub trait OAuth2AccessTokenRepository: Send + Sync {
     /// * `access_token`: The access token to add
     /// * `expires_after`: The duration after which the access token expires. If
     ///   [`None`] the access token never expires
+    /// * `dpop_jkt`: The JWK thumbprint of the DPoP key the access token is
+    ///   bound to, if any
     ///
     /// # Errors
     ///
@@ -71,6 +73,7 @@ pub trait OAuth2AccessTokenRepository: Send + Sync {
         session: &Session,
         access_token: String,
         expires_after: Option<Duration>,
+        dpop_jkt: Option<String>,
     ) -> Result<AccessToken, Self::Error>;
 
     /// Revoke an access token
@@ -136,6 +139,7 @@ repository_impl!(OAuth2AccessTokenRepository:
         session: &Session,
         access_token: String,
         expires_after: Option<Duration>,
+        dpop_jkt: Option<String>,
     ) -> Result<AccessToken, Self::Error>;
 
     async fn revoke(
@@ -15,7 +15,63 @@ use rand_core::RngCore;
 use ulid::Ulid;
 use url::Url;
 
-use crate::repository_impl;
+use crate::{Page, Pagination, repository_impl};
+
+/// Filter parameters for listing OAuth 2.0 clients
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct OAuth2ClientFilter<'a> {
+    is_static: Option<bool>,
+    token_endpoint_auth_method: Option<&'a OAuthClientAuthenticationMethod>,
+}
+
+impl<'a> OAuth2ClientFilter<'a> {
+    /// Create a new [`OAuth2ClientFilter`] with default values
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only return clients defined in the configuration
+    #[must_use]
+    pub const fn static_only(mut self) -> Self {
+        self.is_static = Some(true);
+        self
+    }
+
+    /// Only return clients registered at runtime
+    #[must_use]
+    pub const fn dynamic_only(mut self) -> Self {
+        self.is_static = Some(false);
+        self
+    }
+
+    /// Only return clients using the given token endpoint authentication
+    /// method
+    #[must_use]
+    pub const fn with_token_endpoint_auth_method(
+        mut self,
+        method: &'a OAuthClientAuthenticationMethod,
+    ) -> Self {
+        self.token_endpoint_auth_method = Some(method);
+        self
+    }
+
+    /// Get the static filter
+    ///
+    /// Returns `None` if the filter is not set
+    #[must_use]
+    pub const fn is_static(&self) -> Option<bool> {
+        self.is_static
+    }
+
+    /// Get the token endpoint authentication method filter
+    ///
+    /// Returns `None` if the filter is not set
+    #[must_use]
+    pub const fn token_endpoint_auth_method(&self) -> Option<&'a OAuthClientAuthenticationMethod> {
+        self.token_endpoint_auth_method
+    }
+}
 
 /// An [`OAuth2ClientRepository`] helps interacting with [`Client`] saved in the
 /// storage backend
@@ -62,6 +118,23 @@ pub trait OAuth2ClientRepository: Send + Sync {
         digest: &str,
     ) -> Result<Option<Client>, Self::Error>;
 
+    /// Find an OAuth client by its registration access token
+    ///
+    /// Returns `None` if no client has this registration access token
+    ///
+    /// # Parameters
+    ///
+    /// * `registration_access_token`: The registration access token of the
+    ///   client to find
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn find_by_registration_access_token(
+        &mut self,
+        registration_access_token: &str,
+    ) -> Result<Option<Client>, Self::Error>;
+
     /// Load a batch of OAuth clients by their IDs
     ///
     /// Returns a map of client IDs to clients. If a client does not exist, it
@@ -81,7 +154,8 @@ pub trait OAuth2ClientRepository: Send + Sync {
 
     /// Add a new OAuth client
     ///
-    /// Returns the client that was added
+    /// Returns the client that was added. The client is never static: clients
+    /// defined in the configuration file are added with [`Self::upsert_static`]
     ///
     /// # Parameters
     ///
@@ -109,6 +183,18 @@ pub trait OAuth2ClientRepository: Send + Sync {
     ///   when using the `client_secret_jwt` or `private_key_jwt` authentication
     ///   methods
     /// * `initiate_login_uri`: The URI used to initiate a login, if given
+    /// * `post_logout_redirect_uris`: The list of URIs to which the user can be
+    ///   redirected after logging out
+    /// * `backchannel_logout_uri`: The URI to which logout tokens are sent, if
+    ///   any
+    /// * `backchannel_logout_session_required`: Whether the logout tokens must
+    ///   include a `sid` claim
+    /// * `require_pushed_authorization_requests`: Whether the client must use
+    ///   the pushed authorization request endpoint
+    /// * `require_signed_request_object`: Whether the client must send its
+    ///   authorization requests as signed request objects
+    /// * `access_token_signed_response_alg`: The algorithm used to sign the
+    ///   access tokens. If none, the client gets opaque access tokens
     ///
     /// # Errors
     ///
@@ -135,6 +221,12 @@ pub trait OAuth2ClientRepository: Send + Sync {
         token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
         token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
         initiate_login_uri: Option<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
     ) -> Result<Client, Self::Error>;
 
     /// Add or replace a static client
@@ -149,6 +241,18 @@ pub trait OAuth2ClientRepository: Send + Sync {
     /// * `jwks`: The client JWKS, if any
     /// * `jwks_uri`: The client JWKS URI, if any
     /// * `redirect_uris`: The list of redirect URIs used by this client
+    /// * `post_logout_redirect_uris`: The list of URIs to which the user can be
+    ///   redirected after logging out
+    /// * `backchannel_logout_uri`: The URI to which logout tokens are sent, if
+    ///   any
+    /// * `backchannel_logout_session_required`: Whether the logout tokens must
+    ///   include a `sid` claim
+    /// * `require_pushed_authorization_requests`: Whether the client must use
+    ///   the pushed authorization request endpoint
+    /// * `require_signed_request_object`: Whether the client must send its
+    ///   authorization requests as signed request objects
+    /// * `access_token_signed_response_alg`: The algorithm used to sign the
+    ///   access tokens. If none, the client gets opaque access tokens
     ///
     /// # Errors
     ///
@@ -163,6 +267,137 @@ pub trait OAuth2ClientRepository: Send + Sync {
         jwks: Option<PublicJsonWebKeySet>,
         jwks_uri: Option<Url>,
         redirect_uris: Vec<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+    ) -> Result<Client, Self::Error>;
+
+    /// Update the metadata of a dynamically registered client
+    ///
+    /// The authentication method and the client secret can't be changed. The
+    /// metadata digest of the client is cleared, so that it is not reused by
+    /// subsequent registrations.
+    ///
+    /// Returns the updated client
+    ///
+    /// # Parameters
+    ///
+    /// * `client`: The client to update
+    /// * `redirect_uris`: The list of redirect URIs used by this client
+    /// * `application_type`: The application type of this client
+    /// * `grant_types`: The list of grant types this client can use
+    /// * `client_name`: The human-readable name of this client, if given
+    /// * `logo_uri`: The URI of the logo of this client, if given
+    /// * `client_uri`: The URI of a website of this client, if given
+    /// * `policy_uri`: The URI of the privacy policy of this client, if given
+    /// * `tos_uri`: The URI of the terms of service of this client, if given
+    /// * `jwks_uri`: The URI of the JWKS of this client, if given
+    /// * `jwks`: The JWKS of this client, if given
+    /// * `id_token_signed_response_alg`: The algorithm used to sign the ID
+    ///   token
+    /// * `userinfo_signed_response_alg`: The algorithm used to sign the user
+    ///   info. If none, the user info endpoint will not sign the response
+    /// * `token_endpoint_auth_signing_alg`: The algorithm used to sign the JWT
+    ///   when using the `client_secret_jwt` or `private_key_jwt` authentication
+    ///   methods
+    /// * `initiate_login_uri`: The URI used to initiate a login, if given
+    /// * `post_logout_redirect_uris`: The list of URIs to which the user can be
+    ///   redirected after logging out
+    /// * `backchannel_logout_uri`: The URI to which logout tokens are sent, if
+    ///   any
+    /// * `backchannel_logout_session_required`: Whether the logout tokens must
+    ///   include a `sid` claim
+    /// * `require_pushed_authorization_requests`: Whether the client must use
+    ///   the pushed authorization request endpoint
+    /// * `require_signed_request_object`: Whether the client must send its
+    ///   authorization requests as signed request objects
+    /// * `access_token_signed_response_alg`: The algorithm used to sign the
+    ///   access tokens. If none, the client gets opaque access tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    #[allow(clippy::too_many_arguments)]
+    async fn update_metadata(
+        &mut self,
+        client: Client,
+        redirect_uris: Vec<Url>,
+        application_type: Option<ApplicationType>,
+        grant_types: Vec<GrantType>,
+        client_name: Option<String>,
+        logo_uri: Option<Url>,
+        client_uri: Option<Url>,
+        policy_uri: Option<Url>,
+        tos_uri: Option<Url>,
+        jwks_uri: Option<Url>,
+        jwks: Option<PublicJsonWebKeySet>,
+        id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+        userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
+        token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
+        initiate_login_uri: Option<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+    ) -> Result<Client, Self::Error>;
+
+    /// Set the registration access token of a client, which lets it manage
+    /// its own registration through the client configuration endpoint
+    ///
+    /// # Parameters
+    ///
+    /// * `client`: The client to update
+    /// * `registration_access_token`: The new registration access token, or
+    ///   `None` to remove it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn set_registration_access_token(
+        &mut self,
+        client: &Client,
+        registration_access_token: Option<String>,
+    ) -> Result<(), Self::Error>;
+
+    /// Replace the client secret of a client
+    ///
+    /// Returns the updated client
+    ///
+    /// # Parameters
+    ///
+    /// * `client`: The client to update
+    /// * `encrypted_client_secret`: The new encrypted client secret
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn set_client_secret(
+        &mut self,
+        client: Client,
+        encrypted_client_secret: String,
+    ) -> Result<Client, Self::Error>;
+
+    /// Replace the redirect URIs of a client
+    ///
+    /// Returns the updated client
+    ///
+    /// # Parameters
+    ///
+    /// * `client`: The client to update
+    /// * `redirect_uris`: The new list of redirect URIs
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn set_redirect_uris(
+        &mut self,
+        client: Client,
+        redirect_uris: Vec<Url>,
     ) -> Result<Client, Self::Error>;
 
     /// List all static clients
@@ -172,6 +407,33 @@ pub trait OAuth2ClientRepository: Send + Sync {
     /// Returns [`Self::Error`] if the underlying repository fails
     async fn all_static(&mut self) -> Result<Vec<Client>, Self::Error>;
 
+    /// List [`Client`]s with the given filter and pagination
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter to apply
+    /// * `pagination`: The pagination parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn list(
+        &mut self,
+        filter: OAuth2ClientFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<Client>, Self::Error>;
+
+    /// Count the [`Client`]s with the given filter
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter to apply
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn count(&mut self, filter: OAuth2ClientFilter<'_>) -> Result<usize, Self::Error>;
+
     /// Delete a client
     ///
     /// # Parameters
@@ -207,6 +469,11 @@ repository_impl!(OAuth2ClientRepository:
         digest: &str,
     ) -> Result<Option<Client>, Self::Error>;
 
+    async fn find_by_registration_access_token(
+        &mut self,
+        registration_access_token: &str,
+    ) -> Result<Option<Client>, Self::Error>;
+
     async fn load_batch(
         &mut self,
         ids: BTreeSet<Ulid>,
@@ -233,6 +500,12 @@ repository_impl!(OAuth2ClientRepository:
         token_endpoint_auth_method: Option<OAuthClientAuthenticationMethod>,
         token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
         initiate_login_uri: Option<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
     ) -> Result<Client, Self::Error>;
 
     async fn upsert_static(
@@ -244,10 +517,67 @@ repository_impl!(OAuth2ClientRepository:
         jwks: Option<PublicJsonWebKeySet>,
         jwks_uri: Option<Url>,
         redirect_uris: Vec<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+    ) -> Result<Client, Self::Error>;
+
+    async fn update_metadata(
+        &mut self,
+        client: Client,
+        redirect_uris: Vec<Url>,
+        application_type: Option<ApplicationType>,
+        grant_types: Vec<GrantType>,
+        client_name: Option<String>,
+        logo_uri: Option<Url>,
+        client_uri: Option<Url>,
+        policy_uri: Option<Url>,
+        tos_uri: Option<Url>,
+        jwks_uri: Option<Url>,
+        jwks: Option<PublicJsonWebKeySet>,
+        id_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+        userinfo_signed_response_alg: Option<JsonWebSignatureAlg>,
+        token_endpoint_auth_signing_alg: Option<JsonWebSignatureAlg>,
+        initiate_login_uri: Option<Url>,
+        post_logout_redirect_uris: Vec<Url>,
+        backchannel_logout_uri: Option<Url>,
+        backchannel_logout_session_required: bool,
+        require_pushed_authorization_requests: bool,
+        require_signed_request_object: bool,
+        access_token_signed_response_alg: Option<JsonWebSignatureAlg>,
+    ) -> Result<Client, Self::Error>;
+
+    async fn set_registration_access_token(
+        &mut self,
+        client: &Client,
+        registration_access_token: Option<String>,
+    ) -> Result<(), Self::Error>;
+
+    async fn set_client_secret(
+        &mut self,
+        client: Client,
+        encrypted_client_secret: String,
+    ) -> Result<Client, Self::Error>;
+
+    async fn set_redirect_uris(
+        &mut self,
+        client: Client,
+        redirect_uris: Vec<Url>,
     ) -> Result<Client, Self::Error>;
 
     async fn all_static(&mut self) -> Result<Vec<Client>, Self::Error>;
 
+    async fn list(
+        &mut self,
+        filter: OAuth2ClientFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<Client>, Self::Error>;
+
+    async fn count(&mut self, filter: OAuth2ClientFilter<'_>) -> Result<usize, Self::Error>;
+
     async fn delete(&mut self, client: Client) -> Result<(), Self::Error>;
 
     async fn delete_by_id(&mut self, id: Ulid) -> Result<(), Self::Error>;
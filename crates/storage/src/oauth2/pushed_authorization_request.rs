@@ -0,0 +1,134 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use mas_data_model::{Client, Clock, PushedAuthorizationRequest};
+use rand_core::RngCore;
+use ulid::Ulid;
+
+use crate::repository_impl;
+
+/// An [`OAuth2PushedAuthorizationRequestRepository`] helps interacting with
+/// [`PushedAuthorizationRequest`] saved in the storage backend.
+#[async_trait]
+pub trait OAuth2PushedAuthorizationRequestRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Save a new pushed authorization request
+    ///
+    /// Returns the newly created pushed authorization request, valid for
+    /// [`PushedAuthorizationRequest::VALIDITY`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: A random number generator
+    /// * `clock`: The clock used to generate timestamps
+    /// * `client`: The client which pushed the request
+    /// * `request_uri`: The `request_uri` to hand out to the client
+    /// * `parameters`: The parameters of the authorization request
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        request_uri: String,
+        parameters: BTreeMap<String, String>,
+    ) -> Result<PushedAuthorizationRequest, Self::Error>;
+
+    /// Lookup a pushed authorization request by its ID
+    ///
+    /// Returns the pushed authorization request if found, [`None`] otherwise
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the pushed authorization request
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid)
+    -> Result<Option<PushedAuthorizationRequest>, Self::Error>;
+
+    /// Find a pushed authorization request by its `request_uri`
+    ///
+    /// Returns the pushed authorization request if found, [`None`] otherwise
+    ///
+    /// # Parameters
+    ///
+    /// * `request_uri`: The `request_uri` to search for
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn find_by_request_uri(
+        &mut self,
+        request_uri: &str,
+    ) -> Result<Option<PushedAuthorizationRequest>, Self::Error>;
+
+    /// Mark a pushed authorization request as used, so that it can't be used
+    /// again
+    ///
+    /// Returns the updated pushed authorization request
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `request`: The pushed authorization request to consume
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn consume(
+        &mut self,
+        clock: &dyn Clock,
+        request: PushedAuthorizationRequest,
+    ) -> Result<PushedAuthorizationRequest, Self::Error>;
+
+    /// Cleanup expired [`PushedAuthorizationRequest`]s
+    ///
+    /// Returns the number of requests removed
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock to use
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn cleanup_expired(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(OAuth2PushedAuthorizationRequestRepository:
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        request_uri: String,
+        parameters: BTreeMap<String, String>,
+    ) -> Result<PushedAuthorizationRequest, Self::Error>;
+
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<PushedAuthorizationRequest>, Self::Error>;
+
+    async fn find_by_request_uri(
+        &mut self,
+        request_uri: &str,
+    ) -> Result<Option<PushedAuthorizationRequest>, Self::Error>;
+
+    async fn consume(
+        &mut self,
+        clock: &dyn Clock,
+        request: PushedAuthorizationRequest,
+    ) -> Result<PushedAuthorizationRequest, Self::Error>;
+
+    async fn cleanup_expired(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+);
@@ -8,7 +8,7 @@ use std::net::IpAddr;
 
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use mas_data_model::{BrowserSession, Client, Clock, Device, Session, User};
+use mas_data_model::{BrowserSession, Client, Clock, Device, Impersonation, Session, User};
 use oauth2_types::scope::Scope;
 use rand_core::RngCore;
 use ulid::Ulid;
@@ -363,8 +363,66 @@ pub trait OAuth2SessionRepository: Send + Sync {
         self.add(rng, clock, client, None, None, scope).await
     }
 
+    /// Create a new [`Session`] for a [`Client`] out of an existing
+    /// [`Session`], through a token exchange
+    ///
+    /// The new session belongs to the same user and browser session as the
+    /// parent, and records the parent as its lineage.
+    ///
+    /// Returns the newly created [`Session`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `client`: The [`Client`] which created the [`Session`]
+    /// * `parent`: The [`Session`] of the token which was exchanged
+    /// * `scope`: The [`Scope`] of the [`Session`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_from_token_exchange(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        parent: &Session,
+        scope: Scope,
+    ) -> Result<Session, Self::Error>;
+
+    /// Create a new [`Session`] for a [`User`], on behalf of an admin
+    /// impersonating them
+    ///
+    /// Returns the newly created [`Session`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `client`: The [`Client`] the [`Session`] is for
+    /// * `user`: The [`User`] being impersonated
+    /// * `scope`: The [`Scope`] of the [`Session`]
+    /// * `impersonation`: Who impersonates the user, and until when
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_impersonation(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        user: &User,
+        scope: Scope,
+        impersonation: Impersonation,
+    ) -> Result<Session, Self::Error>;
+
     /// Mark a [`Session`] as finished
     ///
+    /// Sessions obtained by exchanging a token of this session are finished as
+    /// well.
+    ///
     /// Returns the updated [`Session`]
     ///
     /// # Parameters
@@ -380,7 +438,10 @@ pub trait OAuth2SessionRepository: Send + Sync {
 
     /// Mark all the [`Session`] matching the given filter as finished
     ///
-    /// Returns the number of sessions affected
+    /// Sessions obtained by exchanging a token of those sessions are finished
+    /// as well.
+    ///
+    /// Returns the number of sessions matching the filter which were affected
     ///
     /// # Parameters
     ///
@@ -493,6 +554,25 @@ repository_impl!(OAuth2SessionRepository:
         scope: Scope,
     ) -> Result<Session, Self::Error>;
 
+    async fn add_from_token_exchange(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        parent: &Session,
+        scope: Scope,
+    ) -> Result<Session, Self::Error>;
+
+    async fn add_impersonation(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        client: &Client,
+        user: &User,
+        scope: Scope,
+        impersonation: Impersonation,
+    ) -> Result<Session, Self::Error>;
+
     async fn finish(&mut self, clock: &dyn Clock, session: Session)
         -> Result<Session, Self::Error>;
 
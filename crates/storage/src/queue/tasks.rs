@@ -5,10 +5,11 @@
 
 use chrono::{DateTime, Utc};
 use mas_data_model::{
-    BrowserSession, CompatSession, Device, Session, User, UserEmailAuthentication,
-    UserRecoverySession,
+    BrowserSession, CompatSession, Device, SecurityNotification, Session, User, UserEmail,
+    UserEmailAuthentication, UserExport, UserRecoverySession, WebhookDelivery, WebhookEvent,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use ulid::Ulid;
 
 use super::InsertableJob;
@@ -218,6 +219,226 @@ impl InsertableJob for SyncDevicesJob {
     const QUEUE_NAME: &'static str = "sync-devices";
 }
 
+/// A job which notifies the clients affected by the end of a browser session
+/// or of an OAuth 2.0 session through OpenID Connect Back-Channel Logout
+///
+/// This job only looks up which clients need to be notified, and schedules a
+/// [`SendBackchannelLogoutJob`] for each of them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackchannelLogoutJob {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    browser_session_id: Option<Ulid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oauth2_session_id: Option<Ulid>,
+}
+
+impl BackchannelLogoutJob {
+    /// Create a new job to notify the clients which got a session through the
+    /// given browser session that it ended
+    #[must_use]
+    pub fn for_browser_session(browser_session: &BrowserSession) -> Self {
+        Self {
+            browser_session_id: Some(browser_session.id),
+            oauth2_session_id: None,
+        }
+    }
+
+    /// Create a new job to notify the client of the given OAuth 2.0 session
+    /// that it ended
+    #[must_use]
+    pub fn for_oauth2_session(session: &Session) -> Self {
+        Self {
+            browser_session_id: None,
+            oauth2_session_id: Some(session.id),
+        }
+    }
+
+    /// The ID of the browser session which ended, if any
+    #[must_use]
+    pub fn browser_session_id(&self) -> Option<Ulid> {
+        self.browser_session_id
+    }
+
+    /// The ID of the OAuth 2.0 session which ended, if any
+    #[must_use]
+    pub fn oauth2_session_id(&self) -> Option<Ulid> {
+        self.oauth2_session_id
+    }
+}
+
+impl InsertableJob for BackchannelLogoutJob {
+    const QUEUE_NAME: &'static str = "backchannel-logout";
+}
+
+/// A job to send a logout token to the `backchannel_logout_uri` of a client
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(clippy::struct_field_names)]
+pub struct SendBackchannelLogoutJob {
+    client_id: Ulid,
+    user_id: Ulid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    browser_session_id: Option<Ulid>,
+}
+
+impl SendBackchannelLogoutJob {
+    /// Create a new job to send a logout token to a client
+    ///
+    /// # Parameters
+    ///
+    /// * `client_id` - The ID of the client to notify
+    /// * `user_id` - The ID of the user whose session ended
+    /// * `browser_session_id` - The ID of the browser session, sent as the
+    ///   `sid` claim, if the session was tied to one
+    #[must_use]
+    pub fn new(client_id: Ulid, user_id: Ulid, browser_session_id: Option<Ulid>) -> Self {
+        Self {
+            client_id,
+            user_id,
+            browser_session_id,
+        }
+    }
+
+    /// The ID of the client to notify
+    #[must_use]
+    pub fn client_id(&self) -> Ulid {
+        self.client_id
+    }
+
+    /// The ID of the user whose session ended
+    #[must_use]
+    pub fn user_id(&self) -> Ulid {
+        self.user_id
+    }
+
+    /// The ID of the browser session which ended, if any
+    #[must_use]
+    pub fn browser_session_id(&self) -> Option<Ulid> {
+        self.browser_session_id
+    }
+}
+
+impl InsertableJob for SendBackchannelLogoutJob {
+    const QUEUE_NAME: &'static str = "send-backchannel-logout";
+}
+
+/// A job which delivers a lifecycle event to the configured webhook endpoints
+///
+/// This job only records a delivery for each endpoint subscribed to the
+/// event, and schedules a [`SendWebhookJob`] for each of them.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DispatchWebhookEventJob {
+    event: WebhookEvent,
+    data: serde_json::Value,
+}
+
+impl DispatchWebhookEventJob {
+    /// Create a new job to announce that a user registered
+    #[must_use]
+    pub fn user_registered(user: &User) -> Self {
+        Self {
+            event: WebhookEvent::UserRegistered,
+            data: json!({
+                "user_id": user.id,
+                "username": user.username,
+            }),
+        }
+    }
+
+    /// Create a new job to announce that a user was deactivated
+    #[must_use]
+    pub fn user_deactivated(user: &User) -> Self {
+        Self {
+            event: WebhookEvent::UserDeactivated,
+            data: json!({
+                "user_id": user.id,
+                "username": user.username,
+            }),
+        }
+    }
+
+    /// Create a new job to announce that a user verified an email address
+    #[must_use]
+    pub fn user_email_verified(user_email: &UserEmail) -> Self {
+        Self {
+            event: WebhookEvent::UserEmailVerified,
+            data: json!({
+                "user_id": user_email.user_id,
+                "user_email_id": user_email.id,
+                "email": user_email.email,
+            }),
+        }
+    }
+
+    /// Create a new job to announce that a user started a new browser session
+    #[must_use]
+    pub fn browser_session_created(session: &BrowserSession) -> Self {
+        Self {
+            event: WebhookEvent::SessionCreated,
+            data: json!({
+                "user_id": session.user.id,
+                "session_id": session.id,
+                "session_type": "browser",
+            }),
+        }
+    }
+
+    /// Create a new job to announce that a user started a new compatibility
+    /// session
+    #[must_use]
+    pub fn compat_session_created(session: &CompatSession) -> Self {
+        Self {
+            event: WebhookEvent::SessionCreated,
+            data: json!({
+                "user_id": session.user_id,
+                "session_id": session.id,
+                "session_type": "compat",
+            }),
+        }
+    }
+
+    /// The event to deliver
+    #[must_use]
+    pub fn event(&self) -> WebhookEvent {
+        self.event
+    }
+
+    /// The data attached to the event
+    #[must_use]
+    pub fn data(&self) -> &serde_json::Value {
+        &self.data
+    }
+}
+
+impl InsertableJob for DispatchWebhookEventJob {
+    const QUEUE_NAME: &'static str = "dispatch-webhook-event";
+}
+
+/// A job to POST an event to a webhook endpoint
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendWebhookJob {
+    delivery_id: Ulid,
+}
+
+impl SendWebhookJob {
+    /// Create a new job to attempt the given delivery
+    #[must_use]
+    pub fn new(delivery: &WebhookDelivery) -> Self {
+        Self {
+            delivery_id: delivery.id,
+        }
+    }
+
+    /// The ID of the delivery to attempt
+    #[must_use]
+    pub fn delivery_id(&self) -> Ulid {
+        self.delivery_id
+    }
+}
+
+impl InsertableJob for SendWebhookJob {
+    const QUEUE_NAME: &'static str = "send-webhook";
+}
+
 /// A job to deactivate and lock a user
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DeactivateUserJob {
@@ -285,6 +506,36 @@ impl InsertableJob for ReactivateUserJob {
     const QUEUE_NAME: &'static str = "reactivate-user";
 }
 
+/// A job to build the archive of everything stored about a user
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportUserJob {
+    user_export_id: Ulid,
+}
+
+impl ExportUserJob {
+    /// Create a new job to build the archive of a user export
+    ///
+    /// # Parameters
+    ///
+    /// * `user_export` - The user export to build the archive for
+    #[must_use]
+    pub fn new(user_export: &UserExport) -> Self {
+        Self {
+            user_export_id: user_export.id,
+        }
+    }
+
+    /// The ID of the user export to build the archive for
+    #[must_use]
+    pub fn user_export_id(&self) -> Ulid {
+        self.user_export_id
+    }
+}
+
+impl InsertableJob for ExportUserJob {
+    const QUEUE_NAME: &'static str = "export-user";
+}
+
 /// Send account recovery emails
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SendAccountRecoveryEmailsJob {
@@ -317,6 +568,80 @@ impl InsertableJob for SendAccountRecoveryEmailsJob {
     const QUEUE_NAME: &'static str = "send-account-recovery-email";
 }
 
+/// Notify a user by email of a security-relevant event on their account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendSecurityNotificationJob {
+    user_id: Ulid,
+    notification: SecurityNotification,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    additional_recipients: Vec<String>,
+}
+
+impl SendSecurityNotificationJob {
+    /// Create a new job to notify a user of a security-relevant event
+    ///
+    /// # Parameters
+    ///
+    /// * `user` - The user to notify
+    /// * `notification` - The event to notify the user about
+    #[must_use]
+    pub fn new(user: &User, notification: SecurityNotification) -> Self {
+        Self {
+            user_id: user.id,
+            notification,
+            language: None,
+            additional_recipients: Vec::new(),
+        }
+    }
+
+    /// Set the language to use for the email, if known
+    #[must_use]
+    pub fn with_language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Also send the email to the given addresses, on top of the current
+    /// addresses of the user. This is used when the addresses of the user are
+    /// about to be removed.
+    #[must_use]
+    pub fn with_additional_recipients(mut self, recipients: Vec<String>) -> Self {
+        self.additional_recipients = recipients;
+        self
+    }
+
+    /// The ID of the user to notify
+    #[must_use]
+    pub fn user_id(&self) -> Ulid {
+        self.user_id
+    }
+
+    /// The event to notify the user about
+    #[must_use]
+    pub fn notification(&self) -> &SecurityNotification {
+        &self.notification
+    }
+
+    /// The language to use for the email, if known
+    #[must_use]
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Addresses to send the email to, on top of the current addresses of the
+    /// user
+    #[must_use]
+    pub fn additional_recipients(&self) -> &[String] {
+        &self.additional_recipients
+    }
+}
+
+impl InsertableJob for SendSecurityNotificationJob {
+    const QUEUE_NAME: &'static str = "send-security-notification";
+}
+
 /// Cleanup expired tokens
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct CleanupExpiredTokensJob;
@@ -507,6 +832,56 @@ impl InsertableJob for ExpireInactiveUserSessionsJob {
     const QUEUE_NAME: &'static str = "expire-inactive-user-sessions";
 }
 
+/// End a session started by an admin to impersonate a user, once the
+/// impersonation expired
+///
+/// This is scheduled to run when the session is created, so that its device
+/// gets removed from the homeserver as soon as the session can't be used
+/// anymore.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EndImpersonationJob {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oauth2_session_id: Option<Ulid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compat_session_id: Option<Ulid>,
+}
+
+impl EndImpersonationJob {
+    /// Create a new job to end the given OAuth 2.0 impersonation session
+    #[must_use]
+    pub fn for_oauth2_session(session: &Session) -> Self {
+        Self {
+            oauth2_session_id: Some(session.id),
+            compat_session_id: None,
+        }
+    }
+
+    /// Create a new job to end the given compatibility impersonation session
+    #[must_use]
+    pub fn for_compat_session(session: &CompatSession) -> Self {
+        Self {
+            oauth2_session_id: None,
+            compat_session_id: Some(session.id),
+        }
+    }
+
+    /// The ID of the OAuth 2.0 session to end, if any
+    #[must_use]
+    pub fn oauth2_session_id(&self) -> Option<Ulid> {
+        self.oauth2_session_id
+    }
+
+    /// The ID of the compatibility session to end, if any
+    #[must_use]
+    pub fn compat_session_id(&self) -> Option<Ulid> {
+        self.compat_session_id
+    }
+}
+
+impl InsertableJob for EndImpersonationJob {
+    const QUEUE_NAME: &'static str = "end-impersonation";
+}
+
 /// Prune stale policy data
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PruneStalePolicyDataJob;
@@ -514,3 +889,12 @@ pub struct PruneStalePolicyDataJob;
 impl InsertableJob for PruneStalePolicyDataJob {
     const QUEUE_NAME: &'static str = "prune-stale-policy-data";
 }
+
+/// Scheduled job to erase the personal data of users deactivated for longer
+/// than the configured retention period
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EraseDeactivatedUsersJob;
+
+impl InsertableJob for EraseDeactivatedUsersJob {
+    const QUEUE_NAME: &'static str = "erase-deactivated-users";
+}
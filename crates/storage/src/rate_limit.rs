@@ -0,0 +1,80 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Repositories to interact with the rate limiters state saved in the storage
+//! backend, shared by all the replicas of the service.
+
+use std::num::NonZeroU32;
+
+use async_trait::async_trait;
+use chrono::Duration;
+use mas_data_model::Clock;
+
+use crate::repository_impl;
+
+/// A [`RateLimitRepository`] helps interacting with the rate limiters state
+/// saved in the storage backend.
+///
+/// The limiters follow the generic cell rate algorithm (GCRA): each key of a
+/// bucket has a theoretical arrival time, which is pushed forward by
+/// `interval` on each allowed action. An action is allowed as long as the
+/// theoretical arrival time is no further than `burst - 1` intervals in the
+/// future.
+#[async_trait]
+pub trait RateLimitRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Check if an action can be performed for a key in a bucket, and record
+    /// it if so
+    ///
+    /// Returns `true` if the action is allowed, `false` if it is rate limited
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to get the current time
+    /// * `bucket`: The name of the rate limiter
+    /// * `key`: The key to rate limit within the bucket
+    /// * `interval`: The time it takes to replenish one action
+    /// * `burst`: The number of actions which can be performed in one go
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn check(
+        &mut self,
+        clock: &dyn Clock,
+        bucket: &str,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> Result<bool, Self::Error>;
+
+    /// Remove the state of the keys which are fully replenished
+    ///
+    /// Returns the number of keys removed
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to get the current time
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn cleanup(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(RateLimitRepository:
+    async fn check(
+        &mut self,
+        clock: &dyn Clock,
+        bucket: &str,
+        key: &str,
+        interval: Duration,
+        burst: NonZeroU32,
+    ) -> Result<bool, Self::Error>;
+
+    async fn cleanup(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+);
@@ -10,26 +10,31 @@ use thiserror::Error;
 
 use crate::{
     app_session::AppSessionRepository,
+    audit_event::AuditEventRepository,
     compat::{
         CompatAccessTokenRepository, CompatRefreshTokenRepository, CompatSessionRepository,
         CompatSsoLoginRepository,
     },
     oauth2::{
         OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository, OAuth2ClientRepository,
-        OAuth2DeviceCodeGrantRepository, OAuth2RefreshTokenRepository, OAuth2SessionRepository,
+        OAuth2DeviceCodeGrantRepository, OAuth2PushedAuthorizationRequestRepository,
+        OAuth2RefreshTokenRepository, OAuth2SessionRepository,
     },
     personal::{PersonalAccessTokenRepository, PersonalSessionRepository},
     policy_data::PolicyDataRepository,
     queue::{QueueJobRepository, QueueScheduleRepository, QueueWorkerRepository},
+    rate_limit::RateLimitRepository,
     upstream_oauth2::{
         UpstreamOAuthLinkRepository, UpstreamOAuthProviderRepository,
         UpstreamOAuthSessionRepository,
     },
     user::{
-        BrowserSessionRepository, UserEmailRepository, UserPasswordRepository,
+        BrowserSessionRepository, UserEmailRepository, UserExportRepository,
+        UserLdapLinkRepository, UserPasskeyRepository, UserPasswordRepository,
         UserRecoveryRepository, UserRegistrationRepository, UserRegistrationTokenRepository,
-        UserRepository, UserTermsRepository,
+        UserRepository, UserTermsRepository, UserTotpRepository,
     },
+    webhook::WebhookDeliveryRepository,
 };
 
 /// A [`RepositoryFactory`] is a factory that can create a [`BoxRepository`]
@@ -141,6 +146,17 @@ pub trait RepositoryAccess: Send {
     fn user_password<'c>(&'c mut self)
     -> Box<dyn UserPasswordRepository<Error = Self::Error> + 'c>;
 
+    /// Get an [`UserPasskeyRepository`]
+    fn user_passkey<'c>(&'c mut self) -> Box<dyn UserPasskeyRepository<Error = Self::Error> + 'c>;
+
+    /// Get an [`UserTotpRepository`]
+    fn user_totp<'c>(&'c mut self) -> Box<dyn UserTotpRepository<Error = Self::Error> + 'c>;
+
+    /// Get an [`UserLdapLinkRepository`]
+    fn user_ldap_link<'c>(
+        &'c mut self,
+    ) -> Box<dyn UserLdapLinkRepository<Error = Self::Error> + 'c>;
+
     /// Get an [`UserRecoveryRepository`]
     fn user_recovery<'c>(&'c mut self)
     -> Box<dyn UserRecoveryRepository<Error = Self::Error> + 'c>;
@@ -158,6 +174,9 @@ pub trait RepositoryAccess: Send {
     /// Get an [`UserTermsRepository`]
     fn user_terms<'c>(&'c mut self) -> Box<dyn UserTermsRepository<Error = Self::Error> + 'c>;
 
+    /// Get an [`UserExportRepository`]
+    fn user_export<'c>(&'c mut self) -> Box<dyn UserExportRepository<Error = Self::Error> + 'c>;
+
     /// Get a [`BrowserSessionRepository`]
     fn browser_session<'c>(
         &'c mut self,
@@ -195,6 +214,11 @@ pub trait RepositoryAccess: Send {
         &'c mut self,
     ) -> Box<dyn OAuth2DeviceCodeGrantRepository<Error = Self::Error> + 'c>;
 
+    /// Get an [`OAuth2PushedAuthorizationRequestRepository`]
+    fn oauth2_pushed_authorization_request<'c>(
+        &'c mut self,
+    ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c>;
+
     /// Get a [`CompatSessionRepository`]
     fn compat_session<'c>(
         &'c mut self,
@@ -238,6 +262,17 @@ pub trait RepositoryAccess: Send {
 
     /// Get a [`PolicyDataRepository`]
     fn policy_data<'c>(&'c mut self) -> Box<dyn PolicyDataRepository<Error = Self::Error> + 'c>;
+
+    /// Get an [`AuditEventRepository`]
+    fn audit_event<'c>(&'c mut self) -> Box<dyn AuditEventRepository<Error = Self::Error> + 'c>;
+
+    /// Get a [`WebhookDeliveryRepository`]
+    fn webhook_delivery<'c>(
+        &'c mut self,
+    ) -> Box<dyn WebhookDeliveryRepository<Error = Self::Error> + 'c>;
+
+    /// Get a [`RateLimitRepository`]
+    fn rate_limit<'c>(&'c mut self) -> Box<dyn RateLimitRepository<Error = Self::Error> + 'c>;
 }
 
 /// Implementations of the [`RepositoryAccess`], [`RepositoryTransaction`] and
@@ -249,27 +284,32 @@ mod impls {
     use crate::{
         MapErr, Repository, RepositoryTransaction,
         app_session::AppSessionRepository,
+        audit_event::AuditEventRepository,
         compat::{
             CompatAccessTokenRepository, CompatRefreshTokenRepository, CompatSessionRepository,
             CompatSsoLoginRepository,
         },
         oauth2::{
             OAuth2AccessTokenRepository, OAuth2AuthorizationGrantRepository,
-            OAuth2ClientRepository, OAuth2DeviceCodeGrantRepository, OAuth2RefreshTokenRepository,
+            OAuth2ClientRepository, OAuth2DeviceCodeGrantRepository,
+            OAuth2PushedAuthorizationRequestRepository, OAuth2RefreshTokenRepository,
             OAuth2SessionRepository,
         },
         personal::{PersonalAccessTokenRepository, PersonalSessionRepository},
         policy_data::PolicyDataRepository,
         queue::{QueueJobRepository, QueueScheduleRepository, QueueWorkerRepository},
+        rate_limit::RateLimitRepository,
         upstream_oauth2::{
             UpstreamOAuthLinkRepository, UpstreamOAuthProviderRepository,
             UpstreamOAuthSessionRepository,
         },
         user::{
-            BrowserSessionRepository, UserEmailRepository, UserPasswordRepository,
+            BrowserSessionRepository, UserEmailRepository, UserExportRepository,
+            UserLdapLinkRepository, UserPasskeyRepository, UserPasswordRepository,
             UserRegistrationRepository, UserRegistrationTokenRepository, UserRepository,
-            UserTermsRepository,
+            UserTermsRepository, UserTotpRepository,
         },
+        webhook::WebhookDeliveryRepository,
     };
 
     // --- Repository ---
@@ -352,6 +392,22 @@ mod impls {
             Box::new(MapErr::new(self.inner.user_password(), &mut self.mapper))
         }
 
+        fn user_passkey<'c>(
+            &'c mut self,
+        ) -> Box<dyn UserPasskeyRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.user_passkey(), &mut self.mapper))
+        }
+
+        fn user_totp<'c>(&'c mut self) -> Box<dyn UserTotpRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.user_totp(), &mut self.mapper))
+        }
+
+        fn user_ldap_link<'c>(
+            &'c mut self,
+        ) -> Box<dyn UserLdapLinkRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.user_ldap_link(), &mut self.mapper))
+        }
+
         fn user_recovery<'c>(
             &'c mut self,
         ) -> Box<dyn crate::user::UserRecoveryRepository<Error = Self::Error> + 'c> {
@@ -380,6 +436,10 @@ mod impls {
             Box::new(MapErr::new(self.inner.user_terms(), &mut self.mapper))
         }
 
+        fn user_export<'c>(&'c mut self) -> Box<dyn UserExportRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.user_export(), &mut self.mapper))
+        }
+
         fn browser_session<'c>(
             &'c mut self,
         ) -> Box<dyn BrowserSessionRepository<Error = Self::Error> + 'c> {
@@ -440,6 +500,15 @@ mod impls {
             ))
         }
 
+        fn oauth2_pushed_authorization_request<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(
+                self.inner.oauth2_pushed_authorization_request(),
+                &mut self.mapper,
+            ))
+        }
+
         fn compat_session<'c>(
             &'c mut self,
         ) -> Box<dyn CompatSessionRepository<Error = Self::Error> + 'c> {
@@ -506,6 +575,24 @@ mod impls {
         ) -> Box<dyn PolicyDataRepository<Error = Self::Error> + 'c> {
             Box::new(MapErr::new(self.inner.policy_data(), &mut self.mapper))
         }
+
+        fn audit_event<'c>(
+            &'c mut self,
+        ) -> Box<dyn AuditEventRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.audit_event(), &mut self.mapper))
+        }
+
+        fn webhook_delivery<'c>(
+            &'c mut self,
+        ) -> Box<dyn WebhookDeliveryRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.webhook_delivery(), &mut self.mapper))
+        }
+
+        fn rate_limit<'c>(
+            &'c mut self,
+        ) -> Box<dyn RateLimitRepository<Error = Self::Error> + 'c> {
+            Box::new(MapErr::new(self.inner.rate_limit(), &mut self.mapper))
+        }
     }
 
     impl<R: RepositoryAccess + ?Sized> RepositoryAccess for Box<R> {
@@ -543,6 +630,22 @@ mod impls {
             (**self).user_password()
         }
 
+        fn user_passkey<'c>(
+            &'c mut self,
+        ) -> Box<dyn UserPasskeyRepository<Error = Self::Error> + 'c> {
+            (**self).user_passkey()
+        }
+
+        fn user_totp<'c>(&'c mut self) -> Box<dyn UserTotpRepository<Error = Self::Error> + 'c> {
+            (**self).user_totp()
+        }
+
+        fn user_ldap_link<'c>(
+            &'c mut self,
+        ) -> Box<dyn UserLdapLinkRepository<Error = Self::Error> + 'c> {
+            (**self).user_ldap_link()
+        }
+
         fn user_recovery<'c>(
             &'c mut self,
         ) -> Box<dyn crate::user::UserRecoveryRepository<Error = Self::Error> + 'c> {
@@ -565,6 +668,10 @@ mod impls {
             (**self).user_terms()
         }
 
+        fn user_export<'c>(&'c mut self) -> Box<dyn UserExportRepository<Error = Self::Error> + 'c> {
+            (**self).user_export()
+        }
+
         fn browser_session<'c>(
             &'c mut self,
         ) -> Box<dyn BrowserSessionRepository<Error = Self::Error> + 'c> {
@@ -613,6 +720,12 @@ mod impls {
             (**self).oauth2_device_code_grant()
         }
 
+        fn oauth2_pushed_authorization_request<'c>(
+            &'c mut self,
+        ) -> Box<dyn OAuth2PushedAuthorizationRequestRepository<Error = Self::Error> + 'c> {
+            (**self).oauth2_pushed_authorization_request()
+        }
+
         fn compat_session<'c>(
             &'c mut self,
         ) -> Box<dyn CompatSessionRepository<Error = Self::Error> + 'c> {
@@ -670,5 +783,23 @@ mod impls {
         ) -> Box<dyn PolicyDataRepository<Error = Self::Error> + 'c> {
             (**self).policy_data()
         }
+
+        fn audit_event<'c>(
+            &'c mut self,
+        ) -> Box<dyn AuditEventRepository<Error = Self::Error> + 'c> {
+            (**self).audit_event()
+        }
+
+        fn webhook_delivery<'c>(
+            &'c mut self,
+        ) -> Box<dyn WebhookDeliveryRepository<Error = Self::Error> + 'c> {
+            (**self).webhook_delivery()
+        }
+
+        fn rate_limit<'c>(
+            &'c mut self,
+        ) -> Box<dyn RateLimitRepository<Error = Self::Error> + 'c> {
+            (**self).rate_limit()
+        }
     }
 }
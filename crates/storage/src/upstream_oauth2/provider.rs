@@ -11,7 +11,7 @@ use mas_data_model::{
     Clock, UpstreamOAuthProvider, UpstreamOAuthProviderClaimsImports,
     UpstreamOAuthProviderDiscoveryMode, UpstreamOAuthProviderOnBackchannelLogout,
     UpstreamOAuthProviderPkceMode, UpstreamOAuthProviderResponseMode,
-    UpstreamOAuthProviderTokenAuthMethod,
+    UpstreamOAuthProviderSamlSettings, UpstreamOAuthProviderTokenAuthMethod,
 };
 use mas_iana::jose::JsonWebSignatureAlg;
 use oauth2_types::scope::Scope;
@@ -105,6 +105,9 @@ pub struct UpstreamOAuthProviderParams {
 
     /// The behavior when receiving a backchannel logout notification
     pub on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout,
+
+    /// SAML 2.0 settings, if the provider speaks SAML 2.0 instead of OAuth 2.0
+    pub saml: Option<UpstreamOAuthProviderSamlSettings>,
 }
 
 /// Filter parameters for listing upstream OAuth 2.0 providers
@@ -115,6 +118,11 @@ pub struct UpstreamOAuthProviderFilter<'a> {
     /// If `None`, all providers are returned
     enabled: Option<bool>,
 
+    /// Filter by whether the provider is defined in the configuration
+    ///
+    /// If `None`, all providers are returned
+    is_static: Option<bool>,
+
     _lifetime: PhantomData<&'a ()>,
 }
 
@@ -146,6 +154,28 @@ impl UpstreamOAuthProviderFilter<'_> {
     pub const fn enabled(&self) -> Option<bool> {
         self.enabled
     }
+
+    /// Return only providers defined in the configuration
+    #[must_use]
+    pub const fn static_only(mut self) -> Self {
+        self.is_static = Some(true);
+        self
+    }
+
+    /// Return only providers created through the admin API
+    #[must_use]
+    pub const fn dynamic_only(mut self) -> Self {
+        self.is_static = Some(false);
+        self
+    }
+
+    /// Get the static filter
+    ///
+    /// Returns `None` if the filter is not set
+    #[must_use]
+    pub const fn is_static(&self) -> Option<bool> {
+        self.is_static
+    }
 }
 
 /// An [`UpstreamOAuthProviderRepository`] helps interacting with
@@ -170,7 +200,8 @@ pub trait UpstreamOAuthProviderRepository: Send + Sync {
 
     /// Add a new upstream OAuth provider
     ///
-    /// Returns the newly created provider
+    /// Returns the newly created provider. It is not marked as defined in the
+    /// configuration.
     ///
     /// # Parameters
     ///
@@ -214,6 +245,8 @@ pub trait UpstreamOAuthProviderRepository: Send + Sync {
 
     /// Insert or update an upstream OAuth provider
     ///
+    /// The provider is enabled and marked as defined in the configuration.
+    ///
     /// # Parameters
     ///
     /// * `clock`: The clock used to generate timestamps
@@ -230,6 +263,27 @@ pub trait UpstreamOAuthProviderRepository: Send + Sync {
         params: UpstreamOAuthProviderParams,
     ) -> Result<UpstreamOAuthProvider, Self::Error>;
 
+    /// Update the parameters of an existing upstream OAuth provider
+    ///
+    /// Unlike [`Self::upsert`], this doesn't change whether the provider is
+    /// enabled or defined in the configuration.
+    ///
+    /// Returns the updated provider
+    ///
+    /// # Parameters
+    ///
+    /// * `provider`: The provider to update
+    /// * `params`: The new parameters of the provider
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn update(
+        &mut self,
+        provider: UpstreamOAuthProvider,
+        params: UpstreamOAuthProviderParams,
+    ) -> Result<UpstreamOAuthProvider, Self::Error>;
+
     /// Disable an upstream OAuth provider
     ///
     /// Returns the disabled provider
@@ -307,6 +361,12 @@ repository_impl!(UpstreamOAuthProviderRepository:
 
     async fn delete_by_id(&mut self, id: Ulid) -> Result<(), Self::Error>;
 
+    async fn update(
+        &mut self,
+        provider: UpstreamOAuthProvider,
+        params: UpstreamOAuthProviderParams,
+    ) -> Result<UpstreamOAuthProvider, Self::Error>;
+
     async fn disable(
         &mut self,
         clock: &dyn Clock,
@@ -17,6 +17,7 @@ use crate::{Pagination, pagination::Page, repository_impl};
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct UpstreamOAuthSessionFilter<'a> {
     provider: Option<&'a UpstreamOAuthProvider>,
+    link: Option<&'a UpstreamOAuthLink>,
     sub_claim: Option<&'a str>,
     sid_claim: Option<&'a str>,
 }
@@ -43,6 +44,21 @@ impl<'a> UpstreamOAuthSessionFilter<'a> {
         self.provider
     }
 
+    /// Set the upstream OAuth link for which to list sessions
+    #[must_use]
+    pub fn for_link(mut self, link: &'a UpstreamOAuthLink) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    /// Get the upstream OAuth link filter
+    ///
+    /// Returns [`None`] if no filter was set
+    #[must_use]
+    pub fn link(&self) -> Option<&UpstreamOAuthLink> {
+        self.link
+    }
+
     /// Set the `sub` claim to filter by
     #[must_use]
     pub fn with_sub_claim(mut self, sub_claim: &'a str) -> Self {
@@ -0,0 +1,934 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Archive of everything stored about a user, to answer subject access
+//! requests
+//!
+//! The archive is built from the repositories, and never contains secrets like
+//! password hashes, passkey public keys or tokens.
+
+use std::{collections::BTreeMap, net::IpAddr};
+
+use chrono::{DateTime, Utc};
+use mas_data_model::{AuditAction, Clock, User, WebhookDeliveryState, WebhookEvent};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use url::Url;
+
+use crate::{
+    Pagination, RepositoryAccess,
+    audit_event::AuditEventFilter,
+    compat::{CompatSessionFilter, CompatSsoLoginFilter},
+    oauth2::OAuth2SessionFilter,
+    personal::PersonalSessionFilter,
+    upstream_oauth2::{UpstreamOAuthLinkFilter, UpstreamOAuthSessionFilter},
+    user::BrowserSessionFilter,
+    webhook::WebhookDeliveryFilter,
+};
+
+/// The version of the archive format, bumped on incompatible changes
+pub const USER_ARCHIVE_VERSION: u32 = 1;
+
+/// How many items to fetch at once when going through a list
+const PAGE_SIZE: usize = 1000;
+
+/// Everything stored about a user
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserArchive {
+    /// The version of the archive format
+    pub version: u32,
+
+    /// When the archive was built
+    pub exported_at: DateTime<Utc>,
+
+    /// The user account itself
+    pub user: ArchivedUser,
+
+    /// The email addresses of the user
+    pub emails: Vec<ArchivedUserEmail>,
+
+    /// The passwords the user set, most recent first. Only when they were set
+    /// is exported, not the password hashes.
+    pub passwords: Vec<ArchivedPassword>,
+
+    /// The passkeys registered by the user
+    pub passkeys: Vec<ArchivedPasskey>,
+
+    /// The TOTP second factor of the user, if they enrolled one
+    pub totp: Option<ArchivedTotp>,
+
+    /// The entry of the LDAP directory the user is linked to, if any
+    pub ldap_link: Option<ArchivedLdapLink>,
+
+    /// The accounts of upstream identity providers linked to the user
+    pub upstream_oauth_links: Vec<ArchivedUpstreamOAuthLink>,
+
+    /// The logins through upstream identity providers to the linked accounts,
+    /// with what the providers told about the user
+    pub upstream_oauth_sessions: Vec<ArchivedUpstreamOAuthSession>,
+
+    /// The versions of the terms of service the user accepted
+    pub terms: Vec<ArchivedTerms>,
+
+    /// The registration which created the user, if they registered with a
+    /// password
+    pub registration: Option<ArchivedRegistration>,
+
+    /// The verifications of email addresses the user started
+    pub email_authentications: Vec<ArchivedEmailAuthentication>,
+
+    /// The account recoveries started for the email addresses of the user
+    pub recovery_sessions: Vec<ArchivedRecoverySession>,
+
+    /// The sessions of the user in their web browsers
+    pub browser_sessions: Vec<ArchivedBrowserSession>,
+
+    /// The sessions of OAuth 2.0 clients on behalf of the user
+    pub oauth2_sessions: Vec<ArchivedOAuth2Session>,
+
+    /// The sessions of clients using the legacy Matrix login API
+    pub compat_sessions: Vec<ArchivedCompatSession>,
+
+    /// The logins of clients using the legacy Matrix SSO login API
+    pub compat_sso_logins: Vec<ArchivedCompatSsoLogin>,
+
+    /// The personal sessions acting as the user
+    pub personal_sessions: Vec<ArchivedPersonalSession>,
+
+    /// The audit log entries about the user, or of actions they performed
+    pub audit_events: Vec<ArchivedAuditEvent>,
+
+    /// The events about the user sent to webhook endpoints
+    pub webhook_deliveries: Vec<ArchivedWebhookDelivery>,
+}
+
+/// A user account, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedUser {
+    /// The ID of the user
+    pub id: Ulid,
+
+    /// The username (Matrix localpart) of the user
+    pub username: String,
+
+    /// When the user was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the user was locked, if it was
+    pub locked_at: Option<DateTime<Utc>>,
+
+    /// When the user was deactivated, if it was
+    pub deactivated_at: Option<DateTime<Utc>>,
+
+    /// Whether the user can request admin privileges
+    pub can_request_admin: bool,
+
+    /// Whether the user is a guest
+    pub is_guest: bool,
+}
+
+/// An email address, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedUserEmail {
+    /// The ID of the email address
+    pub id: Ulid,
+
+    /// The email address
+    pub email: String,
+
+    /// When the email address was added
+    pub created_at: DateTime<Utc>,
+}
+
+/// A password, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedPassword {
+    /// The ID of the password
+    pub id: Ulid,
+
+    /// When the password was set
+    pub created_at: DateTime<Utc>,
+}
+
+/// A passkey, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedPasskey {
+    /// The ID of the passkey
+    pub id: Ulid,
+
+    /// The name the user gave to the passkey
+    pub name: String,
+
+    /// The AAGUID of the authenticator, if known
+    pub aaguid: Option<String>,
+
+    /// When the passkey was registered
+    pub created_at: DateTime<Utc>,
+
+    /// When the passkey was last used
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A TOTP second factor, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedTotp {
+    /// When the enrollment started
+    pub created_at: DateTime<Utc>,
+
+    /// When the enrollment was confirmed, if it was
+    pub confirmed_at: Option<DateTime<Utc>>,
+
+    /// When a code was last used
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// A link to an LDAP directory entry, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedLdapLink {
+    /// The subject of the directory entry
+    pub subject: String,
+
+    /// When the link was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// A link to an upstream identity provider, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedUpstreamOAuthLink {
+    /// The ID of the link
+    pub id: Ulid,
+
+    /// The ID of the upstream provider
+    pub provider_id: Ulid,
+
+    /// The subject of the account at the upstream provider
+    pub subject: String,
+
+    /// A human-readable name of the upstream account, if known
+    pub human_account_name: Option<String>,
+
+    /// When the link was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// A login through an upstream identity provider, as exported in a
+/// [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedUpstreamOAuthSession {
+    /// The ID of the session
+    pub id: Ulid,
+
+    /// The ID of the upstream provider
+    pub provider_id: Ulid,
+
+    /// The ID of the link to the upstream account
+    pub link_id: Option<Ulid>,
+
+    /// When the login started
+    pub created_at: DateTime<Utc>,
+
+    /// When the upstream provider sent the user back, if it did
+    pub completed_at: Option<DateTime<Utc>>,
+
+    /// The claims of the ID token the upstream provider issued, if any
+    pub id_token_claims: Option<serde_json::Value>,
+
+    /// The response of the userinfo endpoint of the upstream provider, if it
+    /// was fetched
+    pub userinfo: Option<serde_json::Value>,
+}
+
+/// An acceptance of the terms of service, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedTerms {
+    /// The URL of the terms of service
+    pub terms_url: Url,
+
+    /// When the user accepted them
+    pub accepted_at: DateTime<Utc>,
+}
+
+/// A registration, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedRegistration {
+    /// The ID of the registration
+    pub id: Ulid,
+
+    /// The display name the user asked for, if any
+    pub display_name: Option<String>,
+
+    /// The URL of the terms of service at the time of registration, if any
+    pub terms_url: Option<Url>,
+
+    /// The IP address the user registered from, if known
+    pub ip_address: Option<IpAddr>,
+
+    /// The user agent the user registered with, if known
+    pub user_agent: Option<String>,
+
+    /// When the registration started
+    pub created_at: DateTime<Utc>,
+
+    /// When the registration was completed
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// An email address verification, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedEmailAuthentication {
+    /// The ID of the verification
+    pub id: Ulid,
+
+    /// The email address being verified
+    pub email: String,
+
+    /// When the verification started
+    pub created_at: DateTime<Utc>,
+
+    /// When the email address was verified, if it was
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// An account recovery, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedRecoverySession {
+    /// The ID of the recovery session
+    pub id: Ulid,
+
+    /// The email address the recovery was started for
+    pub email: String,
+
+    /// The IP address the recovery was started from, if known
+    pub ip_address: Option<IpAddr>,
+
+    /// The user agent the recovery was started with
+    pub user_agent: String,
+
+    /// The locale of the browser which started the recovery
+    pub locale: String,
+
+    /// When the recovery started
+    pub created_at: DateTime<Utc>,
+
+    /// When the recovery was used to reset the password, if it was
+    pub consumed_at: Option<DateTime<Utc>>,
+}
+
+/// A browser session, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedBrowserSession {
+    /// The ID of the session
+    pub id: Ulid,
+
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the session was finished, if it was
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// The user agent of the browser, if known
+    pub user_agent: Option<String>,
+
+    /// When the session was last active
+    pub last_active_at: Option<DateTime<Utc>>,
+
+    /// The IP address the session was last active from
+    pub last_active_ip: Option<IpAddr>,
+}
+
+/// An OAuth 2.0 session, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedOAuth2Session {
+    /// The ID of the session
+    pub id: Ulid,
+
+    /// The ID of the client the session is for
+    pub client_id: Ulid,
+
+    /// The scope granted to the client
+    pub scope: String,
+
+    /// The name the user gave to the session, if any
+    pub human_name: Option<String>,
+
+    /// Whether the session was started by an admin impersonating the user
+    pub impersonated: bool,
+
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the session was finished, if it was
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// The user agent of the client, if known
+    pub user_agent: Option<String>,
+
+    /// When the session was last active
+    pub last_active_at: Option<DateTime<Utc>>,
+
+    /// The IP address the session was last active from
+    pub last_active_ip: Option<IpAddr>,
+}
+
+/// A compatibility session, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedCompatSession {
+    /// The ID of the session
+    pub id: Ulid,
+
+    /// The Matrix device ID of the session, if any
+    pub device_id: Option<String>,
+
+    /// The name the user gave to the session, if any
+    pub human_name: Option<String>,
+
+    /// Whether the session was started by an admin impersonating the user
+    pub impersonated: bool,
+
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the session was finished, if it was
+    pub finished_at: Option<DateTime<Utc>>,
+
+    /// The user agent of the client, if known
+    pub user_agent: Option<String>,
+
+    /// When the session was last active
+    pub last_active_at: Option<DateTime<Utc>>,
+
+    /// The IP address the session was last active from
+    pub last_active_ip: Option<IpAddr>,
+}
+
+/// A login through the legacy Matrix SSO login API, as exported in a
+/// [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedCompatSsoLogin {
+    /// The ID of the login
+    pub id: Ulid,
+
+    /// Where the client asked to be redirected
+    pub redirect_uri: Url,
+
+    /// When the login started
+    pub created_at: DateTime<Utc>,
+
+    /// When the user completed the login in their browser, if they did
+    pub fulfilled_at: Option<DateTime<Utc>>,
+
+    /// When the client exchanged the login for a session, if it did
+    pub exchanged_at: Option<DateTime<Utc>>,
+
+    /// The ID of the compatibility session the login created, if any
+    pub compat_session_id: Option<Ulid>,
+}
+
+/// A personal session, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedPersonalSession {
+    /// The ID of the session
+    pub id: Ulid,
+
+    /// The name given to the session
+    pub human_name: String,
+
+    /// The scope granted to the session
+    pub scope: String,
+
+    /// When the session was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the session was revoked, if it was
+    pub revoked_at: Option<DateTime<Utc>>,
+
+    /// When the session was last active
+    pub last_active_at: Option<DateTime<Utc>>,
+
+    /// The IP address the session was last active from
+    pub last_active_ip: Option<IpAddr>,
+}
+
+/// An audit log entry, as exported in a [`UserArchive`]
+///
+/// The IP address and user agent are only exported for actions the user
+/// performed themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedAuditEvent {
+    /// The ID of the event
+    pub id: Ulid,
+
+    /// When the action was performed
+    pub created_at: DateTime<Utc>,
+
+    /// The action, e.g. `user.lock`
+    pub action: AuditAction,
+
+    /// Whether the user performed the action themselves
+    pub by_user: bool,
+
+    /// The object affected by the action, if any
+    pub target_id: Option<Ulid>,
+
+    /// The IP address the action was performed from
+    pub ip_address: Option<IpAddr>,
+
+    /// The user agent the action was performed with
+    pub user_agent: Option<String>,
+
+    /// Additional, action-specific details
+    pub details: serde_json::Value,
+}
+
+/// An event sent to a webhook endpoint, as exported in a [`UserArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedWebhookDelivery {
+    /// The ID of the delivery
+    pub id: Ulid,
+
+    /// The kind of event
+    pub event: WebhookEvent,
+
+    /// The URL of the endpoint the event was sent to
+    pub url: Url,
+
+    /// The payload sent to the endpoint
+    pub payload: serde_json::Value,
+
+    /// When the event happened
+    pub created_at: DateTime<Utc>,
+
+    /// When the endpoint acknowledged the event, if it did
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Build a [`UserArchive`] of everything stored about a user
+///
+/// This only reads from the repository, so the caller can roll back or cancel
+/// it afterwards.
+///
+/// # Parameters
+///
+/// * `repo`: The repository to read from
+/// * `clock`: The clock used to timestamp the archive
+/// * `user`: The user to export
+///
+/// # Errors
+///
+/// Returns an error if the underlying repository fails
+#[allow(clippy::too_many_lines)]
+pub async fn export_user<R>(
+    repo: &mut R,
+    clock: &dyn Clock,
+    user: &User,
+) -> Result<UserArchive, R::Error>
+where
+    R: RepositoryAccess + ?Sized,
+{
+    let emails = repo
+        .user_email()
+        .all(user)
+        .await?
+        .into_iter()
+        .map(|email| ArchivedUserEmail {
+            id: email.id,
+            email: email.email,
+            created_at: email.created_at,
+        })
+        .collect();
+
+    let passwords = repo
+        .user_password()
+        .history(user, usize::MAX)
+        .await?
+        .into_iter()
+        .map(|password| ArchivedPassword {
+            id: password.id,
+            created_at: password.created_at,
+        })
+        .collect();
+
+    let passkeys = repo
+        .user_passkey()
+        .all(user)
+        .await?
+        .into_iter()
+        .map(|passkey| ArchivedPasskey {
+            id: passkey.id,
+            name: passkey.name,
+            aaguid: passkey.aaguid,
+            created_at: passkey.created_at,
+            last_used_at: passkey.last_used_at,
+        })
+        .collect();
+
+    let totp = repo
+        .user_totp()
+        .find_for_user(user)
+        .await?
+        .map(|totp| ArchivedTotp {
+            created_at: totp.created_at,
+            confirmed_at: totp.confirmed_at,
+            last_used_at: totp.last_used_at,
+        });
+
+    let ldap_link = repo
+        .user_ldap_link()
+        .find_for_user(user)
+        .await?
+        .map(|link| ArchivedLdapLink {
+            subject: link.subject,
+            created_at: link.created_at,
+        });
+
+    let mut upstream_oauth_links = Vec::new();
+    let mut links = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .upstream_oauth_link()
+            .list(UpstreamOAuthLinkFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let link = edge.node;
+            upstream_oauth_links.push(ArchivedUpstreamOAuthLink {
+                id: link.id,
+                provider_id: link.provider_id,
+                subject: link.subject.clone(),
+                human_account_name: link.human_account_name.clone(),
+                created_at: link.created_at,
+            });
+            links.push(link);
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    let mut upstream_oauth_sessions = Vec::new();
+    for link in &links {
+        let mut cursor = Pagination::first(PAGE_SIZE);
+        loop {
+            let page = repo
+                .upstream_oauth_session()
+                .list(UpstreamOAuthSessionFilter::new().for_link(link), cursor)
+                .await?;
+
+            for edge in page.edges {
+                let session = edge.node;
+                upstream_oauth_sessions.push(ArchivedUpstreamOAuthSession {
+                    id: session.id,
+                    provider_id: session.provider_id,
+                    link_id: session.link_id(),
+                    created_at: session.created_at,
+                    completed_at: session.completed_at(),
+                    id_token_claims: session.id_token_claims().cloned(),
+                    userinfo: session.userinfo().cloned(),
+                });
+                cursor = cursor.after(edge.cursor);
+            }
+
+            if !page.has_next_page {
+                break;
+            }
+        }
+    }
+
+    let terms = repo
+        .user_terms()
+        .all(user)
+        .await?
+        .into_iter()
+        .map(|terms| ArchivedTerms {
+            terms_url: terms.terms_url,
+            accepted_at: terms.created_at,
+        })
+        .collect();
+
+    let registration = repo
+        .user_registration()
+        .find_completed_by_username(&user.username)
+        .await?
+        .map(|registration| ArchivedRegistration {
+            id: registration.id,
+            display_name: registration.display_name,
+            terms_url: registration.terms_url,
+            ip_address: registration.ip_address,
+            user_agent: registration.user_agent,
+            created_at: registration.created_at,
+            completed_at: registration.completed_at,
+        });
+
+    let email_authentications = repo
+        .user_email()
+        .all_authentications(user)
+        .await?
+        .into_iter()
+        .map(|authentication| ArchivedEmailAuthentication {
+            id: authentication.id,
+            email: authentication.email,
+            created_at: authentication.created_at,
+            completed_at: authentication.completed_at,
+        })
+        .collect();
+
+    let recovery_sessions = repo
+        .user_recovery()
+        .all_sessions(user)
+        .await?
+        .into_iter()
+        .map(|session| ArchivedRecoverySession {
+            id: session.id,
+            email: session.email,
+            ip_address: session.ip_address,
+            user_agent: session.user_agent,
+            locale: session.locale,
+            created_at: session.created_at,
+            consumed_at: session.consumed_at,
+        })
+        .collect();
+
+    let mut browser_sessions = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .browser_session()
+            .list(BrowserSessionFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let session = edge.node;
+            browser_sessions.push(ArchivedBrowserSession {
+                id: session.id,
+                created_at: session.created_at,
+                finished_at: session.finished_at,
+                user_agent: session.user_agent,
+                last_active_at: session.last_active_at,
+                last_active_ip: session.last_active_ip,
+            });
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    let mut oauth2_sessions = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .oauth2_session()
+            .list(OAuth2SessionFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let session = edge.node;
+            oauth2_sessions.push(ArchivedOAuth2Session {
+                id: session.id,
+                client_id: session.client_id,
+                scope: session.scope.to_string(),
+                human_name: session.human_name.clone(),
+                impersonated: session.impersonation.is_some(),
+                created_at: session.created_at,
+                finished_at: session.finished_at(),
+                user_agent: session.user_agent.clone(),
+                last_active_at: session.last_active_at,
+                last_active_ip: session.last_active_ip,
+            });
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    let mut compat_sessions = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .compat_session()
+            .list(CompatSessionFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let (session, _) = edge.node;
+            compat_sessions.push(ArchivedCompatSession {
+                id: session.id,
+                device_id: session
+                    .device
+                    .as_ref()
+                    .map(|device| device.as_str().to_owned()),
+                human_name: session.human_name.clone(),
+                impersonated: session.impersonation.is_some(),
+                created_at: session.created_at,
+                finished_at: session.finished_at(),
+                user_agent: session.user_agent.clone(),
+                last_active_at: session.last_active_at,
+                last_active_ip: session.last_active_ip,
+            });
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    let mut compat_sso_logins = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .compat_sso_login()
+            .list(CompatSsoLoginFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let login = edge.node;
+            compat_sso_logins.push(ArchivedCompatSsoLogin {
+                id: login.id,
+                fulfilled_at: login.fulfilled_at(),
+                exchanged_at: login.exchanged_at(),
+                compat_session_id: login.session_id(),
+                redirect_uri: login.redirect_uri,
+                created_at: login.created_at,
+            });
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    let mut personal_sessions = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .personal_session()
+            .list(PersonalSessionFilter::new().for_actor_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let (session, _) = edge.node;
+            personal_sessions.push(ArchivedPersonalSession {
+                id: session.id,
+                revoked_at: session.state.revoked_at(),
+                human_name: session.human_name,
+                scope: session.scope.to_string(),
+                created_at: session.created_at,
+                last_active_at: session.last_active_at,
+                last_active_ip: session.last_active_ip,
+            });
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    // Events can both be performed by and about the user, so they are
+    // deduplicated by ID, which also sorts them chronologically
+    let mut audit_events = BTreeMap::new();
+    for filter in [
+        AuditEventFilter::new().for_actor_user(user),
+        AuditEventFilter::new().for_target_user(user),
+    ] {
+        let mut cursor = Pagination::first(PAGE_SIZE);
+        loop {
+            let page = repo.audit_event().list(filter, cursor).await?;
+
+            for edge in page.edges {
+                let event = edge.node;
+                let by_user = event.actor.user_id == Some(user.id);
+                audit_events.insert(
+                    event.id,
+                    ArchivedAuditEvent {
+                        id: event.id,
+                        created_at: event.created_at,
+                        action: event.action,
+                        by_user,
+                        target_id: event.target_id,
+                        ip_address: event.actor.ip_address.filter(|_| by_user),
+                        user_agent: event.actor.user_agent.filter(|_| by_user),
+                        details: event.details,
+                    },
+                );
+                cursor = cursor.after(edge.cursor);
+            }
+
+            if !page.has_next_page {
+                break;
+            }
+        }
+    }
+
+    let mut webhook_deliveries = Vec::new();
+    let mut cursor = Pagination::first(PAGE_SIZE);
+    loop {
+        let page = repo
+            .webhook_delivery()
+            .list(WebhookDeliveryFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            let delivery = edge.node;
+            let delivered_at = match delivery.state {
+                WebhookDeliveryState::Delivered { delivered_at } => Some(delivered_at),
+                WebhookDeliveryState::Pending | WebhookDeliveryState::Failed { .. } => None,
+            };
+            webhook_deliveries.push(ArchivedWebhookDelivery {
+                id: delivery.id,
+                event: delivery.event,
+                url: delivery.url,
+                payload: delivery.payload,
+                created_at: delivery.created_at,
+                delivered_at,
+            });
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    Ok(UserArchive {
+        version: USER_ARCHIVE_VERSION,
+        exported_at: clock.now(),
+        user: ArchivedUser {
+            id: user.id,
+            username: user.username.clone(),
+            created_at: user.created_at,
+            locked_at: user.locked_at,
+            deactivated_at: user.deactivated_at,
+            can_request_admin: user.can_request_admin,
+            is_guest: user.is_guest,
+        },
+        emails,
+        passwords,
+        passkeys,
+        totp,
+        ldap_link,
+        upstream_oauth_links,
+        upstream_oauth_sessions,
+        terms,
+        registration,
+        email_authentications,
+        recovery_sessions,
+        browser_sessions,
+        oauth2_sessions,
+        compat_sessions,
+        compat_sso_logins,
+        personal_sessions,
+        audit_events: audit_events.into_values().collect(),
+        webhook_deliveries,
+    })
+}
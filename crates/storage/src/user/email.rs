@@ -277,6 +277,21 @@ pub trait UserEmailRepository: Send + Sync {
         id: Ulid,
     ) -> Result<Option<UserEmailAuthentication>, Self::Error>;
 
+    /// Get all the [`UserEmailAuthentication`]s started by a [`User`], either
+    /// from one of their browser sessions or while registering
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] for whom to get the authentications
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying repository fails
+    async fn all_authentications(
+        &mut self,
+        user: &User,
+    ) -> Result<Vec<UserEmailAuthentication>, Self::Error>;
+
     /// Find a [`UserEmailAuthenticationCode`] by its code and session
     ///
     /// # Parameters
@@ -368,6 +383,11 @@ repository_impl!(UserEmailRepository:
         id: Ulid,
     ) -> Result<Option<UserEmailAuthentication>, Self::Error>;
 
+    async fn all_authentications(
+        &mut self,
+        user: &User,
+    ) -> Result<Vec<UserEmailAuthentication>, Self::Error>;
+
     async fn find_authentication_code(
         &mut self,
         authentication: &UserEmailAuthentication,
@@ -0,0 +1,88 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use mas_data_model::{Clock, User, UserExport};
+use rand_core::RngCore;
+use ulid::Ulid;
+
+use crate::repository_impl;
+
+/// A [`UserExportRepository`] helps interacting with [`UserExport`] saved in
+/// the storage backend
+#[async_trait]
+pub trait UserExportRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Lookup a [`UserExport`] by its ID
+    ///
+    /// Returns `None` if no [`UserExport`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`UserExport`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserExport>, Self::Error>;
+
+    /// Start a new [`UserExport`] for a [`User`]
+    ///
+    /// Returns the newly created [`UserExport`], without an archive
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user`: The [`User`] to export
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+    ) -> Result<UserExport, Self::Error>;
+
+    /// Save the archive of a [`UserExport`] and mark it as completed
+    ///
+    /// Returns the updated [`UserExport`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_export`: The [`UserExport`] to complete
+    /// * `archive`: The archive of everything stored about the user
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn complete(
+        &mut self,
+        clock: &dyn Clock,
+        user_export: UserExport,
+        archive: serde_json::Value,
+    ) -> Result<UserExport, Self::Error>;
+}
+
+repository_impl!(UserExportRepository:
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserExport>, Self::Error>;
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+    ) -> Result<UserExport, Self::Error>;
+    async fn complete(
+        &mut self,
+        clock: &dyn Clock,
+        user_export: UserExport,
+        archive: serde_json::Value,
+    ) -> Result<UserExport, Self::Error>;
+);
@@ -7,28 +7,44 @@
 //! Repositories to interact with entities related to user accounts
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use mas_data_model::{Clock, User};
 use rand_core::RngCore;
 use ulid::Ulid;
 
 use crate::{Page, Pagination, repository_impl};
 
+mod archive;
 mod email;
+mod export;
+mod ldap;
+mod passkey;
 mod password;
 mod recovery;
 mod registration;
 mod registration_token;
 mod session;
 mod terms;
+mod totp;
 
 pub use self::{
+    archive::{
+        ArchivedAuditEvent, ArchivedBrowserSession, ArchivedCompatSession, ArchivedLdapLink,
+        ArchivedOAuth2Session, ArchivedPasskey, ArchivedPassword, ArchivedPersonalSession,
+        ArchivedRegistration, ArchivedTerms, ArchivedTotp, ArchivedUpstreamOAuthLink,
+        ArchivedUser, ArchivedUserEmail, USER_ARCHIVE_VERSION, UserArchive, export_user,
+    },
     email::{UserEmailFilter, UserEmailRepository},
+    export::UserExportRepository,
+    ldap::UserLdapLinkRepository,
+    passkey::{UserPasskeyFilter, UserPasskeyRepository},
     password::UserPasswordRepository,
     recovery::UserRecoveryRepository,
     registration::UserRegistrationRepository,
     registration_token::{UserRegistrationTokenFilter, UserRegistrationTokenRepository},
     session::{BrowserSessionFilter, BrowserSessionRepository},
     terms::UserTermsRepository,
+    totp::UserTotpRepository,
 };
 
 /// The state of a user account
@@ -76,6 +92,8 @@ pub struct UserFilter<'a> {
     state: Option<UserState>,
     can_request_admin: Option<bool>,
     is_guest: Option<bool>,
+    is_erased: Option<bool>,
+    deactivated_before: Option<DateTime<Utc>>,
     search: Option<&'a str>,
 }
 
@@ -135,6 +153,27 @@ impl<'a> UserFilter<'a> {
         self
     }
 
+    /// Filter for users whose personal data was erased
+    #[must_use]
+    pub fn erased_only(mut self) -> Self {
+        self.is_erased = Some(true);
+        self
+    }
+
+    /// Filter for users whose personal data wasn't erased
+    #[must_use]
+    pub fn non_erased_only(mut self) -> Self {
+        self.is_erased = Some(false);
+        self
+    }
+
+    /// Filter for users deactivated before the given time
+    #[must_use]
+    pub fn with_deactivated_before(mut self, deactivated_before: DateTime<Utc>) -> Self {
+        self.deactivated_before = Some(deactivated_before);
+        self
+    }
+
     /// Filter for users that match the given search string
     #[must_use]
     pub fn matching_search(mut self, search: &'a str) -> Self {
@@ -166,6 +205,22 @@ impl<'a> UserFilter<'a> {
         self.is_guest
     }
 
+    /// Get the is erased filter
+    ///
+    /// Returns [`None`] if no is erased filter was set
+    #[must_use]
+    pub fn is_erased(&self) -> Option<bool> {
+        self.is_erased
+    }
+
+    /// Get the deactivated before filter
+    ///
+    /// Returns [`None`] if no deactivated before filter was set
+    #[must_use]
+    pub fn deactivated_before(&self) -> Option<DateTime<Utc>> {
+        self.deactivated_before
+    }
+
     /// Get the search filter
     ///
     /// Returns [`None`] if no search filter was set
@@ -295,6 +350,25 @@ pub trait UserRepository: Send + Sync {
     /// Returns [`Self::Error`] if the underlying repository fails
     async fn reactivate(&mut self, user: User) -> Result<User, Self::Error>;
 
+    /// Irreversibly erase the personal data of a deactivated [`User`]
+    ///
+    /// This removes their email addresses, credentials and links to upstream
+    /// identities, and scrubs IP addresses, user agents and device names from
+    /// their sessions and from the audit log. The user itself is kept, so
+    /// that its username can't be registered again.
+    ///
+    /// Returns the erased [`User`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user`: The [`User`] to erase
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn erase(&mut self, clock: &dyn Clock, user: User) -> Result<User, Self::Error>;
+
     /// Set whether a [`User`] can request admin
     ///
     /// Returns the [`User`] with the new `can_request_admin` value
@@ -367,6 +441,7 @@ repository_impl!(UserRepository:
     async fn unlock(&mut self, user: User) -> Result<User, Self::Error>;
     async fn deactivate(&mut self, clock: &dyn Clock, user: User) -> Result<User, Self::Error>;
     async fn reactivate(&mut self, user: User) -> Result<User, Self::Error>;
+    async fn erase(&mut self, clock: &dyn Clock, user: User) -> Result<User, Self::Error>;
     async fn set_can_request_admin(
         &mut self,
         user: User,
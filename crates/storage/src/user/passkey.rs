@@ -0,0 +1,349 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use mas_data_model::{BrowserSession, Clock, User, UserPasskey, UserPasskeyChallenge};
+use rand_core::RngCore;
+use ulid::Ulid;
+
+use crate::{Pagination, pagination::Page, repository_impl};
+
+/// Filter parameters for listing user passkeys
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct UserPasskeyFilter<'a> {
+    user: Option<&'a User>,
+}
+
+impl<'a> UserPasskeyFilter<'a> {
+    /// Create a new [`UserPasskeyFilter`] with default values
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter for passkeys of a specific user
+    #[must_use]
+    pub fn for_user(mut self, user: &'a User) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Get the user filter
+    ///
+    /// Returns [`None`] if no user filter is set
+    #[must_use]
+    pub fn user(&self) -> Option<&User> {
+        self.user
+    }
+}
+
+/// A [`UserPasskeyRepository`] helps interacting with [`UserPasskey`] and
+/// [`UserPasskeyChallenge`] saved in the storage backend
+#[async_trait]
+pub trait UserPasskeyRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Lookup an [`UserPasskey`] by its ID
+    ///
+    /// Returns `None` if no [`UserPasskey`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`UserPasskey`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserPasskey>, Self::Error>;
+
+    /// Find an [`UserPasskey`] by its `WebAuthn` credential ID
+    ///
+    /// Returns `None` if no matching [`UserPasskey`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `credential_id`: The base64url-encoded credential ID to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn find_by_credential_id(
+        &mut self,
+        credential_id: &str,
+    ) -> Result<Option<UserPasskey>, Self::Error>;
+
+    /// Get all [`UserPasskey`] of a [`User`]
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] for whom to lookup the [`UserPasskey`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn all(&mut self, user: &User) -> Result<Vec<UserPasskey>, Self::Error>;
+
+    /// List [`UserPasskey`] with the given filter and pagination
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    /// * `pagination`: The pagination parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn list(
+        &mut self,
+        filter: UserPasskeyFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<UserPasskey>, Self::Error>;
+
+    /// Count the [`UserPasskey`] with the given filter
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn count(&mut self, filter: UserPasskeyFilter<'_>) -> Result<usize, Self::Error>;
+
+    /// Create a new [`UserPasskey`] for a [`User`]
+    ///
+    /// Returns the newly created [`UserPasskey`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock to use
+    /// * `user`: The [`User`] for whom to create the [`UserPasskey`]
+    /// * `name`: A human-readable name for the passkey
+    /// * `credential_id`: The base64url-encoded credential ID
+    /// * `transports`: The transports hinted by the authenticator
+    /// * `public_key`: The COSE-encoded public key of the credential
+    /// * `aaguid`: The AAGUID of the authenticator, if known
+    /// * `sign_count`: The initial signature counter
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    #[expect(clippy::too_many_arguments)]
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        name: String,
+        credential_id: String,
+        transports: Vec<String>,
+        public_key: Vec<u8>,
+        aaguid: Option<String>,
+        sign_count: u32,
+    ) -> Result<UserPasskey, Self::Error>;
+
+    /// Rename a [`UserPasskey`]
+    ///
+    /// Returns the updated [`UserPasskey`]
+    ///
+    /// # Parameters
+    ///
+    /// * `user_passkey`: The [`UserPasskey`] to rename
+    /// * `name`: The new name of the passkey
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn rename(
+        &mut self,
+        user_passkey: UserPasskey,
+        name: String,
+    ) -> Result<UserPasskey, Self::Error>;
+
+    /// Record a successful use of a [`UserPasskey`], updating its signature
+    /// counter and last use timestamp
+    ///
+    /// Returns the updated [`UserPasskey`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock to use
+    /// * `user_passkey`: The [`UserPasskey`] which was used
+    /// * `sign_count`: The new signature counter reported by the authenticator
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn record_use(
+        &mut self,
+        clock: &dyn Clock,
+        user_passkey: UserPasskey,
+        sign_count: u32,
+    ) -> Result<UserPasskey, Self::Error>;
+
+    /// Delete a [`UserPasskey`]
+    ///
+    /// # Parameters
+    ///
+    /// * `user_passkey`: The [`UserPasskey`] to delete
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn remove(&mut self, user_passkey: UserPasskey) -> Result<(), Self::Error>;
+
+    /// Create a new [`UserPasskeyChallenge`] to authenticate with a passkey
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock to use
+    /// * `challenge`: The random challenge sent to the browser
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_challenge(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        challenge: Vec<u8>,
+    ) -> Result<UserPasskeyChallenge, Self::Error>;
+
+    /// Create a new [`UserPasskeyChallenge`] to register a passkey, bound to
+    /// a [`BrowserSession`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock to use
+    /// * `challenge`: The random challenge sent to the browser
+    /// * `browser_session`: The [`BrowserSession`] registering the passkey
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_challenge_for_session(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        challenge: Vec<u8>,
+        browser_session: &BrowserSession,
+    ) -> Result<UserPasskeyChallenge, Self::Error>;
+
+    /// Lookup a [`UserPasskeyChallenge`] by its ID
+    ///
+    /// Returns `None` if no [`UserPasskeyChallenge`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`UserPasskeyChallenge`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup_challenge(
+        &mut self,
+        id: Ulid,
+    ) -> Result<Option<UserPasskeyChallenge>, Self::Error>;
+
+    /// Mark a [`UserPasskeyChallenge`] as completed, so that it can't be
+    /// reused
+    ///
+    /// Returns the updated [`UserPasskeyChallenge`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock to use
+    /// * `challenge`: The [`UserPasskeyChallenge`] to complete
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn complete_challenge(
+        &mut self,
+        clock: &dyn Clock,
+        challenge: UserPasskeyChallenge,
+    ) -> Result<UserPasskeyChallenge, Self::Error>;
+
+    /// Cleanup old [`UserPasskeyChallenge`]s
+    ///
+    /// Returns the number of challenges removed
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock to use
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn cleanup_challenges(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(UserPasskeyRepository:
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserPasskey>, Self::Error>;
+    async fn find_by_credential_id(
+        &mut self,
+        credential_id: &str,
+    ) -> Result<Option<UserPasskey>, Self::Error>;
+    async fn all(&mut self, user: &User) -> Result<Vec<UserPasskey>, Self::Error>;
+    async fn list(
+        &mut self,
+        filter: UserPasskeyFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<UserPasskey>, Self::Error>;
+    async fn count(&mut self, filter: UserPasskeyFilter<'_>) -> Result<usize, Self::Error>;
+
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        name: String,
+        credential_id: String,
+        transports: Vec<String>,
+        public_key: Vec<u8>,
+        aaguid: Option<String>,
+        sign_count: u32,
+    ) -> Result<UserPasskey, Self::Error>;
+    async fn rename(
+        &mut self,
+        user_passkey: UserPasskey,
+        name: String,
+    ) -> Result<UserPasskey, Self::Error>;
+    async fn record_use(
+        &mut self,
+        clock: &dyn Clock,
+        user_passkey: UserPasskey,
+        sign_count: u32,
+    ) -> Result<UserPasskey, Self::Error>;
+    async fn remove(&mut self, user_passkey: UserPasskey) -> Result<(), Self::Error>;
+
+    async fn add_challenge(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        challenge: Vec<u8>,
+    ) -> Result<UserPasskeyChallenge, Self::Error>;
+    async fn add_challenge_for_session(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        challenge: Vec<u8>,
+        browser_session: &BrowserSession,
+    ) -> Result<UserPasskeyChallenge, Self::Error>;
+    async fn lookup_challenge(
+        &mut self,
+        id: Ulid,
+    ) -> Result<Option<UserPasskeyChallenge>, Self::Error>;
+    async fn complete_challenge(
+        &mut self,
+        clock: &dyn Clock,
+        challenge: UserPasskeyChallenge,
+    ) -> Result<UserPasskeyChallenge, Self::Error>;
+    async fn cleanup_challenges(&mut self, clock: &dyn Clock) -> Result<usize, Self::Error>;
+);
@@ -5,6 +5,7 @@
 // Please see LICENSE files in the repository root for full details.
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use mas_data_model::{Clock, Password, User};
 use rand_core::RngCore;
 
@@ -30,6 +31,36 @@ pub trait UserPasswordRepository: Send + Sync {
     /// Returns [`Self::Error`] if underlying repository fails
     async fn active(&mut self, user: &User) -> Result<Option<Password>, Self::Error>;
 
+    /// Get the last passwords a user set, most recent first, including the
+    /// active one
+    ///
+    /// Passwords which were upgraded to a new hashing scheme are only returned
+    /// once, with their most recent hash.
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The user to get the passwords for
+    /// * `limit`: The maximum number of passwords to return
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if underlying repository fails
+    async fn history(&mut self, user: &User, limit: usize) -> Result<Vec<Password>, Self::Error>;
+
+    /// Get the time at which the user set a password
+    ///
+    /// This is the creation time of the first [`Password`] in the chain of
+    /// upgrades leading to this one.
+    ///
+    /// # Parameters
+    ///
+    /// * `password`: The password to look at
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if underlying repository fails
+    async fn set_at(&mut self, password: &Password) -> Result<DateTime<Utc>, Self::Error>;
+
     /// Set a new password for a user
     ///
     /// Returns the newly created [`Password`]
@@ -59,6 +90,8 @@ pub trait UserPasswordRepository: Send + Sync {
 
 repository_impl!(UserPasswordRepository:
     async fn active(&mut self, user: &User) -> Result<Option<Password>, Self::Error>;
+    async fn history(&mut self, user: &User, limit: usize) -> Result<Vec<Password>, Self::Error>;
+    async fn set_at(&mut self, password: &Password) -> Result<DateTime<Utc>, Self::Error>;
     async fn add(
         &mut self,
         rng: &mut (dyn RngCore + Send),
@@ -7,7 +7,7 @@
 use std::net::IpAddr;
 
 use async_trait::async_trait;
-use mas_data_model::{Clock, UserEmail, UserRecoverySession, UserRecoveryTicket};
+use mas_data_model::{Clock, User, UserEmail, UserRecoverySession, UserRecoveryTicket};
 use rand_core::RngCore;
 use ulid::Ulid;
 
@@ -36,6 +36,19 @@ pub trait UserRecoveryRepository: Send + Sync {
         id: Ulid,
     ) -> Result<Option<UserRecoverySession>, Self::Error>;
 
+    /// Get all the [`UserRecoverySession`]s which concern a [`User`], either
+    /// because they were started for one of their email addresses, or because
+    /// a ticket was sent to one of them
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] for whom to get the sessions
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn all_sessions(&mut self, user: &User) -> Result<Vec<UserRecoverySession>, Self::Error>;
+
     /// Create a new [`UserRecoverySession`] for the given email
     ///
     /// Returns the newly created [`UserRecoverySession`]
@@ -126,6 +139,8 @@ pub trait UserRecoveryRepository: Send + Sync {
 repository_impl!(UserRecoveryRepository:
     async fn lookup_session(&mut self, id: Ulid) -> Result<Option<UserRecoverySession>, Self::Error>;
 
+    async fn all_sessions(&mut self, user: &User) -> Result<Vec<UserRecoverySession>, Self::Error>;
+
     async fn add_session(
         &mut self,
         rng: &mut (dyn RngCore + Send),
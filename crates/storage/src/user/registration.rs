@@ -33,6 +33,23 @@ pub trait UserRegistrationRepository: Send + Sync {
     /// Returns [`Self::Error`] if the underlying repository fails
     async fn lookup(&mut self, id: Ulid) -> Result<Option<UserRegistration>, Self::Error>;
 
+    /// Find the completed [`UserRegistration`] which created the user with the
+    /// given username
+    ///
+    /// Returns `None` if the user wasn't created through a registration
+    ///
+    /// # Parameters
+    ///
+    /// * `username`: The username of the user
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn find_completed_by_username(
+        &mut self,
+        username: &str,
+    ) -> Result<Option<UserRegistration>, Self::Error>;
+
     /// Create a new [`UserRegistration`] session
     ///
     /// Returns the newly created [`UserRegistration`]
@@ -179,6 +196,10 @@ pub trait UserRegistrationRepository: Send + Sync {
 
 repository_impl!(UserRegistrationRepository:
     async fn lookup(&mut self, id: Ulid) -> Result<Option<UserRegistration>, Self::Error>;
+    async fn find_completed_by_username(
+        &mut self,
+        username: &str,
+    ) -> Result<Option<UserRegistration>, Self::Error>;
     async fn add(
         &mut self,
         rng: &mut (dyn RngCore + Send),
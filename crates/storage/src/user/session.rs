@@ -10,6 +10,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use mas_data_model::{
     Authentication, BrowserSession, Clock, Password, UpstreamOAuthAuthorizationSession, User,
+    UserLdapLink, UserPasskey, UserRecoveryCode, UserTotp,
 };
 use rand_core::RngCore;
 use ulid::Ulid;
@@ -279,6 +280,91 @@ pub trait BrowserSessionRepository: Send + Sync {
         upstream_oauth_session: &UpstreamOAuthAuthorizationSession,
     ) -> Result<Authentication, Self::Error>;
 
+    /// Authenticate a [`BrowserSession`] with the given [`UserPasskey`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_session`: The session to authenticate
+    /// * `user_passkey`: The passkey which was used to authenticate
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn authenticate_with_passkey(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_passkey: &UserPasskey,
+    ) -> Result<Authentication, Self::Error>;
+
+    /// Authenticate a [`BrowserSession`] with the given [`UserTotp`], as a
+    /// second factor
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_session`: The session to authenticate
+    /// * `user_totp`: The TOTP secret which was used to authenticate
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn authenticate_with_totp(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_totp: &UserTotp,
+    ) -> Result<Authentication, Self::Error>;
+
+    /// Authenticate a [`BrowserSession`] with the given [`UserRecoveryCode`],
+    /// in place of a second factor
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_session`: The session to authenticate
+    /// * `user_recovery_code`: The recovery code which was used to
+    ///   authenticate
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn authenticate_with_recovery_code(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_recovery_code: &UserRecoveryCode,
+    ) -> Result<Authentication, Self::Error>;
+
+    /// Authenticate a [`BrowserSession`] with a password checked against the
+    /// LDAP directory entry linked with the given [`UserLdapLink`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_session`: The session to authenticate
+    /// * `user_ldap_link`: The link to the directory entry which was used to
+    ///   authenticate
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn authenticate_with_ldap(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_ldap_link: &UserLdapLink,
+    ) -> Result<Authentication, Self::Error>;
+
     /// Get the last successful authentication for a [`BrowserSession`]
     ///
     /// # Params
@@ -354,6 +440,38 @@ repository_impl!(BrowserSessionRepository:
         upstream_oauth_session: &UpstreamOAuthAuthorizationSession,
     ) -> Result<Authentication, Self::Error>;
 
+    async fn authenticate_with_passkey(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_passkey: &UserPasskey,
+    ) -> Result<Authentication, Self::Error>;
+
+    async fn authenticate_with_totp(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_totp: &UserTotp,
+    ) -> Result<Authentication, Self::Error>;
+
+    async fn authenticate_with_recovery_code(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_recovery_code: &UserRecoveryCode,
+    ) -> Result<Authentication, Self::Error>;
+
+    async fn authenticate_with_ldap(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user_session: &BrowserSession,
+        user_ldap_link: &UserLdapLink,
+    ) -> Result<Authentication, Self::Error>;
+
     async fn get_last_authentication(
         &mut self,
         user_session: &BrowserSession,
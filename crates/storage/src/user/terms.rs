@@ -5,7 +5,7 @@
 // Please see LICENSE files in the repository root for full details.
 
 use async_trait::async_trait;
-use mas_data_model::{Clock, User};
+use mas_data_model::{Clock, User, UserTerms};
 use rand_core::RngCore;
 use url::Url;
 
@@ -37,6 +37,17 @@ pub trait UserTermsRepository: Send + Sync {
         user: &User,
         terms_url: Url,
     ) -> Result<(), Self::Error>;
+
+    /// Get all the terms of service accepted by a [`User`], oldest first
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] to get the accepted terms for
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn all(&mut self, user: &User) -> Result<Vec<UserTerms>, Self::Error>;
 }
 
 repository_impl!(UserTermsRepository:
@@ -47,4 +58,6 @@ repository_impl!(UserTermsRepository:
         user: &User,
         terms_url: Url,
     ) -> Result<(), Self::Error>;
+
+    async fn all(&mut self, user: &User) -> Result<Vec<UserTerms>, Self::Error>;
 );
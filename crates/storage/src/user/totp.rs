@@ -0,0 +1,231 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use async_trait::async_trait;
+use mas_data_model::{Clock, User, UserRecoveryCode, UserTotp};
+use rand_core::RngCore;
+use ulid::Ulid;
+
+use crate::repository_impl;
+
+/// A [`UserTotpRepository`] helps interacting with [`UserTotp`] and
+/// [`UserRecoveryCode`] saved in the storage backend
+#[async_trait]
+pub trait UserTotpRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Lookup an [`UserTotp`] by its ID
+    ///
+    /// Returns `None` if no [`UserTotp`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`UserTotp`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserTotp>, Self::Error>;
+
+    /// Find the [`UserTotp`] of a [`User`], confirmed or not
+    ///
+    /// Returns `None` if the user has no [`UserTotp`]
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] for whom to lookup the [`UserTotp`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn find_for_user(&mut self, user: &User) -> Result<Option<UserTotp>, Self::Error>;
+
+    /// Create a new, unconfirmed [`UserTotp`] for a [`User`]
+    ///
+    /// Returns the newly created [`UserTotp`]. The user must not already have
+    /// a [`UserTotp`], see [`Self::remove`].
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user`: The [`User`] for whom to create the [`UserTotp`]
+    /// * `encrypted_secret`: The shared secret, encrypted
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        encrypted_secret: String,
+    ) -> Result<UserTotp, Self::Error>;
+
+    /// Mark a [`UserTotp`] as confirmed
+    ///
+    /// Returns the updated [`UserTotp`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_totp`: The [`UserTotp`] to confirm
+    /// * `step`: The time step of the code used to confirm the enrolment
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn confirm(
+        &mut self,
+        clock: &dyn Clock,
+        user_totp: UserTotp,
+        step: i64,
+    ) -> Result<UserTotp, Self::Error>;
+
+    /// Record that a code generated with a [`UserTotp`] was used
+    ///
+    /// Returns the updated [`UserTotp`], or `None` if a code from the same time
+    /// step or a later one was already used, meaning that the code is being
+    /// replayed
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user_totp`: The [`UserTotp`] which was used
+    /// * `step`: The time step of the code which was used
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn record_use(
+        &mut self,
+        clock: &dyn Clock,
+        user_totp: UserTotp,
+        step: i64,
+    ) -> Result<Option<UserTotp>, Self::Error>;
+
+    /// Delete a [`UserTotp`]
+    ///
+    /// # Parameters
+    ///
+    /// * `user_totp`: The [`UserTotp`] to delete
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn remove(&mut self, user_totp: UserTotp) -> Result<(), Self::Error>;
+
+    /// Replace the [`UserRecoveryCode`]s of a [`User`] with new ones
+    ///
+    /// Returns the newly created [`UserRecoveryCode`]s
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `user`: The [`User`] for whom to create the [`UserRecoveryCode`]s
+    /// * `encrypted_codes`: The codes, encrypted
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn replace_recovery_codes(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        encrypted_codes: Vec<String>,
+    ) -> Result<Vec<UserRecoveryCode>, Self::Error>;
+
+    /// Get the [`UserRecoveryCode`]s of a [`User`] which were not used yet
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] for whom to lookup the [`UserRecoveryCode`]s
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn unused_recovery_codes(
+        &mut self,
+        user: &User,
+    ) -> Result<Vec<UserRecoveryCode>, Self::Error>;
+
+    /// Mark a [`UserRecoveryCode`] as used
+    ///
+    /// Returns the updated [`UserRecoveryCode`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `recovery_code`: The [`UserRecoveryCode`] to mark as used
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails, or if the
+    /// code was already used
+    async fn consume_recovery_code(
+        &mut self,
+        clock: &dyn Clock,
+        recovery_code: UserRecoveryCode,
+    ) -> Result<UserRecoveryCode, Self::Error>;
+
+    /// Delete all the [`UserRecoveryCode`]s of a [`User`]
+    ///
+    /// Returns the number of deleted [`UserRecoveryCode`]s
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: The [`User`] for whom to delete the [`UserRecoveryCode`]s
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn remove_recovery_codes(&mut self, user: &User) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(UserTotpRepository:
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<UserTotp>, Self::Error>;
+    async fn find_for_user(&mut self, user: &User) -> Result<Option<UserTotp>, Self::Error>;
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        encrypted_secret: String,
+    ) -> Result<UserTotp, Self::Error>;
+    async fn confirm(
+        &mut self,
+        clock: &dyn Clock,
+        user_totp: UserTotp,
+        step: i64,
+    ) -> Result<UserTotp, Self::Error>;
+    async fn record_use(
+        &mut self,
+        clock: &dyn Clock,
+        user_totp: UserTotp,
+        step: i64,
+    ) -> Result<Option<UserTotp>, Self::Error>;
+    async fn remove(&mut self, user_totp: UserTotp) -> Result<(), Self::Error>;
+    async fn replace_recovery_codes(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        user: &User,
+        encrypted_codes: Vec<String>,
+    ) -> Result<Vec<UserRecoveryCode>, Self::Error>;
+    async fn unused_recovery_codes(
+        &mut self,
+        user: &User,
+    ) -> Result<Vec<UserRecoveryCode>, Self::Error>;
+    async fn consume_recovery_code(
+        &mut self,
+        clock: &dyn Clock,
+        recovery_code: UserRecoveryCode,
+    ) -> Result<UserRecoveryCode, Self::Error>;
+    async fn remove_recovery_codes(&mut self, user: &User) -> Result<usize, Self::Error>;
+);
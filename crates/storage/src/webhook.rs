@@ -0,0 +1,301 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Repositories to interact with the deliveries of webhook events
+
+use async_trait::async_trait;
+use mas_data_model::{Clock, User, WebhookDelivery, WebhookEvent};
+use rand_core::RngCore;
+use ulid::Ulid;
+use url::Url;
+
+use crate::{Page, Pagination, repository_impl};
+
+/// The state of a webhook delivery, used for filtering
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebhookDeliveryState {
+    /// The delivery is still being attempted
+    Pending,
+
+    /// The endpoint acknowledged the event
+    Delivered,
+
+    /// The delivery was abandoned
+    Failed,
+}
+
+impl WebhookDeliveryState {
+    /// Returns true if the filter is set to `Pending`
+    #[must_use]
+    pub fn is_pending(self) -> bool {
+        matches!(self, Self::Pending)
+    }
+
+    /// Returns true if the filter is set to `Delivered`
+    #[must_use]
+    pub fn is_delivered(self) -> bool {
+        matches!(self, Self::Delivered)
+    }
+
+    /// Returns true if the filter is set to `Failed`
+    #[must_use]
+    pub fn is_failed(self) -> bool {
+        matches!(self, Self::Failed)
+    }
+}
+
+/// Filter parameters for listing webhook deliveries
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct WebhookDeliveryFilter<'a> {
+    event: Option<WebhookEvent>,
+    state: Option<WebhookDeliveryState>,
+    user: Option<&'a User>,
+}
+
+impl<'a> WebhookDeliveryFilter<'a> {
+    /// Create a new [`WebhookDeliveryFilter`] with default values
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter for deliveries of the given event
+    #[must_use]
+    pub fn with_event(mut self, event: WebhookEvent) -> Self {
+        self.event = Some(event);
+        self
+    }
+
+    /// Get the event filter
+    ///
+    /// Returns [`None`] if no event filter was set
+    #[must_use]
+    pub fn event(&self) -> Option<WebhookEvent> {
+        self.event
+    }
+
+    /// Filter for deliveries of events about the given user
+    #[must_use]
+    pub fn for_user(mut self, user: &'a User) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Get the user filter
+    ///
+    /// Returns [`None`] if no user filter was set
+    #[must_use]
+    pub fn user(&self) -> Option<&User> {
+        self.user
+    }
+
+    /// Only return pending deliveries
+    #[must_use]
+    pub fn pending_only(mut self) -> Self {
+        self.state = Some(WebhookDeliveryState::Pending);
+        self
+    }
+
+    /// Only return deliveries which were acknowledged by the endpoint
+    #[must_use]
+    pub fn delivered_only(mut self) -> Self {
+        self.state = Some(WebhookDeliveryState::Delivered);
+        self
+    }
+
+    /// Only return deliveries which were abandoned
+    #[must_use]
+    pub fn failed_only(mut self) -> Self {
+        self.state = Some(WebhookDeliveryState::Failed);
+        self
+    }
+
+    /// Get the state filter
+    ///
+    /// Returns [`None`] if no state filter was set
+    #[must_use]
+    pub fn state(&self) -> Option<WebhookDeliveryState> {
+        self.state
+    }
+}
+
+/// A [`WebhookDeliveryRepository`] helps interacting with the deliveries of
+/// webhook events saved in the storage backend
+#[async_trait]
+pub trait WebhookDeliveryRepository: Send + Sync {
+    /// The error type returned by the repository
+    type Error;
+
+    /// Lookup a [`WebhookDelivery`] by its ID, along with its attempts
+    ///
+    /// Returns `None` if no [`WebhookDelivery`] was found
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: The ID of the [`WebhookDelivery`] to lookup
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<WebhookDelivery>, Self::Error>;
+
+    /// Create a new pending [`WebhookDelivery`]
+    ///
+    /// Returns the newly created [`WebhookDelivery`]
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `event`: The event being delivered
+    /// * `url`: The URL of the endpoint the event is delivered to
+    /// * `payload`: The JSON payload to send to the endpoint
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        event: WebhookEvent,
+        url: Url,
+        payload: serde_json::Value,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    /// Record an attempt at delivering a [`WebhookDelivery`]
+    ///
+    /// Returns the [`WebhookDelivery`] with the new attempt
+    ///
+    /// # Parameters
+    ///
+    /// * `rng`: The random number generator to use
+    /// * `clock`: The clock used to generate timestamps
+    /// * `delivery`: The [`WebhookDelivery`] which was attempted
+    /// * `status_code`: The HTTP status code returned by the endpoint, if it
+    ///   responded
+    /// * `error`: Why the attempt failed, if it did
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn add_attempt(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        delivery: WebhookDelivery,
+        status_code: Option<u16>,
+        error: Option<String>,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    /// Mark a [`WebhookDelivery`] as delivered
+    ///
+    /// Returns the updated [`WebhookDelivery`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `delivery`: The [`WebhookDelivery`] to mark as delivered
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails, or if the
+    /// delivery is not pending
+    async fn mark_as_delivered(
+        &mut self,
+        clock: &dyn Clock,
+        delivery: WebhookDelivery,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    /// Mark a [`WebhookDelivery`] as failed, so that it is not attempted
+    /// anymore
+    ///
+    /// Returns the updated [`WebhookDelivery`]
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock used to generate timestamps
+    /// * `delivery`: The [`WebhookDelivery`] to mark as failed
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails, or if the
+    /// delivery is not pending
+    async fn mark_as_failed(
+        &mut self,
+        clock: &dyn Clock,
+        delivery: WebhookDelivery,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    /// List [`WebhookDelivery`]s matching the given filter and pagination
+    /// parameters, along with their attempts
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    /// * `pagination`: The pagination parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn list(
+        &mut self,
+        filter: WebhookDeliveryFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<WebhookDelivery>, Self::Error>;
+
+    /// Count the [`WebhookDelivery`]s matching the given filter
+    ///
+    /// # Parameters
+    ///
+    /// * `filter`: The filter parameters
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the underlying repository fails
+    async fn count(&mut self, filter: WebhookDeliveryFilter<'_>) -> Result<usize, Self::Error>;
+}
+
+repository_impl!(WebhookDeliveryRepository:
+    async fn lookup(&mut self, id: Ulid) -> Result<Option<WebhookDelivery>, Self::Error>;
+
+    async fn add(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        event: WebhookEvent,
+        url: Url,
+        payload: serde_json::Value,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    async fn add_attempt(
+        &mut self,
+        rng: &mut (dyn RngCore + Send),
+        clock: &dyn Clock,
+        delivery: WebhookDelivery,
+        status_code: Option<u16>,
+        error: Option<String>,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    async fn mark_as_delivered(
+        &mut self,
+        clock: &dyn Clock,
+        delivery: WebhookDelivery,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    async fn mark_as_failed(
+        &mut self,
+        clock: &dyn Clock,
+        delivery: WebhookDelivery,
+    ) -> Result<WebhookDelivery, Self::Error>;
+
+    async fn list(
+        &mut self,
+        filter: WebhookDeliveryFilter<'_>,
+        pagination: Pagination,
+    ) -> Result<Page<WebhookDelivery>, Self::Error>;
+
+    async fn count(&mut self, filter: WebhookDeliveryFilter<'_>) -> Result<usize, Self::Error>;
+);
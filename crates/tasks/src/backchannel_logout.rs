@@ -0,0 +1,366 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Jobs to notify clients through [OpenID Connect Back-Channel Logout]
+//!
+//! [OpenID Connect Back-Channel Logout]: https://openid.net/specs/openid-connect-backchannel-1_0.html
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use mas_http::RequestBuilderExt as _;
+use mas_iana::jose::JsonWebSignatureAlg;
+use mas_jose::{
+    claims::{self, Claim, ClaimError},
+    constraints::Constrainable,
+    jwt::{JsonWebSignatureHeader, Jwt},
+};
+use mas_storage::{
+    Pagination, RepositoryAccess,
+    oauth2::OAuth2SessionFilter,
+    queue::{BackchannelLogoutJob, QueueJobRepositoryExt as _, SendBackchannelLogoutJob},
+};
+use serde_json::json;
+use tracing::info;
+use ulid::Ulid;
+
+use crate::{
+    State,
+    new_queue::{JobContext, JobError, RunnableJob},
+};
+
+/// The `events` claim of a logout token
+const EVENTS: Claim<serde_json::Value> = Claim::new("events");
+
+/// The event type identifying a logout token
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// How long a logout token is valid for
+const LOGOUT_TOKEN_TTL: Duration = Duration::minutes(2);
+
+/// Build the claims of a logout token, as defined in section 2.4 of the
+/// specification. Unlike ID tokens, logout tokens never have a `nonce`.
+fn logout_token_claims(
+    issuer: &str,
+    sub: &str,
+    audience: &str,
+    now: DateTime<Utc>,
+    jti: Ulid,
+    browser_session_id: Option<Ulid>,
+) -> Result<HashMap<String, serde_json::Value>, ClaimError> {
+    let mut claims = HashMap::new();
+    claims::ISS.insert(&mut claims, issuer.to_owned())?;
+    claims::SUB.insert(&mut claims, sub.to_owned())?;
+    claims::AUD.insert(&mut claims, audience.to_owned())?;
+    claims::IAT.insert(&mut claims, now)?;
+    claims::EXP.insert(&mut claims, now + LOGOUT_TOKEN_TTL)?;
+    claims::JTI.insert(&mut claims, jti.to_string())?;
+    EVENTS.insert(&mut claims, json!({ BACKCHANNEL_LOGOUT_EVENT: {} }))?;
+    if let Some(browser_session_id) = browser_session_id {
+        claims::SID.insert(&mut claims, browser_session_id.to_string())?;
+    }
+    Ok(claims)
+}
+
+/// POST a logout token to the back-channel logout URI of a client, failing
+/// if the client doesn't respond with a success status
+async fn send(
+    http_client: &reqwest::Client,
+    backchannel_logout_uri: &str,
+    logout_token: &str,
+) -> Result<(), reqwest::Error> {
+    http_client
+        .post(backchannel_logout_uri)
+        .form(&[("logout_token", logout_token)])
+        .send_traced()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// Job to find out which clients need to be notified that a session ended.
+#[async_trait]
+impl RunnableJob for BackchannelLogoutJob {
+    #[tracing::instrument(
+        name = "job.backchannel_logout"
+        fields(
+            browser_session.id = self.browser_session_id().map(tracing::field::display),
+            oauth2_session.id = self.oauth2_session_id().map(tracing::field::display),
+        ),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let mut rng = state.rng();
+        let clock = state.clock();
+
+        // The list of (client ID, user ID, browser session ID) to notify
+        let mut to_notify = BTreeSet::new();
+
+        if let Some(session_id) = self.oauth2_session_id() {
+            let session = repo
+                .oauth2_session()
+                .lookup(session_id)
+                .await
+                .map_err(JobError::retry)?
+                .context("OAuth 2.0 session not found")
+                .map_err(JobError::fail)?;
+
+            // Sessions without a user, like the ones from the client
+            // credentials grant, can't be described by a logout token
+            if let Some(user_id) = session.user_id {
+                to_notify.insert((session.client_id, user_id, session.user_session_id));
+            }
+        }
+
+        if let Some(browser_session_id) = self.browser_session_id() {
+            let browser_session = repo
+                .browser_session()
+                .lookup(browser_session_id)
+                .await
+                .map_err(JobError::retry)?
+                .context("Browser session not found")
+                .map_err(JobError::fail)?;
+
+            // Cycle through all the OAuth 2.0 sessions started from this
+            // browser session, and grab the clients
+            let mut cursor = Pagination::first(1000);
+            loop {
+                let page = repo
+                    .oauth2_session()
+                    .list(
+                        OAuth2SessionFilter::new().for_browser_session(&browser_session),
+                        cursor,
+                    )
+                    .await
+                    .map_err(JobError::retry)?;
+
+                for edge in page.edges {
+                    to_notify.insert((
+                        edge.node.client_id,
+                        browser_session.user.id,
+                        Some(browser_session.id),
+                    ));
+                    cursor = cursor.after(edge.cursor);
+                }
+
+                if !page.has_next_page {
+                    break;
+                }
+            }
+        }
+
+        for (client_id, user_id, browser_session_id) in to_notify {
+            let client = repo
+                .oauth2_client()
+                .lookup(client_id)
+                .await
+                .map_err(JobError::retry)?
+                .context("Client not found")
+                .map_err(JobError::fail)?;
+
+            if client.backchannel_logout_uri.is_none() {
+                continue;
+            }
+
+            info!(%client.id, %user_id, "Scheduling back-channel logout notification");
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    clock,
+                    SendBackchannelLogoutJob::new(client.id, user_id, browser_session_id),
+                )
+                .await
+                .map_err(JobError::retry)?;
+        }
+
+        repo.save().await.map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
+
+/// Job to send a logout token to the `backchannel_logout_uri` of a client.
+#[async_trait]
+impl RunnableJob for SendBackchannelLogoutJob {
+    #[tracing::instrument(
+        name = "job.send_backchannel_logout"
+        fields(
+            client.id = %self.client_id(),
+            user.id = %self.user_id(),
+            browser_session.id = self.browser_session_id().map(tracing::field::display),
+        ),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let mut rng = state.rng();
+        let clock = state.clock();
+
+        let client = repo
+            .oauth2_client()
+            .lookup(self.client_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("Client not found")
+            .map_err(JobError::fail)?;
+
+        let user = repo
+            .user()
+            .lookup(self.user_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("User not found")
+            .map_err(JobError::fail)?;
+
+        repo.cancel().await.map_err(JobError::retry)?;
+
+        // The client may have been updated since the job was scheduled
+        let Some(backchannel_logout_uri) = client.backchannel_logout_uri else {
+            info!("Client doesn't have a back-channel logout URI anymore, skipping");
+            return Ok(());
+        };
+
+        if client.backchannel_logout_session_required && self.browser_session_id().is_none() {
+            info!("Client requires a session ID, but the session wasn't tied to one, skipping");
+            return Ok(());
+        }
+
+        let now = clock.now();
+        let jti = Ulid::from_datetime_with_source(now.into(), &mut rng);
+        let claims = logout_token_claims(
+            state.url_builder().oidc_issuer().as_str(),
+            &user.sub,
+            &client.client_id,
+            now,
+            jti,
+            self.browser_session_id(),
+        )
+        .map_err(JobError::fail)?;
+
+        // Logout tokens are signed the same way as ID tokens
+        let alg = client
+            .id_token_signed_response_alg
+            .unwrap_or(JsonWebSignatureAlg::Rs256);
+        let key = state
+            .key_store()
+            .signing_key_for_algorithm(&alg)
+            .context("No signing key for the client algorithm")
+            .map_err(JobError::fail)?;
+        let kid = key
+            .kid()
+            .context("Signing key has no key ID")
+            .map_err(JobError::fail)?;
+        let signer = key
+            .params()
+            .signing_key_for_alg(&alg)
+            .map_err(JobError::fail)?;
+        let header = JsonWebSignatureHeader::new(alg)
+            .with_kid(kid)
+            .with_typ("logout+jwt".to_owned());
+        let logout_token =
+            Jwt::sign_with_rng(&mut rng, header, claims, &signer).map_err(JobError::fail)?;
+
+        // Errors returned by the client may be transient, so we retry them
+        send(
+            state.http_client(),
+            backchannel_logout_uri.as_str(),
+            logout_token.as_str(),
+        )
+        .await
+        .map_err(JobError::retry)?;
+
+        info!(%backchannel_logout_uri, "Sent back-channel logout notification");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+    use ulid::Ulid;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_string, header, method, path},
+    };
+
+    use super::{LOGOUT_TOKEN_TTL, logout_token_claims, send};
+
+    #[test]
+    fn test_logout_token_claims() {
+        let now = DateTime::<Utc>::UNIX_EPOCH;
+        let jti = Ulid::from_parts(0, 1);
+        let sid = Ulid::from_parts(0, 2);
+
+        let claims = logout_token_claims(
+            "https://auth.example.com/",
+            "subject",
+            "client",
+            now,
+            jti,
+            Some(sid),
+        )
+        .unwrap();
+
+        assert_eq!(claims["iss"], "https://auth.example.com/");
+        assert_eq!(claims["sub"], "subject");
+        assert_eq!(claims["aud"], "client");
+        assert_eq!(claims["iat"], now.timestamp());
+        assert_eq!(claims["exp"], (now + LOGOUT_TOKEN_TTL).timestamp());
+        assert_eq!(claims["jti"], jti.to_string());
+        assert_eq!(
+            claims["events"],
+            json!({ "http://schemas.openid.net/event/backchannel-logout": {} })
+        );
+        assert_eq!(claims["sid"], sid.to_string());
+
+        // Logout tokens must not have a nonce
+        assert!(!claims.contains_key("nonce"));
+
+        // The session ID is only there if the session was tied to one
+        let claims = logout_token_claims(
+            "https://auth.example.com/",
+            "subject",
+            "client",
+            now,
+            jti,
+            None,
+        )
+        .unwrap();
+        assert!(!claims.contains_key("sid"));
+        assert!(!claims.contains_key("nonce"));
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let server = MockServer::start().await;
+        let uri = format!("{}/logout", server.uri());
+
+        Mock::given(method("POST"))
+            .and(path("/logout"))
+            .and(header("content-type", "application/x-www-form-urlencoded"))
+            .and(body_string("logout_token=eyJ.eyJ.sig"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mas_http::reqwest_client();
+        send(&client, &uri, "eyJ.eyJ.sig").await.unwrap();
+
+        // Errors from the client are reported, so that the job is retried
+        let uri = format!("{}/broken", server.uri());
+        Mock::given(method("POST"))
+            .and(path("/broken"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        assert!(send(&client, &uri, "eyJ.eyJ.sig").await.is_err());
+    }
+}
@@ -27,6 +27,18 @@ impl RunnableJob for CleanupExpiredTokensJob {
             .cleanup_revoked(clock)
             .await
             .map_err(JobError::retry)?;
+
+        let challenges = repo
+            .user_passkey()
+            .cleanup_challenges(clock)
+            .await
+            .map_err(JobError::retry)?;
+
+        let pushed_requests = repo
+            .oauth2_pushed_authorization_request()
+            .cleanup_expired(clock)
+            .await
+            .map_err(JobError::retry)?;
         repo.save().await.map_err(JobError::retry)?;
 
         if count == 0 {
@@ -35,6 +47,17 @@ impl RunnableJob for CleanupExpiredTokensJob {
             info!(count, "cleaned up revoked tokens");
         }
 
+        if challenges > 0 {
+            info!(count = challenges, "cleaned up stale passkey challenges");
+        }
+
+        if pushed_requests > 0 {
+            info!(
+                count = pushed_requests,
+                "cleaned up expired pushed authorization requests"
+            );
+        }
+
         Ok(())
     }
 }
@@ -8,6 +8,7 @@ use std::sync::{Arc, LazyLock};
 
 use mas_data_model::{Clock, SiteConfig};
 use mas_email::Mailer;
+use mas_keystore::Keystore;
 use mas_matrix::HomeserverConnection;
 use mas_router::UrlBuilder;
 use mas_storage::{BoxRepository, RepositoryError, RepositoryFactory};
@@ -20,13 +21,16 @@ use tokio_util::{sync::CancellationToken, task::TaskTracker};
 
 pub use crate::new_queue::QueueWorker;
 
+mod backchannel_logout;
 mod database;
 mod email;
 mod matrix;
 mod new_queue;
+mod notification;
 mod recovery;
 mod sessions;
 mod user;
+mod webhook;
 
 static METER: LazyLock<Meter> = LazyLock::new(|| {
     let scope = opentelemetry::InstrumentationScope::builder(env!("CARGO_PKG_NAME"))
@@ -45,9 +49,12 @@ struct State {
     homeserver: Arc<dyn HomeserverConnection>,
     url_builder: UrlBuilder,
     site_config: SiteConfig,
+    key_store: Keystore,
+    http_client: reqwest::Client,
 }
 
 impl State {
+    #[expect(clippy::too_many_arguments, reason = "this is fine")]
     pub fn new(
         repository_factory: PgRepositoryFactory,
         clock: impl Clock + 'static,
@@ -55,6 +62,8 @@ impl State {
         homeserver: impl HomeserverConnection + 'static,
         url_builder: UrlBuilder,
         site_config: SiteConfig,
+        key_store: Keystore,
+        http_client: reqwest::Client,
     ) -> Self {
         Self {
             repository_factory,
@@ -63,6 +72,8 @@ impl State {
             homeserver: Arc::new(homeserver),
             url_builder,
             site_config,
+            key_store,
+            http_client,
         }
     }
 
@@ -99,6 +110,14 @@ impl State {
     pub fn site_config(&self) -> &SiteConfig {
         &self.site_config
     }
+
+    pub fn key_store(&self) -> &Keystore {
+        &self.key_store
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
 }
 
 /// Initialise the worker, without running it.
@@ -108,6 +127,7 @@ impl State {
 /// # Errors
 ///
 /// This function can fail if the database connection fails.
+#[expect(clippy::too_many_arguments, reason = "this is fine")]
 pub async fn init(
     repository_factory: PgRepositoryFactory,
     clock: impl Clock + 'static,
@@ -115,6 +135,8 @@ pub async fn init(
     homeserver: impl HomeserverConnection + 'static,
     url_builder: UrlBuilder,
     site_config: &SiteConfig,
+    key_store: Keystore,
+    http_client: reqwest::Client,
     cancellation_token: CancellationToken,
 ) -> Result<QueueWorker, QueueRunnerError> {
     let state = State::new(
@@ -124,6 +146,8 @@ pub async fn init(
         homeserver,
         url_builder,
         site_config.clone(),
+        key_store,
+        http_client,
     );
     let mut worker = QueueWorker::new(state, cancellation_token).await?;
 
@@ -131,11 +155,15 @@ pub async fn init(
         .register_handler::<mas_storage::queue::CleanupExpiredTokensJob>()
         .register_handler::<mas_storage::queue::DeactivateUserJob>()
         .register_handler::<mas_storage::queue::DeleteDeviceJob>()
+        .register_handler::<mas_storage::queue::EndImpersonationJob>()
+        .register_handler::<mas_storage::queue::EraseDeactivatedUsersJob>()
+        .register_handler::<mas_storage::queue::ExportUserJob>()
         .register_handler::<mas_storage::queue::ProvisionDeviceJob>()
         .register_handler::<mas_storage::queue::ProvisionUserJob>()
         .register_handler::<mas_storage::queue::ReactivateUserJob>()
         .register_handler::<mas_storage::queue::SendAccountRecoveryEmailsJob>()
         .register_handler::<mas_storage::queue::SendEmailAuthenticationCodeJob>()
+        .register_handler::<mas_storage::queue::SendSecurityNotificationJob>()
         .register_handler::<mas_storage::queue::SyncDevicesJob>()
         .register_handler::<mas_storage::queue::VerifyEmailJob>()
         .register_handler::<mas_storage::queue::ExpireInactiveSessionsJob>()
@@ -143,6 +171,10 @@ pub async fn init(
         .register_handler::<mas_storage::queue::ExpireInactiveOAuthSessionsJob>()
         .register_handler::<mas_storage::queue::ExpireInactiveUserSessionsJob>()
         .register_handler::<mas_storage::queue::PruneStalePolicyDataJob>()
+        .register_handler::<mas_storage::queue::BackchannelLogoutJob>()
+        .register_handler::<mas_storage::queue::SendBackchannelLogoutJob>()
+        .register_handler::<mas_storage::queue::DispatchWebhookEventJob>()
+        .register_handler::<mas_storage::queue::SendWebhookJob>()
         .add_schedule(
             "cleanup-expired-tokens",
             "0 0 * * * *".parse()?,
@@ -159,6 +191,12 @@ pub async fn init(
             // Run once a day
             "0 0 2 * * *".parse()?,
             mas_storage::queue::PruneStalePolicyDataJob,
+        )
+        .add_schedule(
+            "erase-deactivated-users",
+            // Run once a day
+            "0 0 3 * * *".parse()?,
+            mas_storage::queue::EraseDeactivatedUsersJob,
         );
 
     Ok(worker)
@@ -177,6 +215,8 @@ pub async fn init_and_run(
     homeserver: impl HomeserverConnection + 'static,
     url_builder: UrlBuilder,
     site_config: &SiteConfig,
+    key_store: Keystore,
+    http_client: reqwest::Client,
     cancellation_token: CancellationToken,
     task_tracker: &TaskTracker,
 ) -> Result<(), QueueRunnerError> {
@@ -187,6 +227,8 @@ pub async fn init_and_run(
         homeserver,
         url_builder,
         site_config,
+        key_store,
+        http_client,
         cancellation_token,
     )
     .await?;
@@ -65,6 +65,12 @@ impl JobContext {
 
         span
     }
+
+    /// Whether this is the last attempt at running the job, after which a
+    /// retryable error fails the job
+    pub fn is_last_attempt(&self) -> bool {
+        self.attempt >= MAX_ATTEMPTS
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
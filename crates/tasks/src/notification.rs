@@ -0,0 +1,390 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+use std::collections::BTreeSet;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use mas_data_model::{DeviceType, SecurityNotification, User, UserAgent};
+use mas_email::{Address, Mailbox};
+use mas_i18n::DataLocale;
+use mas_storage::{
+    Pagination, RepositoryAccess,
+    compat::CompatSessionFilter,
+    queue::SendSecurityNotificationJob,
+    user::{BrowserSessionFilter, UserEmailFilter, UserRepository},
+};
+use mas_templates::{EmailSecurityNotificationContext, TemplateContext};
+use tracing::{error, info};
+use ulid::Ulid;
+
+use crate::{
+    State,
+    new_queue::{JobContext, JobError, RunnableJob},
+};
+
+/// How many of the previous sessions of each kind are looked at to decide
+/// whether a new session comes from a familiar device
+const PREVIOUS_SESSIONS: usize = 100;
+
+/// The parts of a user agent which identify a device. Versions are left out,
+/// so that software updates don't make a device look unfamiliar.
+type DeviceKey = Option<(Option<String>, Option<String>, DeviceType)>;
+
+fn device_key(user_agent: Option<&str>) -> DeviceKey {
+    let user_agent = UserAgent::parse(user_agent?.to_owned());
+    Some((user_agent.name, user_agent.os, user_agent.device_type))
+}
+
+/// Check whether any other session of the user was started from a device
+/// similar to the one of the new session.
+///
+/// Returns `None` if the user has no other session to compare to, which is
+/// the case on the first sign in.
+async fn is_familiar_device(
+    repo: &mut mas_storage::BoxRepository,
+    user: &User,
+    session_id: Ulid,
+    user_agent: Option<&str>,
+) -> Result<Option<bool>, mas_storage::RepositoryError> {
+    let key = device_key(user_agent);
+    let mut previous = Vec::new();
+
+    let page = repo
+        .browser_session()
+        .list(
+            BrowserSessionFilter::new().for_user(user),
+            Pagination::last(PREVIOUS_SESSIONS),
+        )
+        .await?;
+    previous.extend(
+        page.edges
+            .into_iter()
+            .filter(|edge| edge.node.id != session_id)
+            .map(|edge| edge.node.user_agent),
+    );
+
+    let page = repo
+        .compat_session()
+        .list(
+            CompatSessionFilter::new().for_user(user),
+            Pagination::last(PREVIOUS_SESSIONS),
+        )
+        .await?;
+    previous.extend(
+        page.edges
+            .into_iter()
+            .filter(|edge| edge.node.0.id != session_id)
+            .map(|edge| edge.node.0.user_agent),
+    );
+
+    if previous.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(previous.iter().any(|user_agent| {
+        device_key(user_agent.as_deref()) == key
+    })))
+}
+
+/// Get the addresses to send the notification to: all the current addresses
+/// of the user, as well as the ones which were removed, if any
+async fn recipients(
+    repo: &mut mas_storage::BoxRepository,
+    user: &User,
+    job: &SendSecurityNotificationJob,
+) -> Result<BTreeSet<String>, mas_storage::RepositoryError> {
+    let mut recipients: BTreeSet<String> = job.additional_recipients().iter().cloned().collect();
+    if let SecurityNotification::EmailRemoved { email } = job.notification() {
+        recipients.insert(email.clone());
+    }
+
+    let mut cursor = Pagination::first(50);
+    loop {
+        let page = repo
+            .user_email()
+            .list(UserEmailFilter::new().for_user(user), cursor)
+            .await?;
+
+        for edge in page.edges {
+            recipients.insert(edge.node.email);
+            cursor = cursor.after(edge.cursor);
+        }
+
+        if !page.has_next_page {
+            break;
+        }
+    }
+
+    Ok(recipients)
+}
+
+/// Job to notify a user by email of a security-relevant event on their
+/// account
+#[async_trait]
+impl RunnableJob for SendSecurityNotificationJob {
+    #[tracing::instrument(
+        name = "job.send_security_notification",
+        fields(user.id = %self.user_id()),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mailer = state.mailer();
+        let url_builder = state.url_builder();
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+
+        let user = repo
+            .user()
+            .lookup(self.user_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("User not found")
+            .map_err(JobError::fail)?;
+
+        // Only notify about sessions from devices the user didn't use before
+        if let SecurityNotification::NewSession {
+            session_id,
+            user_agent,
+        } = self.notification()
+        {
+            let familiar = is_familiar_device(&mut repo, &user, *session_id, user_agent.as_deref())
+                .await
+                .map_err(JobError::retry)?;
+
+            match familiar {
+                Some(false) => {}
+                Some(true) => {
+                    info!("Session started from a familiar device, not notifying the user");
+                    return Ok(());
+                }
+                None => {
+                    info!("First session of the user, not notifying the user");
+                    return Ok(());
+                }
+            }
+        }
+
+        let recipients = recipients(&mut repo, &user, self)
+            .await
+            .map_err(JobError::retry)?;
+
+        repo.cancel().await.map_err(JobError::retry)?;
+
+        if recipients.is_empty() {
+            info!("User has no email address, not sending a security notification");
+            return Ok(());
+        }
+
+        let language: DataLocale = self
+            .language()
+            .unwrap_or("en")
+            .parse()
+            .context("Invalid locale in security notification job")
+            .map_err(JobError::fail)?;
+
+        let context = EmailSecurityNotificationContext::new(
+            user.clone(),
+            self.notification().clone(),
+            url_builder.account_management_uri(),
+        )
+        .with_language(language);
+
+        for recipient in recipients {
+            let address: Address = match recipient.parse() {
+                Ok(address) => address,
+                Err(e) => {
+                    error!(
+                        error = &e as &dyn std::error::Error,
+                        "Invalid email address, skipping"
+                    );
+                    continue;
+                }
+            };
+            let mailbox = Mailbox::new(Some(user.username.clone()), address);
+
+            info!("Sending security notification to {}", mailbox);
+
+            // XXX: we only log if the email fails to send, to avoid stopping the loop
+            if let Err(e) = mailer
+                .send_security_notification_email(mailbox, &context)
+                .await
+            {
+                error!(
+                    error = &e as &dyn std::error::Error,
+                    "Failed to send security notification email"
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mas_data_model::{SecurityNotification, clock::MockClock};
+    use mas_storage::{RepositoryAccess, queue::SendSecurityNotificationJob};
+    use mas_storage_pg::PgRepository;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use sqlx::PgPool;
+
+    use super::{is_familiar_device, recipients};
+
+    const FIREFOX_LINUX: &str =
+        "Mozilla/5.0 (X11; Linux x86_64; rv:120.0) Gecko/20100101 Firefox/120.0";
+    const FIREFOX_LINUX_UPDATED: &str =
+        "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0";
+    const CHROME_ANDROID: &str = "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Mobile Safari/537.36";
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_is_familiar_device(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        let user = repo
+            .user()
+            .add(&mut rng, &clock, "john".to_owned())
+            .await
+            .unwrap();
+
+        // The first session of the user has nothing to compare to
+        let first = repo
+            .browser_session()
+            .add(&mut rng, &clock, &user, Some(FIREFOX_LINUX.to_owned()))
+            .await
+            .unwrap();
+        let familiar = is_familiar_device(&mut repo, &user, first.id, Some(FIREFOX_LINUX))
+            .await
+            .unwrap();
+        assert_eq!(familiar, None);
+
+        // The same browser on the same OS is familiar, even after an update
+        let updated = repo
+            .browser_session()
+            .add(
+                &mut rng,
+                &clock,
+                &user,
+                Some(FIREFOX_LINUX_UPDATED.to_owned()),
+            )
+            .await
+            .unwrap();
+        let familiar =
+            is_familiar_device(&mut repo, &user, updated.id, Some(FIREFOX_LINUX_UPDATED))
+                .await
+                .unwrap();
+        assert_eq!(familiar, Some(true));
+
+        // Another browser on another device is not
+        let phone = repo
+            .browser_session()
+            .add(&mut rng, &clock, &user, Some(CHROME_ANDROID.to_owned()))
+            .await
+            .unwrap();
+        let familiar = is_familiar_device(&mut repo, &user, phone.id, Some(CHROME_ANDROID))
+            .await
+            .unwrap();
+        assert_eq!(familiar, Some(false));
+
+        // Neither is a session without a user agent
+        let unknown = repo
+            .browser_session()
+            .add(&mut rng, &clock, &user, None)
+            .await
+            .unwrap();
+        let familiar = is_familiar_device(&mut repo, &user, unknown.id, None)
+            .await
+            .unwrap();
+        assert_eq!(familiar, Some(false));
+
+        // Sessions of other users don't count
+        let other = repo
+            .user()
+            .add(&mut rng, &clock, "alice".to_owned())
+            .await
+            .unwrap();
+        let session = repo
+            .browser_session()
+            .add(&mut rng, &clock, &other, Some(FIREFOX_LINUX.to_owned()))
+            .await
+            .unwrap();
+        let familiar = is_familiar_device(&mut repo, &other, session.id, Some(FIREFOX_LINUX))
+            .await
+            .unwrap();
+        assert_eq!(familiar, None);
+    }
+
+    #[sqlx::test(migrator = "mas_storage_pg::MIGRATOR")]
+    async fn test_recipients(pool: PgPool) {
+        let mut rng = ChaChaRng::seed_from_u64(42);
+        let clock = MockClock::default();
+        let mut repo = PgRepository::from_pool(&pool).await.unwrap().boxed();
+
+        let user = repo
+            .user()
+            .add(&mut rng, &clock, "john".to_owned())
+            .await
+            .unwrap();
+
+        // Without any email address, there is no one to notify
+        let job = SendSecurityNotificationJob::new(&user, SecurityNotification::PasswordChanged);
+        let result = recipients(&mut repo, &user, &job).await.unwrap();
+        assert!(result.is_empty());
+
+        repo.user_email()
+            .add(&mut rng, &clock, &user, "john@example.com".to_owned())
+            .await
+            .unwrap();
+        repo.user_email()
+            .add(&mut rng, &clock, &user, "john@example.org".to_owned())
+            .await
+            .unwrap();
+
+        // Addresses of other users are left out
+        let other = repo
+            .user()
+            .add(&mut rng, &clock, "alice".to_owned())
+            .await
+            .unwrap();
+        repo.user_email()
+            .add(&mut rng, &clock, &other, "alice@example.com".to_owned())
+            .await
+            .unwrap();
+
+        // All the current addresses of the user are notified
+        let result = recipients(&mut repo, &user, &job).await.unwrap();
+        assert_eq!(
+            result.into_iter().collect::<Vec<_>>(),
+            ["john@example.com", "john@example.org"]
+        );
+
+        // The removed address is notified as well
+        let job = SendSecurityNotificationJob::new(
+            &user,
+            SecurityNotification::EmailRemoved {
+                email: "john@example.net".to_owned(),
+            },
+        );
+        let result = recipients(&mut repo, &user, &job).await.unwrap();
+        assert_eq!(
+            result.into_iter().collect::<Vec<_>>(),
+            ["john@example.com", "john@example.net", "john@example.org"]
+        );
+
+        // And so are the additional recipients, without duplicates
+        let job = SendSecurityNotificationJob::new(&user, SecurityNotification::AccountDeactivated)
+            .with_additional_recipients(vec![
+                "john@example.com".to_owned(),
+                "old@example.com".to_owned(),
+            ]);
+        let result = recipients(&mut repo, &user, &job).await.unwrap();
+        assert_eq!(
+            result.into_iter().collect::<Vec<_>>(),
+            ["john@example.com", "john@example.org", "old@example.com"]
+        );
+    }
+}
@@ -5,14 +5,16 @@
 
 use std::collections::HashSet;
 
+use anyhow::Context;
 use async_trait::async_trait;
 use chrono::Duration;
 use mas_storage::{
     compat::CompatSessionFilter,
     oauth2::OAuth2SessionFilter,
     queue::{
-        ExpireInactiveCompatSessionsJob, ExpireInactiveOAuthSessionsJob, ExpireInactiveSessionsJob,
-        ExpireInactiveUserSessionsJob, QueueJobRepositoryExt, SyncDevicesJob,
+        BackchannelLogoutJob, EndImpersonationJob, ExpireInactiveCompatSessionsJob,
+        ExpireInactiveOAuthSessionsJob, ExpireInactiveSessionsJob, ExpireInactiveUserSessionsJob,
+        QueueJobRepositoryExt, SyncDevicesJob,
     },
     user::BrowserSessionFilter,
 };
@@ -127,10 +129,20 @@ impl RunnableJob for ExpireInactiveOAuthSessionsJob {
                 }
             }
 
-            repo.oauth2_session()
+            let session = repo
+                .oauth2_session()
                 .finish(clock, edge.node)
                 .await
                 .map_err(JobError::retry)?;
+
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    clock,
+                    BackchannelLogoutJob::for_oauth2_session(&session),
+                )
+                .await
+                .map_err(JobError::retry)?;
         }
 
         repo.save().await.map_err(JobError::retry)?;
@@ -229,10 +241,110 @@ impl RunnableJob for ExpireInactiveUserSessionsJob {
         }
 
         for edge in page.edges {
-            repo.browser_session()
+            let session = repo
+                .browser_session()
                 .finish(clock, edge.node)
                 .await
                 .map_err(JobError::retry)?;
+
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    clock,
+                    BackchannelLogoutJob::for_browser_session(&session),
+                )
+                .await
+                .map_err(JobError::retry)?;
+        }
+
+        repo.save().await.map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
+
+/// Job to end a session impersonating a user once the impersonation expired,
+/// and remove its device from the homeserver
+#[async_trait]
+impl RunnableJob for EndImpersonationJob {
+    #[tracing::instrument(
+        name = "job.end_impersonation"
+        fields(
+            oauth2_session.id = self.oauth2_session_id().map(tracing::field::display),
+            compat_session.id = self.compat_session_id().map(tracing::field::display),
+        ),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let clock = state.clock();
+        let mut rng = state.rng();
+
+        let user_id = if let Some(session_id) = self.oauth2_session_id() {
+            let session = repo
+                .oauth2_session()
+                .lookup(session_id)
+                .await
+                .map_err(JobError::retry)?
+                .context("OAuth 2.0 session not found")
+                .map_err(JobError::fail)?;
+
+            // The session may have been ended in the meantime
+            if !session.is_valid() {
+                tracing::info!("Session already ended, skipping");
+                return Ok(());
+            }
+
+            let user_id = session.user_id;
+            let session = repo
+                .oauth2_session()
+                .finish(clock, session)
+                .await
+                .map_err(JobError::retry)?;
+
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    clock,
+                    BackchannelLogoutJob::for_oauth2_session(&session),
+                )
+                .await
+                .map_err(JobError::retry)?;
+
+            user_id
+        } else if let Some(session_id) = self.compat_session_id() {
+            let session = repo
+                .compat_session()
+                .lookup(session_id)
+                .await
+                .map_err(JobError::retry)?
+                .context("Compatibility session not found")
+                .map_err(JobError::fail)?;
+
+            if !session.is_valid() {
+                tracing::info!("Session already ended, skipping");
+                return Ok(());
+            }
+
+            let user_id = session.user_id;
+            repo.compat_session()
+                .finish(clock, session)
+                .await
+                .map_err(JobError::retry)?;
+
+            Some(user_id)
+        } else {
+            return Err(JobError::fail(anyhow::anyhow!("No session to end")));
+        };
+
+        tracing::info!("Ended impersonation session");
+
+        // Syncing the devices removes the one of the session from the homeserver
+        if let Some(user_id) = user_id {
+            repo.queue_job()
+                .schedule_job(&mut rng, clock, SyncDevicesJob::new_for_id(user_id))
+                .await
+                .map_err(JobError::retry)?;
         }
 
         repo.save().await.map_err(JobError::retry)?;
@@ -6,15 +6,20 @@
 
 use anyhow::Context;
 use async_trait::async_trait;
+use mas_data_model::{AuditAction, AuditActor, SecurityNotification};
 use mas_storage::{
-    RepositoryAccess,
+    Pagination, RepositoryAccess,
     compat::CompatSessionFilter,
     oauth2::OAuth2SessionFilter,
     personal::PersonalSessionFilter,
-    queue::{DeactivateUserJob, ReactivateUserJob},
-    user::{BrowserSessionFilter, UserEmailFilter, UserRepository},
+    queue::{
+        DeactivateUserJob, DispatchWebhookEventJob, EraseDeactivatedUsersJob, ExportUserJob,
+        QueueJobRepositoryExt as _, ReactivateUserJob, SendSecurityNotificationJob,
+    },
+    user::{BrowserSessionFilter, UserEmailFilter, UserFilter, UserRepository, export_user},
 };
-use tracing::info;
+use serde_json::json;
+use tracing::{debug, info};
 
 use crate::{
     State,
@@ -31,6 +36,7 @@ impl RunnableJob for DeactivateUserJob {
     )]
     async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
         let clock = state.clock();
+        let mut rng = state.rng();
         let matrix = state.matrix_connection();
         let mut repo = state.repository().await.map_err(JobError::retry)?;
 
@@ -111,6 +117,42 @@ impl RunnableJob for DeactivateUserJob {
             "Killed all compatibility sessions owned by user"
         );
 
+        // Notify the user of the deactivation. This has to be scheduled with the
+        // email addresses of the user, as they are removed just after.
+        let mut emails = Vec::new();
+        let mut cursor = Pagination::first(50);
+        loop {
+            let page = repo
+                .user_email()
+                .list(UserEmailFilter::new().for_user(&user), cursor)
+                .await
+                .map_err(JobError::retry)?;
+
+            for edge in page.edges {
+                emails.push(edge.node.email);
+                cursor = cursor.after(edge.cursor);
+            }
+
+            if !page.has_next_page {
+                break;
+            }
+        }
+
+        if !emails.is_empty() {
+            repo.queue_job()
+                .schedule_job(
+                    &mut rng,
+                    clock,
+                    SendSecurityNotificationJob::new(
+                        &user,
+                        SecurityNotification::AccountDeactivated,
+                    )
+                    .with_additional_recipients(emails),
+                )
+                .await
+                .map_err(JobError::retry)?;
+        }
+
         // Delete all the email addresses for the user
         let n = repo
             .user_email()
@@ -119,6 +161,15 @@ impl RunnableJob for DeactivateUserJob {
             .map_err(JobError::retry)?;
         info!(affected = n, "Removed all email addresses for user");
 
+        repo.queue_job()
+            .schedule_job(
+                &mut rng,
+                clock,
+                DispatchWebhookEventJob::user_deactivated(&user),
+            )
+            .await
+            .map_err(JobError::retry)?;
+
         // Before calling back to the homeserver, commit the changes to the database, as
         // we want the user to be locked out as soon as possible
         repo.save().await.map_err(JobError::retry)?;
@@ -153,6 +204,12 @@ impl RunnableJob for ReactivateUserJob {
             .context("User not found")
             .map_err(JobError::fail)?;
 
+        if user.erased_at.is_some() {
+            return Err(JobError::fail(anyhow::anyhow!(
+                "User data was erased, it can't be reactivated"
+            )));
+        }
+
         info!("Reactivating user {} on homeserver", user.username);
         matrix
             .reactivate_user(&user.username)
@@ -171,3 +228,126 @@ impl RunnableJob for ReactivateUserJob {
         Ok(())
     }
 }
+
+/// Job to build the archive of everything stored about a user, for a data
+/// export requested through the admin API.
+#[async_trait]
+impl RunnableJob for ExportUserJob {
+    #[tracing::instrument(
+        name = "job.export_user",
+        fields(user_export.id = %self.user_export_id()),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let clock = state.clock();
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+
+        let user_export = repo
+            .user_export()
+            .lookup(self.user_export_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("User export not found")
+            .map_err(JobError::fail)?;
+
+        if user_export.is_completed() {
+            info!("User export already completed, skipping");
+            return Ok(());
+        }
+
+        let user = repo
+            .user()
+            .lookup(user_export.user_id)
+            .await
+            .map_err(JobError::retry)?
+            .context("User not found")
+            .map_err(JobError::fail)?;
+
+        let archive = export_user(&mut repo, clock, &user)
+            .await
+            .map_err(JobError::retry)?;
+        let archive = serde_json::to_value(&archive)
+            .context("Failed to serialize user archive")
+            .map_err(JobError::fail)?;
+
+        repo.user_export()
+            .complete(clock, user_export, archive)
+            .await
+            .map_err(JobError::retry)?;
+        info!(user.id = %user.id, "Exported user data");
+
+        repo.save().await.map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
+
+/// Job to erase the personal data of users who were deactivated for longer
+/// than the configured retention period.
+#[async_trait]
+impl RunnableJob for EraseDeactivatedUsersJob {
+    #[tracing::instrument(name = "job.erase_deactivated_users", skip_all)]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let Some(retention) = state.site_config().deactivated_user_retention else {
+            // Erasure of deactivated users is disabled
+            return Ok(());
+        };
+
+        let clock = state.clock();
+        let mut rng = state.rng();
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+
+        // Users drop out of this filter once erased, so we can always look at
+        // the first page
+        let filter = UserFilter::new()
+            .deactivated_only()
+            .non_erased_only()
+            .with_deactivated_before(clock.now() - retention);
+
+        let page = repo
+            .user()
+            .list(filter, Pagination::first(100))
+            .await
+            .map_err(JobError::retry)?;
+
+        if page.edges.is_empty() {
+            debug!("No deactivated user to erase");
+            return Ok(());
+        }
+
+        for edge in &page.edges {
+            let user = repo
+                .user()
+                .erase(clock, edge.node.clone())
+                .await
+                .map_err(JobError::retry)?;
+
+            repo.audit_event()
+                .add(
+                    &mut rng,
+                    clock,
+                    AuditActor::default(),
+                    AuditAction::UserErase,
+                    Some(user.id),
+                    Some(user.id),
+                    json!({ "retention": retention.num_seconds() }),
+                )
+                .await
+                .map_err(JobError::retry)?;
+
+            info!(user.id = %user.id, "Erased personal data of deactivated user");
+        }
+
+        // Schedule another run to process the next batch
+        if page.has_next_page {
+            repo.queue_job()
+                .schedule_job(&mut rng, clock, EraseDeactivatedUsersJob)
+                .await
+                .map_err(JobError::retry)?;
+        }
+
+        repo.save().await.map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+//! Jobs to deliver lifecycle events to the configured webhook endpoints
+
+use anyhow::Context;
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use mas_data_model::WebhookDelivery;
+use mas_http::RequestBuilderExt as _;
+use mas_storage::{
+    RepositoryAccess,
+    queue::{DispatchWebhookEventJob, QueueJobRepositoryExt as _, SendWebhookJob},
+};
+use reqwest::StatusCode;
+use serde_json::json;
+use sha2::Sha256;
+use tracing::{info, warn};
+use ulid::Ulid;
+
+use crate::{
+    State,
+    new_queue::{JobContext, JobError, RunnableJob},
+};
+
+/// The header carrying the signature of the payload
+const SIGNATURE_HEADER: &str = "X-MAS-Webhook-Signature";
+
+/// The header carrying the kind of event
+const EVENT_HEADER: &str = "X-MAS-Webhook-Event";
+
+/// The header carrying the ID of the delivery, so that the receiver can
+/// deduplicate retries
+const DELIVERY_HEADER: &str = "X-MAS-Webhook-Delivery";
+
+/// Compute the signature of a payload, as the hex-encoded HMAC-SHA256 of the
+/// body with the endpoint secret
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// POST the payload of a delivery to its endpoint, returning the status code
+/// of the response
+async fn send(
+    http_client: &reqwest::Client,
+    delivery: &WebhookDelivery,
+    secret: &str,
+) -> Result<StatusCode, reqwest::Error> {
+    let body = serde_json::to_vec(&delivery.payload).expect("JSON values always serialize");
+    let signature = sign(secret, &body);
+
+    let response = http_client
+        .post(delivery.url.as_str())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(SIGNATURE_HEADER, signature)
+        .header(EVENT_HEADER, delivery.event.as_str())
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .body(body)
+        .send_traced()
+        .await?;
+
+    Ok(response.status())
+}
+
+/// Job to record a delivery for each webhook endpoint subscribed to an event.
+#[async_trait]
+impl RunnableJob for DispatchWebhookEventJob {
+    #[tracing::instrument(
+        name = "job.dispatch_webhook_event"
+        fields(webhook.event = %self.event()),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, _context: JobContext) -> Result<(), JobError> {
+        let endpoints: Vec<_> = state
+            .site_config()
+            .webhooks
+            .iter()
+            .filter(|endpoint| endpoint.is_subscribed_to(self.event()))
+            .collect();
+
+        if endpoints.is_empty() {
+            return Ok(());
+        }
+
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let mut rng = state.rng();
+        let clock = state.clock();
+
+        // All the deliveries of an event share the same payload
+        let now = clock.now();
+        let event_id = Ulid::from_datetime_with_source(now.into(), &mut rng);
+        let payload = json!({
+            "id": event_id,
+            "type": self.event(),
+            "created_at": now,
+            "data": self.data(),
+        });
+
+        for endpoint in endpoints {
+            let delivery = repo
+                .webhook_delivery()
+                .add(
+                    &mut rng,
+                    clock,
+                    self.event(),
+                    endpoint.url.clone(),
+                    payload.clone(),
+                )
+                .await
+                .map_err(JobError::retry)?;
+
+            info!(%delivery.id, %delivery.url, "Scheduling webhook delivery");
+            repo.queue_job()
+                .schedule_job(&mut rng, clock, SendWebhookJob::new(&delivery))
+                .await
+                .map_err(JobError::retry)?;
+        }
+
+        repo.save().await.map_err(JobError::retry)?;
+
+        Ok(())
+    }
+}
+
+/// Job to POST an event to a webhook endpoint, recording the attempt.
+#[async_trait]
+impl RunnableJob for SendWebhookJob {
+    #[tracing::instrument(
+        name = "job.send_webhook"
+        fields(webhook_delivery.id = %self.delivery_id()),
+        skip_all,
+    )]
+    async fn run(&self, state: &State, context: JobContext) -> Result<(), JobError> {
+        let mut repo = state.repository().await.map_err(JobError::retry)?;
+        let mut rng = state.rng();
+        let clock = state.clock();
+
+        let delivery = repo
+            .webhook_delivery()
+            .lookup(self.delivery_id())
+            .await
+            .map_err(JobError::retry)?
+            .context("Webhook delivery not found")
+            .map_err(JobError::fail)?;
+
+        if !delivery.state.is_pending() {
+            info!("Webhook delivery is not pending anymore, skipping");
+            return Ok(());
+        }
+
+        // The endpoint may have been removed from the configuration since the
+        // delivery was recorded
+        let Some(endpoint) = state
+            .site_config()
+            .webhooks
+            .iter()
+            .find(|endpoint| endpoint.url == delivery.url)
+        else {
+            warn!(%delivery.url, "Webhook endpoint is not configured anymore, giving up");
+            repo.webhook_delivery()
+                .mark_as_failed(clock, delivery)
+                .await
+                .map_err(JobError::retry)?;
+            repo.save().await.map_err(JobError::retry)?;
+            return Ok(());
+        };
+
+        let result = send(state.http_client(), &delivery, &endpoint.secret).await;
+
+        let (status_code, error) = match &result {
+            Ok(status) if status.is_success() => (Some(status.as_u16()), None),
+            Ok(status) => (
+                Some(status.as_u16()),
+                Some(format!("Endpoint responded with {status}")),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        let delivery = repo
+            .webhook_delivery()
+            .add_attempt(&mut rng, clock, delivery, status_code, error.clone())
+            .await
+            .map_err(JobError::retry)?;
+
+        let Some(error) = error else {
+            repo.webhook_delivery()
+                .mark_as_delivered(clock, delivery)
+                .await
+                .map_err(JobError::retry)?;
+            repo.save().await.map_err(JobError::retry)?;
+            info!("Delivered webhook event");
+            return Ok(());
+        };
+
+        // Client errors won't get better by retrying, except for timeouts and
+        // rate limiting
+        let permanent = matches!(
+            result,
+            Ok(status) if status.is_client_error()
+                && status != StatusCode::REQUEST_TIMEOUT
+                && status != StatusCode::TOO_MANY_REQUESTS
+        );
+
+        if permanent || context.is_last_attempt() {
+            repo.webhook_delivery()
+                .mark_as_failed(clock, delivery)
+                .await
+                .map_err(JobError::retry)?;
+            repo.save().await.map_err(JobError::retry)?;
+            return Err(JobError::fail(anyhow::anyhow!(error)));
+        }
+
+        repo.save().await.map_err(JobError::retry)?;
+        Err(JobError::retry(anyhow::anyhow!(error)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use mas_data_model::{WebhookDelivery, WebhookDeliveryState, WebhookEvent};
+    use reqwest::StatusCode;
+    use serde_json::json;
+    use ulid::Ulid;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{body_json, header, method, path},
+    };
+
+    use super::{send, sign};
+
+    fn delivery(url: &str) -> WebhookDelivery {
+        WebhookDelivery {
+            id: Ulid::nil(),
+            created_at: DateTime::<Utc>::UNIX_EPOCH,
+            state: WebhookDeliveryState::Pending,
+            event: WebhookEvent::UserRegistered,
+            url: url.parse().unwrap(),
+            payload: json!({
+                "type": "user.registered",
+                "data": { "username": "alice" },
+            }),
+            attempts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        // Test vector from RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let server = MockServer::start().await;
+        let delivery = delivery(&format!("{}/hooks", server.uri()));
+        let body = serde_json::to_vec(&delivery.payload).unwrap();
+
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .and(header("content-type", "application/json"))
+            .and(header("x-mas-webhook-signature", sign("s3cr3t", &body)))
+            .and(header("x-mas-webhook-event", "user.registered"))
+            .and(header("x-mas-webhook-delivery", delivery.id.to_string()))
+            .and(body_json(&delivery.payload))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = mas_http::reqwest_client();
+        let status = send(&client, &delivery, "s3cr3t").await.unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // A wrong secret doesn't match the signature the endpoint expects
+        let status = send(&client, &delivery, "wrong").await.unwrap();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
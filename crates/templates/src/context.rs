@@ -21,15 +21,19 @@ use chrono::{DateTime, Duration, Utc};
 use http::{Method, Uri, Version};
 use mas_data_model::{
     AuthorizationGrant, BrowserSession, Client, CompatSsoLogin, CompatSsoLoginState,
-    DeviceCodeGrant, UpstreamOAuthLink, UpstreamOAuthProvider, UpstreamOAuthProviderClaimsImports,
-    UpstreamOAuthProviderDiscoveryMode, UpstreamOAuthProviderOnBackchannelLogout,
-    UpstreamOAuthProviderPkceMode, UpstreamOAuthProviderTokenAuthMethod, User,
-    UserEmailAuthentication, UserEmailAuthenticationCode, UserRecoverySession, UserRegistration,
+    DeviceCodeGrant, SecurityNotification, UpstreamOAuthLink, UpstreamOAuthProvider,
+    UpstreamOAuthProviderClaimsImports, UpstreamOAuthProviderDiscoveryMode,
+    UpstreamOAuthProviderOnBackchannelLogout, UpstreamOAuthProviderPkceMode,
+    UpstreamOAuthProviderTokenAuthMethod, User, UserAgent, UserEmailAuthentication,
+    UserEmailAuthenticationCode, UserRecoverySession, UserRegistration,
 };
 use mas_i18n::DataLocale;
 use mas_iana::jose::JsonWebSignatureAlg;
 use mas_router::{Account, GraphQL, PostAuthAction, UrlBuilder};
-use oauth2_types::scope::{OPENID, Scope};
+use oauth2_types::{
+    oidc::RpInitiatedLogoutRequest,
+    scope::{OPENID, Scope},
+};
 use rand::{
     Rng,
     distributions::{Alphanumeric, DistString},
@@ -41,7 +45,7 @@ use url::Url;
 pub use self::{
     branding::SiteBranding, captcha::WithCaptcha, ext::SiteConfigExt, features::SiteFeatures,
 };
-use crate::{FieldError, FormField, FormState};
+use crate::{FieldError, FormError, FormField, FormState};
 
 /// Helper trait to construct context wrappers
 pub trait TemplateContext: Serialize {
@@ -536,6 +540,30 @@ pub struct LoginContext {
     form: FormState<LoginFormField>,
     next: Option<PostAuthContext>,
     providers: Vec<UpstreamOAuthProvider>,
+    passkey_login: Option<PasskeyLoginContext>,
+}
+
+/// The endpoints used by the login page to authenticate with a passkey
+#[derive(Serialize, Debug, Clone)]
+pub struct PasskeyLoginContext {
+    challenge_url: String,
+    login_url: String,
+}
+
+impl PasskeyLoginContext {
+    /// Constructs a context for passkey logins
+    ///
+    /// # Parameters
+    ///
+    /// * `challenge_url`: The URL where to request a new `WebAuthn` challenge
+    /// * `login_url`: The URL where to submit the `WebAuthn` assertion
+    #[must_use]
+    pub fn new(challenge_url: String, login_url: String) -> Self {
+        Self {
+            challenge_url,
+            login_url,
+        }
+    }
 }
 
 impl TemplateContext for LoginContext {
@@ -553,11 +581,16 @@ impl TemplateContext for LoginContext {
                 form: FormState::default(),
                 next: None,
                 providers: Vec::new(),
+                passkey_login: None,
             },
             LoginContext {
                 form: FormState::default(),
                 next: None,
                 providers: Vec::new(),
+                passkey_login: Some(PasskeyLoginContext::new(
+                    "/login/passkey/challenge".to_owned(),
+                    "/login/passkey".to_owned(),
+                )),
             },
             LoginContext {
                 form: FormState::default()
@@ -571,12 +604,14 @@ impl TemplateContext for LoginContext {
                     ),
                 next: None,
                 providers: Vec::new(),
+                passkey_login: None,
             },
             LoginContext {
                 form: FormState::default()
                     .with_error_on_field(LoginFormField::Username, FieldError::Exists),
                 next: None,
                 providers: Vec::new(),
+                passkey_login: None,
             },
         ])
     }
@@ -600,6 +635,15 @@ impl LoginContext {
         Self { providers, ..self }
     }
 
+    /// Allow the user to authenticate with a passkey
+    #[must_use]
+    pub fn with_passkey_login(self, passkey_login: PasskeyLoginContext) -> Self {
+        Self {
+            passkey_login: Some(passkey_login),
+            ..self
+        }
+    }
+
     /// Add a post authentication action to the context
     #[must_use]
     pub fn with_post_action(self, context: PostAuthContext) -> Self {
@@ -610,6 +654,135 @@ impl LoginContext {
     }
 }
 
+/// Fields of the second factor login form
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginMfaFormField {
+    /// The TOTP or recovery code field
+    Code,
+}
+
+impl FormField for LoginMfaFormField {
+    fn keep(&self) -> bool {
+        match self {
+            Self::Code => false,
+        }
+    }
+}
+
+/// Context used by the `pages/login_mfa.html` template
+#[derive(Serialize, Default)]
+pub struct LoginMfaContext {
+    form: FormState<LoginMfaFormField>,
+}
+
+impl LoginMfaContext {
+    /// Constructs a context for the second factor login page
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the form state
+    #[must_use]
+    pub fn with_form_state(self, form: FormState<LoginMfaFormField>) -> Self {
+        Self { form }
+    }
+}
+
+impl TemplateContext for LoginMfaContext {
+    fn sample(
+        _now: chrono::DateTime<Utc>,
+        _rng: &mut impl Rng,
+        _locales: &[DataLocale],
+    ) -> BTreeMap<SampleIdentifier, Self>
+    where
+        Self: Sized,
+    {
+        sample_list(vec![
+            Self::new(),
+            Self::new().with_form_state(
+                FormState::default().with_error_on_form(FormError::InvalidCredentials),
+            ),
+        ])
+    }
+}
+
+/// Fields of the expired password change form
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginPasswordExpiredFormField {
+    /// The new password field
+    NewPassword,
+
+    /// The new password confirmation field
+    NewPasswordConfirm,
+
+    /// The TOTP or recovery code field, for users who enrolled a second factor
+    Code,
+}
+
+impl FormField for LoginPasswordExpiredFormField {
+    fn keep(&self) -> bool {
+        match self {
+            Self::NewPassword | Self::NewPasswordConfirm | Self::Code => false,
+        }
+    }
+}
+
+/// Context used by the `pages/login_password_expired.html` template
+#[derive(Serialize, Default)]
+pub struct LoginPasswordExpiredContext {
+    form: FormState<LoginPasswordExpiredFormField>,
+    mfa_required: bool,
+}
+
+impl LoginPasswordExpiredContext {
+    /// Constructs a context for the expired password page
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the form state
+    #[must_use]
+    pub fn with_form_state(self, form: FormState<LoginPasswordExpiredFormField>) -> Self {
+        Self { form, ..self }
+    }
+
+    /// Ask for the second factor of the user along with the new password
+    #[must_use]
+    pub fn with_mfa_required(self, mfa_required: bool) -> Self {
+        Self {
+            mfa_required,
+            ..self
+        }
+    }
+}
+
+impl TemplateContext for LoginPasswordExpiredContext {
+    fn sample(
+        _now: chrono::DateTime<Utc>,
+        _rng: &mut impl Rng,
+        _locales: &[DataLocale],
+    ) -> BTreeMap<SampleIdentifier, Self>
+    where
+        Self: Sized,
+    {
+        sample_list(vec![
+            Self::new(),
+            Self::new().with_form_state(FormState::default().with_error_on_field(
+                LoginPasswordExpiredFormField::NewPassword,
+                FieldError::PasswordReused,
+            )),
+            Self::new().with_mfa_required(true),
+            Self::new().with_mfa_required(true).with_form_state(
+                FormState::default().with_error_on_form(FormError::InvalidCredentials),
+            ),
+        ])
+    }
+}
+
 /// Fields of the registration form
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -772,6 +945,47 @@ impl ConsentContext {
     }
 }
 
+/// Context used by the `pages/end_session.html` template
+#[derive(Serialize)]
+pub struct EndSessionContext {
+    client: Option<Client>,
+    request: RpInitiatedLogoutRequest,
+}
+
+impl TemplateContext for EndSessionContext {
+    fn sample(
+        now: chrono::DateTime<Utc>,
+        rng: &mut impl Rng,
+        _locales: &[DataLocale],
+    ) -> BTreeMap<SampleIdentifier, Self>
+    where
+        Self: Sized,
+    {
+        let mut samples: Vec<Self> = Client::samples(now, rng)
+            .into_iter()
+            .map(|client| {
+                let request = RpInitiatedLogoutRequest {
+                    client_id: Some(client.client_id.clone()),
+                    post_logout_redirect_uri: client.post_logout_redirect_uris.first().cloned(),
+                    state: Some("state".to_owned()),
+                    ..RpInitiatedLogoutRequest::default()
+                };
+                Self::new(Some(client), request)
+            })
+            .collect();
+        samples.push(Self::new(None, RpInitiatedLogoutRequest::default()));
+        sample_list(samples)
+    }
+}
+
+impl EndSessionContext {
+    /// Constructs a context for the RP-initiated logout confirmation page
+    #[must_use]
+    pub fn new(client: Option<Client>, request: RpInitiatedLogoutRequest) -> Self {
+        Self { client, request }
+    }
+}
+
 #[derive(Serialize)]
 #[serde(tag = "grant_type")]
 enum PolicyViolationGrant {
@@ -953,6 +1167,70 @@ impl TemplateContext for EmailRecoveryContext {
     }
 }
 
+/// Context used by the `emails/security_notification.{txt,html,subject}`
+/// templates
+#[derive(Serialize)]
+pub struct EmailSecurityNotificationContext {
+    user: User,
+    notification: SecurityNotification,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user_agent: Option<UserAgent>,
+    account_link: Url,
+}
+
+impl EmailSecurityNotificationContext {
+    /// Constructs a context for the security notification email
+    #[must_use]
+    pub fn new(user: User, notification: SecurityNotification, account_link: Url) -> Self {
+        let user_agent = match &notification {
+            SecurityNotification::NewSession {
+                user_agent: Some(user_agent),
+                ..
+            } => Some(UserAgent::parse(user_agent.clone())),
+            _ => None,
+        };
+
+        Self {
+            user,
+            notification,
+            user_agent,
+            account_link,
+        }
+    }
+
+    /// Returns the user being notified
+    #[must_use]
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// Returns the event the user is notified about
+    #[must_use]
+    pub fn notification(&self) -> &SecurityNotification {
+        &self.notification
+    }
+}
+
+impl TemplateContext for EmailSecurityNotificationContext {
+    fn sample(
+        now: chrono::DateTime<Utc>,
+        rng: &mut impl Rng,
+        _locales: &[DataLocale],
+    ) -> BTreeMap<SampleIdentifier, Self>
+    where
+        Self: Sized,
+    {
+        let user = User::samples(now, rng).swap_remove(0);
+        let link: Url = "https://example.com/account/".parse().unwrap();
+        sample_list(
+            SecurityNotification::samples(now, rng)
+                .into_iter()
+                .map(|notification| Self::new(user.clone(), notification, link.clone()))
+                .collect(),
+        )
+    }
+}
+
 /// Context used by the `emails/verification.{txt,html,subject}` templates
 #[derive(Serialize)]
 pub struct EmailVerificationContext {
@@ -1655,6 +1933,7 @@ impl TemplateContext for UpstreamRegister {
             },
             UpstreamOAuthProvider {
                 id: Ulid::nil(),
+                is_static: true,
                 issuer: Some("https://example.com/".to_owned()),
                 human_name: Some("Example Ltd.".to_owned()),
                 brand_name: None,
@@ -1679,6 +1958,7 @@ impl TemplateContext for UpstreamRegister {
                 created_at: now,
                 disabled_at: None,
                 on_backchannel_logout: UpstreamOAuthProviderOnBackchannelLogout::DoNothing,
+                saml: None,
             },
         )])
     }
@@ -29,6 +29,9 @@ pub struct SiteFeatures {
 
     /// Whether users can log in with their email address.
     pub login_with_email_allowed: bool,
+
+    /// Whether users can log in with a passkey.
+    pub passkeys: bool,
 }
 
 impl Object for SiteFeatures {
@@ -41,6 +44,7 @@ impl Object for SiteFeatures {
             "password_login" => Some(Value::from(self.password_login)),
             "account_recovery" => Some(Value::from(self.account_recovery)),
             "login_with_email_allowed" => Some(Value::from(self.login_with_email_allowed)),
+            "passkeys" => Some(Value::from(self.passkeys)),
             _ => None,
         }
     }
@@ -52,6 +56,7 @@ impl Object for SiteFeatures {
             "password_login",
             "account_recovery",
             "login_with_email_allowed",
+            "passkeys",
         ])
     }
 }
@@ -31,6 +31,12 @@ pub enum FieldError {
     /// The password confirmation doesn't match the password
     PasswordMismatch,
 
+    /// The password appears in a corpus of breached passwords
+    PasswordBreached,
+
+    /// The password is one of the last passwords of the user
+    PasswordReused,
+
     /// That value already exists
     Exists,
 
@@ -39,8 +39,10 @@ pub use self::{
     context::{
         AccountInactiveContext, ApiDocContext, AppContext, CompatSsoContext, ConsentContext,
         DeviceConsentContext, DeviceLinkContext, DeviceLinkFormField, DeviceNameContext,
-        EmailRecoveryContext, EmailVerificationContext, EmptyContext, ErrorContext,
-        FormPostContext, IndexContext, LoginContext, LoginFormField, NotFoundContext,
+        EmailRecoveryContext, EmailSecurityNotificationContext, EmailVerificationContext,
+        EmptyContext, EndSessionContext, ErrorContext, FormPostContext, IndexContext, LoginContext,
+        LoginFormField, LoginMfaContext, LoginMfaFormField, LoginPasswordExpiredContext,
+        LoginPasswordExpiredFormField, NotFoundContext, PasskeyLoginContext,
         PasswordRegisterContext, PolicyViolationContext, PostAuthContext, PostAuthContextInner,
         RecoveryExpiredContext, RecoveryFinishContext, RecoveryFinishFormField,
         RecoveryProgressContext, RecoveryStartContext, RecoveryStartFormField, RegisterContext,
@@ -355,6 +357,12 @@ register_templates! {
     /// Render the login page
     pub fn render_login(WithLanguage<WithCsrf<LoginContext>>) { "pages/login.html" }
 
+    /// Render the second factor login page
+    pub fn render_login_mfa(WithLanguage<WithCsrf<LoginMfaContext>>) { "pages/login_mfa.html" }
+
+    /// Render the page asking users to change their expired password
+    pub fn render_login_password_expired(WithLanguage<WithCsrf<LoginPasswordExpiredContext>>) { "pages/login_password_expired.html" }
+
     /// Render the registration page
     pub fn render_register(WithLanguage<WithCsrf<RegisterContext>>) { "pages/register/index.html" }
 
@@ -376,6 +384,9 @@ register_templates! {
     /// Render the client consent page
     pub fn render_consent(WithLanguage<WithCsrf<WithSession<ConsentContext>>>) { "pages/consent.html" }
 
+    /// Render the RP-initiated logout confirmation page
+    pub fn render_end_session(WithLanguage<WithCsrf<WithSession<EndSessionContext>>>) { "pages/end_session.html" }
+
     /// Render the policy violation page
     pub fn render_policy_violation(WithLanguage<WithCsrf<WithSession<PolicyViolationContext>>>) { "pages/policy_violation.html" }
 
@@ -418,6 +429,15 @@ register_templates! {
     /// Render the email recovery subject
     pub fn render_email_recovery_subject(WithLanguage<EmailRecoveryContext>) { "emails/recovery.subject" }
 
+    /// Render the email security notification (plain text variant)
+    pub fn render_email_security_notification_txt(WithLanguage<EmailSecurityNotificationContext>) { "emails/security_notification.txt" }
+
+    /// Render the email security notification (HTML text variant)
+    pub fn render_email_security_notification_html(WithLanguage<EmailSecurityNotificationContext>) { "emails/security_notification.html" }
+
+    /// Render the email security notification subject
+    pub fn render_email_security_notification_subject(WithLanguage<EmailSecurityNotificationContext>) { "emails/security_notification.subject" }
+
     /// Render the email verification email (plain text variant)
     pub fn render_email_verification_txt(WithLanguage<EmailVerificationContext>) { "emails/verification.txt" }
 
@@ -500,6 +520,7 @@ mod tests {
             password_registration_email_required: true,
             account_recovery: true,
             login_with_email_allowed: true,
+            passkeys: true,
         };
         let vite_manifest_path =
             Utf8Path::new(env!("CARGO_MANIFEST_DIR")).join("../../frontend/dist/manifest.json");
@@ -6,7 +6,7 @@
 import type { KnipConfig } from "knip";
 
 export default {
-  entry: ["src/main.tsx", "src/swagger.ts", "src/routes/*"],
+  entry: ["src/main.tsx", "src/swagger.ts", "src/passkey.ts", "src/routes/*"],
   ignore: ["src/gql/*", "src/routeTree.gen.ts", ".storybook/locales.ts"],
   ignoreDependencies: [
     // This is used by the tailwind PostCSS plugin, but not detected by knip
@@ -0,0 +1,312 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+import { useMutation, useQueryClient } from "@tanstack/react-query";
+import IconDelete from "@vector-im/compound-design-tokens/assets/web/icons/delete";
+import IconEdit from "@vector-im/compound-design-tokens/assets/web/icons/edit";
+import IconKey from "@vector-im/compound-design-tokens/assets/web/icons/key";
+import {
+  Button,
+  ErrorMessage,
+  Form,
+  IconButton,
+  Text,
+  Tooltip,
+} from "@vector-im/compound-web";
+import {
+  type ComponentPropsWithoutRef,
+  forwardRef,
+  useCallback,
+  useRef,
+  useState,
+} from "react";
+import { useTranslation } from "react-i18next";
+import { type FragmentType, graphql, useFragment } from "../../gql";
+import { graphqlRequest } from "../../graphql";
+import DateTime from "../DateTime";
+import { Close, Description, Dialog, Title } from "../Dialog";
+import LoadingSpinner from "../LoadingSpinner";
+import PasswordConfirmationModal, {
+  usePasswordConfirmation,
+} from "../PasswordConfirmation";
+import styles from "./UserPasskey.module.css";
+
+// This component shows a single passkey, with controls to rename and remove it
+
+export const FRAGMENT = graphql(/* GraphQL */ `
+  fragment UserPasskey_passkey on UserPasskey {
+    id
+    name
+    createdAt
+    lastUsedAt
+  }
+`);
+
+const RENAME_PASSKEY_MUTATION = graphql(/* GraphQL */ `
+  mutation RenamePasskey($id: ID!, $name: String!) {
+    renamePasskey(input: { id: $id, name: $name }) {
+      status
+
+      passkey {
+        id
+        name
+      }
+    }
+  }
+`);
+
+const REMOVE_PASSKEY_MUTATION = graphql(/* GraphQL */ `
+  mutation RemovePasskey($id: ID!, $password: String) {
+    removePasskey(input: { id: $id, password: $password }) {
+      status
+
+      passkey {
+        id
+      }
+    }
+  }
+`);
+
+// This needs to be its own component because else props and refs aren't passed properly in the trigger
+const ActionButton = forwardRef<
+  HTMLButtonElement,
+  { label: string; destructive?: boolean } & ComponentPropsWithoutRef<"button">
+>(({ label, destructive, ...props }, ref) => (
+  <Tooltip label={label}>
+    <IconButton ref={ref} type="button" size="var(--cpd-space-8x)" {...props}>
+      {destructive ? (
+        <IconDelete className={styles.userPasskeyDeleteIcon} />
+      ) : (
+        <IconEdit />
+      )}
+    </IconButton>
+  </Tooltip>
+));
+
+const RenamePasskeyButton: React.FC<{ id: string; name: string }> = ({
+  id,
+  name,
+}) => {
+  const { t } = useTranslation();
+  const fieldRef = useRef<HTMLInputElement>(null);
+  const [open, setOpen] = useState(false);
+  const queryClient = useQueryClient();
+
+  const renamePasskey = useMutation({
+    mutationFn: (name: string) =>
+      graphqlRequest({
+        query: RENAME_PASSKEY_MUTATION,
+        variables: { id, name },
+      }),
+
+    onSuccess: (data) => {
+      queryClient.invalidateQueries({ queryKey: ["userPasskeys"] });
+
+      if (data.renamePasskey.status === "INVALID_NAME") return;
+      setOpen(false);
+    },
+  });
+
+  const onSubmit = async (
+    event: React.FormEvent<HTMLFormElement>,
+  ): Promise<void> => {
+    event.preventDefault();
+
+    const formData = new FormData(event.currentTarget);
+    renamePasskey.mutate(formData.get("name") as string);
+  };
+
+  const status = renamePasskey.data?.renamePasskey.status ?? null;
+
+  return (
+    <Dialog
+      trigger={<ActionButton label={t("frontend.user_passkey.rename_title")} />}
+      open={open}
+      onOpenChange={(open) => {
+        // Don't change the modal state if the mutation is pending
+        if (renamePasskey.isPending) return;
+        // Reset the form when the dialog is opened or closed
+        fieldRef.current?.form?.reset();
+        renamePasskey.reset();
+        setOpen(open);
+      }}
+    >
+      <Title>{t("frontend.user_passkey.rename_title")}</Title>
+
+      <Form.Root onSubmit={onSubmit}>
+        <Form.Field name="name" serverInvalid={status === "INVALID_NAME"}>
+          <Form.Label>{t("frontend.user_passkey.name_label")}</Form.Label>
+
+          <Form.TextControl
+            type="text"
+            required
+            defaultValue={name}
+            ref={fieldRef}
+          />
+
+          {status === "INVALID_NAME" && (
+            <Form.ErrorMessage>
+              {t("frontend.user_passkey.invalid_name")}
+            </Form.ErrorMessage>
+          )}
+        </Form.Field>
+
+        <Form.Submit disabled={renamePasskey.isPending}>
+          {renamePasskey.isPending && <LoadingSpinner inline />}
+          {t("action.save")}
+        </Form.Submit>
+      </Form.Root>
+
+      <Close asChild>
+        <Button kind="tertiary">{t("action.cancel")}</Button>
+      </Close>
+    </Dialog>
+  );
+};
+
+const UserPasskey: React.FC<{
+  passkey: FragmentType<typeof FRAGMENT>;
+  shouldPromptPassword?: boolean;
+  onRemove?: () => void;
+}> = ({ passkey, shouldPromptPassword, onRemove }) => {
+  const { t } = useTranslation();
+  const [open, setOpen] = useState(false);
+  const data = useFragment(FRAGMENT, passkey);
+  const queryClient = useQueryClient();
+  const [promptPassword, passwordConfirmationRef] = usePasswordConfirmation();
+
+  const removePasskey = useMutation({
+    mutationFn: ({ id, password }: { id: string; password?: string }) =>
+      graphqlRequest({
+        query: REMOVE_PASSKEY_MUTATION,
+        variables: { id, password },
+      }),
+
+    onSuccess: (data) => {
+      queryClient.invalidateQueries({ queryKey: ["userPasskeys"] });
+
+      // Don't close the modal unless the passkey was removed (or not found)
+      if (
+        data.removePasskey.status !== "NOT_FOUND" &&
+        data.removePasskey.status !== "REMOVED"
+      ) {
+        return;
+      }
+
+      onRemove?.();
+      setOpen(false);
+    },
+  });
+
+  const onRemoveClick = useCallback(
+    async (_e: React.MouseEvent<HTMLButtonElement>): Promise<void> => {
+      let password: string | undefined;
+      if (shouldPromptPassword) {
+        password = await promptPassword();
+      }
+      removePasskey.mutate({ id: data.id, password });
+    },
+    [data.id, promptPassword, shouldPromptPassword, removePasskey.mutate],
+  );
+
+  const onOpenChange = useCallback(
+    (open: boolean) => {
+      // Don't change the modal state if the mutation is pending
+      if (removePasskey.isPending) return;
+      removePasskey.reset();
+      setOpen(open);
+    },
+    [removePasskey.isPending, removePasskey.reset],
+  );
+
+  const status = removePasskey.data?.removePasskey.status ?? null;
+
+  return (
+    <>
+      <PasswordConfirmationModal
+        title={t(
+          "frontend.user_passkey.delete_button_confirmation_modal.password_confirmation",
+        )}
+        destructive
+        ref={passwordConfirmationRef}
+      />
+      <div className={styles.userPasskey}>
+        <IconKey className={styles.userPasskeyIcon} />
+
+        <div className={styles.userPasskeyInfo}>
+          <Text size="md" weight="semibold">
+            {data.name}
+          </Text>
+          <Text size="sm" className="text-secondary">
+            {t("frontend.user_passkey.created")}{" "}
+            <DateTime datetime={data.createdAt} />
+          </Text>
+          <Text size="sm" className="text-secondary">
+            {data.lastUsedAt ? (
+              <>
+                {t("frontend.user_passkey.last_used")}{" "}
+                <DateTime datetime={data.lastUsedAt} />
+              </>
+            ) : (
+              t("frontend.user_passkey.never_used")
+            )}
+          </Text>
+        </div>
+
+        <RenamePasskeyButton id={data.id} name={data.name} />
+
+        <Dialog
+          trigger={
+            <ActionButton
+              destructive
+              label={t("frontend.user_passkey.delete_button_title")}
+            />
+          }
+          open={open}
+          onOpenChange={onOpenChange}
+        >
+          <Title>
+            {t("frontend.user_passkey.delete_button_confirmation_modal.body")}
+          </Title>
+          <Description className={styles.passkeyModalBox}>
+            <IconKey />
+            <div>{data.name}</div>
+          </Description>
+
+          {status === "INCORRECT_PASSWORD" && (
+            <ErrorMessage>
+              {t(
+                "frontend.user_passkey.delete_button_confirmation_modal.incorrect_password",
+              )}
+            </ErrorMessage>
+          )}
+
+          <div className="flex flex-col gap-4">
+            <Button
+              kind="primary"
+              type="button"
+              destructive
+              onClick={onRemoveClick}
+              disabled={removePasskey.isPending}
+              Icon={removePasskey.isPending ? undefined : IconDelete}
+            >
+              {!!removePasskey.isPending && <LoadingSpinner inline />}
+              {t(
+                "frontend.user_passkey.delete_button_confirmation_modal.action",
+              )}
+            </Button>
+            <Close asChild>
+              <Button disabled={removePasskey.isPending} kind="tertiary">
+                {t("action.cancel")}
+              </Button>
+            </Close>
+          </div>
+        </Dialog>
+      </div>
+    </>
+  );
+};
+
+export default UserPasskey;
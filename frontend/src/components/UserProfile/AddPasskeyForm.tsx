@@ -0,0 +1,125 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+import { useMutation, useQueryClient } from "@tanstack/react-query";
+import { EditInPlace, ErrorMessage } from "@vector-im/compound-web";
+import { useCallback } from "react";
+import { useTranslation } from "react-i18next";
+import { graphql } from "../../gql";
+import { graphqlRequest } from "../../graphql";
+import { createCredential, isSupported } from "../../utils/webauthn";
+
+const START_REGISTER_PASSKEY_MUTATION = graphql(/* GraphQL */ `
+  mutation StartRegisterPasskey {
+    startRegisterPasskey {
+      id
+      options
+    }
+  }
+`);
+
+const COMPLETE_REGISTER_PASSKEY_MUTATION = graphql(/* GraphQL */ `
+  mutation CompleteRegisterPasskey(
+    $id: ID!
+    $name: String!
+    $response: String!
+  ) {
+    completeRegisterPasskey(
+      input: { id: $id, name: $name, response: $response }
+    ) {
+      status
+
+      passkey {
+        id
+      }
+    }
+  }
+`);
+
+const AddPasskeyForm: React.FC = () => {
+  const { t } = useTranslation();
+  const queryClient = useQueryClient();
+  const addPasskey = useMutation({
+    mutationFn: async (name: string) => {
+      const { startRegisterPasskey } = await graphqlRequest({
+        query: START_REGISTER_PASSKEY_MUTATION,
+      });
+
+      // This prompts the user to create the passkey on their authenticator
+      const response = await createCredential(startRegisterPasskey.options);
+
+      return graphqlRequest({
+        query: COMPLETE_REGISTER_PASSKEY_MUTATION,
+        variables: { id: startRegisterPasskey.id, name, response },
+      });
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["userPasskeys"] });
+    },
+  });
+
+  const handleSubmit = useCallback(
+    async (e: React.FormEvent<HTMLFormElement>): Promise<void> => {
+      e.preventDefault();
+
+      const formData = new FormData(e.currentTarget);
+      const name = formData.get("input") as string;
+
+      const data = await addPasskey.mutateAsync(name);
+
+      if (data.completeRegisterPasskey.status !== "ADDED") {
+        // This is so that the 'Edit in place' component doesn't show a 'Saved' message
+        throw new Error();
+      }
+    },
+    [addPasskey.mutateAsync],
+  );
+
+  // Passkeys can't be registered if the browser doesn't support WebAuthn
+  if (!isSupported()) {
+    return (
+      <ErrorMessage>{t("frontend.add_passkey_form.unsupported")}</ErrorMessage>
+    );
+  }
+
+  const status = addPasskey.data?.completeRegisterPasskey.status ?? null;
+
+  return (
+    <EditInPlace
+      onSave={handleSubmit}
+      required
+      type="text"
+      serverInvalid={addPasskey.isError || (!!status && status !== "ADDED")}
+      label={t("frontend.add_passkey_form.name_field_label")}
+      helpLabel={t("frontend.add_passkey_form.name_field_help")}
+      saveButtonLabel={t("action.save")}
+      savingLabel={t("common.saving")}
+      savedLabel={t("common.saved")}
+      cancelButtonLabel={t("action.cancel")}
+    >
+      {status === "INVALID_NAME" && (
+        <ErrorMessage>
+          {t("frontend.add_passkey_form.invalid_name")}
+        </ErrorMessage>
+      )}
+
+      {status === "EXISTS" && (
+        <ErrorMessage>
+          {t("frontend.add_passkey_form.exists_error")}
+        </ErrorMessage>
+      )}
+
+      {(addPasskey.isError ||
+        status === "INVALID_CHALLENGE" ||
+        status === "INVALID_RESPONSE") && (
+        <ErrorMessage>
+          {t("frontend.add_passkey_form.failed_error")}
+        </ErrorMessage>
+      )}
+    </EditInPlace>
+  );
+};
+
+export default AddPasskeyForm;
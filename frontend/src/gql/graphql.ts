@@ -242,6 +242,8 @@ export type CompatSession = CreationEvent & Node & {
   humanName?: Maybe<Scalars['String']['output']>;
   /** ID of the object. */
   id: Scalars['ID']['output'];
+  /** Set if the session was started by an admin to impersonate the user. */
+  impersonation?: Maybe<Impersonation>;
   /** The last time the session was active. */
   lastActiveAt?: Maybe<Scalars['DateTime']['output']>;
   /** The last IP address used by the session. */
@@ -356,6 +358,68 @@ export type CompleteEmailAuthenticationStatus =
   /** Too many attempts to complete an email authentication */
   | 'RATE_LIMITED';
 
+/** The input for the `completeEnrollTotp` mutation */
+export type CompleteEnrollTotpInput = {
+  /** A code generated by the authenticator app */
+  code: Scalars['String']['input'];
+};
+
+/** The payload of the `completeEnrollTotp` mutation */
+export type CompleteEnrollTotpPayload = {
+  __typename?: 'CompleteEnrollTotpPayload';
+  /**
+   * The recovery codes, which can be used once each in place of a TOTP
+   * code. They are only shown once.
+   */
+  recoveryCodes?: Maybe<Array<Scalars['String']['output']>>;
+  /** Status of the operation */
+  status: CompleteEnrollTotpStatus;
+};
+
+/** The status of the `completeEnrollTotp` mutation */
+export type CompleteEnrollTotpStatus =
+  /** The TOTP authenticator was enrolled */
+  | 'ENROLLED'
+  /** The code is invalid */
+  | 'INVALID_CODE'
+  /** There is no enrolment in progress */
+  | 'NOT_STARTED';
+
+/** The input for the `completeRegisterPasskey` mutation */
+export type CompleteRegisterPasskeyInput = {
+  /** The ID of the registration challenge */
+  id: Scalars['ID']['input'];
+  /** A human-readable name for the passkey */
+  name: Scalars['String']['input'];
+  /**
+   * The response of `navigator.credentials.create()`, as a JSON string with
+   * binary values encoded as base64url
+   */
+  response: Scalars['String']['input'];
+};
+
+/** The payload of the `completeRegisterPasskey` mutation */
+export type CompleteRegisterPasskeyPayload = {
+  __typename?: 'CompleteRegisterPasskeyPayload';
+  /** The passkey that was added */
+  passkey?: Maybe<UserPasskey>;
+  /** Status of the operation */
+  status: CompleteRegisterPasskeyStatus;
+};
+
+/** The status of the `completeRegisterPasskey` mutation */
+export type CompleteRegisterPasskeyStatus =
+  /** The passkey was added */
+  | 'ADDED'
+  /** The passkey is already registered */
+  | 'EXISTS'
+  /** The registration challenge is invalid or has expired */
+  | 'INVALID_CHALLENGE'
+  /** The name is invalid */
+  | 'INVALID_NAME'
+  /** The response from the authenticator is invalid */
+  | 'INVALID_RESPONSE';
+
 /** The input of the `createOauth2Session` mutation. */
 export type CreateOAuth2SessionInput = {
   /** Whether the session should issue a never-expiring access token */
@@ -497,6 +561,20 @@ export type EndOAuth2SessionStatus =
   /** The session was not found. */
   | 'NOT_FOUND';
 
+/**
+ * Details about a session which was started by an admin to impersonate the
+ * user.
+ */
+export type Impersonation = {
+  __typename?: 'Impersonation';
+  /** When the impersonation ends. The session can't be used after this. */
+  expiresAt: Scalars['DateTime']['output'];
+  /** The OAuth 2.0 client used to start the session, if known. */
+  impersonatorClient?: Maybe<Oauth2Client>;
+  /** The username of the admin who started the session, if known. */
+  impersonatorUsername?: Maybe<Scalars['String']['output']>;
+};
+
 /** The input for the `lockUser` mutation. */
 export type LockUserInput = {
   /** Permanently lock the user. */
@@ -547,6 +625,13 @@ export type Mutation = {
   allowUserCrossSigningReset: AllowUserCrossSigningResetPayload;
   /** Complete the email authentication flow */
   completeEmailAuthentication: CompleteEmailAuthenticationPayload;
+  /**
+   * Complete the enrolment of a TOTP authenticator app, by checking a code
+   * it generated
+   */
+  completeEnrollTotp: CompleteEnrollTotpPayload;
+  /** Complete the registration of a passkey */
+  completeRegisterPasskey: CompleteRegisterPasskeyPayload;
   /**
    * Create a new arbitrary OAuth 2.0 Session.
    *
@@ -567,6 +652,12 @@ export type Mutation = {
   lockUser: LockUserPayload;
   /** Remove an email address */
   removeEmail: RemoveEmailPayload;
+  /** Remove a passkey */
+  removePasskey: RemovePasskeyPayload;
+  /** Remove the TOTP authenticator app and the recovery codes of a user */
+  removeTotp: RemoveTotpPayload;
+  /** Rename a passkey */
+  renamePasskey: RenamePasskeyPayload;
   /** Resend the email authentication code */
   resendEmailAuthenticationCode: ResendEmailAuthenticationCodePayload;
   /**
@@ -604,6 +695,10 @@ export type Mutation = {
   setPrimaryEmail: SetPrimaryEmailPayload;
   /** Start a new email authentication flow */
   startEmailAuthentication: StartEmailAuthenticationPayload;
+  /** Start enrolling a TOTP authenticator app for the current user */
+  startEnrollTotp: StartEnrollTotpPayload;
+  /** Start registering a new passkey for the current user */
+  startRegisterPasskey: StartRegisterPasskeyPayload;
   /** Unlock and reactivate a user. This is only available to administrators. */
   unlockUser: UnlockUserPayload;
 };
@@ -633,6 +728,18 @@ export type MutationCompleteEmailAuthenticationArgs = {
 };
 
 
+/** The mutations root of the GraphQL interface. */
+export type MutationCompleteEnrollTotpArgs = {
+  input: CompleteEnrollTotpInput;
+};
+
+
+/** The mutations root of the GraphQL interface. */
+export type MutationCompleteRegisterPasskeyArgs = {
+  input: CompleteRegisterPasskeyInput;
+};
+
+
 /** The mutations root of the GraphQL interface. */
 export type MutationCreateOauth2SessionArgs = {
   input: CreateOAuth2SessionInput;
@@ -675,6 +782,24 @@ export type MutationRemoveEmailArgs = {
 };
 
 
+/** The mutations root of the GraphQL interface. */
+export type MutationRemovePasskeyArgs = {
+  input: RemovePasskeyInput;
+};
+
+
+/** The mutations root of the GraphQL interface. */
+export type MutationRemoveTotpArgs = {
+  input: RemoveTotpInput;
+};
+
+
+/** The mutations root of the GraphQL interface. */
+export type MutationRenamePasskeyArgs = {
+  input: RenamePasskeyInput;
+};
+
+
 /** The mutations root of the GraphQL interface. */
 export type MutationResendEmailAuthenticationCodeArgs = {
   input: ResendEmailAuthenticationCodeInput;
@@ -794,6 +919,8 @@ export type Oauth2Session = CreationEvent & Node & {
   humanName?: Maybe<Scalars['String']['output']>;
   /** ID of the object. */
   id: Scalars['ID']['output'];
+  /** Set if the session was started by an admin to impersonate the user. */
+  impersonation?: Maybe<Impersonation>;
   /** The last time the session was active. */
   lastActiveAt?: Maybe<Scalars['DateTime']['output']>;
   /** The last IP address used by the session. */
@@ -883,6 +1010,8 @@ export type Query = {
   userEmail?: Maybe<UserEmail>;
   /** Fetch a user email authentication session */
   userEmailAuthentication?: Maybe<UserEmailAuthentication>;
+  /** Fetch a user passkey by its ID. */
+  userPasskey?: Maybe<UserPasskey>;
   /** Fetch a user recovery ticket. */
   userRecoveryTicket?: Maybe<UserRecoveryTicket>;
   /**
@@ -980,6 +1109,12 @@ export type QueryUserEmailAuthenticationArgs = {
 };
 
 
+/** The query root of the GraphQL interface. */
+export type QueryUserPasskeyArgs = {
+  id: Scalars['ID']['input'];
+};
+
+
 /** The query root of the GraphQL interface. */
 export type QueryUserRecoveryTicketArgs = {
   ticket: Scalars['String']['input'];
@@ -1027,6 +1162,88 @@ export type RemoveEmailStatus =
   /** The email address was removed */
   | 'REMOVED';
 
+/** The input for the `removePasskey` mutation */
+export type RemovePasskeyInput = {
+  /** The ID of the passkey to remove */
+  id: Scalars['ID']['input'];
+  /**
+   * The user's current password. This is required if the user is not an
+   * admin and it has a password on its account.
+   */
+  password?: InputMaybe<Scalars['String']['input']>;
+};
+
+/** The payload of the `removePasskey` mutation */
+export type RemovePasskeyPayload = {
+  __typename?: 'RemovePasskeyPayload';
+  /** The passkey that was removed */
+  passkey?: Maybe<UserPasskey>;
+  /** Status of the operation */
+  status: RemovePasskeyStatus;
+};
+
+/** The status of the `removePasskey` mutation */
+export type RemovePasskeyStatus =
+  /** The password provided is incorrect */
+  | 'INCORRECT_PASSWORD'
+  /** The passkey was not found */
+  | 'NOT_FOUND'
+  /** The passkey was removed */
+  | 'REMOVED';
+
+/** The input for the `removeTotp` mutation */
+export type RemoveTotpInput = {
+  /**
+   * The user's current password. This is required if the user is not an
+   * admin and it has a password on its account.
+   */
+  password?: InputMaybe<Scalars['String']['input']>;
+  /** The ID of the user to remove the TOTP authenticator from */
+  userId: Scalars['ID']['input'];
+};
+
+/** The payload of the `removeTotp` mutation */
+export type RemoveTotpPayload = {
+  __typename?: 'RemoveTotpPayload';
+  /** Status of the operation */
+  status: RemoveTotpStatus;
+};
+
+/** The status of the `removeTotp` mutation */
+export type RemoveTotpStatus =
+  /** The password provided is incorrect */
+  | 'INCORRECT_PASSWORD'
+  /** The user does not have a TOTP authenticator */
+  | 'NOT_FOUND'
+  /** The TOTP authenticator and the recovery codes were removed */
+  | 'REMOVED';
+
+/** The input for the `renamePasskey` mutation */
+export type RenamePasskeyInput = {
+  /** The ID of the passkey to rename */
+  id: Scalars['ID']['input'];
+  /** The new name of the passkey */
+  name: Scalars['String']['input'];
+};
+
+/** The payload of the `renamePasskey` mutation */
+export type RenamePasskeyPayload = {
+  __typename?: 'RenamePasskeyPayload';
+  /** The passkey that was renamed */
+  passkey?: Maybe<UserPasskey>;
+  /** Status of the operation */
+  status: RenamePasskeyStatus;
+};
+
+/** The status of the `renamePasskey` mutation */
+export type RenamePasskeyStatus =
+  /** The name is invalid */
+  | 'INVALID_NAME'
+  /** The passkey was not found */
+  | 'NOT_FOUND'
+  /** The passkey was renamed */
+  | 'RENAMED';
+
 /** The input for the `resendEmailAuthenticationCode` mutation */
 export type ResendEmailAuthenticationCodeInput = {
   /** The ID of the authentication session to resend the code for */
@@ -1212,6 +1429,8 @@ export type SetPasswordStatus =
   | 'ACCOUNT_LOCKED'
   /** The password was updated. */
   | 'ALLOWED'
+  /** The new password appears in a corpus of breached passwords. */
+  | 'BREACHED_NEW_PASSWORD'
   /** The specified recovery ticket has expired. */
   | 'EXPIRED_RECOVERY_TICKET'
   /**
@@ -1242,6 +1461,8 @@ export type SetPasswordStatus =
    * again.
    */
   | 'RECOVERY_TICKET_ALREADY_USED'
+  /** The new password is one of the last passwords of the user. */
+  | 'REUSED_NEW_PASSWORD'
   /** The supplied current password was wrong. */
   | 'WRONG_PASSWORD';
 
@@ -1290,6 +1511,8 @@ export type SiteConfig = Node & {
    * in use is <https://crates.io/crates/zxcvbn>.
    */
   minimumPasswordComplexity: Scalars['Int']['output'];
+  /** Whether users can register passkeys and use them to log in. */
+  passkeysEnabled: Scalars['Boolean']['output'];
   /** Whether passwords are enabled and users can change their own passwords. */
   passwordChangeAllowed: Scalars['Boolean']['output'];
   /** Whether passwords are enabled for login. */
@@ -1304,6 +1527,8 @@ export type SiteConfig = Node & {
   serverName: Scalars['String']['output'];
   /** The URL to the terms of service. */
   tosUri?: Maybe<Scalars['Url']['output']>;
+  /** Whether users can enrol a TOTP authenticator app as a second factor. */
+  totpEnabled: Scalars['Boolean']['output'];
 };
 
 /** The input for the `startEmailAuthentication` mutation */
@@ -1345,6 +1570,47 @@ export type StartEmailAuthenticationStatus =
   /** The email address was started */
   | 'STARTED';
 
+/** The payload of the `startEnrollTotp` mutation */
+export type StartEnrollTotpPayload = {
+  __typename?: 'StartEnrollTotpPayload';
+  /** The `otpauth://` URI rendered as a QR code, as an SVG `data:` URI */
+  qrCode?: Maybe<Scalars['String']['output']>;
+  /**
+   * The shared secret, encoded in base32, for users who can't scan the QR
+   * code
+   */
+  secret?: Maybe<Scalars['String']['output']>;
+  /** Status of the operation */
+  status: StartEnrollTotpStatus;
+  /** The `otpauth://` URI to give to the authenticator app */
+  uri?: Maybe<Scalars['String']['output']>;
+};
+
+/** The status of the `startEnrollTotp` mutation */
+export type StartEnrollTotpStatus =
+  /** The user already has a TOTP authenticator */
+  | 'ALREADY_ENROLLED'
+  /**
+   * The enrolment was started, and must be completed with
+   * `completeEnrollTotp`
+   */
+  | 'STARTED';
+
+/** The payload of the `startRegisterPasskey` mutation */
+export type StartRegisterPasskeyPayload = {
+  __typename?: 'StartRegisterPasskeyPayload';
+  /**
+   * The ID of the registration challenge, to pass to
+   * `completeRegisterPasskey`
+   */
+  id: Scalars['ID']['output'];
+  /**
+   * The options to pass to `navigator.credentials.create()`, as a JSON
+   * string with binary values encoded as base64url
+   */
+  options: Scalars['String']['output'];
+};
+
 /** The input for the `unlockUser` mutation. */
 export type UnlockUserInput = {
   /** The ID of the user to unlock */
@@ -1469,6 +1735,8 @@ export type User = Node & {
   emails: UserEmailConnection;
   /** Check if the user has a password set. */
   hasPassword: Scalars['Boolean']['output'];
+  /** Check if the user enrolled a TOTP authenticator app as a second factor. */
+  hasTotp: Scalars['Boolean']['output'];
   /** ID of the object. */
   id: Scalars['ID']['output'];
   /** When the user was locked out. */
@@ -1477,6 +1745,10 @@ export type User = Node & {
   matrix: MatrixUser;
   /** Get the list of OAuth 2.0 sessions, chronologically sorted */
   oauth2Sessions: Oauth2SessionConnection;
+  /** Get the list of passkeys, chronologically sorted */
+  passkeys: UserPasskeyConnection;
+  /** Get the number of recovery codes the user has not used yet. */
+  recoveryCodesRemaining: Scalars['Int']['output'];
   /** Get the list of upstream OAuth 2.0 links */
   upstreamOauth2Links: UpstreamOAuth2LinkConnection;
   /** Username chosen by the user. */
@@ -1551,6 +1823,15 @@ export type UserOauth2SessionsArgs = {
 };
 
 
+/** A user is an individual's account. */
+export type UserPasskeysArgs = {
+  after?: InputMaybe<Scalars['String']['input']>;
+  before?: InputMaybe<Scalars['String']['input']>;
+  first?: InputMaybe<Scalars['Int']['input']>;
+  last?: InputMaybe<Scalars['Int']['input']>;
+};
+
+
 /** A user is an individual's account. */
 export type UserUpstreamOauth2LinksArgs = {
   after?: InputMaybe<Scalars['String']['input']>;
@@ -1657,6 +1938,43 @@ export type UserEmailState =
   /** The email address is pending confirmation. */
   | 'PENDING';
 
+/** A passkey registered by a user */
+export type UserPasskey = CreationEvent & Node & {
+  __typename?: 'UserPasskey';
+  /** When the object was created. */
+  createdAt: Scalars['DateTime']['output'];
+  /** ID of the object. */
+  id: Scalars['ID']['output'];
+  /**
+   * When the passkey was last used to authenticate. Is `null` if the passkey
+   * was never used.
+   */
+  lastUsedAt?: Maybe<Scalars['DateTime']['output']>;
+  /** Human-readable name of the passkey */
+  name: Scalars['String']['output'];
+};
+
+export type UserPasskeyConnection = {
+  __typename?: 'UserPasskeyConnection';
+  /** A list of edges. */
+  edges: Array<UserPasskeyEdge>;
+  /** A list of nodes. */
+  nodes: Array<UserPasskey>;
+  /** Information to aid in pagination. */
+  pageInfo: PageInfo;
+  /** Identifies the total count of items in the connection. */
+  totalCount: Scalars['Int']['output'];
+};
+
+/** An edge in a connection. */
+export type UserPasskeyEdge = {
+  __typename?: 'UserPasskeyEdge';
+  /** A cursor for use in pagination */
+  cursor: Scalars['String']['output'];
+  /** The item at the end of the edge */
+  node: UserPasskey;
+};
+
 /** A recovery ticket */
 export type UserRecoveryTicket = CreationEvent & Node & {
   __typename?: 'UserRecoveryTicket';
@@ -1817,6 +2135,24 @@ export type SetDisplayNameMutationVariables = Exact<{
 
 export type SetDisplayNameMutation = { __typename?: 'Mutation', setDisplayName: { __typename?: 'SetDisplayNamePayload', status: SetDisplayNameStatus } };
 
+export type UserPasskey_PasskeyFragment = { __typename?: 'UserPasskey', id: string, name: string, createdAt: string, lastUsedAt?: string | null } & { ' $fragmentName'?: 'UserPasskey_PasskeyFragment' };
+
+export type RenamePasskeyMutationVariables = Exact<{
+  id: Scalars['ID']['input'];
+  name: Scalars['String']['input'];
+}>;
+
+
+export type RenamePasskeyMutation = { __typename?: 'Mutation', renamePasskey: { __typename?: 'RenamePasskeyPayload', status: RenamePasskeyStatus, passkey?: { __typename?: 'UserPasskey', id: string, name: string } | null } };
+
+export type RemovePasskeyMutationVariables = Exact<{
+  id: Scalars['ID']['input'];
+  password?: InputMaybe<Scalars['String']['input']>;
+}>;
+
+
+export type RemovePasskeyMutation = { __typename?: 'Mutation', removePasskey: { __typename?: 'RemovePasskeyPayload', status: RemovePasskeyStatus, passkey?: { __typename?: 'UserPasskey', id: string } | null } };
+
 export type AddEmailForm_UserFragment = { __typename?: 'User', hasPassword: boolean } & { ' $fragmentName'?: 'AddEmailForm_UserFragment' };
 
 export type AddEmailForm_SiteConfigFragment = { __typename?: 'SiteConfig', passwordLoginEnabled: boolean } & { ' $fragmentName'?: 'AddEmailForm_SiteConfigFragment' };
@@ -1830,6 +2166,20 @@ export type AddEmailMutationVariables = Exact<{
 
 export type AddEmailMutation = { __typename?: 'Mutation', startEmailAuthentication: { __typename?: 'StartEmailAuthenticationPayload', status: StartEmailAuthenticationStatus, violations?: Array<string> | null, authentication?: { __typename?: 'UserEmailAuthentication', id: string } | null } };
 
+export type StartRegisterPasskeyMutationVariables = Exact<{ [key: string]: never; }>;
+
+
+export type StartRegisterPasskeyMutation = { __typename?: 'Mutation', startRegisterPasskey: { __typename?: 'StartRegisterPasskeyPayload', id: string, options: string } };
+
+export type CompleteRegisterPasskeyMutationVariables = Exact<{
+  id: Scalars['ID']['input'];
+  name: Scalars['String']['input'];
+  response: Scalars['String']['input'];
+}>;
+
+
+export type CompleteRegisterPasskeyMutation = { __typename?: 'Mutation', completeRegisterPasskey: { __typename?: 'CompleteRegisterPasskeyPayload', status: CompleteRegisterPasskeyStatus, passkey?: { __typename?: 'UserPasskey', id: string } | null } };
+
 export type UserEmailListQueryVariables = Exact<{
   first?: InputMaybe<Scalars['Int']['input']>;
   after?: InputMaybe<Scalars['String']['input']>;
@@ -1847,6 +2197,23 @@ export type UserEmailList_UserFragment = { __typename?: 'User', hasPassword: boo
 
 export type UserEmailList_SiteConfigFragment = { __typename?: 'SiteConfig', emailChangeAllowed: boolean, passwordLoginEnabled: boolean } & { ' $fragmentName'?: 'UserEmailList_SiteConfigFragment' };
 
+export type UserPasskeyListQueryVariables = Exact<{
+  first?: InputMaybe<Scalars['Int']['input']>;
+  after?: InputMaybe<Scalars['String']['input']>;
+  last?: InputMaybe<Scalars['Int']['input']>;
+  before?: InputMaybe<Scalars['String']['input']>;
+}>;
+
+
+export type UserPasskeyListQuery = { __typename?: 'Query', viewer: { __typename: 'Anonymous' } | { __typename: 'User', passkeys: { __typename?: 'UserPasskeyConnection', totalCount: number, edges: Array<{ __typename?: 'UserPasskeyEdge', cursor: string, node: (
+          { __typename?: 'UserPasskey' }
+          & { ' $fragmentRefs'?: { 'UserPasskey_PasskeyFragment': UserPasskey_PasskeyFragment } }
+        ) }>, pageInfo: { __typename?: 'PageInfo', hasNextPage: boolean, hasPreviousPage: boolean, startCursor?: string | null, endCursor?: string | null } } } };
+
+export type UserPasskeyList_UserFragment = { __typename?: 'User', hasPassword: boolean } & { ' $fragmentName'?: 'UserPasskeyList_UserFragment' };
+
+export type UserPasskeyList_SiteConfigFragment = { __typename?: 'SiteConfig', passwordLoginEnabled: boolean } & { ' $fragmentName'?: 'UserPasskeyList_SiteConfigFragment' };
+
 export type BrowserSessionsOverview_UserFragment = { __typename?: 'User', id: string, browserSessions: { __typename?: 'BrowserSessionConnection', totalCount: number } } & { ' $fragmentName'?: 'BrowserSessionsOverview_UserFragment' };
 
 export type UserProfileQueryVariables = Exact<{ [key: string]: never; }>;
@@ -1854,10 +2221,10 @@ export type UserProfileQueryVariables = Exact<{ [key: string]: never; }>;
 
 export type UserProfileQuery = { __typename?: 'Query', viewerSession: { __typename: 'Anonymous' } | { __typename: 'BrowserSession', id: string, user: (
       { __typename?: 'User', hasPassword: boolean, emails: { __typename?: 'UserEmailConnection', totalCount: number } }
-      & { ' $fragmentRefs'?: { 'AddEmailForm_UserFragment': AddEmailForm_UserFragment;'UserEmailList_UserFragment': UserEmailList_UserFragment;'AccountDeleteButton_UserFragment': AccountDeleteButton_UserFragment } }
+      & { ' $fragmentRefs'?: { 'AddEmailForm_UserFragment': AddEmailForm_UserFragment;'UserEmailList_UserFragment': UserEmailList_UserFragment;'UserPasskeyList_UserFragment': UserPasskeyList_UserFragment;'AccountDeleteButton_UserFragment': AccountDeleteButton_UserFragment } }
     ) } | { __typename: 'Oauth2Session' }, siteConfig: (
-    { __typename?: 'SiteConfig', emailChangeAllowed: boolean, passwordLoginEnabled: boolean, accountDeactivationAllowed: boolean }
-    & { ' $fragmentRefs'?: { 'AddEmailForm_SiteConfigFragment': AddEmailForm_SiteConfigFragment;'UserEmailList_SiteConfigFragment': UserEmailList_SiteConfigFragment;'PasswordChange_SiteConfigFragment': PasswordChange_SiteConfigFragment;'AccountDeleteButton_SiteConfigFragment': AccountDeleteButton_SiteConfigFragment } }
+    { __typename?: 'SiteConfig', emailChangeAllowed: boolean, passwordLoginEnabled: boolean, accountDeactivationAllowed: boolean, passkeysEnabled: boolean }
+    & { ' $fragmentRefs'?: { 'AddEmailForm_SiteConfigFragment': AddEmailForm_SiteConfigFragment;'UserEmailList_SiteConfigFragment': UserEmailList_SiteConfigFragment;'UserPasskeyList_SiteConfigFragment': UserPasskeyList_SiteConfigFragment;'PasswordChange_SiteConfigFragment': PasswordChange_SiteConfigFragment;'AccountDeleteButton_SiteConfigFragment': AccountDeleteButton_SiteConfigFragment } }
   ) };
 
 export type PlanManagementTabQueryVariables = Exact<{ [key: string]: never; }>;
@@ -2355,6 +2722,14 @@ export const UserGreeting_SiteConfigFragmentDoc = new TypedDocumentString(`
   displayNameChangeAllowed
 }
     `, {"fragmentName":"UserGreeting_siteConfig"}) as unknown as TypedDocumentString<UserGreeting_SiteConfigFragment, unknown>;
+export const UserPasskey_PasskeyFragmentDoc = new TypedDocumentString(`
+    fragment UserPasskey_passkey on UserPasskey {
+  id
+  name
+  createdAt
+  lastUsedAt
+}
+    `, {"fragmentName":"UserPasskey_passkey"}) as unknown as TypedDocumentString<UserPasskey_PasskeyFragment, unknown>;
 export const AddEmailForm_UserFragmentDoc = new TypedDocumentString(`
     fragment AddEmailForm_user on User {
   hasPassword
@@ -2376,6 +2751,16 @@ export const UserEmailList_SiteConfigFragmentDoc = new TypedDocumentString(`
   passwordLoginEnabled
 }
     `, {"fragmentName":"UserEmailList_siteConfig"}) as unknown as TypedDocumentString<UserEmailList_SiteConfigFragment, unknown>;
+export const UserPasskeyList_UserFragmentDoc = new TypedDocumentString(`
+    fragment UserPasskeyList_user on User {
+  hasPassword
+}
+    `, {"fragmentName":"UserPasskeyList_user"}) as unknown as TypedDocumentString<UserPasskeyList_UserFragment, unknown>;
+export const UserPasskeyList_SiteConfigFragmentDoc = new TypedDocumentString(`
+    fragment UserPasskeyList_siteConfig on SiteConfig {
+  passwordLoginEnabled
+}
+    `, {"fragmentName":"UserPasskeyList_siteConfig"}) as unknown as TypedDocumentString<UserPasskeyList_SiteConfigFragment, unknown>;
 export const BrowserSessionsOverview_UserFragmentDoc = new TypedDocumentString(`
     fragment BrowserSessionsOverview_user on User {
   id
@@ -2489,6 +2874,27 @@ export const SetDisplayNameDocument = new TypedDocumentString(`
   }
 }
     `) as unknown as TypedDocumentString<SetDisplayNameMutation, SetDisplayNameMutationVariables>;
+export const RenamePasskeyDocument = new TypedDocumentString(`
+    mutation RenamePasskey($id: ID!, $name: String!) {
+  renamePasskey(input: {id: $id, name: $name}) {
+    status
+    passkey {
+      id
+      name
+    }
+  }
+}
+    `) as unknown as TypedDocumentString<RenamePasskeyMutation, RenamePasskeyMutationVariables>;
+export const RemovePasskeyDocument = new TypedDocumentString(`
+    mutation RemovePasskey($id: ID!, $password: String) {
+  removePasskey(input: {id: $id, password: $password}) {
+    status
+    passkey {
+      id
+    }
+  }
+}
+    `) as unknown as TypedDocumentString<RemovePasskeyMutation, RemovePasskeyMutationVariables>;
 export const AddEmailDocument = new TypedDocumentString(`
     mutation AddEmail($email: String!, $password: String, $language: String!) {
   startEmailAuthentication(
@@ -2502,6 +2908,24 @@ export const AddEmailDocument = new TypedDocumentString(`
   }
 }
     `) as unknown as TypedDocumentString<AddEmailMutation, AddEmailMutationVariables>;
+export const StartRegisterPasskeyDocument = new TypedDocumentString(`
+    mutation StartRegisterPasskey {
+  startRegisterPasskey {
+    id
+    options
+  }
+}
+    `) as unknown as TypedDocumentString<StartRegisterPasskeyMutation, StartRegisterPasskeyMutationVariables>;
+export const CompleteRegisterPasskeyDocument = new TypedDocumentString(`
+    mutation CompleteRegisterPasskey($id: ID!, $name: String!, $response: String!) {
+  completeRegisterPasskey(input: {id: $id, name: $name, response: $response}) {
+    status
+    passkey {
+      id
+    }
+  }
+}
+    `) as unknown as TypedDocumentString<CompleteRegisterPasskeyMutation, CompleteRegisterPasskeyMutationVariables>;
 export const UserEmailListDocument = new TypedDocumentString(`
     query UserEmailList($first: Int, $after: String, $last: Int, $before: String) {
   viewer {
@@ -2529,6 +2953,35 @@ export const UserEmailListDocument = new TypedDocumentString(`
   id
   email
 }`) as unknown as TypedDocumentString<UserEmailListQuery, UserEmailListQueryVariables>;
+export const UserPasskeyListDocument = new TypedDocumentString(`
+    query UserPasskeyList($first: Int, $after: String, $last: Int, $before: String) {
+  viewer {
+    __typename
+    ... on User {
+      passkeys(first: $first, after: $after, last: $last, before: $before) {
+        edges {
+          cursor
+          node {
+            ...UserPasskey_passkey
+          }
+        }
+        totalCount
+        pageInfo {
+          hasNextPage
+          hasPreviousPage
+          startCursor
+          endCursor
+        }
+      }
+    }
+  }
+}
+    fragment UserPasskey_passkey on UserPasskey {
+  id
+  name
+  createdAt
+  lastUsedAt
+}`) as unknown as TypedDocumentString<UserPasskeyListQuery, UserPasskeyListQueryVariables>;
 export const UserProfileDocument = new TypedDocumentString(`
     query UserProfile {
   viewerSession {
@@ -2538,6 +2991,7 @@ export const UserProfileDocument = new TypedDocumentString(`
       user {
         ...AddEmailForm_user
         ...UserEmailList_user
+        ...UserPasskeyList_user
         ...AccountDeleteButton_user
         hasPassword
         emails(first: 0) {
@@ -2550,8 +3004,10 @@ export const UserProfileDocument = new TypedDocumentString(`
     emailChangeAllowed
     passwordLoginEnabled
     accountDeactivationAllowed
+    passkeysEnabled
     ...AddEmailForm_siteConfig
     ...UserEmailList_siteConfig
+    ...UserPasskeyList_siteConfig
     ...PasswordChange_siteConfig
     ...AccountDeleteButton_siteConfig
   }
@@ -2582,6 +3038,12 @@ fragment UserEmailList_user on User {
 fragment UserEmailList_siteConfig on SiteConfig {
   emailChangeAllowed
   passwordLoginEnabled
+}
+fragment UserPasskeyList_user on User {
+  hasPassword
+}
+fragment UserPasskeyList_siteConfig on SiteConfig {
+  passwordLoginEnabled
 }`) as unknown as TypedDocumentString<UserProfileQuery, UserProfileQueryVariables>;
 export const PlanManagementTabDocument = new TypedDocumentString(`
     query PlanManagementTab {
@@ -3231,6 +3693,50 @@ export const mockSetDisplayNameMutation = (resolver: GraphQLResponseResolver<Set
     options
   )
 
+/**
+ * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
+ * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
+ * @see https://mswjs.io/docs/basics/response-resolver
+ * @example
+ * mockRenamePasskeyMutation(
+ *   ({ query, variables }) => {
+ *     const { id, name } = variables;
+ *     return HttpResponse.json({
+ *       data: { renamePasskey }
+ *     })
+ *   },
+ *   requestOptions
+ * )
+ */
+export const mockRenamePasskeyMutation = (resolver: GraphQLResponseResolver<RenamePasskeyMutation, RenamePasskeyMutationVariables>, options?: RequestHandlerOptions) =>
+  graphql.mutation<RenamePasskeyMutation, RenamePasskeyMutationVariables>(
+    'RenamePasskey',
+    resolver,
+    options
+  )
+
+/**
+ * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
+ * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
+ * @see https://mswjs.io/docs/basics/response-resolver
+ * @example
+ * mockRemovePasskeyMutation(
+ *   ({ query, variables }) => {
+ *     const { id, password } = variables;
+ *     return HttpResponse.json({
+ *       data: { removePasskey }
+ *     })
+ *   },
+ *   requestOptions
+ * )
+ */
+export const mockRemovePasskeyMutation = (resolver: GraphQLResponseResolver<RemovePasskeyMutation, RemovePasskeyMutationVariables>, options?: RequestHandlerOptions) =>
+  graphql.mutation<RemovePasskeyMutation, RemovePasskeyMutationVariables>(
+    'RemovePasskey',
+    resolver,
+    options
+  )
+
 /**
  * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
  * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
@@ -3253,6 +3759,49 @@ export const mockAddEmailMutation = (resolver: GraphQLResponseResolver<AddEmailM
     options
   )
 
+/**
+ * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
+ * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
+ * @see https://mswjs.io/docs/basics/response-resolver
+ * @example
+ * mockStartRegisterPasskeyMutation(
+ *   ({ query, variables }) => {
+ *     return HttpResponse.json({
+ *       data: { startRegisterPasskey }
+ *     })
+ *   },
+ *   requestOptions
+ * )
+ */
+export const mockStartRegisterPasskeyMutation = (resolver: GraphQLResponseResolver<StartRegisterPasskeyMutation, StartRegisterPasskeyMutationVariables>, options?: RequestHandlerOptions) =>
+  graphql.mutation<StartRegisterPasskeyMutation, StartRegisterPasskeyMutationVariables>(
+    'StartRegisterPasskey',
+    resolver,
+    options
+  )
+
+/**
+ * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
+ * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
+ * @see https://mswjs.io/docs/basics/response-resolver
+ * @example
+ * mockCompleteRegisterPasskeyMutation(
+ *   ({ query, variables }) => {
+ *     const { id, name, response } = variables;
+ *     return HttpResponse.json({
+ *       data: { completeRegisterPasskey }
+ *     })
+ *   },
+ *   requestOptions
+ * )
+ */
+export const mockCompleteRegisterPasskeyMutation = (resolver: GraphQLResponseResolver<CompleteRegisterPasskeyMutation, CompleteRegisterPasskeyMutationVariables>, options?: RequestHandlerOptions) =>
+  graphql.mutation<CompleteRegisterPasskeyMutation, CompleteRegisterPasskeyMutationVariables>(
+    'CompleteRegisterPasskey',
+    resolver,
+    options
+  )
+
 /**
  * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
  * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
@@ -3275,6 +3824,28 @@ export const mockUserEmailListQuery = (resolver: GraphQLResponseResolver<UserEma
     options
   )
 
+/**
+ * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
+ * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
+ * @see https://mswjs.io/docs/basics/response-resolver
+ * @example
+ * mockUserPasskeyListQuery(
+ *   ({ query, variables }) => {
+ *     const { first, after, last, before } = variables;
+ *     return HttpResponse.json({
+ *       data: { viewer }
+ *     })
+ *   },
+ *   requestOptions
+ * )
+ */
+export const mockUserPasskeyListQuery = (resolver: GraphQLResponseResolver<UserPasskeyListQuery, UserPasskeyListQueryVariables>, options?: RequestHandlerOptions) =>
+  graphql.query<UserPasskeyListQuery, UserPasskeyListQueryVariables>(
+    'UserPasskeyList',
+    resolver,
+    options
+  )
+
 /**
  * @param resolver A function that accepts [resolver arguments](https://mswjs.io/docs/api/graphql#resolver-argument) and must always return the instruction on what to do with the intercepted request. ([see more](https://mswjs.io/docs/concepts/response-resolver#resolver-instructions))
  * @param options Options object to customize the behavior of the mock. ([see more](https://mswjs.io/docs/api/graphql#handler-options))
@@ -47,6 +47,14 @@ export function translateSetPasswordError(
       return t(
         "frontend.password_change.failure.description.recovery_ticket_already_used",
       );
+    case "BREACHED_NEW_PASSWORD":
+      return t(
+        "frontend.password_change.failure.description.breached_new_password",
+      );
+    case "REUSED_NEW_PASSWORD":
+      return t(
+        "frontend.password_change.failure.description.reused_new_password",
+      );
 
     case "WRONG_PASSWORD":
     case "INVALID_NEW_PASSWORD":
@@ -0,0 +1,58 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+// Script included by the login page to authenticate with a passkey
+
+import { getCredential, isSupported } from "./utils/webauthn";
+
+type ChallengeResponse = {
+  id: string;
+  options: Parameters<typeof getCredential>[0];
+};
+
+const login = async (button: HTMLButtonElement): Promise<void> => {
+  const form = button.form;
+  const { challengeUrl, loginUrl } = button.dataset;
+  if (!form || !challengeUrl || !loginUrl) return;
+
+  const field = (name: string): HTMLInputElement =>
+    form.elements.namedItem(name) as HTMLInputElement;
+
+  // Ask for a fresh challenge only once the user wants to use a passkey
+  const res = await fetch(challengeUrl, {
+    method: "POST",
+    body: new URLSearchParams({ csrf: field("csrf").value }),
+    credentials: "same-origin",
+  });
+  if (!res.ok) {
+    throw new Error(`Failed to get a passkey challenge: ${res.status}`);
+  }
+
+  const challenge = (await res.json()) as ChallengeResponse;
+  const response = await getCredential(challenge.options);
+
+  field("challenge_id").value = challenge.id;
+  field("response").value = response;
+  // submit() skips the validation of the username and password fields
+  form.action = loginUrl;
+  form.submit();
+};
+
+const button = document.querySelector<HTMLButtonElement>(
+  "[data-passkey-login]",
+);
+
+// The button is hidden until we know the browser supports WebAuthn
+if (button && isSupported()) {
+  button.hidden = false;
+  button.addEventListener("click", () => {
+    button.disabled = true;
+    login(button)
+      .catch((error) => console.error("Passkey authentication failed", error))
+      .finally(() => {
+        button.disabled = false;
+      });
+  });
+}
@@ -19,9 +19,13 @@ import LoadingSpinner from "../components/LoadingSpinner";
 import Separator from "../components/Separator";
 import { useEndBrowserSession } from "../components/Session/EndBrowserSessionButton";
 import AddEmailForm from "../components/UserProfile/AddEmailForm";
+import AddPasskeyForm from "../components/UserProfile/AddPasskeyForm";
 import UserEmailList, {
   query as userEmailListQuery,
 } from "../components/UserProfile/UserEmailList";
+import UserPasskeyList, {
+  query as userPasskeyListQuery,
+} from "../components/UserProfile/UserPasskeyList";
 import { graphql } from "../gql";
 import { graphqlRequest } from "../graphql";
 
@@ -34,6 +38,7 @@ const QUERY = graphql(/* GraphQL */ `
         user {
           ...AddEmailForm_user
           ...UserEmailList_user
+          ...UserPasskeyList_user
           ...AccountDeleteButton_user
           hasPassword
           emails(first: 0) {
@@ -47,8 +52,10 @@ const QUERY = graphql(/* GraphQL */ `
       emailChangeAllowed
       passwordLoginEnabled
       accountDeactivationAllowed
+      passkeysEnabled
       ...AddEmailForm_siteConfig
       ...UserEmailList_siteConfig
+      ...UserPasskeyList_siteConfig
       ...PasswordChange_siteConfig
       ...AccountDeleteButton_siteConfig
     }
@@ -136,6 +143,7 @@ export const Route = createFileRoute({
   loader: ({ context }) =>
     Promise.all([
       context.queryClient.ensureQueryData(userEmailListQuery()),
+      context.queryClient.ensureQueryData(userPasskeyListQuery()),
       context.queryClient.ensureQueryData(query),
     ]),
 
@@ -227,6 +235,24 @@ function Index(): React.ReactElement {
         </>
       )}
 
+      {siteConfig.passkeysEnabled && (
+        <>
+          <Collapsible.Section
+            defaultOpen
+            title={t("frontend.account.passkeys")}
+          >
+            <UserPasskeyList
+              user={viewerSession.user}
+              siteConfig={siteConfig}
+            />
+
+            <AddPasskeyForm />
+          </Collapsible.Section>
+
+          <Separator kind="section" />
+        </>
+      )}
+
       <Collapsible.Section title={t("common.e2ee")}>
         <Text className="text-secondary" size="md">
           {t("frontend.reset_cross_signing.description")}
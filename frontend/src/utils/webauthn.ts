@@ -0,0 +1,119 @@
+// Copyright 2025 New Vector Ltd.
+//
+// SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-Element-Commercial
+// Please see LICENSE files in the repository root for full details.
+
+// Helpers to call the WebAuthn browser APIs with the options sent by the
+// server, where binary values are encoded as base64url strings
+
+type CredentialDescriptorJSON = Omit<PublicKeyCredentialDescriptor, "id"> & {
+  id: string;
+};
+
+type CreationOptionsJSON = Omit<
+  PublicKeyCredentialCreationOptions,
+  "challenge" | "user" | "excludeCredentials"
+> & {
+  challenge: string;
+  user: Omit<PublicKeyCredentialUserEntity, "id"> & { id: string };
+  excludeCredentials?: CredentialDescriptorJSON[];
+};
+
+type RequestOptionsJSON = Omit<
+  PublicKeyCredentialRequestOptions,
+  "challenge" | "allowCredentials"
+> & {
+  challenge: string;
+  allowCredentials?: CredentialDescriptorJSON[];
+};
+
+/** Check whether the browser supports passkeys */
+export const isSupported = (): boolean =>
+  typeof window !== "undefined" && !!window.PublicKeyCredential;
+
+/** Decode a base64url string, with or without padding */
+export const decodeBase64Url = (value: string): Uint8Array<ArrayBuffer> => {
+  const binary = atob(value.replace(/-/g, "+").replace(/_/g, "/"));
+  return Uint8Array.from(binary, (c) => c.charCodeAt(0));
+};
+
+/** Encode a buffer as an unpadded base64url string */
+export const encodeBase64Url = (buffer: ArrayBuffer): string => {
+  const binary = String.fromCharCode(...new Uint8Array(buffer));
+  return btoa(binary)
+    .replace(/\+/g, "-")
+    .replace(/\//g, "_")
+    .replace(/=+$/, "");
+};
+
+const decodeDescriptor = (
+  descriptor: CredentialDescriptorJSON,
+): PublicKeyCredentialDescriptor => ({
+  ...descriptor,
+  id: decodeBase64Url(descriptor.id),
+});
+
+/**
+ * Register a new passkey, returning the response of the authenticator as a
+ * JSON string
+ *
+ * @param options The options returned by the `startRegisterPasskey` mutation
+ */
+export const createCredential = async (options: string): Promise<string> => {
+  const json = JSON.parse(options) as CreationOptionsJSON;
+  const credential = (await navigator.credentials.create({
+    publicKey: {
+      ...json,
+      challenge: decodeBase64Url(json.challenge),
+      user: { ...json.user, id: decodeBase64Url(json.user.id) },
+      excludeCredentials: json.excludeCredentials?.map(decodeDescriptor),
+    },
+  })) as PublicKeyCredential | null;
+
+  if (!credential) throw new Error("No credential was created");
+  const response = credential.response as AuthenticatorAttestationResponse;
+
+  return JSON.stringify({
+    id: credential.id,
+    type: credential.type,
+    response: {
+      clientDataJSON: encodeBase64Url(response.clientDataJSON),
+      attestationObject: encodeBase64Url(response.attestationObject),
+      transports: response.getTransports?.() ?? [],
+    },
+  });
+};
+
+/**
+ * Authenticate with a passkey, returning the response of the authenticator as
+ * a JSON string
+ *
+ * @param options The options returned by the passkey challenge endpoint
+ */
+export const getCredential = async (
+  options: RequestOptionsJSON,
+): Promise<string> => {
+  const credential = (await navigator.credentials.get({
+    publicKey: {
+      ...options,
+      challenge: decodeBase64Url(options.challenge),
+      allowCredentials: options.allowCredentials?.map(decodeDescriptor),
+    },
+  })) as PublicKeyCredential | null;
+
+  if (!credential) throw new Error("No credential was returned");
+  const response = credential.response as AuthenticatorAssertionResponse;
+
+  return JSON.stringify({
+    id: credential.id,
+    type: credential.type,
+    response: {
+      clientDataJSON: encodeBase64Url(response.clientDataJSON),
+      authenticatorData: encodeBase64Url(response.authenticatorData),
+      signature: encodeBase64Url(response.signature),
+      userHandle: response.userHandle
+        ? encodeBase64Url(response.userHandle)
+        : null,
+    },
+  });
+};
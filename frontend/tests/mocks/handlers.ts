@@ -23,12 +23,17 @@ import {
   CONFIG_FRAGMENT as USER_EMAIL_LIST_CONFIG_FRAGMENT,
   USER_FRAGMENT as USER_EMAIL_LIST_USER_FRAGMENT,
 } from "../../src/components/UserProfile/UserEmailList";
+import {
+  CONFIG_FRAGMENT as USER_PASSKEY_LIST_CONFIG_FRAGMENT,
+  USER_FRAGMENT as USER_PASSKEY_LIST_USER_FRAGMENT,
+} from "../../src/components/UserProfile/UserPasskeyList";
 import { makeFragmentData } from "../../src/gql";
 import {
   mockCurrentUserGreetingQuery,
   mockCurrentViewerQuery,
   mockFooterQuery,
   mockUserEmailListQuery,
+  mockUserPasskeyListQuery,
   mockUserProfileQuery,
 } from "../../src/gql/graphql";
 
@@ -117,6 +122,12 @@ export const handlers = [
               },
               USER_EMAIL_LIST_USER_FRAGMENT,
             ),
+            makeFragmentData(
+              {
+                hasPassword: true,
+              },
+              USER_PASSKEY_LIST_USER_FRAGMENT,
+            ),
             makeFragmentData(
               {
                 hasPassword: true,
@@ -136,6 +147,7 @@ export const handlers = [
             emailChangeAllowed: true,
             passwordLoginEnabled: true,
             accountDeactivationAllowed: true,
+            passkeysEnabled: false,
           },
           makeFragmentData(
             {
@@ -151,6 +163,12 @@ export const handlers = [
             },
             USER_EMAIL_LIST_CONFIG_FRAGMENT,
           ),
+          makeFragmentData(
+            {
+              passwordLoginEnabled: true,
+            },
+            USER_PASSKEY_LIST_CONFIG_FRAGMENT,
+          ),
           makeFragmentData(
             {
               passwordChangeAllowed: true,
@@ -200,4 +218,24 @@ export const handlers = [
       },
     }),
   ),
+
+  mockUserPasskeyListQuery(() =>
+    HttpResponse.json({
+      data: {
+        viewer: {
+          __typename: "User",
+          passkeys: {
+            edges: [],
+            totalCount: 0,
+            pageInfo: {
+              hasNextPage: false,
+              hasPreviousPage: false,
+              startCursor: null,
+              endCursor: null,
+            },
+          },
+        },
+      },
+    }),
+  ),
 ];
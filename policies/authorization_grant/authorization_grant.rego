@@ -34,6 +34,14 @@ interactive_grant_type("authorization_code") := true
 
 interactive_grant_type("urn:ietf:params:oauth:grant-type:device_code") := true
 
+token_exchange_grant_type := "urn:ietf:params:oauth:grant-type:token-exchange"
+
+# Clients which are allowed to exchange tokens issued to other clients
+can_exchange_other_clients_tokens(client) if {
+	some exchange_client in data.token_exchange_clients
+	client.id == exchange_client
+}
+
 # Special case to make empty scope work
 allowed_scope("") := true
 
@@ -66,6 +74,38 @@ allowed_scope("urn:mas:admin") if {
 	input.client.id == client
 }
 
+# Fine-grained admin scopes, which grant access to part of the admin API
+admin_scope(scope) if startswith(scope, "urn:mas:admin:")
+
+# Admins can request any of the fine-grained admin scopes
+allowed_scope(scope) if {
+	admin_scope(scope)
+	interactive_grant_type(input.grant_type)
+	can_request_admin(input.user)
+}
+
+allowed_scope(scope) if {
+	admin_scope(scope)
+	input.grant_type == "client_credentials"
+	some client in data.admin_clients
+	input.client.id == client
+}
+
+# Delegated admins can request the scopes of the role they were given
+allowed_scope(scope) if {
+	admin_scope(scope)
+	interactive_grant_type(input.grant_type)
+	role := data.admin_roles.users[input.user.username]
+	scope in data.admin_roles.roles[role]
+}
+
+allowed_scope(scope) if {
+	admin_scope(scope)
+	input.grant_type == "client_credentials"
+	role := data.admin_roles.clients[input.client.id]
+	scope in data.admin_roles.roles[role]
+}
+
 allowed_scope(scope) if {
 	# Grant access to the C-S API only if there is a user
 	interactive_grant_type(input.grant_type)
@@ -88,6 +128,25 @@ allowed_scope("urn:matrix:org.matrix.msc2967.client:api:*") if {
 	interactive_grant_type(input.grant_type)
 }
 
+# Scopes which are bound to the session they were granted to, and can't be
+# carried over by a token exchange
+non_exchangeable_scope("urn:mas:admin") := true
+
+non_exchangeable_scope("urn:synapse:admin:*") := true
+
+non_exchangeable_scope(scope) if admin_scope(scope)
+
+non_exchangeable_scope(scope) if startswith(scope, "urn:matrix:client:device:")
+
+non_exchangeable_scope(scope) if startswith(scope, "urn:matrix:org.matrix.msc2967.client:device:")
+
+# A token exchange can carry over the other scopes of the exchanged token
+allowed_scope(scope) if {
+	input.grant_type == token_exchange_grant_type
+	scope in split(input.subject.scope, " ")
+	not non_exchangeable_scope(scope)
+}
+
 uses_unstable_scopes if {
 	scope_list := split(input.scope, " ")
 	count({scope | some scope in scope_list; startswith(scope, "urn:matrix:org.matrix.msc2967.client:")}) > 0
@@ -147,6 +206,26 @@ violation contains {"msg": "request cannot mix unstable and stable scopes"} if {
 	uses_unstable_scopes
 }
 
+violation contains {"msg": "client is not allowed to exchange tokens issued to other clients"} if {
+	input.grant_type == token_exchange_grant_type
+	input.subject.client.id != input.client.id
+	not can_exchange_other_clients_tokens(input.client)
+}
+
+# Devices are tied to a single session, so they can't be carried over
+violation contains {"msg": "device scopes can't be obtained through a token exchange"} if {
+	input.grant_type == token_exchange_grant_type
+	has_device_scope
+}
+
+# Some scopes can only be granted to users who logged in with a second factor
+violation contains {"msg": sprintf("scope '%s' requires multi-factor authentication", [scope])} if {
+	interactive_grant_type(input.grant_type)
+	some scope in split(input.scope, " ")
+	scope in data.mfa_required_scopes
+	not input.mfa_authenticated
+}
+
 violation contains {"msg": sprintf(
 	"Requester [%s] isn't allowed to do this action",
 	[common.format_requester(input.requester)],
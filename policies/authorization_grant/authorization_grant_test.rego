@@ -222,3 +222,160 @@ test_mas_scopes if {
 		with input.grant_type as "authorization_code"
 		with input.scope as "urn:mas:admin"
 }
+
+test_delegated_admin_scopes if {
+	roles := {
+		"roles": {"helpdesk": ["urn:mas:admin:read", "urn:mas:admin:users"]},
+		"users": {"john": "helpdesk"},
+		"clients": {"helpdesk-tool": "helpdesk"},
+	}
+
+	# Admins can request any of the fine-grained scopes
+	authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_users as ["john"]
+		with input.grant_type as "authorization_code"
+		with input.scope as "urn:mas:admin:users urn:mas:admin:policy-data"
+
+	authorization_grant.allow with input.client as {"id": "admin-client"}
+		with data.admin_clients as ["admin-client"]
+		with input.grant_type as "client_credentials"
+		with input.scope as "urn:mas:admin:read"
+
+	# Delegated admins can only request the scopes of their role
+	authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_roles as roles
+		with input.grant_type as "authorization_code"
+		with input.scope as "urn:mas:admin:read urn:mas:admin:users"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_roles as roles
+		with input.grant_type as "authorization_code"
+		with input.scope as "urn:mas:admin:policy-data"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_roles as roles
+		with input.grant_type as "authorization_code"
+		with input.scope as "urn:mas:admin"
+
+	authorization_grant.allow with input.client as {"id": "helpdesk-tool"}
+		with data.admin_roles as roles
+		with input.grant_type as "client_credentials"
+		with input.scope as "urn:mas:admin:users"
+
+	not authorization_grant.allow with input.client as {"id": "other-client"}
+		with data.admin_roles as roles
+		with input.grant_type as "client_credentials"
+		with input.scope as "urn:mas:admin:users"
+}
+
+test_mfa_required_scopes if {
+	authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_users as ["john"]
+		with data.mfa_required_scopes as ["urn:mas:admin"]
+		with input.mfa_authenticated as true
+		with input.grant_type as "authorization_code"
+		with input.scope as "urn:mas:admin"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.admin_users as ["john"]
+		with data.mfa_required_scopes as ["urn:mas:admin"]
+		with input.mfa_authenticated as false
+		with input.grant_type as "authorization_code"
+		with input.scope as "urn:mas:admin"
+
+	# Other scopes are not affected
+	authorization_grant.allow with input.user as user
+		with input.client as client
+		with data.mfa_required_scopes as ["urn:mas:admin"]
+		with input.mfa_authenticated as false
+		with input.grant_type as "authorization_code"
+		with input.scope as "openid"
+
+	# Nor are non-interactive grants, which have no user to authenticate
+	authorization_grant.allow with input.client as {"id": "admin-client"}
+		with data.admin_clients as ["admin-client"]
+		with data.mfa_required_scopes as ["urn:mas:admin"]
+		with input.mfa_authenticated as false
+		with input.grant_type as "client_credentials"
+		with input.scope as "urn:mas:admin"
+}
+
+test_token_exchange if {
+	own_client := {"id": "client"}
+	subject := {"client": own_client, "scope": "openid urn:matrix:client:api:*"}
+
+	# Clients can exchange their own tokens, keeping a subset of the scopes
+	authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as subject
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:matrix:client:api:*"
+
+	# But they can't get scopes the exchanged token didn't have
+	not authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as subject
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:synapse:admin:*"
+
+	# Device scopes can't be carried over to the new session
+	not authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as {"client": own_client, "scope": "urn:matrix:client:api:* urn:matrix:client:device:AAbbCCdd01"}
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:matrix:client:api:* urn:matrix:client:device:AAbbCCdd01"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as {"client": own_client, "scope": "urn:matrix:org.matrix.msc2967.client:api:* urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd01"}
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:matrix:org.matrix.msc2967.client:api:* urn:matrix:org.matrix.msc2967.client:device:AAbbCCdd01"
+}
+
+test_token_exchange_admin_scopes if {
+	own_client := {"id": "client"}
+
+	# Admin scopes can't be carried over to the new session, even if the
+	# exchanged token had them
+	not authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as {"client": own_client, "scope": "urn:mas:admin"}
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:mas:admin"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as {"client": own_client, "scope": "urn:mas:admin:users:read"}
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:mas:admin:users:read"
+
+	not authorization_grant.allow with input.user as user
+		with input.client as own_client
+		with input.subject as {"client": own_client, "scope": "urn:synapse:admin:*"}
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "urn:synapse:admin:*"
+}
+
+test_token_exchange_other_client if {
+	subject := {"client": {"id": "other-client"}, "scope": "openid"}
+	exchange_client := {"id": "exchange-client"}
+
+	not authorization_grant.allow with input.user as user
+		with input.client as exchange_client
+		with input.subject as subject
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "openid"
+
+	authorization_grant.allow with input.user as user
+		with input.client as exchange_client
+		with input.subject as subject
+		with data.token_exchange_clients as ["exchange-client"]
+		with input.grant_type as "urn:ietf:params:oauth:grant-type:token-exchange"
+		with input.scope as "openid"
+}
@@ -147,6 +147,29 @@ valid_redirect_uri(uri) if {
 	host_matches_client_uri(uri)
 }
 
+# IPv4 literals, which the URI regex parses as host names
+is_ip_literal(host) if {
+	regex.match(`^[0-9]+(\.[0-9]+)*$`, host)
+}
+
+# IPv6 literals
+is_ip_literal(host) if {
+	startswith(host, "[")
+}
+
+# Logout tokens are sent by the server itself, so this URI must not point to
+# internal services
+valid_backchannel_logout_uri(_) if {
+	data.client_registration.allow_insecure_uris
+}
+
+valid_backchannel_logout_uri(x) if {
+	secure_url(x)
+	url := parse_uri(x)
+	url.host != ""
+	not is_ip_literal(url.host)
+}
+
 # METADATA
 # entrypoint: true
 violation contains {"msg": "missing client_uri"} if {
@@ -205,3 +228,16 @@ violation contains {"msg": "invalid redirect_uri", "redirect_uri": redirect_uri}
 	some redirect_uri in input.client_metadata.redirect_uris
 	not valid_redirect_uri(redirect_uri)
 }
+
+violation contains {"msg": "invalid post_logout_redirect_uri", "post_logout_redirect_uri": uri} if {
+	some uri in input.client_metadata.post_logout_redirect_uris
+	not valid_redirect_uri(uri)
+}
+
+violation contains {"msg": "invalid backchannel_logout_uri"} if {
+	not valid_backchannel_logout_uri(input.client_metadata.backchannel_logout_uri)
+}
+
+violation contains {"msg": "backchannel_logout_uri not on the same host as the client_uri"} if {
+	not host_matches_client_uri(input.client_metadata.backchannel_logout_uri)
+}
@@ -375,6 +375,69 @@ test_native_redirect_uri_custom_scheme if {
 	}
 }
 
+test_post_logout_redirect_uri if {
+	client_registration.allow with input.client_metadata as {
+		"application_type": "web",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["https://example.com/callback"],
+		"post_logout_redirect_uris": ["https://example.com/logged-out", "https://app.example.com/logged-out"],
+	}
+
+	# Native apps can use the same redirectors as for redirect_uris
+	client_registration.allow with input.client_metadata as {
+		"application_type": "native",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["com.example.app:/callback"],
+		"post_logout_redirect_uris": ["com.example.app:/logged-out", "http://localhost:1234/logged-out"],
+	}
+}
+
+test_post_logout_redirect_uri_insecure if {
+	# Insecure URL
+	not client_registration.allow with input.client_metadata as {
+		"application_type": "web",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["https://example.com/callback"],
+		"post_logout_redirect_uris": ["http://example.com/logged-out"],
+	}
+
+	# Insecure URL, but allowed by the config
+	client_registration.allow with input.client_metadata as {
+		"application_type": "web",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["https://example.com/callback"],
+		"post_logout_redirect_uris": ["http://example.com/logged-out"],
+	}
+		with client_registration.allow_insecure_uris as true
+}
+
+test_post_logout_redirect_uri_host_mismatch if {
+	# Host mismatch
+	not client_registration.allow with input.client_metadata as {
+		"application_type": "web",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["https://example.com/callback"],
+		"post_logout_redirect_uris": ["https://example.org/logged-out"],
+	}
+
+	# Host mismatch, but allowed by the config
+	client_registration.allow with input.client_metadata as {
+		"application_type": "web",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["https://example.com/callback"],
+		"post_logout_redirect_uris": ["https://example.org/logged-out"],
+	}
+		with client_registration.allow_host_mismatch as true
+
+	# Custom schemes are only allowed for native apps
+	not client_registration.allow with input.client_metadata as {
+		"application_type": "web",
+		"client_uri": "https://example.com/",
+		"redirect_uris": ["https://example.com/callback"],
+		"post_logout_redirect_uris": ["com.example.app:/logged-out"],
+	}
+}
+
 test_reverse_dns_match_parse if {
 	client_uri := client_registration.parse_uri("https://element.io/")
 	redirect_uri := client_registration.parse_uri("io.element.app:/callback")
@@ -402,6 +465,100 @@ test_client_credentials_grant if {
 	}
 }
 
+test_backchannel_logout_uri if {
+	client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://example.com/logout",
+	}
+
+	# On a subdomain of the client_uri host
+	client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://app.example.com/logout",
+	}
+}
+
+test_backchannel_logout_uri_insecure if {
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "http://example.com/logout",
+	}
+
+	# Insecure, but allowed by the config
+	client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "http://example.com/logout",
+	}
+		with client_registration.allow_insecure_uris as true
+}
+
+test_backchannel_logout_uri_host_mismatch if {
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://example.org/logout",
+	}
+
+	# Host mismatch, but allowed by the config
+	client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://example.org/logout",
+	}
+		with client_registration.allow_host_mismatch as true
+}
+
+test_backchannel_logout_uri_internal if {
+	# Local or internal addresses can't be reached by the server, even when
+	# host mismatches are allowed
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://localhost/logout",
+	}
+		with client_registration.allow_host_mismatch as true
+
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://127.0.0.1/logout",
+	}
+		with client_registration.allow_host_mismatch as true
+
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://10.0.0.1/logout",
+	}
+		with client_registration.allow_host_mismatch as true
+
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://169.254.169.254/latest/meta-data",
+	}
+		with client_registration.allow_host_mismatch as true
+
+	not client_registration.allow with input.client_metadata as {
+		"grant_types": [],
+		"client_uri": "https://example.com/",
+		"backchannel_logout_uri": "https://[::1]/logout",
+	}
+		with client_registration.allow_host_mismatch as true
+}
+
+test_is_ip_literal if {
+	client_registration.is_ip_literal("127.0.0.1")
+	client_registration.is_ip_literal("10.0.0.1")
+	client_registration.is_ip_literal("[::1]")
+	not client_registration.is_ip_literal("example.com")
+	not client_registration.is_ip_literal("1password.com")
+}
+
 test_is_subdomain if {
 	client_registration.is_subdomain("example.com", "example.com")
 	client_registration.is_subdomain("example.com", "app.example.com")